@@ -0,0 +1,30 @@
+-- Migration: retroactive rescans when the YARA rule pack changes
+
+-- Distinguish publish-time vetting from retroactive rescans
+ALTER TABLE vetting_jobs
+  ADD COLUMN kind TEXT NOT NULL DEFAULT 'publish'
+      CHECK (kind IN ('publish', 'rescan'));
+
+CREATE INDEX vetting_jobs_pending_idx ON vetting_jobs (kind, created_at)
+  WHERE status = 'pending';
+
+-- Rule-pack version (sha256 of the rules tree) each version was last scanned against
+ALTER TABLE versions
+  ADD COLUMN rule_pack_version TEXT,
+  ADD COLUMN last_scanned_at   TIMESTAMPTZ,
+  ADD COLUMN quarantined_at    TIMESTAMPTZ,
+  ADD COLUMN quarantine_reason TEXT;
+
+-- Security advisories raised against published versions
+CREATE TABLE advisories (
+    id                UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    version_id        UUID NOT NULL REFERENCES versions(id),
+    kind              TEXT NOT NULL CHECK (kind IN ('quarantined')),
+    summary           TEXT NOT NULL,
+    rule_pack_version TEXT,
+    findings          JSONB NOT NULL DEFAULT '[]'::jsonb,
+    published_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX advisories_published_idx ON advisories (published_at);
+CREATE INDEX advisories_version_idx   ON advisories (version_id);
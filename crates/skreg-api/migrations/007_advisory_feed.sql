@@ -0,0 +1,94 @@
+-- Migration: public security advisory feed
+
+-- Feed cursor, stable IDs and the fields installers need to act on an advisory
+ALTER TABLE advisories
+  ADD COLUMN seq           BIGSERIAL UNIQUE,
+  ADD COLUMN package_id    UUID REFERENCES packages(id),
+  ADD COLUMN affected      TEXT NOT NULL DEFAULT '*',
+  ADD COLUMN severity      TEXT NOT NULL DEFAULT 'high'
+      CHECK (severity IN ('low', 'medium', 'high', 'critical')),
+  ADD COLUMN description   TEXT NOT NULL DEFAULT '',
+  ADD COLUMN fixed_version TEXT,
+  ALTER COLUMN version_id DROP NOT NULL;
+
+ALTER TABLE advisories
+  ADD COLUMN advisory_id TEXT GENERATED ALWAYS AS ('SKREG-' || lpad(seq::text, 6, '0')) STORED;
+
+UPDATE advisories a
+   SET package_id = v.package_id,
+       affected   = '=' || v.version
+  FROM versions v
+ WHERE v.id = a.version_id;
+
+ALTER TABLE advisories ALTER COLUMN package_id SET NOT NULL;
+
+ALTER TABLE advisories DROP CONSTRAINT IF EXISTS advisories_kind_check;
+ALTER TABLE advisories
+  ADD CONSTRAINT advisories_kind_check
+  CHECK (kind IN ('quarantined', 'yanked', 'revoked', 'manual'));
+
+-- Raise an advisory when a version is yanked.
+CREATE OR REPLACE FUNCTION advisory_on_yank()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO advisories (version_id, package_id, kind, affected, severity, summary, description)
+    VALUES (
+        NEW.id,
+        NEW.package_id,
+        'yanked',
+        '=' || NEW.version,
+        'medium',
+        'Version ' || NEW.version || ' was yanked',
+        COALESCE(NEW.yank_reason, '')
+    );
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER versions_yank_advisory
+AFTER UPDATE OF yanked_at ON versions
+FOR EACH ROW
+WHEN (OLD.yanked_at IS NULL AND NEW.yanked_at IS NOT NULL)
+EXECUTE FUNCTION advisory_on_yank();
+
+-- Raise an advisory for every CA-verified package when a publisher cert is revoked.
+CREATE OR REPLACE FUNCTION advisory_on_cert_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO advisories (package_id, kind, affected, severity, summary)
+    SELECT DISTINCT p.id, 'revoked', '*', 'high',
+           'Publisher certificate ' || NEW.serial || ' was revoked'
+      FROM packages p
+      JOIN versions v ON v.package_id = p.id
+     WHERE p.namespace_id = NEW.namespace_id
+       AND v.signer = 'publisher';
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER publisher_certs_revoke_advisory
+AFTER UPDATE OF revoked_at ON publisher_certs
+FOR EACH ROW
+WHEN (OLD.revoked_at IS NULL AND NEW.revoked_at IS NOT NULL)
+EXECUTE FUNCTION advisory_on_cert_revoke();
+
+-- Raise an advisory for self-signed packages when their pinned key is revoked.
+CREATE OR REPLACE FUNCTION advisory_on_key_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO advisories (package_id, kind, affected, severity, summary, description)
+    SELECT DISTINCT p.id, 'revoked', '*', 'high',
+           'Publisher key ' || left(NEW.fingerprint, 16) || '… was revoked',
+           COALESCE(NEW.reason, '')
+      FROM packages p
+      JOIN namespaces n ON n.id = p.namespace_id
+      JOIN versions v ON v.package_id = p.id
+     WHERE n.pinned_publisher_key = NEW.fingerprint
+       AND v.signer = 'self_signed';
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER revoked_keys_advisory
+AFTER INSERT ON revoked_self_signed_keys
+FOR EACH ROW EXECUTE FUNCTION advisory_on_key_revoke();
@@ -0,0 +1,48 @@
+-- Migration: indexed, namespace-aware typosquatting detection
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Mirrors skreg_core::names::skeleton: lowercase, fold confusable characters,
+-- drop separators, then collapse multi-character lookalikes.
+CREATE OR REPLACE FUNCTION name_skeleton(name TEXT)
+RETURNS TEXT LANGUAGE sql IMMUTABLE PARALLEL SAFE AS $$
+    SELECT replace(replace(replace(
+               translate(lower(name), '0135i-_.', 'olesl'),
+           'rn', 'm'), 'vv', 'w'), 'cl', 'd')
+$$;
+
+-- Mirrors skreg_core::names::segment_key: hyphen-separated segments sorted bytewise.
+CREATE OR REPLACE FUNCTION name_segment_key(name TEXT)
+RETURNS TEXT LANGUAGE sql IMMUTABLE PARALLEL SAFE AS $$
+    SELECT array_to_string(
+               ARRAY(SELECT s FROM unnest(string_to_array(name, '-')) AS s
+                     ORDER BY s COLLATE "C"),
+               '-')
+$$;
+
+CREATE INDEX packages_name_trgm_idx
+    ON packages USING GIN (name gin_trgm_ops);
+CREATE INDEX packages_name_skeleton_idx
+    ON packages (name_skeleton(name));
+CREATE INDEX packages_name_segment_key_idx
+    ON packages (name_segment_key(name));
+
+CREATE INDEX namespaces_slug_trgm_idx
+    ON namespaces USING GIN (slug gin_trgm_ops);
+CREATE INDEX namespaces_slug_skeleton_idx
+    ON namespaces (name_skeleton(slug));
+CREATE INDEX namespaces_slug_segment_key_idx
+    ON namespaces (name_segment_key(slug));
+
+-- Legitimate near-duplicates approved by a registry admin. Pairs are
+-- symmetric; package entries use `namespace/name` refs, namespace entries
+-- use bare slugs.
+CREATE TABLE name_allowlist (
+    id         UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind       TEXT NOT NULL CHECK (kind IN ('package', 'namespace')),
+    name       TEXT NOT NULL,
+    similar_to TEXT NOT NULL,
+    reason     TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (kind, name, similar_to)
+);
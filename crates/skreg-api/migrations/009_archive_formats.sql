@@ -0,0 +1,8 @@
+-- Migration: Zstandard copies of published archives
+--
+-- versions.sha256 / storage_path keep describing the gzip archive, which
+-- every client can read. Versions published before this migration have no
+-- Zstandard copy and are always served as gzip.
+ALTER TABLE versions
+  ADD COLUMN zstd_sha256       TEXT,
+  ADD COLUMN zstd_storage_path TEXT;
@@ -0,0 +1,9 @@
+-- Migration: registry-signed timestamps on publisher signatures
+--
+-- Stage 4 records a SignedTimestamp (see skreg-core::timestamp) proving the
+-- publisher signature existed when the version was vetted, so installers
+-- can check the signing certificate as of that time. Versions vetted before
+-- this migration, or by a worker without REGISTRY_CA_CERT_PEM, have none and
+-- are checked against the current time.
+ALTER TABLE versions
+  ADD COLUMN signed_timestamp JSONB;
@@ -0,0 +1,28 @@
+-- Migration: public history of confirmed key rotations
+--
+-- Clients pin each namespace's publisher key on first install (see
+-- skreg-client::known_publishers) and only accept a different key when the
+-- registry serves a rotation signed by the pinned one. key_rotations keeps
+-- every confirmed rotation with both signatures for
+-- GET /v1/namespaces/:ns/key-history.
+--
+-- pending_rotations gains the columns the rotate handlers read, so the
+-- confirmed record can be copied from it.
+ALTER TABLE pending_rotations
+  ADD COLUMN new_key_fingerprint TEXT,
+  ADD COLUMN new_cert_pem        TEXT,
+  ADD COLUMN old_cert_pem        TEXT;
+
+CREATE TABLE key_rotations (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    namespace_id        UUID NOT NULL REFERENCES namespaces(id),
+    old_key_fingerprint TEXT NOT NULL,
+    new_key_fingerprint TEXT NOT NULL,
+    rotation_token      JSONB NOT NULL,
+    old_sig             TEXT NOT NULL,
+    new_sig             TEXT NOT NULL,
+    old_cert_pem        TEXT NOT NULL,
+    confirmed_at        TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX key_rotations_namespace_idx ON key_rotations (namespace_id, confirmed_at);
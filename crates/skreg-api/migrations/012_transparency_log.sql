@@ -0,0 +1,99 @@
+-- Migration: append-only transparency log
+--
+-- Every published version and every key pin, rotation and revocation is
+-- appended to transparency_log. The API hashes the entries into an RFC 6962
+-- Merkle tree (see skreg-core::transparency) and serves signed tree heads
+-- and proofs under /v1/log/*.
+--
+-- Leaf indices must be dense and never reordered, so entries are only added
+-- through append_log_entry(), which serializes appenders with a
+-- transaction-scoped advisory lock.
+CREATE TABLE transparency_log (
+    idx       BIGINT PRIMARY KEY,
+    entry     JSONB NOT NULL,
+    logged_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE OR REPLACE FUNCTION append_log_entry(new_entry JSONB)
+RETURNS BIGINT LANGUAGE plpgsql AS $$
+DECLARE
+    next_idx BIGINT;
+BEGIN
+    PERFORM pg_advisory_xact_lock(hashtext('transparency_log'));
+    SELECT COALESCE(MAX(idx) + 1, 0) INTO next_idx FROM transparency_log;
+    INSERT INTO transparency_log (idx, entry) VALUES (next_idx, new_entry);
+    RETURN next_idx;
+END;
+$$;
+
+CREATE RULE transparency_log_no_update AS ON UPDATE TO transparency_log DO INSTEAD NOTHING;
+CREATE RULE transparency_log_no_delete AS ON DELETE TO transparency_log DO INSTEAD NOTHING;
+
+-- Index of each version's publish entry, set by the worker when it passes
+-- vetting. Versions published before this migration have none.
+ALTER TABLE versions
+  ADD COLUMN log_index BIGINT;
+
+-- Log key pins and rotations whenever a namespace's pinned key changes.
+CREATE OR REPLACE FUNCTION log_key_pin()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    IF OLD.pinned_publisher_key IS NULL THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_pin',
+            'namespace', NEW.slug,
+            'fingerprint', NEW.pinned_publisher_key));
+    ELSE
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_rotation',
+            'namespace', NEW.slug,
+            'old_fingerprint', OLD.pinned_publisher_key,
+            'new_fingerprint', NEW.pinned_publisher_key));
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER namespaces_log_key_pin
+AFTER UPDATE OF pinned_publisher_key ON namespaces
+FOR EACH ROW
+WHEN (NEW.pinned_publisher_key IS NOT NULL
+      AND NEW.pinned_publisher_key IS DISTINCT FROM OLD.pinned_publisher_key)
+EXECUTE FUNCTION log_key_pin();
+
+-- Log revoked self-signed keys.
+CREATE OR REPLACE FUNCTION log_key_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    PERFORM append_log_entry(jsonb_build_object(
+        'kind', 'key_revocation',
+        'namespace', (SELECT slug FROM namespaces
+                       WHERE pinned_publisher_key = NEW.fingerprint LIMIT 1),
+        'fingerprint', NEW.fingerprint,
+        'serial', NULL));
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER revoked_keys_log
+AFTER INSERT ON revoked_self_signed_keys
+FOR EACH ROW EXECUTE FUNCTION log_key_revoke();
+
+-- Log revoked CA-issued publisher certificates.
+CREATE OR REPLACE FUNCTION log_cert_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    PERFORM append_log_entry(jsonb_build_object(
+        'kind', 'key_revocation',
+        'namespace', (SELECT slug FROM namespaces WHERE id = NEW.namespace_id),
+        'fingerprint', NULL,
+        'serial', NEW.serial));
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER publisher_certs_revoke_log
+AFTER UPDATE OF revoked_at ON publisher_certs
+FOR EACH ROW
+WHEN (OLD.revoked_at IS NULL AND NEW.revoked_at IS NOT NULL)
+EXECUTE FUNCTION log_cert_revoke();
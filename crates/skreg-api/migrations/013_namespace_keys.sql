@@ -0,0 +1,137 @@
+-- Migration: several signing keys per namespace
+--
+-- namespaces.pinned_publisher_key held a single fingerprint, so a team had
+-- to share one private key. namespace_keys holds every key allowed to sign
+-- for a namespace, with a label, the key that added it and an optional
+-- expiry. The first publish adds its key; further keys are added and removed
+-- by changes signed by a key in the set and confirmed by email.
+CREATE TABLE namespace_keys (
+    id           UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    namespace_id UUID NOT NULL REFERENCES namespaces(id),
+    fingerprint  TEXT NOT NULL,
+    label        TEXT NOT NULL,
+    cert_pem     TEXT,
+    -- Fingerprint of the key that added this one; NULL for the first key.
+    added_by     TEXT,
+    added_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at   TIMESTAMPTZ,
+    removed_at   TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX namespace_keys_active_idx
+    ON namespace_keys (namespace_id, fingerprint)
+    WHERE removed_at IS NULL;
+
+INSERT INTO namespace_keys (namespace_id, fingerprint, label)
+SELECT id, pinned_publisher_key, 'initial'
+  FROM namespaces
+ WHERE pinned_publisher_key IS NOT NULL;
+
+-- Key-set changes awaiting email confirmation.
+CREATE TABLE pending_key_changes (
+    id              UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    namespace_id    UUID NOT NULL REFERENCES namespaces(id),
+    change_token    JSONB NOT NULL,
+    signature       TEXT NOT NULL,
+    signer_cert_pem TEXT NOT NULL,
+    confirm_token   TEXT NOT NULL UNIQUE,
+    expires_at      TIMESTAMPTZ NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Confirmed key-set changes, served by GET /v1/namespaces/:ns/key-changes.
+CREATE TABLE key_changes (
+    id              BIGSERIAL PRIMARY KEY,
+    namespace_id    UUID NOT NULL REFERENCES namespaces(id),
+    change_token    JSONB NOT NULL,
+    signature       TEXT NOT NULL,
+    signer_cert_pem TEXT NOT NULL,
+    confirmed_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX key_changes_namespace_idx ON key_changes (namespace_id, confirmed_at);
+
+-- The key that signed each version. Versions published before this
+-- migration have none.
+ALTER TABLE versions
+  ADD COLUMN signer_fingerprint TEXT;
+
+-- Transparency log: the first key is a pin, later keys are additions, and a
+-- confirmed rotation replaces a key's fingerprint in place.
+DROP TRIGGER namespaces_log_key_pin ON namespaces;
+DROP FUNCTION log_key_pin();
+
+CREATE OR REPLACE FUNCTION log_namespace_key_change()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+DECLARE
+    ns_slug TEXT := (SELECT slug FROM namespaces WHERE id = NEW.namespace_id);
+BEGIN
+    IF TG_OP = 'INSERT' AND NEW.added_by IS NULL THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_pin',
+            'namespace', ns_slug,
+            'fingerprint', NEW.fingerprint));
+    ELSIF TG_OP = 'INSERT' THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_added',
+            'namespace', ns_slug,
+            'fingerprint', NEW.fingerprint,
+            'added_by', NEW.added_by));
+    ELSIF NEW.fingerprint IS DISTINCT FROM OLD.fingerprint THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_rotation',
+            'namespace', ns_slug,
+            'old_fingerprint', OLD.fingerprint,
+            'new_fingerprint', NEW.fingerprint));
+    ELSIF OLD.removed_at IS NULL AND NEW.removed_at IS NOT NULL THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_removed',
+            'namespace', ns_slug,
+            'fingerprint', NEW.fingerprint));
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER namespace_keys_log
+AFTER INSERT OR UPDATE OF fingerprint, removed_at ON namespace_keys
+FOR EACH ROW EXECUTE FUNCTION log_namespace_key_change();
+
+-- Revoked self-signed keys are found through the key sets that held them.
+CREATE OR REPLACE FUNCTION log_key_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    PERFORM append_log_entry(jsonb_build_object(
+        'kind', 'key_revocation',
+        'namespace', (SELECT n.slug FROM namespace_keys k
+                        JOIN namespaces n ON n.id = k.namespace_id
+                       WHERE k.fingerprint = NEW.fingerprint LIMIT 1),
+        'fingerprint', NEW.fingerprint,
+        'serial', NULL));
+    RETURN NEW;
+END;
+$$;
+
+-- Advise on the versions the revoked key signed, or, for versions that do
+-- not record their signer, on self-signed versions of namespaces that held
+-- the key.
+CREATE OR REPLACE FUNCTION advisory_on_key_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO advisories (package_id, kind, affected, severity, summary, description)
+    SELECT DISTINCT p.id, 'revoked', '*', 'high',
+           'Publisher key ' || left(NEW.fingerprint, 16) || '… was revoked',
+           COALESCE(NEW.reason, '')
+      FROM packages p
+      JOIN versions v ON v.package_id = p.id
+     WHERE v.signer = 'self_signed'
+       AND (v.signer_fingerprint = NEW.fingerprint
+            OR (v.signer_fingerprint IS NULL
+                AND p.namespace_id IN (SELECT namespace_id FROM namespace_keys
+                                        WHERE fingerprint = NEW.fingerprint)));
+    RETURN NEW;
+END;
+$$;
+
+ALTER TABLE namespaces
+  DROP COLUMN pinned_publisher_key;
@@ -0,0 +1,35 @@
+-- Migration: self-service publisher certificate lifecycle
+--
+-- Publishers list their certificates, renew them with the same key, and
+-- revoke them, either signed by the certificate's key or confirmed by email.
+-- The worker reminds publishers of certificates about to expire.
+ALTER TABLE publisher_certs
+  ADD COLUMN revocation_reason TEXT,
+  ADD COLUMN expiry_reminded_at TIMESTAMPTZ;
+
+-- Revocations awaiting email confirmation.
+CREATE TABLE pending_cert_revocations (
+    id            UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    namespace_id  UUID NOT NULL REFERENCES namespaces(id),
+    serial        BIGINT NOT NULL REFERENCES publisher_certs(serial),
+    reason        TEXT,
+    confirm_token TEXT NOT NULL UNIQUE,
+    expires_at    TIMESTAMPTZ NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Advisories for a revoked certificate carry the publisher's reason.
+CREATE OR REPLACE FUNCTION advisory_on_cert_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO advisories (package_id, kind, affected, severity, summary, description)
+    SELECT DISTINCT p.id, 'revoked', '*', 'high',
+           'Publisher certificate ' || NEW.serial || ' was revoked',
+           COALESCE(NEW.revocation_reason, '')
+      FROM packages p
+      JOIN versions v ON v.package_id = p.id
+     WHERE p.namespace_id = NEW.namespace_id
+       AND v.signer = 'publisher';
+    RETURN NEW;
+END;
+$$;
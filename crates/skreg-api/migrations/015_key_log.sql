@@ -0,0 +1,144 @@
+-- Migration: rotation cancellation and per-namespace key logs
+--
+-- A namespace owner can list and cancel pending rotations. Cancelled rows
+-- are kept, so they still count towards the daily submission limit.
+ALTER TABLE pending_rotations
+  ADD COLUMN cancelled_at TIMESTAMPTZ;
+
+-- Every change to a namespace's key set, numbered per namespace. The API
+-- serves it hash-linked and signed by the Publisher CA (see
+-- skreg-core::key_log), so clients can check that a version was signed by a
+-- key that was valid at the time, after the key was rotated out or removed.
+CREATE TABLE key_log (
+    namespace_id UUID NOT NULL REFERENCES namespaces(id),
+    seq          BIGINT NOT NULL,
+    event        JSONB NOT NULL,
+    at           TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (namespace_id, seq)
+);
+
+CREATE RULE key_log_no_update AS ON UPDATE TO key_log DO INSTEAD NOTHING;
+CREATE RULE key_log_no_delete AS ON DELETE TO key_log DO INSTEAD NOTHING;
+
+-- Backfill. Since migration 012 every key event is in the transparency log;
+-- namespaces pinned before it get a pin dated at their creation, with the
+-- key their first rotation replaced, followed by their rotations from
+-- before the transparency log.
+WITH log_start AS (
+    SELECT COALESCE(MIN(logged_at), 'infinity'::timestamptz) AS at FROM transparency_log
+),
+logged AS (
+    SELECT n.id AS namespace_id, t.logged_at AS at, t.idx AS ord,
+           CASE t.entry->>'kind'
+               WHEN 'key_pin' THEN jsonb_build_object(
+                   'kind', 'pinned', 'fingerprint', t.entry->>'fingerprint')
+               WHEN 'key_added' THEN jsonb_build_object(
+                   'kind', 'added', 'fingerprint', t.entry->>'fingerprint',
+                   'added_by', t.entry->>'added_by')
+               WHEN 'key_removed' THEN jsonb_build_object(
+                   'kind', 'removed', 'fingerprint', t.entry->>'fingerprint')
+               ELSE jsonb_build_object(
+                   'kind', 'rotated', 'old_fingerprint', t.entry->>'old_fingerprint',
+                   'new_fingerprint', t.entry->>'new_fingerprint')
+           END AS event
+      FROM transparency_log t
+      JOIN namespaces n ON n.slug = t.entry->>'namespace'
+     WHERE t.entry->>'kind' IN ('key_pin', 'key_added', 'key_removed', 'key_rotation')
+),
+early_rotations AS (
+    SELECT kr.namespace_id, kr.confirmed_at AS at, 0::bigint AS ord,
+           jsonb_build_object(
+               'kind', 'rotated', 'old_fingerprint', kr.old_key_fingerprint,
+               'new_fingerprint', kr.new_key_fingerprint) AS event
+      FROM key_rotations kr, log_start
+     WHERE kr.confirmed_at < log_start.at
+),
+early_pins AS (
+    SELECT k.namespace_id, n.created_at AS at, 0::bigint AS ord,
+           jsonb_build_object(
+               'kind', 'pinned',
+               'fingerprint', COALESCE(
+                   (SELECT kr.old_key_fingerprint FROM key_rotations kr
+                     WHERE kr.namespace_id = k.namespace_id
+                     ORDER BY kr.confirmed_at, kr.id LIMIT 1),
+                   k.fingerprint)) AS event
+      FROM namespace_keys k
+      JOIN namespaces n ON n.id = k.namespace_id
+     WHERE k.added_by IS NULL
+       AND NOT EXISTS (SELECT 1 FROM logged l
+                        WHERE l.namespace_id = k.namespace_id
+                          AND l.event->>'kind' = 'pinned')
+),
+events AS (
+    SELECT namespace_id, at, 0 AS source, ord, event FROM early_pins
+    UNION ALL
+    SELECT namespace_id, at, 1, ord, event FROM early_rotations
+    UNION ALL
+    SELECT namespace_id, at, 2, ord, event FROM logged
+)
+INSERT INTO key_log (namespace_id, seq, event, at)
+SELECT namespace_id,
+       ROW_NUMBER() OVER (PARTITION BY namespace_id ORDER BY at, source, ord) - 1,
+       event, at
+  FROM events;
+
+-- Append a key event to a namespace's log. Appenders are serialized per
+-- namespace with a transaction-scoped advisory lock, and date entries by the
+-- clock once they hold it, so entries are dated in sequence order.
+CREATE OR REPLACE FUNCTION append_key_log(ns UUID, new_event JSONB)
+RETURNS VOID LANGUAGE plpgsql AS $$
+BEGIN
+    PERFORM pg_advisory_xact_lock(hashtext('key_log:' || ns::text));
+    INSERT INTO key_log (namespace_id, seq, event, at)
+    SELECT ns, COALESCE(MAX(seq) + 1, 0), new_event, clock_timestamp()
+      FROM key_log WHERE namespace_id = ns;
+END;
+$$;
+
+-- Record key-set changes in the namespace's key log as well as the
+-- transparency log.
+CREATE OR REPLACE FUNCTION log_namespace_key_change()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+DECLARE
+    ns_slug TEXT := (SELECT slug FROM namespaces WHERE id = NEW.namespace_id);
+BEGIN
+    IF TG_OP = 'INSERT' AND NEW.added_by IS NULL THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_pin',
+            'namespace', ns_slug,
+            'fingerprint', NEW.fingerprint));
+        PERFORM append_key_log(NEW.namespace_id, jsonb_build_object(
+            'kind', 'pinned',
+            'fingerprint', NEW.fingerprint));
+    ELSIF TG_OP = 'INSERT' THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_added',
+            'namespace', ns_slug,
+            'fingerprint', NEW.fingerprint,
+            'added_by', NEW.added_by));
+        PERFORM append_key_log(NEW.namespace_id, jsonb_build_object(
+            'kind', 'added',
+            'fingerprint', NEW.fingerprint,
+            'added_by', NEW.added_by));
+    ELSIF NEW.fingerprint IS DISTINCT FROM OLD.fingerprint THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_rotation',
+            'namespace', ns_slug,
+            'old_fingerprint', OLD.fingerprint,
+            'new_fingerprint', NEW.fingerprint));
+        PERFORM append_key_log(NEW.namespace_id, jsonb_build_object(
+            'kind', 'rotated',
+            'old_fingerprint', OLD.fingerprint,
+            'new_fingerprint', NEW.fingerprint));
+    ELSIF OLD.removed_at IS NULL AND NEW.removed_at IS NOT NULL THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_removed',
+            'namespace', ns_slug,
+            'fingerprint', NEW.fingerprint));
+        PERFORM append_key_log(NEW.namespace_id, jsonb_build_object(
+            'kind', 'removed',
+            'fingerprint', NEW.fingerprint));
+    END IF;
+    RETURN NEW;
+END;
+$$;
@@ -0,0 +1,11 @@
+-- Migration: mark versions signed under the legacy scheme
+--
+-- Publishers now sign a domain-separated digest of the manifest's file list,
+-- and Stage 4 rejects manifests without one. Versions vetted before this
+-- migration were signed over `manifest.sha256` itself, with or without a
+-- file list. Clients verify a version under the legacy scheme only when the
+-- registry marks it here, so a stripped file list cannot downgrade the check.
+ALTER TABLE versions
+  ADD COLUMN legacy_signature BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE versions SET legacy_signature = TRUE;
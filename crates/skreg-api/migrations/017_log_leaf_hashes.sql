@@ -0,0 +1,62 @@
+-- Migration: store each transparency log entry's leaf hash
+--
+-- The API keeps the log's Merkle tree in memory and only needs the hashes
+-- of entries appended since it last looked, instead of rehashing the whole
+-- table on every request.
+--
+-- A leaf hashes the entry's serde_json serialization (see
+-- skreg-core::transparency::LogEntry): compact JSON with the "kind" tag
+-- first and the remaining fields in declaration order. JSONB does not keep
+-- key order, so log_leaf_data() rebuilds that text from the known field
+-- order of each kind. An entry of an unknown kind has no leaf data, and
+-- appending it fails on the NOT NULL constraint below.
+CREATE OR REPLACE FUNCTION log_leaf_data(entry JSONB)
+RETURNS TEXT LANGUAGE sql IMMUTABLE AS $$
+    SELECT '{' || string_agg(
+                      to_json(field)::text || ':' || COALESCE(entry -> field, 'null'::jsonb)::text,
+                      ',' ORDER BY ord) || '}'
+      FROM unnest(CASE entry ->> 'kind'
+               WHEN 'publish' THEN
+                   ARRAY['kind', 'namespace', 'name', 'version', 'sha256', 'signer_fingerprint']
+               WHEN 'key_pin' THEN
+                   ARRAY['kind', 'namespace', 'fingerprint']
+               WHEN 'key_added' THEN
+                   ARRAY['kind', 'namespace', 'fingerprint', 'added_by']
+               WHEN 'key_removed' THEN
+                   ARRAY['kind', 'namespace', 'fingerprint']
+               WHEN 'key_rotation' THEN
+                   ARRAY['kind', 'namespace', 'old_fingerprint', 'new_fingerprint']
+               WHEN 'key_revocation' THEN
+                   ARRAY['kind', 'namespace', 'fingerprint', 'serial']
+           END) WITH ORDINALITY AS fields(field, ord)
+$$;
+
+-- RFC 6962 leaf hash, SHA-256(0x00 || data), as lowercase hex.
+CREATE OR REPLACE FUNCTION log_leaf_hash(entry JSONB)
+RETURNS TEXT LANGUAGE sql IMMUTABLE AS $$
+    SELECT encode(sha256('\x00'::bytea || convert_to(log_leaf_data(entry), 'UTF8')), 'hex')
+$$;
+
+ALTER TABLE transparency_log
+  ADD COLUMN leaf_hash TEXT;
+
+-- Backfill past entries; updates are otherwise rewritten to nothing.
+DROP RULE transparency_log_no_update ON transparency_log;
+UPDATE transparency_log SET leaf_hash = log_leaf_hash(entry);
+CREATE RULE transparency_log_no_update AS ON UPDATE TO transparency_log DO INSTEAD NOTHING;
+
+ALTER TABLE transparency_log
+  ALTER COLUMN leaf_hash SET NOT NULL;
+
+CREATE OR REPLACE FUNCTION append_log_entry(new_entry JSONB)
+RETURNS BIGINT LANGUAGE plpgsql AS $$
+DECLARE
+    next_idx BIGINT;
+BEGIN
+    PERFORM pg_advisory_xact_lock(hashtext('transparency_log'));
+    SELECT COALESCE(MAX(idx) + 1, 0) INTO next_idx FROM transparency_log;
+    INSERT INTO transparency_log (idx, entry, leaf_hash)
+    VALUES (next_idx, new_entry, log_leaf_hash(new_entry));
+    RETURN next_idx;
+END;
+$$;
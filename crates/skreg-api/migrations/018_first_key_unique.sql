@@ -0,0 +1,10 @@
+-- Migration: at most one first key per namespace
+--
+-- A namespace's first publish adds its signing key when the key set is
+-- empty. Two concurrent first publishes could both see it empty and each
+-- add a key. Only one active key per namespace may lack the key that added
+-- it, so the second insert conflicts and that publish must be signed by the
+-- key that won.
+CREATE UNIQUE INDEX namespace_keys_first_key_idx
+    ON namespace_keys (namespace_id)
+    WHERE added_by IS NULL AND removed_at IS NULL;
@@ -0,0 +1,32 @@
+-- Migration: advise only on versions signed by a revoked certificate
+--
+-- Revoking a publisher certificate flagged every publisher-signed version
+-- of the namespace, including those signed by its other certificates.
+-- Publishes now record the serial of the registry-issued certificate that
+-- signed them, and a revocation advises on each of those versions only.
+ALTER TABLE versions
+  ADD COLUMN signer_serial BIGINT;
+
+CREATE INDEX versions_signer_serial_idx ON versions (signer_serial)
+    WHERE signer_serial IS NOT NULL;
+
+-- Versions published before this migration record no serial; of those, only
+-- the ones published while the certificate was valid can have been signed
+-- by it.
+CREATE OR REPLACE FUNCTION advisory_on_cert_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO advisories (version_id, package_id, kind, affected, severity, summary, description)
+    SELECT v.id, p.id, 'revoked', '=' || v.version, 'high',
+           'Publisher certificate ' || NEW.serial || ' was revoked',
+           COALESCE(NEW.revocation_reason, '')
+      FROM packages p
+      JOIN versions v ON v.package_id = p.id
+     WHERE v.signer = 'publisher'
+       AND (v.signer_serial = NEW.serial
+            OR (v.signer_serial IS NULL
+                AND p.namespace_id = NEW.namespace_id
+                AND v.published_at BETWEEN NEW.issued_at AND NEW.expires_at));
+    RETURN NEW;
+END;
+$$;
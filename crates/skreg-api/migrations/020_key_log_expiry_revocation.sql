@@ -0,0 +1,166 @@
+-- Migration: key expiry and revocations in the key log
+--
+-- A client checks that a version's key was valid when it was signed against
+-- the namespace's key log, so the log has to show when a key stops being
+-- valid: key additions carry the key's expiry, and revoking a self-signed
+-- key, or the last unrevoked publisher certificate for a key, appends a
+-- 'revoked' event. Entries already in the log are immutable, and clients
+-- keep their hashes, so keys added before this migration keep additions
+-- without an expiry.
+
+-- Fingerprint of each certificate's key, comparable with
+-- namespace_keys.fingerprint. The API stores it on issue and revocation;
+-- older certificates get the fingerprint of the namespace key they certify
+-- where the key was added with them.
+ALTER TABLE publisher_certs
+  ADD COLUMN key_fingerprint TEXT;
+
+UPDATE publisher_certs c
+   SET key_fingerprint = k.fingerprint
+  FROM namespace_keys k
+ WHERE k.namespace_id = c.namespace_id
+   AND k.cert_pem = c.pem;
+
+CREATE INDEX publisher_certs_key_fingerprint_idx
+    ON publisher_certs (namespace_id, key_fingerprint);
+
+-- Whether key `fp` was ever one of namespace `ns`'s keys.
+CREATE OR REPLACE FUNCTION key_log_has_key(ns UUID, fp TEXT)
+RETURNS BOOLEAN LANGUAGE sql STABLE AS $$
+    SELECT EXISTS (SELECT 1 FROM key_log
+                    WHERE namespace_id = ns
+                      AND fp IN (event->>'fingerprint', event->>'new_fingerprint'))
+$$;
+
+-- Whether namespace `ns` still has an unrevoked, unexpired certificate for
+-- key `fp`.
+CREATE OR REPLACE FUNCTION key_has_active_cert(ns UUID, fp TEXT)
+RETURNS BOOLEAN LANGUAGE sql STABLE AS $$
+    SELECT EXISTS (SELECT 1 FROM publisher_certs
+                    WHERE namespace_id = ns
+                      AND key_fingerprint = fp
+                      AND revoked_at IS NULL
+                      AND expires_at > now())
+$$;
+
+-- Backfill revocations made before this migration, oldest first, each dated
+-- at its revocation or, if later, at the namespace's last key event, so
+-- entries stay dated in sequence order.
+DO $$
+DECLARE
+    r RECORD;
+BEGIN
+    FOR r IN
+        SELECT ns, fp, revoked_at FROM (
+            SELECT DISTINCT kl.namespace_id AS ns, rk.fingerprint AS fp, rk.revoked_at
+              FROM revoked_self_signed_keys rk
+              JOIN key_log kl
+                ON rk.fingerprint IN (kl.event->>'fingerprint', kl.event->>'new_fingerprint')
+            UNION ALL
+            SELECT c.namespace_id, c.key_fingerprint, MAX(c.revoked_at)
+              FROM publisher_certs c
+             WHERE c.key_fingerprint IS NOT NULL
+               AND c.revoked_at IS NOT NULL
+               AND key_log_has_key(c.namespace_id, c.key_fingerprint)
+               AND NOT key_has_active_cert(c.namespace_id, c.key_fingerprint)
+             GROUP BY c.namespace_id, c.key_fingerprint
+        ) revocations
+        ORDER BY revoked_at
+    LOOP
+        INSERT INTO key_log (namespace_id, seq, event, at)
+        SELECT r.ns, MAX(seq) + 1,
+               jsonb_build_object('kind', 'revoked', 'fingerprint', r.fp),
+               GREATEST(r.revoked_at, MAX(at))
+          FROM key_log WHERE namespace_id = r.ns;
+    END LOOP;
+END;
+$$;
+
+-- As in migration 015, with the expiry of added keys in the key log. The
+-- transparency log entry is unchanged.
+CREATE OR REPLACE FUNCTION log_namespace_key_change()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+DECLARE
+    ns_slug TEXT := (SELECT slug FROM namespaces WHERE id = NEW.namespace_id);
+BEGIN
+    IF TG_OP = 'INSERT' AND NEW.added_by IS NULL THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_pin',
+            'namespace', ns_slug,
+            'fingerprint', NEW.fingerprint));
+        PERFORM append_key_log(NEW.namespace_id, jsonb_build_object(
+            'kind', 'pinned',
+            'fingerprint', NEW.fingerprint));
+    ELSIF TG_OP = 'INSERT' THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_added',
+            'namespace', ns_slug,
+            'fingerprint', NEW.fingerprint,
+            'added_by', NEW.added_by));
+        PERFORM append_key_log(NEW.namespace_id, jsonb_strip_nulls(jsonb_build_object(
+            'kind', 'added',
+            'fingerprint', NEW.fingerprint,
+            'added_by', NEW.added_by,
+            'expires_at', NEW.expires_at)));
+    ELSIF NEW.fingerprint IS DISTINCT FROM OLD.fingerprint THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_rotation',
+            'namespace', ns_slug,
+            'old_fingerprint', OLD.fingerprint,
+            'new_fingerprint', NEW.fingerprint));
+        PERFORM append_key_log(NEW.namespace_id, jsonb_build_object(
+            'kind', 'rotated',
+            'old_fingerprint', OLD.fingerprint,
+            'new_fingerprint', NEW.fingerprint));
+    ELSIF OLD.removed_at IS NULL AND NEW.removed_at IS NOT NULL THEN
+        PERFORM append_log_entry(jsonb_build_object(
+            'kind', 'key_removed',
+            'namespace', ns_slug,
+            'fingerprint', NEW.fingerprint));
+        PERFORM append_key_log(NEW.namespace_id, jsonb_build_object(
+            'kind', 'removed',
+            'fingerprint', NEW.fingerprint));
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+-- A revoked self-signed key is revoked in every namespace it signed for.
+CREATE OR REPLACE FUNCTION key_log_key_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    PERFORM append_key_log(ns, jsonb_build_object(
+        'kind', 'revoked',
+        'fingerprint', NEW.fingerprint))
+       FROM (SELECT DISTINCT namespace_id AS ns FROM key_log
+              WHERE NEW.fingerprint IN (event->>'fingerprint', event->>'new_fingerprint')) keyed;
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER revoked_keys_key_log
+AFTER INSERT ON revoked_self_signed_keys
+FOR EACH ROW EXECUTE FUNCTION key_log_key_revoke();
+
+-- A key is revoked once its last unrevoked, unexpired certificate is.
+-- Renewals certify the same key, so revoking a superseded certificate
+-- leaves the key valid.
+CREATE OR REPLACE FUNCTION key_log_cert_revoke()
+RETURNS TRIGGER LANGUAGE plpgsql AS $$
+BEGIN
+    IF NEW.key_fingerprint IS NOT NULL
+       AND key_log_has_key(NEW.namespace_id, NEW.key_fingerprint)
+       AND NOT key_has_active_cert(NEW.namespace_id, NEW.key_fingerprint) THEN
+        PERFORM append_key_log(NEW.namespace_id, jsonb_build_object(
+            'kind', 'revoked',
+            'fingerprint', NEW.key_fingerprint));
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER publisher_certs_revoke_key_log
+AFTER UPDATE OF revoked_at ON publisher_certs
+FOR EACH ROW
+WHEN (OLD.revoked_at IS NULL AND NEW.revoked_at IS NOT NULL)
+EXECUTE FUNCTION key_log_cert_revoke();
@@ -0,0 +1,104 @@
+//! POST /v1/admin/name-allowlist — approve a legitimate near-duplicate name.
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::middleware::require_admin;
+use crate::router::SharedState;
+
+/// Request body for `POST /v1/admin/name-allowlist`.
+#[derive(Debug, Deserialize)]
+pub struct AllowlistRequest {
+    /// `package` or `namespace`.
+    pub kind: String,
+    /// Name being allowed: a `namespace/name` ref for packages, a slug for namespaces.
+    pub name: String,
+    /// The existing name it is allowed to resemble.
+    pub similar_to: String,
+    /// Why the pair is legitimate.
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Response body for `POST /v1/admin/name-allowlist`.
+#[derive(Debug, Serialize)]
+pub struct AllowlistResponse {
+    /// ID of the allowlist entry.
+    pub id: uuid::Uuid,
+}
+
+/// Return `true` if `name` has the shape required for `kind`.
+#[must_use]
+pub fn is_valid_allowlist_name(kind: &str, name: &str) -> bool {
+    match kind {
+        "package" => name
+            .split_once('/')
+            .is_some_and(|(ns, pkg)| !ns.is_empty() && !pkg.is_empty() && !pkg.contains('/')),
+        "namespace" => !name.is_empty() && !name.contains('/'),
+        _ => false,
+    }
+}
+
+/// Handle `POST /v1/admin/name-allowlist`.
+///
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`. The pair is symmetric:
+/// neither name is reported as squatting on the other.
+///
+/// # Errors
+///
+/// - `401` — missing or wrong admin token, or admin access not configured
+/// - `409` — the pair is already allowlisted
+/// - `422` — unknown kind or malformed names
+/// - `500` — database error
+pub async fn add_name_allowlist_handler(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(req): Json<AllowlistRequest>,
+) -> Result<(StatusCode, Json<AllowlistResponse>), StatusCode> {
+    require_admin(&headers, state.admin_token_hash.as_deref())?;
+
+    if !is_valid_allowlist_name(&req.kind, &req.name)
+        || !is_valid_allowlist_name(&req.kind, &req.similar_to)
+        || req.name == req.similar_to
+    {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let id = sqlx::query_scalar::<_, uuid::Uuid>(
+        "INSERT INTO name_allowlist (kind, name, similar_to, reason)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (kind, name, similar_to) DO NOTHING
+         RETURNING id",
+    )
+    .bind(&req.kind)
+    .bind(&req.name)
+    .bind(&req.similar_to)
+    .bind(&req.reason)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::CONFLICT)?;
+
+    Ok((StatusCode::CREATED, Json(AllowlistResponse { id })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowlist_names_match_kind() {
+        assert!(is_valid_allowlist_name("package", "acme/lint"));
+        assert!(!is_valid_allowlist_name("package", "lint"));
+        assert!(!is_valid_allowlist_name("package", "acme/lint/extra"));
+        assert!(is_valid_allowlist_name("namespace", "acme"));
+        assert!(!is_valid_allowlist_name("namespace", "acme/lint"));
+        assert!(!is_valid_allowlist_name("version", "acme"));
+    }
+}
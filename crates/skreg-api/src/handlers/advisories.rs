@@ -0,0 +1,250 @@
+//! GET  /v1/advisories — signed, incremental security advisory feed.
+//! POST /v1/advisories — create an advisory (registry admin only).
+
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use log::error;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::pss::BlindedSigningKey;
+use rsa::signature::hazmat::RandomizedPrehashSigner;
+use rsa::signature::SignatureEncoding;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use skreg_core::advisory::{Advisory, AdvisoryFeed, Severity, SignedAdvisoryFeed};
+
+use crate::middleware::require_admin;
+use crate::router::SharedState;
+
+/// Maximum advisories returned per feed page.
+const PAGE_SIZE: i64 = 500;
+
+/// Query parameters for `GET /v1/advisories`.
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    /// Return advisories with a sequence number greater than this (default 0).
+    pub since: Option<i64>,
+}
+
+/// Request body for `POST /v1/advisories`.
+#[derive(Debug, Deserialize)]
+pub struct CreateAdvisoryRequest {
+    /// Namespace slug of the affected package.
+    pub namespace: String,
+    /// Affected package name.
+    pub package: String,
+    /// Affected versions as a semver requirement; defaults to `*`.
+    pub affected: Option<String>,
+    /// `low`, `medium`, `high` or `critical`.
+    pub severity: String,
+    /// One-line summary.
+    pub summary: String,
+    /// Longer description.
+    pub description: Option<String>,
+    /// First unaffected version, if any.
+    pub fixed_version: Option<String>,
+}
+
+/// Response body for `POST /v1/advisories`.
+#[derive(Debug, Serialize)]
+pub struct CreateAdvisoryResponse {
+    /// Assigned advisory identifier.
+    pub id: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct AdvisoryRow {
+    advisory_id: String,
+    seq: i64,
+    namespace: String,
+    package: String,
+    affected: String,
+    severity: String,
+    kind: String,
+    summary: String,
+    description: String,
+    fixed_version: Option<String>,
+    published_at: DateTime<Utc>,
+}
+
+impl AdvisoryRow {
+    fn into_advisory(self) -> Advisory {
+        Advisory {
+            id: self.advisory_id,
+            seq: self.seq,
+            namespace: self.namespace,
+            package: self.package,
+            affected: self.affected,
+            // The column is constrained to valid names; fall back to the most
+            // severe level rather than silently downgrading.
+            severity: Severity::parse(&self.severity).unwrap_or(Severity::Critical),
+            kind: self.kind,
+            summary: self.summary,
+            description: self.description,
+            fixed_version: self.fixed_version,
+            published_at: self.published_at,
+        }
+    }
+}
+
+/// Sign `payload` with the Publisher CA key (RSA-PSS over its SHA-256), as
+/// for advisory feeds and transparency log tree heads.
+pub(crate) fn sign_with_publisher_ca(payload: &str, ca_key_pem: &str) -> anyhow::Result<String> {
+    let key = rsa::RsaPrivateKey::from_pkcs8_pem(ca_key_pem)
+        .map_err(|e| anyhow::anyhow!("parsing Publisher CA key: {e}"))?;
+    let digest = Sha256::digest(payload.as_bytes());
+    let sig = BlindedSigningKey::<Sha256>::new(key)
+        .sign_prehash_with_rng(&mut rand::thread_rng(), &digest)
+        .map_err(|e| anyhow::anyhow!("signing with Publisher CA key: {e}"))?;
+    Ok(hex::encode(sig.to_bytes()))
+}
+
+/// Handle `GET /v1/advisories?since=<seq>`.
+///
+/// Returns up to 500 advisories newer than `since`, oldest first, inside a
+/// [`SignedAdvisoryFeed`] envelope signed by the Publisher CA.
+///
+/// # Errors
+///
+/// Returns `400` for a negative `since`, or `500` on a database or signing error.
+pub async fn advisories_feed_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<FeedQuery>,
+) -> Result<Json<SignedAdvisoryFeed>, StatusCode> {
+    let since = params.since.unwrap_or(0);
+    if since < 0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let rows: Vec<AdvisoryRow> = sqlx::query_as(
+        "SELECT a.advisory_id, a.seq, n.slug AS namespace, p.name AS package,
+                a.affected, a.severity, a.kind, a.summary, a.description,
+                a.fixed_version, a.published_at
+         FROM advisories a
+         JOIN packages p ON p.id = a.package_id
+         JOIN namespaces n ON n.id = p.namespace_id
+         WHERE a.seq > $1
+         ORDER BY a.seq
+         LIMIT $2",
+    )
+    .bind(since)
+    .bind(PAGE_SIZE + 1)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("advisories query failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let has_more = rows.len() > usize::try_from(PAGE_SIZE).unwrap_or(usize::MAX);
+    let advisories: Vec<Advisory> = rows
+        .into_iter()
+        .take(usize::try_from(PAGE_SIZE).unwrap_or(usize::MAX))
+        .map(AdvisoryRow::into_advisory)
+        .collect();
+    let next_since = advisories.last().map_or(since, |a| a.seq);
+
+    let feed = serde_json::to_string(&AdvisoryFeed {
+        advisories,
+        next_since,
+        has_more,
+        generated_at: Utc::now(),
+    })
+    .map_err(|e| {
+        error!("serialising advisory feed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let signature_hex =
+        sign_with_publisher_ca(&feed, &state.publisher_ca_key_pem).map_err(|e| {
+            error!("{e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(SignedAdvisoryFeed {
+        feed,
+        signature_hex,
+        signer_cert_pem: state.publisher_ca_cert_pem.clone(),
+    }))
+}
+
+/// Handle `POST /v1/advisories` — publish a manual advisory.
+///
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`.
+///
+/// # Errors
+///
+/// - `401` — missing or wrong admin token, or admin access not configured
+/// - `404` — the package does not exist
+/// - `422` — unknown severity, invalid version range or empty summary
+/// - `500` — database error
+pub async fn create_advisory_handler(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateAdvisoryRequest>,
+) -> Result<(StatusCode, Json<CreateAdvisoryResponse>), StatusCode> {
+    require_admin(&headers, state.admin_token_hash.as_deref())?;
+
+    let severity = Severity::parse(&req.severity).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let affected = req.affected.unwrap_or_else(|| "*".to_owned());
+    if semver::VersionReq::parse(&affected).is_err() || req.summary.trim().is_empty() {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    if let Some(fixed) = &req.fixed_version {
+        semver::Version::parse(fixed).map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    }
+
+    let id: Option<String> = sqlx::query_scalar(
+        "INSERT INTO advisories
+             (package_id, kind, affected, severity, summary, description, fixed_version)
+         SELECT p.id, 'manual', $3, $4, $5, $6, $7
+         FROM packages p
+         JOIN namespaces n ON n.id = p.namespace_id
+         WHERE n.slug = $1 AND p.name = $2
+         RETURNING advisory_id",
+    )
+    .bind(&req.namespace)
+    .bind(&req.package)
+    .bind(&affected)
+    .bind(severity.as_str())
+    .bind(req.summary.trim())
+    .bind(req.description.unwrap_or_default())
+    .bind(&req.fixed_version)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let id = id.ok_or(StatusCode::NOT_FOUND)?;
+    Ok((StatusCode::CREATED, Json(CreateAdvisoryResponse { id })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::pkcs8::{EncodePrivateKey, LineEnding};
+    use rsa::pss::{Signature, VerifyingKey};
+    use rsa::signature::hazmat::PrehashVerifier;
+
+    #[test]
+    fn feed_signature_verifies_over_payload_digest() {
+        let key = rsa::RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+        let pem = key.to_pkcs8_pem(LineEnding::LF).unwrap();
+        let payload = r#"{"advisories":[],"next_since":0}"#;
+
+        let sig_hex = sign_with_publisher_ca(payload, &pem).unwrap();
+        let sig = Signature::try_from(hex::decode(sig_hex).unwrap().as_slice()).unwrap();
+        let verifier = VerifyingKey::<Sha256>::new(key.to_public_key());
+        verifier
+            .verify_prehash(&Sha256::digest(payload.as_bytes()), &sig)
+            .unwrap();
+    }
+
+    #[test]
+    fn sign_feed_rejects_missing_key() {
+        assert!(sign_with_publisher_ca("{}", "").is_err());
+    }
+}
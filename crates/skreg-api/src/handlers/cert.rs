@@ -8,8 +8,9 @@ use log::error;
 use rcgen::{Certificate, CertificateParams, CertificateSigningRequest, KeyPair};
 use serde::Serialize;
 
+use super::publish::spki_fingerprint;
 use crate::middleware::{extract_bearer, resolve_namespace};
-use crate::router::SharedState;
+use crate::router::{AppState, SharedState};
 
 /// Maximum allowed CSR size in bytes (16 KiB).
 pub(crate) const MAX_CSR_BYTES: usize = 16 * 1024;
@@ -35,15 +36,17 @@ pub(crate) fn validate_csr_size(csr_pem: &str) -> Result<(), StatusCode> {
     Ok(())
 }
 
-/// Parse a PKCS#10 CSR PEM and validate that its CN matches `expected_namespace`.
+/// Parse a PKCS#10 CSR PEM and validate that its CN matches `expected_namespace`
+/// and its key is RSA, Ed25519 or ECDSA P-256.
 ///
 /// Returns the parsed [`CertificateSigningRequest`] on success.
 ///
 /// # Errors
 ///
-/// Returns `422 Unprocessable Entity` if the CSR cannot be parsed or the CN
-/// does not match the authenticated namespace.
-fn parse_and_validate_csr(
+/// Returns `422 Unprocessable Entity` if the CSR cannot be parsed, its key
+/// algorithm is not accepted, or the CN does not match the authenticated
+/// namespace.
+pub(crate) fn parse_and_validate_csr(
     csr_pem: &str,
     expected_namespace: &str,
 ) -> Result<CertificateSigningRequest, StatusCode> {
@@ -52,6 +55,17 @@ fn parse_and_validate_csr(
         StatusCode::UNPROCESSABLE_ENTITY
     })?;
 
+    // rcgen names the CSR's key by the algorithm that signed it; the leaf
+    // keeps that key, so only accept algorithms clients can verify.
+    let alg = csr.params.alg;
+    if alg != &rcgen::PKCS_RSA_SHA256
+        && alg != &rcgen::PKCS_ED25519
+        && alg != &rcgen::PKCS_ECDSA_P256_SHA256
+    {
+        error!("CSR key algorithm {alg:?} is not accepted");
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
     // Extract CN from the CSR's distinguished name and verify it matches
     // the authenticated namespace.
     let cn = csr
@@ -124,8 +138,8 @@ fn ca_params_from_cert_and_key(
 ///
 /// Builds the CA [`Certificate`] from the CA key + cert PEM, then calls
 /// [`CertificateSigningRequest::serialize_pem_with_signer`] so that the leaf
-/// cert contains the *client's* public key — the server never generates or
-/// sees the client private key.
+/// cert contains the *client's* public key, of whatever algorithm it is — the
+/// server never generates or sees the client private key.
 ///
 /// # Errors
 ///
@@ -155,6 +169,97 @@ fn sign_csr_with_ca(
     Ok((leaf_pem, serial_i64))
 }
 
+/// Reject the issuance if namespace `ns_id` already had 5 certificates issued
+/// in the last 24 h.
+///
+/// # Errors
+///
+/// Returns `429` if the limit is reached, or `500` on database error.
+pub(crate) async fn check_issuance_rate(
+    state: &AppState,
+    ns_id: uuid::Uuid,
+) -> Result<(), StatusCode> {
+    let issuances_24h = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM publisher_certs
+         WHERE namespace_id = $1 AND issued_at > now() - INTERVAL '24 hours'",
+    )
+    .bind(ns_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db rate-limit check: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if issuances_24h >= 5 {
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    }
+    Ok(())
+}
+
+/// Sign `csr` with the Publisher CA for 90 days, persist the leaf to
+/// `publisher_certs`, and write an audit log entry for `operation`.
+///
+/// # Errors
+///
+/// Returns `422` if the CSR cannot be signed, or `500` on database error.
+pub(crate) async fn issue_cert(
+    state: &AppState,
+    ns_id: uuid::Uuid,
+    ns_slug: &str,
+    csr: &CertificateSigningRequest,
+    operation: &str,
+) -> Result<CertResponse, StatusCode> {
+    let ca_key_pem = state.publisher_ca_key_pem.clone();
+    let pub_ca_cert_pem = state.publisher_ca_cert_pem.clone();
+
+    // Sign the CSR with the CA — the leaf cert contains the client's public key
+    let (leaf_pem, serial) = sign_csr_with_ca(csr, &ca_key_pem, &pub_ca_cert_pem).map_err(|e| {
+        error!("sign_csr_with_ca: {e}");
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+
+    // Compute expiry (90 days from now)
+    let expires_at = chrono::Utc::now() + chrono::Duration::days(90);
+
+    // Persist to publisher_certs
+    sqlx::query(
+        "INSERT INTO publisher_certs (namespace_id, serial, pem, expires_at, key_fingerprint)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(ns_id)
+    .bind(serial)
+    .bind(&leaf_pem)
+    .bind(expires_at)
+    .bind(spki_fingerprint(&leaf_pem)?)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db insert publisher_cert: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Write audit log
+    sqlx::query(
+        "INSERT INTO pki_audit_log (namespace_id, operation, outcome, detail)
+         VALUES ($1, $2, 'success', $3)",
+    )
+    .bind(ns_id)
+    .bind(operation)
+    .bind(serde_json::json!({ "serial": serial, "namespace": ns_slug }))
+    .execute(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db audit log: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(CertResponse {
+        cert: leaf_pem,
+        ca_cert: pub_ca_cert_pem,
+    })
+}
+
 /// Handle `POST /v1/namespaces/:ns/cert`.
 ///
 /// Authenticates the caller via Bearer token, validates the PKCS#10 CSR
@@ -202,21 +307,7 @@ pub async fn cert_handler(
     let csr = parse_and_validate_csr(csr_pem, &ns_slug)?;
 
     // Rate limit: max 5 issuances per namespace per 24h
-    let issuances_24h = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM publisher_certs
-         WHERE namespace_id = $1 AND issued_at > now() - INTERVAL '24 hours'",
-    )
-    .bind(ns_id)
-    .fetch_one(&state.pool)
-    .await
-    .map_err(|e| {
-        error!("db rate-limit check: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    if issuances_24h >= 5 {
-        return Err(StatusCode::TOO_MANY_REQUESTS);
-    }
+    check_issuance_rate(&state, ns_id).await?;
 
     // Check for existing active cert (not revoked, not expired)
     let has_active = sqlx::query_scalar::<_, bool>(
@@ -239,53 +330,9 @@ pub async fn cert_handler(
         return Err(StatusCode::CONFLICT);
     }
 
-    let ca_key_pem = state.publisher_ca_key_pem.clone();
-    let pub_ca_cert_pem = state.publisher_ca_cert_pem.clone();
-
-    // Sign the CSR with the CA — the leaf cert contains the client's public key
-    let (leaf_pem, serial) =
-        sign_csr_with_ca(&csr, &ca_key_pem, &pub_ca_cert_pem).map_err(|e| {
-            error!("sign_csr_with_ca: {e}");
-            StatusCode::UNPROCESSABLE_ENTITY
-        })?;
-
-    // Compute expiry (90 days from now)
-    let expires_at = chrono::Utc::now() + chrono::Duration::days(90);
-
-    // Persist to publisher_certs
-    sqlx::query(
-        "INSERT INTO publisher_certs (namespace_id, serial, pem, expires_at)
-         VALUES ($1, $2, $3, $4)",
-    )
-    .bind(ns_id)
-    .bind(serial)
-    .bind(&leaf_pem)
-    .bind(expires_at)
-    .execute(&state.pool)
-    .await
-    .map_err(|e| {
-        error!("db insert publisher_cert: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    // Write audit log
-    sqlx::query(
-        "INSERT INTO pki_audit_log (namespace_id, operation, outcome, detail)
-         VALUES ($1, 'cert_issue', 'success', $2)",
-    )
-    .bind(ns_id)
-    .bind(serde_json::json!({ "serial": serial, "namespace": ns_slug }))
-    .execute(&state.pool)
-    .await
-    .map_err(|e| {
-        error!("db audit log: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    Ok(Json(CertResponse {
-        cert: leaf_pem,
-        ca_cert: pub_ca_cert_pem,
-    }))
+    issue_cert(&state, ns_id, &ns_slug, &csr, "cert_issue")
+        .await
+        .map(Json)
 }
 
 #[cfg(test)]
@@ -344,6 +391,52 @@ mod tests {
         );
     }
 
+    /// Build a CSR for `cn` with a fresh key of `alg` (not RSA).
+    fn csr_for(cn: &str, alg: &'static rcgen::SignatureAlgorithm) -> String {
+        let mut params = rcgen::CertificateParams::new(vec![cn.to_owned()]);
+        params.alg = alg;
+        params.key_pair = Some(KeyPair::generate(alg).unwrap());
+        let mut dn = rcgen::DistinguishedName::new();
+        dn.push(rcgen::DnType::CommonName, cn);
+        params.distinguished_name = dn;
+        rcgen::Certificate::from_params(params)
+            .unwrap()
+            .serialize_request_pem()
+            .unwrap()
+    }
+
+    #[test]
+    fn issued_leaf_keeps_the_csr_key_algorithm() {
+        use x509_cert::der::DecodePem;
+
+        let ca_key_pem = include_str!("../../tests/fixtures/pss-ca.key");
+        let ca_cert_pem = include_str!("../../tests/fixtures/pss-ca.pem");
+
+        for (alg, spki_oid) in [
+            (&rcgen::PKCS_ED25519, "1.3.101.112"),
+            (&rcgen::PKCS_ECDSA_P256_SHA256, "1.2.840.10045.2.1"),
+        ] {
+            let csr_pem = csr_for("acme", alg);
+            let csr = parse_and_validate_csr(&csr_pem, "acme").unwrap();
+            let (leaf_pem, _) = sign_csr_with_ca(&csr, ca_key_pem, ca_cert_pem).unwrap();
+            let leaf = x509_cert::Certificate::from_pem(leaf_pem.as_bytes()).unwrap();
+            assert_eq!(
+                leaf.tbs_certificate
+                    .subject_public_key_info
+                    .algorithm
+                    .oid
+                    .to_string(),
+                spki_oid
+            );
+        }
+    }
+
+    #[test]
+    fn parse_and_validate_csr_rejects_other_key_algorithms() {
+        let csr_pem = csr_for("acme", &rcgen::PKCS_ECDSA_P384_SHA384);
+        assert!(parse_and_validate_csr(&csr_pem, "acme").is_err());
+    }
+
     #[test]
     fn parse_and_validate_csr_rejects_cn_mismatch() {
         // Build a CSR with CN="acme" and check it rejects namespace "other"
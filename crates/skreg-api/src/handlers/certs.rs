@@ -0,0 +1,470 @@
+//! GET  /v1/namespaces/:ns/certs — the namespace's publisher certificates.
+//! POST /v1/namespaces/:ns/certs/:serial/renew — re-certify the same key.
+//! POST /v1/namespaces/:ns/certs/:serial/revoke — revoke a certificate.
+//! GET  /v1/namespaces/:ns/certs/:serial/revoke/confirm — confirm via email.
+//!
+//! A revocation signed by the certificate's own key takes effect at once;
+//! an unsigned one, for a lost key, waits for the namespace owner to confirm
+//! it by email.
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use skreg_core::certs::{CertRevocationToken, PublisherCert};
+use skreg_core::types::Sha256Digest;
+use x509_cert::der::{DecodePem, Encode};
+
+use super::cert::{
+    check_issuance_rate, issue_cert, parse_and_validate_csr, validate_csr_size, CertResponse,
+};
+use super::db_error;
+use super::publish::spki_fingerprint;
+use super::rotate::{
+    check_nonce_unused, send_confirmation_email, validate_token_window, verify_sig_from_cert,
+    ConfirmQuery, ConfirmResponse,
+};
+use crate::middleware::{extract_bearer, resolve_namespace};
+use crate::router::{AppState, SharedState};
+
+/// Maximum length of a revocation reason.
+const MAX_REASON_LEN: usize = 256;
+
+/// Maximum unsigned revocations a namespace may submit per 24 hours.
+const MAX_PENDING_PER_DAY: i64 = 5;
+
+/// Request body for `POST /v1/namespaces/:ns/certs/:serial/revoke`.
+#[derive(Debug, Deserialize)]
+pub struct CertRevokeRequest {
+    /// The revocation.
+    pub token: CertRevocationToken,
+    /// Hex signature over `sha256(token.signing_bytes())` by the
+    /// certificate's key. Without one, the revocation is confirmed by email.
+    pub signature: Option<String>,
+}
+
+/// Response body for `POST /v1/namespaces/:ns/certs/:serial/revoke`.
+#[derive(Debug, Serialize)]
+pub struct CertRevokeResponse {
+    /// Human-readable status message.
+    pub message: String,
+    /// Whether the certificate is now revoked, rather than awaiting email
+    /// confirmation.
+    pub revoked: bool,
+}
+
+/// Authenticate the Bearer token in `headers` as the owner of `ns`.
+pub(crate) async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    ns: &str,
+) -> Result<(uuid::Uuid, String), StatusCode> {
+    let auth = headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let raw_key = extract_bearer(auth).ok_or(StatusCode::UNAUTHORIZED)?;
+    let (ns_id, ns_slug) = resolve_namespace(&state.pool, &raw_key).await?;
+    if ns_slug != ns {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok((ns_id, ns_slug))
+}
+
+/// The PEM and revocation time of certificate `serial` of namespace `ns_id`.
+///
+/// # Errors
+///
+/// Returns `404` if the namespace has no such certificate, or `500` on
+/// database error.
+async fn fetch_cert(
+    state: &AppState,
+    ns_id: uuid::Uuid,
+    serial: i64,
+) -> Result<(String, Option<DateTime<Utc>>), StatusCode> {
+    sqlx::query_as::<_, (String, Option<DateTime<Utc>>)>(
+        "SELECT pem, revoked_at FROM publisher_certs WHERE namespace_id = $1 AND serial = $2",
+    )
+    .bind(ns_id)
+    .bind(serial)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(db_error("fetch publisher_cert"))?
+    .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// SHA-256 fingerprint (hex) of the DER `SubjectPublicKeyInfo` in a PKCS#10
+/// CSR, comparable with [`spki_fingerprint`] of a certificate.
+///
+/// # Errors
+///
+/// Returns `422` if the CSR cannot be parsed.
+pub(crate) fn csr_fingerprint(csr_pem: &str) -> Result<String, StatusCode> {
+    let csr = x509_cert::request::CertReq::from_pem(csr_pem.as_bytes()).map_err(|e| {
+        error!("CSR parse error: {e}");
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+    let spki_der = csr
+        .info
+        .public_key
+        .to_der()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(hex::encode(Sha256::digest(&spki_der)))
+}
+
+/// Handle `GET /v1/namespaces/:ns/certs`.
+///
+/// Returns every publisher certificate issued to `:ns`, including expired
+/// and revoked ones, oldest first.
+///
+/// # Errors
+///
+/// - `401` — missing or invalid Bearer token
+/// - `403` — token namespace does not match `:ns`
+/// - `500` — database error or a stored certificate that no longer parses
+pub async fn certs_handler(
+    State(state): State<SharedState>,
+    Path(ns): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PublisherCert>>, StatusCode> {
+    type Row = (
+        i64,
+        String,
+        DateTime<Utc>,
+        DateTime<Utc>,
+        Option<DateTime<Utc>>,
+        Option<String>,
+    );
+    let (ns_id, _) = authorize(&state, &headers, &ns).await?;
+
+    let rows = sqlx::query_as::<_, Row>(
+        "SELECT serial, pem, issued_at, expires_at, revoked_at, revocation_reason
+         FROM publisher_certs
+         WHERE namespace_id = $1
+         ORDER BY issued_at, serial",
+    )
+    .bind(ns_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(db_error("fetch publisher_certs"))?;
+
+    let certs = rows
+        .into_iter()
+        .map(
+            |(serial, pem, issued_at, expires_at, revoked_at, revocation_reason)| {
+                let fingerprint = spki_fingerprint(&pem).map_err(|_| {
+                    error!("stored certificate {serial} does not parse");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+                Ok(PublisherCert {
+                    serial,
+                    fingerprint,
+                    issued_at: issued_at.to_rfc3339(),
+                    expires_at: expires_at.to_rfc3339(),
+                    revoked_at: revoked_at.map(|t| t.to_rfc3339()),
+                    revocation_reason,
+                    cert_pem: pem,
+                })
+            },
+        )
+        .collect::<Result<Vec<_>, StatusCode>>()?;
+    Ok(Json(certs))
+}
+
+/// Handle `POST /v1/namespaces/:ns/certs/:serial/renew`.
+///
+/// Issues a new certificate for the key of certificate `:serial`. The body
+/// is a PKCS#10 CSR for that same key, so its self-signature proves the
+/// caller still holds it. Unlike `POST /v1/namespaces/:ns/cert`, this is
+/// allowed while the old certificate is active; it stays valid until it
+/// expires.
+///
+/// # Errors
+///
+/// - `401` — missing or invalid Bearer token
+/// - `403` — token namespace does not match `:ns`
+/// - `404` — `:ns` has no certificate `:serial`
+/// - `409` — certificate `:serial` is revoked
+/// - `413` — CSR body exceeds 16 KiB
+/// - `422` — CSR is malformed, its CN does not match `:ns`, or it is for
+///   another key
+/// - `429` — rate limit exceeded (5 issuances per 24 h)
+/// - `500` — database error
+pub async fn cert_renew_handler(
+    State(state): State<SharedState>,
+    Path((ns, serial)): Path<(String, i64)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<CertResponse>, StatusCode> {
+    let (ns_id, ns_slug) = authorize(&state, &headers, &ns).await?;
+
+    let csr_pem = std::str::from_utf8(&body).map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    validate_csr_size(csr_pem)?;
+    let csr = parse_and_validate_csr(csr_pem, &ns_slug)?;
+
+    check_issuance_rate(&state, ns_id).await?;
+
+    let (cert_pem, revoked_at) = fetch_cert(&state, ns_id, serial).await?;
+    if revoked_at.is_some() {
+        return Err(StatusCode::CONFLICT);
+    }
+    if csr_fingerprint(csr_pem)? != spki_fingerprint(&cert_pem)? {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    issue_cert(&state, ns_id, &ns_slug, &csr, "cert_renew")
+        .await
+        .map(Json)
+}
+
+/// Revoke certificate `serial` of `ns_id` for `reason` and audit it.
+///
+/// Also stores the certificate's key fingerprint, which certificates issued
+/// before it was recorded lack, so the namespace's key log can show the key
+/// revoked once none of its certificates remain.
+async fn revoke_cert(
+    state: &AppState,
+    ns_id: uuid::Uuid,
+    serial: i64,
+    reason: Option<&str>,
+) -> Result<(), StatusCode> {
+    let (cert_pem, _) = fetch_cert(state, ns_id, serial).await?;
+    sqlx::query(
+        "UPDATE publisher_certs
+         SET revoked_at = now(), revocation_reason = $3, key_fingerprint = $4
+         WHERE namespace_id = $1 AND serial = $2 AND revoked_at IS NULL",
+    )
+    .bind(ns_id)
+    .bind(serial)
+    .bind(reason)
+    .bind(spki_fingerprint(&cert_pem)?)
+    .execute(&state.pool)
+    .await
+    .map_err(db_error("revoke publisher_cert"))?;
+
+    // Audit log (after revocation — best-effort).
+    let _ = sqlx::query(
+        "INSERT INTO pki_audit_log (namespace_id, operation, outcome, detail)
+         VALUES ($1, 'cert_revoke', 'success', $2)",
+    )
+    .bind(ns_id)
+    .bind(serde_json::json!({ "serial": serial, "reason": reason }))
+    .execute(&state.pool)
+    .await;
+    Ok(())
+}
+
+/// Store an unsigned revocation and return its `confirm_token`.
+async fn persist_pending_revocation(
+    state: &AppState,
+    ns_id: uuid::Uuid,
+    serial: i64,
+    reason: Option<&str>,
+) -> Result<String, StatusCode> {
+    let pending_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM pending_cert_revocations
+         WHERE namespace_id = $1 AND created_at > now() - INTERVAL '24 hours'",
+    )
+    .bind(ns_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(db_error("rate-limit check"))?;
+    if pending_count >= MAX_PENDING_PER_DAY {
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let confirm_token = hex::encode(rand::random::<[u8; 32]>());
+    sqlx::query(
+        "INSERT INTO pending_cert_revocations
+             (namespace_id, serial, reason, confirm_token, expires_at)
+         VALUES ($1, $2, $3, $4, now() + INTERVAL '24 hours')",
+    )
+    .bind(ns_id)
+    .bind(serial)
+    .bind(reason)
+    .bind(&confirm_token)
+    .execute(&state.pool)
+    .await
+    .map_err(db_error("insert pending_cert_revocation"))?;
+    Ok(confirm_token)
+}
+
+/// Handle `POST /v1/namespaces/:ns/certs/:serial/revoke`.
+///
+/// Validates the revocation token (auth, time bounds, serial, nonce). With a
+/// signature by the certificate's key the certificate is revoked at once;
+/// without one a pending revocation is stored and a confirmation email sent.
+///
+/// # Errors
+///
+/// - `401` — missing/invalid Bearer token or bad signature
+/// - `403` — token namespace does not match `:ns`
+/// - `404` — `:ns` has no certificate `:serial`
+/// - `409` — nonce already used, or the certificate is already revoked
+/// - `422` — malformed token, or its serial is not `:serial`
+/// - `429` — rate limit exceeded (unsigned revocations only)
+/// - `500` — database error
+/// - `503` — the confirmation email could not be sent
+pub async fn cert_revoke_handler(
+    State(state): State<SharedState>,
+    Path((ns, serial)): Path<(String, i64)>,
+    headers: HeaderMap,
+    Json(body): Json<CertRevokeRequest>,
+) -> Result<Json<CertRevokeResponse>, StatusCode> {
+    let (ns_id, ns_slug) = authorize(&state, &headers, &ns).await?;
+
+    let token = &body.token;
+    validate_token_window(
+        &token.namespace,
+        &token.issued_at,
+        &token.expires_at,
+        &ns_slug,
+    )?;
+    if token.serial != serial.to_string()
+        || token
+            .reason
+            .as_ref()
+            .is_some_and(|r| r.len() > MAX_REASON_LEN)
+    {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    check_nonce_unused(&state, &token.nonce).await?;
+
+    let (cert_pem, revoked_at) = fetch_cert(&state, ns_id, serial).await?;
+    if revoked_at.is_some() {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    if let Some(signature) = &body.signature {
+        let token_bytes = token.signing_bytes().map_err(|e| {
+            error!("revocation signing bytes: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        verify_sig_from_cert(&cert_pem, signature, &Sha256Digest::of(&token_bytes))?;
+    }
+
+    let expires_at = DateTime::parse_from_rfc3339(&token.expires_at)
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    sqlx::query("INSERT INTO rotation_nonces (nonce, expires_at) VALUES ($1, $2)")
+        .bind(&token.nonce)
+        .bind(expires_at)
+        .execute(&state.pool)
+        .await
+        .map_err(db_error("insert nonce"))?;
+
+    if body.signature.is_some() {
+        revoke_cert(&state, ns_id, serial, token.reason.as_deref()).await?;
+        return Ok(Json(CertRevokeResponse {
+            message: format!("Certificate {serial} revoked."),
+            revoked: true,
+        }));
+    }
+
+    let confirm_token =
+        persist_pending_revocation(&state, ns_id, serial, token.reason.as_deref()).await?;
+    if let Err(e) = send_confirmation_email(
+        &state,
+        ns_id,
+        &ns_slug,
+        "certificate revocation",
+        &format!("certs/{serial}/revoke/confirm"),
+        &confirm_token,
+    )
+    .await
+    {
+        error!("send_confirmation_email failed: {e:?}");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    Ok(Json(CertRevokeResponse {
+        message: "Check your email to confirm the revocation.".to_owned(),
+        revoked: false,
+    }))
+}
+
+/// Handle `GET /v1/namespaces/:ns/certs/:serial/revoke/confirm?token=...`.
+///
+/// Looks up the pending revocation by `confirm_token`, verifies it is not
+/// expired and is for certificate `:serial` of `:ns`, then revokes the
+/// certificate and deletes the pending row.
+///
+/// # Errors
+///
+/// - `404` — confirm token not found, expired, or for another certificate
+/// - `403` — token belongs to a different namespace
+/// - `500` — database error
+pub async fn cert_revoke_confirm_handler(
+    State(state): State<SharedState>,
+    Path((ns, serial)): Path<(String, i64)>,
+    Query(params): Query<ConfirmQuery>,
+) -> Result<Json<ConfirmResponse>, StatusCode> {
+    let (ns_id, row_ns_slug, row_serial, reason) =
+        sqlx::query_as::<_, (uuid::Uuid, String, i64, Option<String>)>(
+            "SELECT pr.namespace_id, n.slug, pr.serial, pr.reason
+             FROM pending_cert_revocations pr
+             JOIN namespaces n ON n.id = pr.namespace_id
+             WHERE pr.confirm_token = $1 AND pr.expires_at > now()",
+        )
+        .bind(&params.token)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(db_error("fetch pending_cert_revocation"))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if row_ns_slug != ns {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if row_serial != serial {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    revoke_cert(&state, ns_id, serial, reason.as_deref()).await?;
+
+    sqlx::query("DELETE FROM pending_cert_revocations WHERE confirm_token = $1")
+        .bind(&params.token)
+        .execute(&state.pool)
+        .await
+        .map_err(db_error("delete pending_cert_revocation"))?;
+
+    Ok(Json(ConfirmResponse {
+        message: format!("Certificate {serial} revoked."),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csr_fingerprint_matches_the_certified_key() {
+        let key_pair = rcgen::KeyPair::generate(&rcgen::PKCS_ED25519).unwrap();
+        let mut params = rcgen::CertificateParams::new(vec!["acme".to_owned()]);
+        params.alg = &rcgen::PKCS_ED25519;
+        params.key_pair = Some(key_pair);
+        let cert = rcgen::Certificate::from_params(params).unwrap();
+
+        let csr_pem = cert.serialize_request_pem().unwrap();
+        let cert_pem = cert.serialize_pem().unwrap();
+        assert_eq!(
+            csr_fingerprint(&csr_pem).unwrap(),
+            spki_fingerprint(&cert_pem).unwrap()
+        );
+
+        let other = rcgen::KeyPair::generate(&rcgen::PKCS_ED25519).unwrap();
+        let mut params = rcgen::CertificateParams::new(vec!["acme".to_owned()]);
+        params.alg = &rcgen::PKCS_ED25519;
+        params.key_pair = Some(other);
+        let other_csr = rcgen::Certificate::from_params(params)
+            .unwrap()
+            .serialize_request_pem()
+            .unwrap();
+        assert_ne!(
+            csr_fingerprint(&other_csr).unwrap(),
+            spki_fingerprint(&cert_pem).unwrap()
+        );
+    }
+}
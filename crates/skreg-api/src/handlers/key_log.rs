@@ -0,0 +1,71 @@
+//! GET /v1/namespaces/:ns/key-log — the namespace's signed key history.
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use log::error;
+use skreg_core::key_log::{KeyEvent, KeyLog, SignedKeyLog};
+
+use super::advisories::sign_with_publisher_ca;
+use crate::router::SharedState;
+
+/// Handle `GET /v1/namespaces/:ns/key-log`.
+///
+/// Returns every key pin, addition, rotation, removal and revocation of
+/// `:ns`, oldest first and hash-linked, signed by the Publisher CA. An
+/// unknown namespace has an empty log.
+///
+/// # Errors
+///
+/// Returns `500` on a database or signing error, or if a stored event no
+/// longer parses.
+pub async fn key_log_handler(
+    State(state): State<SharedState>,
+    Path(ns): Path<String>,
+) -> Result<Json<SignedKeyLog>, StatusCode> {
+    let rows = sqlx::query_as::<_, (serde_json::Value, DateTime<Utc>)>(
+        "SELECT kl.event, kl.at
+         FROM key_log kl
+         JOIN namespaces n ON n.id = kl.namespace_id
+         WHERE n.slug = $1
+         ORDER BY kl.seq",
+    )
+    .bind(&ns)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db fetch key_log: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let events = rows
+        .into_iter()
+        .map(|(event, at)| {
+            let event = serde_json::from_value::<KeyEvent>(event).map_err(|e| {
+                error!("parsing stored key event: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            Ok((at, event))
+        })
+        .collect::<Result<Vec<_>, StatusCode>>()?;
+
+    let key_log = KeyLog::build(ns, events)
+        .map_err(|e| e.to_string())
+        .and_then(|log| serde_json::to_string(&log).map_err(|e| e.to_string()))
+        .map_err(|e| {
+            error!("serialising key log: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let signature_hex =
+        sign_with_publisher_ca(&key_log, &state.publisher_ca_key_pem).map_err(|e| {
+            error!("{e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(SignedKeyLog {
+        key_log,
+        signature_hex,
+        signer_cert_pem: state.publisher_ca_cert_pem.clone(),
+    }))
+}
@@ -0,0 +1,491 @@
+//! GET  /v1/namespaces/:ns/keys — the namespace's active signing keys.
+//! POST /v1/namespaces/:ns/keys — submit a signed key-set change.
+//! GET  /v1/namespaces/:ns/keys/confirm — confirm it via email token.
+//! GET  /v1/namespaces/:ns/key-changes — confirmed changes, oldest first.
+//!
+//! A change adds or removes one key. It must be signed by an unexpired key
+//! already in the set and confirmed by the namespace owner by email, like a
+//! key rotation.
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use skreg_core::keyset::{KeyChangeAction, KeyChangeRecord, KeyChangeToken, NamespaceKey};
+use skreg_core::types::Sha256Digest;
+
+use super::db_error;
+use super::publish::spki_fingerprint;
+use super::rotate::{
+    check_nonce_unused, key_set, may_sign, send_confirmation_email, validate_token_window,
+    verify_sig_from_cert, ConfirmQuery, ConfirmResponse,
+};
+use crate::middleware::{extract_bearer, resolve_namespace};
+use crate::router::{AppState, SharedState};
+
+/// Maximum length of a key label.
+const MAX_LABEL_LEN: usize = 64;
+
+/// Maximum key-set changes a namespace may submit per 24 hours.
+const MAX_PENDING_PER_DAY: i64 = 5;
+
+/// Request body for `POST /v1/namespaces/:ns/keys`.
+#[derive(Debug, Deserialize)]
+pub struct KeyChangeRequest {
+    /// The change.
+    pub token: KeyChangeToken,
+    /// Hex signature over `sha256(token.signing_bytes())` by the key
+    /// `token.signer_fingerprint`.
+    pub signature: String,
+    /// PEM certificate of the signing key.
+    pub signer_cert_pem: String,
+}
+
+/// Response body for `POST /v1/namespaces/:ns/keys`.
+#[derive(Debug, Serialize)]
+pub struct KeyChangeResponse {
+    /// Human-readable status message instructing the user to check their email.
+    pub message: String,
+}
+
+/// Check the change against the key set and its signature.
+async fn validate_key_change(
+    state: &AppState,
+    ns_id: uuid::Uuid,
+    body: &KeyChangeRequest,
+) -> Result<(), StatusCode> {
+    let token = &body.token;
+
+    let pending_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM pending_key_changes
+         WHERE namespace_id = $1 AND created_at > now() - INTERVAL '24 hours'",
+    )
+    .bind(ns_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(db_error("rate-limit check"))?;
+    if pending_count >= MAX_PENDING_PER_DAY {
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    check_nonce_unused(state, &token.nonce).await?;
+
+    if spki_fingerprint(&body.signer_cert_pem)? != token.signer_fingerprint {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let keys = key_set(&state.pool, ns_id).await?;
+    if !may_sign(&keys, &token.signer_fingerprint) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let in_set = keys.iter().any(|(key, _)| key == &token.fingerprint);
+    match token.action {
+        KeyChangeAction::Add => {
+            let cert_pem = token
+                .cert_pem
+                .as_deref()
+                .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+            if spki_fingerprint(cert_pem)? != token.fingerprint {
+                return Err(StatusCode::UNPROCESSABLE_ENTITY);
+            }
+            let label = token.label.as_deref().unwrap_or_default();
+            if label.trim().is_empty() || label.len() > MAX_LABEL_LEN {
+                return Err(StatusCode::UNPROCESSABLE_ENTITY);
+            }
+            if let Some(expires_at) = &token.key_expires_at {
+                if parse_time(expires_at)? <= Utc::now() {
+                    return Err(StatusCode::UNPROCESSABLE_ENTITY);
+                }
+            }
+            if in_set {
+                return Err(StatusCode::CONFLICT);
+            }
+        }
+        KeyChangeAction::Remove => {
+            if !in_set {
+                return Err(StatusCode::NOT_FOUND);
+            }
+            // Never leave the namespace without a key that can sign.
+            if !keys
+                .iter()
+                .any(|(key, expired)| key != &token.fingerprint && !expired)
+            {
+                return Err(StatusCode::UNPROCESSABLE_ENTITY);
+            }
+        }
+    }
+
+    let token_bytes = token.signing_bytes().map_err(|e| {
+        error!("key change signing bytes: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    verify_sig_from_cert(
+        &body.signer_cert_pem,
+        &body.signature,
+        &Sha256Digest::of(&token_bytes),
+    )
+}
+
+fn parse_time(raw: &str) -> Result<DateTime<Utc>, StatusCode> {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)
+}
+
+/// Persist the validated change: insert nonce, pending row, and audit log.
+///
+/// Returns the `confirm_token`.
+async fn persist_key_change(
+    state: &AppState,
+    ns_id: uuid::Uuid,
+    body: &KeyChangeRequest,
+) -> Result<String, StatusCode> {
+    let token = &body.token;
+    sqlx::query("INSERT INTO rotation_nonces (nonce, expires_at) VALUES ($1, $2)")
+        .bind(&token.nonce)
+        .bind(parse_time(&token.expires_at)?)
+        .execute(&state.pool)
+        .await
+        .map_err(db_error("insert nonce"))?;
+
+    let confirm_token = hex::encode(rand::random::<[u8; 32]>());
+    let token_json = serde_json::to_value(token).map_err(|e| {
+        error!("serializing key change: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    sqlx::query(
+        "INSERT INTO pending_key_changes
+             (namespace_id, change_token, signature, signer_cert_pem, confirm_token, expires_at)
+         VALUES ($1, $2, $3, $4, $5, now() + INTERVAL '24 hours')",
+    )
+    .bind(ns_id)
+    .bind(&token_json)
+    .bind(&body.signature)
+    .bind(&body.signer_cert_pem)
+    .bind(&confirm_token)
+    .execute(&state.pool)
+    .await
+    .map_err(db_error("insert pending_key_change"))?;
+
+    sqlx::query(
+        "INSERT INTO pki_audit_log (namespace_id, operation, outcome, detail)
+         VALUES ($1, 'key_change_submit', 'success', $2)",
+    )
+    .bind(ns_id)
+    .bind(&token_json)
+    .execute(&state.pool)
+    .await
+    .map_err(db_error("audit log"))?;
+
+    Ok(confirm_token)
+}
+
+/// Handle `GET /v1/namespaces/:ns/keys`.
+///
+/// Returns the keys allowed to sign for `:ns`, including expired ones,
+/// oldest first. An unknown namespace has no keys.
+///
+/// # Errors
+///
+/// - `500` — database error
+pub async fn keys_handler(
+    State(state): State<SharedState>,
+    Path(ns): Path<String>,
+) -> Result<Json<Vec<NamespaceKey>>, StatusCode> {
+    type Row = (
+        String,
+        String,
+        Option<String>,
+        DateTime<Utc>,
+        Option<DateTime<Utc>>,
+        Option<String>,
+    );
+    let rows = sqlx::query_as::<_, Row>(
+        "SELECT k.fingerprint, k.label, k.added_by, k.added_at, k.expires_at, k.cert_pem
+         FROM namespace_keys k
+         JOIN namespaces n ON n.id = k.namespace_id
+         WHERE n.slug = $1 AND k.removed_at IS NULL
+         ORDER BY k.added_at, k.id",
+    )
+    .bind(&ns)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(db_error("fetch namespace_keys"))?;
+
+    Ok(Json(
+        rows.into_iter()
+            .map(
+                |(fingerprint, label, added_by, added_at, expires_at, cert_pem)| NamespaceKey {
+                    fingerprint,
+                    label,
+                    added_by,
+                    added_at: added_at.to_rfc3339(),
+                    expires_at: expires_at.map(|t| t.to_rfc3339()),
+                    cert_pem,
+                },
+            )
+            .collect(),
+    ))
+}
+
+/// Handle `POST /v1/namespaces/:ns/keys`.
+///
+/// Validates a key-set change (auth, time bounds, rate limit, nonce, signer
+/// in the key set, signature), stores a `pending_key_changes` row, and sends
+/// a confirmation email.
+///
+/// # Errors
+///
+/// - `401` — missing/invalid Bearer token or bad signature
+/// - `403` — token namespace does not match `:ns`, or the signer is not an
+///   unexpired key of the namespace
+/// - `404` — removing a key that is not in the set
+/// - `409` — nonce already used, or adding a key already in the set
+/// - `422` — malformed token or cert, or removing the last key that can sign
+/// - `429` — rate limit exceeded
+/// - `500` — database error
+/// - `503` — the confirmation email could not be sent
+pub async fn key_change_submit_handler(
+    State(state): State<SharedState>,
+    Path(ns): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<KeyChangeRequest>,
+) -> Result<Json<KeyChangeResponse>, StatusCode> {
+    let auth = headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let raw_key = extract_bearer(auth).ok_or(StatusCode::UNAUTHORIZED)?;
+    let (ns_id, ns_slug) = resolve_namespace(&state.pool, &raw_key).await?;
+
+    if ns_slug != ns {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let token = &body.token;
+    validate_token_window(
+        &token.namespace,
+        &token.issued_at,
+        &token.expires_at,
+        &ns_slug,
+    )?;
+    validate_key_change(&state, ns_id, &body).await?;
+    let confirm_token = persist_key_change(&state, ns_id, &body).await?;
+
+    if let Err(e) = send_confirmation_email(
+        &state,
+        ns_id,
+        &ns_slug,
+        "signing key change",
+        "keys/confirm",
+        &confirm_token,
+    )
+    .await
+    {
+        error!("send_confirmation_email failed: {e:?}");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    Ok(Json(KeyChangeResponse {
+        message: "Check your email to confirm the key change.".to_owned(),
+    }))
+}
+
+/// Apply a confirmed change to the key set of `ns_id`.
+///
+/// The namespace row is locked first, so confirmations of the same
+/// namespace apply one at a time, each to the key set the others left. The
+/// key that signed the change must still be in that set and unexpired.
+async fn apply_key_change(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ns_id: uuid::Uuid,
+    token: &KeyChangeToken,
+) -> Result<(), StatusCode> {
+    sqlx::query("SELECT 1 FROM namespaces WHERE id = $1 FOR UPDATE")
+        .bind(ns_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error("lock namespace"))?;
+    if !may_sign(&key_set(&mut **tx, ns_id).await?, &token.signer_fingerprint) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match token.action {
+        KeyChangeAction::Add => {
+            let expires_at = token
+                .key_expires_at
+                .as_deref()
+                .map(parse_time)
+                .transpose()?;
+            sqlx::query(
+                "INSERT INTO namespace_keys
+                     (namespace_id, fingerprint, label, cert_pem, added_by, expires_at)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(ns_id)
+            .bind(&token.fingerprint)
+            .bind(token.label.as_deref().unwrap_or_default())
+            .bind(&token.cert_pem)
+            .bind(&token.signer_fingerprint)
+            .bind(expires_at)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(ref db) if db.is_unique_violation() => StatusCode::CONFLICT,
+                e => db_error("insert namespace_key")(e),
+            })?;
+        }
+        KeyChangeAction::Remove => {
+            sqlx::query(
+                "UPDATE namespace_keys SET removed_at = now()
+                 WHERE namespace_id = $1 AND fingerprint = $2 AND removed_at IS NULL",
+            )
+            .bind(ns_id)
+            .bind(&token.fingerprint)
+            .execute(&mut **tx)
+            .await
+            .map_err(db_error("remove namespace_key"))?;
+
+            // Another removal may have been confirmed since this one was
+            // submitted.
+            let remaining: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM namespace_keys
+                 WHERE namespace_id = $1 AND removed_at IS NULL
+                   AND (expires_at IS NULL OR expires_at > now())",
+            )
+            .bind(ns_id)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(db_error("count namespace_keys"))?;
+            if remaining == 0 {
+                return Err(StatusCode::CONFLICT);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handle `GET /v1/namespaces/:ns/keys/confirm?token=...`.
+///
+/// Looks up the pending change by `confirm_token`, verifies it is not
+/// expired and belongs to `:ns`, then in a transaction applies it to the key
+/// set, records it in `key_changes` and deletes the pending row.
+///
+/// # Errors
+///
+/// - `404` — confirm token not found or expired
+/// - `403` — token belongs to a different namespace, or the key that signed
+///   the change was removed or expired since it was submitted
+/// - `409` — the key was added meanwhile, or removing it would leave no key
+///   that can sign
+/// - `500` — database error
+pub async fn key_change_confirm_handler(
+    State(state): State<SharedState>,
+    Path(ns): Path<String>,
+    Query(params): Query<ConfirmQuery>,
+) -> Result<Json<ConfirmResponse>, StatusCode> {
+    let (ns_id, row_ns_slug, token_json) =
+        sqlx::query_as::<_, (uuid::Uuid, String, serde_json::Value)>(
+            "SELECT pk.namespace_id, n.slug, pk.change_token
+             FROM pending_key_changes pk
+             JOIN namespaces n ON n.id = pk.namespace_id
+             WHERE pk.confirm_token = $1 AND pk.expires_at > now()",
+        )
+        .bind(&params.token)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(db_error("fetch pending_key_change"))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if row_ns_slug != ns {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let token = serde_json::from_value::<KeyChangeToken>(token_json).map_err(|e| {
+        error!("parsing stored key change: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut tx = state.pool.begin().await.map_err(db_error("begin tx"))?;
+    apply_key_change(&mut tx, ns_id, &token).await?;
+
+    sqlx::query(
+        "INSERT INTO key_changes (namespace_id, change_token, signature, signer_cert_pem)
+         SELECT namespace_id, change_token, signature, signer_cert_pem
+         FROM pending_key_changes WHERE confirm_token = $1",
+    )
+    .bind(&params.token)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error("insert key_change"))?;
+
+    sqlx::query("DELETE FROM pending_key_changes WHERE confirm_token = $1")
+        .bind(&params.token)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error("delete pending_key_change"))?;
+
+    tx.commit().await.map_err(db_error("commit key change"))?;
+
+    // Audit log (after commit — best-effort).
+    let _ = sqlx::query(
+        "INSERT INTO pki_audit_log (namespace_id, operation, outcome, detail)
+         VALUES ($1, 'key_change_confirm', 'success', $2)",
+    )
+    .bind(ns_id)
+    .bind(serde_json::json!({ "namespace": ns, "fingerprint": token.fingerprint }))
+    .execute(&state.pool)
+    .await;
+
+    let message = match token.action {
+        KeyChangeAction::Add => "Key added. It can now sign for the namespace.",
+        KeyChangeAction::Remove => "Key removed. It can no longer sign for the namespace.",
+    };
+    Ok(Json(ConfirmResponse {
+        message: message.to_owned(),
+    }))
+}
+
+/// Handle `GET /v1/namespaces/:ns/key-changes`.
+///
+/// Returns every confirmed key-set change of `:ns`, oldest first, with its
+/// signature, so clients that pinned one key can check that another was
+/// added by the holder of a key they trust. An unknown namespace has none.
+///
+/// # Errors
+///
+/// - `500` — database error or a stored change that no longer parses
+pub async fn key_changes_handler(
+    State(state): State<SharedState>,
+    Path(ns): Path<String>,
+) -> Result<Json<Vec<KeyChangeRecord>>, StatusCode> {
+    let rows = sqlx::query_as::<_, (serde_json::Value, String, String, DateTime<Utc>)>(
+        "SELECT kc.change_token, kc.signature, kc.signer_cert_pem, kc.confirmed_at
+         FROM key_changes kc
+         JOIN namespaces n ON n.id = kc.namespace_id
+         WHERE n.slug = $1
+         ORDER BY kc.confirmed_at, kc.id",
+    )
+    .bind(&ns)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(db_error("fetch key_changes"))?;
+
+    let records = rows
+        .into_iter()
+        .map(|(token_json, signature, signer_cert_pem, confirmed_at)| {
+            let token = serde_json::from_value::<KeyChangeToken>(token_json).map_err(|e| {
+                error!("parsing stored key change: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            Ok(KeyChangeRecord {
+                token,
+                signature,
+                signer_cert_pem,
+                confirmed_at: confirmed_at.to_rfc3339(),
+            })
+        })
+        .collect::<Result<Vec<_>, StatusCode>>()?;
+    Ok(Json(records))
+}
@@ -0,0 +1,246 @@
+//! GET /v1/log/tree-head — signed head of the transparency log.
+//! GET /v1/log/entries — log entries by index.
+//! GET /v1/log/proof/inclusion — audit path for one entry.
+//! GET /v1/log/proof/consistency — proof that an older tree is a prefix.
+//!
+//! Entries are appended by the database (`append_log_entry()`, see migrations
+//! 012 and 017) and by the worker; these handlers only read. The database
+//! stores each entry's leaf hash, and the tree kept in [`AppState`] is only
+//! extended with the entries appended since it was last used.
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::Deserialize;
+use skreg_core::transparency::{
+    ConsistencyProof, InclusionProof, LogEntry, SignedTreeHead, TreeHead,
+};
+use skreg_core::types::Sha256Digest;
+use skreg_crypto::merkle::MerkleTree;
+use tokio::sync::MutexGuard;
+
+use super::advisories::sign_with_publisher_ca;
+use crate::router::{AppState, SharedState};
+
+/// Maximum entries returned by one `GET /v1/log/entries` request.
+const MAX_ENTRIES: u64 = 1000;
+
+/// Query parameters for `GET /v1/log/entries`.
+#[derive(Debug, Deserialize)]
+pub struct EntriesQuery {
+    /// Index of the first entry to return.
+    pub start: u64,
+    /// Index after the last entry to return (default: up to 1000 entries).
+    pub end: Option<u64>,
+}
+
+/// Query parameters for `GET /v1/log/proof/inclusion`.
+#[derive(Debug, Deserialize)]
+pub struct InclusionQuery {
+    /// Index of the entry.
+    pub index: u64,
+    /// Size of the tree to prove inclusion in.
+    pub tree_size: u64,
+}
+
+/// Query parameters for `GET /v1/log/proof/consistency`.
+#[derive(Debug, Deserialize)]
+pub struct ConsistencyQuery {
+    /// Size of the older tree.
+    pub first: u64,
+    /// Size of the newer tree.
+    pub second: u64,
+}
+
+/// Load entries `start..end` in index order.
+async fn load_entries(state: &AppState, start: u64, end: u64) -> Result<Vec<LogEntry>, StatusCode> {
+    let to_i64 = |n: u64| i64::try_from(n).map_err(|_| StatusCode::BAD_REQUEST);
+    let rows: Vec<serde_json::Value> = sqlx::query_scalar(
+        "SELECT entry FROM transparency_log WHERE idx >= $1 AND idx < $2 ORDER BY idx",
+    )
+    .bind(to_i64(start)?)
+    .bind(to_i64(end)?)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db fetch transparency_log: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    rows.into_iter()
+        .map(|entry| {
+            serde_json::from_value(entry).map_err(|e| {
+                error!("parsing stored log entry: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })
+        })
+        .collect()
+}
+
+/// The log's Merkle tree, extended with every entry appended since it was
+/// last used.
+async fn log_tree(state: &AppState) -> Result<MutexGuard<'_, MerkleTree>, StatusCode> {
+    let mut tree = state.log_tree.lock().await;
+    let known = i64::try_from(tree.len()).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let rows: Vec<(i64, String)> =
+        sqlx::query_as("SELECT idx, leaf_hash FROM transparency_log WHERE idx >= $1 ORDER BY idx")
+            .bind(known)
+            .fetch_all(&state.pool)
+            .await
+            .map_err(|e| {
+                error!("db fetch transparency_log leaf hashes: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    for (idx, leaf_hash) in rows {
+        if u64::try_from(idx).ok() != Some(tree.len()) {
+            error!(
+                "transparency_log has entry {idx} after {} entries",
+                tree.len()
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        let leaf = Sha256Digest::from_hex(&leaf_hash).map_err(|e| {
+            error!("stored leaf hash of entry {idx}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        tree.push(&leaf).map_err(|e| proof_error(&e))?;
+    }
+    Ok(tree)
+}
+
+/// `400` unless the log has at least `tree_size` entries.
+fn check_size(tree: &MerkleTree, tree_size: u64) -> Result<(), StatusCode> {
+    if tree_size > tree.len() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+/// When the first entry was logged, if the log has any.
+async fn log_start(state: &AppState) -> Result<Option<DateTime<Utc>>, StatusCode> {
+    sqlx::query_scalar("SELECT logged_at FROM transparency_log WHERE idx = 0")
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| {
+            error!("db fetch transparency_log start: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+fn proof_error(e: &skreg_crypto::error::VerifyError) -> StatusCode {
+    error!("building log proof: {e}");
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// Handle `GET /v1/log/tree-head`.
+///
+/// Returns the current size and root hash of the log and when it started,
+/// signed by the Publisher CA.
+///
+/// # Errors
+///
+/// Returns `500` on a database or signing error.
+pub async fn tree_head_handler(
+    State(state): State<SharedState>,
+) -> Result<Json<SignedTreeHead>, StatusCode> {
+    let (tree_size, root_hash) = {
+        let tree = log_tree(&state).await?;
+        let root_hash = tree.root(tree.len()).map_err(|e| proof_error(&e))?;
+        (tree.len(), root_hash)
+    };
+    // Read after the tree, so a non-empty tree always has a start.
+    let log_start = log_start(&state).await?;
+
+    let tree_head = serde_json::to_string(&TreeHead {
+        tree_size,
+        root_hash,
+        timestamp: Utc::now(),
+        log_start,
+    })
+    .map_err(|e| {
+        error!("serialising tree head: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let signature_hex =
+        sign_with_publisher_ca(&tree_head, &state.publisher_ca_key_pem).map_err(|e| {
+            error!("{e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(SignedTreeHead {
+        tree_head,
+        signature_hex,
+        signer_cert_pem: state.publisher_ca_cert_pem.clone(),
+    }))
+}
+
+/// Handle `GET /v1/log/entries?start=<index>&end=<index>`.
+///
+/// Returns entries `start..end` in index order, at most 1000, so auditors
+/// can replay the log and rebuild its tree.
+///
+/// # Errors
+///
+/// Returns `400` if `end` is before `start`, or `500` on a database error.
+pub async fn entries_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<EntriesQuery>,
+) -> Result<Json<Vec<LogEntry>>, StatusCode> {
+    let limit = params.start.saturating_add(MAX_ENTRIES);
+    let end = params.end.map_or(limit, |end| end.min(limit));
+    if end < params.start {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(Json(load_entries(&state, params.start, end).await?))
+}
+
+/// Handle `GET /v1/log/proof/inclusion?index=<index>&tree_size=<size>`.
+///
+/// # Errors
+///
+/// Returns `400` if `index` is not below `tree_size` or the log has fewer
+/// than `tree_size` entries, or `500` on a database error.
+pub async fn inclusion_proof_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<InclusionQuery>,
+) -> Result<Json<InclusionProof>, StatusCode> {
+    if params.index >= params.tree_size {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let tree = log_tree(&state).await?;
+    check_size(&tree, params.tree_size)?;
+    let audit_path = tree
+        .inclusion_proof(params.index, params.tree_size)
+        .map_err(|e| proof_error(&e))?;
+    Ok(Json(InclusionProof {
+        leaf_index: params.index,
+        tree_size: params.tree_size,
+        audit_path,
+    }))
+}
+
+/// Handle `GET /v1/log/proof/consistency?first=<size>&second=<size>`.
+///
+/// # Errors
+///
+/// Returns `400` if `first` exceeds `second` or the log has fewer than
+/// `second` entries, or `500` on a database error.
+pub async fn consistency_proof_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<ConsistencyQuery>,
+) -> Result<Json<ConsistencyProof>, StatusCode> {
+    if params.first > params.second {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let tree = log_tree(&state).await?;
+    check_size(&tree, params.second)?;
+    let proof = tree
+        .consistency_proof(params.first, params.second)
+        .map_err(|e| proof_error(&e))?;
+    Ok(Json(ConsistencyProof {
+        first: params.first,
+        second: params.second,
+        proof,
+    }))
+}
@@ -1,11 +1,28 @@
 //! HTTP request handlers.
 
+pub mod admin;
+pub mod advisories;
 pub mod auth;
 pub mod cert;
+pub mod certs;
 pub mod jobs;
+pub mod key_log;
+pub mod keys;
+pub mod log;
 pub mod namespaces;
 pub mod packages;
 pub mod preview;
 pub mod publish;
 pub mod rotate;
+pub mod rotations;
 pub mod search;
+
+use axum::http::StatusCode;
+
+/// Map a database error to `500`, logging it with `context`.
+pub(crate) fn db_error(context: &str) -> impl FnOnce(sqlx::Error) -> StatusCode + '_ {
+    move |e| {
+        ::log::error!("db {context}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
@@ -3,8 +3,10 @@
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::Json;
-use log::error;
+use log::{error, info};
 use serde::{Deserialize, Serialize};
+use skreg_core::names::{classify, NameMatch};
+use sqlx::PgPool;
 
 use crate::auth::{generate_api_key, hash_secret};
 use crate::router::SharedState;
@@ -37,12 +39,52 @@ pub fn is_valid_slug(slug: &str) -> bool {
             .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
 }
 
+/// Find an existing namespace that `slug` would impersonate.
+///
+/// Verified organisations (an `org` namespace holding an unrevoked publisher
+/// certificate) are protected against any close match; other namespaces only
+/// against confusable or reordered slugs. Admin-allowlisted pairs are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn find_similar_namespace(
+    pool: &PgPool,
+    slug: &str,
+) -> Result<Option<(String, NameMatch)>, sqlx::Error> {
+    let candidates: Vec<(String, bool)> = sqlx::query_as(
+        "SELECT n.slug,
+                (n.kind = 'org' AND EXISTS (
+                    SELECT 1 FROM publisher_certs pc
+                    WHERE pc.namespace_id = n.id AND pc.revoked_at IS NULL))
+         FROM namespaces n
+         WHERE n.banned_at IS NULL
+           AND (n.slug % $1
+                OR name_skeleton(n.slug) = name_skeleton($1)
+                OR name_segment_key(n.slug) = name_segment_key($1))
+           AND NOT EXISTS (
+                SELECT 1 FROM name_allowlist a
+                WHERE a.kind = 'namespace'
+                  AND ((a.name = $1 AND a.similar_to = n.slug)
+                    OR (a.similar_to = $1 AND a.name = n.slug)))
+         ORDER BY similarity(n.slug, $1) DESC
+         LIMIT 50",
+    )
+    .bind(slug)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(candidates.into_iter().find_map(|(existing, verified)| {
+        classify(slug, &existing, verified).map(|m| (existing, m))
+    }))
+}
+
 /// Handle `POST /v1/namespaces` — register a new namespace and return a plaintext API key.
 ///
 /// # Errors
 ///
-/// Returns `422` if the slug is invalid, `409` if the slug is already taken,
-/// or `500` on a database error.
+/// Returns `422` if the slug is invalid, `409` if the slug is already taken
+/// or too similar to an existing namespace, or `500` on a database error.
 pub async fn create_namespace_handler(
     State(state): State<SharedState>,
     Json(body): Json<CreateNamespaceRequest>,
@@ -53,6 +95,20 @@ pub async fn create_namespace_handler(
 
     let pool = &state.pool;
 
+    let similar = find_similar_namespace(pool, &body.slug)
+        .await
+        .map_err(|e| {
+            error!("db error checking namespace similarity: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if let Some((existing, reason)) = similar {
+        info!(
+            "rejected namespace '{}': too similar to '{existing}' ({reason})",
+            body.slug
+        );
+        return Err(StatusCode::CONFLICT);
+    }
+
     // Insert namespace (409 if slug taken)
     let ns_id = sqlx::query_scalar::<_, uuid::Uuid>(
         "INSERT INTO namespaces (slug, kind) VALUES ($1, 'individual')
@@ -1,14 +1,17 @@
 //! GET /v1/packages/:ns/:name/:version — package metadata
-//! GET /v1/download/:ns/:name/:version — tarball download
+//! GET /v1/download/:ns/:name/:version — tarball download, gzip or zstd per `Accept`
 //! GET /v1/download/:ns/:name/:version/sig — signature download
 
 use axum::body::Bytes;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use log::error;
 use serde::Serialize;
+use skreg_core::timestamp::SignedTimestamp;
 use skreg_core::types::{Namespace, PackageName};
+use skreg_pack::format::ArchiveFormat;
 
 use crate::router::{AppState, SharedState};
 
@@ -18,13 +21,19 @@ pub(crate) struct VersionRow {
     pub(crate) version: String,
     pub(crate) sha256: String,
     pub(crate) storage_path: String,
+    pub(crate) zstd_sha256: Option<String>,
+    pub(crate) zstd_storage_path: Option<String>,
     pub(crate) sig_path: String,
+    pub(crate) signed_timestamp: Option<sqlx::types::Json<SignedTimestamp>>,
+    pub(crate) log_index: Option<i64>,
+    pub(crate) legacy_signature: bool,
     pub(crate) description: String,
     pub(crate) category: Option<String>,
 }
 
 /// Response body for the package metadata endpoint.
-/// Field names must match `skreg_core::manifest::Manifest` exactly.
+/// Field names must match `skreg_core::manifest::Manifest` exactly; fields
+/// `Manifest` lacks are ignored by clients that deserialize it directly.
 #[derive(Debug, Serialize)]
 pub struct ManifestResponse {
     /// Publisher namespace slug.
@@ -37,10 +46,22 @@ pub struct ManifestResponse {
     pub description: String,
     /// Optional category tag.
     pub category: Option<String>,
-    /// SHA-256 hex digest of the tarball.
+    /// SHA-256 hex digest of the gzip tarball.
     pub sha256: String,
+    /// SHA-256 hex digest of the Zstandard tarball, if one is stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zstd_sha256: Option<String>,
     /// PEM-encoded certificate chain. Empty for registry-signed packages.
     pub cert_chain_pem: Vec<String>,
+    /// Registry timestamp of the publisher signature, recorded at vetting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<SignedTimestamp>,
+    /// Index of the version's publish entry in the transparency log.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_index: Option<i64>,
+    /// Whether the version was signed under the legacy scheme, before
+    /// domain-separated file-list signatures.
+    pub legacy_signature: bool,
 }
 
 /// Validate a version segment: "latest" or alphanumeric + `.`, `-`, `+`, max 32 chars.
@@ -65,16 +86,19 @@ pub(crate) async fn resolve_version_row(
 ) -> Result<VersionRow, StatusCode> {
     let row = if version == "latest" {
         sqlx::query_as::<_, VersionRow>(
-            "SELECT v.version, v.sha256, v.storage_path, v.sig_path,
-                    p.description, p.category
+            "SELECT v.version, v.sha256, v.storage_path, v.zstd_sha256,
+                    v.zstd_storage_path, v.sig_path, v.signed_timestamp, v.log_index,
+                    v.legacy_signature, p.description, p.category
              FROM versions v
              JOIN packages p ON p.id = v.package_id
              JOIN namespaces n ON n.id = p.namespace_id
              JOIN vetting_jobs j ON j.version_id = v.id
              WHERE n.slug = $1
                AND p.name = $2
+               AND j.kind = 'publish'
                AND j.status = 'pass'
                AND v.yanked_at IS NULL
+               AND v.quarantined_at IS NULL
                AND n.banned_at IS NULL
              ORDER BY v.published_at DESC, v.id DESC
              LIMIT 1",
@@ -85,8 +109,9 @@ pub(crate) async fn resolve_version_row(
         .await
     } else {
         sqlx::query_as::<_, VersionRow>(
-            "SELECT v.version, v.sha256, v.storage_path, v.sig_path,
-                    p.description, p.category
+            "SELECT v.version, v.sha256, v.storage_path, v.zstd_sha256,
+                    v.zstd_storage_path, v.sig_path, v.signed_timestamp, v.log_index,
+                    v.legacy_signature, p.description, p.category
              FROM versions v
              JOIN packages p ON p.id = v.package_id
              JOIN namespaces n ON n.id = p.namespace_id
@@ -94,6 +119,7 @@ pub(crate) async fn resolve_version_row(
                AND p.name = $2
                AND v.version = $3
                AND v.yanked_at IS NULL
+               AND v.quarantined_at IS NULL
                AND n.banned_at IS NULL",
         )
         .bind(ns)
@@ -138,12 +164,20 @@ pub async fn package_meta_handler(
         description: row.description,
         category: row.category,
         sha256: row.sha256,
+        zstd_sha256: row.zstd_sha256,
         cert_chain_pem: vec![],
+        timestamp: row.signed_timestamp.map(|ts| ts.0),
+        log_index: row.log_index,
+        legacy_signature: row.legacy_signature,
     }))
 }
 
 /// Handle `GET /v1/download/:ns/:name/:version` — return tarball bytes.
 ///
+/// The Zstandard copy is served when `Accept` prefers `application/zstd` and
+/// one is stored; otherwise the gzip tarball is. `Content-Type` names the
+/// format served.
+///
 /// # Errors
 ///
 /// Returns `400` for invalid namespace, name, or version. Returns `404` if the
@@ -151,7 +185,8 @@ pub async fn package_meta_handler(
 pub async fn package_download_handler(
     State(state): State<SharedState>,
     Path((ns_raw, name_raw, version_raw)): Path<(String, String, String)>,
-) -> Result<Bytes, StatusCode> {
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
     let ns = Namespace::new(&ns_raw).map_err(|_| StatusCode::BAD_REQUEST)?;
     let pkg_name = PackageName::new(&name_raw).map_err(|_| StatusCode::BAD_REQUEST)?;
     if !validate_version(&version_raw) {
@@ -160,11 +195,17 @@ pub async fn package_download_handler(
 
     let row = resolve_version_row(&state, ns.as_str(), pkg_name.as_str(), &version_raw).await?;
 
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let (key, format) = match (ArchiveFormat::negotiate(accept), row.zstd_storage_path) {
+        (ArchiveFormat::Zstd, Some(path)) => (path, ArchiveFormat::Zstd),
+        _ => (row.storage_path, ArchiveFormat::Gzip),
+    };
+
     let obj = state
         .s3
         .get_object()
         .bucket(&state.s3_bucket)
-        .key(&row.storage_path)
+        .key(&key)
         .send()
         .await
         .map_err(|e| {
@@ -177,7 +218,14 @@ pub async fn package_download_handler(
         StatusCode::SERVICE_UNAVAILABLE
     })?;
 
-    Ok(data.into_bytes())
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.media_type()),
+            (header::VARY, "accept"),
+        ],
+        data.into_bytes(),
+    )
+        .into_response())
 }
 
 /// Handle `GET /v1/download/:ns/:name/:version/sig` — return signature bytes.
@@ -1,4 +1,7 @@
 //! POST /v1/publish — accept a .skill tarball, validate, store to S3, enqueue vetting.
+//!
+//! Uploads may be gzip or Zstandard. Every version is stored in both formats;
+//! the gzip copy is the one recorded in `versions.sha256`.
 
 use axum::body::Bytes;
 use axum::extract::State;
@@ -8,10 +11,15 @@ use log::error;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 use skreg_core::manifest::Manifest;
+use skreg_crypto::verifier::MAX_CHAIN_LEN;
+use skreg_pack::error::PackError;
+use skreg_pack::format::{transcode, ArchiveFormat};
+use skreg_pack::reproducible::verify_contents;
 use skreg_pack::unpack::unpack_to_tempdir;
 use x509_cert::der::{DecodePem, Encode};
 use x509_cert::Certificate;
 
+use crate::handlers::rotate::{key_set, may_sign};
 use crate::middleware::resolve_namespace;
 use crate::router::{AppState, SharedState};
 
@@ -32,9 +40,9 @@ pub fn make_storage_path(ns: &str, name: &str, version: &str, sha256: &str) -> S
     format!("{ns}/{name}/{version}/{sha256}.skill")
 }
 
-/// Validate `cert_chain_pem` length (1 or 2) and total size.
+/// Validate `cert_chain_pem` length (1 to [`MAX_CHAIN_LEN`]) and total size.
 pub(crate) fn validate_cert_chain(chain: &[String]) -> Result<(), StatusCode> {
-    if chain.is_empty() || chain.len() > 2 {
+    if chain.is_empty() || chain.len() > MAX_CHAIN_LEN {
         return Err(StatusCode::BAD_REQUEST);
     }
     let total: usize = chain.iter().map(String::len).sum();
@@ -55,12 +63,34 @@ pub(crate) fn spki_fingerprint(cert_pem: &str) -> Result<String, StatusCode> {
     Ok(hex::encode(Sha256::digest(&spki_der)))
 }
 
-/// Compute sha256, unpack, parse and validate manifest ownership and integrity.
-fn validate_manifest(body: &Bytes, ns_slug: &str) -> Result<(Manifest, String), StatusCode> {
-    let sha256 = hex::encode(Sha256::digest(body));
+/// Run CPU-bound archive work (unpacking, rebuilding, compressing) on the
+/// blocking thread pool, so a large upload does not stall the runtime.
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, StatusCode> + Send + 'static,
+) -> Result<T, StatusCode> {
+    tokio::task::spawn_blocking(work).await.map_err(|e| {
+        error!("blocking archive task: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+}
 
+/// Unpack, parse and validate manifest ownership and integrity.
+fn validate_manifest(body: &Bytes, ns_slug: &str) -> Result<Manifest, StatusCode> {
     let tmp = unpack_to_tempdir(body).map_err(|e| {
         error!("unpack: {e}");
+        match e {
+            PackError::TooManyFiles { .. }
+            | PackError::FileTooLarge { .. }
+            | PackError::PackageTooLarge { .. }
+            | PackError::DecompressedTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    })?;
+    // Reject archives holding anything packing their own contents would not
+    // write: stray entries, or files whose mode or bytes differ. Timestamps,
+    // ordering and compression settings of other packers are accepted.
+    verify_contents(body, tmp.path()).map_err(|e| {
+        error!("reproducibility: {e}");
         StatusCode::UNPROCESSABLE_ENTITY
     })?;
     let manifest_bytes = std::fs::read(tmp.path().join("manifest.json"))
@@ -74,7 +104,82 @@ fn validate_manifest(body: &Bytes, ns_slug: &str) -> Result<(Manifest, String),
         return Err(StatusCode::FORBIDDEN);
     }
 
-    Ok((manifest, sha256))
+    Ok(manifest)
+}
+
+/// One stored encoding of a version's archive.
+struct Archive {
+    bytes: Bytes,
+    sha256: String,
+    storage_path: String,
+}
+
+impl Archive {
+    /// Re-encode a validated upload as `format`, keyed by its own digest.
+    fn encode(body: &[u8], format: ArchiveFormat, manifest: &Manifest) -> Result<Self, StatusCode> {
+        let bytes = transcode(body, format).map_err(|e| {
+            error!("transcode to {format}: {e}");
+            StatusCode::UNPROCESSABLE_ENTITY
+        })?;
+        let sha256 = hex::encode(Sha256::digest(&bytes));
+        let storage_path = make_storage_path(
+            manifest.namespace.as_str(),
+            manifest.name.as_str(),
+            &manifest.version.to_string(),
+            &sha256,
+        );
+        Ok(Self {
+            bytes: Bytes::from(bytes),
+            sha256,
+            storage_path,
+        })
+    }
+
+    async fn upload(&self, state: &AppState) -> Result<(), StatusCode> {
+        state
+            .s3
+            .put_object()
+            .bucket(&state.s3_bucket)
+            .key(&self.storage_path)
+            .body(aws_sdk_s3::primitives::ByteStream::from(self.bytes.clone()))
+            .send()
+            .await
+            .map_err(|e| {
+                error!("s3 upload: {e}");
+                StatusCode::SERVICE_UNAVAILABLE
+            })?;
+        Ok(())
+    }
+}
+
+/// Serial of the certificate the registry issued to namespace `ns_id` that
+/// is `leaf_pem`, compared by DER encoding. `None` for a certificate the
+/// registry has no record of.
+async fn issued_serial(
+    state: &AppState,
+    ns_id: uuid::Uuid,
+    leaf_pem: &str,
+) -> Result<Option<i64>, StatusCode> {
+    let der = |pem: &str| {
+        Certificate::from_pem(pem.as_bytes())
+            .ok()
+            .and_then(|cert| cert.to_der().ok())
+    };
+    let leaf = der(leaf_pem).ok_or(StatusCode::BAD_REQUEST)?;
+    let certs = sqlx::query_as::<_, (i64, String)>(
+        "SELECT serial, pem FROM publisher_certs WHERE namespace_id = $1",
+    )
+    .bind(ns_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db fetch publisher_certs: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(certs
+        .into_iter()
+        .find(|(_, pem)| der(pem).as_ref() == Some(&leaf))
+        .map(|(serial, _)| serial))
 }
 
 /// Insert package and version rows and return the `version_id`.
@@ -82,9 +187,8 @@ async fn insert_package_version(
     state: &AppState,
     ns_id: uuid::Uuid,
     manifest: &Manifest,
-    sha256: &str,
-    storage_path: &str,
-    signer: &str,
+    (gzip, zstd): (&Archive, &Archive),
+    (signer, signer_fingerprint, signer_serial): (&str, &str, Option<i64>),
 ) -> Result<uuid::Uuid, StatusCode> {
     let pkg_id = sqlx::query_scalar::<_, uuid::Uuid>(
         "INSERT INTO packages (namespace_id, name, description)
@@ -103,15 +207,21 @@ async fn insert_package_version(
     })?;
 
     sqlx::query_scalar::<_, uuid::Uuid>(
-        "INSERT INTO versions (package_id, version, sha256, storage_path, sig_path, signer)
-         VALUES ($1, $2, $3, $4, '', $5)
+        "INSERT INTO versions (package_id, version, sha256, storage_path,
+                               zstd_sha256, zstd_storage_path, sig_path, signer,
+                               signer_fingerprint, signer_serial)
+         VALUES ($1, $2, $3, $4, $5, $6, '', $7, $8, $9)
          RETURNING id",
     )
     .bind(pkg_id)
     .bind(manifest.version.to_string())
-    .bind(sha256)
-    .bind(storage_path)
+    .bind(&gzip.sha256)
+    .bind(&gzip.storage_path)
+    .bind(&zstd.sha256)
+    .bind(&zstd.storage_path)
     .bind(signer)
+    .bind(signer_fingerprint)
+    .bind(signer_serial)
     .fetch_one(&state.pool)
     .await
     .map_err(|e| {
@@ -120,31 +230,68 @@ async fn insert_package_version(
     })
 }
 
+/// Whether namespace `ns_id` has ever had a key, including removed and
+/// expired ones.
+async fn has_any_key(state: &AppState, ns_id: uuid::Uuid) -> Result<bool, StatusCode> {
+    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM namespace_keys WHERE namespace_id = $1)")
+        .bind(ns_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| {
+            error!("db: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Add `fingerprint` as the first key of namespace `ns_id`, before the
+/// worker hears of the job so the key log dates the pin before the version's
+/// signature timestamp. Returns `false` if another publish pinned a first key
+/// meanwhile (see migration 018).
+async fn pin_first_key(
+    state: &AppState,
+    ns_id: uuid::Uuid,
+    fingerprint: &str,
+    cert_pem: &str,
+) -> Result<bool, StatusCode> {
+    let pinned = sqlx::query(
+        "INSERT INTO namespace_keys (namespace_id, fingerprint, label, cert_pem)
+         VALUES ($1, $2, 'initial', $3)
+         ON CONFLICT (namespace_id) WHERE added_by IS NULL AND removed_at IS NULL
+         DO NOTHING",
+    )
+    .bind(ns_id)
+    .bind(fingerprint)
+    .bind(cert_pem)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db pin: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+    Ok(pinned == 1)
+}
+
 /// Arguments for [`persist_and_notify`] that carry publish-specific state.
 struct PublishArgs<'a> {
     ns_id: uuid::Uuid,
-    ns_slug: &'a str,
     manifest: &'a Manifest,
-    sha256: &'a str,
     body: Bytes,
     fingerprint: &'a str,
-    pinned: Option<String>,
 }
 
-/// Check version uniqueness, upload tarball to S3, persist package + version rows,
-/// insert a vetting job, and notify the worker via `pg_notify`.
+/// Check version uniqueness, upload both tarball encodings to S3, persist
+/// package + version rows, insert a vetting job, and notify the worker via
+/// `pg_notify`.
 async fn persist_and_notify(
     state: &AppState,
     args: PublishArgs<'_>,
 ) -> Result<uuid::Uuid, StatusCode> {
     let PublishArgs {
         ns_id,
-        ns_slug,
         manifest,
-        sha256,
         body,
         fingerprint,
-        pinned,
     } = args;
     let existing = sqlx::query_scalar::<_, bool>(
         "SELECT EXISTS(
@@ -167,33 +314,34 @@ async fn persist_and_notify(
         return Err(StatusCode::CONFLICT);
     }
 
-    let storage_path = make_storage_path(
-        ns_slug,
-        manifest.name.as_str(),
-        &manifest.version.to_string(),
-        sha256,
-    );
-    state
-        .s3
-        .put_object()
-        .bucket(&state.s3_bucket)
-        .key(&storage_path)
-        .body(aws_sdk_s3::primitives::ByteStream::from(body.to_vec()))
-        .send()
-        .await
-        .map_err(|e| {
-            error!("s3 upload: {e}");
-            StatusCode::SERVICE_UNAVAILABLE
-        })?;
+    let (gzip, zstd) = {
+        let manifest = manifest.clone();
+        blocking(move || {
+            Ok((
+                Archive::encode(&body, ArchiveFormat::Gzip, &manifest)?,
+                Archive::encode(&body, ArchiveFormat::Zstd, &manifest)?,
+            ))
+        })
+        .await?
+    };
+    gzip.upload(state).await?;
+    zstd.upload(state).await?;
 
-    let signer = if manifest.cert_chain_pem.len() == 1 {
-        "self_signed"
+    let (signer, signer_serial) = if manifest.cert_chain_pem.len() == 1 {
+        ("self_signed", None)
     } else {
-        "publisher"
+        let serial = issued_serial(state, ns_id, &manifest.cert_chain_pem[0]).await?;
+        ("publisher", serial)
     };
 
-    let version_id =
-        insert_package_version(state, ns_id, manifest, sha256, &storage_path, signer).await?;
+    let version_id = insert_package_version(
+        state,
+        ns_id,
+        manifest,
+        (&gzip, &zstd),
+        (signer, fingerprint, signer_serial),
+    )
+    .await?;
 
     let job_id = sqlx::query_scalar::<_, uuid::Uuid>(
         "INSERT INTO vetting_jobs (version_id) VALUES ($1) RETURNING id",
@@ -215,18 +363,6 @@ async fn persist_and_notify(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    if pinned.is_none() {
-        sqlx::query("UPDATE namespaces SET pinned_publisher_key = $1 WHERE id = $2")
-            .bind(fingerprint)
-            .bind(ns_id)
-            .execute(&state.pool)
-            .await
-            .map_err(|e| {
-                error!("db pin: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-    }
-
     Ok(job_id)
 }
 
@@ -249,37 +385,36 @@ pub async fn publish_handler(
     let raw_key = crate::middleware::extract_bearer(auth).ok_or(StatusCode::UNAUTHORIZED)?;
     let (ns_id, ns_slug) = resolve_namespace(&state.pool, &raw_key).await?;
 
-    let (manifest, sha256) = validate_manifest(&body, &ns_slug)?;
+    let manifest = {
+        let (body, ns_slug) = (body.clone(), ns_slug.clone());
+        blocking(move || validate_manifest(&body, &ns_slug)).await?
+    };
 
     validate_cert_chain(&manifest.cert_chain_pem)?;
     let fingerprint = spki_fingerprint(&manifest.cert_chain_pem[0])?;
 
-    let pinned: Option<String> =
-        sqlx::query_scalar("SELECT pinned_publisher_key FROM namespaces WHERE id = $1 FOR UPDATE")
-            .bind(ns_id)
-            .fetch_one(&state.pool)
-            .await
-            .map_err(|e| {
-                error!("db: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-    if let Some(ref pinned_fp) = pinned {
-        if pinned_fp != &fingerprint {
-            return Err(StatusCode::FORBIDDEN);
-        }
+    // The signing key must be an active, unexpired key of the namespace's
+    // key set, unless the set is empty and this publish adds its first key.
+    // Of concurrent first publishes only one pins its key; the others must
+    // then be signed by that key.
+    let keys = key_set(&state.pool, ns_id).await?;
+    let signs = if keys.is_empty() && !has_any_key(&state, ns_id).await? {
+        pin_first_key(&state, ns_id, &fingerprint, &manifest.cert_chain_pem[0]).await?
+            || may_sign(&key_set(&state.pool, ns_id).await?, &fingerprint)
+    } else {
+        may_sign(&keys, &fingerprint)
+    };
+    if !signs {
+        return Err(StatusCode::FORBIDDEN);
     }
 
     let job_id = persist_and_notify(
         &state,
         PublishArgs {
             ns_id,
-            ns_slug: &ns_slug,
             manifest: &manifest,
-            sha256: &sha256,
             body,
             fingerprint: &fingerprint,
-            pinned,
         },
     )
     .await?;
@@ -309,8 +444,8 @@ mod tests {
     }
 
     #[test]
-    fn rejects_chain_length_3() {
-        assert!(validate_cert_chain(&["a".to_string(), "b".to_string(), "c".to_string()]).is_err());
+    fn rejects_chain_longer_than_max() {
+        assert!(validate_cert_chain(&vec!["cert".to_string(); MAX_CHAIN_LEN + 1]).is_err());
     }
 
     #[test]
@@ -323,6 +458,11 @@ mod tests {
         assert!(validate_cert_chain(&["cert1".to_string(), "cert2".to_string()]).is_ok());
     }
 
+    #[test]
+    fn accepts_multi_tier_chain() {
+        assert!(validate_cert_chain(&vec!["cert".to_string(); MAX_CHAIN_LEN]).is_ok());
+    }
+
     #[test]
     fn rejects_oversized_cert_chain() {
         let big = "A".repeat(33_000);
@@ -1,45 +1,22 @@
 //! POST /v1/namespaces/:ns/rotate-key  — submit a key-rotation request.
 //! GET  /v1/namespaces/:ns/rotate-key/confirm — confirm via email token.
+//! GET  /v1/namespaces/:ns/key-history — confirmed rotations, oldest first.
 
 use axum::extract::{Path, Query, State};
 use axum::http::{HeaderMap, StatusCode};
 use axum::Json;
+use chrono::{DateTime, Utc};
 use der::DecodePem;
 use log::error;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+use skreg_core::rotation::{RotationRecord, RotationToken};
+use skreg_core::types::Sha256Digest;
+use skreg_crypto::algorithm::verify_digest;
+use skreg_crypto::error::VerifyError;
 
 use crate::middleware::{extract_bearer, resolve_namespace};
 use crate::router::SharedState;
 
-// ---------------------------------------------------------------------------
-// Shared token shape (mirrors the CLI's RotationToken)
-// ---------------------------------------------------------------------------
-
-/// Rotation token submitted by the client.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RotationToken {
-    /// Authenticated namespace slug.
-    pub namespace: String,
-    /// SHA-256 SPKI fingerprint (hex) of the current key being replaced.
-    pub old_key_fingerprint: String,
-    /// SHA-256 SPKI fingerprint (hex) of the new key.
-    pub new_key_fingerprint: String,
-    /// PEM-encoded cert chain for the new key (leaf first).
-    pub new_cert_chain_pem: Vec<String>,
-    /// 32-byte random nonce (hex) — prevents replay.
-    pub nonce: String,
-    /// RFC 3339 timestamp when this token was created.
-    pub issued_at: String,
-    /// RFC 3339 timestamp when this token expires.
-    pub expires_at: String,
-}
-
-/// Serialize `token` to the canonical JSON bytes used for signature verification.
-fn canonical_json_bytes(token: &RotationToken) -> Result<Vec<u8>, serde_json::Error> {
-    serde_json::to_string(token).map(String::into_bytes)
-}
-
 // ---------------------------------------------------------------------------
 // Request / response types
 // ---------------------------------------------------------------------------
@@ -49,9 +26,9 @@ fn canonical_json_bytes(token: &RotationToken) -> Result<Vec<u8>, serde_json::Er
 pub struct RotateSubmitRequest {
     /// The rotation token containing key fingerprints and timestamps.
     pub token: RotationToken,
-    /// Hex-encoded RSA-PSS signature over `sha256(canonical_json)` with the *old* key.
+    /// Hex-encoded RSA-PSS signature over `sha256(token.signing_bytes())` with the *old* key.
     pub old_sig: String,
-    /// Hex-encoded RSA-PSS signature over `sha256(canonical_json)` with the *new* key.
+    /// Hex-encoded RSA-PSS signature over `sha256(token.signing_bytes())` with the *new* key.
     pub new_sig: String,
 }
 
@@ -77,80 +54,72 @@ pub struct ConfirmResponse {
 }
 
 // ---------------------------------------------------------------------------
-// PSS verification helper
+// Signature verification helper
 // ---------------------------------------------------------------------------
 
-/// Verify a hex-encoded RSA-PSS signature over a pre-hashed digest.
+/// Verify a hex-encoded signature over a SHA-256 digest with the key in a
+/// certificate, using the scheme for the key's algorithm.
 ///
 /// `cert_pem` — PEM certificate whose public key is used for verification.
 /// `sig_hex`  — hex-encoded signature bytes.
-/// `digest`   — raw 32-byte SHA-256 digest.
+/// `digest`   — SHA-256 digest of the signed token.
 ///
 /// Returns `Ok(())` on success, `Err(StatusCode)` on any failure.
-fn verify_pss_from_cert(cert_pem: &str, sig_hex: &str, digest: &[u8]) -> Result<(), StatusCode> {
-    use rsa::pkcs8::DecodePublicKey;
-    use rsa::pss::{Signature, VerifyingKey};
-    use rsa::signature::hazmat::PrehashVerifier;
-
-    // Parse the certificate and extract the DER-encoded SPKI.
+pub(crate) fn verify_sig_from_cert(
+    cert_pem: &str,
+    sig_hex: &str,
+    digest: &Sha256Digest,
+) -> Result<(), StatusCode> {
     let cert = x509_cert::Certificate::from_pem(cert_pem).map_err(|e| {
         error!("parsing cert PEM for verification: {e}");
         StatusCode::UNPROCESSABLE_ENTITY
     })?;
 
-    let spki_der = {
-        use der::Encode;
-        cert.tbs_certificate
-            .subject_public_key_info
-            .to_der()
-            .map_err(|e| {
-                error!("DER-encoding SPKI: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?
-    };
-
-    let public_key = rsa::RsaPublicKey::from_public_key_der(&spki_der).map_err(|e| {
-        error!("parsing RSA public key from SPKI: {e}");
-        StatusCode::UNPROCESSABLE_ENTITY
-    })?;
-
-    let verifying_key = VerifyingKey::<Sha256>::new(public_key);
-
     let sig_bytes = hex::decode(sig_hex).map_err(|e| {
         error!("decoding signature hex: {e}");
         StatusCode::UNPROCESSABLE_ENTITY
     })?;
 
-    let signature = Signature::try_from(sig_bytes.as_slice()).map_err(|e| {
-        error!("parsing RSA-PSS signature: {e}");
-        StatusCode::UNPROCESSABLE_ENTITY
-    })?;
-
-    verifying_key
-        .verify_prehash(digest, &signature)
-        .map_err(|e| {
-            error!("PSS signature verification failed: {e}");
+    verify_digest(
+        &cert.tbs_certificate.subject_public_key_info,
+        digest,
+        &sig_bytes,
+    )
+    .map_err(|e| match e {
+        VerifyError::SignatureMismatch => {
+            error!("signature verification failed");
             StatusCode::UNAUTHORIZED
-        })
+        }
+        e => {
+            error!("checking signature: {e}");
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+    })
 }
 
 // ---------------------------------------------------------------------------
 // Email helper
 // ---------------------------------------------------------------------------
 
-async fn send_rotation_email(
+/// Email the namespace owner a link confirming a requested `action` (e.g.
+/// "key rotation"), which `confirm_path` (e.g. `rotate-key/confirm`) under
+/// the namespace applies.
+pub(crate) async fn send_confirmation_email(
     state: &crate::router::AppState,
     ns_id: uuid::Uuid,
-    confirm_token: &str,
     namespace: &str,
+    action: &str,
+    confirm_path: &str,
+    confirm_token: &str,
 ) -> Result<(), StatusCode> {
     let confirm_url = format!(
-        "{}/v1/namespaces/{}/rotate-key/confirm?token={}",
+        "{}/v1/namespaces/{}/{}?token={}",
         // Use the from_email domain as a rough base; in production this would
         // be a configured public API URL env var. For now derive from registry
         // env or fall back to a placeholder.
         std::env::var("PUBLIC_API_URL").unwrap_or_else(|_| "https://api.skreg.ai".to_owned()),
         namespace,
+        confirm_path,
         confirm_token,
     );
 
@@ -162,7 +131,7 @@ async fn send_rotation_email(
     .fetch_optional(&state.pool)
     .await
     .map_err(|e| {
-        error!("db fetch email for rotation: {e}");
+        error!("db fetch email for {action}: {e}");
         StatusCode::INTERNAL_SERVER_ERROR
     })?
     .ok_or_else(|| {
@@ -171,7 +140,7 @@ async fn send_rotation_email(
     })?;
 
     if state.smtp_disabled {
-        log::info!("[DEV] rotation email for namespace '{namespace}': {confirm_url}");
+        log::info!("[DEV] {action} email for namespace '{namespace}': {confirm_url}");
         return Ok(());
     }
 
@@ -179,9 +148,9 @@ async fn send_rotation_email(
         &state.smtp,
         &state.from_email,
         &email,
-        "Confirm your skreg key rotation",
+        &format!("Confirm your skreg {action}"),
         &format!(
-            "A key rotation was requested for namespace \"{namespace}\".\n\n\
+            "A {action} was requested for namespace \"{namespace}\".\n\n\
              Confirm here (link valid for 24 h):\n{confirm_url}\n\n\
              If you did not request this, ignore this email."
         ),
@@ -200,13 +169,29 @@ async fn send_rotation_email(
 
 /// Validate token time bounds and namespace match.
 fn validate_token(token: &RotationToken, ns_slug: &str) -> Result<(), StatusCode> {
-    if token.namespace != ns_slug {
+    validate_token_window(
+        &token.namespace,
+        &token.issued_at,
+        &token.expires_at,
+        ns_slug,
+    )
+}
+
+/// Check that a signed token names `ns_slug`, has not expired, was not
+/// issued in the future and is valid for at most 5 minutes.
+pub(crate) fn validate_token_window(
+    namespace: &str,
+    issued_at: &str,
+    expires_at: &str,
+    ns_slug: &str,
+) -> Result<(), StatusCode> {
+    if namespace != ns_slug {
         return Err(StatusCode::UNPROCESSABLE_ENTITY);
     }
-    let issued_at = chrono::DateTime::parse_from_rfc3339(&token.issued_at)
+    let issued_at = chrono::DateTime::parse_from_rfc3339(issued_at)
         .map(|dt| dt.with_timezone(&chrono::Utc))
         .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
-    let expires_at = chrono::DateTime::parse_from_rfc3339(&token.expires_at)
+    let expires_at = chrono::DateTime::parse_from_rfc3339(expires_at)
         .map(|dt| dt.with_timezone(&chrono::Utc))
         .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
     let now = chrono::Utc::now();
@@ -222,7 +207,54 @@ fn validate_token(token: &RotationToken, ns_slug: &str) -> Result<(), StatusCode
     Ok(())
 }
 
-/// Run all DB checks: rate limit, nonce, pinned key, old cert, signatures.
+/// `409` if `nonce` was already used by a rotation or key-set change.
+pub(crate) async fn check_nonce_unused(
+    state: &crate::router::AppState,
+    nonce: &str,
+) -> Result<(), StatusCode> {
+    let nonce_exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM rotation_nonces WHERE nonce = $1)",
+    )
+    .bind(nonce)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db nonce check: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if nonce_exists {
+        return Err(StatusCode::CONFLICT);
+    }
+    Ok(())
+}
+
+/// The key set of namespace `ns_id`: the fingerprint of each key not
+/// removed, and whether it has expired. Pass a transaction as `db` to read
+/// the set a change is applied to.
+pub(crate) async fn key_set(
+    db: impl sqlx::PgExecutor<'_>,
+    ns_id: uuid::Uuid,
+) -> Result<Vec<(String, bool)>, StatusCode> {
+    sqlx::query_as(
+        "SELECT fingerprint, COALESCE(expires_at <= now(), false) FROM namespace_keys
+         WHERE namespace_id = $1 AND removed_at IS NULL",
+    )
+    .bind(ns_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        error!("db fetch namespace_keys: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Whether `fingerprint` is an unexpired key of `keys`.
+pub(crate) fn may_sign(keys: &[(String, bool)], fingerprint: &str) -> bool {
+    keys.iter()
+        .any(|(key, expired)| key == fingerprint && !expired)
+}
+
+/// Run all DB checks: rate limit, nonce, key set, old cert, signatures.
 ///
 /// Returns `(old_cert_pem, new_cert_pem)` on success.
 async fn validate_rotation_db(
@@ -248,48 +280,42 @@ async fn validate_rotation_db(
         return Err(StatusCode::TOO_MANY_REQUESTS);
     }
 
-    let nonce_exists = sqlx::query_scalar::<_, bool>(
-        "SELECT EXISTS(SELECT 1 FROM rotation_nonces WHERE nonce = $1)",
-    )
-    .bind(&token.nonce)
-    .fetch_one(&state.pool)
-    .await
-    .map_err(|e| {
-        error!("db nonce check: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    if nonce_exists {
-        return Err(StatusCode::CONFLICT);
-    }
+    check_nonce_unused(state, &token.nonce).await?;
 
-    let pinned: Option<String> =
-        sqlx::query_scalar("SELECT pinned_publisher_key FROM namespaces WHERE id = $1")
-            .bind(ns_id)
-            .fetch_optional(&state.pool)
-            .await
-            .map_err(|e| {
-                error!("db pinned key check: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?
-            .flatten();
-    if let Some(ref fp) = pinned {
-        if fp != &token.old_key_fingerprint {
-            return Err(StatusCode::UNPROCESSABLE_ENTITY);
-        }
+    // Once the namespace has keys, the old key must be an unexpired one of
+    // them, and the new key must not be in the set already.
+    let keys = key_set(&state.pool, ns_id).await?;
+    if !keys.is_empty() && !may_sign(&keys, &token.old_key_fingerprint) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    if keys
+        .iter()
+        .any(|(key, _)| key == &token.new_key_fingerprint)
+    {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
     }
 
-    let token_bytes = canonical_json_bytes(token).map_err(|e| {
-        error!("canonical_json_bytes: {e}");
+    let token_bytes = token.signing_bytes().map_err(|e| {
+        error!("rotation token signing bytes: {e}");
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
-    let digest: Vec<u8> = Sha256::digest(&token_bytes).to_vec();
+    let digest = Sha256Digest::of(&token_bytes);
 
+    // The old key's certificate, from its key-set entry or, for keys added
+    // before the registry kept certificates, the newest publisher cert.
     let old_cert_pem: String = sqlx::query_scalar(
-        "SELECT pem FROM publisher_certs
-         WHERE namespace_id = $1 AND revoked_at IS NULL
-         ORDER BY created_at DESC LIMIT 1",
+        "SELECT pem FROM (
+             SELECT cert_pem AS pem, 0 AS rank, added_at AS created_at FROM namespace_keys
+              WHERE namespace_id = $1 AND fingerprint = $2
+                AND removed_at IS NULL AND cert_pem IS NOT NULL
+             UNION ALL
+             SELECT pem, 1, created_at FROM publisher_certs
+              WHERE namespace_id = $1 AND revoked_at IS NULL
+         ) certs
+         ORDER BY rank, created_at DESC LIMIT 1",
     )
     .bind(ns_id)
+    .bind(&token.old_key_fingerprint)
     .fetch_optional(&state.pool)
     .await
     .map_err(|e| {
@@ -300,14 +326,18 @@ async fn validate_rotation_db(
         error!("no active publisher cert for {ns_slug}");
         StatusCode::UNPROCESSABLE_ENTITY
     })?;
+    if crate::handlers::publish::spki_fingerprint(&old_cert_pem)? != token.old_key_fingerprint {
+        error!("current cert of {ns_slug} does not match old_key_fingerprint");
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
 
-    verify_pss_from_cert(&old_cert_pem, &body.old_sig, &digest)?;
+    verify_sig_from_cert(&old_cert_pem, &body.old_sig, &digest)?;
 
     let new_cert_pem = token.new_cert_chain_pem.first().ok_or_else(|| {
         error!("new_cert_chain_pem is empty");
         StatusCode::UNPROCESSABLE_ENTITY
     })?;
-    verify_pss_from_cert(new_cert_pem, &body.new_sig, &digest)?;
+    verify_sig_from_cert(new_cert_pem, &body.new_sig, &digest)?;
 
     Ok((old_cert_pem, new_cert_pem.clone()))
 }
@@ -319,11 +349,18 @@ async fn persist_rotation(
     state: &crate::router::AppState,
     ns_id: uuid::Uuid,
     ns_slug: &str,
-    token: &RotationToken,
+    body: &RotateSubmitRequest,
+    old_cert_pem: &str,
     new_cert_pem: &str,
 ) -> Result<String, StatusCode> {
-    sqlx::query("INSERT INTO rotation_nonces (nonce) VALUES ($1)")
+    let token = &body.token;
+    let expires_at = DateTime::parse_from_rfc3339(&token.expires_at)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    sqlx::query("INSERT INTO rotation_nonces (nonce, expires_at) VALUES ($1, $2)")
         .bind(&token.nonce)
+        .bind(expires_at)
         .execute(&state.pool)
         .await
         .map_err(|e| {
@@ -339,14 +376,18 @@ async fn persist_rotation(
 
     sqlx::query(
         "INSERT INTO pending_rotations
-             (namespace_id, confirm_token, token_json, new_key_fingerprint, new_cert_pem)
-         VALUES ($1, $2, $3, $4, $5)",
+             (namespace_id, rotation_token, old_sig, new_sig, confirm_token, expires_at,
+              new_key_fingerprint, new_cert_pem, old_cert_pem)
+         VALUES ($1, $2, $3, $4, $5, now() + INTERVAL '24 hours', $6, $7, $8)",
     )
     .bind(ns_id)
-    .bind(&confirm_token)
     .bind(&token_json)
+    .bind(&body.old_sig)
+    .bind(&body.new_sig)
+    .bind(&confirm_token)
     .bind(&token.new_key_fingerprint)
     .bind(new_cert_pem)
+    .bind(old_cert_pem)
     .execute(&state.pool)
     .await
     .map_err(|e| {
@@ -370,6 +411,49 @@ async fn persist_rotation(
     Ok(confirm_token)
 }
 
+/// Replace key `old_fp` of namespace `ns_id` with `new_fp`, keeping its
+/// label and expiry. A namespace without that key (one that has never
+/// published) gets `new_fp` as its first key.
+async fn replace_key(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ns_id: uuid::Uuid,
+    old_fp: &str,
+    new_fp: &str,
+    new_cert_pem: &str,
+) -> Result<(), StatusCode> {
+    let replaced = sqlx::query(
+        "UPDATE namespace_keys SET fingerprint = $1, cert_pem = $2
+         WHERE namespace_id = $3 AND fingerprint = $4 AND removed_at IS NULL",
+    )
+    .bind(new_fp)
+    .bind(new_cert_pem)
+    .bind(ns_id)
+    .bind(old_fp)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| {
+        error!("db update namespace_keys: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+    if replaced == 0 {
+        sqlx::query(
+            "INSERT INTO namespace_keys (namespace_id, fingerprint, label, cert_pem)
+             VALUES ($1, $2, 'initial', $3)",
+        )
+        .bind(ns_id)
+        .bind(new_fp)
+        .bind(new_cert_pem)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| {
+            error!("db insert namespace_keys: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Handlers
 // ---------------------------------------------------------------------------
@@ -377,7 +461,7 @@ async fn persist_rotation(
 /// Handle `POST /v1/namespaces/:ns/rotate-key`.
 ///
 /// Validates the rotation request (auth, time bounds, rate limit, nonce,
-/// pinned key, PSS signatures), stores a `pending_rotations` row, and sends
+/// key set, signatures), stores a `pending_rotations` row, and sends
 /// a confirmation email.
 ///
 /// # Errors
@@ -406,12 +490,21 @@ pub async fn rotate_submit_handler(
     }
 
     validate_token(&body.token, &ns_slug)?;
-    let (_old_cert, new_cert_pem) = validate_rotation_db(&state, ns_id, &ns_slug, &body).await?;
+    let (old_cert_pem, new_cert_pem) = validate_rotation_db(&state, ns_id, &ns_slug, &body).await?;
     let confirm_token =
-        persist_rotation(&state, ns_id, &ns_slug, &body.token, &new_cert_pem).await?;
-
-    if let Err(e) = send_rotation_email(&state, ns_id, &confirm_token, &ns_slug).await {
-        error!("send_rotation_email failed: {e:?}");
+        persist_rotation(&state, ns_id, &ns_slug, &body, &old_cert_pem, &new_cert_pem).await?;
+
+    if let Err(e) = send_confirmation_email(
+        &state,
+        ns_id,
+        &ns_slug,
+        "key rotation",
+        "rotate-key/confirm",
+        &confirm_token,
+    )
+    .await
+    {
+        error!("send_confirmation_email failed: {e:?}");
         return Err(StatusCode::SERVICE_UNAVAILABLE);
     }
 
@@ -423,12 +516,13 @@ pub async fn rotate_submit_handler(
 /// Handle `GET /v1/namespaces/:ns/rotate-key/confirm?token=...`.
 ///
 /// Looks up the pending rotation by `confirm_token`, verifies it is not
-/// expired and belongs to `:ns`, then in a transaction updates
-/// `namespaces.pinned_publisher_key` and deletes the pending row.
+/// expired or cancelled and belongs to `:ns`, then in a transaction replaces
+/// the old key with the new one in the namespace's key set, records the
+/// rotation in `key_rotations` and deletes the pending row.
 ///
 /// # Errors
 ///
-/// - `404` — confirm token not found or expired
+/// - `404` — confirm token not found, expired or cancelled
 /// - `403` — token belongs to a different namespace
 /// - `500` — database error
 pub async fn rotate_confirm_handler(
@@ -437,12 +531,14 @@ pub async fn rotate_confirm_handler(
     Query(params): Query<ConfirmQuery>,
 ) -> Result<Json<ConfirmResponse>, StatusCode> {
     // Look up pending rotation
-    let row = sqlx::query_as::<_, (uuid::Uuid, String, String, String)>(
-        "SELECT pr.namespace_id, n.slug, pr.new_key_fingerprint, pr.new_cert_pem
+    let row = sqlx::query_as::<_, (uuid::Uuid, String, String, String, String)>(
+        "SELECT pr.namespace_id, n.slug, pr.rotation_token->>'old_key_fingerprint',
+                pr.new_key_fingerprint, pr.new_cert_pem
          FROM pending_rotations pr
          JOIN namespaces n ON n.id = pr.namespace_id
          WHERE pr.confirm_token = $1
-           AND pr.created_at > now() - INTERVAL '24 hours'",
+           AND pr.cancelled_at IS NULL
+           AND pr.expires_at > now()",
     )
     .bind(&params.token)
     .fetch_optional(&state.pool)
@@ -453,7 +549,7 @@ pub async fn rotate_confirm_handler(
     })?
     .ok_or(StatusCode::NOT_FOUND)?;
 
-    let (ns_id, row_ns_slug, new_fp, new_cert_pem) = row;
+    let (ns_id, row_ns_slug, old_fp, new_fp, new_cert_pem) = row;
 
     if row_ns_slug != ns {
         return Err(StatusCode::FORBIDDEN);
@@ -465,15 +561,23 @@ pub async fn rotate_confirm_handler(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    sqlx::query("UPDATE namespaces SET pinned_publisher_key = $1 WHERE id = $2")
-        .bind(&new_fp)
-        .bind(ns_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| {
-            error!("db update pinned_publisher_key: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    replace_key(&mut tx, ns_id, &old_fp, &new_fp, &new_cert_pem).await?;
+
+    sqlx::query(
+        "INSERT INTO key_rotations
+             (namespace_id, old_key_fingerprint, new_key_fingerprint, rotation_token,
+              old_sig, new_sig, old_cert_pem)
+         SELECT namespace_id, rotation_token->>'old_key_fingerprint', new_key_fingerprint,
+                rotation_token, old_sig, new_sig, old_cert_pem
+         FROM pending_rotations WHERE confirm_token = $1",
+    )
+    .bind(&params.token)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("db insert key_rotation: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
     sqlx::query("DELETE FROM pending_rotations WHERE confirm_token = $1")
         .bind(&params.token)
@@ -527,12 +631,62 @@ pub async fn rotate_confirm_handler(
     }))
 }
 
+/// Handle `GET /v1/namespaces/:ns/key-history`.
+///
+/// Returns every confirmed key rotation of `:ns`, oldest first, with the
+/// token and both signatures, so clients that pinned an earlier key can
+/// check that each new key was endorsed by the one before it. An unknown
+/// namespace has no history.
+///
+/// # Errors
+///
+/// - `500` — database error or a stored token that no longer parses
+pub async fn key_history_handler(
+    State(state): State<SharedState>,
+    Path(ns): Path<String>,
+) -> Result<Json<Vec<RotationRecord>>, StatusCode> {
+    let rows = sqlx::query_as::<_, (serde_json::Value, String, String, String, DateTime<Utc>)>(
+        "SELECT kr.rotation_token, kr.old_sig, kr.new_sig, kr.old_cert_pem, kr.confirmed_at
+         FROM key_rotations kr
+         JOIN namespaces n ON n.id = kr.namespace_id
+         WHERE n.slug = $1
+         ORDER BY kr.confirmed_at, kr.id",
+    )
+    .bind(&ns)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| {
+        error!("db fetch key_rotations: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let records = rows
+        .into_iter()
+        .map(
+            |(token_json, old_sig, new_sig, old_cert_pem, confirmed_at)| {
+                let token = serde_json::from_value::<RotationToken>(token_json).map_err(|e| {
+                    error!("parsing stored rotation token: {e}");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+                Ok(RotationRecord {
+                    token,
+                    old_sig,
+                    new_sig,
+                    old_cert_pem,
+                    confirmed_at: confirmed_at.to_rfc3339(),
+                })
+            },
+        )
+        .collect::<Result<Vec<_>, StatusCode>>()?;
+    Ok(Json(records))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn canonical_json_is_deterministic() {
+    fn signing_bytes_are_deterministic() {
         let t = RotationToken {
             namespace: "acme".into(),
             old_key_fingerprint: "aaa".into(),
@@ -542,8 +696,8 @@ mod tests {
             issued_at: "2026-01-01T00:00:00Z".into(),
             expires_at: "2026-01-01T00:05:00Z".into(),
         };
-        let b1 = canonical_json_bytes(&t).unwrap();
-        let b2 = canonical_json_bytes(&t.clone()).unwrap();
+        let b1 = t.signing_bytes().unwrap();
+        let b2 = t.clone().signing_bytes().unwrap();
         assert_eq!(b1, b2);
     }
 }
@@ -0,0 +1,207 @@
+//! GET  /v1/namespaces/:ns/rotations — rotations awaiting confirmation.
+//! POST /v1/namespaces/:ns/rotations/:id/cancel — cancel one of them.
+//! POST /v1/namespaces/:ns/rotations/:id/resend — email its link again.
+//!
+//! A cancelled rotation can no longer be confirmed, but still counts towards
+//! the namespace's daily rotation limit.
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::Serialize;
+use skreg_core::rotation::PendingRotation;
+
+use super::certs::authorize;
+use super::db_error;
+use super::rotate::send_confirmation_email;
+use crate::router::SharedState;
+
+/// Confirmation emails a namespace may have resent per 24 hours.
+const MAX_RESENDS_PER_DAY: i64 = 5;
+
+/// Response body for `POST /v1/namespaces/:ns/rotations/:id/cancel`.
+#[derive(Debug, Serialize)]
+pub struct RotationCancelResponse {
+    /// Human-readable status message.
+    pub message: String,
+}
+
+/// Response body for `POST /v1/namespaces/:ns/rotations/:id/resend`.
+#[derive(Debug, Serialize)]
+pub struct RotationResendResponse {
+    /// Human-readable status message.
+    pub message: String,
+}
+
+/// Handle `GET /v1/namespaces/:ns/rotations`.
+///
+/// Returns the rotations of `:ns` that are neither confirmed, cancelled nor
+/// expired, oldest first.
+///
+/// # Errors
+///
+/// - `401` — missing/invalid Bearer token
+/// - `403` — token belongs to a different namespace
+/// - `500` — database error
+pub async fn rotations_handler(
+    State(state): State<SharedState>,
+    Path(ns): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PendingRotation>>, StatusCode> {
+    let (ns_id, _) = authorize(&state, &headers, &ns).await?;
+    let rows = sqlx::query_as::<_, (uuid::Uuid, String, String, DateTime<Utc>, DateTime<Utc>)>(
+        "SELECT id, rotation_token->>'old_key_fingerprint', new_key_fingerprint,
+                created_at, expires_at
+         FROM pending_rotations
+         WHERE namespace_id = $1 AND cancelled_at IS NULL AND expires_at > now()
+         ORDER BY created_at",
+    )
+    .bind(ns_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(db_error("fetch pending_rotations"))?;
+
+    Ok(Json(
+        rows.into_iter()
+            .map(
+                |(id, old_key_fingerprint, new_key_fingerprint, created_at, expires_at)| {
+                    PendingRotation {
+                        id: id.to_string(),
+                        old_key_fingerprint,
+                        new_key_fingerprint,
+                        created_at: created_at.to_rfc3339(),
+                        expires_at: expires_at.to_rfc3339(),
+                    }
+                },
+            )
+            .collect(),
+    ))
+}
+
+/// Handle `POST /v1/namespaces/:ns/rotations/:id/cancel`.
+///
+/// Marks the pending rotation cancelled, so its confirmation link stops
+/// working, and records the cancellation in the PKI audit log.
+///
+/// # Errors
+///
+/// - `401` — missing/invalid Bearer token
+/// - `403` — token belongs to a different namespace
+/// - `404` — no pending rotation `:id` in `:ns`
+/// - `500` — database error
+pub async fn rotation_cancel_handler(
+    State(state): State<SharedState>,
+    Path((ns, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<RotationCancelResponse>, StatusCode> {
+    let (ns_id, ns_slug) = authorize(&state, &headers, &ns).await?;
+    let id = uuid::Uuid::parse_str(&id).map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let new_fp = sqlx::query_scalar::<_, String>(
+        "UPDATE pending_rotations SET cancelled_at = now()
+         WHERE id = $1 AND namespace_id = $2
+           AND cancelled_at IS NULL AND expires_at > now()
+         RETURNING new_key_fingerprint",
+    )
+    .bind(id)
+    .bind(ns_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(db_error("cancel pending_rotation"))?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    sqlx::query(
+        "INSERT INTO pki_audit_log (namespace_id, operation, outcome, detail)
+         VALUES ($1, 'rotate_cancel', 'success', $2)",
+    )
+    .bind(ns_id)
+    .bind(serde_json::json!({ "namespace": ns_slug, "new_fp": new_fp }))
+    .execute(&state.pool)
+    .await
+    .map_err(db_error("audit log"))?;
+
+    Ok(Json(RotationCancelResponse {
+        message: "Key rotation cancelled.".to_owned(),
+    }))
+}
+
+/// Handle `POST /v1/namespaces/:ns/rotations/:id/resend`.
+///
+/// Gives the pending rotation a new confirmation link, so any earlier one
+/// stops working, and emails it to the namespace owner. The link still
+/// expires with the rotation.
+///
+/// # Errors
+///
+/// - `401` — missing/invalid Bearer token
+/// - `403` — token belongs to a different namespace
+/// - `404` — no pending rotation `:id` in `:ns`
+/// - `429` — more than [`MAX_RESENDS_PER_DAY`] resends in 24 hours
+/// - `500` — database error
+/// - `503` — the email could not be sent
+pub async fn rotation_resend_handler(
+    State(state): State<SharedState>,
+    Path((ns, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<RotationResendResponse>, StatusCode> {
+    let (ns_id, ns_slug) = authorize(&state, &headers, &ns).await?;
+    let id = uuid::Uuid::parse_str(&id).map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let resends = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM pki_audit_log
+         WHERE namespace_id = $1 AND operation = 'rotate_resend'
+           AND occurred_at > now() - INTERVAL '24 hours'",
+    )
+    .bind(ns_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(db_error("count rotation resends"))?;
+    if resends >= MAX_RESENDS_PER_DAY {
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let confirm_token = hex::encode(rand::random::<[u8; 32]>());
+    let new_fp = sqlx::query_scalar::<_, String>(
+        "UPDATE pending_rotations SET confirm_token = $3
+         WHERE id = $1 AND namespace_id = $2
+           AND cancelled_at IS NULL AND expires_at > now()
+         RETURNING new_key_fingerprint",
+    )
+    .bind(id)
+    .bind(ns_id)
+    .bind(&confirm_token)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(db_error("renew rotation confirm_token"))?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    sqlx::query(
+        "INSERT INTO pki_audit_log (namespace_id, operation, outcome, detail)
+         VALUES ($1, 'rotate_resend', 'success', $2)",
+    )
+    .bind(ns_id)
+    .bind(serde_json::json!({ "namespace": ns_slug, "new_fp": new_fp }))
+    .execute(&state.pool)
+    .await
+    .map_err(db_error("audit log"))?;
+
+    if let Err(e) = send_confirmation_email(
+        &state,
+        ns_id,
+        &ns_slug,
+        "key rotation",
+        "rotate-key/confirm",
+        &confirm_token,
+    )
+    .await
+    {
+        error!("send_confirmation_email failed: {e:?}");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    Ok(Json(RotationResendResponse {
+        message: "Check your email to confirm the rotation.".to_owned(),
+    }))
+}
@@ -30,10 +30,10 @@ pub async fn search_handler(
         "
         SELECT p.id, n.slug AS namespace, p.name, p.description, p.category, p.created_at,
                (SELECT v2.version FROM versions v2
-                WHERE v2.package_id = p.id AND v2.yanked_at IS NULL
+                WHERE v2.package_id = p.id AND v2.yanked_at IS NULL AND v2.quarantined_at IS NULL
                 ORDER BY v2.published_at DESC LIMIT 1) AS latest_version,
                COALESCE((SELECT v3.signer FROM versions v3
-                WHERE v3.package_id = p.id AND v3.yanked_at IS NULL
+                WHERE v3.package_id = p.id AND v3.yanked_at IS NULL AND v3.quarantined_at IS NULL
                 ORDER BY v3.published_at DESC LIMIT 1), 'self_signed') AS verification
         FROM packages p
         JOIN namespaces n ON n.id = p.namespace_id
@@ -45,6 +45,7 @@ pub async fn search_handler(
               SELECT 1 FROM versions v4
               WHERE v4.package_id = p.id
                 AND v4.yanked_at IS NULL
+                AND v4.quarantined_at IS NULL
                 AND v4.signer = 'publisher'
           ))
         ORDER BY p.created_at DESC
@@ -75,6 +76,7 @@ pub async fn search_handler(
               SELECT 1 FROM versions v4
               WHERE v4.package_id = p.id
                 AND v4.yanked_at IS NULL
+                AND v4.quarantined_at IS NULL
                 AND v4.signer = 'publisher'
           ))
         ",
@@ -30,6 +30,11 @@ async fn main() -> anyhow::Result<()> {
         publisher_ca_key_pem,
         publisher_ca_cert_pem: std::env::var("PUBLISHER_CA_CERT_PEM").unwrap_or_default(),
         smtp_disabled: std::env::var("SMTP_DISABLED").as_deref() == Ok("true"),
+        admin_token_hash: std::env::var("ADMIN_API_TOKEN")
+            .ok()
+            .filter(|t| !t.is_empty())
+            .map(|t| skreg_api::auth::hash_secret(&t)),
+        log_tree: std::sync::Arc::default(),
     };
     let app = build_router(state);
     let listener = tokio::net::TcpListener::bind(&config.bind_addr).await?;
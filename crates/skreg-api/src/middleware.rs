@@ -1,6 +1,6 @@
 //! Auth helpers: extract Bearer token, resolve namespace from DB.
 
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use log::error;
 use sqlx::PgPool;
 
@@ -17,6 +17,29 @@ pub fn extract_bearer(header: &str) -> Option<String> {
     }
 }
 
+/// Check `headers` for `Authorization: Bearer <ADMIN_API_TOKEN>`.
+///
+/// # Errors
+///
+/// Returns `UNAUTHORIZED` if the token is missing or wrong, or if admin
+/// access is not configured (`admin_token_hash` is `None`).
+pub fn require_admin(
+    headers: &HeaderMap,
+    admin_token_hash: Option<&str>,
+) -> Result<(), StatusCode> {
+    let token = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(extract_bearer)
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let expected = admin_token_hash.ok_or(StatusCode::UNAUTHORIZED)?;
+    if hash_secret(&token) == expected {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
 /// Resolve a namespace slug from a raw API key.
 ///
 /// Also updates `last_used_at`.
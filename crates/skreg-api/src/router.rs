@@ -9,20 +9,36 @@ use axum::{
     Json, Router,
 };
 use serde::Serialize;
+use skreg_crypto::merkle::MerkleTree;
 use sqlx::PgPool;
 use tower_http::cors::{AllowHeaders, AllowMethods, CorsLayer};
 use tower_http::services::ServeDir;
 
+use crate::handlers::admin::add_name_allowlist_handler;
+use crate::handlers::advisories::{advisories_feed_handler, create_advisory_handler};
 use crate::handlers::auth::{login_handler, token_handler};
 use crate::handlers::cert::cert_handler;
+use crate::handlers::certs::{
+    cert_renew_handler, cert_revoke_confirm_handler, cert_revoke_handler, certs_handler,
+};
 use crate::handlers::jobs::job_status_handler;
+use crate::handlers::key_log::key_log_handler;
+use crate::handlers::keys::{
+    key_change_confirm_handler, key_change_submit_handler, key_changes_handler, keys_handler,
+};
+use crate::handlers::log::{
+    consistency_proof_handler, entries_handler, inclusion_proof_handler, tree_head_handler,
+};
 use crate::handlers::namespaces::create_namespace_handler;
 use crate::handlers::packages::{
     package_download_handler, package_meta_handler, package_sig_handler,
 };
 use crate::handlers::preview::package_preview_handler;
 use crate::handlers::publish::publish_handler;
-use crate::handlers::rotate::{rotate_confirm_handler, rotate_submit_handler};
+use crate::handlers::rotate::{key_history_handler, rotate_confirm_handler, rotate_submit_handler};
+use crate::handlers::rotations::{
+    rotation_cancel_handler, rotation_resend_handler, rotations_handler,
+};
 use crate::handlers::search::search_handler;
 
 /// Shared application state injected into every handler.
@@ -44,6 +60,11 @@ pub struct AppState {
     pub publisher_ca_cert_pem: String,
     /// When `true`, OTPs are logged at INFO level instead of sent via SMTP.
     pub smtp_disabled: bool,
+    /// SHA-256 hex of the registry admin token; `None` disables admin endpoints.
+    pub admin_token_hash: Option<String>,
+    /// The transparency log's Merkle tree, extended with the stored leaf
+    /// hashes of new entries as requests need it.
+    pub log_tree: Arc<tokio::sync::Mutex<MerkleTree>>,
 }
 
 /// Arc-wrapped [`AppState`] used as the Axum router state.
@@ -76,15 +97,57 @@ pub fn build_router(state: AppState) -> Router {
         .route("/v1/search", get(search_handler))
         .route("/v1/namespaces", post(create_namespace_handler))
         .route("/v1/namespaces/:ns/cert", post(cert_handler))
+        .route("/v1/namespaces/:ns/certs", get(certs_handler))
+        .route(
+            "/v1/namespaces/:ns/certs/:serial/renew",
+            post(cert_renew_handler),
+        )
+        .route(
+            "/v1/namespaces/:ns/certs/:serial/revoke",
+            post(cert_revoke_handler),
+        )
+        .route(
+            "/v1/namespaces/:ns/certs/:serial/revoke/confirm",
+            get(cert_revoke_confirm_handler),
+        )
         .route("/v1/namespaces/:ns/rotate-key", post(rotate_submit_handler))
         .route(
             "/v1/namespaces/:ns/rotate-key/confirm",
             get(rotate_confirm_handler),
         )
+        .route("/v1/namespaces/:ns/rotations", get(rotations_handler))
+        .route(
+            "/v1/namespaces/:ns/rotations/:id/cancel",
+            post(rotation_cancel_handler),
+        )
+        .route(
+            "/v1/namespaces/:ns/rotations/:id/resend",
+            post(rotation_resend_handler),
+        )
+        .route("/v1/namespaces/:ns/key-history", get(key_history_handler))
+        .route("/v1/namespaces/:ns/key-log", get(key_log_handler))
+        .route(
+            "/v1/namespaces/:ns/keys",
+            get(keys_handler).post(key_change_submit_handler),
+        )
+        .route(
+            "/v1/namespaces/:ns/keys/confirm",
+            get(key_change_confirm_handler),
+        )
+        .route("/v1/namespaces/:ns/key-changes", get(key_changes_handler))
         .route("/v1/auth/login", post(login_handler))
         .route("/v1/auth/token", post(token_handler))
         .route("/v1/publish", post(publish_handler))
         .route("/v1/jobs/:id", get(job_status_handler))
+        .route("/v1/log/tree-head", get(tree_head_handler))
+        .route("/v1/log/entries", get(entries_handler))
+        .route("/v1/log/proof/inclusion", get(inclusion_proof_handler))
+        .route("/v1/log/proof/consistency", get(consistency_proof_handler))
+        .route(
+            "/v1/advisories",
+            get(advisories_feed_handler).post(create_advisory_handler),
+        )
+        .route("/v1/admin/name-allowlist", post(add_name_allowlist_handler))
         .route("/v1/packages/:ns/:name/:version", get(package_meta_handler))
         .route(
             "/v1/packages/:ns/:name/:version/preview",
@@ -0,0 +1,55 @@
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use skreg_api::router::{build_router, AppState};
+
+async fn make_state() -> AppState {
+    let pool = sqlx::PgPool::connect_lazy("postgres://localhost/test").expect("lazy pool");
+    let aws_cfg = aws_config::defaults(aws_config::BehaviorVersion::latest())
+        .region(aws_config::meta::region::RegionProviderChain::default_provider())
+        .load()
+        .await;
+    AppState {
+        pool,
+        s3: aws_sdk_s3::Client::new(&aws_cfg),
+        s3_bucket: "test-bucket".to_owned(),
+        from_email: "test@example.com".to_owned(),
+        smtp: skreg_api::email::SmtpConfig {
+            host: "localhost".to_owned(),
+            port: 25,
+            username: None,
+            password: None,
+        },
+        publisher_ca_key_pem: String::new(),
+        publisher_ca_cert_pem: String::new(),
+        smtp_disabled: true,
+        admin_token_hash: None,
+        log_tree: std::sync::Arc::default(),
+    }
+}
+
+#[tokio::test]
+async fn advisories_rejects_negative_since() {
+    let app = build_router(make_state().await);
+    let server = TestServer::new(app).unwrap();
+    let response = server
+        .get("/v1/advisories")
+        .add_query_param("since", -1)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn create_advisory_requires_admin_token() {
+    let app = build_router(make_state().await);
+    let server = TestServer::new(app).unwrap();
+    let response = server
+        .post("/v1/advisories")
+        .json(&serde_json::json!({
+            "namespace": "acme",
+            "package": "helper",
+            "severity": "high",
+            "summary": "bad"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
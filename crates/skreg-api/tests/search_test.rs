@@ -22,6 +22,8 @@ async fn make_state() -> AppState {
         publisher_ca_key_pem: String::new(),
         publisher_ca_cert_pem: String::new(),
         smtp_disabled: true,
+        admin_token_hash: None,
+        log_tree: std::sync::Arc::default(),
     }
 }
 
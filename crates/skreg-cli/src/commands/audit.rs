@@ -0,0 +1,211 @@
+//! `skreg audit` — check installed skills against the registry advisory feed.
+
+use anyhow::{Context, Result};
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{ContentArrangement, Table};
+use crossterm::terminal;
+use serde::Serialize;
+
+use skreg_client::advisories::{check_feed_age, sync_advisories};
+use skreg_client::client::HttpRegistryClient;
+use skreg_core::advisory::{default_advisory_cache_path, AdvisoryCache, Severity};
+use skreg_tui::views::installed::{packages_dir, scan_installed, InstalledPkg};
+
+use crate::config::{default_config_path, load_config, root_verifier};
+
+/// Exit code when at least one advisory at or above the threshold matches.
+pub const EXIT_VULNERABLE: i32 = 1;
+/// Exit code when the audit itself could not complete.
+pub const EXIT_AUDIT_ERROR: i32 = 2;
+
+/// One advisory matching one installed package.
+#[derive(Debug, Serialize)]
+pub struct AuditFinding {
+    /// `namespace/name` of the installed package.
+    pub package: String,
+    /// Installed version.
+    pub version: String,
+    /// Advisory identifier.
+    pub advisory: String,
+    /// Advisory severity.
+    pub severity: Severity,
+    /// One-line summary.
+    pub summary: String,
+    /// First unaffected version, if any.
+    pub fixed_version: Option<String>,
+}
+
+/// Match `installed` packages against `cache`, most severe first.
+///
+/// Directories whose name is not a semver version are skipped.
+#[must_use]
+pub fn audit_installed(installed: &[InstalledPkg], cache: &AdvisoryCache) -> Vec<AuditFinding> {
+    let mut findings: Vec<AuditFinding> = installed
+        .iter()
+        .filter_map(|pkg| semver::Version::parse(&pkg.version).ok().map(|v| (pkg, v)))
+        .flat_map(|(pkg, version)| {
+            cache
+                .affecting(&pkg.namespace, &pkg.name, &version)
+                .into_iter()
+                .map(move |a| AuditFinding {
+                    package: format!("{}/{}", pkg.namespace, pkg.name),
+                    version: pkg.version.clone(),
+                    advisory: a.id.clone(),
+                    severity: a.severity,
+                    summary: a.summary.clone(),
+                    fixed_version: a.fixed_version.clone(),
+                })
+        })
+        .collect();
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.package.cmp(&b.package))
+    });
+    findings
+}
+
+/// Run `skreg audit`.
+///
+/// Syncs the advisory feed (unless `offline`), then reports every installed
+/// package affected by an advisory. Returns the number of findings at or
+/// above `fail_on`; callers exit with [`EXIT_VULNERABLE`] when it is non-zero.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded, the feed cannot be
+/// fetched, fails signature verification or is older than the cached one,
+/// the feed was generated more than `max_feed_age` ago, or installed
+/// packages cannot be scanned.
+pub async fn run_audit(
+    fail_on: Severity,
+    json: bool,
+    offline: bool,
+    max_feed_age: Option<chrono::Duration>,
+    context: Option<&str>,
+) -> Result<usize> {
+    let cfg_path = default_config_path();
+    let cfg =
+        load_config(&cfg_path).context("not logged in — run `skreg login <namespace>` first")?;
+    let cfg = crate::config::apply_context(cfg, context)?;
+    let cache_path = default_advisory_cache_path(cfg.registry());
+
+    let cache = if offline {
+        AdvisoryCache::load(&cache_path)?
+    } else {
+        let client = HttpRegistryClient::new(cfg.registry());
+        let verifier = root_verifier(&cfg)?;
+        sync_advisories(&client, &verifier, &cache_path)
+            .await
+            .context("syncing advisory feed")?
+    };
+    if let Some(max_age) = max_feed_age {
+        check_feed_age(&cache, max_age, chrono::Utc::now())
+            .context("checking advisory feed age")?;
+    }
+
+    let installed = scan_installed(&packages_dir())?;
+    let findings = audit_installed(&installed, &cache);
+    let failing = findings.iter().filter(|f| f.severity >= fail_on).count();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+        return Ok(failing);
+    }
+
+    if findings.is_empty() {
+        println!(
+            "✓ {} installed package{} checked, no advisories",
+            installed.len(),
+            if installed.len() == 1 { "" } else { "s" }
+        );
+        return Ok(failing);
+    }
+
+    let term_width = terminal::size().map_or(120, |(w, _)| w);
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(term_width)
+        .set_header([
+            "Package", "Version", "Advisory", "Severity", "Fix", "Summary",
+        ]);
+    for f in &findings {
+        table.add_row([
+            f.package.as_str(),
+            f.version.as_str(),
+            f.advisory.as_str(),
+            f.severity.as_str(),
+            f.fixed_version.as_deref().unwrap_or("—"),
+            f.summary.as_str(),
+        ]);
+    }
+    println!("{table}");
+    println!(
+        "✗ {} advisor{} affect installed packages ({failing} at or above {fail_on})",
+        findings.len(),
+        if findings.len() == 1 { "y" } else { "ies" }
+    );
+    Ok(failing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use skreg_core::advisory::{Advisory, AdvisoryFeed};
+
+    fn installed(ns: &str, name: &str, version: &str) -> InstalledPkg {
+        InstalledPkg {
+            namespace: ns.to_owned(),
+            name: name.to_owned(),
+            version: version.to_owned(),
+            path: std::path::PathBuf::new(),
+        }
+    }
+
+    fn cache_with(affected: &str, severity: Severity) -> AdvisoryCache {
+        let mut cache = AdvisoryCache::default();
+        cache.merge(AdvisoryFeed {
+            advisories: vec![Advisory {
+                id: "SKREG-000001".to_owned(),
+                seq: 1,
+                namespace: "acme".to_owned(),
+                package: "helper".to_owned(),
+                affected: affected.to_owned(),
+                severity,
+                kind: "manual".to_owned(),
+                summary: "exfiltrates env".to_owned(),
+                description: String::new(),
+                fixed_version: Some("1.2.0".to_owned()),
+                published_at: Utc::now(),
+            }],
+            next_since: 1,
+            has_more: false,
+            generated_at: Utc::now(),
+        });
+        cache
+    }
+
+    #[test]
+    fn audit_flags_affected_versions_only() {
+        let cache = cache_with("<1.2.0", Severity::High);
+        let pkgs = [
+            installed("acme", "helper", "1.1.0"),
+            installed("acme", "helper", "1.2.0"),
+            installed("acme", "other", "1.0.0"),
+        ];
+        let findings = audit_installed(&pkgs, &cache);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].version, "1.1.0");
+        assert_eq!(findings[0].fixed_version.as_deref(), Some("1.2.0"));
+    }
+
+    #[test]
+    fn audit_skips_non_semver_directories() {
+        let cache = cache_with("*", Severity::Low);
+        let findings = audit_installed(&[installed("acme", "helper", "latest")], &cache);
+        assert!(findings.is_empty());
+    }
+}
@@ -1,12 +1,51 @@
-//! `skreg certify` — obtain a CA-issued publisher cert.
+//! `skreg certify` — obtain, list, renew and revoke CA-issued publisher
+//! certs.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Context, Result};
+use clap::Subcommand;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use skreg_core::certs::{CertRevocationToken, PublisherCert};
 
-use crate::config::{default_config_path, load_config};
-use crate::keys::{ensure_keys_exist, keys_dir};
+use skreg_crypto::algorithm::KeyAlgorithm;
+
+use crate::config::{default_config_path, load_config, CliConfig};
+use crate::keyfile::Passphrases;
+use crate::keys::{
+    ensure_keys_exist, key_algorithm, read_private_key, spki_fingerprint, store_dir,
+};
+use crate::signer::{signing_program, Signer};
+
+/// Commands for the namespace's existing certificates.
+#[derive(Subcommand, Debug)]
+pub enum CertifyCommands {
+    /// List the namespace's certificates
+    List,
+    /// Certify the current key again before its certificate expires
+    Renew {
+        /// Send this PEM CSR for the current key instead of building one
+        /// (required when a signing program holds the key)
+        #[arg(long, value_name = "FILE")]
+        csr: Option<PathBuf>,
+    },
+    /// Revoke a certificate; signed by its key if the key store holds it,
+    /// otherwise confirmed by email
+    Revoke {
+        /// Serial of the certificate, as shown by `skreg certify list`
+        serial: i64,
+        /// Why the certificate is revoked, e.g. "key compromise"
+        #[arg(long, value_name = "TEXT")]
+        reason: Option<String>,
+    },
+}
+
+/// Response body from `POST /v1/namespaces/:ns/certs/:serial/revoke`.
+#[derive(Deserialize)]
+struct RevokeResponse {
+    message: String,
+}
 
 /// Response body from `POST /v1/namespaces/:ns/cert`.
 #[derive(Deserialize)]
@@ -20,16 +59,29 @@ struct CertResponse {
 /// Loads the current config, ensures a local key pair exists, generates a
 /// proper PKCS#10 CSR containing the public key signed by the local private
 /// key, POSTs it to the registry, then writes the returned `publisher.crt`
-/// and `publisher-ca.crt` to `~/.skreg/keys/`.
+/// and `publisher-ca.crt` to the context's key store (see [`store_dir`]).
 ///
 /// The private key never leaves the local machine — the server signs only the
-/// public key embedded in the CSR.
+/// public key embedded in the CSR, so the issued certificate carries a key of
+/// the same algorithm. A missing key is generated with `algorithm` (RSA-2048
+/// if `None`); an existing key must match `algorithm` if one is given.
+/// Encrypted keys are unlocked with `SKREG_KEY_PASSPHRASE` or a prompt.
+///
+/// If `csr` is given that CSR is sent as-is and no local key is used. This
+/// is required when a signing program holds the key: the program signs
+/// digests, not CSRs, so the tool holding the key must create the CSR.
 ///
 /// # Errors
 ///
-/// Returns an error if the config is missing, the registry is unreachable,
-/// or the cert response cannot be written to disk.
-pub async fn run_certify(key: Option<&Path>, context: Option<&str>) -> Result<()> {
+/// Returns an error if the config is missing, a signing program is
+/// configured but no `csr` is given, the registry is unreachable, or the
+/// cert response cannot be written to disk.
+pub async fn run_certify(
+    key: Option<&Path>,
+    csr: Option<&Path>,
+    algorithm: Option<KeyAlgorithm>,
+    context: Option<&str>,
+) -> Result<()> {
     let cfg_path = default_config_path();
     let cfg =
         load_config(&cfg_path).context("not logged in — run `skreg login <namespace>` first")?;
@@ -39,26 +91,60 @@ pub async fn run_certify(key: Option<&Path>, context: Option<&str>) -> Result<()
     let api_key = cfg.api_key().to_owned();
     let registry = cfg.registry().to_owned();
 
-    let kdir = keys_dir()?;
+    let kdir = store_dir(Some(&cfg))?;
 
-    // Load or generate keys. If the caller passed an explicit key path, read
-    // it from disk; otherwise fall through to ensure_keys_exist which will
-    // load or auto-generate.
-    let private_key_pem = if let Some(key_path) = key {
-        std::fs::read_to_string(key_path)
-            .with_context(|| format!("reading key from {}", key_path.display()))?
+    let csr_pem = if let Some(csr_path) = csr {
+        std::fs::read_to_string(csr_path)
+            .with_context(|| format!("reading CSR from {}", csr_path.display()))?
     } else {
-        let keys = ensure_keys_exist(&kdir, &namespace)?;
-        keys.private_key_pem
+        if signing_program(Some(cfg.active_context_config())).is_some() {
+            bail!(
+                "a signing program is configured, so skreg cannot sign a CSR; \
+                 create one with the tool holding the key and pass --csr FILE"
+            );
+        }
+        let passphrases = Passphrases::from_env();
+
+        // Load or generate keys. If the caller passed an explicit key path,
+        // read it from disk; otherwise fall through to ensure_keys_exist which
+        // will load or auto-generate.
+        let private_key_pem = if let Some(key_path) = key {
+            let pem = read_private_key(key_path, passphrases)?;
+            if let Some(wanted) = algorithm {
+                let existing = key_algorithm(&pem)?;
+                if existing != wanted {
+                    bail!("{} is an {existing} key, not {wanted}", key_path.display());
+                }
+            }
+            pem
+        } else {
+            let keys = ensure_keys_exist(&kdir, &namespace, algorithm, passphrases)?;
+            keys.private_key_pem()
+                .context("publisher keys are held by a signing program")?
+                .to_owned()
+        };
+
+        // Build a proper PKCS#10 CSR. The CSR contains the public key and a
+        // proof-of-possession signature so the server never needs the private
+        // key.
+        build_csr_pem(&namespace, &private_key_pem)?
     };
 
-    // Build a proper PKCS#10 CSR. The CSR contains the public key and a
-    // proof-of-possession signature so the server never needs the private key.
-    let csr_pem = build_csr_pem(&namespace, &private_key_pem)?;
+    request_cert(
+        &format!("{registry}/v1/namespaces/{namespace}/cert"),
+        &api_key,
+        csr_pem,
+        &kdir,
+    )
+    .await
+}
 
+/// POST `csr_pem` to `url` and write the issued cert and the CA cert to
+/// `kdir`.
+async fn request_cert(url: &str, api_key: &str, csr_pem: String, kdir: &Path) -> Result<()> {
     let client = reqwest::Client::new();
     let resp = client
-        .post(format!("{registry}/v1/namespaces/{namespace}/cert"))
+        .post(url)
         .header("Authorization", format!("Bearer {api_key}"))
         .header("Content-Type", "text/plain")
         .body(csr_pem)
@@ -77,7 +163,7 @@ pub async fn run_certify(key: Option<&Path>, context: Option<&str>) -> Result<()
     let cert_resp: CertResponse = resp.json().await.context("parsing cert response")?;
 
     // Write publisher.crt and publisher-ca.crt to the keys directory.
-    std::fs::create_dir_all(&kdir)
+    std::fs::create_dir_all(kdir)
         .with_context(|| format!("creating keys directory {}", kdir.display()))?;
 
     let cert_path = kdir.join("publisher.crt");
@@ -94,11 +180,162 @@ pub async fn run_certify(key: Option<&Path>, context: Option<&str>) -> Result<()
     Ok(())
 }
 
+/// Dispatch a `skreg certify` subcommand for the namespace of `context` (or
+/// the active context).
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded or the command fails.
+pub async fn handle(command: CertifyCommands, context: Option<&str>) -> Result<()> {
+    let cfg = load_config(&default_config_path())
+        .context("not logged in — run `skreg login <namespace>` first")?;
+    let cfg = crate::config::apply_context(cfg, context)?;
+    match command {
+        CertifyCommands::List => {
+            for cert in fetch_certs(&cfg).await? {
+                let status = match (&cert.revoked_at, &cert.revocation_reason) {
+                    (Some(at), Some(reason)) => format!("revoked {at}: {reason}"),
+                    (Some(at), None) => format!("revoked {at}"),
+                    (None, _) => format!("expires {}", cert.expires_at),
+                };
+                println!("{}  key {}  {status}", cert.serial, cert.fingerprint);
+            }
+            Ok(())
+        }
+        CertifyCommands::Renew { csr } => renew(&cfg, csr.as_deref()).await,
+        CertifyCommands::Revoke { serial, reason } => revoke(&cfg, serial, reason).await,
+    }
+}
+
+/// Fetch the namespace's certificates from `GET /v1/namespaces/:ns/certs`.
+async fn fetch_certs(cfg: &CliConfig) -> Result<Vec<PublisherCert>> {
+    let (registry, namespace) = (cfg.registry(), cfg.namespace());
+    reqwest::Client::new()
+        .get(format!("{registry}/v1/namespaces/{namespace}/certs"))
+        .header("Authorization", format!("Bearer {}", cfg.api_key()))
+        .send()
+        .await
+        .context("fetching certificates")?
+        .error_for_status()?
+        .json()
+        .await
+        .context("parsing certificates")
+}
+
+/// Renew the unrevoked certificate of the key store's `publisher.crt` key,
+/// with a CSR for the same key, and store the new certificate.
+async fn renew(cfg: &CliConfig, csr: Option<&Path>) -> Result<()> {
+    let kdir = store_dir(Some(cfg))?;
+    let cert_path = kdir.join("publisher.crt");
+    let cert_pem = std::fs::read_to_string(&cert_path)
+        .with_context(|| format!("reading {}", cert_path.display()))?;
+    let fingerprint = spki_fingerprint(&cert_pem)?;
+    let serial = fetch_certs(cfg)
+        .await?
+        .into_iter()
+        .filter(|c| c.fingerprint == fingerprint && c.revoked_at.is_none())
+        .map(|c| c.serial)
+        .max()
+        .context("the current key has no unrevoked certificate; run `skreg certify`")?;
+
+    let csr_pem = if let Some(csr_path) = csr {
+        std::fs::read_to_string(csr_path)
+            .with_context(|| format!("reading CSR from {}", csr_path.display()))?
+    } else {
+        if signing_program(Some(cfg.active_context_config())).is_some() {
+            bail!(
+                "a signing program is configured, so skreg cannot sign a CSR; \
+                 create one with the tool holding the key and pass --csr FILE"
+            );
+        }
+        let key_pem = read_private_key(&kdir.join("publisher.key"), Passphrases::from_env())?;
+        build_csr_pem(cfg.namespace(), &key_pem)?
+    };
+
+    let (registry, namespace) = (cfg.registry(), cfg.namespace());
+    request_cert(
+        &format!("{registry}/v1/namespaces/{namespace}/certs/{serial}/renew"),
+        cfg.api_key(),
+        csr_pem,
+        &kdir,
+    )
+    .await
+}
+
+/// Revoke certificate `serial`, signing the revocation with the key store's
+/// key when it is the certified key.
+async fn revoke(cfg: &CliConfig, serial: i64, reason: Option<String>) -> Result<()> {
+    let cert = fetch_certs(cfg)
+        .await?
+        .into_iter()
+        .find(|c| c.serial == serial)
+        .with_context(|| format!("{} has no certificate {serial}", cfg.namespace()))?;
+
+    let now = chrono::Utc::now();
+    let token = CertRevocationToken {
+        namespace: cfg.namespace().to_owned(),
+        serial: serial.to_string(),
+        reason,
+        nonce: hex::encode(rand::random::<[u8; 32]>()),
+        issued_at: now.to_rfc3339(),
+        expires_at: (now + chrono::Duration::minutes(5)).to_rfc3339(),
+    };
+
+    // Sign with the certified key if the key store holds it; without a
+    // signature the registry asks for email confirmation instead.
+    let kdir = store_dir(Some(cfg))?;
+    let local_cert = std::fs::read_to_string(kdir.join("publisher.crt")).ok();
+    let holds_key = local_cert
+        .as_deref()
+        .and_then(|pem| spki_fingerprint(pem).ok())
+        .is_some_and(|fp| fp == cert.fingerprint);
+    let signature = if holds_key {
+        let signer = match signing_program(Some(cfg.active_context_config())) {
+            Some(program) => Signer::Program(program),
+            None => Signer::Key(read_private_key(
+                &kdir.join("publisher.key"),
+                Passphrases::from_env(),
+            )?),
+        };
+        let digest_hex = hex::encode(Sha256::digest(token.signing_bytes()?));
+        Some(
+            signer
+                .sign_digest(&digest_hex, &cert.cert_pem)
+                .context("signing revocation")?,
+        )
+    } else {
+        None
+    };
+
+    let (registry, namespace) = (cfg.registry(), cfg.namespace());
+    let resp = reqwest::Client::new()
+        .post(format!(
+            "{registry}/v1/namespaces/{namespace}/certs/{serial}/revoke"
+        ))
+        .header("Authorization", format!("Bearer {}", cfg.api_key()))
+        .json(&serde_json::json!({ "token": token, "signature": signature }))
+        .send()
+        .await
+        .context("sending revoke request")?;
+
+    if !resp.status().is_success() {
+        bail!(
+            "revoke failed: {} — {}",
+            resp.status(),
+            resp.text().await.unwrap_or_default()
+        );
+    }
+    let revoke_resp: RevokeResponse = resp.json().await.context("parsing revoke response")?;
+    println!("{}", revoke_resp.message);
+    Ok(())
+}
+
 /// Build a PKCS#10 CSR PEM for `namespace` using the provided private key.
 ///
 /// Uses [`rcgen::Certificate::serialize_request_pem`] which produces a
 /// standards-compliant CSR containing the public key and a proof-of-possession
-/// signature made with the private key.  The CN is set to `namespace`.
+/// signature made with the private key, using the key's own algorithm.  The
+/// CN is set to `namespace`.
 ///
 /// # Errors
 ///
@@ -110,7 +347,7 @@ pub(crate) fn build_csr_pem(namespace: &str, private_key_pem: &str) -> Result<St
         KeyPair::from_pem(private_key_pem).context("parsing private key PEM for rcgen")?;
 
     let mut params = CertificateParams::new(vec![namespace.to_owned()]);
-    params.alg = &rcgen::PKCS_RSA_SHA256;
+    params.alg = key_pair.algorithm();
     params.key_pair = Some(key_pair);
 
     let mut dn = DistinguishedName::new();
@@ -133,8 +370,34 @@ mod tests {
     #[test]
     fn build_csr_pem_produces_pkcs10() {
         let dir = tempdir().unwrap();
-        let keys = crate::keys::ensure_keys_exist(dir.path(), "acme").unwrap();
-        let pem = build_csr_pem("acme", &keys.private_key_pem).unwrap();
+        let keys = crate::keys::ensure_keys_exist(
+            dir.path(),
+            "acme",
+            None,
+            Passphrases::non_interactive(),
+        )
+        .unwrap();
+        let pem = build_csr_pem("acme", keys.private_key_pem().unwrap()).unwrap();
         assert!(pem.contains("CERTIFICATE REQUEST"));
     }
+
+    #[test]
+    fn build_csr_pem_keeps_the_key_algorithm() {
+        for (algorithm, rcgen_alg) in [
+            (KeyAlgorithm::Ed25519, &rcgen::PKCS_ED25519),
+            (KeyAlgorithm::EcdsaP256, &rcgen::PKCS_ECDSA_P256_SHA256),
+        ] {
+            let dir = tempdir().unwrap();
+            let keys = ensure_keys_exist(
+                dir.path(),
+                "acme",
+                Some(algorithm),
+                Passphrases::non_interactive(),
+            )
+            .unwrap();
+            let pem = build_csr_pem("acme", keys.private_key_pem().unwrap()).unwrap();
+            let csr = rcgen::CertificateSigningRequest::from_pem(&pem).unwrap();
+            assert!(csr.params.alg == rcgen_alg, "{algorithm}");
+        }
+    }
 }
@@ -21,9 +21,13 @@ pub enum ContextCommands {
         /// Registry URL
         #[arg(long)]
         registry: String,
-        /// Optional PEM-encoded root CA certificate for the registry.
+        /// Optional PEM file of root CA certificates trusted for the registry.
         #[arg(long, value_name = "FILE")]
         root_ca: Option<PathBuf>,
+        /// Shell command that signs for this context's publisher key instead
+        /// of a key file (receives a hex digest on stdin, prints a hex signature)
+        #[arg(long, value_name = "COMMAND")]
+        signing_program: Option<String>,
         /// Do not switch to this context after adding
         #[arg(long)]
         no_activate: bool,
@@ -54,8 +58,16 @@ pub fn handle(command: ContextCommands) -> Result<()> {
             name,
             registry,
             root_ca,
+            signing_program,
             no_activate,
-        } => add(&path, &name, registry, root_ca, !no_activate),
+        } => add(
+            &path,
+            &name,
+            registry,
+            root_ca,
+            signing_program,
+            !no_activate,
+        ),
         ContextCommands::Use { name } => set_active(&path, &name),
         ContextCommands::List => list(&path, &mut std::io::stdout()),
         ContextCommands::Remove { name } => remove(&path, &name),
@@ -67,6 +79,7 @@ fn add(
     name: &str,
     registry: String,
     root_ca: Option<PathBuf>,
+    signing_program: Option<String>,
     activate: bool,
 ) -> Result<()> {
     let root_ca_pem = root_ca;
@@ -88,6 +101,7 @@ fn add(
             namespace: String::new(),
             api_key: String::new(),
             root_ca_pem,
+            signing_program,
         },
         activate,
     );
@@ -148,7 +162,7 @@ mod tests {
         let tmp = NamedTempFile::new().unwrap();
         let path = tmp.path();
 
-        add(path, "test", "https://reg.ai".to_owned(), None, true).unwrap();
+        add(path, "test", "https://reg.ai".to_owned(), None, None, true).unwrap();
 
         let cfg = load_config(path).unwrap();
         assert_eq!(cfg.active_context, "test");
@@ -169,6 +183,7 @@ mod tests {
             "with-ca",
             "https://ca.reg".to_owned(),
             Some(ca_file.path().to_owned()),
+            None,
             true,
         )
         .unwrap();
@@ -185,8 +200,8 @@ mod tests {
         let tmp = NamedTempFile::new().unwrap();
         let path = tmp.path();
 
-        add(path, "c1", "https://r1".to_owned(), None, true).unwrap();
-        add(path, "c2", "https://r2".to_owned(), None, false).unwrap();
+        add(path, "c1", "https://r1".to_owned(), None, None, true).unwrap();
+        add(path, "c2", "https://r2".to_owned(), None, None, false).unwrap();
 
         let cfg = load_config(path).unwrap();
         assert_eq!(cfg.active_context, "c1");
@@ -198,8 +213,8 @@ mod tests {
         let tmp = NamedTempFile::new().unwrap();
         let path = tmp.path();
 
-        add(path, "c1", "https://r1".to_owned(), None, true).unwrap();
-        add(path, "c2", "https://r2".to_owned(), None, true).unwrap();
+        add(path, "c1", "https://r1".to_owned(), None, None, true).unwrap();
+        add(path, "c2", "https://r2".to_owned(), None, None, true).unwrap();
 
         set_active(path, "c1").unwrap();
         let cfg = load_config(path).unwrap();
@@ -215,7 +230,7 @@ mod tests {
         let tmp = NamedTempFile::new().unwrap();
         let path = tmp.path();
 
-        add(path, "exists", "https://r".to_owned(), None, true).unwrap();
+        add(path, "exists", "https://r".to_owned(), None, None, true).unwrap();
         assert!(set_active(path, "missing").is_err());
     }
 
@@ -224,8 +239,8 @@ mod tests {
         let tmp = NamedTempFile::new().unwrap();
         let path = tmp.path();
 
-        add(path, "a", "https://r-a".to_owned(), None, true).unwrap();
-        add(path, "b", "https://r-b".to_owned(), None, false).unwrap();
+        add(path, "a", "https://r-a".to_owned(), None, None, true).unwrap();
+        add(path, "b", "https://r-b".to_owned(), None, None, false).unwrap();
         set_active(path, "a").unwrap();
 
         let mut out = Vec::new();
@@ -241,8 +256,8 @@ mod tests {
         let tmp = NamedTempFile::new().unwrap();
         let path = tmp.path();
 
-        add(path, "keep", "https://r1".to_owned(), None, true).unwrap();
-        add(path, "drop", "https://r2".to_owned(), None, false).unwrap();
+        add(path, "keep", "https://r1".to_owned(), None, None, true).unwrap();
+        add(path, "drop", "https://r2".to_owned(), None, None, false).unwrap();
 
         remove(path, "drop").unwrap();
 
@@ -256,7 +271,7 @@ mod tests {
         let tmp = NamedTempFile::new().unwrap();
         let path = tmp.path();
 
-        add(path, "active", "https://r".to_owned(), None, true).unwrap();
+        add(path, "active", "https://r".to_owned(), None, None, true).unwrap();
         assert!(remove(path, "active").is_err());
     }
 
@@ -265,7 +280,7 @@ mod tests {
         let tmp = NamedTempFile::new().unwrap();
         let path = tmp.path();
 
-        add(path, "only", "https://r".to_owned(), None, true).unwrap();
+        add(path, "only", "https://r".to_owned(), None, None, true).unwrap();
         assert!(remove(path, "ghost").is_err());
     }
 }
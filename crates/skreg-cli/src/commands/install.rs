@@ -8,7 +8,6 @@ use anyhow::{Context, Result};
 use skreg_client::client::HttpRegistryClient;
 use skreg_core::config::EnforcementLevel;
 use skreg_core::package_ref::PackageRef;
-use skreg_crypto::verifier::RsaPssVerifier;
 
 use crate::config::{default_config_path, load_config};
 use crate::installer::Installer;
@@ -26,6 +25,9 @@ fn default_install_root() -> Result<PathBuf> {
 /// Run `skreg install <package_ref>`.
 ///
 /// `package_ref` is in the form `namespace/name` or `namespace/name@semver`.
+/// The publisher key is pinned on first install; `trust_new_key` accepts a
+/// namespace's new key even without a rotation or key addition signed by a
+/// pinned one.
 ///
 /// # Errors
 ///
@@ -34,6 +36,7 @@ fn default_install_root() -> Result<PathBuf> {
 pub async fn run_install(
     package_ref: &str,
     enforcement_override: Option<EnforcementLevel>,
+    trust_new_key: bool,
     context: Option<&str>,
 ) -> Result<()> {
     let pkg_ref = PackageRef::parse(package_ref)
@@ -49,31 +52,8 @@ pub async fn run_install(
 
     let client = Arc::new(HttpRegistryClient::new(cfg.registry()));
 
-    // Use custom root CA if the active context specifies one.
-    let verifier: Arc<dyn skreg_crypto::verifier::SignatureVerifier> = {
-        let ctx_cfg = cfg.active_context_config();
-        if let Some(ref ca_path) = ctx_cfg.root_ca_pem {
-            // Expand leading ~ manually since std::fs doesn't do tilde expansion.
-            let expanded = if ca_path.starts_with("~") {
-                let home = home::home_dir()
-                    .ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))?;
-                let suffix = ca_path
-                    .strip_prefix("~")
-                    .map_err(|_| anyhow::anyhow!("failed to strip ~ prefix from path"))?;
-                home.join(suffix)
-            } else {
-                ca_path.clone()
-            };
-            let pem = std::fs::read(&expanded)
-                .with_context(|| format!("reading root CA from {}", expanded.display()))?;
-            Arc::new(RsaPssVerifier::new_with_root_pem(&pem))
-        } else {
-            Arc::new(RsaPssVerifier::new())
-        }
-    };
     let install_root = default_install_root()?;
-
-    let installer = Installer::new(client, install_root).with_verifier(verifier);
+    let installer = Installer::for_config(client, install_root, &cfg, trust_new_key)?;
     let (result, manifest) = installer.install(&pkg_ref).await?;
 
     let ns = result.pkg_ref.namespace.as_str();
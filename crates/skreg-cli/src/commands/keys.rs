@@ -0,0 +1,314 @@
+//! `skreg keys` — manage the per-registry, per-namespace key stores.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+
+use crate::config::{apply_context, default_config_path, load_config};
+use crate::keyfile::{is_encrypted, Passphrases};
+use crate::keys::{
+    cert_algorithm, create_dir_secure, keys_dir, read_private_key, spki_fingerprint, store_dir,
+    write_file, write_secure,
+};
+use crate::signer::Signer;
+
+/// Commands for publisher key management.
+#[derive(Subcommand, Debug)]
+pub enum KeysCommands {
+    /// List the key stores and the keys in them
+    List,
+    /// Import a key and certificate into the active context's key store
+    Import {
+        /// PEM certificate of the key
+        #[arg(long, value_name = "FILE")]
+        cert: PathBuf,
+        /// PEM private key, encrypted or not (omit when a signing program
+        /// holds the key)
+        #[arg(long, value_name = "FILE")]
+        key: Option<PathBuf>,
+        /// PEM certificate of the CA that issued `--cert`
+        #[arg(long, value_name = "FILE")]
+        ca_cert: Option<PathBuf>,
+        /// Replace keys already in the store
+        #[arg(long)]
+        force: bool,
+    },
+    /// Copy the active context's key and certificates to a directory
+    Export {
+        /// Directory to write `publisher.key`, `publisher.crt` and
+        /// `publisher-ca.crt` to
+        #[arg(long, value_name = "DIR")]
+        out: PathBuf,
+        /// Export only the certificates
+        #[arg(long)]
+        public_only: bool,
+    },
+    /// Print the SHA-256 SPKI fingerprint of the active context's key
+    ShowFingerprint,
+}
+
+/// Dispatch a `skreg keys` subcommand against the key store of `context`
+/// (or the active context).
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded or the command fails.
+pub fn handle(command: KeysCommands, context: Option<&str>) -> Result<()> {
+    let cfg = load_config(&default_config_path())
+        .context("not logged in — run `skreg login <namespace>` first")?;
+    let cfg = apply_context(cfg, context)?;
+    let store = store_dir(Some(&cfg))?;
+    match command {
+        KeysCommands::List => list(&keys_dir()?, &store, &mut std::io::stdout()),
+        KeysCommands::Import {
+            cert,
+            key,
+            ca_cert,
+            force,
+        } => {
+            import(&store, &cert, key.as_deref(), ca_cert.as_deref(), force)?;
+            println!("Imported keys into {}", store.display());
+            Ok(())
+        }
+        KeysCommands::Export { out, public_only } => {
+            export(&store, &out, public_only)?;
+            println!(
+                "Exported keys from {} to {}",
+                store.display(),
+                out.display()
+            );
+            Ok(())
+        }
+        KeysCommands::ShowFingerprint => {
+            let cert_pem = read_store_cert(&store)?;
+            println!("{}", spki_fingerprint(&cert_pem)?);
+            Ok(())
+        }
+    }
+}
+
+/// Write one line per key store under `root`, marking `active` with `*`:
+/// the registry and namespace, key algorithm, fingerprint prefix, and how
+/// the key is held.
+fn list<W: Write>(root: &Path, active: &Path, out: &mut W) -> Result<()> {
+    let mut stores = Vec::new();
+    for registry in sorted_subdirs(root)? {
+        for namespace in sorted_subdirs(&registry)? {
+            if namespace.join("publisher.crt").exists() {
+                stores.push(namespace);
+            }
+        }
+    }
+    if stores.is_empty() {
+        writeln!(out, "No keys in {}.", root.display())?;
+        return Ok(());
+    }
+
+    for store in stores {
+        let prefix = if store == active { "*" } else { " " };
+        let name = store
+            .strip_prefix(root)
+            .unwrap_or(&store)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let cert_pem = read_store_cert(&store)?;
+        let algorithm =
+            cert_algorithm(&cert_pem).map_or_else(|_| "unknown".to_owned(), |alg| alg.to_string());
+        let fingerprint = spki_fingerprint(&cert_pem)?;
+        let key_path = store.join("publisher.key");
+        let held = if !key_path.exists() {
+            "no key file"
+        } else if is_encrypted(&std::fs::read_to_string(&key_path)?) {
+            "encrypted"
+        } else {
+            "unencrypted"
+        };
+        writeln!(
+            out,
+            "{prefix} {name:<32} {algorithm:<10} {fp}  {held}",
+            fp = &fingerprint[..16]
+        )?;
+    }
+    Ok(())
+}
+
+/// Copy a certificate, and optionally its key and issuing CA, into `store`.
+///
+/// The key is checked against the certificate and stored exactly as given,
+/// so an encrypted key stays encrypted.
+fn import(
+    store: &Path,
+    cert: &Path,
+    key: Option<&Path>,
+    ca_cert: Option<&Path>,
+    force: bool,
+) -> Result<()> {
+    if !force && store.join("publisher.crt").exists() {
+        bail!(
+            "{} already has keys; pass --force to replace them",
+            store.display()
+        );
+    }
+    let cert_pem =
+        std::fs::read_to_string(cert).with_context(|| format!("reading {}", cert.display()))?;
+    spki_fingerprint(&cert_pem)
+        .with_context(|| format!("{} is not a certificate", cert.display()))?;
+
+    let stored_key_pem = match key {
+        Some(key) => {
+            let private_key_pem = read_private_key(key, Passphrases::from_env())?;
+            let probe = skreg_core::types::Sha256Digest::of(b"skreg keys import");
+            Signer::Key(private_key_pem)
+                .sign_digest(probe.as_hex(), &cert_pem)
+                .and_then(|sig_hex| {
+                    crate::signer::check_signature(probe.as_hex(), &sig_hex, &cert_pem)
+                })
+                .with_context(|| {
+                    format!("{} is not the key of {}", key.display(), cert.display())
+                })?;
+            Some(
+                std::fs::read_to_string(key)
+                    .with_context(|| format!("reading {}", key.display()))?,
+            )
+        }
+        None => None,
+    };
+    let ca_pem = ca_cert
+        .map(|ca| std::fs::read_to_string(ca).with_context(|| format!("reading {}", ca.display())))
+        .transpose()?;
+
+    create_dir_secure(store)?;
+    for file in ["publisher.key", "publisher-ca.crt"] {
+        let path = store.join(file);
+        if path.exists() {
+            std::fs::remove_file(&path).with_context(|| format!("removing {}", path.display()))?;
+        }
+    }
+    if let Some(stored_key_pem) = stored_key_pem {
+        write_secure(&store.join("publisher.key"), &stored_key_pem)?;
+    }
+    write_file(&store.join("publisher.crt"), &cert_pem)?;
+    if let Some(ca_pem) = ca_pem {
+        write_file(&store.join("publisher-ca.crt"), &ca_pem)?;
+    }
+    Ok(())
+}
+
+/// Copy the key (unless `public_only`) and certificates in `store` to `out`,
+/// refusing to overwrite files there.
+fn export(store: &Path, out: &Path, public_only: bool) -> Result<()> {
+    read_store_cert(store)?;
+    let mut files = vec!["publisher.crt", "publisher-ca.crt"];
+    if !public_only {
+        files.insert(0, "publisher.key");
+    }
+    let files: Vec<_> = files
+        .into_iter()
+        .filter(|file| store.join(file).exists())
+        .collect();
+    if let Some(existing) = files.iter().find(|file| out.join(file).exists()) {
+        bail!("{} already exists", out.join(existing).display());
+    }
+
+    create_dir_secure(out)?;
+    for file in files {
+        let contents = std::fs::read_to_string(store.join(file))
+            .with_context(|| format!("reading {}", store.join(file).display()))?;
+        if file == "publisher.key" {
+            write_secure(&out.join(file), &contents)?;
+        } else {
+            write_file(&out.join(file), &contents)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_store_cert(store: &Path) -> Result<String> {
+    let cert_path = store.join("publisher.crt");
+    if !cert_path.exists() {
+        bail!(
+            "no publisher certificate in {}; run `skreg pack` or `skreg certify` to create one, \
+             or `skreg keys import`",
+            store.display()
+        );
+    }
+    std::fs::read_to_string(&cert_path).with_context(|| format!("reading {}", cert_path.display()))
+}
+
+/// Subdirectories of `dir` in name order; none if `dir` does not exist.
+fn sorted_subdirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::{generate_key_and_cert, store_path};
+    use skreg_crypto::algorithm::KeyAlgorithm;
+    use tempfile::tempdir;
+
+    fn write_pair(dir: &Path, algorithm: KeyAlgorithm) -> (PathBuf, PathBuf) {
+        let (key_pem, cert_pem) = generate_key_and_cert("acme", algorithm).unwrap();
+        let key = dir.join(format!("{algorithm}.key"));
+        let cert = dir.join(format!("{algorithm}.crt"));
+        std::fs::write(&key, key_pem).unwrap();
+        std::fs::write(&cert, cert_pem).unwrap();
+        (key, cert)
+    }
+
+    #[test]
+    fn import_list_and_export_round_trip() {
+        let src = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let public = store_path(root.path(), "https://api.skreg.ai", "acme").unwrap();
+        let private = store_path(root.path(), "http://localhost:8080/", "acme").unwrap();
+        let (key, cert) = write_pair(src.path(), KeyAlgorithm::Ed25519);
+        import(&public, &cert, Some(&key), None, false).unwrap();
+        import(&private, &cert, None, None, false).unwrap();
+
+        let mut out = Vec::new();
+        list(root.path(), &public, &mut out).unwrap();
+        let listing = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = listing.lines().collect();
+        assert_eq!(lines.len(), 2, "{listing}");
+        assert!(lines[0].starts_with("* api.skreg.ai/acme"), "{listing}");
+        assert!(lines[0].contains("ed25519") && lines[0].ends_with("unencrypted"));
+        assert!(lines[1].starts_with("  localhost_8080/acme"), "{listing}");
+        assert!(lines[1].ends_with("no key file"), "{listing}");
+
+        let out = tempdir().unwrap();
+        export(&public, out.path(), false).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(out.path().join("publisher.key")).unwrap(),
+            std::fs::read_to_string(&key).unwrap()
+        );
+        assert!(export(&public, out.path(), true).is_err());
+    }
+
+    #[test]
+    fn import_rejects_mismatched_keys_and_existing_stores() {
+        let src = tempdir().unwrap();
+        let store = tempdir().unwrap();
+        let (key, _) = write_pair(src.path(), KeyAlgorithm::Ed25519);
+        let (_, other_cert) = write_pair(src.path(), KeyAlgorithm::EcdsaP256);
+        let err = import(store.path(), &other_cert, Some(&key), None, false).unwrap_err();
+        assert!(err.to_string().contains("is not the key of"), "{err}");
+
+        import(store.path(), &other_cert, None, None, false).unwrap();
+        assert!(import(store.path(), &other_cert, None, None, false).is_err());
+        import(store.path(), &other_cert, None, None, true).unwrap();
+    }
+}
@@ -31,6 +31,7 @@ pub async fn run_login(namespace: &str) -> Result<()> {
                 namespace: String::new(),
                 api_key: String::new(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         CliConfig {
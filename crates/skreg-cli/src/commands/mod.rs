@@ -1,13 +1,18 @@
 //! CLI subcommand implementations.
 
+pub mod audit;
 pub mod certify;
+pub mod check;
 pub mod context;
 pub mod install;
+pub mod keys;
 pub mod links;
 pub mod login;
 pub mod pack;
 pub mod publish;
 pub mod rotate;
 pub mod search;
+pub mod signers;
 pub mod tui;
 pub mod uninstall;
+pub mod verify;
@@ -4,8 +4,15 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
+use skreg_core::frontmatter::SkillFrontmatter;
 use skreg_core::manifest::Manifest;
 use skreg_core::types::{Namespace, PackageName, Sha256Digest};
+use skreg_pack::format::ArchiveFormat;
+
+use crate::config::CliConfig;
+use crate::keyfile::Passphrases;
+use crate::keys::PublisherKeys;
+use crate::signer::{signing_program, Signer};
 
 /// Pack the current directory into a `.skill` tarball.
 ///
@@ -13,9 +20,22 @@ use skreg_core::types::{Namespace, PackageName, Sha256Digest};
 /// `manifest.json` in the source directory.  The source directory is never
 /// modified; `manifest.json` is injected synthetically into the tarball.
 ///
+/// Files are chosen by [`skreg_pack::select`] (`.skregignore`, include
+/// globs and the registry layout); a summary of what was included and
+/// excluded is printed.
+///
+/// The manifest lists every packed file with its size and SHA-256, and the
+/// publisher signature covers that list (see [`Manifest::signed_payload`]),
+/// so installers can check the files they extract against it.
+///
 /// If `key_override` and `cert_override` are both `Some`, those key/cert files
-/// are used; otherwise publisher keys are auto-generated or loaded from
-/// `~/.skreg/keys/`.
+/// are used. Otherwise, if a signing program is configured (see
+/// [`crate::signer`]), it signs with the certificate in `cert_override` or
+/// the key store; failing that, publisher keys are auto-generated or loaded
+/// from the key store of `context` (or the active context; see
+/// [`crate::keys::store_dir`]). Encrypted keys are unlocked with
+/// `SKREG_KEY_PASSPHRASE` or a prompt. The archive is compressed with
+/// `format`.
 ///
 /// # Errors
 ///
@@ -25,81 +45,151 @@ pub fn run_pack(
     dir: &Path,
     key_override: Option<&Path>,
     cert_override: Option<&Path>,
+    format: ArchiveFormat,
+    context: Option<&str>,
 ) -> Result<PathBuf> {
     // Step 1: Read SKILL.md frontmatter.
-    let skill_md = dir.join("SKILL.md");
-    let meta = crate::frontmatter::read_skill_metadata(&skill_md)?;
+    let meta = read_frontmatter(dir)?;
 
     // Step 2: Resolve namespace — from config, or fall back to "local".
-    let namespace_str = resolve_namespace();
-
-    // Load or generate publisher keys.
-    let keys = if let (Some(k), Some(c)) = (key_override, cert_override) {
-        crate::keys::load_explicit_keys(k, c)?
-    } else {
-        let kdir = crate::keys::keys_dir()?;
-        crate::keys::ensure_keys_exist(&kdir, &namespace_str)?
-    };
+    let cfg = load_cli_config()
+        .map(|cfg| crate::config::apply_context(cfg, context))
+        .transpose()?;
+    let namespace_str = namespace_of(cfg.as_ref());
 
-    let namespace = Namespace::new(&namespace_str)
-        .with_context(|| format!("invalid namespace slug {namespace_str:?}"))?;
-    let name =
-        PackageName::new(&meta.name).with_context(|| format!("invalid name {:?}", meta.name))?;
-    let version = meta.version.clone();
-    let description = meta.description.clone();
+    // Load or generate publisher keys, or use the signing program.
+    let passphrases = Passphrases::from_env();
+    let program = signing_program(cfg.as_ref().map(CliConfig::active_context_config));
+    let keys = match (key_override, cert_override, program) {
+        (Some(k), Some(c), _) => crate::keys::load_explicit_keys(k, c, passphrases)?,
+        (_, Some(c), Some(program)) => {
+            let cert_pem =
+                std::fs::read_to_string(c).with_context(|| format!("reading {}", c.display()))?;
+            PublisherKeys {
+                signer: Signer::Program(program),
+                cert_chain_pem: vec![cert_pem.clone()],
+                cert_pem,
+            }
+        }
+        (_, None, Some(program)) => {
+            crate::keys::program_keys(&crate::keys::store_dir(cfg.as_ref())?, program)?
+        }
+        (_, _, None) => {
+            let kdir = crate::keys::store_dir(cfg.as_ref())?;
+            crate::keys::ensure_keys_exist(&kdir, &namespace_str, None, passphrases)?
+        }
+    };
 
     let output = dir.join(format!("{}-{}.skill", meta.name, meta.version));
 
-    // Step 3: Build a draft Manifest with placeholder sha256 (64 zeros).
-    let draft_sha256 =
-        Sha256Digest::from_hex(&"0".repeat(64)).context("building placeholder sha256 digest")?;
-    let draft = Manifest {
-        namespace: namespace.clone(),
-        name: name.clone(),
-        version: version.clone(),
-        description: description.clone(),
+    // Step 3: Build the manifest with the per-file digest list.
+    let mut manifest = build_manifest(dir, &namespace_str, meta)?;
+    manifest.cert_chain_pem.clone_from(&keys.cert_chain_pem);
+
+    // Step 4: Sign the domain-separated file-list digest once.
+    let signing_digest = manifest
+        .signing_digest()
+        .context("package file paths cannot be listed in the manifest")?;
+    manifest.publisher_sig_hex = Some(keys.sign_digest(signing_digest.as_hex())?);
+
+    // Step 5: Write the tarball.
+    print_selection(dir)?;
+    skreg_pack::pack::pack_with_format(dir, &manifest, &output, format)?;
+
+    println!("packed: {} ({format})", output.display());
+    Ok(output)
+}
+
+/// Print which files of `dir` are packed and which are left out, and why.
+fn print_selection(dir: &Path) -> Result<()> {
+    let selection = skreg_pack::select::select(dir).context("selecting package files")?;
+    println!("including:");
+    for (path, _) in &selection.included {
+        println!("  {path}");
+    }
+    if !selection.excluded.is_empty() {
+        println!("excluding:");
+        for (path, reason) in &selection.excluded {
+            println!("  {path}  ({reason})");
+        }
+    }
+    Ok(())
+}
+
+/// Build an unsigned manifest for `dir` whose `sha256` is the digest of its
+/// file list.
+fn build_manifest(dir: &Path, namespace_str: &str, meta: SkillFrontmatter) -> Result<Manifest> {
+    let files = skreg_pack::pack::digest_source_files(dir).context("hashing package files")?;
+    let mut manifest = Manifest {
+        namespace: Namespace::new(namespace_str)
+            .with_context(|| format!("invalid namespace slug {namespace_str:?}"))?,
+        name: PackageName::new(&meta.name)
+            .with_context(|| format!("invalid name {:?}", meta.name))?,
+        version: meta.version,
+        description: meta.description,
         category: None,
-        sha256: draft_sha256,
+        sha256: Sha256Digest::from_hex(&"0".repeat(64))
+            .context("building placeholder sha256 digest")?,
         cert_chain_pem: vec![],
         publisher_sig_hex: None,
+        files,
     };
+    manifest.sha256 = manifest
+        .payload_digest()
+        .context("package file paths cannot be listed in the manifest")?;
+    Ok(manifest)
+}
 
-    // Step 4: First pass — pack to a temp file to compute sha256.
-    let tmp = tempfile::NamedTempFile::new().context("creating temporary file")?;
-    skreg_pack::pack::pack_with_manifest(dir, &draft, tmp.path())?;
-    let bytes = std::fs::read(tmp.path()).context("reading temporary tarball")?;
-    let sha256_hex = hex::encode(Sha256::digest(&bytes));
-
-    // Step 5: Sign the sha256 digest.
-    let publisher_sig_hex = crate::keys::pss_sign_digest(&keys.private_key_pem, &sha256_hex)?;
-
-    // Step 6: Build the signed Manifest.
-    let real_sha256 =
-        Sha256Digest::from_hex(&sha256_hex).context("building sha256 digest from hex")?;
-    let signed = Manifest {
-        namespace,
-        name,
-        version,
-        description,
-        category: None,
-        sha256: real_sha256,
-        cert_chain_pem: keys.cert_chain_pem,
-        publisher_sig_hex: Some(publisher_sig_hex),
-    };
+/// Pack `dir` into `output` exactly as `skreg pack` would, but without
+/// signing: the injected `manifest.json` carries the file list but no
+/// signature or certificate chain. Used to vet the files that would actually
+/// be uploaded.
+///
+/// # Errors
+///
+/// Returns an error if `SKILL.md` is missing or malformed, or packing fails.
+pub fn pack_unsigned(dir: &Path, output: &Path) -> Result<()> {
+    let meta = read_frontmatter(dir)?;
+    let manifest = build_manifest(dir, &namespace_of(load_cli_config().as_ref()), meta)?;
+    skreg_pack::pack::pack_with_manifest(dir, &manifest, output)
+}
 
-    // Step 7: Second pass — write the final tarball with real manifest.
-    skreg_pack::pack::pack_with_manifest(dir, &signed, &output)?;
+/// Check that the archive at `archive` is byte-identical to one rebuilt from
+/// its own unpacked contents, as the registry does on publish.
+///
+/// # Errors
+///
+/// Returns an error if the archive cannot be read or unpacked, or if the
+/// rebuilt archive differs.
+pub fn verify_reproducible(archive: &Path) -> Result<()> {
+    let bytes =
+        std::fs::read(archive).with_context(|| format!("cannot read {}", archive.display()))?;
+    let unpacked = skreg_pack::unpack::unpack_to_tempdir(&bytes).context("unpacking archive")?;
+    skreg_pack::reproducible::verify_reproducible(&bytes, unpacked.path())?;
+    println!(
+        "reproducible: sha256 {}",
+        hex::encode(Sha256::digest(&bytes))
+    );
+    Ok(())
+}
 
-    println!("packed: {}", output.display());
-    Ok(output)
+/// Read and validate the `SKILL.md` frontmatter in `dir`.
+fn read_frontmatter(dir: &Path) -> Result<SkillFrontmatter> {
+    let path = dir.join("SKILL.md");
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("cannot read {}", path.display()))?;
+    Ok(SkillFrontmatter::parse(&content)?)
 }
 
-/// Resolve the namespace from the CLI config, falling back to `"local"` when
-/// the config file is absent (e.g. in tests or fresh installs).
-fn resolve_namespace() -> String {
-    let config_path = crate::config::default_config_path();
-    crate::config::load_config(&config_path)
-        .map_or_else(|_| "local".to_owned(), |cfg| cfg.namespace().to_owned())
+/// Load the CLI config, or `None` when the config file is absent (e.g. in
+/// tests or fresh installs).
+fn load_cli_config() -> Option<CliConfig> {
+    crate::config::load_config(&crate::config::default_config_path()).ok()
+}
+
+/// The namespace from the CLI config, falling back to `"local"` without one.
+fn namespace_of(cfg: Option<&CliConfig>) -> String {
+    cfg.map_or_else(|| "local".to_owned(), |cfg| cfg.namespace().to_owned())
 }
 
 #[cfg(test)]
@@ -125,13 +215,26 @@ mod tests {
         assert!(!dir.path().join("manifest.json").exists());
 
         let keys_dir = tempdir().unwrap();
-        let keys = crate::keys::ensure_keys_exist(keys_dir.path(), "acme").unwrap();
+        let keys = crate::keys::ensure_keys_exist(
+            keys_dir.path(),
+            "acme",
+            None,
+            Passphrases::non_interactive(),
+        )
+        .unwrap();
         let key_path = keys_dir.path().join("test.key");
         let cert_path = keys_dir.path().join("test.crt");
-        fs::write(&key_path, &keys.private_key_pem).unwrap();
+        fs::write(&key_path, keys.private_key_pem().unwrap()).unwrap();
         fs::write(&cert_path, &keys.cert_pem).unwrap();
 
-        let out = run_pack(dir.path(), Some(&key_path), Some(&cert_path)).unwrap();
+        let out = run_pack(
+            dir.path(),
+            Some(&key_path),
+            Some(&cert_path),
+            ArchiveFormat::Gzip,
+            None,
+        )
+        .unwrap();
 
         assert!(out.exists(), "expected {}", out.display());
         assert!(out.metadata().unwrap().len() > 0);
@@ -143,18 +246,62 @@ mod tests {
         make_skill_dir(dir.path(), "2.0.0");
 
         let keys_dir = tempdir().unwrap();
-        let keys = crate::keys::ensure_keys_exist(keys_dir.path(), "acme").unwrap();
+        let keys = crate::keys::ensure_keys_exist(
+            keys_dir.path(),
+            "acme",
+            None,
+            Passphrases::non_interactive(),
+        )
+        .unwrap();
         let key_path = keys_dir.path().join("test.key");
         let cert_path = keys_dir.path().join("test.crt");
-        fs::write(&key_path, &keys.private_key_pem).unwrap();
+        fs::write(&key_path, keys.private_key_pem().unwrap()).unwrap();
         fs::write(&cert_path, &keys.cert_pem).unwrap();
 
-        run_pack(dir.path(), Some(&key_path), Some(&cert_path)).unwrap();
+        run_pack(
+            dir.path(),
+            Some(&key_path),
+            Some(&cert_path),
+            ArchiveFormat::Gzip,
+            None,
+        )
+        .unwrap();
 
         // Source dir must not gain a manifest.json
         assert!(!dir.path().join("manifest.json").exists());
     }
 
+    #[test]
+    fn run_pack_output_is_reproducible() {
+        let dir = tempdir().unwrap();
+        make_skill_dir(dir.path(), "1.0.0");
+        fs::create_dir(dir.path().join("scripts")).unwrap();
+        fs::write(dir.path().join("scripts").join("run.sh"), "echo hi").unwrap();
+
+        let keys_dir = tempdir().unwrap();
+        let keys = crate::keys::ensure_keys_exist(
+            keys_dir.path(),
+            "acme",
+            None,
+            Passphrases::non_interactive(),
+        )
+        .unwrap();
+        let key_path = keys_dir.path().join("test.key");
+        let cert_path = keys_dir.path().join("test.crt");
+        fs::write(&key_path, keys.private_key_pem().unwrap()).unwrap();
+        fs::write(&cert_path, &keys.cert_pem).unwrap();
+
+        let out = run_pack(
+            dir.path(),
+            Some(&key_path),
+            Some(&cert_path),
+            ArchiveFormat::Gzip,
+            None,
+        )
+        .unwrap();
+        verify_reproducible(&out).unwrap();
+    }
+
     #[test]
     fn run_pack_missing_metadata_version_errors() {
         let dir = tempdir().unwrap();
@@ -164,13 +311,26 @@ mod tests {
         )
         .unwrap();
         let keys_dir = tempdir().unwrap();
-        let keys = crate::keys::ensure_keys_exist(keys_dir.path(), "acme").unwrap();
+        let keys = crate::keys::ensure_keys_exist(
+            keys_dir.path(),
+            "acme",
+            None,
+            Passphrases::non_interactive(),
+        )
+        .unwrap();
         let key_path = keys_dir.path().join("test.key");
         let cert_path = keys_dir.path().join("test.crt");
-        fs::write(&key_path, &keys.private_key_pem).unwrap();
+        fs::write(&key_path, keys.private_key_pem().unwrap()).unwrap();
         fs::write(&cert_path, &keys.cert_pem).unwrap();
 
-        let err = run_pack(dir.path(), Some(&key_path), Some(&cert_path)).unwrap_err();
+        let err = run_pack(
+            dir.path(),
+            Some(&key_path),
+            Some(&cert_path),
+            ArchiveFormat::Gzip,
+            None,
+        )
+        .unwrap_err();
         assert!(err.to_string().contains("metadata.version"), "got: {err}");
     }
 }
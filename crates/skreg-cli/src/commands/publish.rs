@@ -4,6 +4,7 @@ use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
 use serde::Deserialize;
+use skreg_pack::format::ArchiveFormat;
 
 use crate::commands::pack::run_pack;
 use crate::config::{default_config_path, load_config};
@@ -37,7 +38,7 @@ pub async fn run_publish(context: Option<&str>) -> Result<()> {
 
     let cwd = std::env::current_dir()?;
 
-    let skill_file = run_pack(&cwd, None, None)?;
+    let skill_file = run_pack(&cwd, None, None, ArchiveFormat::Gzip, context)?;
     let name = skill_file
         .file_stem()
         .and_then(|s| s.to_str())
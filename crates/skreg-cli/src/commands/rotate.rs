@@ -1,105 +1,338 @@
 //! `skreg rotate` — rotate the publisher key (requires email confirmation).
+//!
+//! `skreg rotate status` lists rotations awaiting confirmation,
+//! `skreg rotate resend` emails the confirmation link of one again and
+//! `skreg rotate cancel` withdraws one. Once a rotation is confirmed,
+//! `skreg rotate finish` puts the new key in place.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Context, Result};
-use serde::{Deserialize, Serialize};
+use clap::Subcommand;
 use sha2::{Digest, Sha256};
+use skreg_core::keyset::NamespaceKey;
+use skreg_core::rotation::PendingRotation;
+use skreg_crypto::algorithm::KeyAlgorithm;
 
-use crate::config::{default_config_path, load_config};
-use crate::keys::{generate_self_signed_cert, keys_dir, pss_sign_digest};
+pub use skreg_core::rotation::RotationToken;
 
-/// Rotation token sent to the registry for key-rotation requests.
-///
-/// Both the old and new keys sign over the canonical JSON serialization of
-/// this token to prove possession of both private keys.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RotationToken {
-    /// Authenticated namespace slug.
-    pub namespace: String,
-    /// SHA-256 SPKI fingerprint (hex) of the current key being replaced.
-    pub old_key_fingerprint: String,
-    /// SHA-256 SPKI fingerprint (hex) of the new key.
-    pub new_key_fingerprint: String,
-    /// PEM-encoded cert chain for the new key (leaf first).
-    pub new_cert_chain_pem: Vec<String>,
-    /// 32-byte random nonce (hex) — prevents replay.
-    pub nonce: String,
-    /// RFC 3339 timestamp when this token was created.
-    pub issued_at: String,
-    /// RFC 3339 timestamp when this token expires (5 min after `issued_at`).
-    pub expires_at: String,
+use crate::config::{default_config_path, load_config, CliConfig};
+use crate::keyfile::Passphrases;
+use crate::keys::{
+    cert_algorithm, generate_key_and_cert, generate_self_signed_cert, read_private_key,
+    spki_fingerprint, store_dir,
+};
+use crate::signer::{signing_program, Signer};
+
+/// Commands for key rotations awaiting email confirmation.
+#[derive(Subcommand, Debug)]
+pub enum RotateCommands {
+    /// Show rotations awaiting email confirmation
+    Status,
+    /// Email the confirmation link of a pending rotation again
+    Resend {
+        /// Id of the rotation, as shown by `skreg rotate status`; needed
+        /// only when several are pending
+        id: Option<String>,
+    },
+    /// Cancel a rotation awaiting email confirmation
+    Cancel {
+        /// Id of the rotation, as shown by `skreg rotate status`; needed
+        /// only when several are pending
+        id: Option<String>,
+    },
+    /// Put the new key in place once its rotation is confirmed
+    Finish,
 }
 
-/// Serialize `token` to canonical JSON bytes (UTF-8).
-///
-/// `serde_json::to_string` produces a deterministic representation for a
-/// given set of field values because the fields are declared in struct order
-/// and `serde_json` does not reorder them.
+/// Dispatch a `skreg rotate` subcommand for the namespace of `context` (or
+/// the active context).
 ///
 /// # Errors
 ///
-/// Returns an error if serialization fails (in practice this never happens
-/// for a plain-struct type with only `String`/`Vec<String>` fields).
-pub fn canonical_json_bytes(token: &RotationToken) -> Result<Vec<u8>> {
-    Ok(serde_json::to_string(token)?.into_bytes())
+/// Returns an error if the config cannot be loaded or the command fails.
+pub async fn handle(command: RotateCommands, context: Option<&str>) -> Result<()> {
+    let cfg = load_config(&default_config_path())
+        .context("not logged in — run `skreg login <namespace>` first")?;
+    let cfg = crate::config::apply_context(cfg, context)?;
+    match command {
+        RotateCommands::Status => status(&cfg).await,
+        RotateCommands::Resend { id } => resend(&cfg, id.as_deref()).await,
+        RotateCommands::Cancel { id } => cancel(&cfg, id.as_deref()).await,
+        RotateCommands::Finish => finish(&cfg).await,
+    }
 }
 
-/// Compute the SHA-256 SPKI fingerprint of a PEM certificate.
-///
-/// Parses the cert using `x509_cert`, DER-encodes the
-/// `SubjectPublicKeyInfo`, and returns `hex::encode(sha256(spki_der))`.
-///
-/// # Errors
+/// Fetch the namespace's pending rotations from
+/// `GET /v1/namespaces/:ns/rotations`.
+async fn fetch_pending(cfg: &CliConfig) -> Result<Vec<PendingRotation>> {
+    let (registry, namespace) = (cfg.registry(), cfg.namespace());
+    reqwest::Client::new()
+        .get(format!("{registry}/v1/namespaces/{namespace}/rotations"))
+        .header("Authorization", format!("Bearer {}", cfg.api_key()))
+        .send()
+        .await
+        .context("fetching pending rotations")?
+        .error_for_status()?
+        .json()
+        .await
+        .context("parsing pending rotations")
+}
+
+/// Fetch the namespace's active keys from `GET /v1/namespaces/:ns/keys`.
+async fn fetch_keys(cfg: &CliConfig) -> Result<Vec<NamespaceKey>> {
+    let (registry, namespace) = (cfg.registry(), cfg.namespace());
+    reqwest::get(format!("{registry}/v1/namespaces/{namespace}/keys"))
+        .await
+        .context("fetching namespace keys")?
+        .error_for_status()?
+        .json()
+        .await
+        .context("parsing namespace keys")
+}
+
+/// The rotation of `pending` with `id`, or the only one if `id` is `None`.
+fn choose<'a>(
+    pending: &'a [PendingRotation],
+    id: Option<&str>,
+    action: &str,
+) -> Result<&'a PendingRotation> {
+    match (id, pending) {
+        (Some(id), _) => pending
+            .iter()
+            .find(|r| r.id == id)
+            .with_context(|| format!("no pending rotation {id}; see `skreg rotate status`")),
+        (None, [only]) => Ok(only),
+        (None, []) => bail!("no key rotation is awaiting confirmation"),
+        (None, _) => bail!("several rotations are pending; pass the id of one to {action}"),
+    }
+}
+
+/// Fingerprint of the new key waiting in the key store's `pending/`, if any.
+fn local_pending_fingerprint(cfg: &CliConfig) -> Result<Option<String>> {
+    let path = store_dir(Some(cfg))?.join("pending").join("publisher.crt");
+    match std::fs::read_to_string(&path) {
+        Ok(pem) => Ok(Some(spki_fingerprint(&pem).with_context(|| {
+            format!("computing fingerprint of {}", path.display())
+        })?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+    }
+}
+
+async fn status(cfg: &CliConfig) -> Result<()> {
+    let pending = fetch_pending(cfg).await?;
+    let local = local_pending_fingerprint(cfg)?;
+    if pending.is_empty() {
+        println!("No key rotation is awaiting confirmation.");
+    }
+    for rotation in &pending {
+        let here = if local.as_deref() == Some(rotation.new_key_fingerprint.as_str()) {
+            " (new key in the key store's pending/)"
+        } else {
+            ""
+        };
+        println!(
+            "{}  {} -> {}{here}\n    submitted {}, confirm before {}",
+            rotation.id,
+            rotation.old_key_fingerprint,
+            rotation.new_key_fingerprint,
+            rotation.created_at,
+            rotation.expires_at
+        );
+    }
+    if let Some(local) = local.filter(|fp| pending.iter().all(|r| &r.new_key_fingerprint != fp)) {
+        println!(
+            "The key store's pending/ holds key {local}, which no pending rotation \
+             introduces; if its rotation was confirmed, run `skreg rotate finish`."
+        );
+    }
+    Ok(())
+}
+
+async fn resend(cfg: &CliConfig, id: Option<&str>) -> Result<()> {
+    let pending = fetch_pending(cfg).await?;
+    let rotation = choose(&pending, id, "resend")?;
+
+    let (registry, namespace) = (cfg.registry(), cfg.namespace());
+    let resp = reqwest::Client::new()
+        .post(format!(
+            "{registry}/v1/namespaces/{namespace}/rotations/{}/resend",
+            rotation.id
+        ))
+        .header("Authorization", format!("Bearer {}", cfg.api_key()))
+        .send()
+        .await
+        .context("sending resend request")?;
+    if !resp.status().is_success() {
+        bail!(
+            "resend failed: {} — {}",
+            resp.status(),
+            resp.text().await.unwrap_or_default()
+        );
+    }
+    println!(
+        "Sent a new confirmation link for rotation {}; earlier links no longer work.",
+        rotation.id
+    );
+    Ok(())
+}
+
+async fn cancel(cfg: &CliConfig, id: Option<&str>) -> Result<()> {
+    let pending = fetch_pending(cfg).await?;
+    let rotation = choose(&pending, id, "cancel")?;
+
+    let (registry, namespace) = (cfg.registry(), cfg.namespace());
+    let resp = reqwest::Client::new()
+        .post(format!(
+            "{registry}/v1/namespaces/{namespace}/rotations/{}/cancel",
+            rotation.id
+        ))
+        .header("Authorization", format!("Bearer {}", cfg.api_key()))
+        .send()
+        .await
+        .context("sending cancel request")?;
+    if !resp.status().is_success() {
+        bail!(
+            "cancel failed: {} — {}",
+            resp.status(),
+            resp.text().await.unwrap_or_default()
+        );
+    }
+    println!("Rotation {} cancelled.", rotation.id);
+
+    // The new key of a cancelled rotation can never become active.
+    if local_pending_fingerprint(cfg)?.as_deref() == Some(rotation.new_key_fingerprint.as_str()) {
+        let pending_dir = store_dir(Some(cfg))?.join("pending");
+        std::fs::remove_dir_all(&pending_dir)
+            .with_context(|| format!("removing {}", pending_dir.display()))?;
+        println!("Removed its new key from {}.", pending_dir.display());
+    }
+    Ok(())
+}
+
+async fn finish(cfg: &CliConfig) -> Result<()> {
+    let kdir = store_dir(Some(cfg))?;
+    let Some(new_fp) = local_pending_fingerprint(cfg)? else {
+        bail!(
+            "no new key is waiting in {}",
+            kdir.join("pending").display()
+        );
+    };
+    if !fetch_keys(cfg)
+        .await?
+        .iter()
+        .any(|k| k.fingerprint == new_fp)
+    {
+        if fetch_pending(cfg)
+            .await?
+            .iter()
+            .any(|r| r.new_key_fingerprint == new_fp)
+        {
+            bail!(
+                "the rotation to key {new_fp} is not confirmed yet; follow the emailed \
+                 link, or get a new one with `skreg rotate resend`"
+            );
+        }
+        bail!(
+            "key {new_fp} is not one of {}'s keys and no pending rotation introduces it; \
+             its rotation was cancelled or expired, so run `skreg rotate` again",
+            cfg.namespace()
+        );
+    }
+
+    let holds_key = kdir.join("pending").join("publisher.key").exists();
+    let retired = promote_pending(&kdir)?;
+    println!("Key {new_fp} is now the key of {}.", cfg.namespace());
+    if let Some(retired) = retired {
+        println!("The old key and certificate are in {}.", retired.display());
+    }
+    if !holds_key {
+        println!("Switch the context's signing_program to the new key's.");
+    }
+    println!("Run `skreg certify` to get a CA-verified certificate for the new key.");
+    Ok(())
+}
+
+/// Move the key store's key and certificates to `retired/<fingerprint>/`
+/// and the new key and certificate from `pending/` into their place.
 ///
-/// Returns an error if the cert cannot be parsed or DER-encoded.
-fn spki_fingerprint(cert_pem: &str) -> Result<String> {
-    use der::{DecodePem, Encode};
-    use x509_cert::Certificate;
-
-    let cert = Certificate::from_pem(cert_pem).context("parsing certificate PEM")?;
-    let spki_der = cert
-        .tbs_certificate
-        .subject_public_key_info
-        .to_der()
-        .context("DER-encoding SPKI")?;
-    Ok(hex::encode(Sha256::digest(&spki_der)))
+/// Returns the directory the old files went to, if there were any. The old
+/// `publisher-ca.crt` is retired too: it certified the old key, not the new
+/// self-signed one.
+fn promote_pending(kdir: &Path) -> Result<Option<PathBuf>> {
+    let pending_dir = kdir.join("pending");
+    let old_cert_path = kdir.join("publisher.crt");
+    let retired = match std::fs::read_to_string(&old_cert_path) {
+        Ok(pem) => {
+            let old_fp = spki_fingerprint(&pem)
+                .with_context(|| format!("computing fingerprint of {}", old_cert_path.display()))?;
+            let retired = kdir.join("retired").join(old_fp);
+            std::fs::create_dir_all(&retired)
+                .with_context(|| format!("creating {}", retired.display()))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&retired, std::fs::Permissions::from_mode(0o700))
+                    .context("setting permissions on retired key dir")?;
+            }
+            for name in ["publisher.key", "publisher.crt", "publisher-ca.crt"] {
+                move_if_present(&kdir.join(name), &retired.join(name))?;
+            }
+            Some(retired)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e).with_context(|| format!("reading {}", old_cert_path.display())),
+    };
+
+    for name in ["publisher.key", "publisher.crt"] {
+        move_if_present(&pending_dir.join(name), &kdir.join(name))?;
+    }
+    std::fs::remove_dir_all(&pending_dir)
+        .with_context(|| format!("removing {}", pending_dir.display()))?;
+    Ok(retired)
 }
 
-/// Generate a fresh RSA-2048 key + self-signed cert and return
-/// `(private_key_pem, cert_pem)`.
-fn generate_new_key(namespace: &str) -> Result<(String, String)> {
-    use rand::rngs::OsRng;
-    use rsa::pkcs8::EncodePrivateKey;
-
-    let private_key =
-        rsa::RsaPrivateKey::new(&mut OsRng, 2048).context("generating RSA-2048 key")?;
-    let private_key_pem = private_key
-        .to_pkcs8_pem(rsa::pkcs8::LineEnding::LF)
-        .context("encoding RSA key to PKCS#8 PEM")?
-        .to_string();
-
-    let cert_pem = generate_self_signed_cert(namespace, &private_key_pem)?;
-    Ok((private_key_pem, cert_pem))
+/// Rename `from` to `to`, doing nothing if `from` does not exist.
+fn move_if_present(from: &Path, to: &Path) -> Result<()> {
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result.with_context(|| format!("moving {} to {}", from.display(), to.display())),
+    }
 }
 
 /// Run `skreg rotate` — initiate a publisher key rotation.
 ///
 /// Steps:
 /// 1. Load config (namespace + `api_key`).
-/// 2. Read the current `publisher.key` and `publisher.crt` from `~/.skreg/keys/`.
-/// 3. Generate a new RSA-2048 key + self-signed cert (or read from `new_key_override`).
+/// 2. Read the current `publisher.crt` from the context's key store (see
+///    [`store_dir`]), and
+///    `publisher.key` unless the context's signing program holds the key.
+/// 3. Generate a new key + self-signed cert (or read from `new_key_override`);
+///    the new key uses `algorithm`, or the current key's algorithm if `None`.
+///    With `new_program`, the new key is held by that signing program and
+///    certified by `new_cert` instead.
 /// 4. Compute SPKI fingerprints for both keys.
 /// 5. Build a [`RotationToken`] with a 32-byte random nonce and 5-min expiry.
-/// 6. Sign `sha256(canonical_json)` with both old and new private keys.
+/// 6. Sign `sha256(token.signing_bytes())` with both old and new keys.
 /// 7. POST `{ token, old_sig, new_sig }` to `{registry}/v1/namespaces/{ns}/rotate-key`.
-/// 8. Save the new key to `~/.skreg/keys/pending/`.
+/// 8. Save the new key (if any) and cert to `pending/` in the key store.
 /// 9. Print instructions to check email.
 ///
+/// Encrypted keys are unlocked with `SKREG_KEY_PASSPHRASE` or a prompt, and
+/// a generated key is encrypted the same way.
+///
 /// # Errors
 ///
-/// Returns an error if config, keys, or the registry request fails.
-pub async fn run_rotate(new_key_override: Option<&Path>, context: Option<&str>) -> Result<()> {
+/// Returns an error if config, keys, a signing program, or the registry
+/// request fails, or if `new_program` is given without `new_cert`.
+pub async fn run_rotate(
+    new_key_override: Option<&Path>,
+    algorithm: Option<KeyAlgorithm>,
+    new_program: Option<&str>,
+    new_cert: Option<&Path>,
+    context: Option<&str>,
+) -> Result<()> {
     let cfg_path = default_config_path();
     let cfg =
         load_config(&cfg_path).context("not logged in — run `skreg login <namespace>` first")?;
@@ -109,23 +342,39 @@ pub async fn run_rotate(new_key_override: Option<&Path>, context: Option<&str>)
     let api_key = cfg.api_key().to_owned();
     let registry = cfg.registry().to_owned();
 
-    let kdir = keys_dir()?;
+    let kdir = store_dir(Some(&cfg))?;
     let old_key_path = kdir.join("publisher.key");
     let old_cert_path = kdir.join("publisher.crt");
+    let passphrases = Passphrases::from_env();
 
-    let old_key_pem = std::fs::read_to_string(&old_key_path)
-        .with_context(|| format!("reading {}", old_key_path.display()))?;
     let old_cert_pem = std::fs::read_to_string(&old_cert_path)
         .with_context(|| format!("reading {}", old_cert_path.display()))?;
+    let old_signer = match signing_program(Some(cfg.active_context_config())) {
+        Some(program) => Signer::Program(program),
+        None => Signer::Key(read_private_key(&old_key_path, passphrases.clone())?),
+    };
 
-    // New key: either read from override path or generate fresh.
-    let (new_key_pem, new_cert_pem) = if let Some(key_path) = new_key_override {
-        let pem = std::fs::read_to_string(key_path)
+    // New key: held by a signing program, read from the override path, or
+    // generated fresh. `stored_key_pem` is what gets written to pending/.
+    let (new_signer, new_cert_pem, stored_key_pem) = if let Some(program) = new_program {
+        let cert_path = new_cert.context("--new-signing-program requires --new-cert")?;
+        let cert = std::fs::read_to_string(cert_path)
+            .with_context(|| format!("reading new cert from {}", cert_path.display()))?;
+        (Signer::Program(program.to_owned()), cert, None)
+    } else if let Some(key_path) = new_key_override {
+        let stored = std::fs::read_to_string(key_path)
             .with_context(|| format!("reading new key from {}", key_path.display()))?;
+        let pem = passphrases.unlock(stored.clone(), &key_path.display().to_string())?;
         let cert = generate_self_signed_cert(&namespace, &pem)?;
-        (pem, cert)
+        (Signer::Key(pem), cert, Some(stored))
     } else {
-        generate_new_key(&namespace)?
+        let algorithm = match algorithm {
+            Some(algorithm) => algorithm,
+            None => cert_algorithm(&old_cert_pem)?,
+        };
+        let (pem, cert) = generate_key_and_cert(&namespace, algorithm)?;
+        let stored = passphrases.protect(&pem)?;
+        (Signer::Key(pem), cert, Some(stored))
     };
 
     // Compute SPKI fingerprints.
@@ -149,13 +398,15 @@ pub async fn run_rotate(new_key_override: Option<&Path>, context: Option<&str>)
         expires_at: expires.to_rfc3339(),
     };
 
-    // Sign sha256(canonical_json) with both keys.
-    let token_bytes = canonical_json_bytes(&token)?;
+    // Sign sha256 of the token's canonical JSON envelope with both keys.
+    let token_bytes = token.signing_bytes()?;
     let digest_hex = hex::encode(Sha256::digest(&token_bytes));
 
-    let old_sig = pss_sign_digest(&old_key_pem, &digest_hex)
+    let old_sig = old_signer
+        .sign_digest(&digest_hex, &old_cert_pem)
         .context("signing rotation token with old key")?;
-    let new_sig = pss_sign_digest(&new_key_pem, &digest_hex)
+    let new_sig = new_signer
+        .sign_digest(&digest_hex, &new_cert_pem)
         .context("signing rotation token with new key")?;
 
     // POST to the registry.
@@ -180,38 +431,65 @@ pub async fn run_rotate(new_key_override: Option<&Path>, context: Option<&str>)
         );
     }
 
-    // Save new key to ~/.skreg/keys/pending/.
-    let pending_dir = kdir.join("pending");
-    std::fs::create_dir_all(&pending_dir)
+    // Save new key to pending/ in the key store.
+    save_pending(
+        &kdir.join("pending"),
+        stored_key_pem.as_deref(),
+        &new_cert_pem,
+    )
+}
+
+/// Save the new key (if skreg holds it) and cert to `pending_dir`, where they
+/// wait for the rotation to be confirmed, and tell the user what happens next.
+fn save_pending(
+    pending_dir: &Path,
+    stored_key_pem: Option<&str>,
+    new_cert_pem: &str,
+) -> Result<()> {
+    std::fs::create_dir_all(pending_dir)
         .with_context(|| format!("creating pending dir {}", pending_dir.display()))?;
 
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        std::fs::set_permissions(&pending_dir, std::fs::Permissions::from_mode(0o700))
+        std::fs::set_permissions(pending_dir, std::fs::Permissions::from_mode(0o700))
             .context("setting permissions on pending dir")?;
     }
 
     let pending_key_path = pending_dir.join("publisher.key");
     let pending_cert_path = pending_dir.join("publisher.crt");
 
-    std::fs::write(&pending_key_path, &new_key_pem)
-        .with_context(|| format!("writing {}", pending_key_path.display()))?;
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        std::fs::set_permissions(&pending_key_path, std::fs::Permissions::from_mode(0o600))
-            .context("setting permissions on pending key")?;
+    if let Some(stored_key_pem) = stored_key_pem {
+        std::fs::write(&pending_key_path, stored_key_pem)
+            .with_context(|| format!("writing {}", pending_key_path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&pending_key_path, std::fs::Permissions::from_mode(0o600))
+                .context("setting permissions on pending key")?;
+        }
     }
 
-    std::fs::write(&pending_cert_path, &new_cert_pem)
+    std::fs::write(&pending_cert_path, new_cert_pem)
         .with_context(|| format!("writing {}", pending_cert_path.display()))?;
 
     println!("Rotation initiated. Check your email to confirm.");
     println!(
-        "New key saved to {} (active after confirmation).",
-        pending_key_path.display()
+        "See it with `skreg rotate status`, get a new link with `skreg rotate resend`, \
+         or withdraw it with `skreg rotate cancel`."
     );
+    if stored_key_pem.is_some() {
+        println!(
+            "New key saved to {}; once the rotation is confirmed, run `skreg rotate finish`.",
+            pending_key_path.display()
+        );
+    } else {
+        println!(
+            "New cert saved to {}; once the rotation is confirmed, run \
+             `skreg rotate finish` and switch the context's signing_program to the new key's.",
+            pending_cert_path.display()
+        );
+    }
 
     Ok(())
 }
@@ -231,8 +509,63 @@ mod tests {
             issued_at: "2026-01-01T00:00:00Z".into(),
             expires_at: "2026-01-01T00:05:00Z".into(),
         };
-        let b1 = canonical_json_bytes(&t).unwrap();
-        let b2 = canonical_json_bytes(&t.clone()).unwrap();
+        let b1 = t.signing_bytes().unwrap();
+        let b2 = t.clone().signing_bytes().unwrap();
         assert_eq!(b1, b2);
     }
+
+    #[test]
+    fn finishing_retires_the_old_key_and_promotes_the_new_one() {
+        let kdir = tempfile::tempdir().unwrap();
+        let kdir = kdir.path();
+        let (old_key, old_cert) = generate_key_and_cert("acme", KeyAlgorithm::Ed25519).unwrap();
+        let (new_key, new_cert) = generate_key_and_cert("acme", KeyAlgorithm::Ed25519).unwrap();
+        std::fs::write(kdir.join("publisher.key"), &old_key).unwrap();
+        std::fs::write(kdir.join("publisher.crt"), &old_cert).unwrap();
+        std::fs::write(kdir.join("publisher-ca.crt"), "ca").unwrap();
+        std::fs::create_dir(kdir.join("pending")).unwrap();
+        std::fs::write(kdir.join("pending").join("publisher.key"), &new_key).unwrap();
+        std::fs::write(kdir.join("pending").join("publisher.crt"), &new_cert).unwrap();
+
+        let retired = promote_pending(kdir).unwrap().unwrap();
+
+        assert_eq!(
+            retired,
+            kdir.join("retired")
+                .join(spki_fingerprint(&old_cert).unwrap())
+        );
+        assert_eq!(
+            std::fs::read_to_string(retired.join("publisher.key")).unwrap(),
+            old_key
+        );
+        assert!(retired.join("publisher-ca.crt").is_file());
+        assert_eq!(
+            std::fs::read_to_string(kdir.join("publisher.key")).unwrap(),
+            new_key
+        );
+        assert_eq!(
+            std::fs::read_to_string(kdir.join("publisher.crt")).unwrap(),
+            new_cert
+        );
+        assert!(!kdir.join("publisher-ca.crt").exists());
+        assert!(!kdir.join("pending").exists());
+    }
+
+    #[test]
+    fn only_the_named_or_single_rotation_is_chosen() {
+        let rotation = |id: &str| PendingRotation {
+            id: id.into(),
+            old_key_fingerprint: "aaa".into(),
+            new_key_fingerprint: "bbb".into(),
+            created_at: "2026-01-01T00:00:00Z".into(),
+            expires_at: "2026-01-02T00:00:00Z".into(),
+        };
+        let one = [rotation("r1")];
+        assert_eq!(choose(&one, None, "resend").unwrap().id, "r1");
+        let two = [rotation("r1"), rotation("r2")];
+        assert_eq!(choose(&two, Some("r2"), "resend").unwrap().id, "r2");
+        assert!(choose(&two, None, "resend").is_err());
+        assert!(choose(&two, Some("r3"), "resend").is_err());
+        assert!(choose(&[], None, "resend").is_err());
+    }
 }
@@ -0,0 +1,182 @@
+//! `skreg signers` — manage the keys allowed to sign for a namespace.
+//!
+//! Adding or removing a key is signed by the active context's key, which
+//! must already be one of the namespace's keys, and confirmed by email.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use sha2::{Digest, Sha256};
+use skreg_core::keyset::{KeyChangeAction, KeyChangeToken, NamespaceKey};
+
+use crate::config::{apply_context, default_config_path, load_config, CliConfig};
+use crate::keyfile::Passphrases;
+use crate::keys::{read_private_key, spki_fingerprint, store_dir};
+use crate::signer::{signing_program, Signer};
+
+/// Commands for a namespace's signing keys.
+#[derive(Subcommand, Debug)]
+pub enum SignersCommands {
+    /// List the keys allowed to sign for the namespace
+    List,
+    /// Allow another key to sign for the namespace
+    Add {
+        /// PEM certificate of the key to add
+        #[arg(long, value_name = "FILE")]
+        cert: std::path::PathBuf,
+        /// Label for the key, e.g. the maintainer's name
+        #[arg(long, value_name = "NAME")]
+        label: String,
+        /// RFC 3339 time after which the key may no longer sign
+        #[arg(long, value_name = "TIME")]
+        expires: Option<String>,
+    },
+    /// Stop a key from signing for the namespace
+    Remove {
+        /// SHA-256 SPKI fingerprint (hex) of the key to remove
+        fingerprint: String,
+    },
+}
+
+/// Dispatch a `skreg signers` subcommand for the namespace of `context` (or
+/// the active context).
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded or the command fails.
+pub async fn handle(command: SignersCommands, context: Option<&str>) -> Result<()> {
+    let cfg = load_config(&default_config_path())
+        .context("not logged in — run `skreg login <namespace>` first")?;
+    let cfg = apply_context(cfg, context)?;
+    match command {
+        SignersCommands::List => list(&cfg).await,
+        SignersCommands::Add {
+            cert,
+            label,
+            expires,
+        } => add(&cfg, &cert, label, expires).await,
+        SignersCommands::Remove { fingerprint } => {
+            let token = change_token(&cfg, KeyChangeAction::Remove, fingerprint)?;
+            submit(&cfg, token).await
+        }
+    }
+}
+
+async fn list(cfg: &CliConfig) -> Result<()> {
+    let (registry, namespace) = (cfg.registry(), cfg.namespace());
+    let keys: Vec<NamespaceKey> =
+        reqwest::get(format!("{registry}/v1/namespaces/{namespace}/keys"))
+            .await
+            .context("fetching namespace keys")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("parsing namespace keys")?;
+    if keys.is_empty() {
+        println!("{namespace} has no signing keys yet; the first publish adds one.");
+    }
+    for key in keys {
+        let expiry = key
+            .expires_at
+            .map(|t| format!(", expires {t}"))
+            .unwrap_or_default();
+        println!(
+            "{}  {} (added {}{expiry})",
+            key.fingerprint, key.label, key.added_at
+        );
+    }
+    Ok(())
+}
+
+async fn add(
+    cfg: &CliConfig,
+    cert_path: &Path,
+    label: String,
+    expires: Option<String>,
+) -> Result<()> {
+    if let Some(expires) = &expires {
+        chrono::DateTime::parse_from_rfc3339(expires)
+            .with_context(|| format!("--expires {expires} is not an RFC 3339 time"))?;
+    }
+    let cert_pem = std::fs::read_to_string(cert_path)
+        .with_context(|| format!("reading {}", cert_path.display()))?;
+    let fingerprint = spki_fingerprint(&cert_pem)
+        .with_context(|| format!("computing fingerprint of {}", cert_path.display()))?;
+    let mut token = change_token(cfg, KeyChangeAction::Add, fingerprint)?;
+    token.cert_pem = Some(cert_pem);
+    token.label = Some(label);
+    token.key_expires_at = expires;
+    submit(cfg, token).await
+}
+
+/// Build an unsigned change of `fingerprint`, to be signed by the context's
+/// key, with a 32-byte random nonce and 5-min expiry.
+fn change_token(
+    cfg: &CliConfig,
+    action: KeyChangeAction,
+    fingerprint: String,
+) -> Result<KeyChangeToken> {
+    let signer_cert_pem = signer_cert(cfg)?;
+    let now = chrono::Utc::now();
+    Ok(KeyChangeToken {
+        namespace: cfg.namespace().to_owned(),
+        action,
+        fingerprint,
+        cert_pem: None,
+        label: None,
+        key_expires_at: None,
+        signer_fingerprint: spki_fingerprint(&signer_cert_pem)
+            .context("computing fingerprint of current publisher cert")?,
+        nonce: hex::encode(rand::random::<[u8; 32]>()),
+        issued_at: now.to_rfc3339(),
+        expires_at: (now + chrono::Duration::minutes(5)).to_rfc3339(),
+    })
+}
+
+/// The certificate of the context's key, which signs key changes.
+fn signer_cert(cfg: &CliConfig) -> Result<String> {
+    let path = store_dir(Some(cfg))?.join("publisher.crt");
+    std::fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))
+}
+
+/// Sign `sha256(token.signing_bytes())` with the context's key and POST the
+/// change to `{registry}/v1/namespaces/{ns}/keys`.
+async fn submit(cfg: &CliConfig, token: KeyChangeToken) -> Result<()> {
+    let kdir = store_dir(Some(cfg))?;
+    let signer_cert_pem = signer_cert(cfg)?;
+    let signer = match signing_program(Some(cfg.active_context_config())) {
+        Some(program) => Signer::Program(program),
+        None => Signer::Key(read_private_key(
+            &kdir.join("publisher.key"),
+            Passphrases::from_env(),
+        )?),
+    };
+    let digest_hex = hex::encode(Sha256::digest(token.signing_bytes()?));
+    let signature = signer
+        .sign_digest(&digest_hex, &signer_cert_pem)
+        .context("signing key change")?;
+
+    let (registry, namespace) = (cfg.registry(), cfg.namespace());
+    let resp = reqwest::Client::new()
+        .post(format!("{registry}/v1/namespaces/{namespace}/keys"))
+        .header("Authorization", format!("Bearer {}", cfg.api_key()))
+        .json(&serde_json::json!({
+            "token": token,
+            "signature": signature,
+            "signer_cert_pem": signer_cert_pem,
+        }))
+        .send()
+        .await
+        .context("sending key change request")?;
+
+    if !resp.status().is_success() {
+        bail!(
+            "key change failed: {} — {}",
+            resp.status(),
+            resp.text().await.unwrap_or_default()
+        );
+    }
+    println!("Key change submitted. Check your email to confirm.");
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! `skreg verify` — check that the registry's transparency log only grew.
+
+use anyhow::{Context, Result};
+
+use skreg_client::client::HttpRegistryClient;
+use skreg_client::transparency::{check_consistency, default_tree_heads_path, SeenTreeHeads};
+
+use crate::config::{default_config_path, load_config, root_verifier};
+
+/// Run `skreg verify`.
+///
+/// Fetches the registry's signed tree head and proves it extends the last
+/// one seen from that registry, then records it for next time. The first run
+/// against a registry only checks the signature.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded, the tree head is not
+/// registry-signed, or the log is inconsistent with the one seen before.
+pub async fn run_verify(context: Option<&str>) -> Result<()> {
+    let cfg_path = default_config_path();
+    let cfg =
+        load_config(&cfg_path).context("not logged in — run `skreg login <namespace>` first")?;
+    let cfg = crate::config::apply_context(cfg, context)?;
+    let registry = cfg.registry().to_owned();
+
+    let heads_path = default_tree_heads_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))?;
+    let mut seen = SeenTreeHeads::load(&heads_path)?;
+    let last = seen.get(&registry).cloned();
+
+    let client = HttpRegistryClient::new(&registry);
+    let verifier = root_verifier(&cfg)?;
+    let head = check_consistency(&client, &verifier, last.as_ref())
+        .await
+        .with_context(|| format!("verifying the transparency log of {registry}"))?;
+
+    match last {
+        Some(last) => println!(
+            "✓ log of {registry} grew consistently from {} to {} entries",
+            last.tree_size, head.tree_size
+        ),
+        None => println!(
+            "✓ log of {registry} has {} entries; later runs will check it only grows",
+            head.tree_size
+        ),
+    }
+    seen.set(&registry, head);
+    seen.save(&heads_path)?;
+    Ok(())
+}
@@ -4,6 +4,7 @@ pub use skreg_core::config::{
 };
 
 use anyhow::Result;
+use skreg_crypto::verifier::RsaPssVerifier;
 
 /// Override `cfg.active_context` with `ctx` if provided.
 ///
@@ -26,6 +27,19 @@ pub fn apply_context(mut cfg: CliConfig, ctx: Option<&str>) -> Result<CliConfig>
     Ok(cfg)
 }
 
+/// Build a verifier anchored at the active context's trusted root CAs.
+///
+/// Uses the context's `root_ca_pem` bundle when set (expanding a leading
+/// `~`), otherwise the bundled root CA.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined or the root CA
+/// file cannot be read.
+pub fn root_verifier(cfg: &CliConfig) -> Result<RsaPssVerifier> {
+    Ok(skreg_client::installer::root_verifier(cfg)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,6 +54,7 @@ mod tests {
                 namespace: "acme".to_owned(),
                 api_key: "key1".to_owned(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         contexts.insert(
@@ -49,6 +64,7 @@ mod tests {
                 namespace: "devuser".to_owned(),
                 api_key: "dev_key".to_owned(),
                 root_ca_pem: Some(std::path::PathBuf::from("/home/dev/.skreg/dev/root-ca.pem")),
+                signing_program: None,
             },
         );
         CliConfig {
@@ -1,21 +1,63 @@
-//! Publisher key management — auto-keygen and RSA-PSS signing for `skreg pack`.
+//! Publisher key management — auto-keygen and signing for `skreg pack`.
+//!
+//! Keys are PKCS#8 PEM files of any [`KeyAlgorithm`], optionally encrypted
+//! with a passphrase (see [`crate::keyfile`]); signatures follow the scheme
+//! described in [`skreg_crypto::algorithm`]. Instead of a key file, a
+//! signing program can sign with a key that never touches disk (see
+//! [`crate::signer`]).
 
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use sha2::Sha256;
+use skreg_core::types::Namespace;
+use skreg_crypto::algorithm::KeyAlgorithm;
+
+use crate::config::CliConfig;
+use crate::keyfile::Passphrases;
+use crate::signer::Signer;
 
 /// Publisher keys loaded from disk or freshly generated.
 pub struct PublisherKeys {
-    /// PEM-encoded RSA private key.
-    pub private_key_pem: String,
+    /// How signatures are made: an unlocked key or a signing program.
+    pub signer: Signer,
     /// PEM-encoded leaf certificate.
     pub cert_pem: String,
     /// Certificate chain (leaf first, then any intermediates).
     pub cert_chain_pem: Vec<String>,
 }
 
-/// Returns `~/.skreg/keys/`.
+impl PublisherKeys {
+    /// The unencrypted PKCS#8 PEM private key, unless a signing program
+    /// holds it.
+    #[must_use]
+    pub fn private_key_pem(&self) -> Option<&str> {
+        match &self.signer {
+            Signer::Key(pem) => Some(pem),
+            Signer::Program(_) => None,
+        }
+    }
+
+    /// Sign a hex SHA-256 digest with the publisher key, returning the hex
+    /// signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if signing fails; see [`Signer::sign_digest`].
+    pub fn sign_digest(&self, digest_hex: &str) -> Result<String> {
+        self.signer.sign_digest(digest_hex, &self.cert_pem)
+    }
+}
+
+/// Files making up a key store, moved together when legacy keys migrate.
+const STORE_FILES: [&str; 4] = [
+    "publisher.key",
+    "publisher.crt",
+    "publisher-ca.crt",
+    "pending",
+];
+
+/// Returns `~/.skreg/keys/`, the root of all key stores.
 ///
 /// # Errors
 ///
@@ -25,51 +67,190 @@ pub fn keys_dir() -> Result<PathBuf> {
     Ok(home.join(".skreg").join("keys"))
 }
 
+/// Returns the key store of the active context in `cfg`:
+/// `~/.skreg/keys/<registry>/<namespace>/`, so each namespace on each
+/// registry signs with its own key. Without a config (e.g. packing before
+/// `skreg login`) the store is `~/.skreg/keys/local/`.
+///
+/// Keys left directly in `~/.skreg/keys/` by older versions are moved into
+/// the store their certificate belongs to (see [`legacy_store`]), with a
+/// notice on stderr.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined, the active
+/// context has no valid namespace, or migrating legacy keys fails.
+pub fn store_dir(cfg: Option<&CliConfig>) -> Result<PathBuf> {
+    let root = keys_dir()?;
+    let store = match cfg {
+        Some(cfg) => store_path(&root, cfg.registry(), cfg.namespace())?,
+        None => root.join("local"),
+    };
+    migrate_legacy_keys(&root, cfg)?;
+    Ok(store)
+}
+
+/// Path of the key store for `namespace` on `registry` under `root`.
+///
+/// # Errors
+///
+/// Returns an error if `namespace` is not a valid namespace slug.
+pub fn store_path(root: &Path, registry: &str, namespace: &str) -> Result<PathBuf> {
+    if namespace.is_empty() {
+        bail!("the active context has no namespace — run `skreg login <namespace>` first");
+    }
+    let namespace =
+        Namespace::new(namespace).with_context(|| format!("invalid namespace {namespace:?}"))?;
+    Ok(root
+        .join(registry_dir_name(registry))
+        .join(namespace.as_str()))
+}
+
+/// Directory name for `registry`: its URL without the scheme, with anything
+/// but letters, digits, `.` and `-` replaced by `_`.
+fn registry_dir_name(registry: &str) -> String {
+    let url = registry.trim().trim_end_matches('/');
+    let host = url.split_once("://").map_or(url, |(_, rest)| rest);
+    host.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The store legacy keys in `root` belong to: the one for the namespace in
+/// their certificate's Common Name, on the registry of the `default`
+/// context if that context is for this namespace, else of the active
+/// context, else of the only context for it. Without a config that is
+/// `root/local` for a `local` certificate.
+///
+/// Returns `None` when no store fits — the certificate is unreadable, no
+/// context is for its namespace, or several on different registries are.
+fn legacy_store(root: &Path, cfg: Option<&CliConfig>) -> Option<PathBuf> {
+    let cert_pem = std::fs::read_to_string(root.join("publisher.crt")).ok()?;
+    let namespace = cert_common_name(&cert_pem).ok()?;
+    let Some(cfg) = cfg else {
+        return (namespace == "local").then(|| root.join("local"));
+    };
+    let preferred = ["default", cfg.active_context.as_str()]
+        .into_iter()
+        .filter_map(|name| cfg.contexts.get(name))
+        .find(|ctx| ctx.namespace == namespace);
+    let registry = if let Some(ctx) = preferred {
+        &ctx.registry
+    } else {
+        let mut registries = cfg
+            .contexts
+            .values()
+            .filter(|ctx| ctx.namespace == namespace)
+            .map(|ctx| &ctx.registry);
+        let first = registries.next()?;
+        if registries.any(|other| registry_dir_name(other) != registry_dir_name(first)) {
+            return None;
+        }
+        first
+    };
+    store_path(root, registry, &namespace).ok()
+}
+
+/// Move keys from the single pre-store layout in `root` into the store they
+/// belong to (see [`legacy_store`]), unless that store already has keys of
+/// its own. When no store fits, the keys are left in place and the user is
+/// told to move them.
+fn migrate_legacy_keys(root: &Path, cfg: Option<&CliConfig>) -> Result<()> {
+    let has_legacy = ["publisher.key", "publisher.crt"]
+        .iter()
+        .any(|file| root.join(file).exists());
+    if !has_legacy {
+        return Ok(());
+    }
+    let Some(store) = legacy_store(root, cfg) else {
+        eprintln!(
+            "skreg: publisher keys in {} match no single context; move them into \
+             {}/<registry>/<namespace>/ for the namespace they sign for",
+            root.display(),
+            root.display()
+        );
+        return Ok(());
+    };
+    if store.join("publisher.crt").exists() {
+        return Ok(());
+    }
+    create_dir_secure(&store)?;
+    for file in STORE_FILES {
+        let from = root.join(file);
+        if from.exists() {
+            let to = store.join(file);
+            std::fs::rename(&from, &to)
+                .with_context(|| format!("moving {} to {}", from.display(), to.display()))?;
+        }
+    }
+    eprintln!(
+        "skreg: moved publisher keys from {} to {}; keys are now kept per registry and namespace",
+        root.display(),
+        store.display()
+    );
+    Ok(())
+}
+
 /// Load or generate publisher keys in `dir`.
 ///
-/// If `publisher.key` and `publisher.crt` already exist they are loaded.
-/// If `publisher-ca.crt` also exists the cert chain is `[leaf, ca]`.
+/// If `publisher.key` and `publisher.crt` already exist they are loaded, and
+/// an encrypted key is unlocked with `passphrases`. If `publisher-ca.crt`
+/// also exists the cert chain is `[leaf, ca]`.
 ///
-/// If any key or cert is missing, a fresh RSA-2048 key and self-signed
-/// certificate are generated and written to disk. A notice is printed to
-/// stderr.
+/// If any key or cert is missing, a fresh key of `algorithm` (RSA-2048 when
+/// `None`) and a self-signed certificate are generated and written to disk,
+/// the key encrypted as [`Passphrases::protect`] decides. A notice is
+/// printed to stderr.
 ///
 /// # Errors
 ///
-/// Returns an error if key generation or file I/O fails.
-pub fn ensure_keys_exist(dir: &Path, namespace: &str) -> Result<PublisherKeys> {
+/// Returns an error if key generation, unlocking or file I/O fails, or if
+/// `algorithm` is given and the existing key is of a different algorithm.
+pub fn ensure_keys_exist(
+    dir: &Path,
+    namespace: &str,
+    algorithm: Option<KeyAlgorithm>,
+    passphrases: Passphrases,
+) -> Result<PublisherKeys> {
     let key_path = dir.join("publisher.key");
     let cert_path = dir.join("publisher.crt");
-    let ca_path = dir.join("publisher-ca.crt");
 
     if key_path.exists() && cert_path.exists() {
-        let private_key_pem = std::fs::read_to_string(&key_path)
-            .with_context(|| format!("reading {}", key_path.display()))?;
-        let cert_pem = std::fs::read_to_string(&cert_path)
-            .with_context(|| format!("reading {}", cert_path.display()))?;
-        let cert_chain_pem = if ca_path.exists() {
-            let ca_pem = std::fs::read_to_string(&ca_path)
-                .with_context(|| format!("reading {}", ca_path.display()))?;
-            vec![cert_pem.clone(), ca_pem]
-        } else {
-            vec![cert_pem.clone()]
-        };
+        let private_key_pem = read_private_key(&key_path, passphrases)?;
+        if let Some(wanted) = algorithm {
+            let existing = key_algorithm(&private_key_pem)?;
+            if existing != wanted {
+                bail!(
+                    "{} is an {existing} key, not {wanted}; move it aside to generate a new one",
+                    key_path.display()
+                );
+            }
+        }
+        let (cert_pem, cert_chain_pem) = read_cert_chain(dir)?;
         return Ok(PublisherKeys {
-            private_key_pem,
+            signer: Signer::Key(private_key_pem),
             cert_pem,
             cert_chain_pem,
         });
     }
 
+    let algorithm = algorithm.unwrap_or_default();
     eprintln!(
-        "skreg: no publisher keys found in {}; generating RSA-2048 key pair…",
+        "skreg: no publisher keys found in {}; generating {algorithm} key pair…",
         dir.display()
     );
 
-    let (private_key_pem, cert_pem) = generate_key_and_cert(namespace)?;
+    let (private_key_pem, cert_pem) = generate_key_and_cert(namespace, algorithm)?;
+    let stored_key_pem = passphrases.protect(&private_key_pem)?;
 
     create_dir_secure(dir)?;
-    write_secure(&key_path, &private_key_pem)?;
+    write_secure(&key_path, &stored_key_pem)?;
     write_file(&cert_path, &cert_pem)?;
 
     eprintln!(
@@ -79,49 +260,206 @@ pub fn ensure_keys_exist(dir: &Path, namespace: &str) -> Result<PublisherKeys> {
     );
 
     Ok(PublisherKeys {
-        private_key_pem,
+        signer: Signer::Key(private_key_pem),
         cert_pem: cert_pem.clone(),
         cert_chain_pem: vec![cert_pem],
     })
 }
 
-/// Load keys from explicit paths.
+/// Load the certificates in `dir` for keys held by the signing `program`.
+///
+/// No key file is read or generated: `publisher.crt` must already exist,
+/// for example from `skreg certify --csr`.
+///
+/// # Errors
+///
+/// Returns an error if `publisher.crt` is missing or cannot be read.
+pub fn program_keys(dir: &Path, program: String) -> Result<PublisherKeys> {
+    let cert_path = dir.join("publisher.crt");
+    if !cert_path.exists() {
+        bail!(
+            "a signing program is configured but {} is missing; \
+             run `skreg certify --csr FILE` with a CSR from the program's key",
+            cert_path.display()
+        );
+    }
+    let (cert_pem, cert_chain_pem) = read_cert_chain(dir)?;
+    Ok(PublisherKeys {
+        signer: Signer::Program(program),
+        cert_pem,
+        cert_chain_pem,
+    })
+}
+
+/// Load keys from explicit paths, unlocking an encrypted key with
+/// `passphrases`.
 ///
 /// # Errors
 ///
-/// Returns an error if either file cannot be read.
-pub fn load_explicit_keys(key_path: &Path, cert_path: &Path) -> Result<PublisherKeys> {
-    let private_key_pem = std::fs::read_to_string(key_path)
-        .with_context(|| format!("reading {}", key_path.display()))?;
+/// Returns an error if either file cannot be read or the key cannot be
+/// unlocked.
+pub fn load_explicit_keys(
+    key_path: &Path,
+    cert_path: &Path,
+    passphrases: Passphrases,
+) -> Result<PublisherKeys> {
+    let private_key_pem = read_private_key(key_path, passphrases)?;
     let cert_pem = std::fs::read_to_string(cert_path)
         .with_context(|| format!("reading {}", cert_path.display()))?;
     let cert_chain_pem = vec![cert_pem.clone()];
     Ok(PublisherKeys {
-        private_key_pem,
+        signer: Signer::Key(private_key_pem),
         cert_pem,
         cert_chain_pem,
     })
 }
 
-/// Generate a fresh RSA-2048 private key and a self-signed certificate valid
-/// for 90 days.  Returns `(private_key_pem, cert_pem)`.
-fn generate_key_and_cert(namespace: &str) -> Result<(String, String)> {
-    use rand::rngs::OsRng;
-    use rsa::pkcs8::EncodePrivateKey;
+/// Read the private key at `path`, unlocking it with `passphrases` if it is
+/// encrypted.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or the key cannot be
+/// unlocked.
+pub fn read_private_key(path: &Path, passphrases: Passphrases) -> Result<String> {
+    let pem =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    passphrases.unlock(pem, &path.display().to_string())
+}
 
-    let private_key =
-        rsa::RsaPrivateKey::new(&mut OsRng, 2048).context("generating RSA-2048 key")?;
-    let private_key_pem = private_key
-        .to_pkcs8_pem(rsa::pkcs8::LineEnding::LF)
-        .context("encoding private key to PEM")?
-        .to_string();
+/// Read `publisher.crt` from `dir`, returning it and the chain `[leaf, ca]`
+/// if `publisher-ca.crt` exists, else `[leaf]`.
+fn read_cert_chain(dir: &Path) -> Result<(String, Vec<String>)> {
+    let cert_path = dir.join("publisher.crt");
+    let ca_path = dir.join("publisher-ca.crt");
+    let cert_pem = std::fs::read_to_string(&cert_path)
+        .with_context(|| format!("reading {}", cert_path.display()))?;
+    let cert_chain_pem = if ca_path.exists() {
+        let ca_pem = std::fs::read_to_string(&ca_path)
+            .with_context(|| format!("reading {}", ca_path.display()))?;
+        vec![cert_pem.clone(), ca_pem]
+    } else {
+        vec![cert_pem.clone()]
+    };
+    Ok((cert_pem, cert_chain_pem))
+}
 
+/// Generate a fresh private key of `algorithm` and a self-signed certificate
+/// valid for 90 days.  Returns `(private_key_pem, cert_pem)`.
+///
+/// # Errors
+///
+/// Returns an error if key or cert generation fails.
+pub(crate) fn generate_key_and_cert(
+    namespace: &str,
+    algorithm: KeyAlgorithm,
+) -> Result<(String, String)> {
+    let private_key_pem = generate_private_key(algorithm)?;
     let cert_pem = generate_self_signed_cert(namespace, &private_key_pem)?;
     Ok((private_key_pem, cert_pem))
 }
 
+/// Generate a private key of `algorithm` as PKCS#8 PEM.  RSA keys are 2048
+/// bits.
+fn generate_private_key(algorithm: KeyAlgorithm) -> Result<String> {
+    use rand::rngs::OsRng;
+    use rsa::pkcs8::EncodePrivateKey;
+
+    let rcgen_alg = match algorithm {
+        KeyAlgorithm::Rsa => {
+            let private_key =
+                rsa::RsaPrivateKey::new(&mut OsRng, 2048).context("generating RSA-2048 key")?;
+            return Ok(private_key
+                .to_pkcs8_pem(rsa::pkcs8::LineEnding::LF)
+                .context("encoding private key to PEM")?
+                .to_string());
+        }
+        KeyAlgorithm::Ed25519 => &rcgen::PKCS_ED25519,
+        KeyAlgorithm::EcdsaP256 => &rcgen::PKCS_ECDSA_P256_SHA256,
+    };
+    let key_pair = rcgen::KeyPair::generate(rcgen_alg)
+        .with_context(|| format!("generating {algorithm} key"))?;
+    Ok(key_pair.serialize_pem())
+}
+
+/// Identify the algorithm of a PKCS#8 PEM private key.
+///
+/// # Errors
+///
+/// Returns an error if the key cannot be parsed or is of an unsupported
+/// algorithm.
+pub fn key_algorithm(private_key_pem: &str) -> Result<KeyAlgorithm> {
+    let key_pair = rcgen::KeyPair::from_pem(private_key_pem).context("parsing private key PEM")?;
+    let alg = key_pair.algorithm();
+    if alg == &rcgen::PKCS_ED25519 {
+        Ok(KeyAlgorithm::Ed25519)
+    } else if alg == &rcgen::PKCS_ECDSA_P256_SHA256 {
+        Ok(KeyAlgorithm::EcdsaP256)
+    } else if alg == &rcgen::PKCS_RSA_SHA256 {
+        Ok(KeyAlgorithm::Rsa)
+    } else {
+        bail!("unsupported private key algorithm (expected RSA, Ed25519 or ECDSA P-256)")
+    }
+}
+
+/// Identify the algorithm of the key certified by a PEM certificate.
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be parsed or its key is of an
+/// unsupported algorithm.
+pub fn cert_algorithm(cert_pem: &str) -> Result<KeyAlgorithm> {
+    use der::DecodePem;
+
+    let cert = x509_cert::Certificate::from_pem(cert_pem).context("parsing certificate PEM")?;
+    Ok(KeyAlgorithm::of_spki(
+        &cert.tbs_certificate.subject_public_key_info,
+    )?)
+}
+
+/// The Common Name of a PEM certificate's subject — the namespace it was
+/// issued for.
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be parsed or has no Common
+/// Name.
+fn cert_common_name(cert_pem: &str) -> Result<String> {
+    use der::asn1::{PrintableStringRef, Utf8StringRef};
+    use der::DecodePem;
+
+    let cert = x509_cert::Certificate::from_pem(cert_pem).context("parsing certificate PEM")?;
+    let cn_oid = der::asn1::ObjectIdentifier::new_unwrap("2.5.4.3");
+    cert.tbs_certificate
+        .subject
+        .0
+        .iter()
+        .flat_map(|rdn| rdn.0.iter())
+        .filter(|atv| atv.oid == cn_oid)
+        .find_map(|atv| {
+            Utf8StringRef::try_from(&atv.value)
+                .map(|s| s.as_str().to_owned())
+                .or_else(|_| {
+                    PrintableStringRef::try_from(&atv.value).map(|s| s.as_str().to_owned())
+                })
+                .ok()
+        })
+        .context("certificate has no Common Name")
+}
+
+/// Compute the SHA-256 SPKI fingerprint of a PEM certificate — the
+/// fingerprint the registry pins.
+///
+/// # Errors
+///
+/// Returns an error if the cert cannot be parsed.
+pub fn spki_fingerprint(cert_pem: &str) -> Result<String> {
+    skreg_crypto::rotation::spki_fingerprint(cert_pem).context("parsing certificate PEM")
+}
+
 /// Generate a self-signed certificate for `namespace` using the provided PEM
-/// private key.  Uses `rcgen::PKCS_RSA_SHA256` (PKCS#1) and 90-day validity.
+/// private key.  The certificate is signed with the key's own algorithm
+/// (SHA256-with-RSA for RSA keys) and is valid for 90 days.
 ///
 /// # Errors
 ///
@@ -150,7 +488,7 @@ pub(crate) fn generate_self_signed_cert(namespace: &str, key_pem: &str) -> Resul
     );
 
     let mut params = CertificateParams::default();
-    params.alg = &rcgen::PKCS_RSA_SHA256;
+    params.alg = key_pair.algorithm();
     params.key_pair = Some(key_pair);
     params.not_before = not_before;
     params.not_after = not_after;
@@ -164,34 +502,69 @@ pub(crate) fn generate_self_signed_cert(namespace: &str, key_pem: &str) -> Resul
         .context("serializing certificate to PEM")
 }
 
-/// Sign a SHA-256 digest (hex-encoded) with RSA-PSS using the provided PEM
-/// private key.  Returns the hex-encoded signature.
+/// Sign a SHA-256 digest (hex-encoded) with the provided PEM private key,
+/// using the scheme for the key's algorithm.  Returns the hex-encoded
+/// signature.
 ///
 /// # Errors
 ///
 /// Returns an error if the key cannot be parsed or signing fails.
-pub fn pss_sign_digest(private_key_pem: &str, digest_hex: &str) -> Result<String> {
+pub fn sign_digest(private_key_pem: &str, digest_hex: &str) -> Result<String> {
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, Ed25519KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    let digest_bytes = hex::decode(digest_hex).context("decoding digest hex")?;
+
+    let sig = match key_algorithm(private_key_pem)? {
+        KeyAlgorithm::Rsa => return pss_sign_digest(private_key_pem, &digest_bytes),
+        KeyAlgorithm::Ed25519 => {
+            let pkcs8 = rcgen::KeyPair::from_pem(private_key_pem)
+                .context("parsing private key PEM")?
+                .serialize_der();
+            Ed25519KeyPair::from_pkcs8_maybe_unchecked(&pkcs8)
+                .map_err(|e| anyhow::anyhow!("parsing Ed25519 key: {e}"))?
+                .sign(&digest_bytes)
+                .as_ref()
+                .to_vec()
+        }
+        KeyAlgorithm::EcdsaP256 => {
+            let pkcs8 = rcgen::KeyPair::from_pem(private_key_pem)
+                .context("parsing private key PEM")?
+                .serialize_der();
+            let rng = SystemRandom::new();
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &pkcs8, &rng)
+                .map_err(|e| anyhow::anyhow!("parsing ECDSA P-256 key: {e}"))?
+                .sign(&rng, &digest_bytes)
+                .map_err(|_| anyhow::anyhow!("signing digest"))?
+                .as_ref()
+                .to_vec()
+        }
+    };
+
+    Ok(hex::encode(sig))
+}
+
+/// Sign raw digest bytes with RSA-PSS using an RSA PKCS#8 PEM private key.
+fn pss_sign_digest(private_key_pem: &str, digest_bytes: &[u8]) -> Result<String> {
     use rand::rngs::OsRng;
     use rsa::pkcs8::DecodePrivateKey;
     use rsa::pss::BlindedSigningKey;
     use rsa::signature::hazmat::RandomizedPrehashSigner;
     use rsa::signature::SignatureEncoding;
 
-    let digest_bytes = hex::decode(digest_hex).context("decoding digest hex")?;
-
     let private_key =
         rsa::RsaPrivateKey::from_pkcs8_pem(private_key_pem).context("parsing private key PEM")?;
     let signing_key = BlindedSigningKey::<Sha256>::new(private_key);
 
     let sig = signing_key
-        .sign_prehash_with_rng(&mut OsRng, &digest_bytes)
+        .sign_prehash_with_rng(&mut OsRng, digest_bytes)
         .context("signing digest")?;
 
     Ok(hex::encode(sig.to_bytes()))
 }
 
 /// Create directory (and parents) with mode 0o700 on Unix.
-fn create_dir_secure(dir: &Path) -> Result<()> {
+pub(crate) fn create_dir_secure(dir: &Path) -> Result<()> {
     std::fs::create_dir_all(dir)
         .with_context(|| format!("creating directory {}", dir.display()))?;
     #[cfg(unix)]
@@ -204,7 +577,7 @@ fn create_dir_secure(dir: &Path) -> Result<()> {
 }
 
 /// Write file with mode 0o600 on Unix.
-fn write_secure(path: &Path, contents: &str) -> Result<()> {
+pub(crate) fn write_secure(path: &Path, contents: &str) -> Result<()> {
     std::fs::write(path, contents).with_context(|| format!("writing {}", path.display()))?;
     #[cfg(unix)]
     {
@@ -216,7 +589,7 @@ fn write_secure(path: &Path, contents: &str) -> Result<()> {
 }
 
 /// Write file with default permissions.
-fn write_file(path: &Path, contents: &str) -> Result<()> {
+pub(crate) fn write_file(path: &Path, contents: &str) -> Result<()> {
     std::fs::write(path, contents).with_context(|| format!("writing {}", path.display()))?;
     Ok(())
 }
@@ -229,10 +602,11 @@ mod tests {
     #[test]
     fn generates_key_and_cert_when_absent() {
         let dir = tempdir().unwrap();
-        let keys = ensure_keys_exist(dir.path(), "testns").unwrap();
+        let keys =
+            ensure_keys_exist(dir.path(), "testns", None, Passphrases::non_interactive()).unwrap();
         assert!(dir.path().join("publisher.key").exists());
         assert!(dir.path().join("publisher.crt").exists());
-        assert!(!keys.private_key_pem.is_empty());
+        assert!(!keys.private_key_pem().unwrap().is_empty());
         assert!(!keys.cert_pem.is_empty());
         assert_eq!(keys.cert_chain_pem.len(), 1);
     }
@@ -240,12 +614,12 @@ mod tests {
     #[test]
     fn returns_existing_keys_without_regenerating() {
         let dir = tempdir().unwrap();
-        ensure_keys_exist(dir.path(), "testns").unwrap();
+        ensure_keys_exist(dir.path(), "testns", None, Passphrases::non_interactive()).unwrap();
         let mtime1 = std::fs::metadata(dir.path().join("publisher.key"))
             .unwrap()
             .modified()
             .unwrap();
-        ensure_keys_exist(dir.path(), "testns").unwrap();
+        ensure_keys_exist(dir.path(), "testns", None, Passphrases::non_interactive()).unwrap();
         let mtime2 = std::fs::metadata(dir.path().join("publisher.key"))
             .unwrap()
             .modified()
@@ -257,11 +631,183 @@ mod tests {
     fn pss_sign_and_verify_roundtrip() {
         use sha2::Digest;
         let dir = tempdir().unwrap();
-        let keys = ensure_keys_exist(dir.path(), "testns").unwrap();
+        let keys =
+            ensure_keys_exist(dir.path(), "testns", None, Passphrases::non_interactive()).unwrap();
         let digest_hex = hex::encode(sha2::Sha256::new().finalize());
-        let sig_hex = pss_sign_digest(&keys.private_key_pem, &digest_hex).unwrap();
+        let sig_hex = keys.sign_digest(&digest_hex).unwrap();
         assert!(!sig_hex.is_empty());
         // RSA-2048 signatures are 256 bytes = 512 hex chars
         assert_eq!(sig_hex.len(), 512);
     }
+
+    #[test]
+    fn ed25519_and_p256_keys_sign_verifiable_digests() {
+        use skreg_core::types::Sha256Digest;
+        use skreg_crypto::verifier::{RsaPssVerifier, SignatureVerifier};
+
+        for algorithm in [KeyAlgorithm::Ed25519, KeyAlgorithm::EcdsaP256] {
+            let dir = tempdir().unwrap();
+            let keys = ensure_keys_exist(
+                dir.path(),
+                "testns",
+                Some(algorithm),
+                Passphrases::non_interactive(),
+            )
+            .unwrap();
+            assert_eq!(
+                key_algorithm(keys.private_key_pem().unwrap()).unwrap(),
+                algorithm
+            );
+
+            let digest = Sha256Digest::of(b"payload");
+            let sig_hex = keys.sign_digest(digest.as_hex()).unwrap();
+            let sig = hex::decode(sig_hex).unwrap();
+            let signer = RsaPssVerifier::new()
+                .verify_with_namespace(&digest, &sig, &keys.cert_chain_pem, "testns")
+                .unwrap_or_else(|e| panic!("{algorithm}: {e}"));
+            assert!(!signer.ca_verified);
+        }
+    }
+
+    #[test]
+    fn rejects_existing_key_of_another_algorithm() {
+        let dir = tempdir().unwrap();
+        ensure_keys_exist(
+            dir.path(),
+            "testns",
+            Some(KeyAlgorithm::Ed25519),
+            Passphrases::non_interactive(),
+        )
+        .unwrap();
+        assert!(
+            ensure_keys_exist(dir.path(), "testns", None, Passphrases::non_interactive()).is_ok()
+        );
+        let err = ensure_keys_exist(
+            dir.path(),
+            "testns",
+            Some(KeyAlgorithm::EcdsaP256),
+            Passphrases::non_interactive(),
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("not ecdsa-p256"), "{err}");
+    }
+
+    #[test]
+    fn unlocks_encrypted_keys_and_refuses_without_a_passphrase() {
+        let dir = tempdir().unwrap();
+        let (key_pem, cert_pem) = generate_key_and_cert("testns", KeyAlgorithm::Ed25519).unwrap();
+        let encrypted = crate::keyfile::encrypt(&key_pem, "hunter2").unwrap();
+        std::fs::write(dir.path().join("publisher.key"), encrypted).unwrap();
+        std::fs::write(dir.path().join("publisher.crt"), &cert_pem).unwrap();
+        assert_eq!(cert_algorithm(&cert_pem).unwrap(), KeyAlgorithm::Ed25519);
+
+        let keys = ensure_keys_exist(
+            dir.path(),
+            "testns",
+            None,
+            Passphrases::fixed(Some("hunter2")),
+        )
+        .unwrap();
+        assert_eq!(keys.private_key_pem(), Some(key_pem.as_str()));
+
+        let err = ensure_keys_exist(
+            dir.path(),
+            "testns",
+            None,
+            Passphrases::fixed(Some("wrong")),
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("decrypting"), "{err}");
+
+        let err = ensure_keys_exist(dir.path(), "testns", None, Passphrases::fixed(None))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("SKREG_KEY_PASSPHRASE"), "{err}");
+    }
+
+    #[test]
+    fn stores_are_per_registry_and_namespace() {
+        let root = Path::new("/keys");
+        assert_eq!(
+            store_path(root, "https://api.skreg.ai", "acme").unwrap(),
+            root.join("api.skreg.ai").join("acme")
+        );
+        assert_eq!(
+            store_path(root, "http://LocalHost:8080/", "acme").unwrap(),
+            root.join("localhost_8080").join("acme")
+        );
+        assert!(store_path(root, "https://api.skreg.ai", "").is_err());
+        assert!(store_path(root, "https://api.skreg.ai", "../acme").is_err());
+    }
+
+    fn context(registry: &str, namespace: &str) -> crate::config::ContextConfig {
+        crate::config::ContextConfig {
+            registry: registry.to_owned(),
+            namespace: namespace.to_owned(),
+            api_key: String::new(),
+            root_ca_pem: None,
+            signing_program: None,
+        }
+    }
+
+    fn config(active: &str, contexts: &[(&str, &str, &str)]) -> CliConfig {
+        CliConfig {
+            active_context: active.to_owned(),
+            contexts: contexts
+                .iter()
+                .map(|(name, registry, ns)| ((*name).to_owned(), context(registry, ns)))
+                .collect(),
+            policy: crate::config::PolicyConfig::default(),
+        }
+    }
+
+    #[test]
+    fn legacy_keys_move_into_the_store_of_their_namespace() {
+        let root = tempdir().unwrap();
+        ensure_keys_exist(root.path(), "acme", None, Passphrases::non_interactive()).unwrap();
+        let cert_pem = std::fs::read_to_string(root.path().join("publisher.crt")).unwrap();
+
+        // The active context is for another namespace; the keys still go to
+        // the default context's store for `acme`.
+        let cfg = config(
+            "work",
+            &[
+                ("default", "https://api.skreg.ai", "acme"),
+                ("work", "https://internal.example", "other"),
+            ],
+        );
+        migrate_legacy_keys(root.path(), Some(&cfg)).unwrap();
+        assert!(!root.path().join("publisher.key").exists());
+        let store = store_path(root.path(), "https://api.skreg.ai", "acme").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(store.join("publisher.crt")).unwrap(),
+            cert_pem
+        );
+        let other = store_path(root.path(), "https://internal.example", "other").unwrap();
+        assert!(!other.exists());
+    }
+
+    #[test]
+    fn legacy_keys_stay_put_without_a_matching_context() {
+        let root = tempdir().unwrap();
+        ensure_keys_exist(root.path(), "acme", None, Passphrases::non_interactive()).unwrap();
+
+        let unrelated = config("work", &[("work", "https://api.skreg.ai", "other")]);
+        migrate_legacy_keys(root.path(), Some(&unrelated)).unwrap();
+        migrate_legacy_keys(root.path(), None).unwrap();
+        // Two non-default contexts for `acme` on different registries: ambiguous.
+        let ambiguous = config(
+            "work",
+            &[
+                ("a", "https://api.skreg.ai", "acme"),
+                ("b", "https://internal.example", "acme"),
+                ("work", "https://api.skreg.ai", "other"),
+            ],
+        );
+        migrate_legacy_keys(root.path(), Some(&ambiguous)).unwrap();
+        assert!(root.path().join("publisher.key").exists());
+        assert!(root.path().join("publisher.crt").exists());
+    }
 }
@@ -6,11 +6,13 @@
 pub mod commands;
 /// CLI configuration — read/write `~/.skreg/config.toml`.
 pub mod config;
-/// SKILL.md frontmatter parser — extracts metadata from YAML frontmatter blocks.
-pub mod frontmatter;
-/// Publisher key management — auto-keygen and RSA-PSS signing.
+/// Passphrase-encrypted PKCS#8 private key files.
+pub mod keyfile;
+/// Publisher key management — auto-keygen and signing.
 pub mod keys;
 /// Symlink creation, removal, and tracking in `~/.skreg/links.toml`.
 pub mod linker;
+/// Signing with a key file or an external signing program.
+pub mod signer;
 /// Re-export installer from skreg-client for backwards compatibility.
 pub use skreg_client::installer;
@@ -22,6 +22,26 @@ enum Commands {
         /// Path to PEM certificate (overrides auto-generated cert)
         #[arg(long, value_name = "FILE")]
         cert: Option<PathBuf>,
+        /// Rebuild the archive from its contents and fail unless the bytes match
+        #[arg(long)]
+        verify_reproducible: bool,
+        /// Archive compression (gzip | zstd); registries older than zstd
+        /// support only accept gzip
+        #[arg(long, value_name = "FORMAT", default_value = "gzip")]
+        format: skreg_pack::format::ArchiveFormat,
+    },
+    /// Run the registry's vetting checks locally
+    ///
+    /// Exits 0 when the registry would accept the package and 1 otherwise.
+    Check {
+        /// Skill source directory or .skill file (defaults to the current directory)
+        path: Option<PathBuf>,
+        /// Output format (human | json | sarif)
+        #[arg(long, value_name = "FORMAT", default_value = "human")]
+        format: String,
+        /// YARA rules directory (defaults to the rule pack bundled with skreg)
+        #[arg(long, value_name = "DIR")]
+        rules: Option<PathBuf>,
     },
     /// Register a namespace or re-authenticate
     Login { namespace: String },
@@ -42,6 +62,10 @@ enum Commands {
         /// Trust policy enforcement level (hint | confirm | strict)
         #[arg(long, value_name = "LEVEL")]
         enforcement: Option<String>,
+        /// Accept a publisher key not pinned for the namespace, even without
+        /// a rotation or key addition signed by a pinned key
+        #[arg(long)]
+        trust_new_key: bool,
     },
     /// List all tracked skill symlinks
     Links,
@@ -53,23 +77,84 @@ enum Commands {
         #[arg(value_name = "PACKAGE")]
         package_ref: String,
     },
-    /// Obtain a CA-issued publisher certificate
+    /// Obtain a CA-issued publisher certificate, or manage existing ones
+    #[command(args_conflicts_with_subcommands = true)]
     Certify {
-        /// Path to existing PEM private key (uses ~/.skreg/keys/publisher.key if omitted)
+        #[command(subcommand)]
+        command: Option<skreg_cli::commands::certify::CertifyCommands>,
+        /// Path to existing PEM private key (uses the context's key store if omitted)
         #[arg(long, value_name = "FILE")]
         key: Option<PathBuf>,
+        /// Algorithm of a newly generated key (rsa | ed25519 | ecdsa-p256);
+        /// an existing key must match it
+        #[arg(long, value_name = "ALG")]
+        algorithm: Option<skreg_crypto::algorithm::KeyAlgorithm>,
+        /// Send this PEM CSR instead of building one from a local key
+        /// (required when a signing program holds the key)
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["key", "algorithm"])]
+        csr: Option<PathBuf>,
     },
-    /// Rotate the publisher key (requires email confirmation)
+    /// Rotate the publisher key (requires email confirmation), or manage
+    /// pending rotations
+    #[command(args_conflicts_with_subcommands = true)]
     Rotate {
-        /// Path to new PEM private key (generates a fresh RSA-2048 key if omitted)
+        #[command(subcommand)]
+        command: Option<skreg_cli::commands::rotate::RotateCommands>,
+        /// Path to new PEM private key (generates a fresh key if omitted)
         #[arg(long, value_name = "FILE")]
         new_key: Option<PathBuf>,
+        /// Algorithm of the generated key (rsa | ed25519 | ecdsa-p256);
+        /// defaults to the current key's
+        #[arg(long, value_name = "ALG", conflicts_with = "new_key")]
+        algorithm: Option<skreg_crypto::algorithm::KeyAlgorithm>,
+        /// Signing program holding the new key, instead of a key file
+        #[arg(
+            long,
+            value_name = "COMMAND",
+            requires = "new_cert",
+            conflicts_with_all = ["new_key", "algorithm"]
+        )]
+        new_signing_program: Option<String>,
+        /// Path to the PEM certificate of the signing program's new key
+        #[arg(long, value_name = "FILE", requires = "new_signing_program")]
+        new_cert: Option<PathBuf>,
     },
+    /// Check installed skills against the registry advisory feed
+    ///
+    /// Exits 0 when clean, 1 when an advisory at or above --fail-on matches,
+    /// and 2 when the audit could not complete.
+    Audit {
+        /// Minimum severity that causes a non-zero exit (low | medium | high | critical)
+        #[arg(long, value_name = "LEVEL", default_value = "low")]
+        fail_on: String,
+        /// Print findings as JSON
+        #[arg(long)]
+        json: bool,
+        /// Use the cached feed without contacting the registry
+        #[arg(long)]
+        offline: bool,
+        /// Fail when the advisory feed was generated more than this many
+        /// hours ago (0 disables the check)
+        #[arg(long, value_name = "HOURS", default_value_t = 168)]
+        max_feed_age: u32,
+    },
+    /// Check that the registry's transparency log only grew since the last run
+    Verify,
     /// Manage registry contexts
     Context {
         #[command(subcommand)]
         command: skreg_cli::commands::context::ContextCommands,
     },
+    /// Manage publisher keys, kept per registry and namespace
+    Keys {
+        #[command(subcommand)]
+        command: skreg_cli::commands::keys::KeysCommands,
+    },
+    /// Manage the keys allowed to sign for the namespace
+    Signers {
+        #[command(subcommand)]
+        command: skreg_cli::commands::signers::SignersCommands,
+    },
 }
 
 #[tokio::main]
@@ -77,12 +162,38 @@ async fn main() -> anyhow::Result<()> {
     env_logger::init();
     let cli = Cli::parse();
     match cli.command {
-        Commands::Pack { key, cert } => {
-            skreg_cli::commands::pack::run_pack(
+        Commands::Pack {
+            key,
+            cert,
+            verify_reproducible,
+            format,
+        } => {
+            let output = skreg_cli::commands::pack::run_pack(
                 std::env::current_dir()?.as_path(),
                 key.as_deref(),
                 cert.as_deref(),
+                format,
+                cli.context.as_deref(),
             )?;
+            if verify_reproducible {
+                skreg_cli::commands::pack::verify_reproducible(&output)?;
+            }
+        }
+        Commands::Check {
+            path,
+            format,
+            rules,
+        } => {
+            let Some(format) = skreg_cli::commands::check::OutputFormat::parse(&format) else {
+                anyhow::bail!("unknown format {format:?} — expected human, json, or sarif");
+            };
+            let target = match path {
+                Some(p) => p,
+                None => std::env::current_dir()?,
+            };
+            if !skreg_cli::commands::check::run_check(&target, format, rules.as_deref())? {
+                std::process::exit(1);
+            }
         }
         Commands::Login { namespace } => {
             skreg_cli::commands::login::run_login(&namespace).await?;
@@ -97,6 +208,7 @@ async fn main() -> anyhow::Result<()> {
         Commands::Install {
             package_ref,
             enforcement,
+            trust_new_key,
         } => {
             let level = match enforcement.as_deref() {
                 None => None,
@@ -107,8 +219,13 @@ async fn main() -> anyhow::Result<()> {
                     "unknown enforcement level {other:?} — expected hint, confirm, or strict"
                 ),
             };
-            skreg_cli::commands::install::run_install(&package_ref, level, cli.context.as_deref())
-                .await?;
+            skreg_cli::commands::install::run_install(
+                &package_ref,
+                level,
+                trust_new_key,
+                cli.context.as_deref(),
+            )
+            .await?;
         }
         Commands::Links => {
             skreg_cli::commands::links::run_links()?;
@@ -119,17 +236,88 @@ async fn main() -> anyhow::Result<()> {
         Commands::Uninstall { package_ref } => {
             skreg_cli::commands::uninstall::run_uninstall(&package_ref)?;
         }
-        Commands::Certify { key } => {
-            skreg_cli::commands::certify::run_certify(key.as_deref(), cli.context.as_deref())
-                .await?;
+        Commands::Certify {
+            command: Some(command),
+            ..
+        } => {
+            skreg_cli::commands::certify::handle(command, cli.context.as_deref()).await?;
         }
-        Commands::Rotate { new_key } => {
-            skreg_cli::commands::rotate::run_rotate(new_key.as_deref(), cli.context.as_deref())
-                .await?;
+        Commands::Certify {
+            command: None,
+            key,
+            algorithm,
+            csr,
+        } => {
+            skreg_cli::commands::certify::run_certify(
+                key.as_deref(),
+                csr.as_deref(),
+                algorithm,
+                cli.context.as_deref(),
+            )
+            .await?;
+        }
+        Commands::Rotate {
+            command: Some(command),
+            ..
+        } => {
+            skreg_cli::commands::rotate::handle(command, cli.context.as_deref()).await?;
+        }
+        Commands::Rotate {
+            command: None,
+            new_key,
+            algorithm,
+            new_signing_program,
+            new_cert,
+        } => {
+            skreg_cli::commands::rotate::run_rotate(
+                new_key.as_deref(),
+                algorithm,
+                new_signing_program.as_deref(),
+                new_cert.as_deref(),
+                cli.context.as_deref(),
+            )
+            .await?;
+        }
+        Commands::Audit {
+            fail_on,
+            json,
+            offline,
+            max_feed_age,
+        } => {
+            let Some(threshold) = skreg_core::advisory::Severity::parse(&fail_on) else {
+                anyhow::bail!(
+                    "unknown severity {fail_on:?} — expected low, medium, high, or critical"
+                );
+            };
+            match skreg_cli::commands::audit::run_audit(
+                threshold,
+                json,
+                offline,
+                (max_feed_age > 0).then(|| chrono::Duration::hours(max_feed_age.into())),
+                cli.context.as_deref(),
+            )
+            .await
+            {
+                Ok(0) => {}
+                Ok(_) => std::process::exit(skreg_cli::commands::audit::EXIT_VULNERABLE),
+                Err(e) => {
+                    eprintln!("Error: {e:?}");
+                    std::process::exit(skreg_cli::commands::audit::EXIT_AUDIT_ERROR);
+                }
+            }
+        }
+        Commands::Verify => {
+            skreg_cli::commands::verify::run_verify(cli.context.as_deref()).await?;
         }
         Commands::Context { command } => {
             skreg_cli::commands::context::handle(command)?;
         }
+        Commands::Keys { command } => {
+            skreg_cli::commands::keys::handle(command, cli.context.as_deref())?;
+        }
+        Commands::Signers { command } => {
+            skreg_cli::commands::signers::handle(command, cli.context.as_deref()).await?;
+        }
     }
     Ok(())
 }
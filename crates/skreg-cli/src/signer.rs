@@ -0,0 +1,166 @@
+//! Signing with a local key file or an external signing program.
+//!
+//! A signing program keeps the publisher key out of `~/.skreg/keys/`: it can
+//! be a wrapper around a secret manager, a hardware token or an agent
+//! process, much like git's `gpg.program`. It is configured per context
+//! (`signing_program` in `~/.skreg/config.toml`) or with
+//! `SKREG_SIGNING_PROGRAM`, which takes precedence.
+//!
+//! The protocol:
+//!
+//! - the command is run through the shell (`sh -c`, or `cmd /C` on Windows);
+//! - it receives the lowercase hex SHA-256 digest and a newline on stdin;
+//! - it prints the hex signature on stdout, made with the scheme for its
+//!   key's algorithm (see [`skreg_crypto::algorithm`]);
+//! - a non-zero exit status means signing failed; stderr is passed through
+//!   so the program can prompt or explain.
+//!
+//! Every signature a program returns is checked against the publisher
+//! certificate before it is used.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use skreg_core::config::ContextConfig;
+use skreg_core::types::Sha256Digest;
+use skreg_crypto::algorithm::verify_digest;
+use x509_cert::der::DecodePem;
+use x509_cert::Certificate;
+
+/// Environment variable naming a signing program; overrides the context's.
+pub const PROGRAM_ENV: &str = "SKREG_SIGNING_PROGRAM";
+
+/// How publisher signatures are made.
+pub enum Signer {
+    /// An unencrypted PKCS#8 PEM private key held in memory.
+    Key(String),
+    /// A shell command speaking the signing-program protocol.
+    Program(String),
+}
+
+impl Signer {
+    /// Sign a hex SHA-256 digest, returning the hex signature. `cert_pem` is
+    /// the certificate of the signing key; a program's signature is checked
+    /// against it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if signing fails, or if a program fails, prints
+    /// something other than hex, or returns a signature that does not
+    /// verify against `cert_pem`.
+    pub fn sign_digest(&self, digest_hex: &str, cert_pem: &str) -> Result<String> {
+        match self {
+            Self::Key(private_key_pem) => crate::keys::sign_digest(private_key_pem, digest_hex),
+            Self::Program(program) => {
+                let sig_hex = run_program(program, digest_hex)?;
+                check_signature(digest_hex, &sig_hex, cert_pem).with_context(|| {
+                    format!("signing program `{program}` returned an invalid signature")
+                })?;
+                Ok(sig_hex)
+            }
+        }
+    }
+}
+
+/// The signing program to use: `SKREG_SIGNING_PROGRAM` if set, otherwise the
+/// context's `signing_program`.
+#[must_use]
+pub fn signing_program(context: Option<&ContextConfig>) -> Option<String> {
+    std::env::var(PROGRAM_ENV)
+        .ok()
+        .filter(|program| !program.trim().is_empty())
+        .or_else(|| context.and_then(|ctx| ctx.signing_program.clone()))
+}
+
+/// Run `program` with `digest_hex` on stdin and return the hex signature it
+/// prints.
+fn run_program(program: &str, digest_hex: &str) -> Result<String> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    let mut child = command
+        .arg(program)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("starting signing program `{program}`"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A program that exits without reading its input closes the pipe;
+        // its exit status reports the real problem.
+        let _ = writeln!(stdin, "{digest_hex}");
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("running signing program `{program}`"))?;
+    if !output.status.success() {
+        bail!("signing program `{program}` failed ({})", output.status);
+    }
+
+    let sig_hex = String::from_utf8(output.stdout)
+        .context("signing program output is not UTF-8")?
+        .trim()
+        .to_ascii_lowercase();
+    if sig_hex.is_empty() || hex::decode(&sig_hex).is_err() {
+        bail!("signing program `{program}` did not print a hex signature");
+    }
+    Ok(sig_hex)
+}
+
+/// Check that `sig_hex` is a signature over `digest_hex` by the key in
+/// `cert_pem`.
+pub(crate) fn check_signature(digest_hex: &str, sig_hex: &str, cert_pem: &str) -> Result<()> {
+    let cert = Certificate::from_pem(cert_pem).context("parsing publisher certificate")?;
+    let digest = Sha256Digest::from_hex(digest_hex).context("parsing digest")?;
+    let sig = hex::decode(sig_hex).context("decoding signature hex")?;
+    verify_digest(&cert.tbs_certificate.subject_public_key_info, &digest, &sig)?;
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use skreg_crypto::algorithm::KeyAlgorithm;
+
+    fn key_and_cert() -> (String, String) {
+        crate::keys::generate_key_and_cert("acme", KeyAlgorithm::Ed25519).unwrap()
+    }
+
+    #[test]
+    fn program_signature_is_checked_against_the_cert() {
+        let (key_pem, cert_pem) = key_and_cert();
+        let digest = Sha256Digest::of(b"payload");
+        // Ed25519 signatures are deterministic, so a canned answer is valid.
+        let sig_hex = crate::keys::sign_digest(&key_pem, digest.as_hex()).unwrap();
+        let signer = Signer::Program(format!("read digest; echo {sig_hex}"));
+        assert_eq!(
+            signer.sign_digest(digest.as_hex(), &cert_pem).unwrap(),
+            sig_hex
+        );
+
+        let (_, other_cert_pem) = key_and_cert();
+        assert!(signer
+            .sign_digest(digest.as_hex(), &other_cert_pem)
+            .is_err());
+    }
+
+    #[test]
+    fn program_failures_are_errors() {
+        let (_, cert_pem) = key_and_cert();
+        let digest = Sha256Digest::of(b"payload");
+        for program in ["exit 3", "echo not-hex", "true"] {
+            let err = Signer::Program(program.to_owned())
+                .sign_digest(digest.as_hex(), &cert_pem)
+                .unwrap_err();
+            assert!(err.to_string().contains(program), "{program}: {err}");
+        }
+    }
+}
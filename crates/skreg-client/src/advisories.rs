@@ -0,0 +1,172 @@
+//! Advisory feed verification and incremental local sync.
+
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use skreg_core::advisory::{AdvisoryCache, AdvisoryFeed, SignedAdvisoryFeed};
+use skreg_core::types::Sha256Digest;
+use skreg_crypto::verifier::RsaPssVerifier;
+
+use crate::client::RegistryClient;
+use crate::error::ClientError;
+
+/// Upper bound on feed pages fetched in one sync, guarding against a
+/// misbehaving registry that never stops reporting `has_more`.
+const MAX_PAGES: usize = 100;
+
+/// Verify a feed envelope against the root CA and parse its payload.
+///
+/// # Errors
+///
+/// Returns [`ClientError::Verify`] if the signature or signer certificate is
+/// invalid, or [`ClientError::Parse`] if the payload is malformed.
+pub fn verify_feed(
+    signed: &SignedAdvisoryFeed,
+    verifier: &RsaPssVerifier,
+) -> Result<AdvisoryFeed, ClientError> {
+    let digest = Sha256Digest::from_hex(&hex::encode(Sha256::digest(signed.feed.as_bytes())))
+        .map_err(|e| ClientError::Parse(e.to_string()))?;
+    let signature =
+        hex::decode(&signed.signature_hex).map_err(|e| ClientError::Parse(e.to_string()))?;
+    verifier.verify_registry_signed(&digest, &signature, &signed.signer_cert_pem)?;
+    serde_json::from_str(&signed.feed).map_err(|e| ClientError::Parse(e.to_string()))
+}
+
+/// Merge a verified feed page into `cache`, refusing one generated before the
+/// feed last cached, so a replayed old response cannot hide newer advisories.
+///
+/// # Errors
+///
+/// Returns [`ClientError::StaleFeed`] if `feed` is older than the cache.
+fn merge_page(cache: &mut AdvisoryCache, feed: AdvisoryFeed) -> Result<(), ClientError> {
+    if let Some(synced_at) = cache.synced_at {
+        if feed.generated_at < synced_at {
+            return Err(ClientError::StaleFeed(format!(
+                "generated at {}, before the cached feed from {synced_at}",
+                feed.generated_at
+            )));
+        }
+    }
+    cache.merge(feed);
+    Ok(())
+}
+
+/// Check that `cache` was synced from a feed generated at most `max_age`
+/// before `now`.
+///
+/// # Errors
+///
+/// Returns [`ClientError::StaleFeed`] if the feed is older, or the cache was
+/// never synced.
+pub fn check_feed_age(
+    cache: &AdvisoryCache,
+    max_age: Duration,
+    now: DateTime<Utc>,
+) -> Result<(), ClientError> {
+    match cache.synced_at {
+        Some(synced_at) if now - synced_at <= max_age => Ok(()),
+        Some(synced_at) => Err(ClientError::StaleFeed(format!(
+            "generated at {synced_at}, more than {} hour(s) ago",
+            max_age.num_hours()
+        ))),
+        None => Err(ClientError::StaleFeed("never synced".to_owned())),
+    }
+}
+
+/// Pull every advisory newer than the cached cursor, verify each page, and
+/// persist the merged cache to `cache_path`.
+///
+/// # Errors
+///
+/// Returns [`ClientError`] if a request fails, a page does not verify, or a
+/// page is older than the cached feed ([`ClientError::StaleFeed`]). Pages
+/// merged before the failure are not saved, so a bad page never advances the
+/// cursor.
+pub async fn sync_advisories(
+    client: &dyn RegistryClient,
+    verifier: &RsaPssVerifier,
+    cache_path: &Path,
+) -> Result<AdvisoryCache, ClientError> {
+    let mut cache =
+        AdvisoryCache::load(cache_path).map_err(|e| ClientError::Parse(e.to_string()))?;
+    for _ in 0..MAX_PAGES {
+        let signed = client.advisories(cache.since).await?;
+        let feed = verify_feed(&signed, verifier)?;
+        let has_more = feed.has_more;
+        merge_page(&mut cache, feed)?;
+        if !has_more {
+            break;
+        }
+    }
+    cache
+        .save(cache_path)
+        .map_err(|e| ClientError::Parse(e.to_string()))?;
+    Ok(cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_at(generated_at: DateTime<Utc>) -> AdvisoryFeed {
+        AdvisoryFeed {
+            advisories: Vec::new(),
+            next_since: 0,
+            has_more: false,
+            generated_at,
+        }
+    }
+
+    #[test]
+    fn merge_page_rejects_a_feed_older_than_the_cache() {
+        let now = Utc::now();
+        let mut cache = AdvisoryCache::default();
+        merge_page(&mut cache, feed_at(now)).unwrap();
+
+        let result = merge_page(&mut cache, feed_at(now - Duration::hours(1)));
+        assert!(matches!(result, Err(ClientError::StaleFeed(_))));
+        assert_eq!(cache.synced_at, Some(now));
+        merge_page(&mut cache, feed_at(now + Duration::hours(1))).unwrap();
+    }
+
+    #[test]
+    fn check_feed_age_rejects_old_and_unsynced_caches() {
+        let now = Utc::now();
+        let max_age = Duration::hours(24);
+        assert!(matches!(
+            check_feed_age(&AdvisoryCache::default(), max_age, now),
+            Err(ClientError::StaleFeed(_))
+        ));
+
+        let mut cache = AdvisoryCache::default();
+        cache.merge(feed_at(now - Duration::hours(23)));
+        check_feed_age(&cache, max_age, now).unwrap();
+        assert!(matches!(
+            check_feed_age(&cache, max_age, now + Duration::hours(2)),
+            Err(ClientError::StaleFeed(_))
+        ));
+    }
+
+    #[test]
+    fn verify_feed_rejects_garbage_signature() {
+        let signed = SignedAdvisoryFeed {
+            feed: "{}".to_owned(),
+            signature_hex: "zz".to_owned(),
+            signer_cert_pem: String::new(),
+        };
+        let result = verify_feed(&signed, &RsaPssVerifier::new());
+        assert!(matches!(result, Err(ClientError::Parse(_))));
+    }
+
+    #[test]
+    fn verify_feed_rejects_unknown_signer() {
+        let signed = SignedAdvisoryFeed {
+            feed: "{}".to_owned(),
+            signature_hex: "00".to_owned(),
+            signer_cert_pem: "not a cert".to_owned(),
+        };
+        let result = verify_feed(&signed, &RsaPssVerifier::new());
+        assert!(matches!(result, Err(ClientError::Verify(_))));
+    }
+}
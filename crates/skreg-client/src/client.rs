@@ -5,8 +5,16 @@ use std::pin::Pin;
 use std::sync::Arc;
 
 use log::debug;
+use skreg_core::advisory::SignedAdvisoryFeed;
+use skreg_core::key_log::SignedKeyLog;
+use skreg_core::keyset::KeyChangeRecord;
 use skreg_core::manifest::Manifest;
 use skreg_core::package_ref::PackageRef;
+use skreg_core::rotation::RotationRecord;
+use skreg_core::timestamp::SignedTimestamp;
+use skreg_core::transparency::{ConsistencyProof, InclusionProof, SignedTreeHead};
+use skreg_core::types::Sha256Digest;
+use skreg_pack::format::ArchiveFormat;
 
 use crate::error::ClientError;
 
@@ -18,10 +26,20 @@ pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 pub struct ResolvedVersion {
     /// The full manifest for this version.
     pub manifest: Manifest,
-    /// Signed tarball bytes.
+    /// Signed tarball bytes, gzip or Zstandard.
     pub tarball: Vec<u8>,
+    /// Expected SHA-256 of `tarball` in the format the registry served.
+    pub tarball_sha256: Sha256Digest,
     /// Detached signature bytes.
     pub signature: Vec<u8>,
+    /// Registry timestamp of the publisher signature, if one was recorded.
+    pub timestamp: Option<SignedTimestamp>,
+    /// Index of the version's publish entry in the transparency log, if the
+    /// version was logged.
+    pub log_index: Option<u64>,
+    /// Whether the registry marks the version as signed under the legacy
+    /// scheme; see [`skreg_crypto::package::SigningScheme`].
+    pub legacy_signature: bool,
 }
 
 /// A single result from a registry search.
@@ -93,6 +111,107 @@ pub trait RegistryClient: Send + Sync {
         name: &'a str,
         version: &'a str,
     ) -> BoxFuture<'a, Result<PackagePreview, ClientError>>;
+
+    /// Fetch one page of the signed advisory feed, starting after `since`.
+    ///
+    /// Calls `GET /v1/advisories?since={since}`. The envelope is returned
+    /// unverified; see [`crate::advisories::verify_feed`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError`] on network or parse failure.
+    fn advisories(&self, since: i64) -> BoxFuture<'_, Result<SignedAdvisoryFeed, ClientError>>;
+
+    /// Fetch the confirmed key rotations of namespace `ns`, oldest first.
+    ///
+    /// Calls `GET /v1/namespaces/{ns}/key-history`. The records are returned
+    /// unverified; see [`skreg_crypto::rotation::verify_rotation_chain`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError`] on network or parse failure.
+    fn key_history<'a>(
+        &'a self,
+        ns: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RotationRecord>, ClientError>>;
+
+    /// Fetch the confirmed key-set changes of namespace `ns`, oldest first.
+    ///
+    /// Calls `GET /v1/namespaces/{ns}/key-changes`. The records are returned
+    /// unverified; see [`skreg_crypto::rotation::verify_key_chain`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError`] on network or parse failure.
+    fn key_changes<'a>(
+        &'a self,
+        ns: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<KeyChangeRecord>, ClientError>>;
+
+    /// Fetch the signed key log of namespace `ns`.
+    ///
+    /// Calls `GET /v1/namespaces/{ns}/key-log`. The log is returned
+    /// unverified; see
+    /// [`skreg_crypto::verifier::SignatureVerifier::verify_key_log`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError`] on network or parse failure.
+    fn key_log<'a>(&'a self, ns: &'a str) -> BoxFuture<'a, Result<SignedKeyLog, ClientError>>;
+
+    /// Fetch the current signed head of the transparency log.
+    ///
+    /// Calls `GET /v1/log/tree-head`. The head is returned unverified; see
+    /// [`skreg_crypto::verifier::SignatureVerifier::verify_tree_head`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError`] on network or parse failure.
+    fn tree_head(&self) -> BoxFuture<'_, Result<SignedTreeHead, ClientError>>;
+
+    /// Fetch the audit path of log entry `index` in the tree of `tree_size`.
+    ///
+    /// Calls `GET /v1/log/proof/inclusion?index={index}&tree_size={tree_size}`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError`] on network or parse failure.
+    fn inclusion_proof(
+        &self,
+        index: u64,
+        tree_size: u64,
+    ) -> BoxFuture<'_, Result<InclusionProof, ClientError>>;
+
+    /// Fetch a proof that the log's tree of `first` entries is a prefix of
+    /// its tree of `second`.
+    ///
+    /// Calls `GET /v1/log/proof/consistency?first={first}&second={second}`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError`] on network or parse failure.
+    fn consistency_proof(
+        &self,
+        first: u64,
+        second: u64,
+    ) -> BoxFuture<'_, Result<ConsistencyProof, ClientError>>;
+}
+
+/// Body of `GET /v1/packages/:ns/:name/:version`: the manifest plus the
+/// digest of the Zstandard tarball and the signature timestamp, if the
+/// registry has them.
+#[derive(serde::Deserialize)]
+struct VersionMeta {
+    #[serde(flatten)]
+    manifest: Manifest,
+    #[serde(default)]
+    zstd_sha256: Option<Sha256Digest>,
+    #[serde(default)]
+    timestamp: Option<SignedTimestamp>,
+    #[serde(default)]
+    log_index: Option<u64>,
+    #[serde(default)]
+    legacy_signature: bool,
 }
 
 /// `reqwest`-backed implementation of [`RegistryClient`].
@@ -110,6 +229,24 @@ impl HttpRegistryClient {
             http: Arc::new(reqwest::Client::new()),
         }
     }
+
+    /// GET `url` with `query` and parse the JSON body.
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, u64)],
+    ) -> Result<T, ClientError> {
+        self.http
+            .get(url)
+            .query(query)
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(ClientError::Http)?
+            .json::<T>()
+            .await
+            .map_err(|e| ClientError::Parse(e.to_string()))
+    }
 }
 
 impl RegistryClient for HttpRegistryClient {
@@ -130,7 +267,7 @@ impl RegistryClient for HttpRegistryClient {
 
             debug!("resolving package from {meta_url}");
 
-            let manifest: Manifest = self
+            let meta: VersionMeta = self
                 .http
                 .get(&meta_url)
                 .send()
@@ -141,6 +278,8 @@ impl RegistryClient for HttpRegistryClient {
                 .await
                 .map_err(|e| ClientError::Parse(e.to_string()))?;
 
+            let manifest = meta.manifest;
+
             let dl_url = format!(
                 "{}/v1/download/{}/{}/{}",
                 self.base_url, pkg_ref.namespace, pkg_ref.name, manifest.version,
@@ -149,6 +288,14 @@ impl RegistryClient for HttpRegistryClient {
             let tarball = self
                 .http
                 .get(&dl_url)
+                .header(
+                    reqwest::header::ACCEPT,
+                    format!(
+                        "{}, {};q=0.9",
+                        ArchiveFormat::Zstd.media_type(),
+                        ArchiveFormat::Gzip.media_type()
+                    ),
+                )
                 .send()
                 .await?
                 .error_for_status()
@@ -156,6 +303,13 @@ impl RegistryClient for HttpRegistryClient {
                 .bytes()
                 .await?
                 .to_vec();
+            let tarball_sha256 = if ArchiveFormat::detect(&tarball) == Some(ArchiveFormat::Zstd) {
+                meta.zstd_sha256.ok_or_else(|| {
+                    ClientError::Parse("registry served zstd without a zstd_sha256".to_owned())
+                })?
+            } else {
+                manifest.sha256.clone()
+            };
             let sig_url = format!("{dl_url}/sig");
             let signature = self
                 .http
@@ -171,7 +325,11 @@ impl RegistryClient for HttpRegistryClient {
             Ok(ResolvedVersion {
                 manifest,
                 tarball,
+                tarball_sha256,
                 signature,
+                timestamp: meta.timestamp,
+                log_index: meta.log_index,
+                legacy_signature: meta.legacy_signature,
             })
         })
     }
@@ -231,6 +389,103 @@ impl RegistryClient for HttpRegistryClient {
                 .map_err(|e| ClientError::Parse(e.to_string()))
         })
     }
+
+    fn advisories(&self, since: i64) -> BoxFuture<'_, Result<SignedAdvisoryFeed, ClientError>> {
+        Box::pin(async move {
+            let url = format!("{}/v1/advisories", self.base_url);
+            debug!("fetching advisories from {url}?since={since}");
+            self.http
+                .get(&url)
+                .query(&[("since", since)])
+                .send()
+                .await?
+                .error_for_status()
+                .map_err(ClientError::Http)?
+                .json::<SignedAdvisoryFeed>()
+                .await
+                .map_err(|e| ClientError::Parse(e.to_string()))
+        })
+    }
+
+    fn key_history<'a>(
+        &'a self,
+        ns: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RotationRecord>, ClientError>> {
+        Box::pin(async move {
+            let url = format!("{}/v1/namespaces/{ns}/key-history", self.base_url);
+            debug!("fetching key history from {url}");
+            self.http
+                .get(&url)
+                .send()
+                .await?
+                .error_for_status()
+                .map_err(ClientError::Http)?
+                .json::<Vec<RotationRecord>>()
+                .await
+                .map_err(|e| ClientError::Parse(e.to_string()))
+        })
+    }
+
+    fn key_changes<'a>(
+        &'a self,
+        ns: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<KeyChangeRecord>, ClientError>> {
+        Box::pin(async move {
+            let url = format!("{}/v1/namespaces/{ns}/key-changes", self.base_url);
+            debug!("fetching key changes from {url}");
+            self.http
+                .get(&url)
+                .send()
+                .await?
+                .error_for_status()
+                .map_err(ClientError::Http)?
+                .json::<Vec<KeyChangeRecord>>()
+                .await
+                .map_err(|e| ClientError::Parse(e.to_string()))
+        })
+    }
+
+    fn key_log<'a>(&'a self, ns: &'a str) -> BoxFuture<'a, Result<SignedKeyLog, ClientError>> {
+        Box::pin(async move {
+            let url = format!("{}/v1/namespaces/{ns}/key-log", self.base_url);
+            debug!("fetching key log from {url}");
+            self.get_json(&url, &[]).await
+        })
+    }
+
+    fn tree_head(&self) -> BoxFuture<'_, Result<SignedTreeHead, ClientError>> {
+        Box::pin(async move {
+            let url = format!("{}/v1/log/tree-head", self.base_url);
+            debug!("fetching tree head from {url}");
+            self.get_json(&url, &[]).await
+        })
+    }
+
+    fn inclusion_proof(
+        &self,
+        index: u64,
+        tree_size: u64,
+    ) -> BoxFuture<'_, Result<InclusionProof, ClientError>> {
+        Box::pin(async move {
+            let url = format!("{}/v1/log/proof/inclusion", self.base_url);
+            debug!("fetching inclusion proof of entry {index} from {url}");
+            self.get_json(&url, &[("index", index), ("tree_size", tree_size)])
+                .await
+        })
+    }
+
+    fn consistency_proof(
+        &self,
+        first: u64,
+        second: u64,
+    ) -> BoxFuture<'_, Result<ConsistencyProof, ClientError>> {
+        Box::pin(async move {
+            let url = format!("{}/v1/log/proof/consistency", self.base_url);
+            debug!("fetching consistency proof {first} -> {second} from {url}");
+            self.get_json(&url, &[("first", first), ("second", second)])
+                .await
+        })
+    }
 }
 
 #[cfg(test)]
@@ -19,4 +19,11 @@ pub enum ClientError {
     /// The response body could not be parsed.
     #[error("failed to parse response: {0}")]
     Parse(String),
+    /// A signed registry document failed verification.
+    #[error("registry signature verification failed: {0}")]
+    Verify(#[from] skreg_crypto::error::VerifyError),
+    /// The advisory feed is older than the cached one, or than the maximum
+    /// age allowed.
+    #[error("stale advisory feed: {0}")]
+    StaleFeed(String),
 }
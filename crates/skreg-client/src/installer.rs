@@ -1,21 +1,27 @@
 //! Orchestrates the full package install pipeline.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
 use log::{debug, info};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 
+use skreg_core::config::CliConfig;
 use skreg_core::installed::{InstalledPackage, SignerKind};
-use skreg_core::manifest::Manifest;
+use skreg_core::manifest::{FileEntry, Manifest};
 use skreg_core::package_ref::PackageRef;
+use skreg_core::timestamp::Timestamp;
 use skreg_core::types::Sha256Digest;
-use skreg_crypto::verifier::SignatureVerifier;
+use skreg_crypto::package::{verify_files, verify_manifest, SigningScheme};
+use skreg_crypto::verifier::{RsaPssVerifier, SignatureVerifier};
 use skreg_pack::unpack::unpack_tarball_skip_manifest;
 
-use crate::client::RegistryClient;
+use crate::client::{RegistryClient, ResolvedVersion};
 use crate::error::ClientError;
+use crate::known_publishers::{default_known_publishers_path, PinError, PublisherPins};
+use crate::transparency::{default_tree_heads_path, verify_publish_logged, TreeHeadTracker};
 
 /// Errors that can occur during package installation.
 #[derive(Debug, Error)]
@@ -23,7 +29,7 @@ pub enum InstallError {
     /// The registry client returned an error.
     #[error("registry error: {0}")]
     Registry(#[from] ClientError),
-    /// The tarball sha256 does not match the manifest.
+    /// The tarball sha256 does not match the digest the registry published.
     #[error("sha256 mismatch: expected {expected}, got {actual}")]
     DigestMismatch {
         /// Expected hex digest from the manifest.
@@ -43,6 +49,153 @@ pub enum InstallError {
     /// A core validation error occurred.
     #[error("validation error: {0}")]
     Validation(#[from] skreg_core::types::ValidationError),
+    /// The publisher key is not one pinned for the namespace.
+    #[error("publisher key check failed: {0}")]
+    PublisherKey(#[from] PinError),
+    /// The signed manifest in the archive is for another package than the
+    /// one requested or resolved.
+    #[error("package mismatch: requested {requested}, archive is signed for {signed}")]
+    PackageMismatch {
+        /// The package requested, as resolved by the registry.
+        requested: String,
+        /// The package the archive's manifest names.
+        signed: String,
+    },
+    /// The registry gave no signature timestamp for a version it does not
+    /// mark as legacy.
+    #[error("{0} has no signature timestamp but is not a legacy version")]
+    MissingTimestamp(String),
+}
+
+/// When `resolved` was signed, from its signature timestamp.
+///
+/// Only versions the registry marks as legacy, vetted before signature
+/// timestamps, may have none; they predate the transparency log and the key
+/// log.
+///
+/// # Errors
+///
+/// Returns [`InstallError::MissingTimestamp`] if any other version has no
+/// timestamp, or [`InstallError::Crypto`] if it cannot be parsed.
+fn signing_time(
+    pkg_ref: &PackageRef,
+    resolved: &ResolvedVersion,
+) -> Result<Option<DateTime<Utc>>, InstallError> {
+    let Some(timestamp) = &resolved.timestamp else {
+        if resolved.legacy_signature {
+            return Ok(None);
+        }
+        return Err(InstallError::MissingTimestamp(pkg_ref.to_string()));
+    };
+    let timestamp = serde_json::from_str::<Timestamp>(&timestamp.timestamp)
+        .map_err(|e| InstallError::Crypto(e.to_string()))?;
+    Ok(Some(timestamp.signed_at))
+}
+
+/// Check that `signed`, the manifest in the archive, is for the package
+/// `pkg_ref` requests and the registry resolved it to (`resolved`).
+///
+/// # Errors
+///
+/// Returns [`InstallError::PackageMismatch`] if the namespace, name or
+/// version differ.
+fn check_package(
+    pkg_ref: &PackageRef,
+    resolved: &Manifest,
+    signed: &Manifest,
+) -> Result<(), InstallError> {
+    let requested_version = pkg_ref.version.as_ref().unwrap_or(&resolved.version);
+    let signed_id = (&signed.namespace, &signed.name, &signed.version);
+    if signed_id == (&pkg_ref.namespace, &pkg_ref.name, requested_version)
+        && signed_id == (&resolved.namespace, &resolved.name, &resolved.version)
+    {
+        return Ok(());
+    }
+    Err(InstallError::PackageMismatch {
+        requested: format!("{}/{}@{requested_version}", pkg_ref.namespace, pkg_ref.name),
+        signed: format!("{}/{}@{}", signed.namespace, signed.name, signed.version),
+    })
+}
+
+/// Unpack `tarball`, without its `manifest.json`, into a new staging
+/// directory in `parent` and check its files against `files` there.
+///
+/// `parent` must be on the same filesystem as the install path, so the
+/// caller can rename the returned directory into place once it holds only
+/// verified files.
+///
+/// # Errors
+///
+/// Returns [`InstallError::Crypto`] if the files do not match `files`, or
+/// an I/O or unpack error. The staging directory is removed on error.
+pub fn unpack_verified(
+    tarball: &[u8],
+    files: &[FileEntry],
+    parent: &Path,
+) -> Result<tempfile::TempDir, InstallError> {
+    std::fs::create_dir_all(parent)?;
+    let staging = tempfile::Builder::new()
+        .prefix(".staging-")
+        .tempdir_in(parent)?;
+    let tmp = tempfile::NamedTempFile::new()?;
+    std::fs::write(tmp.path(), tarball)?;
+    unpack_tarball_skip_manifest(tmp.path(), staging.path())?;
+
+    // Legacy packages may carry no file list; their signature covers an
+    // unsigned tarball that cannot be reconstructed, so only the transport
+    // digest protects their contents.
+    if !files.is_empty() {
+        verify_files(staging.path(), files).map_err(|e| InstallError::Crypto(e.to_string()))?;
+    }
+    Ok(staging)
+}
+
+/// Build a verifier anchored at the active context's trusted root CAs.
+///
+/// Uses the context's `root_ca_pem` bundle when set (expanding a leading
+/// `~`), otherwise the bundled root CA.
+///
+/// # Errors
+///
+/// Returns [`InstallError::Io`] if the home directory cannot be determined
+/// or the root CA file cannot be read.
+pub fn root_verifier(cfg: &CliConfig) -> Result<RsaPssVerifier, InstallError> {
+    let Some(ref ca_path) = cfg.active_context_config().root_ca_pem else {
+        return Ok(RsaPssVerifier::new());
+    };
+    // Expand leading ~ manually since std::fs doesn't do tilde expansion.
+    let expanded = match ca_path.strip_prefix("~") {
+        Ok(suffix) => home_dir()?.join(suffix),
+        Err(_) => ca_path.clone(),
+    };
+    let pem = std::fs::read(&expanded).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!("reading root CA from {}: {e}", expanded.display()),
+        )
+    })?;
+    Ok(RsaPssVerifier::new_with_root_pem(&pem))
+}
+
+fn home_dir() -> std::io::Result<PathBuf> {
+    dirs::home_dir().ok_or_else(|| std::io::Error::other("cannot determine home directory"))
+}
+
+/// Remove all version subdirectories under `name_dir`, enforcing the
+/// single-version constraint. No-op if `name_dir` does not exist.
+fn clear_existing_versions(name_dir: &Path) -> std::io::Result<()> {
+    if !name_dir.exists() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(name_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(entry.path())?;
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
 }
 
 /// Orchestrates download, verification, and extraction of a skill package.
@@ -50,6 +203,8 @@ pub struct Installer {
     client: Arc<dyn RegistryClient>,
     install_root: PathBuf,
     verifier: Option<Arc<dyn SignatureVerifier>>,
+    pins: Option<PublisherPins>,
+    tree_heads: Option<TreeHeadTracker>,
 }
 
 impl Installer {
@@ -65,27 +220,82 @@ impl Installer {
             client,
             install_root,
             verifier: None,
+            pins: None,
+            tree_heads: None,
         }
     }
 
+    /// Create an `Installer` that makes every check `skreg install` makes
+    /// for the active context of `cfg`: publisher signatures against the
+    /// context's root CAs, transparency log entries and tree heads, and the
+    /// namespace's pinned keys and key log.
+    ///
+    /// `trust_new_key` accepts a namespace's new key even without a
+    /// rotation or key addition signed by a pinned one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InstallError::Io`] if the home directory cannot be
+    /// determined or the root CA file cannot be read.
+    pub fn for_config(
+        client: Arc<dyn RegistryClient>,
+        install_root: PathBuf,
+        cfg: &CliConfig,
+        trust_new_key: bool,
+    ) -> Result<Self, InstallError> {
+        let known_publishers = default_known_publishers_path()
+            .ok_or_else(|| std::io::Error::other("cannot determine home directory"))?;
+        let tree_heads = default_tree_heads_path()
+            .ok_or_else(|| std::io::Error::other("cannot determine home directory"))?;
+        Ok(Self::new(client, install_root)
+            .with_verifier(Arc::new(root_verifier(cfg)?))
+            .with_publisher_pins(
+                PublisherPins::new(known_publishers, cfg.registry()).trust_new_key(trust_new_key),
+            )
+            .with_tree_heads(TreeHeadTracker::new(tree_heads, cfg.registry())))
+    }
+
     /// Attach an optional signature verifier.
     ///
-    /// When set, `install()` will call `verifier.verify()` after the sha256
-    /// check and return an error if the signature is invalid.
+    /// When set, `install()` will verify the publisher signature after the
+    /// sha256 check and return an error if it is invalid or the signing
+    /// certificate's common name is not the package's namespace. A version
+    /// the registry says it logged must also carry a valid inclusion proof
+    /// against a registry-signed transparency log head.
     #[must_use]
     pub fn with_verifier(mut self, verifier: Arc<dyn SignatureVerifier>) -> Self {
         self.verifier = Some(verifier);
         self
     }
 
+    /// Check each verified publisher key against the pins in `pins`.
+    ///
+    /// Has no effect without a verifier.
+    #[must_use]
+    pub fn with_publisher_pins(mut self, pins: PublisherPins) -> Self {
+        self.pins = Some(pins);
+        self
+    }
+
+    /// Check every transparency log tree head against the last one seen in
+    /// `tree_heads`, and record it there.
+    ///
+    /// Has no effect without a verifier.
+    #[must_use]
+    pub fn with_tree_heads(mut self, tree_heads: TreeHeadTracker) -> Self {
+        self.tree_heads = Some(tree_heads);
+        self
+    }
+
     /// Download, verify, and extract a package.
     ///
     /// Returns the installed package descriptor and manifest on success.
     ///
     /// # Errors
     ///
-    /// Returns [`InstallError`] if any step fails. On extraction failure, the
-    /// partially-created installation directory may remain on disk.
+    /// Returns [`InstallError`] if any step fails. The package is unpacked and
+    /// checked in a staging directory, so a failed install leaves the
+    /// installed version untouched.
     pub async fn install(
         &self,
         pkg_ref: &PackageRef,
@@ -99,7 +309,7 @@ impl Installer {
             hasher.update(&resolved.tarball);
             format!("{:x}", hasher.finalize())
         };
-        let expected_hex = resolved.manifest.sha256.as_hex();
+        let expected_hex = resolved.tarball_sha256.as_hex();
         if actual_hex != expected_hex {
             return Err(InstallError::DigestMismatch {
                 expected: expected_hex.to_owned(),
@@ -110,41 +320,74 @@ impl Installer {
 
         let digest = Sha256Digest::from_hex(&actual_hex)?;
 
+        let tarball_manifest = skreg_pack::unpack::read_manifest_from_bytes(&resolved.tarball)?;
         if let Some(ref verifier) = self.verifier {
-            let tarball_manifest = skreg_pack::unpack::read_manifest_from_bytes(&resolved.tarball)?;
-            let sig_hex = tarball_manifest
-                .publisher_sig_hex
-                .as_deref()
-                .ok_or_else(|| {
-                    InstallError::Crypto("package is missing publisher_sig_hex".to_owned())
-                })?;
-            let sig_bytes = hex::decode(sig_hex)
-                .map_err(|e| InstallError::Crypto(format!("invalid publisher_sig_hex: {e}")))?;
-            // publisher_sig_hex was signed over hash(tarball_1) = tarball_manifest.sha256,
-            // not over hash(tarball_2) which is what `digest` holds.
-            verifier
-                .verify(
-                    &tarball_manifest.sha256,
-                    &sig_bytes,
-                    &tarball_manifest.cert_chain_pem,
-                )
-                .map_err(|e| InstallError::Crypto(e.to_string()))?;
+            let signed_at = signing_time(pkg_ref, &resolved)?;
+            verify_manifest(
+                verifier.as_ref(),
+                &tarball_manifest,
+                SigningScheme::from_legacy(resolved.legacy_signature),
+                Some(pkg_ref.namespace.as_str()),
+                resolved.timestamp.as_ref(),
+            )
+            .map_err(|e| InstallError::Crypto(e.to_string()))?;
             debug!("publisher signature verified for {pkg_ref}");
+
+            let logged = verify_publish_logged(
+                self.client.as_ref(),
+                verifier.as_ref(),
+                self.tree_heads.as_ref(),
+                &tarball_manifest,
+                resolved.log_index,
+                signed_at,
+            )
+            .await
+            .map_err(|e| InstallError::Crypto(e.to_string()))?;
+            if let Some(index) = logged {
+                debug!("transparency log entry {index} verified for {pkg_ref}");
+            }
+
+            if let (Some(pins), Some(cert_pem)) =
+                (&self.pins, tarball_manifest.cert_chain_pem.first())
+            {
+                pins.check(self.client.as_ref(), pkg_ref.namespace.as_str(), cert_pem)
+                    .await?;
+                debug!("publisher key of {} matches its pin", pkg_ref.namespace);
+
+                if let Some(signed_at) = signed_at {
+                    pins.check_key_log(
+                        self.client.as_ref(),
+                        verifier.as_ref(),
+                        pkg_ref.namespace.as_str(),
+                        cert_pem,
+                        signed_at,
+                    )
+                    .await?;
+                    debug!(
+                        "publisher key of {} was valid when {pkg_ref} was signed",
+                        pkg_ref.namespace
+                    );
+                }
+            }
         }
+        check_package(pkg_ref, &resolved.manifest, &tarball_manifest)?;
 
-        let install_path = self
-            .install_root
-            .join(resolved.manifest.namespace.as_str())
-            .join(resolved.manifest.name.as_str())
-            .join(resolved.manifest.version.to_string());
+        let ns_dir = self.install_root.join(tarball_manifest.namespace.as_str());
+        let name_dir = ns_dir.join(tarball_manifest.name.as_str());
+        let install_path = name_dir.join(tarball_manifest.version.to_string());
 
-        let tmp = tempfile::NamedTempFile::new()?;
-        std::fs::write(tmp.path(), &resolved.tarball)?;
-        unpack_tarball_skip_manifest(tmp.path(), &install_path)?;
+        // Stage beside the package directory, which the single-version
+        // cleanup below empties, and only replace the old version once the
+        // new one has been verified.
+        let staged = unpack_verified(&resolved.tarball, &tarball_manifest.files, &ns_dir)?;
+        debug!("extracted files verified for {pkg_ref}");
+        clear_existing_versions(&name_dir)?;
+        std::fs::create_dir_all(&name_dir)?;
+        std::fs::rename(staged.keep(), &install_path)?;
 
         info!("installed {} to {}", pkg_ref, install_path.display());
 
-        let manifest = resolved.manifest;
+        let manifest = tarball_manifest;
         Ok((
             InstalledPackage {
                 pkg_ref: pkg_ref.clone(),
@@ -156,3 +399,230 @@ impl Installer {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use skreg_core::key_log::{KeyEvent, KeyLog};
+    use skreg_core::timestamp::SignedTimestamp;
+    use skreg_core::types::Sha256Digest;
+    use skreg_crypto::rotation::spki_fingerprint;
+    use skreg_pack::format::ArchiveFormat;
+    use skreg_pack::pack::{digest_source_files, pack_to_writer};
+
+    use crate::test_support::{FakeRegistry, TrustingVerifier};
+
+    fn packed() -> (Vec<u8>, Vec<FileEntry>) {
+        let src = tempfile::tempdir().unwrap();
+        std::fs::write(src.path().join("SKILL.md"), "---\nname: x\n---\n").unwrap();
+        let tarball = pack_to_writer(src.path(), b"{}", ArchiveFormat::Gzip, Vec::new()).unwrap();
+        (tarball, digest_source_files(src.path()).unwrap())
+    }
+
+    #[test]
+    fn unpack_verified_stages_only_matching_files() {
+        let (tarball, files) = packed();
+        let parent = tempfile::tempdir().unwrap();
+        let staged = unpack_verified(&tarball, &files, parent.path()).unwrap();
+        assert!(staged.path().starts_with(parent.path()));
+        assert!(staged.path().join("SKILL.md").is_file());
+        assert!(!staged.path().join("manifest.json").exists());
+    }
+
+    #[test]
+    fn unpack_verified_leaves_nothing_behind_on_mismatch() {
+        let (tarball, mut files) = packed();
+        files[0].sha256 = Sha256Digest::of(b"other");
+        let parent = tempfile::tempdir().unwrap();
+        let err = unpack_verified(&tarball, &files, parent.path()).unwrap_err();
+        assert!(matches!(err, InstallError::Crypto(_)), "got: {err}");
+        assert_eq!(std::fs::read_dir(parent.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn clear_existing_versions_removes_old_version_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let name_dir = tmp.path().join("acme").join("my-skill");
+        // Simulate an old installed version with a file inside
+        let old_version = name_dir.join("1.0.0");
+        std::fs::create_dir_all(old_version.join("subdir")).unwrap();
+
+        clear_existing_versions(&name_dir).unwrap();
+
+        // Version dir should be gone
+        assert!(!old_version.exists());
+        // name_dir itself should still exist (we only remove children)
+        assert!(name_dir.exists());
+    }
+
+    #[test]
+    fn clear_existing_versions_is_noop_when_dir_absent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let name_dir = tmp.path().join("acme").join("my-skill");
+        // Should not error even if name_dir doesn't exist
+        clear_existing_versions(&name_dir).unwrap();
+    }
+
+    #[test]
+    fn clear_existing_versions_removes_multiple_version_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let name_dir = tmp.path().join("acme").join("my-skill");
+        std::fs::create_dir_all(name_dir.join("1.0.0")).unwrap();
+        std::fs::create_dir_all(name_dir.join("2.0.0")).unwrap();
+
+        clear_existing_versions(&name_dir).unwrap();
+
+        assert!(!name_dir.join("1.0.0").exists());
+        assert!(!name_dir.join("2.0.0").exists());
+        assert!(name_dir.exists());
+    }
+
+    /// A self-signed certificate with CN `acme`.
+    fn acme_cert() -> String {
+        let mut params = rcgen::CertificateParams::new(vec!["acme".to_owned()]);
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "acme");
+        rcgen::Certificate::from_params(params)
+            .unwrap()
+            .serialize_pem()
+            .unwrap()
+    }
+
+    /// `acme/skill@1.0.0`, signed with `cert_pem` and timestamped at
+    /// `signed_at`.
+    fn resolved(cert_pem: &str, signed_at: DateTime<Utc>) -> ResolvedVersion {
+        resolved_named("skill", cert_pem, signed_at)
+    }
+
+    /// As [`resolved`], for `acme/<name>@1.0.0`.
+    fn resolved_named(name: &str, cert_pem: &str, signed_at: DateTime<Utc>) -> ResolvedVersion {
+        let src = tempfile::tempdir().unwrap();
+        std::fs::write(src.path().join("SKILL.md"), "---\nname: skill\n---\n").unwrap();
+        let mut manifest: Manifest = serde_json::from_value(serde_json::json!({
+            "namespace": "acme",
+            "name": name,
+            "version": "1.0.0",
+            "description": "A skill for testing installs",
+            "category": null,
+            "sha256": Sha256Digest::of(b""),
+            "cert_chain_pem": [cert_pem],
+            "publisher_sig_hex": "00",
+            "files": digest_source_files(src.path()).unwrap(),
+        }))
+        .unwrap();
+        manifest.sha256 = manifest.payload_digest().unwrap();
+        let manifest_json = serde_json::to_vec(&manifest).unwrap();
+        let tarball =
+            pack_to_writer(src.path(), &manifest_json, ArchiveFormat::Gzip, Vec::new()).unwrap();
+        let timestamp = Timestamp {
+            imprint: Timestamp::imprint_of(&[0]),
+            signed_at,
+        };
+        ResolvedVersion {
+            manifest,
+            tarball_sha256: Sha256Digest::of(&tarball),
+            tarball,
+            signature: vec![0],
+            timestamp: Some(SignedTimestamp {
+                timestamp: serde_json::to_string(&timestamp).unwrap(),
+                signature_hex: String::new(),
+                signer_cert_pem: String::new(),
+            }),
+            log_index: None,
+            legacy_signature: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn install_requires_the_key_log_to_show_the_signing_key() {
+        let cert_pem = acme_cert();
+        let signed_at = Utc::now();
+        let pinned_before = |fingerprint: &str| {
+            KeyLog::build(
+                "acme",
+                [(
+                    signed_at - Duration::days(1),
+                    KeyEvent::Pinned {
+                        fingerprint: fingerprint.to_owned(),
+                    },
+                )],
+            )
+            .unwrap()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let installer = |key_log| {
+            let registry = FakeRegistry {
+                resolved: Some(resolved(&cert_pem, signed_at)),
+                key_log: Some(key_log),
+                ..FakeRegistry::default()
+            };
+            Installer::new(Arc::new(registry), dir.path().join("packages"))
+                .with_verifier(Arc::new(TrustingVerifier))
+                .with_publisher_pins(PublisherPins::new(
+                    dir.path().join("known_publishers.toml"),
+                    "https://registry.test",
+                ))
+        };
+        let pkg_ref = PackageRef::parse("acme/skill@1.0.0").unwrap();
+
+        let err = installer(pinned_before("0000"))
+            .install(&pkg_ref)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, InstallError::PublisherKey(PinError::KeyLog { .. })),
+            "got: {err}"
+        );
+        assert!(!dir.path().join("packages").join("acme").exists());
+
+        let fingerprint = spki_fingerprint(&cert_pem).unwrap();
+        let (package, _) = installer(pinned_before(&fingerprint))
+            .install(&pkg_ref)
+            .await
+            .unwrap();
+        assert!(package.install_path.join("SKILL.md").is_file());
+    }
+
+    #[tokio::test]
+    async fn install_refuses_an_archive_signed_for_another_package() {
+        let registry = FakeRegistry {
+            resolved: Some(resolved_named("other", &acme_cert(), Utc::now())),
+            ..FakeRegistry::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let installer = Installer::new(Arc::new(registry), dir.path().join("packages"))
+            .with_verifier(Arc::new(TrustingVerifier));
+        let pkg_ref = PackageRef::parse("acme/pkg").unwrap();
+
+        let err = installer.install(&pkg_ref).await.unwrap_err();
+        assert!(
+            matches!(err, InstallError::PackageMismatch { .. }),
+            "got: {err}"
+        );
+        assert!(!dir.path().join("packages").join("acme").exists());
+    }
+
+    #[tokio::test]
+    async fn install_requires_a_timestamp_unless_the_version_is_legacy() {
+        let mut unstamped = resolved(&acme_cert(), Utc::now());
+        unstamped.timestamp = None;
+        let registry = FakeRegistry {
+            resolved: Some(unstamped),
+            ..FakeRegistry::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let installer = Installer::new(Arc::new(registry), dir.path().join("packages"))
+            .with_verifier(Arc::new(TrustingVerifier));
+        let pkg_ref = PackageRef::parse("acme/skill@1.0.0").unwrap();
+
+        let err = installer.install(&pkg_ref).await.unwrap_err();
+        assert!(
+            matches!(err, InstallError::MissingTimestamp(_)),
+            "got: {err}"
+        );
+        assert!(!dir.path().join("packages").join("acme").exists());
+    }
+}
@@ -0,0 +1,455 @@
+//! Trust-on-first-use pinning of publisher keys.
+//!
+//! The first install from a namespace records the SHA-256 SPKI fingerprint
+//! of its publisher key in `~/.skreg/known_publishers.toml`, separately for
+//! each registry. A later package signed by a different key is only accepted
+//! if the registry's key history and key-set changes hold a chain from a
+//! pinned key to the new one — rotations signed by the key they replaced, or
+//! additions signed by a key already in the set; otherwise a registry that
+//! re-pinned the namespace, legitimately or not, cannot slip a new key past
+//! the client.
+//!
+//! A namespace's pin is the set of keys accepted so far. It only grows, so
+//! versions signed by a key that was since rotated or removed stay
+//! installable. For versions the registry timestamped, the namespace's
+//! signed key log must also show that the signing key was one of its keys
+//! at that time, and the head of the log is kept with the pin so a log that
+//! rewrites history is refused.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use skreg_core::key_log::KeyLogHead;
+use skreg_crypto::error::VerifyError;
+use skreg_crypto::rotation::{spki_fingerprint, verify_key_chain};
+use skreg_crypto::verifier::SignatureVerifier;
+use thiserror::Error;
+
+use crate::client::RegistryClient;
+use crate::error::ClientError;
+
+/// Errors from checking a publisher key against its pin.
+#[derive(Debug, Error)]
+pub enum PinError {
+    /// The known-publishers file could not be read, parsed or written.
+    #[error("known publishers file {path}: {reason}")]
+    Store {
+        /// Path of the file.
+        path: PathBuf,
+        /// What went wrong.
+        reason: String,
+    },
+    /// The publisher certificate could not be fingerprinted.
+    #[error("publisher certificate: {0}")]
+    Certificate(#[from] VerifyError),
+    /// The key history or key-set changes could not be fetched.
+    #[error("fetching key history: {0}")]
+    Registry(#[from] ClientError),
+    /// The namespace is signed by a key other than the pinned ones, and no
+    /// verified rotation or key addition leads to it.
+    #[error(
+        "publisher key of {namespace} changed from {} to {presented} without a rotation or \
+         key addition signed by a pinned key: {reason}",
+        pinned.join(", ")
+    )]
+    KeyChanged {
+        /// Namespace slug.
+        namespace: String,
+        /// Pinned fingerprints.
+        pinned: Vec<String>,
+        /// Fingerprint of the key that signed the package.
+        presented: String,
+        /// Why the key history did not justify the change.
+        reason: String,
+    },
+    /// The namespace's key log does not verify, rewrites the log seen
+    /// before, or shows the signing key was not valid when the version was
+    /// timestamped.
+    #[error("key log of {namespace}: {reason}")]
+    KeyLog {
+        /// Namespace slug.
+        namespace: String,
+        /// What is wrong with the log.
+        reason: String,
+    },
+}
+
+/// The pinned publisher keys of a namespace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownPublisher {
+    /// SHA-256 SPKI fingerprints (hex) of the namespace's keys, in the order
+    /// they were first accepted.
+    ///
+    /// Files written before namespaces had several keys hold a single
+    /// `fingerprint` string, which loads as a one-key set.
+    #[serde(alias = "fingerprint", deserialize_with = "one_or_many")]
+    pub fingerprints: Vec<String>,
+    /// When the namespace was first installed from.
+    pub first_seen: DateTime<Utc>,
+    /// When a key was last added to `fingerprints`.
+    pub pinned_at: DateTime<Utc>,
+    /// Head of the namespace's key log when it was last checked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_log_head: Option<KeyLogHead>,
+}
+
+impl KnownPublisher {
+    /// Whether `fingerprint` is one of the pinned keys.
+    #[must_use]
+    pub fn trusts(&self, fingerprint: &str) -> bool {
+        self.fingerprints.iter().any(|f| f == fingerprint)
+    }
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(fingerprint) => vec![fingerprint],
+        OneOrMany::Many(fingerprints) => fingerprints,
+    })
+}
+
+/// The contents of `~/.skreg/known_publishers.toml`: pinned keys by
+/// registry URL, then by namespace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnownPublishers {
+    /// Pinned keys by registry URL and namespace slug.
+    #[serde(default)]
+    pub registries: BTreeMap<String, BTreeMap<String, KnownPublisher>>,
+}
+
+impl KnownPublishers {
+    /// Load the pins from `path`, returning none if it is absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::Store`] if the file exists but cannot be read or
+    /// parsed. A damaged file is never treated as empty, which would re-pin
+    /// every namespace on the next install.
+    pub fn load(path: &Path) -> Result<Self, PinError> {
+        let store_err = |reason: String| PinError::Store {
+            path: path.to_owned(),
+            reason,
+        };
+        match std::fs::read_to_string(path) {
+            Ok(raw) => toml::from_str(&raw).map_err(|e| store_err(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(store_err(e.to_string())),
+        }
+    }
+
+    /// Write the pins to `path`, creating parent directories if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::Store`] if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), PinError> {
+        let store_err = |reason: String| PinError::Store {
+            path: path.to_owned(),
+            reason,
+        };
+        let raw = toml::to_string_pretty(self).map_err(|e| store_err(e.to_string()))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| store_err(e.to_string()))?;
+        }
+        std::fs::write(path, raw).map_err(|e| store_err(e.to_string()))
+    }
+
+    /// The pin for `namespace` on `registry`, if any.
+    #[must_use]
+    pub fn get(&self, registry: &str, namespace: &str) -> Option<&KnownPublisher> {
+        self.registries
+            .get(registry_key(registry))
+            .and_then(|namespaces| namespaces.get(namespace))
+    }
+
+    /// Add `fingerprint` to the pinned keys of `namespace` on `registry`,
+    /// keeping its `first_seen` time if it was already known.
+    pub fn pin(&mut self, registry: &str, namespace: &str, fingerprint: &str) {
+        let now = Utc::now();
+        self.registries
+            .entry(registry_key(registry).to_owned())
+            .or_default()
+            .entry(namespace.to_owned())
+            .and_modify(|known| {
+                if !known.trusts(fingerprint) {
+                    known.fingerprints.push(fingerprint.to_owned());
+                    known.pinned_at = now;
+                }
+            })
+            .or_insert_with(|| KnownPublisher {
+                fingerprints: vec![fingerprint.to_owned()],
+                first_seen: now,
+                pinned_at: now,
+                key_log_head: None,
+            });
+    }
+
+    /// Remember `head` as the key log head of `namespace` on `registry`,
+    /// if the namespace is pinned.
+    pub fn record_key_log_head(&mut self, registry: &str, namespace: &str, head: KeyLogHead) {
+        if let Some(known) = self
+            .registries
+            .get_mut(registry_key(registry))
+            .and_then(|namespaces| namespaces.get_mut(namespace))
+        {
+            known.key_log_head = Some(head);
+        }
+    }
+}
+
+/// Outcome of a successful [`PublisherPins::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinStatus {
+    /// The namespace was unknown; its key is now pinned.
+    FirstUse,
+    /// The key is one of the pinned keys.
+    Unchanged,
+    /// The key is not pinned, and a verified chain of rotations and key
+    /// additions from a pinned key leads to it; it is now pinned too.
+    Endorsed,
+    /// The key is not pinned and was accepted because the caller asked to
+    /// trust new keys.
+    TrustedNewKey,
+}
+
+/// Checks publisher keys against the pins kept for one registry.
+#[derive(Debug, Clone)]
+pub struct PublisherPins {
+    path: PathBuf,
+    registry: String,
+    trust_new_key: bool,
+}
+
+impl PublisherPins {
+    /// Check keys against the pins in `path` for the registry at `registry`.
+    pub fn new(path: PathBuf, registry: impl Into<String>) -> Self {
+        Self {
+            path,
+            registry: registry.into(),
+            trust_new_key: false,
+        }
+    }
+
+    /// Accept a changed key without a rotation chain, and re-pin it.
+    #[must_use]
+    pub fn trust_new_key(mut self, trust: bool) -> Self {
+        self.trust_new_key = trust;
+        self
+    }
+
+    /// Check that the key in `cert_pem` may sign for `namespace`, pinning
+    /// it on first use or after a verified rotation or key addition.
+    ///
+    /// `cert_pem` must already be verified as the package signer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::KeyChanged`] if the key is not pinned and no
+    /// verified chain leads to it, or any error reading or saving the pins,
+    /// fingerprinting the certificate, or fetching the key history.
+    pub async fn check(
+        &self,
+        client: &dyn RegistryClient,
+        namespace: &str,
+        cert_pem: &str,
+    ) -> Result<PinStatus, PinError> {
+        let presented = spki_fingerprint(cert_pem)?;
+        let mut known = KnownPublishers::load(&self.path)?;
+        let status = match known.get(&self.registry, namespace) {
+            None => PinStatus::FirstUse,
+            Some(pin) if pin.trusts(&presented) => return Ok(PinStatus::Unchanged),
+            Some(_) if self.trust_new_key => PinStatus::TrustedNewKey,
+            Some(pin) => {
+                let history = client.key_history(namespace).await?;
+                let changes = client.key_changes(namespace).await?;
+                verify_key_chain(&history, &changes, namespace, &pin.fingerprints, &presented)
+                    .map_err(|e| PinError::KeyChanged {
+                        namespace: namespace.to_owned(),
+                        pinned: pin.fingerprints.clone(),
+                        presented: presented.clone(),
+                        reason: e.to_string(),
+                    })?;
+                PinStatus::Endorsed
+            }
+        };
+        match status {
+            PinStatus::FirstUse => info!("pinning publisher key {presented} for {namespace}"),
+            PinStatus::Endorsed => info!("pinning endorsed key {presented} for {namespace}"),
+            _ => warn!("trusting new publisher key {presented} for {namespace}"),
+        }
+        known.pin(&self.registry, namespace, &presented);
+        known.save(&self.path)?;
+        Ok(status)
+    }
+
+    /// Check that the key in `cert_pem` was one of `namespace`'s keys at
+    /// `signed_at`, according to the registry-signed key log, and that the
+    /// log extends the one seen last time. A namespace with an empty log
+    /// has no history to check against.
+    ///
+    /// Call after [`check`](Self::check), which pins the namespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::KeyLog`] if the log does not verify, rewrites an
+    /// earlier log, or does not show the key as valid at `signed_at`, or
+    /// any error reading or saving the pins, fingerprinting the
+    /// certificate, or fetching the log.
+    pub async fn check_key_log(
+        &self,
+        client: &dyn RegistryClient,
+        verifier: &dyn SignatureVerifier,
+        namespace: &str,
+        cert_pem: &str,
+        signed_at: DateTime<Utc>,
+    ) -> Result<(), PinError> {
+        let key_log_err = |reason: String| PinError::KeyLog {
+            namespace: namespace.to_owned(),
+            reason,
+        };
+        let fingerprint = spki_fingerprint(cert_pem)?;
+        let log = verifier
+            .verify_key_log(&client.key_log(namespace).await?)
+            .map_err(|e| key_log_err(e.to_string()))?;
+        if log.namespace != namespace {
+            return Err(key_log_err(format!(
+                "registry served the log of {}",
+                log.namespace
+            )));
+        }
+
+        let mut known = KnownPublishers::load(&self.path)?;
+        let seen = known
+            .get(&self.registry, namespace)
+            .and_then(|pin| pin.key_log_head.as_ref());
+        if let Some(seen) = seen {
+            if !log.extends(seen).map_err(|e| key_log_err(e.to_string()))? {
+                return Err(key_log_err(format!(
+                    "log no longer starts with the {} entries seen before",
+                    seen.len
+                )));
+            }
+        }
+        let Some(head) = log.head().map_err(|e| key_log_err(e.to_string()))? else {
+            return Ok(());
+        };
+        if !log.valid_at(&fingerprint, signed_at) {
+            return Err(key_log_err(format!(
+                "key {fingerprint} was not one of its keys at {signed_at}"
+            )));
+        }
+        if seen != Some(&head) {
+            known.record_key_log_head(&self.registry, namespace, head);
+            known.save(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Default path for `~/.skreg/known_publishers.toml`.
+#[must_use]
+pub fn default_known_publishers_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".skreg").join("known_publishers.toml"))
+}
+
+/// Registry URLs differing only by a trailing slash share their pins.
+fn registry_key(registry: &str) -> &str {
+    registry.trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_round_trip_per_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("known_publishers.toml");
+        let mut known = KnownPublishers::load(&path).unwrap();
+        assert!(known.get("https://api.skreg.ai", "acme").is_none());
+
+        known.pin("https://api.skreg.ai/", "acme", "aaa");
+        known.pin("http://localhost:8080", "acme", "bbb");
+        known.save(&path).unwrap();
+
+        let mut known = KnownPublishers::load(&path).unwrap();
+        let first = known.get("https://api.skreg.ai", "acme").unwrap().clone();
+        assert_eq!(first.fingerprints, ["aaa"]);
+        assert_eq!(
+            known
+                .get("http://localhost:8080", "acme")
+                .unwrap()
+                .fingerprints,
+            ["bbb"]
+        );
+
+        known.pin("https://api.skreg.ai", "acme", "ccc");
+        known.pin("https://api.skreg.ai", "acme", "aaa");
+        let grown = known.get("https://api.skreg.ai", "acme").unwrap();
+        assert_eq!(grown.fingerprints, ["aaa", "ccc"]);
+        assert!(grown.trusts("aaa") && grown.trusts("ccc"));
+        assert_eq!(grown.first_seen, first.first_seen);
+    }
+
+    #[test]
+    fn single_fingerprint_files_still_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("known_publishers.toml");
+        std::fs::write(
+            &path,
+            "[registries.\"https://api.skreg.ai\".acme]\n\
+             fingerprint = \"aaa\"\n\
+             first_seen = \"2026-01-01T00:00:00Z\"\n\
+             pinned_at = \"2026-01-01T00:00:00Z\"\n",
+        )
+        .unwrap();
+        let known = KnownPublishers::load(&path).unwrap();
+        let acme = known.get("https://api.skreg.ai", "acme").unwrap();
+        assert_eq!(acme.fingerprints, ["aaa"]);
+    }
+
+    #[test]
+    fn key_log_head_is_kept_with_the_pin() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("known_publishers.toml");
+        let head = KeyLogHead {
+            len: 2,
+            hash: skreg_core::types::Sha256Digest::of(b"entry"),
+        };
+        let mut known = KnownPublishers::default();
+        known.record_key_log_head("https://api.skreg.ai", "acme", head.clone());
+        assert!(known.get("https://api.skreg.ai", "acme").is_none());
+
+        known.pin("https://api.skreg.ai", "acme", "aaa");
+        known.record_key_log_head("https://api.skreg.ai/", "acme", head.clone());
+        known.save(&path).unwrap();
+
+        let known = KnownPublishers::load(&path).unwrap();
+        let acme = known.get("https://api.skreg.ai", "acme").unwrap();
+        assert_eq!(acme.key_log_head, Some(head));
+        assert_eq!(acme.fingerprints, ["aaa"]);
+    }
+
+    #[test]
+    fn damaged_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("known_publishers.toml");
+        std::fs::write(&path, "registries = 3").unwrap();
+        assert!(matches!(
+            KnownPublishers::load(&path),
+            Err(PinError::Store { .. })
+        ));
+    }
+}
@@ -2,7 +2,12 @@
 #![deny(warnings, clippy::all, clippy::pedantic)]
 #![warn(missing_docs)]
 
+pub mod advisories;
 pub mod client;
 pub mod error;
 pub mod installer;
+pub mod known_publishers;
 pub mod linker;
+#[cfg(test)]
+mod test_support;
+pub mod transparency;
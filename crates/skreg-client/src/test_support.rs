@@ -0,0 +1,198 @@
+//! In-memory registry and verifier for unit tests.
+
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use skreg_core::advisory::SignedAdvisoryFeed;
+use skreg_core::key_log::{KeyLog, SignedKeyLog};
+use skreg_core::keyset::KeyChangeRecord;
+use skreg_core::package_ref::PackageRef;
+use skreg_core::rotation::RotationRecord;
+use skreg_core::timestamp::{SignedTimestamp, Timestamp};
+use skreg_core::transparency::{
+    ConsistencyProof, InclusionProof, LogEntry, SignedTreeHead, TreeHead,
+};
+use skreg_core::types::Sha256Digest;
+use skreg_crypto::error::VerifyError;
+use skreg_crypto::merkle;
+use skreg_crypto::verifier::{SignatureVerifier, VerifiedSigner};
+
+use crate::client::{BoxFuture, PackagePreview, RegistryClient, ResolvedVersion, SearchResult};
+use crate::error::ClientError;
+
+/// A registry serving a transparency log of `leaves` from memory, and
+/// optionally one package version and key log.
+#[derive(Default)]
+pub struct FakeRegistry {
+    /// Leaf hashes of the log, in order.
+    pub leaves: Mutex<Vec<Sha256Digest>>,
+    /// Log start served in tree heads.
+    pub log_start: Option<DateTime<Utc>>,
+    /// Version served for every package reference.
+    pub resolved: Option<ResolvedVersion>,
+    /// Key log served, unsigned, for every namespace.
+    pub key_log: Option<KeyLog>,
+}
+
+impl FakeRegistry {
+    /// Append `entry` to the log, returning its index.
+    pub fn append(&self, entry: &LogEntry) -> u64 {
+        let mut leaves = self.leaves.lock().unwrap();
+        leaves.push(merkle::leaf_hash(&entry.leaf_data().unwrap()));
+        leaves.len() as u64 - 1
+    }
+
+    fn leaves(&self, tree_size: u64) -> Vec<Sha256Digest> {
+        let leaves = self.leaves.lock().unwrap();
+        leaves[..usize::try_from(tree_size).unwrap()].to_vec()
+    }
+}
+
+fn not_served<T>() -> Result<T, ClientError> {
+    Err(ClientError::Parse(
+        "not served by the fake registry".to_owned(),
+    ))
+}
+
+impl RegistryClient for FakeRegistry {
+    fn resolve<'a>(
+        &'a self,
+        _pkg_ref: &'a PackageRef,
+    ) -> BoxFuture<'a, Result<ResolvedVersion, ClientError>> {
+        Box::pin(async { self.resolved.clone().map_or_else(not_served, Ok) })
+    }
+
+    fn search<'a>(
+        &'a self,
+        _query: &'a str,
+        _verified_only: bool,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, ClientError>> {
+        Box::pin(async { not_served() })
+    }
+
+    fn preview_package<'a>(
+        &'a self,
+        _ns: &'a str,
+        _name: &'a str,
+        _version: &'a str,
+    ) -> BoxFuture<'a, Result<PackagePreview, ClientError>> {
+        Box::pin(async { not_served() })
+    }
+
+    fn advisories(&self, _since: i64) -> BoxFuture<'_, Result<SignedAdvisoryFeed, ClientError>> {
+        Box::pin(async { not_served() })
+    }
+
+    fn key_history<'a>(
+        &'a self,
+        _ns: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RotationRecord>, ClientError>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn key_changes<'a>(
+        &'a self,
+        _ns: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<KeyChangeRecord>, ClientError>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn key_log<'a>(&'a self, _ns: &'a str) -> BoxFuture<'a, Result<SignedKeyLog, ClientError>> {
+        Box::pin(async {
+            let Some(key_log) = &self.key_log else {
+                return not_served();
+            };
+            Ok(SignedKeyLog {
+                key_log: serde_json::to_string(key_log).unwrap(),
+                signature_hex: String::new(),
+                signer_cert_pem: String::new(),
+            })
+        })
+    }
+
+    fn tree_head(&self) -> BoxFuture<'_, Result<SignedTreeHead, ClientError>> {
+        Box::pin(async move {
+            let leaves = self.leaves.lock().unwrap().clone();
+            let head = TreeHead {
+                tree_size: leaves.len() as u64,
+                root_hash: merkle::root_hash(&leaves)?,
+                timestamp: Utc::now(),
+                log_start: self.log_start,
+            };
+            Ok(SignedTreeHead {
+                tree_head: serde_json::to_string(&head).unwrap(),
+                signature_hex: String::new(),
+                signer_cert_pem: String::new(),
+            })
+        })
+    }
+
+    fn inclusion_proof(
+        &self,
+        index: u64,
+        tree_size: u64,
+    ) -> BoxFuture<'_, Result<InclusionProof, ClientError>> {
+        Box::pin(async move {
+            Ok(InclusionProof {
+                leaf_index: index,
+                tree_size,
+                audit_path: merkle::inclusion_proof(&self.leaves(tree_size), index)?,
+            })
+        })
+    }
+
+    fn consistency_proof(
+        &self,
+        first: u64,
+        second: u64,
+    ) -> BoxFuture<'_, Result<ConsistencyProof, ClientError>> {
+        Box::pin(async move {
+            Ok(ConsistencyProof {
+                first,
+                second,
+                proof: merkle::consistency_proof(&self.leaves(second), first)?,
+            })
+        })
+    }
+}
+
+/// A verifier that trusts every signature and every registry document it is
+/// shown unsigned, and reports every publisher as namespace `acme`.
+pub struct TrustingVerifier;
+
+impl SignatureVerifier for TrustingVerifier {
+    fn verify_at(
+        &self,
+        _digest: &Sha256Digest,
+        _signature: &[u8],
+        _cert_chain_pem: &[String],
+        _at: SystemTime,
+    ) -> Result<VerifiedSigner, VerifyError> {
+        Ok(VerifiedSigner {
+            cert_serial: None,
+            common_name: "acme".to_owned(),
+            ca_verified: false,
+        })
+    }
+
+    fn verify_timestamp(
+        &self,
+        timestamp: &SignedTimestamp,
+        _signature: &[u8],
+    ) -> Result<SystemTime, VerifyError> {
+        let timestamp: Timestamp = serde_json::from_str(&timestamp.timestamp)
+            .map_err(|e| VerifyError::InvalidTimestamp(e.to_string()))?;
+        Ok(timestamp.signed_at.into())
+    }
+
+    fn verify_tree_head(&self, tree_head: &SignedTreeHead) -> Result<TreeHead, VerifyError> {
+        serde_json::from_str(&tree_head.tree_head)
+            .map_err(|e| VerifyError::InvalidLogProof(e.to_string()))
+    }
+
+    fn verify_key_log(&self, key_log: &SignedKeyLog) -> Result<KeyLog, VerifyError> {
+        serde_json::from_str(&key_log.key_log)
+            .map_err(|e| VerifyError::InvalidKeyLog(e.to_string()))
+    }
+}
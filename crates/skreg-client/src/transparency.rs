@@ -0,0 +1,384 @@
+//! Transparency log checks: that an installed version was logged, and that
+//! the log only ever grows.
+//!
+//! The last verified tree head of each registry is kept in
+//! `~/.skreg/tree_heads.toml`. [`check_consistency`] proves the registry's
+//! current tree extends it, so entries can be added to the log but never
+//! removed or rewritten without clients noticing.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use skreg_core::manifest::Manifest;
+use skreg_core::transparency::{LogEntry, TreeHead};
+use skreg_crypto::error::VerifyError;
+use skreg_crypto::merkle;
+use skreg_crypto::rotation::spki_fingerprint;
+use skreg_crypto::verifier::SignatureVerifier;
+
+use crate::client::RegistryClient;
+use crate::error::ClientError;
+
+/// The last verified tree head of each registry, by registry URL.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeenTreeHeads {
+    /// Tree heads by registry URL.
+    #[serde(default)]
+    pub registries: BTreeMap<String, TreeHead>,
+}
+
+impl SeenTreeHeads {
+    /// Load the tree heads from `path`, returning none if it is absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Parse`] if the file exists but cannot be read
+    /// or parsed.
+    pub fn load(path: &Path) -> Result<Self, ClientError> {
+        match std::fs::read_to_string(path) {
+            Ok(raw) => toml::from_str(&raw)
+                .map_err(|e| ClientError::Parse(format!("{}: {e}", path.display()))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(ClientError::Parse(format!("{}: {e}", path.display()))),
+        }
+    }
+
+    /// Write the tree heads to `path`, creating parent directories if
+    /// necessary.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Parse`] if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), ClientError> {
+        let write = || -> Result<(), Box<dyn std::error::Error>> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, toml::to_string_pretty(self)?)?;
+            Ok(())
+        };
+        write().map_err(|e| ClientError::Parse(format!("{}: {e}", path.display())))
+    }
+
+    /// The last tree head seen from `registry`, if any.
+    #[must_use]
+    pub fn get(&self, registry: &str) -> Option<&TreeHead> {
+        self.registries.get(registry.trim_end_matches('/'))
+    }
+
+    /// Record `head` as the last tree head seen from `registry`.
+    pub fn set(&mut self, registry: &str, head: TreeHead) {
+        self.registries
+            .insert(registry.trim_end_matches('/').to_owned(), head);
+    }
+}
+
+/// Keeps the last verified tree head of one registry in a [`SeenTreeHeads`]
+/// file, so every proof is checked against a log proven to extend it.
+#[derive(Debug, Clone)]
+pub struct TreeHeadTracker {
+    path: PathBuf,
+    registry: String,
+}
+
+impl TreeHeadTracker {
+    /// Track the tree heads of the registry at `registry` in `path`.
+    pub fn new(path: PathBuf, registry: impl Into<String>) -> Self {
+        Self {
+            path,
+            registry: registry.into(),
+        }
+    }
+
+    /// Fetch and verify the registry's current tree head, check that it
+    /// extends the last one seen (see [`check_consistency`]), and record it.
+    ///
+    /// # Errors
+    ///
+    /// As for [`check_consistency`], or [`ClientError::Parse`] if the tree
+    /// heads cannot be read or saved.
+    pub async fn advance(
+        &self,
+        client: &dyn RegistryClient,
+        verifier: &dyn SignatureVerifier,
+    ) -> Result<TreeHead, ClientError> {
+        let mut seen = SeenTreeHeads::load(&self.path)?;
+        let head = check_consistency(client, verifier, seen.get(&self.registry)).await?;
+        seen.set(&self.registry, head.clone());
+        seen.save(&self.path)?;
+        Ok(head)
+    }
+}
+
+/// The registry's current tree head: advanced through `tracker` if given,
+/// else only checked for the registry's signature.
+async fn current_head(
+    client: &dyn RegistryClient,
+    verifier: &dyn SignatureVerifier,
+    tracker: Option<&TreeHeadTracker>,
+) -> Result<TreeHead, ClientError> {
+    match tracker {
+        Some(tracker) => tracker.advance(client, verifier).await,
+        None => Ok(verifier.verify_tree_head(&client.tree_head().await?)?),
+    }
+}
+
+/// Check that `entry` is entry `index` of the registry's current log, and
+/// return the verified tree head it was proven against.
+///
+/// With a `tracker`, that tree head must extend the last one seen from the
+/// registry and is recorded in its place.
+///
+/// # Errors
+///
+/// Returns [`ClientError::Verify`] if the tree head is not registry-signed,
+/// is inconsistent with the last one seen, or the proof does not hold, or
+/// any error fetching them or saving the tree head.
+pub async fn verify_inclusion(
+    client: &dyn RegistryClient,
+    verifier: &dyn SignatureVerifier,
+    tracker: Option<&TreeHeadTracker>,
+    entry: &LogEntry,
+    index: u64,
+) -> Result<TreeHead, ClientError> {
+    let head = current_head(client, verifier, tracker).await?;
+    if index >= head.tree_size {
+        return Err(log_error(format!(
+            "entry {index} is not in the log of {} entries",
+            head.tree_size
+        )));
+    }
+    let proof = client.inclusion_proof(index, head.tree_size).await?;
+    if proof.leaf_index != index || proof.tree_size != head.tree_size {
+        return Err(log_error(
+            "registry answered for a different entry".to_owned(),
+        ));
+    }
+    let leaf_data = entry
+        .leaf_data()
+        .map_err(|e| ClientError::Parse(e.to_string()))?;
+    merkle::verify_inclusion(
+        &merkle::leaf_hash(&leaf_data),
+        index,
+        head.tree_size,
+        &proof.audit_path,
+        &head.root_hash,
+    )?;
+    Ok(head)
+}
+
+/// Check that the publish of `manifest`, signed by the first certificate of
+/// its chain, is entry `log_index` of the registry's log.
+///
+/// A version without an entry is accepted only if it predates the log: it
+/// was signed before the log start in the registry-signed tree head, or it
+/// has no signing time (`signed_at`). Callers pass `None` only for versions
+/// the registry marks as legacy, which were vetted before signature
+/// timestamps. Returns the index checked, if any. The
+/// tree head is checked against `tracker` as in [`verify_inclusion`].
+///
+/// # Errors
+///
+/// As for [`verify_inclusion`]; [`ClientError::Verify`] if the manifest has
+/// no parseable signing certificate, or the version was signed since the
+/// log started but the registry gives no entry for it.
+pub async fn verify_publish_logged(
+    client: &dyn RegistryClient,
+    verifier: &dyn SignatureVerifier,
+    tracker: Option<&TreeHeadTracker>,
+    manifest: &Manifest,
+    log_index: Option<u64>,
+    signed_at: Option<DateTime<Utc>>,
+) -> Result<Option<u64>, ClientError> {
+    let Some(index) = log_index else {
+        let head = current_head(client, verifier, tracker).await?;
+        if entry_required(&head, signed_at) {
+            return Err(log_error(format!(
+                "{}/{}@{} was signed since the log started but has no log entry",
+                manifest.namespace, manifest.name, manifest.version
+            )));
+        }
+        return Ok(None);
+    };
+    let cert_pem = manifest
+        .cert_chain_pem
+        .first()
+        .ok_or(ClientError::Verify(VerifyError::MissingSignature))?;
+    let entry = LogEntry::publish(manifest, spki_fingerprint(cert_pem)?);
+    verify_inclusion(client, verifier, tracker, &entry, index).await?;
+    Ok(Some(index))
+}
+
+/// Whether a version signed at `signed_at` must have an entry in the log
+/// `head` describes. A head without a log start is only valid for an empty
+/// log.
+fn entry_required(head: &TreeHead, signed_at: Option<DateTime<Utc>>) -> bool {
+    match (signed_at, head.log_start) {
+        (None, _) => false,
+        (Some(signed_at), Some(start)) => signed_at >= start,
+        (Some(_), None) => head.tree_size > 0,
+    }
+}
+
+/// Fetch and verify the registry's current tree head, and check that the log
+/// it describes extends `last`, the head seen before.
+///
+/// # Errors
+///
+/// Returns [`ClientError::Verify`] if the tree head is not registry-signed,
+/// the log has shrunk or its start has moved, or the consistency proof does
+/// not hold, or any error fetching them.
+pub async fn check_consistency(
+    client: &dyn RegistryClient,
+    verifier: &dyn SignatureVerifier,
+    last: Option<&TreeHead>,
+) -> Result<TreeHead, ClientError> {
+    let head = verifier.verify_tree_head(&client.tree_head().await?)?;
+    let Some(last) = last else {
+        return Ok(head);
+    };
+    if last.tree_size > 0 && last.log_start.is_some() && last.log_start != head.log_start {
+        return Err(log_error(format!(
+            "log start moved from {:?} to {:?}",
+            last.log_start, head.log_start
+        )));
+    }
+    if last.tree_size > head.tree_size {
+        return Err(log_error(format!(
+            "log shrank from {} to {} entries",
+            last.tree_size, head.tree_size
+        )));
+    }
+    let proof = if last.tree_size == head.tree_size || last.tree_size == 0 {
+        Vec::new()
+    } else {
+        let proof = client
+            .consistency_proof(last.tree_size, head.tree_size)
+            .await?;
+        if proof.first != last.tree_size || proof.second != head.tree_size {
+            return Err(log_error(
+                "registry answered for different tree sizes".to_owned(),
+            ));
+        }
+        proof.proof
+    };
+    merkle::verify_consistency(
+        last.tree_size,
+        head.tree_size,
+        &last.root_hash,
+        &head.root_hash,
+        &proof,
+    )?;
+    Ok(head)
+}
+
+/// Default path for `~/.skreg/tree_heads.toml`.
+#[must_use]
+pub fn default_tree_heads_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".skreg").join("tree_heads.toml"))
+}
+
+fn log_error(reason: String) -> ClientError {
+    ClientError::Verify(VerifyError::InvalidLogProof(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use skreg_core::types::Sha256Digest;
+
+    use crate::test_support::{FakeRegistry, TrustingVerifier};
+
+    #[test]
+    fn tree_heads_round_trip_per_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tree_heads.toml");
+        let mut seen = SeenTreeHeads::load(&path).unwrap();
+        assert!(seen.get("https://api.skreg.ai").is_none());
+
+        let head = TreeHead {
+            tree_size: 7,
+            root_hash: Sha256Digest::of(b"root"),
+            timestamp: Utc::now(),
+            log_start: None,
+        };
+        seen.set("https://api.skreg.ai/", head.clone());
+        seen.save(&path).unwrap();
+        let seen = SeenTreeHeads::load(&path).unwrap();
+        assert_eq!(seen.get("https://api.skreg.ai"), Some(&head));
+    }
+
+    #[test]
+    fn only_versions_signed_before_the_log_may_lack_an_entry() {
+        let start = Utc::now() - Duration::days(30);
+        let head = TreeHead {
+            tree_size: 3,
+            root_hash: Sha256Digest::of(b"root"),
+            timestamp: Utc::now(),
+            log_start: Some(start),
+        };
+        assert!(!entry_required(&head, None));
+        assert!(!entry_required(&head, Some(start - Duration::days(1))));
+        assert!(entry_required(&head, Some(start)));
+        assert!(entry_required(&head, Some(start + Duration::days(1))));
+
+        // A non-empty log must say when it started.
+        let undated = TreeHead {
+            log_start: None,
+            ..head.clone()
+        };
+        assert!(entry_required(&undated, Some(start)));
+        let empty = TreeHead {
+            tree_size: 0,
+            ..undated
+        };
+        assert!(!entry_required(&empty, Some(start)));
+    }
+
+    fn key_pin(fingerprint: &str) -> LogEntry {
+        LogEntry::KeyPin {
+            namespace: "acme".to_owned(),
+            fingerprint: fingerprint.to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn inclusion_checks_extend_and_record_the_last_tree_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tree_heads.toml");
+        let tracker = TreeHeadTracker::new(path.clone(), "https://api.skreg.ai");
+        let registry = FakeRegistry::default();
+        let first = key_pin("aa");
+        registry.append(&first);
+        registry.append(&key_pin("bb"));
+
+        let head = verify_inclusion(&registry, &TrustingVerifier, Some(&tracker), &first, 0)
+            .await
+            .unwrap();
+        let seen = SeenTreeHeads::load(&path).unwrap();
+        assert_eq!(seen.get("https://api.skreg.ai"), Some(&head));
+
+        // The log grew: the new head is proven consistent and recorded.
+        registry.append(&key_pin("cc"));
+        let head = verify_inclusion(&registry, &TrustingVerifier, Some(&tracker), &first, 0)
+            .await
+            .unwrap();
+        assert_eq!(head.tree_size, 3);
+
+        // The log was rewritten: even a valid inclusion proof is refused.
+        registry.leaves.lock().unwrap()[1] = merkle::leaf_hash(b"rewritten");
+        registry.append(&key_pin("dd"));
+        let err = verify_inclusion(&registry, &TrustingVerifier, Some(&tracker), &first, 0)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ClientError::Verify(VerifyError::InvalidLogProof(_))),
+            "{err}"
+        );
+        let seen = SeenTreeHeads::load(&path).unwrap();
+        assert_eq!(seen.get("https://api.skreg.ai"), Some(&head));
+    }
+}
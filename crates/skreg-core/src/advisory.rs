@@ -0,0 +1,287 @@
+//! Security advisories published by the registry against released versions.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// How serious an advisory is. Ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Informational; no action strictly required.
+    Low,
+    /// Should be addressed at the next opportunity.
+    Medium,
+    /// Likely exploitable; upgrade or uninstall promptly.
+    High,
+    /// Known malicious or actively exploited; uninstall immediately.
+    Critical,
+}
+
+impl Severity {
+    /// Parse a severity from its lowercase name.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+
+    /// Lowercase display name.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single registry advisory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Advisory {
+    /// Stable advisory identifier, e.g. `SKREG-000042`.
+    pub id: String,
+    /// Monotonic feed sequence number; used as the incremental sync cursor.
+    pub seq: i64,
+    /// Namespace slug of the affected package.
+    pub namespace: String,
+    /// Name of the affected package.
+    pub package: String,
+    /// Affected versions as a semver requirement (e.g. `=1.2.0`, `<1.4.0`, `*`).
+    pub affected: String,
+    /// How serious the issue is.
+    pub severity: Severity,
+    /// What raised the advisory: `quarantined`, `yanked`, `revoked` or `manual`.
+    pub kind: String,
+    /// One-line summary.
+    pub summary: String,
+    /// Longer description; may be empty.
+    #[serde(default)]
+    pub description: String,
+    /// First version that is no longer affected, if one exists.
+    pub fixed_version: Option<String>,
+    /// When the advisory was published.
+    pub published_at: DateTime<Utc>,
+}
+
+impl Advisory {
+    /// Whether this advisory applies to `namespace/package@version`.
+    ///
+    /// An unparseable `affected` range is treated as matching every version
+    /// so that a malformed advisory fails loud rather than silent.
+    #[must_use]
+    pub fn affects(&self, namespace: &str, package: &str, version: &Version) -> bool {
+        if self.namespace != namespace || self.package != package {
+            return false;
+        }
+        VersionReq::parse(&self.affected).map_or(true, |req| req.matches(version))
+    }
+}
+
+/// The signed body of `GET /v1/advisories`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvisoryFeed {
+    /// Advisories with `seq` greater than the request's `since`, oldest first.
+    pub advisories: Vec<Advisory>,
+    /// Cursor to pass as `since` on the next request.
+    pub next_since: i64,
+    /// Whether more advisories are available past `next_since`.
+    pub has_more: bool,
+    /// When the registry generated this response.
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Wire envelope for the advisory feed.
+///
+/// `feed` is the exact JSON text of an [`AdvisoryFeed`]; `signature_hex` is an
+/// RSA-PSS/SHA-256 signature over the SHA-256 of those bytes, made with the
+/// key of `signer_cert_pem` (the registry's Publisher CA certificate).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedAdvisoryFeed {
+    /// Serialized [`AdvisoryFeed`].
+    pub feed: String,
+    /// Hex-encoded signature over `sha256(feed)`.
+    pub signature_hex: String,
+    /// PEM certificate whose key produced `signature_hex`.
+    pub signer_cert_pem: String,
+}
+
+/// Locally cached advisories for one registry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdvisoryCache {
+    /// Cursor of the last successful sync.
+    pub since: i64,
+    /// When the cache was last synced.
+    pub synced_at: Option<DateTime<Utc>>,
+    /// Advisories keyed by ID.
+    pub advisories: BTreeMap<String, Advisory>,
+}
+
+impl AdvisoryCache {
+    /// Load the cache from `path`, returning an empty cache if it is absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(raw) => Ok(serde_json::from_str(&raw)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the cache to `path`, creating parent directories if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Merge a verified feed page into the cache and advance the cursor.
+    pub fn merge(&mut self, feed: AdvisoryFeed) {
+        for advisory in feed.advisories {
+            self.advisories.insert(advisory.id.clone(), advisory);
+        }
+        self.since = self.since.max(feed.next_since);
+        self.synced_at = Some(feed.generated_at);
+    }
+
+    /// All cached advisories affecting `namespace/package@version`, most severe first.
+    #[must_use]
+    pub fn affecting(&self, namespace: &str, package: &str, version: &Version) -> Vec<&Advisory> {
+        let mut hits: Vec<&Advisory> = self
+            .advisories
+            .values()
+            .filter(|a| a.affects(namespace, package, version))
+            .collect();
+        hits.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.id.cmp(&b.id)));
+        hits
+    }
+}
+
+/// Return the advisory cache path for `registry` (`~/.skreg/advisories/<hash>.json`).
+///
+/// Each registry gets its own file so switching contexts never mixes feeds.
+#[must_use]
+pub fn default_advisory_cache_path(registry: &str) -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_owned());
+    let digest = hex_prefix(&Sha256::digest(registry.trim_end_matches('/').as_bytes()));
+    PathBuf::from(home)
+        .join(".skreg")
+        .join("advisories")
+        .join(format!("{digest}.json"))
+}
+
+fn hex_prefix(bytes: &[u8]) -> String {
+    use std::fmt::Write as _;
+    bytes[..8].iter().fold(String::new(), |mut s, b| {
+        let _ = write!(s, "{b:02x}");
+        s
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn advisory(id: &str, seq: i64, affected: &str, severity: Severity) -> Advisory {
+        Advisory {
+            id: id.to_owned(),
+            seq,
+            namespace: "acme".to_owned(),
+            package: "helper".to_owned(),
+            affected: affected.to_owned(),
+            severity,
+            kind: "manual".to_owned(),
+            summary: "bad".to_owned(),
+            description: String::new(),
+            fixed_version: None,
+            published_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn affects_matches_range_and_package() {
+        let a = advisory("SKREG-000001", 1, "<1.2.0", Severity::High);
+        assert!(a.affects("acme", "helper", &Version::new(1, 1, 9)));
+        assert!(!a.affects("acme", "helper", &Version::new(1, 2, 0)));
+        assert!(!a.affects("acme", "other", &Version::new(1, 0, 0)));
+    }
+
+    #[test]
+    fn malformed_range_matches_everything() {
+        let a = advisory("SKREG-000001", 1, "not a range", Severity::Low);
+        assert!(a.affects("acme", "helper", &Version::new(9, 9, 9)));
+    }
+
+    #[test]
+    fn severity_orders_low_to_critical() {
+        assert!(Severity::Low < Severity::Medium);
+        assert!(Severity::High < Severity::Critical);
+        assert_eq!(Severity::parse("critical"), Some(Severity::Critical));
+        assert_eq!(Severity::parse("urgent"), None);
+    }
+
+    #[test]
+    fn merge_advances_cursor_and_sorts_by_severity() {
+        let mut cache = AdvisoryCache::default();
+        cache.merge(AdvisoryFeed {
+            advisories: vec![
+                advisory("SKREG-000001", 1, "*", Severity::Low),
+                advisory("SKREG-000002", 2, "*", Severity::Critical),
+            ],
+            next_since: 2,
+            has_more: false,
+            generated_at: Utc::now(),
+        });
+        assert_eq!(cache.since, 2);
+        let hits = cache.affecting("acme", "helper", &Version::new(1, 0, 0));
+        assert_eq!(hits[0].id, "SKREG-000002");
+        assert_eq!(hits.len(), 2);
+    }
+
+    #[test]
+    fn cache_round_trips_through_disk() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("cache.json");
+        assert!(AdvisoryCache::load(&path).unwrap().advisories.is_empty());
+        let mut cache = AdvisoryCache::default();
+        cache.merge(AdvisoryFeed {
+            advisories: vec![advisory("SKREG-000003", 3, "*", Severity::High)],
+            next_since: 3,
+            has_more: false,
+            generated_at: Utc::now(),
+        });
+        cache.save(&path).unwrap();
+        let loaded = AdvisoryCache::load(&path).unwrap();
+        assert_eq!(loaded.since, 3);
+        assert!(loaded.advisories.contains_key("SKREG-000003"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Canonical JSON (RFC 8785, JCS) and the envelope skreg signs JSON in.
+//!
+//! Signatures over JSON must not depend on field order, whitespace or escape
+//! choices, which differ between serializers and between versions of one.
+//! [`to_canonical_json`] renders a value the single way RFC 8785 allows:
+//! object keys sorted by UTF-16 code units, no whitespace, minimal string
+//! escapes and numbers formatted as ECMAScript does.
+//!
+//! [`signing_bytes`] prefixes the canonical JSON with a domain naming what
+//! is being signed, so a signature over one kind of document can never be
+//! replayed as another.
+//!
+//! `serde_json` is built with `float_roundtrip` so that numbers parsed from
+//! JSON text round to the same doubles as in other JCS implementations.
+
+use std::fmt::Write as _;
+
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use thiserror::Error;
+
+/// First line of every signing envelope.
+const ENVELOPE_PREFIX: &str = "skreg-signed-json-v1";
+
+/// Largest integer magnitude an IEEE 754 double represents exactly.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Errors from canonicalizing JSON.
+#[derive(Debug, Error)]
+pub enum CanonicalError {
+    /// The value could not be serialized to JSON.
+    #[error("serializing JSON: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    /// A number has no exact IEEE 754 double representation.
+    #[error("number {0} cannot be represented exactly as a double")]
+    Number(String),
+
+    /// A signing domain is empty or contains a newline.
+    #[error("invalid signing domain {0:?}")]
+    Domain(String),
+}
+
+/// Serialize `value` to RFC 8785 canonical JSON.
+///
+/// # Errors
+///
+/// Returns [`CanonicalError::Serialize`] if `value` cannot be serialized, or
+/// [`CanonicalError::Number`] if it contains an integer beyond ±(2^53 − 1)
+/// or a non-finite float.
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<String, CanonicalError> {
+    let mut out = String::new();
+    write_value(&mut out, &serde_json::to_value(value)?)?;
+    Ok(out)
+}
+
+/// The bytes to sign for `value` in `domain`: the line
+/// `skreg-signed-json-v1`, the domain on its own line, then the canonical
+/// JSON of `value`.
+///
+/// # Errors
+///
+/// Returns [`CanonicalError::Domain`] if `domain` is empty or contains a
+/// newline, or any error from [`to_canonical_json`].
+pub fn signing_bytes<T: Serialize + ?Sized>(
+    domain: &str,
+    value: &T,
+) -> Result<Vec<u8>, CanonicalError> {
+    if domain.is_empty() || domain.contains('\n') {
+        return Err(CanonicalError::Domain(domain.to_owned()));
+    }
+    let json = to_canonical_json(value)?;
+    Ok(format!("{ENVELOPE_PREFIX}\n{domain}\n{json}").into_bytes())
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), CanonicalError> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(out, n)?,
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => write_object(out, map)?,
+    }
+    Ok(())
+}
+
+fn write_object(out: &mut String, map: &Map<String, Value>) -> Result<(), CanonicalError> {
+    let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+    out.push('{');
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_string(out, key);
+        out.push(':');
+        write_value(out, value)?;
+    }
+    out.push('}');
+    Ok(())
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_number(out: &mut String, n: &Number) -> Result<(), CanonicalError> {
+    if let Some(u) = n.as_u64() {
+        if u > MAX_SAFE_INTEGER {
+            return Err(CanonicalError::Number(n.to_string()));
+        }
+        let _ = write!(out, "{u}");
+    } else if let Some(i) = n.as_i64() {
+        if i.unsigned_abs() > MAX_SAFE_INTEGER {
+            return Err(CanonicalError::Number(n.to_string()));
+        }
+        let _ = write!(out, "{i}");
+    } else {
+        match n.as_f64() {
+            Some(f) if f.is_finite() => write_double(out, f),
+            _ => return Err(CanonicalError::Number(n.to_string())),
+        }
+    }
+    Ok(())
+}
+
+/// Format a finite double as ECMAScript's `Number.prototype.toString` does.
+fn write_double(out: &mut String, f: f64) {
+    if f == 0.0 {
+        out.push('0');
+        return;
+    }
+    if f < 0.0 {
+        out.push('-');
+    }
+    // `{:e}` gives the shortest digits that round-trip, as ECMAScript
+    // requires, in the form `d[.ddd]e<exp>`.
+    let sci = format!("{:e}", f.abs());
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let k = i32::try_from(digits.len()).unwrap_or(i32::MAX);
+    // The decimal point sits after the first `n` digits.
+    let n = exp.parse::<i32>().unwrap_or(0) + 1;
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat('0').take((n - k).unsigned_abs() as usize));
+    } else if 0 < n && n <= 21 {
+        let (int, frac) = digits.split_at(n.unsigned_abs() as usize);
+        let _ = write!(out, "{int}.{frac}");
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat('0').take(n.unsigned_abs() as usize));
+        out.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        out.push_str(first);
+        if !rest.is_empty() {
+            let _ = write!(out, ".{rest}");
+        }
+        let sign = if n > 0 { '+' } else { '-' };
+        let _ = write!(out, "e{sign}{}", (n - 1).unsigned_abs());
+    }
+}
@@ -0,0 +1,69 @@
+//! CA-issued publisher certificates as listed by the registry, and the
+//! signed requests that revoke them.
+//!
+//! A publisher revokes one of its certificates either with a
+//! [`CertRevocationToken`] signed by the certificate's own key, which takes
+//! effect at once, or, when the key is lost, with an unsigned token that the
+//! namespace owner confirms by email.
+
+use serde::{Deserialize, Serialize};
+
+use crate::canonical::{signing_bytes, CanonicalError};
+
+/// A publisher certificate of a namespace, as served by
+/// `GET /v1/namespaces/:ns/certs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublisherCert {
+    /// Registry serial of the certificate.
+    pub serial: i64,
+    /// SHA-256 SPKI fingerprint (hex) of the certified key.
+    pub fingerprint: String,
+    /// RFC 3339 timestamp when the certificate was issued.
+    pub issued_at: String,
+    /// RFC 3339 timestamp when the certificate expires.
+    pub expires_at: String,
+    /// RFC 3339 timestamp when the certificate was revoked, if it was.
+    pub revoked_at: Option<String>,
+    /// Why the certificate was revoked, if a reason was given.
+    pub revocation_reason: Option<String>,
+    /// PEM-encoded certificate.
+    pub cert_pem: String,
+}
+
+/// A request to revoke a publisher certificate.
+///
+/// When signed, the certificate's key signs the SHA-256 of
+/// [`CertRevocationToken::signing_bytes`], using the scheme for its
+/// algorithm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertRevocationToken {
+    /// Authenticated namespace slug.
+    pub namespace: String,
+    /// Registry serial of the certificate, in decimal. A string because
+    /// serials exceed the integers canonical JSON can hold.
+    pub serial: String,
+    /// Why the certificate is revoked, e.g. `"key compromise"`.
+    pub reason: Option<String>,
+    /// 32-byte random nonce (hex) — prevents replay.
+    pub nonce: String,
+    /// RFC 3339 timestamp when this token was created.
+    pub issued_at: String,
+    /// RFC 3339 timestamp when this token expires (5 min after `issued_at`).
+    pub expires_at: String,
+}
+
+impl CertRevocationToken {
+    /// Signing domain of certificate revocations; see [`crate::canonical`].
+    pub const SIGNING_DOMAIN: &'static str = "cert-revocation";
+
+    /// The bytes the certificate's key signs: the token's RFC 8785 canonical
+    /// JSON in the `cert-revocation` signing envelope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails (in practice this never
+    /// happens for these plain fields).
+    pub fn signing_bytes(&self) -> Result<Vec<u8>, CanonicalError> {
+        signing_bytes(Self::SIGNING_DOMAIN, self)
+    }
+}
@@ -36,12 +36,20 @@ pub struct ContextConfig {
     pub namespace: String,
     /// Plaintext API key for this namespace.
     pub api_key: String,
-    /// Optional path to a PEM root CA file.
+    /// Optional path to a PEM file of one or more root CA certificates.
     ///
-    /// When set, overrides the compiled-in production root CA for all
-    /// signature verification in this context.
+    /// When set, replaces the compiled-in production root CA for all
+    /// signature verification in this context; every certificate in the
+    /// file is trusted.
     #[serde(default)]
     pub root_ca_pem: Option<std::path::PathBuf>,
+    /// Optional shell command that signs with the publisher key instead of
+    /// a key file, like git's `gpg.program`.
+    ///
+    /// It receives a hex SHA-256 digest on stdin and prints the hex
+    /// signature on stdout; see `skreg_cli::signer`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_program: Option<String>,
 }
 
 /// Persisted CLI configuration — supports multiple named contexts.
@@ -163,6 +171,7 @@ impl CliConfig {
                 namespace: old.namespace,
                 api_key: old.api_key,
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         Ok(CliConfig {
@@ -266,6 +275,7 @@ api_key = "skreg_abc123"
                 namespace: "acme".to_owned(),
                 api_key: "skreg_abc".to_owned(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         let cfg = CliConfig {
@@ -291,6 +301,7 @@ api_key = "skreg_abc123"
             namespace: "n".into(),
             api_key: "k".into(),
             root_ca_pem: None,
+            signing_program: None,
         };
         config.add_context("new".into(), ctx.clone(), false);
         assert_eq!(config.active_context, "new");
@@ -307,6 +318,7 @@ api_key = "skreg_abc123"
                 namespace: "n1".into(),
                 api_key: "k1".into(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         let mut config = CliConfig {
@@ -319,6 +331,7 @@ api_key = "skreg_abc123"
             namespace: "n2".into(),
             api_key: "k2".into(),
             root_ca_pem: None,
+            signing_program: None,
         };
         config.add_context("new".into(), ctx2, true);
         assert_eq!(config.active_context, "new");
@@ -334,6 +347,7 @@ api_key = "skreg_abc123"
                 namespace: "n1".into(),
                 api_key: "k1".into(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         contexts.insert(
@@ -343,6 +357,7 @@ api_key = "skreg_abc123"
                 namespace: "n2".into(),
                 api_key: "k2".into(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         let mut config = CliConfig {
@@ -374,6 +389,7 @@ api_key = "skreg_abc123"
                 namespace: "n1".into(),
                 api_key: "k1".into(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         contexts.insert(
@@ -383,6 +399,7 @@ api_key = "skreg_abc123"
                 namespace: "n2".into(),
                 api_key: "k2".into(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         let mut config = CliConfig {
@@ -405,6 +422,7 @@ api_key = "skreg_abc123"
                 namespace: "n1".into(),
                 api_key: "k1".into(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         let mut config = CliConfig {
@@ -547,6 +565,7 @@ root_ca_pem = "/home/user/.skreg/dev/root-ca.pem"
                 namespace: "acme".to_owned(),
                 api_key: "skreg_abc".to_owned(),
                 root_ca_pem: None,
+                signing_program: None,
             },
         );
         let cfg = CliConfig {
@@ -0,0 +1,563 @@
+//! The `SKILL.md` YAML frontmatter schema.
+//!
+//! Parsing happens at two levels. [`RawFrontmatter::parse`] only checks that
+//! the block is present and well-typed YAML; every field is optional. It is
+//! what the scanners use, so that a package with, say, a bad version still
+//! has its hook commands inspected. [`SkillFrontmatter::parse`] additionally
+//! enforces required fields and registry limits and is what `skreg pack` and
+//! Stage 1 vetting use.
+//!
+//! Line numbers in errors refer to lines of `SKILL.md` itself (the opening
+//! `---` is line 1).
+
+use std::collections::BTreeMap;
+
+use semver::Version;
+use serde::Deserialize;
+use serde_yaml::Value;
+use thiserror::Error;
+
+use crate::limits;
+
+/// Highest frontmatter schema version this build understands.
+///
+/// Packages declare it as `metadata.skreg-schema`; an absent key means 1.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Errors produced while parsing or validating frontmatter.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FrontmatterError {
+    /// The document does not start with `---`.
+    #[error("SKILL.md is missing YAML frontmatter")]
+    Missing,
+    /// No closing `---` line was found.
+    #[error("SKILL.md frontmatter is not closed (missing closing ---)")]
+    Unclosed,
+    /// The block is not valid YAML, or a field has the wrong type.
+    #[error("SKILL.md frontmatter is not valid YAML{}: {message}", at(*.line, *.column))]
+    Yaml {
+        /// Parser message.
+        message: String,
+        /// 1-based line in `SKILL.md`, when known.
+        line: Option<usize>,
+        /// 1-based column, when known.
+        column: Option<usize>,
+    },
+    /// A required field is absent or empty.
+    #[error("SKILL.md frontmatter is missing required field '{field}'")]
+    MissingField {
+        /// Dotted field path, e.g. `metadata.version`.
+        field: String,
+    },
+    /// A field is present but violates the schema.
+    #[error("SKILL.md frontmatter field '{field}' is invalid{}: {reason}", at(*.line, None))]
+    InvalidField {
+        /// Dotted field path.
+        field: String,
+        /// Why it was rejected.
+        reason: String,
+        /// 1-based line in `SKILL.md`, when known.
+        line: Option<usize>,
+    },
+    /// The package targets a newer schema than this build supports.
+    #[error("SKILL.md uses frontmatter schema {found}; this version of skreg supports up to {supported}")]
+    UnsupportedSchema {
+        /// Declared schema version.
+        found: u32,
+        /// [`CURRENT_SCHEMA_VERSION`].
+        supported: u32,
+    },
+}
+
+impl FrontmatterError {
+    /// 1-based `SKILL.md` line the error points at, when known.
+    #[must_use]
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::Yaml { line, .. } | Self::InvalidField { line, .. } => *line,
+            Self::Missing | Self::Unclosed => Some(1),
+            Self::MissingField { .. } | Self::UnsupportedSchema { .. } => None,
+        }
+    }
+}
+
+fn at(line: Option<usize>, column: Option<usize>) -> String {
+    match (line, column) {
+        (Some(l), Some(c)) => format!(" at line {l}, column {c}"),
+        (Some(l), None) => format!(" at line {l}"),
+        _ => String::new(),
+    }
+}
+
+/// `allowed-tools`, written either as a space-delimited string or a list.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum AllowedTools {
+    Delimited(String),
+    List(Vec<String>),
+}
+
+impl AllowedTools {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::Delimited(s) => s.split_whitespace().map(str::to_owned).collect(),
+            Self::List(v) => v,
+        }
+    }
+}
+
+/// One `{ type, command, timeout }` entry under a hook matcher.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct HookCommand {
+    /// Hook type; `command` is the only type that runs a process.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Shell command, for `type: command`.
+    pub command: Option<String>,
+    /// Timeout in seconds.
+    pub timeout: Option<u64>,
+}
+
+/// A matcher and the hooks it triggers for one event.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct HookMatcher {
+    /// Tool-name pattern, e.g. `Bash`. Absent matches every tool.
+    pub matcher: Option<String>,
+    /// Hooks to run.
+    #[serde(default)]
+    pub hooks: Vec<HookCommand>,
+}
+
+/// The `hooks:` block.
+///
+/// Kept as raw YAML so that unfamiliar or malformed shapes never hide a
+/// command from the scanners; [`Hooks::events`] gives the typed view.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct Hooks(Value);
+
+impl Hooks {
+    /// Whether no hooks are declared.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        match &self.0 {
+            Value::Null => true,
+            Value::Mapping(m) => m.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Well-formed events keyed by event name (`PreToolUse`, ...). Events
+    /// whose value does not match the schema are left out.
+    #[must_use]
+    pub fn events(&self) -> BTreeMap<String, Vec<HookMatcher>> {
+        let Value::Mapping(map) = &self.0 else {
+            return BTreeMap::new();
+        };
+        map.iter()
+            .filter_map(|(k, v)| {
+                let event = k.as_str()?.to_owned();
+                let matchers = serde_yaml::from_value(v.clone()).ok()?;
+                Some((event, matchers))
+            })
+            .collect()
+    }
+
+    /// Every string under a `command` key anywhere in the block, regardless
+    /// of whether the surrounding structure is well-formed.
+    #[must_use]
+    pub fn commands(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        collect_commands(&self.0, &mut out);
+        out
+    }
+}
+
+fn collect_commands<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::Mapping(map) => {
+            for (k, v) in map {
+                match (k.as_str(), v.as_str()) {
+                    (Some("command"), Some(cmd)) => out.push(cmd),
+                    _ => collect_commands(v, out),
+                }
+            }
+        }
+        Value::Sequence(seq) => {
+            for item in seq {
+                collect_commands(item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Frontmatter exactly as written, with every field optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RawFrontmatter {
+    /// `name`.
+    pub name: Option<String>,
+    /// `description`.
+    pub description: Option<String>,
+    /// `license`.
+    pub license: Option<String>,
+    /// `compatibility`.
+    pub compatibility: Option<String>,
+    allowed_tools: Option<AllowedTools>,
+    /// `metadata`, with values as written.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, Value>,
+    /// `hooks`.
+    #[serde(default)]
+    pub hooks: Hooks,
+    #[serde(skip)]
+    yaml: String,
+}
+
+impl RawFrontmatter {
+    /// Parse the frontmatter block of `content` without checking required
+    /// fields or limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FrontmatterError::Missing`], [`FrontmatterError::Unclosed`]
+    /// or [`FrontmatterError::Yaml`].
+    pub fn parse(content: &str) -> Result<Self, FrontmatterError> {
+        let yaml = extract_block(content)?;
+        let mut raw: Self = if yaml.trim().is_empty() {
+            Self::default()
+        } else {
+            serde_yaml::from_str(yaml).map_err(|e| {
+                let loc = e.location();
+                FrontmatterError::Yaml {
+                    message: strip_location(&e.to_string()),
+                    line: loc.as_ref().map(serde_yaml::Location::line),
+                    column: loc.as_ref().map(serde_yaml::Location::column),
+                }
+            })?
+        };
+        yaml.clone_into(&mut raw.yaml);
+        Ok(raw)
+    }
+
+    /// `allowed-tools` as a list, whichever form it was written in.
+    #[must_use]
+    pub fn allowed_tools(&self) -> Vec<String> {
+        self.allowed_tools
+            .clone()
+            .map(AllowedTools::into_vec)
+            .unwrap_or_default()
+    }
+
+    /// Space-delimited globs from `metadata.skreg-include`, limiting which
+    /// files `skreg pack` includes.
+    #[must_use]
+    pub fn include_globs(&self) -> Vec<String> {
+        match self.metadata.get("skreg-include") {
+            Some(Value::String(s)) => s.split_whitespace().map(str::to_owned).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 1-based `SKILL.md` line on which the dotted `field` is declared.
+    #[must_use]
+    pub fn line_of(&self, field: &str) -> Option<usize> {
+        field_line(&self.yaml, field)
+    }
+
+    fn invalid(&self, field: &str, reason: impl Into<String>) -> FrontmatterError {
+        FrontmatterError::InvalidField {
+            field: field.to_owned(),
+            reason: reason.into(),
+            line: self.line_of(field),
+        }
+    }
+}
+
+/// Validated frontmatter of a publishable skill.
+#[derive(Debug, Clone)]
+pub struct SkillFrontmatter {
+    /// Schema version from `metadata.skreg-schema`.
+    pub schema_version: u32,
+    /// Skill name slug.
+    pub name: String,
+    /// Human-readable description.
+    pub description: String,
+    /// Package version from `metadata.version`.
+    pub version: Version,
+    /// SPDX identifier or license file reference.
+    pub license: Option<String>,
+    /// Free-form environment requirements.
+    pub compatibility: Option<String>,
+    /// Tools the skill may use without prompting.
+    pub allowed_tools: Vec<String>,
+    /// All `metadata` entries, scalars rendered as strings.
+    pub metadata: BTreeMap<String, String>,
+    /// Declared hooks.
+    pub hooks: Hooks,
+}
+
+impl SkillFrontmatter {
+    /// Parse and validate the frontmatter of `content`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`FrontmatterError`] found.
+    pub fn parse(content: &str) -> Result<Self, FrontmatterError> {
+        RawFrontmatter::parse(content)?.validate()
+    }
+}
+
+impl RawFrontmatter {
+    /// Enforce required fields and limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`FrontmatterError`] found.
+    pub fn validate(self) -> Result<SkillFrontmatter, FrontmatterError> {
+        let mut metadata = BTreeMap::new();
+        for (key, value) in &self.metadata {
+            let s = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => return Err(self.invalid(&format!("metadata.{key}"), "must be a scalar value")),
+            };
+            metadata.insert(key.clone(), s);
+        }
+
+        let schema_version = match metadata.get("skreg-schema") {
+            None => 1,
+            Some(s) => s.parse::<u32>().ok().filter(|v| *v >= 1).ok_or_else(|| {
+                self.invalid("metadata.skreg-schema", "must be a positive integer")
+            })?,
+        };
+        if schema_version > CURRENT_SCHEMA_VERSION {
+            return Err(FrontmatterError::UnsupportedSchema {
+                found: schema_version,
+                supported: CURRENT_SCHEMA_VERSION,
+            });
+        }
+
+        let name = required(self.name.as_deref(), "name")?;
+        validate_name(name).map_err(|reason| self.invalid("name", reason))?;
+
+        let description = required(self.description.as_deref(), "description")?;
+        if description.len() > limits::LIMIT_DESCRIPTION_LEN {
+            return Err(self.invalid(
+                "description",
+                format!(
+                    "must be {} characters or fewer",
+                    limits::LIMIT_DESCRIPTION_LEN
+                ),
+            ));
+        }
+
+        let version_str = required(
+            metadata.get("version").map(String::as_str),
+            "metadata.version",
+        )?;
+        let version = Version::parse(version_str).map_err(|_| {
+            self.invalid(
+                "metadata.version",
+                format!("{version_str:?} is not valid semver"),
+            )
+        })?;
+
+        if let Some(compat) = self.compatibility.as_deref() {
+            if compat.is_empty() || compat.len() > limits::LIMIT_COMPATIBILITY_LEN {
+                return Err(self.invalid(
+                    "compatibility",
+                    format!("must be 1–{} characters", limits::LIMIT_COMPATIBILITY_LEN),
+                ));
+            }
+        }
+
+        Ok(SkillFrontmatter {
+            schema_version,
+            name: name.to_owned(),
+            description: description.to_owned(),
+            version,
+            allowed_tools: self.allowed_tools(),
+            license: self.license,
+            compatibility: self.compatibility,
+            metadata,
+            hooks: self.hooks,
+        })
+    }
+}
+
+fn required<'a>(value: Option<&'a str>, field: &str) -> Result<&'a str, FrontmatterError> {
+    value
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| FrontmatterError::MissingField {
+            field: field.to_owned(),
+        })
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.len() > limits::LIMIT_NAME_LEN {
+        return Err(format!(
+            "must be {} characters or fewer",
+            limits::LIMIT_NAME_LEN
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err("must contain only lowercase letters, digits, and hyphens".to_owned());
+    }
+    if name.starts_with('-') || name.ends_with('-') {
+        return Err("must not start or end with a hyphen".to_owned());
+    }
+    if name.contains("--") {
+        return Err("must not contain consecutive hyphens".to_owned());
+    }
+    Ok(())
+}
+
+/// The YAML between the opening `---` and the closing `---` line. The
+/// returned slice starts with the remainder of line 1, so YAML line numbers
+/// coincide with `SKILL.md` line numbers.
+fn extract_block(content: &str) -> Result<&str, FrontmatterError> {
+    let after_open = content
+        .strip_prefix("---")
+        .ok_or(FrontmatterError::Missing)?;
+    let close = after_open
+        .find("\n---\n")
+        .or_else(|| after_open.strip_suffix("\n---").map(str::len))
+        .ok_or(FrontmatterError::Unclosed)?;
+    Ok(&after_open[..close])
+}
+
+/// `serde_yaml` appends " at line L column C" to its messages; the location is
+/// reported separately.
+fn strip_location(message: &str) -> String {
+    message
+        .rfind(" at line ")
+        .map_or(message, |i| &message[..i])
+        .to_owned()
+}
+
+/// Locate `key:` for a top-level key, or `parent.child` one level deep.
+fn field_line(yaml: &str, field: &str) -> Option<usize> {
+    let (parent, child) = match field.split_once('.') {
+        Some((p, c)) => (p, Some(c)),
+        None => (field, None),
+    };
+    let is_key = |line: &str, key: &str| {
+        line.strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with(':'))
+    };
+    let lines: Vec<&str> = yaml.lines().collect();
+    let start = lines.iter().position(|l| is_key(l, parent))?;
+    let Some(child) = child else {
+        return Some(start + 1);
+    };
+    lines[start + 1..]
+        .iter()
+        .take_while(|l| l.is_empty() || l.starts_with(char::is_whitespace))
+        .position(|l| is_key(l.trim_start(), child))
+        .map(|i| start + i + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = "---\nname: my-skill\ndescription: Does something useful\nlicense: MIT\nallowed-tools: Bash Read\nmetadata:\n  version: \"1.2.3\"\n  author: acme\n---\n# Body\n";
+
+    #[test]
+    fn parses_known_fields() {
+        let fm = SkillFrontmatter::parse(VALID).unwrap();
+        assert_eq!(fm.name, "my-skill");
+        assert_eq!(fm.version, Version::new(1, 2, 3));
+        assert_eq!(fm.license.as_deref(), Some("MIT"));
+        assert_eq!(fm.allowed_tools, ["Bash", "Read"]);
+        assert_eq!(fm.metadata["author"], "acme");
+        assert_eq!(fm.schema_version, 1);
+    }
+
+    #[test]
+    fn allowed_tools_accepts_a_list() {
+        let raw = RawFrontmatter::parse("---\nallowed-tools:\n  - Bash\n  - Read\n---\n").unwrap();
+        assert_eq!(raw.allowed_tools(), ["Bash", "Read"]);
+    }
+
+    #[test]
+    fn include_globs_are_space_delimited() {
+        let raw = RawFrontmatter::parse(
+            "---\nmetadata:\n  skreg-include: \"references/*.md  scripts/*\"\n---\n",
+        )
+        .unwrap();
+        assert_eq!(raw.include_globs(), ["references/*.md", "scripts/*"]);
+        assert!(RawFrontmatter::parse(VALID)
+            .unwrap()
+            .include_globs()
+            .is_empty());
+    }
+
+    #[test]
+    fn missing_and_unclosed_frontmatter() {
+        assert_eq!(
+            RawFrontmatter::parse("# Title\n").unwrap_err(),
+            FrontmatterError::Missing
+        );
+        assert_eq!(
+            RawFrontmatter::parse("---\nname: x\n").unwrap_err(),
+            FrontmatterError::Unclosed
+        );
+    }
+
+    #[test]
+    fn yaml_errors_point_at_skill_md_lines() {
+        let err =
+            RawFrontmatter::parse("---\nname: ok\ndescription: [unclosed\n---\n").unwrap_err();
+        let FrontmatterError::Yaml { line, .. } = err else {
+            panic!("expected Yaml, got {err:?}");
+        };
+        assert!(line.is_some_and(|l| l >= 3), "line: {line:?}");
+    }
+
+    #[test]
+    fn invalid_field_reports_its_line() {
+        let md = "---\nname: my-skill\ndescription: Something useful\nmetadata:\n  author: acme\n  version: not-semver\n---\n";
+        let err = SkillFrontmatter::parse(md).unwrap_err();
+        assert_eq!(err.line(), Some(6));
+        assert!(err.to_string().contains("not valid semver"), "got: {err}");
+    }
+
+    #[test]
+    fn missing_required_fields() {
+        let err = SkillFrontmatter::parse("---\ndescription: Something useful\n---\n").unwrap_err();
+        assert_eq!(
+            err,
+            FrontmatterError::MissingField {
+                field: "name".to_owned()
+            }
+        );
+        let err = SkillFrontmatter::parse("---\nname: a\ndescription: d\n---\n").unwrap_err();
+        assert!(err.to_string().contains("metadata.version"), "got: {err}");
+    }
+
+    #[test]
+    fn newer_schema_is_rejected() {
+        let md = "---\nname: a\ndescription: d\nmetadata:\n  version: \"1.0.0\"\n  skreg-schema: 2\n---\n";
+        assert!(matches!(
+            SkillFrontmatter::parse(md),
+            Err(FrontmatterError::UnsupportedSchema { found: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn hooks_expose_typed_events_and_all_commands() {
+        let md = "---\nhooks:\n  PreToolUse:\n    - matcher: Bash\n      hooks:\n        - type: command\n          command: lint.sh\n          timeout: 30\n  custom:\n    nested:\n      - command: other.sh\n---\n";
+        let raw = RawFrontmatter::parse(md).unwrap();
+        let events = raw.hooks.events();
+        assert_eq!(events["PreToolUse"][0].hooks[0].timeout, Some(30));
+        assert!(!events.contains_key("custom"));
+        assert_eq!(raw.hooks.commands(), ["lint.sh", "other.sh"]);
+    }
+}
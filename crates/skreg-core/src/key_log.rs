@@ -0,0 +1,276 @@
+//! A namespace's signed, hash-linked history of its signing keys.
+//!
+//! Every pin, addition, rotation, removal and revocation of a namespace's
+//! keys is recorded as a [`KeyLogEntry`] whose `prev_hash` is the hash of the entry
+//! before it. The registry serves the whole log signed by the Publisher CA
+//! as a [`SignedKeyLog`], so a client can check that a version was signed by
+//! a key that was valid when the registry timestamped it, even after that
+//! key was rotated out or removed. A client that remembers a log's
+//! [`KeyLogHead`] can also tell whether a later log extends it or rewrites
+//! history.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::canonical::{signing_bytes, CanonicalError};
+use crate::types::Sha256Digest;
+
+/// Errors from checking a [`KeyLog`].
+#[derive(Debug, Error)]
+pub enum KeyLogError {
+    /// An entry could not be hashed.
+    #[error(transparent)]
+    Canonical(#[from] CanonicalError),
+
+    /// Entries are not numbered `0, 1, 2, …` in order.
+    #[error("entry {position} has sequence number {seq}")]
+    Sequence {
+        /// Position of the entry in the log.
+        position: usize,
+        /// Sequence number it carries.
+        seq: u64,
+    },
+
+    /// An entry's `prev_hash` is not the hash of the entry before it.
+    #[error("entry {0} does not link to the entry before it")]
+    BrokenLink(u64),
+
+    /// An entry is dated before the entry before it.
+    #[error("entry {0} is dated before the entry before it")]
+    OutOfOrder(u64),
+}
+
+/// A change to a namespace's key set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum KeyEvent {
+    /// The namespace's first key, pinned by its first publish.
+    Pinned {
+        /// SHA-256 SPKI fingerprint (hex) of the key.
+        fingerprint: String,
+    },
+    /// A confirmed key-set change added a key.
+    Added {
+        /// Fingerprint of the key added.
+        fingerprint: String,
+        /// Fingerprint of the key that signed the change.
+        added_by: String,
+        /// When the key stops being valid, if it was added with an expiry.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<DateTime<Utc>>,
+    },
+    /// A confirmed rotation replaced a key.
+    Rotated {
+        /// Fingerprint of the key replaced.
+        old_fingerprint: String,
+        /// Fingerprint of the new key.
+        new_fingerprint: String,
+    },
+    /// A confirmed key-set change removed a key.
+    Removed {
+        /// Fingerprint of the key removed.
+        fingerprint: String,
+    },
+    /// The registry revoked a key, or the last unrevoked publisher
+    /// certificate for it.
+    Revoked {
+        /// Fingerprint of the key revoked.
+        fingerprint: String,
+    },
+}
+
+/// One event in a [`KeyLog`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyLogEntry {
+    /// Position of the entry in the log, starting at 0.
+    pub seq: u64,
+    /// When the event took effect.
+    pub at: DateTime<Utc>,
+    /// What changed.
+    pub event: KeyEvent,
+    /// [`hash`](Self::hash) of the entry before this one; for the first
+    /// entry, the SHA-256 of the empty string.
+    pub prev_hash: Sha256Digest,
+}
+
+impl KeyLogEntry {
+    /// Signing domain of key log entries; see [`crate::canonical`].
+    pub const SIGNING_DOMAIN: &'static str = "key-log-entry";
+
+    /// The hash the next entry links to: the SHA-256 of the entry's RFC 8785
+    /// canonical JSON in the `key-log-entry` signing envelope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails (in practice this never
+    /// happens for these plain fields).
+    pub fn hash(&self) -> Result<Sha256Digest, CanonicalError> {
+        Ok(Sha256Digest::of(&signing_bytes(
+            Self::SIGNING_DOMAIN,
+            self,
+        )?))
+    }
+}
+
+/// The length and last entry hash of a [`KeyLog`], which a client keeps to
+/// check that later logs only append to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyLogHead {
+    /// Number of entries.
+    pub len: u64,
+    /// Hash of the last entry.
+    pub hash: Sha256Digest,
+}
+
+/// The key history of one namespace, oldest entry first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyLog {
+    /// Namespace slug.
+    pub namespace: String,
+    /// Every key event of the namespace, oldest first.
+    pub entries: Vec<KeyLogEntry>,
+}
+
+impl KeyLog {
+    /// Number each of `events` in turn and link it to the one before.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry cannot be hashed.
+    pub fn build(
+        namespace: impl Into<String>,
+        events: impl IntoIterator<Item = (DateTime<Utc>, KeyEvent)>,
+    ) -> Result<Self, CanonicalError> {
+        let mut entries: Vec<KeyLogEntry> = Vec::new();
+        let mut prev_hash = Sha256Digest::of(b"");
+        for (seq, (at, event)) in (0..).zip(events) {
+            let entry = KeyLogEntry {
+                seq,
+                at,
+                event,
+                prev_hash,
+            };
+            prev_hash = entry.hash()?;
+            entries.push(entry);
+        }
+        Ok(Self {
+            namespace: namespace.into(),
+            entries,
+        })
+    }
+
+    /// Check that the entries are numbered in order, dated in order and each
+    /// linked to the one before.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`KeyLogError`] naming the first entry that breaks the
+    /// chain.
+    pub fn verify_links(&self) -> Result<(), KeyLogError> {
+        let mut prev: Option<&KeyLogEntry> = None;
+        let mut prev_hash = Sha256Digest::of(b"");
+        for (position, entry) in self.entries.iter().enumerate() {
+            if usize::try_from(entry.seq).ok() != Some(position) {
+                return Err(KeyLogError::Sequence {
+                    position,
+                    seq: entry.seq,
+                });
+            }
+            if entry.prev_hash != prev_hash {
+                return Err(KeyLogError::BrokenLink(entry.seq));
+            }
+            if prev.is_some_and(|p| entry.at < p.at) {
+                return Err(KeyLogError::OutOfOrder(entry.seq));
+            }
+            prev_hash = entry.hash()?;
+            prev = Some(entry);
+        }
+        Ok(())
+    }
+
+    /// The head of the log, or `None` if it is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the last entry cannot be hashed.
+    pub fn head(&self) -> Result<Option<KeyLogHead>, CanonicalError> {
+        self.entries
+            .last()
+            .map(|last| {
+                Ok(KeyLogHead {
+                    len: last.seq + 1,
+                    hash: last.hash()?,
+                })
+            })
+            .transpose()
+    }
+
+    /// Whether this log starts with the log whose head is `head`. Only
+    /// meaningful once [`verify_links`](Self::verify_links) has passed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry cannot be hashed.
+    pub fn extends(&self, head: &KeyLogHead) -> Result<bool, CanonicalError> {
+        let Some(entry) = usize::try_from(head.len)
+            .ok()
+            .and_then(|len| len.checked_sub(1))
+            .and_then(|i| self.entries.get(i))
+        else {
+            return Ok(false);
+        };
+        Ok(entry.hash()? == head.hash)
+    }
+
+    /// Whether `fingerprint` was one of the namespace's keys at `at`, after
+    /// every event dated at or before it, and had not expired by then. A
+    /// rotated-in key keeps the expiry of the key it replaced.
+    #[must_use]
+    pub fn valid_at(&self, fingerprint: &str, at: DateTime<Utc>) -> bool {
+        let mut keys = BTreeMap::new();
+        for entry in self.entries.iter().take_while(|e| e.at <= at) {
+            match &entry.event {
+                KeyEvent::Pinned { fingerprint } => {
+                    keys.insert(fingerprint.as_str(), None);
+                }
+                KeyEvent::Added {
+                    fingerprint,
+                    expires_at,
+                    ..
+                } => {
+                    keys.insert(fingerprint.as_str(), *expires_at);
+                }
+                KeyEvent::Rotated {
+                    old_fingerprint,
+                    new_fingerprint,
+                } => {
+                    let expires_at = keys.remove(old_fingerprint.as_str()).flatten();
+                    keys.insert(new_fingerprint.as_str(), expires_at);
+                }
+                KeyEvent::Removed { fingerprint } | KeyEvent::Revoked { fingerprint } => {
+                    keys.remove(fingerprint.as_str());
+                }
+            }
+        }
+        keys.get(fingerprint)
+            .is_some_and(|expires_at| expires_at.map_or(true, |expires_at| at < expires_at))
+    }
+}
+
+/// Wire envelope for a key log, served by `GET /v1/namespaces/:ns/key-log`.
+///
+/// `key_log` is the exact JSON text of a [`KeyLog`]; `signature_hex` is an
+/// RSA-PSS/SHA-256 signature over the SHA-256 of those bytes, made with the
+/// key of `signer_cert_pem`, which must chain to a trusted root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedKeyLog {
+    /// Serialized [`KeyLog`].
+    pub key_log: String,
+    /// Hex-encoded signature over `sha256(key_log)`.
+    pub signature_hex: String,
+    /// PEM certificate whose key produced `signature_hex`.
+    pub signer_cert_pem: String,
+}
@@ -0,0 +1,101 @@
+//! Namespace key sets: the keys allowed to sign for a namespace, and the
+//! signed changes that add and remove them.
+//!
+//! A namespace may have several active signing keys, one per maintainer.
+//! The first publish adds the key that signed it. Further keys are added,
+//! and keys removed, with a [`KeyChangeToken`] signed by a key already in
+//! the set and confirmed by the namespace owner by email. The registry keeps
+//! every confirmed change as a [`KeyChangeRecord`], so clients that pinned
+//! one key can check that another was added by its holder rather than by the
+//! registry.
+
+use serde::{Deserialize, Serialize};
+
+use crate::canonical::{signing_bytes, CanonicalError};
+
+/// What a [`KeyChangeToken`] does to the key set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyChangeAction {
+    /// Allow a new key to sign for the namespace.
+    Add,
+    /// Stop a key from signing for the namespace.
+    Remove,
+}
+
+/// A change to a namespace's key set, signed by a key in the set.
+///
+/// The signing key signs the SHA-256 of [`KeyChangeToken::signing_bytes`],
+/// using the scheme for its algorithm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyChangeToken {
+    /// Authenticated namespace slug.
+    pub namespace: String,
+    /// Whether the key is added or removed.
+    pub action: KeyChangeAction,
+    /// SHA-256 SPKI fingerprint (hex) of the key added or removed.
+    pub fingerprint: String,
+    /// PEM certificate of the key added; `None` for a removal.
+    pub cert_pem: Option<String>,
+    /// Human-readable label of the key added, e.g. the maintainer's name.
+    pub label: Option<String>,
+    /// RFC 3339 timestamp after which the added key may no longer sign, if
+    /// any.
+    pub key_expires_at: Option<String>,
+    /// SHA-256 SPKI fingerprint (hex) of the key signing this change.
+    pub signer_fingerprint: String,
+    /// 32-byte random nonce (hex) — prevents replay.
+    pub nonce: String,
+    /// RFC 3339 timestamp when this token was created.
+    pub issued_at: String,
+    /// RFC 3339 timestamp when this token expires (5 min after `issued_at`).
+    pub expires_at: String,
+}
+
+impl KeyChangeToken {
+    /// Signing domain of key-set changes; see [`crate::canonical`].
+    pub const SIGNING_DOMAIN: &'static str = "key-set-change";
+
+    /// The bytes the signing key signs: the token's RFC 8785 canonical JSON
+    /// in the `key-set-change` signing envelope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails (in practice this never
+    /// happens for these plain fields).
+    pub fn signing_bytes(&self) -> Result<Vec<u8>, CanonicalError> {
+        signing_bytes(Self::SIGNING_DOMAIN, self)
+    }
+}
+
+/// A confirmed key-set change, as served by
+/// `GET /v1/namespaces/:ns/key-changes`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyChangeRecord {
+    /// The signed change.
+    pub token: KeyChangeToken,
+    /// Hex signature over the token by the key `token.signer_fingerprint`.
+    pub signature: String,
+    /// PEM certificate of the signing key.
+    pub signer_cert_pem: String,
+    /// RFC 3339 timestamp when the namespace owner confirmed the change.
+    pub confirmed_at: String,
+}
+
+/// An active key of a namespace, as served by `GET /v1/namespaces/:ns/keys`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceKey {
+    /// SHA-256 SPKI fingerprint (hex) of the key.
+    pub fingerprint: String,
+    /// Human-readable label.
+    pub label: String,
+    /// Fingerprint of the key that added this one; `None` for the key pinned
+    /// by the namespace's first publish.
+    pub added_by: Option<String>,
+    /// RFC 3339 timestamp when the key was added.
+    pub added_at: String,
+    /// RFC 3339 timestamp after which the key may no longer sign, if any.
+    pub expires_at: Option<String>,
+    /// PEM certificate of the key, if the registry has it.
+    pub cert_pem: Option<String>,
+}
@@ -0,0 +1,190 @@
+//! Which paths a `.skill` package may contain.
+//!
+//! `skreg pack` and the registry's structure stage both consult this policy,
+//! so the packer never includes a file the registry would reject for its
+//! location or extension.
+
+use std::path::{Component, Path};
+
+use thiserror::Error;
+
+use crate::limits;
+
+/// Files that MUST be present at the package root.
+pub const REQUIRED_FILES: &[&str] = &["SKILL.md"];
+
+/// Files permitted at the package root by exact name.
+pub const ROOT_FILES: &[&str] = &["SKILL.md", "manifest.json"];
+
+/// Prefixes of further files permitted at the package root (`LICENSE`,
+/// `LICENSE.md`, `LICENSE-MIT`, ...).
+pub const ROOT_PREFIXES: &[&str] = &["LICENSE"];
+
+/// Top-level directories and the file extensions each may hold. Nested
+/// directories are not allowed.
+pub const DIRECTORIES: &[(&str, &[&str])] = &[
+    ("scripts", &["py", "sh", "bash", "js", "ts", "rb"]),
+    ("references", &["md"]),
+    (
+        "assets",
+        &[
+            "md", "txt", "json", "yaml", "yml", "csv", "png", "jpg", "svg", "pdf",
+        ],
+    ),
+];
+
+/// Why a path is not allowed in a package.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LayoutViolation {
+    /// A root file that is neither listed nor prefixed by an allowed name.
+    #[error("'{0}' is not allowed at the package root")]
+    RootFile(String),
+    /// A top-level directory that is not in [`DIRECTORIES`].
+    #[error("directory '{0}' is not allowed")]
+    Directory(String),
+    /// A directory nested inside an allowed directory.
+    #[error("subdirectories are not allowed in '{0}'")]
+    Subdirectory(String),
+    /// A file whose extension its directory does not accept.
+    #[error("'{path}' has an extension not allowed in '{dir}/'")]
+    Extension {
+        /// The file's package path.
+        path: String,
+        /// Its top-level directory.
+        dir: String,
+    },
+    /// A path that is absolute, contains `..`, or is not valid UTF-8.
+    #[error("'{0}' is not a plain relative path")]
+    Path(String),
+}
+
+/// Split `rel` into its normal components, rejecting anything else.
+fn components(rel: &Path) -> Result<Vec<&str>, LayoutViolation> {
+    let mut parts = Vec::new();
+    for component in rel.components() {
+        match component {
+            Component::Normal(part) => parts.push(
+                part.to_str()
+                    .ok_or_else(|| LayoutViolation::Path(rel.display().to_string()))?,
+            ),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(LayoutViolation::Path(rel.display().to_string()));
+            }
+        }
+    }
+    Ok(parts)
+}
+
+/// Check that a directory at package path `rel` may hold packaged files.
+///
+/// # Errors
+///
+/// Returns [`LayoutViolation::Directory`] for an unknown top-level
+/// directory and [`LayoutViolation::Subdirectory`] for any nested one.
+pub fn check_dir(rel: &Path) -> Result<(), LayoutViolation> {
+    match components(rel)?.as_slice() {
+        [] => Ok(()),
+        [dir] if DIRECTORIES.iter().any(|(d, _)| d == dir) => Ok(()),
+        [dir] => Err(LayoutViolation::Directory((*dir).to_owned())),
+        [dir, ..] => Err(LayoutViolation::Subdirectory((*dir).to_owned())),
+    }
+}
+
+/// Check that a regular file may sit at package path `rel`.
+///
+/// # Errors
+///
+/// Returns the [`LayoutViolation`] describing why the path is not allowed.
+pub fn check_file(rel: &Path) -> Result<(), LayoutViolation> {
+    match components(rel)?.as_slice() {
+        [name] => {
+            if ROOT_FILES.contains(name) || ROOT_PREFIXES.iter().any(|p| name.starts_with(p)) {
+                Ok(())
+            } else {
+                Err(LayoutViolation::RootFile((*name).to_owned()))
+            }
+        }
+        [dir, _] => {
+            let (_, extensions) = DIRECTORIES
+                .iter()
+                .find(|(d, _)| d == dir)
+                .ok_or_else(|| LayoutViolation::Directory((*dir).to_owned()))?;
+            let ext = rel.extension().and_then(|e| e.to_str()).unwrap_or("");
+            if extensions.contains(&ext) {
+                Ok(())
+            } else {
+                Err(LayoutViolation::Extension {
+                    path: rel.display().to_string(),
+                    dir: (*dir).to_owned(),
+                })
+            }
+        }
+        [dir, ..] => Err(LayoutViolation::Subdirectory((*dir).to_owned())),
+        [] => Err(LayoutViolation::Path(rel.display().to_string())),
+    }
+}
+
+/// Size limit for the file at package path `rel`; scripts have a tighter
+/// cap than everything else.
+#[must_use]
+pub fn file_size_limit(rel: &Path) -> u64 {
+    if rel.starts_with("scripts") {
+        limits::LIMIT_SCRIPT_FILE_SIZE
+    } else {
+        limits::LIMIT_FILE_SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_files_and_license_variants_are_allowed() {
+        for name in ["SKILL.md", "manifest.json", "LICENSE", "LICENSE-MIT"] {
+            assert_eq!(check_file(Path::new(name)), Ok(()), "{name}");
+        }
+        assert_eq!(
+            check_file(Path::new("notes.txt")),
+            Err(LayoutViolation::RootFile("notes.txt".to_owned()))
+        );
+    }
+
+    #[test]
+    fn directory_files_are_checked_by_extension() {
+        assert_eq!(check_file(Path::new("scripts/run.sh")), Ok(()));
+        assert!(matches!(
+            check_file(Path::new("references/data.csv")),
+            Err(LayoutViolation::Extension { .. })
+        ));
+        assert_eq!(
+            check_file(Path::new("docs/guide.md")),
+            Err(LayoutViolation::Directory("docs".to_owned()))
+        );
+        assert_eq!(
+            check_file(Path::new("assets/img/a.png")),
+            Err(LayoutViolation::Subdirectory("assets".to_owned()))
+        );
+    }
+
+    #[test]
+    fn directories_and_unsafe_paths() {
+        assert_eq!(check_dir(Path::new("assets")), Ok(()));
+        assert!(check_dir(Path::new("node_modules")).is_err());
+        assert!(check_dir(Path::new("assets/img")).is_err());
+        assert!(check_file(Path::new("../SKILL.md")).is_err());
+    }
+
+    #[test]
+    fn scripts_have_a_tighter_size_limit() {
+        assert_eq!(
+            file_size_limit(Path::new("scripts/a.py")),
+            limits::LIMIT_SCRIPT_FILE_SIZE
+        );
+        assert_eq!(
+            file_size_limit(Path::new("assets/a.png")),
+            limits::LIMIT_FILE_SIZE
+        );
+    }
+}
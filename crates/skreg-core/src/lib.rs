@@ -2,11 +2,22 @@
 #![deny(warnings, clippy::all, clippy::pedantic)]
 #![warn(missing_docs)]
 
+pub mod advisory;
+pub mod canonical;
+pub mod certs;
 pub mod config;
+pub mod frontmatter;
 pub mod installed;
+pub mod key_log;
+pub mod keyset;
+pub mod layout;
 pub mod limits;
 pub mod manifest;
+pub mod names;
 pub mod package_ref;
+pub mod rotation;
+pub mod timestamp;
+pub mod transparency;
 pub mod types;
 pub mod verification;
 pub use verification::VerificationKind;
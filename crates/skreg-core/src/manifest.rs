@@ -1,10 +1,34 @@
 //! Package manifest type representing `manifest.json` inside a `.skill` tarball.
+//!
+//! Current packages list every file with its size and SHA-256 in
+//! [`Manifest::files`]. The publisher signs [`Manifest::signing_digest`],
+//! derived from [`Manifest::signed_payload`], a canonical rendering of that
+//! list plus the package identity, so a signature can be checked against the
+//! files actually extracted. Legacy packages have no file list; their
+//! signature covers the digest of an unsigned tarball recorded in
+//! [`Manifest::sha256`].
+
+use std::fmt::Write as _;
 
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
 use crate::types::{Namespace, PackageName, Sha256Digest};
 
+/// First line of [`Manifest::signed_payload`], versioning its format.
+const PAYLOAD_HEADER: &str = "skreg-files/1";
+
+/// One file in a package, as listed in [`Manifest::files`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    /// Path relative to the package root, `/`-separated.
+    pub path: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// SHA-256 of the file contents.
+    pub sha256: Sha256Digest,
+}
+
 /// The contents of a `manifest.json` file inside a `.skill` package.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Manifest {
@@ -18,13 +42,65 @@ pub struct Manifest {
     pub description: String,
     /// Optional category tag.
     pub category: Option<String>,
-    /// SHA-256 hex digest of the tarball this manifest describes.
+    /// SHA-256 of the signed payload: [`Manifest::payload_digest`] when
+    /// [`files`](Self::files) is present, otherwise (legacy packages) the
+    /// digest of the unsigned tarball the publisher signed.
     pub sha256: Sha256Digest,
     /// PEM-encoded certificate chain used to verify the package signature.
     /// Empty for registry-signed packages (cert chain is implicit).
     pub cert_chain_pem: Vec<String>,
-    /// RSA-PSS signature (hex) over the tarball SHA-256 digest, made by the publisher.
+    /// Signature (hex) over [`signing_digest`](Self::signing_digest) (legacy
+    /// packages: over `sha256`), made by the publisher with an RSA-PSS,
+    /// Ed25519 or ECDSA P-256 key.
     /// Present for all newly published packages; absent on legacy registry-signed packages.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub publisher_sig_hex: Option<String>,
+    /// Every file in the package except `manifest.json`, sorted by path.
+    /// Empty on legacy packages.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileEntry>,
+}
+
+impl Manifest {
+    /// Canonical bytes covered by the publisher signature.
+    ///
+    /// A header line, then `namespace/name@version`, then one
+    /// `sha256 size path` line per file in path order, each `\n`-terminated.
+    /// Returns `None` for legacy manifests without a file list, or if a path
+    /// contains a newline and so cannot be rendered unambiguously.
+    #[must_use]
+    pub fn signed_payload(&self) -> Option<String> {
+        if self.files.is_empty() || self.files.iter().any(|f| f.path.contains('\n')) {
+            return None;
+        }
+        let mut files: Vec<&FileEntry> = self.files.iter().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut payload = format!(
+            "{PAYLOAD_HEADER}\n{}/{}@{}\n",
+            self.namespace, self.name, self.version
+        );
+        for f in files {
+            let _ = writeln!(payload, "{} {} {}", f.sha256, f.size, f.path);
+        }
+        Some(payload)
+    }
+
+    /// SHA-256 of [`signed_payload`](Self::signed_payload).
+    #[must_use]
+    pub fn payload_digest(&self) -> Option<Sha256Digest> {
+        self.signed_payload()
+            .map(|payload| Sha256Digest::of(payload.as_bytes()))
+    }
+
+    /// The digest the publisher signs: the SHA-256 of the payload header
+    /// line followed by the hex [`payload_digest`](Self::payload_digest).
+    ///
+    /// The prefix keeps a file-list signature from ever verifying as a legacy
+    /// signature over [`sha256`](Self::sha256), which holds the unprefixed
+    /// payload digest.
+    #[must_use]
+    pub fn signing_digest(&self) -> Option<Sha256Digest> {
+        self.payload_digest()
+            .map(|digest| Sha256Digest::of(format!("{PAYLOAD_HEADER}\n{digest}").as_bytes()))
+    }
 }
@@ -0,0 +1,176 @@
+//! Name similarity checks used to detect typosquatting of package names and
+//! namespace slugs.
+//!
+//! [`skeleton`] and [`segment_key`] are mirrored by the `name_skeleton` and
+//! `name_segment_key` SQL functions (API migration `008_typosquatting.sql`),
+//! which index candidate lookups. The two implementations must stay in sync.
+
+use std::fmt;
+
+/// Single characters folded to the glyph they are commonly mistaken for.
+const CONFUSABLE_CHARS: &[(char, char)] =
+    &[('0', 'o'), ('1', 'l'), ('3', 'e'), ('5', 's'), ('i', 'l')];
+
+/// Character sequences that render like a single glyph, applied in order.
+const CONFUSABLE_SEQUENCES: &[(&str, &str)] = &[("rn", "m"), ("vv", "w"), ("cl", "d")];
+
+/// Why two names were judged too similar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameMatch {
+    /// The names render alike (e.g. `rnodel` / `model`, `1int` / `lint`).
+    Confusable,
+    /// The same hyphen-separated segments in a different order.
+    SwappedSegments,
+    /// Within a small Levenshtein edit distance.
+    EditDistance(usize),
+}
+
+impl fmt::Display for NameMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Confusable => f.write_str("visually confusable"),
+            Self::SwappedSegments => f.write_str("same segments reordered"),
+            Self::EditDistance(d) => write!(f, "Levenshtein distance {d}"),
+        }
+    }
+}
+
+/// Compute the Levenshtein edit distance between two strings.
+#[must_use]
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0usize; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            cur[j + 1] = if ca == cb {
+                prev[j]
+            } else {
+                1 + prev[j].min(prev[j + 1]).min(cur[j])
+            };
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// Reduce `name` to a form in which visually confusable names compare equal.
+///
+/// Lowercases, folds confusable characters, drops `-`, `_` and `.`, then
+/// collapses multi-character lookalikes such as `rn` → `m`.
+#[must_use]
+pub fn skeleton(name: &str) -> String {
+    let folded: String = name
+        .to_lowercase()
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_' | '.'))
+        .map(|c| {
+            CONFUSABLE_CHARS
+                .iter()
+                .find(|(from, _)| *from == c)
+                .map_or(c, |(_, to)| *to)
+        })
+        .collect();
+    CONFUSABLE_SEQUENCES
+        .iter()
+        .fold(folded, |s, (from, to)| s.replace(from, to))
+}
+
+/// Hyphen-separated segments of `name` sorted bytewise and re-joined, so that
+/// `fast-lint` and `lint-fast` share a key.
+#[must_use]
+pub fn segment_key(name: &str) -> String {
+    let mut segments: Vec<&str> = name.split('-').collect();
+    segments.sort_unstable();
+    segments.join("-")
+}
+
+/// Maximum edit distance treated as a typo of a name of `len` characters.
+///
+/// Very short names are one edit apart from many legitimate words, so they
+/// only match at distance 1.
+#[must_use]
+pub fn max_edit_distance(len: usize) -> usize {
+    if len <= 4 {
+        1
+    } else {
+        2
+    }
+}
+
+/// Decide whether `candidate` is too similar to `existing`.
+///
+/// Identical names never match; uniqueness is enforced elsewhere. With
+/// `strict` unset only [`NameMatch::Confusable`] and
+/// [`NameMatch::SwappedSegments`] are reported, which is the policy applied to
+/// names owned by unverified namespaces.
+#[must_use]
+pub fn classify(candidate: &str, existing: &str, strict: bool) -> Option<NameMatch> {
+    if candidate == existing {
+        return None;
+    }
+    if skeleton(candidate) == skeleton(existing) {
+        return Some(NameMatch::Confusable);
+    }
+    if candidate.contains('-') && segment_key(candidate) == segment_key(existing) {
+        return Some(NameMatch::SwappedSegments);
+    }
+    if strict {
+        let d = levenshtein(candidate, existing);
+        if d <= max_edit_distance(candidate.chars().count().min(existing.chars().count())) {
+            return Some(NameMatch::EditDistance(d));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("abc", "abc"), 0);
+        assert_eq!(levenshtein("abc", "abx"), 1);
+        assert_eq!(levenshtein("abc", "xyz"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn skeleton_folds_homoglyphs() {
+        assert_eq!(skeleton("rnodel"), skeleton("model"));
+        assert_eq!(skeleton("1int"), skeleton("lint"));
+        assert_eq!(skeleton("c1oud"), skeleton("doud"));
+        assert_eq!(skeleton("my-tool"), skeleton("mytool"));
+        assert_ne!(skeleton("lint"), skeleton("lent"));
+    }
+
+    #[test]
+    fn classify_detects_swapped_segments() {
+        assert_eq!(
+            classify("lint-fast", "fast-lint", false),
+            Some(NameMatch::SwappedSegments)
+        );
+    }
+
+    #[test]
+    fn classify_only_reports_edit_distance_when_strict() {
+        assert_eq!(
+            classify("reakt", "react", true),
+            Some(NameMatch::EditDistance(1))
+        );
+        assert_eq!(classify("reakt", "react", false), None);
+        assert_eq!(classify("lint", "lint", true), None);
+    }
+
+    #[test]
+    fn short_names_need_a_closer_match() {
+        assert_eq!(
+            classify("lint", "list", true),
+            Some(NameMatch::EditDistance(1))
+        );
+        assert_eq!(classify("lint", "hunt", true), None);
+    }
+}
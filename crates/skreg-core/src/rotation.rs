@@ -0,0 +1,85 @@
+//! Publisher key rotation tokens and the records the registry keeps of them.
+//!
+//! A rotation is requested with a [`RotationToken`] signed by both the old
+//! and the new key. Once the namespace owner confirms it by email, the
+//! registry keeps the token and both signatures as a [`RotationRecord`], so
+//! clients that pinned the old key can check that the new one was endorsed
+//! by it rather than substituted by the registry. Until then it is a
+//! [`PendingRotation`], which the owner may cancel.
+
+use serde::{Deserialize, Serialize};
+
+use crate::canonical::{signing_bytes, CanonicalError};
+
+/// Rotation token signed by both keys of a key rotation.
+///
+/// Both the old and new keys sign the SHA-256 of
+/// [`RotationToken::signing_bytes`], using the scheme for each key's
+/// algorithm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationToken {
+    /// Authenticated namespace slug.
+    pub namespace: String,
+    /// SHA-256 SPKI fingerprint (hex) of the current key being replaced.
+    pub old_key_fingerprint: String,
+    /// SHA-256 SPKI fingerprint (hex) of the new key.
+    pub new_key_fingerprint: String,
+    /// PEM-encoded cert chain for the new key (leaf first).
+    pub new_cert_chain_pem: Vec<String>,
+    /// 32-byte random nonce (hex) — prevents replay.
+    pub nonce: String,
+    /// RFC 3339 timestamp when this token was created.
+    pub issued_at: String,
+    /// RFC 3339 timestamp when this token expires (5 min after `issued_at`).
+    pub expires_at: String,
+}
+
+impl RotationToken {
+    /// Signing domain of rotation tokens; see [`crate::canonical`].
+    pub const SIGNING_DOMAIN: &'static str = "rotation-token";
+
+    /// The bytes both keys sign: the token's RFC 8785 canonical JSON in the
+    /// `rotation-token` signing envelope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails (in practice this never
+    /// happens for a plain-struct type with only `String`/`Vec<String>`
+    /// fields).
+    pub fn signing_bytes(&self) -> Result<Vec<u8>, CanonicalError> {
+        signing_bytes(Self::SIGNING_DOMAIN, self)
+    }
+}
+
+/// A confirmed key rotation, as served by
+/// `GET /v1/namespaces/:ns/key-history`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationRecord {
+    /// The token both keys signed.
+    pub token: RotationToken,
+    /// Hex signature over the token by the old key.
+    pub old_sig: String,
+    /// Hex signature over the token by the new key.
+    pub new_sig: String,
+    /// PEM certificate of the old key, whose fingerprint is
+    /// `token.old_key_fingerprint`.
+    pub old_cert_pem: String,
+    /// RFC 3339 timestamp when the namespace owner confirmed the rotation.
+    pub confirmed_at: String,
+}
+
+/// A rotation awaiting email confirmation, as served by
+/// `GET /v1/namespaces/:ns/rotations`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingRotation {
+    /// Registry id of the pending rotation, used to cancel it.
+    pub id: String,
+    /// SHA-256 SPKI fingerprint (hex) of the key being replaced.
+    pub old_key_fingerprint: String,
+    /// SHA-256 SPKI fingerprint (hex) of the new key.
+    pub new_key_fingerprint: String,
+    /// RFC 3339 timestamp when the rotation was submitted.
+    pub created_at: String,
+    /// RFC 3339 timestamp after which the confirmation link no longer works.
+    pub expires_at: String,
+}
@@ -0,0 +1,44 @@
+//! Registry-signed timestamps proving when a publisher signature existed.
+//!
+//! When a version passes vetting the registry signs a [`Timestamp`] over the
+//! digest of the publisher's signature, much like an RFC 3161 time-stamp
+//! token. Installers check the publisher's certificate as of `signed_at`
+//! rather than the current time, so a package stays installable after the
+//! certificate that signed it expires.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::Sha256Digest;
+
+/// The signed body of a [`SignedTimestamp`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timestamp {
+    /// SHA-256 of the publisher signature bytes being timestamped.
+    pub imprint: Sha256Digest,
+    /// When the registry observed the signature.
+    pub signed_at: DateTime<Utc>,
+}
+
+impl Timestamp {
+    /// The imprint of a raw publisher signature.
+    #[must_use]
+    pub fn imprint_of(signature: &[u8]) -> Sha256Digest {
+        Sha256Digest::of(signature)
+    }
+}
+
+/// Wire envelope for a timestamp.
+///
+/// `timestamp` is the exact JSON text of a [`Timestamp`]; `signature_hex` is
+/// an RSA-PSS/SHA-256 signature over the SHA-256 of those bytes, made with
+/// the key of `signer_cert_pem`, which must chain to a trusted root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTimestamp {
+    /// Serialized [`Timestamp`].
+    pub timestamp: String,
+    /// Hex-encoded signature over `sha256(timestamp)`.
+    pub signature_hex: String,
+    /// PEM certificate whose key produced `signature_hex`.
+    pub signer_cert_pem: String,
+}
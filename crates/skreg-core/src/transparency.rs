@@ -0,0 +1,155 @@
+//! Entries, tree heads and proofs of the registry's transparency log.
+//!
+//! The registry appends every published version and every key pin,
+//! addition, removal, rotation and revocation to an append-only Merkle tree
+//! (RFC 6962). It serves signed tree heads, inclusion proofs and consistency
+//! proofs under `/v1/log/*`, so clients can check that what they install was
+//! logged and that the log is never rewritten.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::Manifest;
+use crate::types::Sha256Digest;
+
+/// One event recorded in the log.
+///
+/// The leaf data for an entry is its `serde_json` serialization, with fields
+/// in declaration order; clients rebuild the entry for a package they
+/// install and hash it themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LogEntry {
+    /// A version passed vetting and was published.
+    Publish {
+        /// Namespace slug.
+        namespace: String,
+        /// Package name.
+        name: String,
+        /// Version string.
+        version: String,
+        /// The digest the publisher signed (`sha256` in `manifest.json`).
+        sha256: Sha256Digest,
+        /// SHA-256 SPKI fingerprint (hex) of the signing key.
+        signer_fingerprint: String,
+    },
+    /// A namespace's first publish pinned its key.
+    KeyPin {
+        /// Namespace slug.
+        namespace: String,
+        /// SHA-256 SPKI fingerprint (hex) of the pinned key.
+        fingerprint: String,
+    },
+    /// A confirmed key-set change added a key to a namespace.
+    KeyAdded {
+        /// Namespace slug.
+        namespace: String,
+        /// Fingerprint of the key added.
+        fingerprint: String,
+        /// Fingerprint of the key that signed the change.
+        added_by: String,
+    },
+    /// A confirmed key-set change removed a key from a namespace.
+    KeyRemoved {
+        /// Namespace slug.
+        namespace: String,
+        /// Fingerprint of the key removed.
+        fingerprint: String,
+    },
+    /// A confirmed rotation replaced one of a namespace's keys.
+    KeyRotation {
+        /// Namespace slug.
+        namespace: String,
+        /// Fingerprint of the key replaced.
+        old_fingerprint: String,
+        /// Fingerprint of the new key.
+        new_fingerprint: String,
+    },
+    /// A publisher key or CA-issued certificate was revoked.
+    KeyRevocation {
+        /// Namespace slug, if the revoked key belongs to a known namespace.
+        namespace: Option<String>,
+        /// Fingerprint of a revoked self-signed key.
+        fingerprint: Option<String>,
+        /// Serial number of a revoked CA-issued certificate.
+        serial: Option<i64>,
+    },
+}
+
+impl LogEntry {
+    /// The entry recording the publish of `manifest`, signed by the key
+    /// with SPKI fingerprint `signer_fingerprint`.
+    #[must_use]
+    pub fn publish(manifest: &Manifest, signer_fingerprint: String) -> Self {
+        Self::Publish {
+            namespace: manifest.namespace.as_str().to_owned(),
+            name: manifest.name.as_str().to_owned(),
+            version: manifest.version.to_string(),
+            sha256: manifest.sha256.clone(),
+            signer_fingerprint,
+        }
+    }
+
+    /// The bytes hashed into this entry's leaf.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails (in practice this never
+    /// happens for these plain fields).
+    pub fn leaf_data(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+}
+
+/// The signed body of a [`SignedTreeHead`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeHead {
+    /// Number of entries in the tree.
+    pub tree_size: u64,
+    /// Merkle root hash of the tree.
+    pub root_hash: Sha256Digest,
+    /// When the registry signed this head.
+    pub timestamp: DateTime<Utc>,
+    /// When the first entry was logged, if the log has any. Versions signed
+    /// since then must have a publish entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_start: Option<DateTime<Utc>>,
+}
+
+/// Wire envelope for a tree head, served by `GET /v1/log/tree-head`.
+///
+/// `tree_head` is the exact JSON text of a [`TreeHead`]; `signature_hex` is
+/// an RSA-PSS/SHA-256 signature over the SHA-256 of those bytes, made with
+/// the key of `signer_cert_pem`, which must chain to a trusted root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTreeHead {
+    /// Serialized [`TreeHead`].
+    pub tree_head: String,
+    /// Hex-encoded signature over `sha256(tree_head)`.
+    pub signature_hex: String,
+    /// PEM certificate whose key produced `signature_hex`.
+    pub signer_cert_pem: String,
+}
+
+/// Audit path for one entry, served by `GET /v1/log/proof/inclusion`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// Index of the entry.
+    pub leaf_index: u64,
+    /// Size of the tree the path leads to the root of.
+    pub tree_size: u64,
+    /// Sibling hashes from the leaf up to the root.
+    pub audit_path: Vec<Sha256Digest>,
+}
+
+/// Proof that one tree is a prefix of another, served by
+/// `GET /v1/log/proof/consistency`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    /// Size of the older tree.
+    pub first: u64,
+    /// Size of the newer tree.
+    pub second: u64,
+    /// Node hashes proving the older tree is a prefix of the newer.
+    pub proof: Vec<Sha256Digest>,
+}
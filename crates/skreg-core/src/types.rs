@@ -135,6 +135,24 @@ impl Sha256Digest {
         Ok(Self(hex.to_ascii_lowercase()))
     }
 
+    /// Hash `data` with SHA-256.
+    #[must_use]
+    pub fn of(data: &[u8]) -> Self {
+        use sha2::Digest as _;
+        Self::from_bytes(&sha2::Sha256::digest(data).into())
+    }
+
+    /// Wrap a raw 32-byte SHA-256 hash.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        use std::fmt::Write as _;
+        let hex = bytes.iter().fold(String::with_capacity(64), |mut s, b| {
+            let _ = write!(s, "{b:02x}");
+            s
+        });
+        Self(hex)
+    }
+
     /// Return the hex string representation.
     #[must_use]
     pub fn as_hex(&self) -> &str {
@@ -0,0 +1,133 @@
+use serde_json::{json, Value};
+use skreg_core::canonical::{signing_bytes, to_canonical_json, CanonicalError};
+use skreg_core::rotation::RotationToken;
+
+fn canonical(raw: &str) -> String {
+    to_canonical_json(&serde_json::from_str::<Value>(raw).unwrap()).unwrap()
+}
+
+#[test]
+fn rfc_8785_example_object() {
+    // RFC 8785 §3.2.2.
+    let raw = r#"{
+        "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+        "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+        "literals": [null, true, false]
+    }"#;
+    assert_eq!(
+        canonical(raw),
+        r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+    );
+}
+
+#[test]
+fn rfc_8785_keys_sort_by_utf16_code_units() {
+    // RFC 8785 §3.2.3: U+1F600 sorts before U+FB33 as a surrogate pair.
+    let raw = r#"{
+        "\u20ac": "Euro Sign",
+        "\r": "Carriage Return",
+        "\ufb33": "Hebrew Letter Dalet With Dagesh",
+        "1": "One",
+        "\ud83d\ude00": "Emoji: Grinning Face",
+        "\u0080": "Control",
+        "\u00f6": "Latin Small Letter O With Diaeresis"
+    }"#;
+    assert_eq!(
+        canonical(raw),
+        "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\
+         \"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\
+         \"\u{1f600}\":\"Emoji: Grinning Face\",\
+         \"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+    );
+}
+
+#[test]
+fn rfc_8785_number_vectors() {
+    // RFC 8785 Appendix B, by IEEE 754 bit pattern.
+    let vectors: [(u64, &str); 23] = [
+        (0x0000_0000_0000_0000, "0"),
+        (0x8000_0000_0000_0000, "0"),
+        (0x0000_0000_0000_0001, "5e-324"),
+        (0x8000_0000_0000_0001, "-5e-324"),
+        (0x7fef_ffff_ffff_ffff, "1.7976931348623157e+308"),
+        (0xffef_ffff_ffff_ffff, "-1.7976931348623157e+308"),
+        (0x4340_0000_0000_0000, "9007199254740992"),
+        (0xc340_0000_0000_0000, "-9007199254740992"),
+        (0x4430_0000_0000_0000, "295147905179352830000"),
+        (0x44b5_2d02_c7e1_4af5, "9.999999999999997e+22"),
+        (0x44b5_2d02_c7e1_4af6, "1e+23"),
+        (0x44b5_2d02_c7e1_4af7, "1.0000000000000001e+23"),
+        (0x444b_1ae4_d6e2_ef4e, "999999999999999700000"),
+        (0x444b_1ae4_d6e2_ef4f, "999999999999999900000"),
+        (0x444b_1ae4_d6e2_ef50, "1e+21"),
+        (0x3eb0_c6f7_a0b5_ed8c, "9.999999999999997e-7"),
+        (0x3eb0_c6f7_a0b5_ed8d, "0.000001"),
+        (0x41b3_de43_5555_5553, "333333333.3333332"),
+        (0x41b3_de43_5555_5554, "333333333.33333325"),
+        (0x41b3_de43_5555_5555, "333333333.3333333"),
+        (0x41b3_de43_5555_5556, "333333333.3333334"),
+        (0x41b3_de43_5555_5557, "333333333.33333343"),
+        (0xbecb_f647_612f_3696, "-0.0000033333333333333333"),
+    ];
+    for (bits, expected) in vectors {
+        let value = json!(f64::from_bits(bits));
+        assert_eq!(to_canonical_json(&value).unwrap(), expected, "{bits:#018x}");
+    }
+}
+
+#[test]
+fn integers_beyond_double_precision_are_rejected() {
+    assert_eq!(
+        to_canonical_json(&json!(9_007_199_254_740_991_u64)).unwrap(),
+        "9007199254740991"
+    );
+    assert!(matches!(
+        to_canonical_json(&json!(9_007_199_254_740_993_u64)),
+        Err(CanonicalError::Number(_))
+    ));
+}
+
+#[test]
+fn envelope_separates_domains() {
+    let value = json!({"b": 1, "a": "x"});
+    let rotation = signing_bytes("rotation-token", &value).unwrap();
+    assert_eq!(
+        rotation,
+        b"skreg-signed-json-v1\nrotation-token\n{\"a\":\"x\",\"b\":1}"
+    );
+    assert_ne!(rotation, signing_bytes("advisory-feed", &value).unwrap());
+    assert!(signing_bytes("two\nlines", &value).is_err());
+    assert!(signing_bytes("", &value).is_err());
+}
+
+#[test]
+fn rotation_token_signing_bytes_vector() {
+    let token = RotationToken {
+        namespace: "acme".to_owned(),
+        old_key_fingerprint: "aa".repeat(32),
+        new_key_fingerprint: "bb".repeat(32),
+        new_cert_chain_pem: vec![
+            "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n".to_owned(),
+        ],
+        nonce: "00".repeat(32),
+        issued_at: "2026-01-01T00:00:00Z".to_owned(),
+        expires_at: "2026-01-01T00:05:00Z".to_owned(),
+    };
+    let expected = format!(
+        "skreg-signed-json-v1\nrotation-token\n{{\
+         \"expires_at\":\"2026-01-01T00:05:00Z\",\
+         \"issued_at\":\"2026-01-01T00:00:00Z\",\
+         \"namespace\":\"acme\",\
+         \"new_cert_chain_pem\":[\"-----BEGIN CERTIFICATE-----\\nMIIB\\n-----END CERTIFICATE-----\\n\"],\
+         \"new_key_fingerprint\":\"{}\",\
+         \"nonce\":\"{}\",\
+         \"old_key_fingerprint\":\"{}\"}}",
+        "bb".repeat(32),
+        "00".repeat(32),
+        "aa".repeat(32),
+    );
+    assert_eq!(
+        String::from_utf8(token.signing_bytes().unwrap()).unwrap(),
+        expected
+    );
+}
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use skreg_core::key_log::{KeyEvent, KeyLog, KeyLogError};
+
+fn at(raw: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(raw)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn sample() -> KeyLog {
+    KeyLog::build(
+        "acme",
+        [
+            (
+                at("2026-01-01T00:00:00Z"),
+                KeyEvent::Pinned {
+                    fingerprint: "aaa".into(),
+                },
+            ),
+            (
+                at("2026-02-01T00:00:00Z"),
+                KeyEvent::Added {
+                    fingerprint: "bbb".into(),
+                    added_by: "aaa".into(),
+                    expires_at: None,
+                },
+            ),
+            (
+                at("2026-03-01T00:00:00Z"),
+                KeyEvent::Rotated {
+                    old_fingerprint: "aaa".into(),
+                    new_fingerprint: "ccc".into(),
+                },
+            ),
+            (
+                at("2026-04-01T00:00:00Z"),
+                KeyEvent::Removed {
+                    fingerprint: "bbb".into(),
+                },
+            ),
+        ],
+    )
+    .unwrap()
+}
+
+#[test]
+fn built_log_verifies() {
+    let log = sample();
+    log.verify_links().unwrap();
+    let round_tripped: KeyLog =
+        serde_json::from_str(&serde_json::to_string(&log).unwrap()).unwrap();
+    round_tripped.verify_links().unwrap();
+    assert_eq!(round_tripped.head().unwrap(), log.head().unwrap());
+}
+
+#[test]
+fn rewritten_entry_breaks_the_chain() {
+    let mut log = sample();
+    log.entries[1].event = KeyEvent::Added {
+        fingerprint: "evil".into(),
+        added_by: "aaa".into(),
+        expires_at: None,
+    };
+    assert!(matches!(
+        log.verify_links(),
+        Err(KeyLogError::BrokenLink(2))
+    ));
+}
+
+#[test]
+fn dropped_entry_is_a_sequence_error() {
+    let mut log = sample();
+    log.entries.remove(1);
+    assert!(matches!(
+        log.verify_links(),
+        Err(KeyLogError::Sequence {
+            position: 1,
+            seq: 2
+        })
+    ));
+}
+
+#[test]
+fn longer_log_extends_its_prefix() {
+    let log = sample();
+    let mut prefix = log.clone();
+    prefix.entries.truncate(2);
+    let head = prefix.head().unwrap().unwrap();
+    assert!(log.extends(&head).unwrap());
+
+    let mut rewritten = sample();
+    rewritten.entries[1].at = at("2026-02-02T00:00:00Z");
+    let rewritten = KeyLog::build(
+        "acme",
+        rewritten.entries.into_iter().map(|e| (e.at, e.event)),
+    )
+    .unwrap();
+    assert!(!rewritten.extends(&head).unwrap());
+}
+
+#[test]
+fn keys_are_valid_between_their_events() {
+    let log = sample();
+    assert!(log.valid_at("aaa", at("2026-02-15T00:00:00Z")));
+    assert!(!log.valid_at("aaa", at("2026-03-15T00:00:00Z")));
+    assert!(!log.valid_at("ccc", at("2026-02-15T00:00:00Z")));
+    assert!(log.valid_at("ccc", at("2026-03-01T00:00:00Z")));
+    assert!(log.valid_at("bbb", at("2026-03-15T00:00:00Z")));
+    assert!(!log.valid_at("bbb", at("2026-04-15T00:00:00Z")));
+    assert!(!log.valid_at("aaa", at("2025-12-31T00:00:00Z")));
+}
+
+#[test]
+fn expired_and_revoked_keys_are_no_longer_valid() {
+    let log = KeyLog::build(
+        "acme",
+        [
+            (
+                at("2026-01-01T00:00:00Z"),
+                KeyEvent::Pinned {
+                    fingerprint: "aaa".into(),
+                },
+            ),
+            (
+                at("2026-02-01T00:00:00Z"),
+                KeyEvent::Added {
+                    fingerprint: "bbb".into(),
+                    added_by: "aaa".into(),
+                    expires_at: Some(at("2026-05-01T00:00:00Z")),
+                },
+            ),
+            (
+                at("2026-03-01T00:00:00Z"),
+                KeyEvent::Revoked {
+                    fingerprint: "aaa".into(),
+                },
+            ),
+            (
+                at("2026-04-01T00:00:00Z"),
+                KeyEvent::Rotated {
+                    old_fingerprint: "bbb".into(),
+                    new_fingerprint: "ccc".into(),
+                },
+            ),
+        ],
+    )
+    .unwrap();
+    assert!(log.valid_at("aaa", at("2026-02-15T00:00:00Z")));
+    assert!(!log.valid_at("aaa", at("2026-03-15T00:00:00Z")));
+    assert!(log.valid_at("bbb", at("2026-03-15T00:00:00Z")));
+    assert!(log.valid_at("ccc", at("2026-04-15T00:00:00Z")));
+    assert!(!log.valid_at("ccc", at("2026-05-01T00:00:00Z")));
+}
+
+#[test]
+fn entries_without_an_expiry_keep_their_hash() {
+    let added = KeyEvent::Added {
+        fingerprint: "bbb".into(),
+        added_by: "aaa".into(),
+        expires_at: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&added).unwrap(),
+        serde_json::json!({ "kind": "added", "fingerprint": "bbb", "added_by": "aaa" })
+    );
+}
@@ -16,6 +16,7 @@ fn manifest_serialises_and_roundtrips() {
         .unwrap(),
         cert_chain_pem: vec![],
         publisher_sig_hex: None,
+        files: vec![],
     };
 
     let json = serde_json::to_string(&manifest).unwrap();
@@ -55,3 +56,42 @@ fn manifest_without_publisher_sig_deserializes_as_none() {
     let m: Manifest = serde_json::from_str(json).unwrap();
     assert!(m.publisher_sig_hex.is_none());
 }
+
+#[test]
+fn signed_payload_is_sorted_and_absent_for_legacy_manifests() {
+    use skreg_core::manifest::FileEntry;
+
+    let entry = |path: &str| FileEntry {
+        path: path.to_owned(),
+        size: 1,
+        sha256: Sha256Digest::of(path.as_bytes()),
+    };
+    let mut m: Manifest = serde_json::from_str(
+        r#"{
+        "namespace": "acme",
+        "name": "my-skill",
+        "version": "1.0.0",
+        "description": "a test skill that is long enough",
+        "sha256": "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        "cert_chain_pem": []
+    }"#,
+    )
+    .unwrap();
+    assert!(m.payload_digest().is_none());
+
+    m.files = vec![entry("scripts/b.sh"), entry("SKILL.md")];
+    let payload = m.signed_payload().unwrap();
+    assert!(payload.starts_with("skreg-files/1\nacme/my-skill@1.0.0\n"));
+    assert!(payload.find("SKILL.md").unwrap() < payload.find("scripts/b.sh").unwrap());
+
+    let reordered = m.payload_digest();
+    m.files.reverse();
+    assert_eq!(m.payload_digest(), reordered);
+
+    let signing = m.signing_digest().unwrap();
+    assert_ne!(Some(&signing), reordered.as_ref());
+    assert_eq!(
+        signing,
+        Sha256Digest::of(format!("skreg-files/1\n{}", reordered.unwrap()).as_bytes())
+    );
+}
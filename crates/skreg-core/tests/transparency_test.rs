@@ -0,0 +1,69 @@
+use skreg_core::transparency::LogEntry;
+use skreg_core::types::Sha256Digest;
+
+/// Migration 017's `log_leaf_data()` rebuilds these exact bytes from the
+/// stored JSONB; a change here must change it too.
+#[test]
+fn leaf_data_is_compact_json_in_declaration_order() {
+    let publish = LogEntry::Publish {
+        namespace: "acme".to_owned(),
+        name: "tool".to_owned(),
+        version: "1.0.0".to_owned(),
+        sha256: Sha256Digest::of(b"pkg"),
+        signer_fingerprint: "aa".to_owned(),
+    };
+    assert_eq!(
+        String::from_utf8(publish.leaf_data().unwrap()).unwrap(),
+        format!(
+            r#"{{"kind":"publish","namespace":"acme","name":"tool","version":"1.0.0","sha256":"{}","signer_fingerprint":"aa"}}"#,
+            Sha256Digest::of(b"pkg").as_hex()
+        )
+    );
+
+    let cases = [
+        (
+            LogEntry::KeyPin {
+                namespace: "acme".to_owned(),
+                fingerprint: "aa".to_owned(),
+            },
+            r#"{"kind":"key_pin","namespace":"acme","fingerprint":"aa"}"#,
+        ),
+        (
+            LogEntry::KeyAdded {
+                namespace: "acme".to_owned(),
+                fingerprint: "bb".to_owned(),
+                added_by: "aa".to_owned(),
+            },
+            r#"{"kind":"key_added","namespace":"acme","fingerprint":"bb","added_by":"aa"}"#,
+        ),
+        (
+            LogEntry::KeyRemoved {
+                namespace: "acme".to_owned(),
+                fingerprint: "aa".to_owned(),
+            },
+            r#"{"kind":"key_removed","namespace":"acme","fingerprint":"aa"}"#,
+        ),
+        (
+            LogEntry::KeyRotation {
+                namespace: "acme".to_owned(),
+                old_fingerprint: "aa".to_owned(),
+                new_fingerprint: "bb".to_owned(),
+            },
+            r#"{"kind":"key_rotation","namespace":"acme","old_fingerprint":"aa","new_fingerprint":"bb"}"#,
+        ),
+        (
+            LogEntry::KeyRevocation {
+                namespace: None,
+                fingerprint: None,
+                serial: Some(42),
+            },
+            r#"{"kind":"key_revocation","namespace":null,"fingerprint":null,"serial":42}"#,
+        ),
+    ];
+    for (entry, expected) in cases {
+        assert_eq!(
+            String::from_utf8(entry.leaf_data().unwrap()).unwrap(),
+            expected
+        );
+    }
+}
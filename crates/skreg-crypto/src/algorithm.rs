@@ -0,0 +1,227 @@
+//! Publisher key algorithms and the signatures made with them.
+//!
+//! A key's algorithm is named by the OID in its certificate's
+//! `SubjectPublicKeyInfo`, so verification dispatches on that rather than on
+//! anything the signer claims. Package and token signatures cover a SHA-256
+//! digest:
+//!
+//! - RSA keys sign it with RSA-PSS/SHA-256, treating it as the prehash;
+//! - Ed25519 keys sign the 32 digest bytes as the message;
+//! - ECDSA P-256 keys sign the 32 digest bytes as the message with
+//!   ECDSA/SHA-256, encoding the signature as ASN.1 DER.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ring::signature::{UnparsedPublicKey, VerificationAlgorithm, ECDSA_P256_SHA256_ASN1, ED25519};
+use rsa::pkcs1v15::{Signature as Pkcs1v15Signature, VerifyingKey as Pkcs1v15VerifyingKey};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::pss::{Signature as PssSignature, VerifyingKey as PssVerifyingKey};
+use rsa::signature::hazmat::PrehashVerifier;
+use rsa::signature::Verifier;
+use rsa::RsaPublicKey;
+use sha2::Sha256;
+use skreg_core::types::Sha256Digest;
+use x509_cert::der::asn1::ObjectIdentifier;
+use x509_cert::der::Encode;
+use x509_cert::spki::SubjectPublicKeyInfoOwned;
+
+use crate::error::VerifyError;
+
+/// OID for `rsaEncryption` keys (1.2.840.113549.1.1.1).
+const OID_RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+
+/// OID for `id-ecPublicKey` keys (1.2.840.10045.2.1); the curve is a parameter.
+const OID_EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+
+/// OID for the NIST P-256 curve, `prime256v1` (1.2.840.10045.3.1.7).
+const OID_PRIME256V1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+
+/// OID for `id-Ed25519`, naming both the key and its signatures (1.3.101.112).
+const OID_ED25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+
+/// OID for RSASSA-PSS signatures (1.2.840.113549.1.1.10); SHA-256 is assumed.
+const OID_RSASSA_PSS: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.10");
+
+/// OID for `sha256WithRSAEncryption` (PKCS#1 v1.5) signatures.
+const OID_SHA256_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+
+/// OID for `ecdsa-with-SHA256` signatures (1.2.840.10045.4.3.2).
+const OID_ECDSA_WITH_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+
+/// Algorithm of a publisher signing key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyAlgorithm {
+    /// RSA with RSA-PSS/SHA-256 signatures.
+    #[default]
+    Rsa,
+    /// Ed25519.
+    Ed25519,
+    /// ECDSA over NIST P-256 with SHA-256.
+    EcdsaP256,
+}
+
+impl KeyAlgorithm {
+    /// Identify the algorithm of a certificate's public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::UnsupportedAlgorithm`] for any other key type,
+    /// including EC keys on curves other than P-256.
+    pub fn of_spki(spki: &SubjectPublicKeyInfoOwned) -> Result<Self, VerifyError> {
+        let oid = spki.algorithm.oid;
+        if oid == OID_RSA_ENCRYPTION {
+            Ok(Self::Rsa)
+        } else if oid == OID_ED25519 {
+            Ok(Self::Ed25519)
+        } else if oid == OID_EC_PUBLIC_KEY {
+            let curve = spki
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|p| p.decode_as::<ObjectIdentifier>().ok());
+            match curve {
+                Some(curve) if curve == OID_PRIME256V1 => Ok(Self::EcdsaP256),
+                Some(curve) => Err(VerifyError::UnsupportedAlgorithm(format!(
+                    "EC curve {curve}"
+                ))),
+                None => Err(VerifyError::UnsupportedAlgorithm(
+                    "EC key without a named curve".into(),
+                )),
+            }
+        } else {
+            Err(VerifyError::UnsupportedAlgorithm(oid.to_string()))
+        }
+    }
+
+    /// Name used on the command line and in messages.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Rsa => "rsa",
+            Self::Ed25519 => "ed25519",
+            Self::EcdsaP256 => "ecdsa-p256",
+        }
+    }
+}
+
+impl fmt::Display for KeyAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for KeyAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rsa" => Ok(Self::Rsa),
+            "ed25519" => Ok(Self::Ed25519),
+            "ecdsa-p256" | "p256" => Ok(Self::EcdsaP256),
+            other => Err(format!(
+                "unknown key algorithm {other:?} (expected rsa, ed25519 or ecdsa-p256)"
+            )),
+        }
+    }
+}
+
+/// Verify a package or token `signature` over `digest` made with the key in
+/// `spki`, using the scheme for the key's algorithm.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::UnsupportedAlgorithm`] if the key is of an
+/// unsupported type, [`VerifyError::Der`] if it is malformed, or
+/// [`VerifyError::SignatureMismatch`] if the signature does not match.
+pub fn verify_digest(
+    spki: &SubjectPublicKeyInfoOwned,
+    digest: &Sha256Digest,
+    signature: &[u8],
+) -> Result<(), VerifyError> {
+    let digest_bytes = hex::decode(digest.as_hex())
+        .map_err(|e| VerifyError::InvalidCertChain(format!("invalid digest hex: {e}")))?;
+    match KeyAlgorithm::of_spki(spki)? {
+        KeyAlgorithm::Rsa => {
+            let sig =
+                PssSignature::try_from(signature).map_err(|_| VerifyError::SignatureMismatch)?;
+            PssVerifyingKey::<Sha256>::new(rsa_public_key(spki)?)
+                .verify_prehash(&digest_bytes, &sig)
+                .map_err(|_| VerifyError::SignatureMismatch)
+        }
+        KeyAlgorithm::Ed25519 => ring_verify(&ED25519, spki, &digest_bytes, signature),
+        KeyAlgorithm::EcdsaP256 => {
+            ring_verify(&ECDSA_P256_SHA256_ASN1, spki, &digest_bytes, signature)
+        }
+    }
+}
+
+/// Verify a certificate `signature` over `signed_data` (the DER of its
+/// `TBSCertificate`), made with `signature_algorithm` by the key in
+/// `issuer_spki`.
+///
+/// Returns `Ok(false)` if the signature does not verify.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::UnsupportedAlgorithm`] if the signature algorithm
+/// is unsupported or does not suit the issuer's key, or [`VerifyError::Der`]
+/// if the key is malformed.
+pub(crate) fn verify_signed_data(
+    signature_algorithm: ObjectIdentifier,
+    issuer_spki: &SubjectPublicKeyInfoOwned,
+    signed_data: &[u8],
+    signature: &[u8],
+) -> Result<bool, VerifyError> {
+    let key_algorithm = KeyAlgorithm::of_spki(issuer_spki)?;
+    let verified = match key_algorithm {
+        KeyAlgorithm::Rsa if signature_algorithm == OID_RSASSA_PSS => {
+            let key = rsa_public_key(issuer_spki)?;
+            PssSignature::try_from(signature).is_ok_and(|sig| {
+                PssVerifyingKey::<Sha256>::new(key)
+                    .verify(signed_data, &sig)
+                    .is_ok()
+            })
+        }
+        KeyAlgorithm::Rsa if signature_algorithm == OID_SHA256_WITH_RSA => {
+            let key = rsa_public_key(issuer_spki)?;
+            Pkcs1v15Signature::try_from(signature).is_ok_and(|sig| {
+                Pkcs1v15VerifyingKey::<Sha256>::new(key)
+                    .verify(signed_data, &sig)
+                    .is_ok()
+            })
+        }
+        KeyAlgorithm::Ed25519 if signature_algorithm == OID_ED25519 => {
+            ring_verify(&ED25519, issuer_spki, signed_data, signature).is_ok()
+        }
+        KeyAlgorithm::EcdsaP256 if signature_algorithm == OID_ECDSA_WITH_SHA256 => {
+            ring_verify(&ECDSA_P256_SHA256_ASN1, issuer_spki, signed_data, signature).is_ok()
+        }
+        _ => {
+            return Err(VerifyError::UnsupportedAlgorithm(format!(
+                "{signature_algorithm} with a {key_algorithm} key"
+            )))
+        }
+    };
+    Ok(verified)
+}
+
+fn rsa_public_key(spki: &SubjectPublicKeyInfoOwned) -> Result<RsaPublicKey, VerifyError> {
+    let spki_der = spki.to_der().map_err(|e| VerifyError::Der(e.to_string()))?;
+    RsaPublicKey::from_public_key_der(&spki_der).map_err(|e| VerifyError::Der(e.to_string()))
+}
+
+fn ring_verify(
+    algorithm: &'static dyn VerificationAlgorithm,
+    spki: &SubjectPublicKeyInfoOwned,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), VerifyError> {
+    let key = spki
+        .subject_public_key
+        .as_bytes()
+        .ok_or_else(|| VerifyError::Der("public key has unused bits".into()))?;
+    UnparsedPublicKey::new(algorithm, key)
+        .verify(message, signature)
+        .map_err(|_| VerifyError::SignatureMismatch)
+}
@@ -20,6 +20,57 @@ pub enum VerifyError {
     /// A DER/ASN.1 parsing error.
     #[error("DER parsing error: {0}")]
     Der(String),
+    /// The chain has no certificates, or more than the verifier accepts.
+    #[error("certificate chain has {len} certificates; expected 1 to {max}")]
+    ChainLength {
+        /// Number of certificates supplied.
+        len: usize,
+        /// Largest accepted chain, leaf included.
+        max: usize,
+    },
+    /// The trust bundle holds no root certificates.
+    #[error("trust bundle contains no root certificates")]
+    NoTrustedRoots,
+    /// No valid root in the trust bundle issued the top of the chain.
+    #[error("certificate '{subject}' is not issued by a trusted root")]
+    UntrustedRoot {
+        /// Subject common name of the topmost certificate in the chain.
+        subject: String,
+    },
+    /// A certificate's issuer is not the subject of the next one in the chain.
+    #[error("certificate '{subject}' is not issued by the next certificate in the chain")]
+    IssuerMismatch {
+        /// Subject common name of the certificate.
+        subject: String,
+    },
+    /// A certificate that issues another in the chain is not marked as a CA.
+    #[error("certificate '{subject}' is not a CA")]
+    NotCa {
+        /// Subject common name of the certificate.
+        subject: String,
+    },
+    /// A CA certificate has more CAs below it than its path length allows.
+    #[error("certificate '{subject}' allows at most {path_len} intermediate CAs below it")]
+    PathLenExceeded {
+        /// Subject common name of the CA certificate.
+        subject: String,
+        /// The CA's `pathLenConstraint`.
+        path_len: u8,
+    },
+    /// A certificate's keyUsage does not permit how the chain uses it.
+    #[error("certificate '{subject}' keyUsage does not permit {usage}")]
+    KeyUsageNotPermitted {
+        /// Subject common name of the certificate.
+        subject: String,
+        /// The missing usage, e.g. `keyCertSign`.
+        usage: &'static str,
+    },
+    /// A key or signature uses an algorithm the verifier does not support.
+    #[error("unsupported algorithm {0}")]
+    UnsupportedAlgorithm(String),
+    /// A registry timestamp is malformed or does not cover the signature.
+    #[error("invalid signature timestamp: {0}")]
+    InvalidTimestamp(String),
     /// The certificate's validity period has passed.
     #[error("certificate expired on {0}")]
     CertExpired(String),
@@ -34,9 +85,36 @@ pub enum VerifyError {
         /// The actual common name found in the certificate.
         got: String,
     },
+    /// A key rotation record is malformed or does not link the keys it names.
+    #[error("invalid key rotation: {0}")]
+    InvalidRotation(String),
+    /// A transparency log proof or tree head does not verify.
+    #[error("invalid transparency log proof: {0}")]
+    InvalidLogProof(String),
+    /// A namespace's key log is malformed or its entries do not link.
+    #[error("invalid key log: {0}")]
+    InvalidKeyLog(String),
     /// The self-signed publisher key has been revoked by the registry.
     #[error("publisher key has been revoked by the registry")]
     SelfSignedKeyRevoked,
+    /// The manifest carries no publisher signature.
+    #[error("package is missing publisher_sig_hex")]
+    MissingSignature,
+    /// The manifest has no file list, but its version was not published
+    /// under the legacy signing scheme.
+    #[error("manifest has no file list")]
+    MissingFileList,
+    /// The manifest's file list cannot be rendered or does not hash to its `sha256`.
+    #[error("manifest file list does not match its sha256")]
+    PayloadMismatch,
+    /// An extracted file does not match the signed file list.
+    #[error("file '{path}' does not match the signed manifest: {reason}")]
+    FileMismatch {
+        /// Path relative to the package root.
+        path: String,
+        /// What differed.
+        reason: String,
+    },
 }
 
 /// Errors that can occur when checking or refreshing revocation state.
@@ -2,6 +2,10 @@
 #![deny(warnings, clippy::all, clippy::pedantic)]
 #![warn(missing_docs)]
 
+pub mod algorithm;
 pub mod error;
+pub mod merkle;
+pub mod package;
 pub mod revocation;
+pub mod rotation;
 pub mod verifier;
@@ -0,0 +1,408 @@
+//! Merkle tree hashing and proofs for the transparency log (RFC 6962).
+//!
+//! Leaves are hashed as `SHA-256(0x00 || data)` and interior nodes as
+//! `SHA-256(0x01 || left || right)`, so a leaf can never be passed off as a
+//! node. Proof generation is used by the registry, which keeps a
+//! [`MerkleTree`] of the log in memory; verification by clients, following
+//! the algorithms of RFC 9162 §2.1.3 and §2.1.4.
+
+use sha2::{Digest, Sha256};
+use skreg_core::types::Sha256Digest;
+
+use crate::error::VerifyError;
+
+type Hash = [u8; 32];
+
+/// Hash of a log leaf holding `data`.
+#[must_use]
+pub fn leaf_hash(data: &[u8]) -> Sha256Digest {
+    to_digest(&raw_leaf(data))
+}
+
+/// Root hash of a tree whose leaves have the given leaf hashes.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::InvalidLogProof`] if a leaf hash is not 32 bytes
+/// of hex.
+pub fn root_hash(leaves: &[Sha256Digest]) -> Result<Sha256Digest, VerifyError> {
+    MerkleTree::from_leaves(leaves)?.root(leaves.len() as u64)
+}
+
+/// Audit path proving leaf `index` is in the tree of `leaves`.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::InvalidLogProof`] if `index` is out of range or a
+/// leaf hash is malformed.
+pub fn inclusion_proof(
+    leaves: &[Sha256Digest],
+    index: u64,
+) -> Result<Vec<Sha256Digest>, VerifyError> {
+    MerkleTree::from_leaves(leaves)?.inclusion_proof(index, leaves.len() as u64)
+}
+
+/// Proof that the tree of the first `first` of `leaves` is a prefix of the
+/// tree of all of them.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::InvalidLogProof`] if `first` exceeds the number of
+/// leaves or a leaf hash is malformed.
+pub fn consistency_proof(
+    leaves: &[Sha256Digest],
+    first: u64,
+) -> Result<Vec<Sha256Digest>, VerifyError> {
+    MerkleTree::from_leaves(leaves)?.consistency_proof(first, leaves.len() as u64)
+}
+
+/// An append-only Merkle tree that keeps the hash of every complete subtree,
+/// so the root of, and proofs in, the tree of any prefix of its leaves take
+/// O(log² n) hashes rather than rehashing every leaf.
+#[derive(Debug, Clone, Default)]
+pub struct MerkleTree {
+    /// `levels[h][i]` is the hash of the subtree over leaves
+    /// `i * 2^h .. (i + 1) * 2^h`, once all of them are present.
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    /// An empty tree.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tree with the given leaf hashes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::InvalidLogProof`] if a leaf hash is malformed.
+    pub fn from_leaves(leaves: &[Sha256Digest]) -> Result<Self, VerifyError> {
+        let mut tree = Self::new();
+        for leaf in leaves {
+            tree.push(leaf)?;
+        }
+        Ok(tree)
+    }
+
+    /// Number of leaves in the tree.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.levels.first().map_or(0, Vec::len) as u64
+    }
+
+    /// Whether the tree has no leaves.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append a leaf hash, completing every subtree it closes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::InvalidLogProof`] if `leaf` is malformed.
+    pub fn push(&mut self, leaf: &Sha256Digest) -> Result<(), VerifyError> {
+        let mut hash = decode(leaf)?;
+        for level in 0.. {
+            if self.levels.len() == level {
+                self.levels.push(Vec::new());
+            }
+            let nodes = &mut self.levels[level];
+            nodes.push(hash);
+            if let [.., left, right] = nodes.as_slice() {
+                if nodes.len() % 2 == 0 {
+                    hash = node(left, right);
+                    continue;
+                }
+            }
+            break;
+        }
+        Ok(())
+    }
+
+    /// Root hash of the tree of the first `tree_size` leaves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::InvalidLogProof`] if the tree has fewer than
+    /// `tree_size` leaves.
+    pub fn root(&self, tree_size: u64) -> Result<Sha256Digest, VerifyError> {
+        let n = self.prefix(tree_size)?;
+        Ok(to_digest(&self.mth(0, n)))
+    }
+
+    /// Audit path proving leaf `index` is in the tree of the first
+    /// `tree_size` leaves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::InvalidLogProof`] if `index` is not below
+    /// `tree_size` or the tree has fewer than `tree_size` leaves.
+    pub fn inclusion_proof(
+        &self,
+        index: u64,
+        tree_size: u64,
+    ) -> Result<Vec<Sha256Digest>, VerifyError> {
+        let n = self.prefix(tree_size)?;
+        let index = usize::try_from(index)
+            .ok()
+            .filter(|&i| i < n)
+            .ok_or_else(|| proof_err(format!("leaf {index} is not in a tree of {n}")))?;
+        Ok(self.path(index, 0, n).iter().map(to_digest).collect())
+    }
+
+    /// Proof that the tree of the first `first` leaves is a prefix of the
+    /// tree of the first `second`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::InvalidLogProof`] if `first` exceeds `second`
+    /// or the tree has fewer than `second` leaves.
+    pub fn consistency_proof(
+        &self,
+        first: u64,
+        second: u64,
+    ) -> Result<Vec<Sha256Digest>, VerifyError> {
+        let n = self.prefix(second)?;
+        let first = usize::try_from(first)
+            .ok()
+            .filter(|&m| m <= n)
+            .ok_or_else(|| proof_err(format!("tree of {first} is larger than {n}")))?;
+        if first == 0 {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .subproof(first, 0, n, true)
+            .iter()
+            .map(to_digest)
+            .collect())
+    }
+
+    fn prefix(&self, tree_size: u64) -> Result<usize, VerifyError> {
+        usize::try_from(tree_size)
+            .ok()
+            .filter(|_| tree_size <= self.len())
+            .ok_or_else(|| {
+                proof_err(format!(
+                    "tree of {tree_size} is larger than the {} leaves logged",
+                    self.len()
+                ))
+            })
+    }
+
+    /// MTH(D[start:end]) of RFC 6962 §2.1.
+    fn mth(&self, start: usize, end: usize) -> Hash {
+        let n = end - start;
+        if n == 0 {
+            return Sha256::digest([]).into();
+        }
+        if n.is_power_of_two() && start % n == 0 {
+            return self.levels[n.trailing_zeros() as usize][start / n];
+        }
+        let k = split(n);
+        node(&self.mth(start, start + k), &self.mth(start + k, end))
+    }
+
+    /// PATH(m, D[start:end]) of RFC 6962 §2.1.1.
+    fn path(&self, m: usize, start: usize, end: usize) -> Vec<Hash> {
+        if end - start <= 1 {
+            return Vec::new();
+        }
+        let k = split(end - start);
+        if m < k {
+            let mut proof = self.path(m, start, start + k);
+            proof.push(self.mth(start + k, end));
+            proof
+        } else {
+            let mut proof = self.path(m - k, start + k, end);
+            proof.push(self.mth(start, start + k));
+            proof
+        }
+    }
+
+    /// SUBPROOF(m, D[start:end], b) of RFC 6962 §2.1.2.
+    fn subproof(&self, m: usize, start: usize, end: usize, complete: bool) -> Vec<Hash> {
+        if m == end - start {
+            return if complete {
+                Vec::new()
+            } else {
+                vec![self.mth(start, end)]
+            };
+        }
+        let k = split(end - start);
+        if m <= k {
+            let mut proof = self.subproof(m, start, start + k, complete);
+            proof.push(self.mth(start + k, end));
+            proof
+        } else {
+            let mut proof = self.subproof(m - k, start + k, end, false);
+            proof.push(self.mth(start, start + k));
+            proof
+        }
+    }
+}
+
+/// Check that `leaf` is leaf `index` of the tree of `tree_size` leaves with
+/// root `root`, given its audit `path`.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::InvalidLogProof`] if the proof does not hold.
+pub fn verify_inclusion(
+    leaf: &Sha256Digest,
+    index: u64,
+    tree_size: u64,
+    path: &[Sha256Digest],
+    root: &Sha256Digest,
+) -> Result<(), VerifyError> {
+    if index >= tree_size {
+        return Err(proof_err(format!(
+            "leaf {index} is not in a tree of {tree_size}"
+        )));
+    }
+    let (mut fnode, mut snode) = (index, tree_size - 1);
+    let mut r = decode(leaf)?;
+    for p in decode_all(path)? {
+        if snode == 0 {
+            return Err(proof_err("inclusion proof is too long"));
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            r = node(&p, &r);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            r = node(&r, &p);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    if snode != 0 || r != decode(root)? {
+        return Err(proof_err(format!(
+            "leaf {index} is not in the tree of {tree_size} with root {root}"
+        )));
+    }
+    Ok(())
+}
+
+/// Check that the tree of `first` leaves with root `first_root` is a prefix
+/// of the tree of `second` leaves with root `second_root`.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::InvalidLogProof`] if the proof does not hold,
+/// which means the log was rewritten between the two tree heads.
+pub fn verify_consistency(
+    first: u64,
+    second: u64,
+    first_root: &Sha256Digest,
+    second_root: &Sha256Digest,
+    proof: &[Sha256Digest],
+) -> Result<(), VerifyError> {
+    let inconsistent = || {
+        proof_err(format!(
+            "tree of {first} with root {first_root} is not a prefix of the tree of {second} \
+             with root {second_root}"
+        ))
+    };
+    if first > second {
+        return Err(inconsistent());
+    }
+    if first == second {
+        return if proof.is_empty() && first_root == second_root {
+            Ok(())
+        } else {
+            Err(inconsistent())
+        };
+    }
+    if first == 0 {
+        return if proof.is_empty() {
+            Ok(())
+        } else {
+            Err(inconsistent())
+        };
+    }
+
+    let mut proof = decode_all(proof)?;
+    if first.is_power_of_two() {
+        proof.insert(0, decode(first_root)?);
+    }
+    let Some((start, rest)) = proof.split_first() else {
+        return Err(inconsistent());
+    };
+    let (mut fnode, mut snode) = (first - 1, second - 1);
+    while fnode & 1 == 1 {
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    let (mut fr, mut sr) = (*start, *start);
+    for c in rest {
+        if snode == 0 {
+            return Err(inconsistent());
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            fr = node(c, &fr);
+            sr = node(c, &sr);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            sr = node(&sr, c);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    if snode == 0 && fr == decode(first_root)? && sr == decode(second_root)? {
+        Ok(())
+    } else {
+        Err(inconsistent())
+    }
+}
+
+fn raw_leaf(data: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+fn node(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Largest power of two smaller than `n` (for `n > 1`).
+fn split(n: usize) -> usize {
+    let k = n.next_power_of_two() / 2;
+    if k == n {
+        k / 2
+    } else {
+        k
+    }
+}
+
+fn proof_err(reason: impl Into<String>) -> VerifyError {
+    VerifyError::InvalidLogProof(reason.into())
+}
+
+fn decode(digest: &Sha256Digest) -> Result<Hash, VerifyError> {
+    hex::decode(digest.as_hex())
+        .ok()
+        .and_then(|bytes| Hash::try_from(bytes).ok())
+        .ok_or_else(|| proof_err(format!("malformed hash {digest}")))
+}
+
+fn decode_all(digests: &[Sha256Digest]) -> Result<Vec<Hash>, VerifyError> {
+    digests.iter().map(decode).collect()
+}
+
+fn to_digest(hash: &Hash) -> Sha256Digest {
+    Sha256Digest::from_bytes(hash)
+}
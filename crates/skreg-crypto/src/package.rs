@@ -0,0 +1,186 @@
+//! Verification of a package manifest's publisher signature and of the files
+//! extracted alongside it.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+use skreg_core::manifest::{FileEntry, Manifest};
+use skreg_core::timestamp::SignedTimestamp;
+use skreg_core::types::Sha256Digest;
+use x509_cert::der::DecodePem;
+use x509_cert::Certificate;
+
+use crate::error::VerifyError;
+use crate::verifier::{SignatureVerifier, VerifiedSigner};
+
+/// How the publisher signature on a version's manifest was made.
+///
+/// The scheme comes from the registry's record of the version, never from
+/// the manifest, so stripping a manifest's file list cannot downgrade its
+/// check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigningScheme {
+    /// Published before domain-separated signatures: the publisher signed
+    /// `manifest.sha256`, the payload digest of its file list or, without
+    /// one, the digest of an unsigned tarball that cannot be recomputed.
+    Legacy,
+    /// The publisher signed [`Manifest::signing_digest`]; a file list is
+    /// required.
+    FileList,
+}
+
+impl SigningScheme {
+    /// The scheme of a version the registry does or does not mark as legacy.
+    #[must_use]
+    pub fn from_legacy(legacy: bool) -> Self {
+        if legacy {
+            Self::Legacy
+        } else {
+            Self::FileList
+        }
+    }
+}
+
+/// The digest the publisher signed under `scheme`.
+///
+/// A file list is recomputed and must hash to `manifest.sha256`. Legacy
+/// manifests without one are trusted to carry the digest of the unsigned
+/// tarball that was signed.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::MissingFileList`] if `scheme` requires a file list
+/// and the manifest has none, or [`VerifyError::PayloadMismatch`] if the file
+/// list does not hash to `manifest.sha256`.
+pub fn signed_digest(
+    manifest: &Manifest,
+    scheme: SigningScheme,
+) -> Result<Sha256Digest, VerifyError> {
+    if manifest.files.is_empty() {
+        return match scheme {
+            SigningScheme::Legacy => Ok(manifest.sha256.clone()),
+            SigningScheme::FileList => Err(VerifyError::MissingFileList),
+        };
+    }
+    if manifest.payload_digest().as_ref() != Some(&manifest.sha256) {
+        return Err(VerifyError::PayloadMismatch);
+    }
+    match scheme {
+        SigningScheme::Legacy => Ok(manifest.sha256.clone()),
+        SigningScheme::FileList => manifest
+            .signing_digest()
+            .ok_or(VerifyError::PayloadMismatch),
+    }
+}
+
+/// Verify the publisher signature on `manifest`, made under `scheme`,
+/// optionally requiring the signer's common name to equal `namespace`.
+///
+/// With a registry `timestamp`, the certificate chain is judged at the time
+/// it proves. Without one, a [`SigningScheme::Legacy`] version, vetted before
+/// timestamps were issued, is judged at the last moment its signing
+/// certificate was valid, so it keeps verifying after that certificate
+/// expires; any other version is judged at the current time.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::MissingSignature`] if there is no signature, any
+/// error from [`signed_digest`], or any error from the verifier.
+pub fn verify_manifest(
+    verifier: &dyn SignatureVerifier,
+    manifest: &Manifest,
+    scheme: SigningScheme,
+    namespace: Option<&str>,
+    timestamp: Option<&SignedTimestamp>,
+) -> Result<VerifiedSigner, VerifyError> {
+    let sig_hex = manifest
+        .publisher_sig_hex
+        .as_deref()
+        .ok_or(VerifyError::MissingSignature)?;
+    let signature = hex::decode(sig_hex).map_err(|_| VerifyError::SignatureMismatch)?;
+    let digest = signed_digest(manifest, scheme)?;
+    let now = SystemTime::now();
+    let at = match (timestamp, scheme) {
+        (Some(ts), _) => verifier.verify_timestamp(ts, &signature)?,
+        (None, SigningScheme::Legacy) => {
+            leaf_not_after(&manifest.cert_chain_pem).map_or(now, |not_after| not_after.min(now))
+        }
+        (None, SigningScheme::FileList) => now,
+    };
+    let signer = verifier.verify_at(&digest, &signature, &manifest.cert_chain_pem, at)?;
+    match namespace {
+        Some(ns) => signer.require_namespace(ns),
+        None => Ok(signer),
+    }
+}
+
+/// End of the validity period of the first certificate in `cert_chain_pem`,
+/// or `None` if it cannot be parsed (the verifier then reports why).
+fn leaf_not_after(cert_chain_pem: &[String]) -> Option<SystemTime> {
+    let leaf = Certificate::from_pem(cert_chain_pem.first()?.as_bytes()).ok()?;
+    Some(leaf.tbs_certificate.validity.not_after.to_system_time())
+}
+
+/// Check that the files under `dir` are exactly those in `files`, with
+/// matching sizes and digests. A root `manifest.json` is ignored.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::FileMismatch`] for the first missing, extra or
+/// altered file.
+pub fn verify_files(dir: &Path, files: &[FileEntry]) -> Result<(), VerifyError> {
+    let mut on_disk = BTreeMap::new();
+    collect_files(dir, "", &mut on_disk).map_err(|e| mismatch(".", &e.to_string()))?;
+
+    for entry in files {
+        let Some(path) = on_disk.remove(&entry.path) else {
+            return Err(mismatch(&entry.path, "missing"));
+        };
+        let bytes = std::fs::read(&path).map_err(|e| mismatch(&entry.path, &e.to_string()))?;
+        if bytes.len() as u64 != entry.size {
+            return Err(mismatch(
+                &entry.path,
+                &format!("size {} != {}", bytes.len(), entry.size),
+            ));
+        }
+        if hex::encode(Sha256::digest(&bytes)) != entry.sha256.as_hex() {
+            return Err(mismatch(&entry.path, "sha256 differs"));
+        }
+    }
+
+    match on_disk.into_keys().next() {
+        Some(extra) => Err(mismatch(&extra, "not listed")),
+        None => Ok(()),
+    }
+}
+
+fn mismatch(path: &str, reason: &str) -> VerifyError {
+    VerifyError::FileMismatch {
+        path: path.to_owned(),
+        reason: reason.to_owned(),
+    }
+}
+
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    out: &mut BTreeMap<String, std::path::PathBuf>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let rel = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), &rel, out)?;
+        } else if rel != "manifest.json" {
+            out.insert(rel, entry.path());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,200 @@
+//! Publisher key fingerprints and verification of key rotation and key-set
+//! change records.
+//!
+//! Clients pin a namespace's key by its SPKI fingerprint. When the registry
+//! later presents a different key, a chain of [`RotationRecord`]s, each
+//! signed by the key it replaces, and [`KeyChangeRecord`]s adding keys, each
+//! signed by a key already in the set, shows that every step was endorsed by
+//! the holder of a key the client trusted.
+
+use der::{DecodePem, Encode};
+use sha2::{Digest, Sha256};
+use skreg_core::keyset::{KeyChangeAction, KeyChangeRecord};
+use skreg_core::rotation::RotationRecord;
+use skreg_core::types::Sha256Digest;
+use x509_cert::Certificate;
+
+use crate::algorithm::verify_digest;
+use crate::error::VerifyError;
+
+/// SHA-256 fingerprint (hex) of the DER `SubjectPublicKeyInfo` of a PEM
+/// certificate — the value the registry pins for a namespace.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::Der`] if the certificate cannot be parsed.
+pub fn spki_fingerprint(cert_pem: &str) -> Result<String, VerifyError> {
+    let spki_der = Certificate::from_pem(cert_pem)
+        .and_then(|cert| cert.tbs_certificate.subject_public_key_info.to_der())
+        .map_err(|e| VerifyError::Der(e.to_string()))?;
+    Ok(hex::encode(Sha256::digest(&spki_der)))
+}
+
+/// Check that `record` is a rotation signed by both its old and new keys,
+/// and that its certificates carry the fingerprints the token names.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::InvalidRotation`] if the certificates do not match
+/// the token, [`VerifyError::SignatureMismatch`] if either signature does
+/// not verify, or any error from parsing the certificates.
+pub fn verify_rotation(record: &RotationRecord) -> Result<(), VerifyError> {
+    let token = &record.token;
+    let new_cert_pem = token
+        .new_cert_chain_pem
+        .first()
+        .ok_or_else(|| VerifyError::InvalidRotation("no certificate for the new key".into()))?;
+    if spki_fingerprint(&record.old_cert_pem)? != token.old_key_fingerprint {
+        return Err(VerifyError::InvalidRotation(
+            "old certificate does not match old_key_fingerprint".into(),
+        ));
+    }
+    if spki_fingerprint(new_cert_pem)? != token.new_key_fingerprint {
+        return Err(VerifyError::InvalidRotation(
+            "new certificate does not match new_key_fingerprint".into(),
+        ));
+    }
+
+    let token_bytes = token
+        .signing_bytes()
+        .map_err(|e| VerifyError::InvalidRotation(e.to_string()))?;
+    let digest = Sha256Digest::of(&token_bytes);
+    for (cert_pem, sig_hex) in [
+        (record.old_cert_pem.as_str(), &record.old_sig),
+        (new_cert_pem.as_str(), &record.new_sig),
+    ] {
+        let cert = Certificate::from_pem(cert_pem).map_err(|e| VerifyError::Der(e.to_string()))?;
+        let sig = hex::decode(sig_hex).map_err(|_| VerifyError::SignatureMismatch)?;
+        verify_digest(&cert.tbs_certificate.subject_public_key_info, &digest, &sig)?;
+    }
+    Ok(())
+}
+
+/// Check that `records`, in the order given, contain a chain of verified
+/// rotations for `namespace` leading from the key fingerprinted `from` to
+/// the one fingerprinted `to`. Records that are not part of the chain are
+/// ignored.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::InvalidRotation`] if no such chain exists, or any
+/// error from [`verify_rotation`] for a record on it.
+pub fn verify_rotation_chain(
+    records: &[RotationRecord],
+    namespace: &str,
+    from: &str,
+    to: &str,
+) -> Result<(), VerifyError> {
+    let mut current = from;
+    for record in records {
+        if current == to {
+            break;
+        }
+        if record.token.namespace == namespace && record.token.old_key_fingerprint == current {
+            verify_rotation(record)?;
+            current = &record.token.new_key_fingerprint;
+        }
+    }
+    if current == to {
+        Ok(())
+    } else {
+        Err(VerifyError::InvalidRotation(format!(
+            "no rotation signed by key {from} leads to key {to}"
+        )))
+    }
+}
+
+/// Check that `record` is signed by the key it names as signer and, for an
+/// addition, that its certificate carries the fingerprint added.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::InvalidRotation`] if the certificates do not match
+/// the token or an addition has no certificate,
+/// [`VerifyError::SignatureMismatch`] if the signature does not verify, or
+/// any error from parsing the certificates.
+pub fn verify_key_change(record: &KeyChangeRecord) -> Result<(), VerifyError> {
+    let token = &record.token;
+    if spki_fingerprint(&record.signer_cert_pem)? != token.signer_fingerprint {
+        return Err(VerifyError::InvalidRotation(
+            "signer certificate does not match signer_fingerprint".into(),
+        ));
+    }
+    if token.action == KeyChangeAction::Add {
+        let cert_pem = token.cert_pem.as_deref().ok_or_else(|| {
+            VerifyError::InvalidRotation("no certificate for the added key".into())
+        })?;
+        if spki_fingerprint(cert_pem)? != token.fingerprint {
+            return Err(VerifyError::InvalidRotation(
+                "added certificate does not match fingerprint".into(),
+            ));
+        }
+    }
+
+    let token_bytes = token
+        .signing_bytes()
+        .map_err(|e| VerifyError::InvalidRotation(e.to_string()))?;
+    let cert = Certificate::from_pem(&record.signer_cert_pem)
+        .map_err(|e| VerifyError::Der(e.to_string()))?;
+    let sig = hex::decode(&record.signature).map_err(|_| VerifyError::SignatureMismatch)?;
+    verify_digest(
+        &cert.tbs_certificate.subject_public_key_info,
+        &Sha256Digest::of(&token_bytes),
+        &sig,
+    )
+}
+
+/// Check that the key fingerprinted `to` was endorsed, directly or through
+/// other keys, by one of the `trusted` keys of `namespace`: either a
+/// verified rotation from a trusted key to it, or a verified key-set
+/// addition of it signed by a trusted key. Records of other namespaces, and
+/// removals, are ignored.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::InvalidRotation`] if no such chain exists, or any
+/// error from [`verify_rotation`] or [`verify_key_change`] for a record on
+/// it.
+pub fn verify_key_chain(
+    rotations: &[RotationRecord],
+    changes: &[KeyChangeRecord],
+    namespace: &str,
+    trusted: &[String],
+    to: &str,
+) -> Result<(), VerifyError> {
+    let mut reached: Vec<&str> = trusted.iter().map(String::as_str).collect();
+    // Each pass follows every endorsement out of the keys reached so far;
+    // stop once a pass reaches nothing new.
+    loop {
+        if reached.contains(&to) {
+            return Ok(());
+        }
+        let before = reached.len();
+        for record in rotations {
+            let token = &record.token;
+            if token.namespace == namespace
+                && reached.contains(&token.old_key_fingerprint.as_str())
+                && !reached.contains(&token.new_key_fingerprint.as_str())
+            {
+                verify_rotation(record)?;
+                reached.push(&token.new_key_fingerprint);
+            }
+        }
+        for record in changes {
+            let token = &record.token;
+            if token.namespace == namespace
+                && token.action == KeyChangeAction::Add
+                && reached.contains(&token.signer_fingerprint.as_str())
+                && !reached.contains(&token.fingerprint.as_str())
+            {
+                verify_key_change(record)?;
+                reached.push(&token.fingerprint);
+            }
+        }
+        if reached.len() == before {
+            return Err(VerifyError::InvalidRotation(format!(
+                "no rotation or key addition signed by a trusted key leads to key {to}"
+            )));
+        }
+    }
+}
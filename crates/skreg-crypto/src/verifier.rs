@@ -1,21 +1,38 @@
-//! Signature verification against the embedded root CA.
-
-use rsa::pkcs8::DecodePublicKey;
-use rsa::pss::{Signature as PssSignature, VerifyingKey as PssVerifyingKey};
-use rsa::signature::hazmat::PrehashVerifier;
-use rsa::signature::Verifier;
-use rsa::RsaPublicKey;
-use sha2::Sha256;
+//! Signature verification against a bundle of trusted root CAs.
+//!
+//! A CA-verified chain is validated as an X.509 certification path: each
+//! certificate must name the next as its issuer and carry its signature,
+//! every issuing certificate must be a CA whose `pathLenConstraint` and
+//! keyUsage allow it to issue, every certificate must be within its validity
+//! period, and the topmost one must be issued by a valid root from the trust
+//! bundle. Validity is judged at the time being verified: now, or the time
+//! proven by a registry [`SignedTimestamp`]. A bundle may hold several
+//! roots, so a new root can be trusted alongside the old one before the old
+//! one is retired.
+//!
+//! Signatures are checked with whichever algorithm each certificate's key
+//! uses; see [`crate::algorithm`].
+
+use std::time::SystemTime;
+
+use skreg_core::key_log::{KeyLog, SignedKeyLog};
+use skreg_core::timestamp::{SignedTimestamp, Timestamp};
+use skreg_core::transparency::{SignedTreeHead, TreeHead};
 use skreg_core::types::Sha256Digest;
-use x509_cert::der::asn1::{PrintableStringRef, Utf8StringRef};
+use x509_cert::der::asn1::{ObjectIdentifier, PrintableStringRef, Utf8StringRef};
 use x509_cert::der::{DecodePem, Encode};
+use x509_cert::ext::pkix::{BasicConstraints, KeyUsage, KeyUsages};
 use x509_cert::Certificate;
 
+use crate::algorithm::{verify_digest, verify_signed_data};
 use crate::error::VerifyError;
 
 /// OID for Common Name attribute (2.5.4.3).
 const OID_COMMON_NAME: &str = "2.5.4.3";
 
+/// Most certificates a publisher chain may contain, leaf included.
+pub const MAX_CHAIN_LEN: usize = 4;
+
 /// The identity of a verified signer extracted from a certificate chain.
 #[derive(Debug, Clone)]
 pub struct VerifiedSigner {
@@ -27,24 +44,92 @@ pub struct VerifiedSigner {
     pub ca_verified: bool,
 }
 
+impl VerifiedSigner {
+    /// Check that the signer's common name is `namespace`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::CnMismatch`] if it is not.
+    pub fn require_namespace(self, namespace: &str) -> Result<Self, VerifyError> {
+        if self.common_name == namespace {
+            Ok(self)
+        } else {
+            Err(VerifyError::CnMismatch {
+                expected: namespace.to_owned(),
+                got: self.common_name,
+            })
+        }
+    }
+}
+
 /// Verifies a detached package signature against a certificate chain and root CA.
 pub trait SignatureVerifier: Send + Sync {
-    /// Verify a detached `signature` over the given `digest`.
+    /// Verify a detached `signature` over the given `digest`, with every
+    /// certificate in the chain required to be valid at time `at`.
     ///
-    /// `cert_chain_pem` must have exactly 1 entry (self-signed) or 2 entries
-    /// (CA-verified: leaf first, then Publisher CA cert).
+    /// `cert_chain_pem` holds either one self-signed certificate, or a leaf
+    /// followed by the intermediate CAs that lead to a trusted root, at most
+    /// [`MAX_CHAIN_LEN`] in all.
     ///
     /// # Errors
     ///
-    /// Returns [`VerifyError`] if the chain is invalid, the signature does not
-    /// match, or any certificate in the chain has been revoked.
-    fn verify(
+    /// Returns [`VerifyError`] if the chain is invalid or not valid at `at`,
+    /// the signature does not match, or any certificate in the chain has been
+    /// revoked.
+    fn verify_at(
         &self,
         digest: &Sha256Digest,
         signature: &[u8],
         cert_chain_pem: &[String],
+        at: SystemTime,
     ) -> Result<VerifiedSigner, VerifyError>;
 
+    /// Verify a registry-signed `timestamp` over a publisher `signature` and
+    /// return the time it proves the signature existed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::InvalidTimestamp`] if the timestamp is malformed
+    /// or covers a different signature, or any error from checking the
+    /// registry's signature on it.
+    fn verify_timestamp(
+        &self,
+        timestamp: &SignedTimestamp,
+        signature: &[u8],
+    ) -> Result<SystemTime, VerifyError>;
+
+    /// Verify a registry-signed transparency log tree head and return it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::InvalidLogProof`] if the tree head is
+    /// malformed, or any error from checking the registry's signature on it.
+    fn verify_tree_head(&self, tree_head: &SignedTreeHead) -> Result<TreeHead, VerifyError>;
+
+    /// Verify a registry-signed key log and return it, with its entries
+    /// checked to be numbered, dated and linked in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::InvalidKeyLog`] if the log is malformed or its
+    /// entries do not link, or any error from checking the registry's
+    /// signature on it.
+    fn verify_key_log(&self, key_log: &SignedKeyLog) -> Result<KeyLog, VerifyError>;
+
+    /// Verify a detached `signature` over the given `digest` as of now.
+    ///
+    /// # Errors
+    ///
+    /// As for [`verify_at`](Self::verify_at).
+    fn verify(
+        &self,
+        digest: &Sha256Digest,
+        signature: &[u8],
+        cert_chain_pem: &[String],
+    ) -> Result<VerifiedSigner, VerifyError> {
+        self.verify_at(digest, signature, cert_chain_pem, SystemTime::now())
+    }
+
     /// Verify signature and additionally check that the cert CN matches `namespace`.
     ///
     /// # Errors
@@ -57,50 +142,92 @@ pub trait SignatureVerifier: Send + Sync {
         signature: &[u8],
         cert_chain_pem: &[String],
         namespace: &str,
-    ) -> Result<VerifiedSigner, VerifyError>;
+    ) -> Result<VerifiedSigner, VerifyError> {
+        self.verify(digest, signature, cert_chain_pem)?
+            .require_namespace(namespace)
+    }
 }
 
-/// Path to the bundled root CA certificate (relative to this file).
+/// Path to the bundled trust bundle (relative to this file).
 const ROOT_CA_PEM: &[u8] = include_bytes!("../../../certs/root-ca.pem");
 
-/// RSA-PSS + SHA-256 verifier backed by a bundled root CA.
+/// Verifier backed by a bundle of trusted root CAs.
+///
+/// Named for the registry's own RSA-PSS signatures, it accepts publisher
+/// keys of every [`KeyAlgorithm`](crate::algorithm::KeyAlgorithm).
 ///
-/// `cert_chain_pem` must contain exactly 1 certificate (self-signed publisher)
-/// or 2 certificates (leaf + Publisher CA, in that order).
+/// `cert_chain_pem` must contain exactly 1 certificate (self-signed
+/// publisher) or a leaf followed by up to `MAX_CHAIN_LEN - 1` intermediates,
+/// each issued by the next. The chain may end with the root itself.
 pub struct RsaPssVerifier {
-    root_ca_pem: Vec<u8>,
+    trust_bundle_pem: Vec<u8>,
 }
 
 impl RsaPssVerifier {
-    /// Create a verifier using the bundled root CA.
+    /// Create a verifier using the bundled root CAs.
     #[must_use]
     pub fn new() -> Self {
         Self {
-            root_ca_pem: ROOT_CA_PEM.to_vec(),
+            trust_bundle_pem: ROOT_CA_PEM.to_vec(),
         }
     }
 
-    /// Create a verifier with a custom root CA PEM.
+    /// Create a verifier trusting every certificate in a PEM bundle.
     ///
-    /// Intended for testing; production code should use [`RsaPssVerifier::new`].
+    /// Lets a deployment trust additional or replacement roots without a new
+    /// binary; tests use it to supply their own root.
     #[must_use]
     pub fn new_with_root_pem(pem: &[u8]) -> Self {
         Self {
-            root_ca_pem: pem.to_vec(),
+            trust_bundle_pem: pem.to_vec(),
         }
     }
 
-    fn parse_cert(pem: &str) -> Result<Certificate, VerifyError> {
-        Certificate::from_pem(pem.as_bytes()).map_err(|e| VerifyError::Der(e.to_string()))
+    /// Verify a registry-signed document such as the advisory feed.
+    ///
+    /// `signer_cert_pem` must be one of the trusted roots or be issued
+    /// directly by one (the Publisher CA). `signature` is made over `digest`
+    /// as described in [`crate::algorithm`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::UntrustedRoot`] if the signer is not anchored
+    /// at a trusted root, another [`VerifyError`] if it is not currently
+    /// valid, or [`VerifyError::SignatureMismatch`] if the signature does not
+    /// match.
+    pub fn verify_registry_signed(
+        &self,
+        digest: &Sha256Digest,
+        signature: &[u8],
+        signer_cert_pem: &str,
+    ) -> Result<VerifiedSigner, VerifyError> {
+        let signer = Self::parse_cert(signer_cert_pem)?;
+        let now = SystemTime::now();
+        if self.roots()?.contains(&signer) {
+            Self::check_validity(&signer, now)?;
+        } else {
+            self.validate_path(std::slice::from_ref(&signer), now)?;
+        }
+        Self::verify_package_sig(&signer, digest, signature)?;
+        Ok(VerifiedSigner {
+            cert_serial: Self::extract_serial(&signer),
+            common_name: Self::extract_common_name(&signer),
+            ca_verified: true,
+        })
     }
 
-    fn extract_rsa_public_key(cert: &Certificate) -> Result<RsaPublicKey, VerifyError> {
-        let spki_der = cert
-            .tbs_certificate
-            .subject_public_key_info
-            .to_der()
+    /// Parse every certificate in the trust bundle.
+    fn roots(&self) -> Result<Vec<Certificate>, VerifyError> {
+        let roots = Certificate::load_pem_chain(&self.trust_bundle_pem)
             .map_err(|e| VerifyError::Der(e.to_string()))?;
-        RsaPublicKey::from_public_key_der(&spki_der).map_err(|e| VerifyError::Der(e.to_string()))
+        if roots.is_empty() {
+            return Err(VerifyError::NoTrustedRoots);
+        }
+        Ok(roots)
+    }
+
+    fn parse_cert(pem: &str) -> Result<Certificate, VerifyError> {
+        Certificate::from_pem(pem.as_bytes()).map_err(|e| VerifyError::Der(e.to_string()))
     }
 
     /// Extract the raw Common Name value from the cert subject DN.
@@ -108,7 +235,7 @@ impl RsaPssVerifier {
     /// Tries `UTF8String` then `PrintableString` encoding. Falls back to the full
     /// RFC 4514 subject string if no CN attribute is found.
     fn extract_common_name(cert: &Certificate) -> String {
-        let cn_oid = x509_cert::der::asn1::ObjectIdentifier::new_unwrap(OID_COMMON_NAME);
+        let cn_oid = ObjectIdentifier::new_unwrap(OID_COMMON_NAME);
         for rdn in &cert.tbs_certificate.subject.0 {
             for atv in rdn.0.iter() {
                 if atv.oid == cn_oid {
@@ -132,7 +259,8 @@ impl RsaPssVerifier {
             .and_then(|b| b.try_into().ok().map(u64::from_be_bytes))
     }
 
-    /// Verify that `cert` was signed by `signer` using RSA-PSS / SHA-256.
+    /// Verify that `cert` was signed by `signer`, with the signature
+    /// algorithm the certificate names and `signer`'s key.
     fn verify_cert_signed_by(cert: &Certificate, signer: &Certificate) -> Result<(), VerifyError> {
         let tbs_der = cert
             .tbs_certificate
@@ -142,37 +270,153 @@ impl RsaPssVerifier {
             .signature
             .as_bytes()
             .ok_or_else(|| VerifyError::InvalidCertChain("no cert signature bytes".into()))?;
-        let sig = PssSignature::try_from(sig_bytes)
-            .map_err(|_| VerifyError::InvalidCertChain("cert signature not valid PSS".into()))?;
-        let signer_key = Self::extract_rsa_public_key(signer)?;
-        let verifying_key = PssVerifyingKey::<Sha256>::new(signer_key);
-        verifying_key
-            .verify(&tbs_der, &sig)
-            .map_err(|_| VerifyError::InvalidCertChain("cert signature verification failed".into()))
+        let verified = verify_signed_data(
+            cert.signature_algorithm.oid,
+            &signer.tbs_certificate.subject_public_key_info,
+            &tbs_der,
+            sig_bytes,
+        )?;
+        if verified {
+            Ok(())
+        } else {
+            Err(VerifyError::InvalidCertChain(format!(
+                "signature on '{}' does not verify",
+                Self::extract_common_name(cert)
+            )))
+        }
+    }
+
+    /// Check that `now` falls within `cert`'s validity period.
+    fn check_validity(cert: &Certificate, now: SystemTime) -> Result<(), VerifyError> {
+        let validity = &cert.tbs_certificate.validity;
+        if now < validity.not_before.to_system_time() {
+            return Err(VerifyError::CertNotYetValid(
+                validity.not_before.to_string(),
+            ));
+        }
+        if now > validity.not_after.to_system_time() {
+            return Err(VerifyError::CertExpired(validity.not_after.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Check that `cert`'s keyUsage, if present, includes `usage`.
+    fn require_key_usage(
+        cert: &Certificate,
+        usage: KeyUsages,
+        name: &'static str,
+    ) -> Result<(), VerifyError> {
+        match cert
+            .tbs_certificate
+            .get::<KeyUsage>()
+            .map_err(|e| VerifyError::Der(e.to_string()))?
+        {
+            Some((_, key_usage)) if !key_usage.0.contains(usage) => {
+                Err(VerifyError::KeyUsageNotPermitted {
+                    subject: Self::extract_common_name(cert),
+                    usage: name,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that `cert` may act as a CA with `below` intermediate CAs
+    /// beneath it in the path.
+    fn check_issuer(cert: &Certificate, below: usize) -> Result<(), VerifyError> {
+        let constraints = cert
+            .tbs_certificate
+            .get::<BasicConstraints>()
+            .map_err(|e| VerifyError::Der(e.to_string()))?;
+        match constraints {
+            Some((_, bc)) if bc.ca => {
+                if let Some(path_len) = bc.path_len_constraint {
+                    if below > usize::from(path_len) {
+                        return Err(VerifyError::PathLenExceeded {
+                            subject: Self::extract_common_name(cert),
+                            path_len,
+                        });
+                    }
+                }
+            }
+            _ => {
+                return Err(VerifyError::NotCa {
+                    subject: Self::extract_common_name(cert),
+                })
+            }
+        }
+        Self::require_key_usage(cert, KeyUsages::KeyCertSign, "keyCertSign")
+    }
+
+    /// Validate `chain` (leaf first) as a certification path to a trusted
+    /// root at time `now`.
+    ///
+    /// The leaf's own keyUsage is left to the caller, since registry and
+    /// publisher signers use their keys differently.
+    fn validate_path(&self, chain: &[Certificate], now: SystemTime) -> Result<(), VerifyError> {
+        let roots = self.roots()?;
+        // A chain that ends with the root itself adds nothing to the path.
+        let path = match chain.split_last() {
+            Some((last, rest)) if !rest.is_empty() && roots.contains(last) => rest,
+            _ => chain,
+        };
+        let Some(top) = path.last() else {
+            return Err(VerifyError::ChainLength {
+                len: 0,
+                max: MAX_CHAIN_LEN,
+            });
+        };
+
+        for (depth, cert) in path.iter().enumerate() {
+            Self::check_validity(cert, now)?;
+            if depth > 0 {
+                Self::check_issuer(cert, depth - 1)?;
+            }
+            if let Some(issuer) = path.get(depth + 1) {
+                if cert.tbs_certificate.issuer != issuer.tbs_certificate.subject {
+                    return Err(VerifyError::IssuerMismatch {
+                        subject: Self::extract_common_name(cert),
+                    });
+                }
+                Self::verify_cert_signed_by(cert, issuer)?;
+            }
+        }
+
+        let anchored = roots.iter().any(|root| {
+            root.tbs_certificate.subject == top.tbs_certificate.issuer
+                && Self::check_validity(root, now).is_ok()
+                && Self::verify_cert_signed_by(top, root).is_ok()
+        });
+        if anchored {
+            Ok(())
+        } else {
+            Err(VerifyError::UntrustedRoot {
+                subject: Self::extract_common_name(top),
+            })
+        }
     }
 
-    /// Verify a package signature (RSA-PSS over prehashed digest).
+    /// Verify a package signature over `digest` with `cert`'s key.
     fn verify_package_sig(
-        public_key: &RsaPublicKey,
+        cert: &Certificate,
         digest: &Sha256Digest,
         sig_bytes: &[u8],
     ) -> Result<(), VerifyError> {
-        let digest_bytes = hex::decode(digest.as_hex())
-            .map_err(|e| VerifyError::InvalidCertChain(format!("invalid digest hex: {e}")))?;
-        let sig = PssSignature::try_from(sig_bytes).map_err(|_| VerifyError::SignatureMismatch)?;
-        let verifying_key = PssVerifyingKey::<Sha256>::new(public_key.clone());
-        verifying_key
-            .verify_prehash(&digest_bytes, &sig)
-            .map_err(|_| VerifyError::SignatureMismatch)
+        verify_digest(
+            &cert.tbs_certificate.subject_public_key_info,
+            digest,
+            sig_bytes,
+        )
     }
 
     fn verify_self_signed(
         cert: &Certificate,
         digest: &Sha256Digest,
         sig_bytes: &[u8],
+        at: SystemTime,
     ) -> Result<VerifiedSigner, VerifyError> {
-        let public_key = Self::extract_rsa_public_key(cert)?;
-        Self::verify_package_sig(&public_key, digest, sig_bytes)?;
+        Self::check_validity(cert, at)?;
+        Self::verify_package_sig(cert, digest, sig_bytes)?;
         Ok(VerifiedSigner {
             cert_serial: Self::extract_serial(cert),
             common_name: Self::extract_common_name(cert),
@@ -182,17 +426,15 @@ impl RsaPssVerifier {
 
     fn verify_ca_chain(
         &self,
-        leaf_cert: &Certificate,
-        intermediate_cert: &Certificate,
+        chain: &[Certificate],
         digest: &Sha256Digest,
         sig_bytes: &[u8],
+        at: SystemTime,
     ) -> Result<VerifiedSigner, VerifyError> {
-        let root_cert = Certificate::from_pem(&self.root_ca_pem)
-            .map_err(|e| VerifyError::Der(e.to_string()))?;
-        Self::verify_cert_signed_by(intermediate_cert, &root_cert)?;
-        Self::verify_cert_signed_by(leaf_cert, intermediate_cert)?;
-        let leaf_key = Self::extract_rsa_public_key(leaf_cert)?;
-        Self::verify_package_sig(&leaf_key, digest, sig_bytes)?;
+        self.validate_path(chain, at)?;
+        let leaf_cert = &chain[0];
+        Self::require_key_usage(leaf_cert, KeyUsages::DigitalSignature, "digitalSignature")?;
+        Self::verify_package_sig(leaf_cert, digest, sig_bytes)?;
         Ok(VerifiedSigner {
             cert_serial: Self::extract_serial(leaf_cert),
             common_name: Self::extract_common_name(leaf_cert),
@@ -208,43 +450,69 @@ impl Default for RsaPssVerifier {
 }
 
 impl SignatureVerifier for RsaPssVerifier {
-    fn verify(
+    fn verify_at(
         &self,
         digest: &Sha256Digest,
         signature: &[u8],
         cert_chain_pem: &[String],
+        at: SystemTime,
     ) -> Result<VerifiedSigner, VerifyError> {
         match cert_chain_pem.len() {
             1 => {
                 let cert = Self::parse_cert(&cert_chain_pem[0])?;
-                Self::verify_self_signed(&cert, digest, signature)
+                Self::verify_self_signed(&cert, digest, signature, at)
             }
-            2 => {
-                let leaf = Self::parse_cert(&cert_chain_pem[0])?;
-                let intermediate = Self::parse_cert(&cert_chain_pem[1])?;
-                self.verify_ca_chain(&leaf, &intermediate, digest, signature)
+            2..=MAX_CHAIN_LEN => {
+                let chain = cert_chain_pem
+                    .iter()
+                    .map(|pem| Self::parse_cert(pem))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.verify_ca_chain(&chain, digest, signature, at)
             }
-            _ => Err(VerifyError::InvalidCertChain(format!(
-                "expected 1 or 2 certs, got {}",
-                cert_chain_pem.len()
-            ))),
+            len => Err(VerifyError::ChainLength {
+                len,
+                max: MAX_CHAIN_LEN,
+            }),
         }
     }
 
-    fn verify_with_namespace(
+    fn verify_timestamp(
         &self,
-        digest: &Sha256Digest,
+        timestamp: &SignedTimestamp,
         signature: &[u8],
-        cert_chain_pem: &[String],
-        namespace: &str,
-    ) -> Result<VerifiedSigner, VerifyError> {
-        let signer = self.verify(digest, signature, cert_chain_pem)?;
-        if signer.common_name != namespace {
-            return Err(VerifyError::CnMismatch {
-                expected: namespace.to_owned(),
-                got: signer.common_name.clone(),
-            });
+    ) -> Result<SystemTime, VerifyError> {
+        let token_sig = hex::decode(&timestamp.signature_hex)
+            .map_err(|e| VerifyError::InvalidTimestamp(format!("signature hex: {e}")))?;
+        let digest = Sha256Digest::of(timestamp.timestamp.as_bytes());
+        self.verify_registry_signed(&digest, &token_sig, &timestamp.signer_cert_pem)?;
+        let body: Timestamp = serde_json::from_str(&timestamp.timestamp)
+            .map_err(|e| VerifyError::InvalidTimestamp(e.to_string()))?;
+        if body.imprint != Timestamp::imprint_of(signature) {
+            return Err(VerifyError::InvalidTimestamp(
+                "timestamp covers a different signature".into(),
+            ));
         }
-        Ok(signer)
+        Ok(body.signed_at.into())
+    }
+
+    fn verify_tree_head(&self, tree_head: &SignedTreeHead) -> Result<TreeHead, VerifyError> {
+        let sig = hex::decode(&tree_head.signature_hex)
+            .map_err(|e| VerifyError::InvalidLogProof(format!("tree head signature hex: {e}")))?;
+        let digest = Sha256Digest::of(tree_head.tree_head.as_bytes());
+        self.verify_registry_signed(&digest, &sig, &tree_head.signer_cert_pem)?;
+        serde_json::from_str(&tree_head.tree_head)
+            .map_err(|e| VerifyError::InvalidLogProof(format!("tree head: {e}")))
+    }
+
+    fn verify_key_log(&self, key_log: &SignedKeyLog) -> Result<KeyLog, VerifyError> {
+        let sig = hex::decode(&key_log.signature_hex)
+            .map_err(|e| VerifyError::InvalidKeyLog(format!("signature hex: {e}")))?;
+        let digest = Sha256Digest::of(key_log.key_log.as_bytes());
+        self.verify_registry_signed(&digest, &sig, &key_log.signer_cert_pem)?;
+        let log: KeyLog = serde_json::from_str(&key_log.key_log)
+            .map_err(|e| VerifyError::InvalidKeyLog(e.to_string()))?;
+        log.verify_links()
+            .map_err(|e| VerifyError::InvalidKeyLog(e.to_string()))?;
+        Ok(log)
     }
 }
@@ -0,0 +1,99 @@
+use skreg_core::types::Sha256Digest;
+use skreg_crypto::error::VerifyError;
+use skreg_crypto::merkle::{
+    consistency_proof, inclusion_proof, leaf_hash, root_hash, verify_consistency, verify_inclusion,
+    MerkleTree,
+};
+
+fn leaves(n: u8) -> Vec<Sha256Digest> {
+    (0..n).map(|i| leaf_hash(&[i])).collect()
+}
+
+#[test]
+fn known_roots_match_rfc_6962() {
+    // The empty tree and a single leaf, from RFC 6962's definitions.
+    assert_eq!(
+        root_hash(&[]).unwrap().as_hex(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        leaf_hash(b"").as_hex(),
+        "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"
+    );
+}
+
+#[test]
+fn inclusion_proofs_verify_for_every_leaf() {
+    for n in 1..=17u8 {
+        let leaves = leaves(n);
+        let root = root_hash(&leaves).unwrap();
+        for (i, leaf) in leaves.iter().enumerate() {
+            let i = i as u64;
+            let path = inclusion_proof(&leaves, i).unwrap();
+            verify_inclusion(leaf, i, u64::from(n), &path, &root)
+                .unwrap_or_else(|e| panic!("leaf {i} of {n}: {e}"));
+            if n > 1 {
+                let wrong = leaf_hash(b"not logged");
+                assert!(verify_inclusion(&wrong, i, u64::from(n), &path, &root).is_err());
+            }
+        }
+        assert!(inclusion_proof(&leaves, u64::from(n)).is_err());
+    }
+}
+
+#[test]
+fn consistency_proofs_verify_for_every_prefix() {
+    for n in 1..=17u8 {
+        let leaves = leaves(n);
+        let root = root_hash(&leaves).unwrap();
+        for m in 0..=n {
+            let old_root = root_hash(&leaves[..usize::from(m)]).unwrap();
+            let proof = consistency_proof(&leaves, u64::from(m)).unwrap();
+            verify_consistency(u64::from(m), u64::from(n), &old_root, &root, &proof)
+                .unwrap_or_else(|e| panic!("{m} -> {n}: {e}"));
+        }
+    }
+}
+
+#[test]
+fn rewritten_history_is_inconsistent() {
+    let original = leaves(6);
+    let old_root = root_hash(&original[..4]).unwrap();
+
+    let mut rewritten = leaves(9);
+    rewritten[2] = leaf_hash(b"swapped artifact");
+    let new_root = root_hash(&rewritten).unwrap();
+    let proof = consistency_proof(&rewritten, 4).unwrap();
+    let result = verify_consistency(4, 9, &old_root, &new_root, &proof);
+    assert!(matches!(result, Err(VerifyError::InvalidLogProof(_))));
+
+    // A shrunken log can never be consistent with a larger earlier head.
+    let result = verify_consistency(6, 4, &root_hash(&original).unwrap(), &old_root, &[]);
+    assert!(matches!(result, Err(VerifyError::InvalidLogProof(_))));
+}
+
+#[test]
+fn grown_tree_serves_roots_and_proofs_for_every_earlier_size() {
+    let all = leaves(17);
+    let tree = MerkleTree::from_leaves(&all).unwrap();
+    assert_eq!(tree.len(), 17);
+    for n in 0..=17u8 {
+        let prefix = &all[..usize::from(n)];
+        let size = u64::from(n);
+        assert_eq!(tree.root(size).unwrap(), root_hash(prefix).unwrap());
+        for i in 0..size {
+            assert_eq!(
+                tree.inclusion_proof(i, size).unwrap(),
+                inclusion_proof(prefix, i).unwrap()
+            );
+        }
+        for m in 0..=size {
+            assert_eq!(
+                tree.consistency_proof(m, size).unwrap(),
+                consistency_proof(prefix, m).unwrap()
+            );
+        }
+    }
+    assert!(tree.root(18).is_err());
+    assert!(tree.consistency_proof(3, 18).is_err());
+}
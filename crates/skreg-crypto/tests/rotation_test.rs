@@ -0,0 +1,185 @@
+use ring::signature::Ed25519KeyPair;
+use skreg_core::keyset::{KeyChangeAction, KeyChangeRecord, KeyChangeToken};
+use skreg_core::rotation::{RotationRecord, RotationToken};
+use skreg_core::types::Sha256Digest;
+use skreg_crypto::error::VerifyError;
+use skreg_crypto::rotation::{
+    spki_fingerprint, verify_key_chain, verify_key_change, verify_rotation, verify_rotation_chain,
+};
+
+/// An Ed25519 key (PKCS#8 PEM) and a self-signed cert for it with CN `acme`.
+struct TestKey {
+    key_pem: String,
+    cert_pem: String,
+}
+
+impl TestKey {
+    fn generate() -> Self {
+        let key_pair = rcgen::KeyPair::generate(&rcgen::PKCS_ED25519).unwrap();
+        let key_pem = key_pair.serialize_pem();
+        let mut params = rcgen::CertificateParams::new(vec!["acme".to_owned()]);
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "acme");
+        params.key_pair = Some(key_pair);
+        params.alg = &rcgen::PKCS_ED25519;
+        let cert_pem = rcgen::Certificate::from_params(params)
+            .unwrap()
+            .serialize_pem()
+            .unwrap();
+        Self { key_pem, cert_pem }
+    }
+
+    fn fingerprint(&self) -> String {
+        spki_fingerprint(&self.cert_pem).unwrap()
+    }
+
+    fn sign(&self, digest: &Sha256Digest) -> String {
+        let der = rcgen::KeyPair::from_pem(&self.key_pem)
+            .unwrap()
+            .serialize_der();
+        let message = hex::decode(digest.as_hex()).unwrap();
+        hex::encode(Ed25519KeyPair::from_pkcs8(&der).unwrap().sign(&message))
+    }
+}
+
+/// A rotation of `acme` from `old` to `new`, signed by `old_signer` and
+/// `new_signer`.
+fn rotation(
+    old: &TestKey,
+    new: &TestKey,
+    old_signer: &TestKey,
+    new_signer: &TestKey,
+) -> RotationRecord {
+    let token = RotationToken {
+        namespace: "acme".to_owned(),
+        old_key_fingerprint: old.fingerprint(),
+        new_key_fingerprint: new.fingerprint(),
+        new_cert_chain_pem: vec![new.cert_pem.clone()],
+        nonce: hex::encode(rand::random::<[u8; 32]>()),
+        issued_at: "2026-01-01T00:00:00Z".to_owned(),
+        expires_at: "2026-01-01T00:05:00Z".to_owned(),
+    };
+    let digest = Sha256Digest::of(&token.signing_bytes().unwrap());
+    RotationRecord {
+        old_sig: old_signer.sign(&digest),
+        new_sig: new_signer.sign(&digest),
+        token,
+        old_cert_pem: old.cert_pem.clone(),
+        confirmed_at: "2026-01-01T01:00:00Z".to_owned(),
+    }
+}
+
+/// An addition of `added` to the key set of `acme`, signed by `signer`
+/// (whose certificate is `signer_cert`).
+fn addition(added: &TestKey, signer_cert: &TestKey, signer: &TestKey) -> KeyChangeRecord {
+    let token = KeyChangeToken {
+        namespace: "acme".to_owned(),
+        action: KeyChangeAction::Add,
+        fingerprint: added.fingerprint(),
+        cert_pem: Some(added.cert_pem.clone()),
+        label: Some("bob".to_owned()),
+        key_expires_at: None,
+        signer_fingerprint: signer_cert.fingerprint(),
+        nonce: hex::encode(rand::random::<[u8; 32]>()),
+        issued_at: "2026-01-01T00:00:00Z".to_owned(),
+        expires_at: "2026-01-01T00:05:00Z".to_owned(),
+    };
+    let digest = Sha256Digest::of(&token.signing_bytes().unwrap());
+    KeyChangeRecord {
+        signature: signer.sign(&digest),
+        token,
+        signer_cert_pem: signer_cert.cert_pem.clone(),
+        confirmed_at: "2026-01-01T01:00:00Z".to_owned(),
+    }
+}
+
+#[test]
+fn rotation_signed_by_both_keys_verifies() {
+    let (a, b) = (TestKey::generate(), TestKey::generate());
+    verify_rotation(&rotation(&a, &b, &a, &b)).unwrap();
+}
+
+#[test]
+fn rotation_not_signed_by_old_key_rejected() {
+    let (a, b, mallory) = (
+        TestKey::generate(),
+        TestKey::generate(),
+        TestKey::generate(),
+    );
+    let result = verify_rotation(&rotation(&a, &b, &mallory, &b));
+    assert!(matches!(result, Err(VerifyError::SignatureMismatch)));
+
+    // Substituting the attacker's cert as the old one breaks the fingerprint.
+    let mut record = rotation(&a, &b, &mallory, &b);
+    record.old_cert_pem.clone_from(&mallory.cert_pem);
+    let result = verify_rotation(&record);
+    assert!(matches!(result, Err(VerifyError::InvalidRotation(_))));
+}
+
+#[test]
+fn rotation_chain_follows_successive_keys() {
+    let (a, b, c) = (
+        TestKey::generate(),
+        TestKey::generate(),
+        TestKey::generate(),
+    );
+    let history = vec![rotation(&a, &b, &a, &b), rotation(&b, &c, &b, &c)];
+    verify_rotation_chain(&history, "acme", &a.fingerprint(), &c.fingerprint()).unwrap();
+    verify_rotation_chain(&history, "acme", &b.fingerprint(), &c.fingerprint()).unwrap();
+
+    let result = verify_rotation_chain(&history, "acme", &c.fingerprint(), &a.fingerprint());
+    assert!(matches!(result, Err(VerifyError::InvalidRotation(_))));
+    let result = verify_rotation_chain(&history, "other", &a.fingerprint(), &c.fingerprint());
+    assert!(matches!(result, Err(VerifyError::InvalidRotation(_))));
+}
+
+#[test]
+fn key_chain_follows_additions_and_rotations() {
+    let (a, b, c, d) = (
+        TestKey::generate(),
+        TestKey::generate(),
+        TestKey::generate(),
+        TestKey::generate(),
+    );
+    // a adds b, b adds c, then c rotates to d.
+    let changes = vec![addition(&b, &a, &a), addition(&c, &b, &b)];
+    let rotations = vec![rotation(&c, &d, &c, &d)];
+    let trusted = vec![a.fingerprint()];
+    for key in [&b, &c, &d] {
+        verify_key_chain(&rotations, &changes, "acme", &trusted, &key.fingerprint()).unwrap();
+    }
+
+    // Nothing leads back from d to a, and other namespaces' records do not count.
+    let result = verify_key_chain(
+        &rotations,
+        &changes,
+        "acme",
+        &[d.fingerprint()],
+        &a.fingerprint(),
+    );
+    assert!(matches!(result, Err(VerifyError::InvalidRotation(_))));
+    let result = verify_key_chain(&rotations, &changes, "other", &trusted, &b.fingerprint());
+    assert!(matches!(result, Err(VerifyError::InvalidRotation(_))));
+}
+
+#[test]
+fn key_addition_not_signed_by_named_key_rejected() {
+    let (a, b, mallory) = (
+        TestKey::generate(),
+        TestKey::generate(),
+        TestKey::generate(),
+    );
+    verify_key_change(&addition(&b, &a, &a)).unwrap();
+
+    let result = verify_key_change(&addition(&b, &a, &mallory));
+    assert!(matches!(result, Err(VerifyError::SignatureMismatch)));
+
+    let mut record = addition(&b, &a, &mallory);
+    record.signer_cert_pem.clone_from(&mallory.cert_pem);
+    let result = verify_key_change(&record);
+    assert!(matches!(result, Err(VerifyError::InvalidRotation(_))));
+    let result = verify_key_chain(&[], &[record], "acme", &[a.fingerprint()], &b.fingerprint());
+    assert!(matches!(result, Err(VerifyError::InvalidRotation(_))));
+}
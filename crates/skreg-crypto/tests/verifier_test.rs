@@ -23,7 +23,7 @@ use rsa::signature::hazmat::RandomizedPrehashSigner;
 use rsa::signature::SignatureEncoding;
 use sha2::Sha256;
 use skreg_core::types::Sha256Digest;
-use skreg_crypto::verifier::{RsaPssVerifier, SignatureVerifier};
+use skreg_crypto::verifier::{RsaPssVerifier, SignatureVerifier, MAX_CHAIN_LEN};
 
 /// Generate a self-signed cert with RSA-2048 (PKCS#1 signing), returning (ca_cert_pem, key_pem).
 ///
@@ -109,26 +109,21 @@ fn pss_verifier_rejects_empty_chain() {
     let verifier = RsaPssVerifier::new_with_root_pem(ca_pem.as_bytes());
     let result = verifier.verify(&digest, &signature, &[]);
     assert!(
-        matches!(
-            result,
-            Err(skreg_crypto::error::VerifyError::InvalidCertChain(_))
-        ),
-        "expected InvalidCertChain, got {result:?}"
+        matches!(result, Err(VerifyError::ChainLength { len: 0, .. })),
+        "expected ChainLength, got {result:?}"
     );
 }
 
 #[test]
-fn pss_verifier_rejects_chain_length_3() {
+fn pss_verifier_rejects_chain_longer_than_max() {
     let (ca_pem, _) = make_test_ca("test-ca");
     let digest = Sha256Digest::from_hex(&"d".repeat(64)).unwrap();
     let verifier = RsaPssVerifier::new_with_root_pem(ca_pem.as_bytes());
-    let result = verifier.verify(&digest, &[], &[ca_pem.clone(), ca_pem.clone(), ca_pem]);
+    let chain = vec![ca_pem; MAX_CHAIN_LEN + 1];
+    let result = verifier.verify(&digest, &[], &chain);
     assert!(
-        matches!(
-            result,
-            Err(skreg_crypto::error::VerifyError::InvalidCertChain(_))
-        ),
-        "expected InvalidCertChain, got {result:?}"
+        matches!(result, Err(VerifyError::ChainLength { len, .. }) if len == MAX_CHAIN_LEN + 1),
+        "expected ChainLength, got {result:?}"
     );
 }
 
@@ -196,3 +191,737 @@ fn pss_verifier_self_signed_cn_mismatch_rejects() {
         "expected CnMismatch, got {result:?}"
     );
 }
+
+#[test]
+fn registry_signed_accepts_root_signer() {
+    let (root_pem, key_pem) = make_test_ca("skreg-root");
+    let digest_hex = "1".repeat(64);
+    let signature = pss_sign(&key_pem, &digest_hex);
+    let digest = Sha256Digest::from_hex(&digest_hex).unwrap();
+
+    let verifier = RsaPssVerifier::new_with_root_pem(root_pem.as_bytes());
+    let signer = verifier
+        .verify_registry_signed(&digest, &signature, &root_pem)
+        .expect("root-signed document should verify");
+    assert_eq!(signer.common_name, "skreg-root");
+}
+
+#[test]
+fn registry_signed_rejects_signer_outside_root() {
+    let (root_pem, _) = make_test_ca("skreg-root");
+    let (other_pem, other_key_pem) = make_test_ca("impostor");
+    let digest_hex = "2".repeat(64);
+    let signature = pss_sign(&other_key_pem, &digest_hex);
+    let digest = Sha256Digest::from_hex(&digest_hex).unwrap();
+
+    let verifier = RsaPssVerifier::new_with_root_pem(root_pem.as_bytes());
+    let result = verifier.verify_registry_signed(&digest, &signature, &other_pem);
+    assert!(
+        matches!(result, Err(VerifyError::UntrustedRoot { .. })),
+        "expected UntrustedRoot, got {result:?}"
+    );
+}
+
+// ---- Manifest file-list signatures ----
+
+use skreg_core::manifest::{FileEntry, Manifest};
+use skreg_core::types::{Namespace, PackageName};
+use skreg_crypto::package::{verify_files, verify_manifest, SigningScheme};
+
+fn signed_manifest(key_pem: &str, cert_pem: &str, files: Vec<FileEntry>) -> Manifest {
+    let mut manifest = Manifest {
+        namespace: Namespace::new("acme").unwrap(),
+        name: PackageName::new("helper").unwrap(),
+        version: semver::Version::new(1, 0, 0),
+        description: "a test skill that is long enough".to_owned(),
+        category: None,
+        sha256: Sha256Digest::from_hex(&"0".repeat(64)).unwrap(),
+        cert_chain_pem: vec![cert_pem.to_owned()],
+        publisher_sig_hex: None,
+        files,
+    };
+    manifest.sha256 = manifest.payload_digest().unwrap();
+    let signing = manifest.signing_digest().unwrap();
+    manifest.publisher_sig_hex = Some(hex::encode(pss_sign(key_pem, signing.as_hex())));
+    manifest
+}
+
+fn file_entry(path: &str, content: &[u8]) -> FileEntry {
+    FileEntry {
+        path: path.to_owned(),
+        size: content.len() as u64,
+        sha256: Sha256Digest::of(content),
+    }
+}
+
+#[test]
+fn manifest_signature_covers_file_list() {
+    let (cert_pem, key_pem) = make_test_ca("acme");
+    let verifier = RsaPssVerifier::new_with_root_pem(cert_pem.as_bytes());
+    let mut manifest = signed_manifest(
+        &key_pem,
+        &cert_pem,
+        vec![file_entry("SKILL.md", b"# skill")],
+    );
+    assert!(verify_manifest(
+        &verifier,
+        &manifest,
+        SigningScheme::FileList,
+        Some("acme"),
+        None
+    )
+    .is_ok());
+
+    manifest.files[0].sha256 = Sha256Digest::of(b"# other");
+    assert!(matches!(
+        verify_manifest(
+            &verifier,
+            &manifest,
+            SigningScheme::FileList,
+            Some("acme"),
+            None
+        ),
+        Err(VerifyError::PayloadMismatch)
+    ));
+}
+
+#[test]
+fn stripped_file_list_does_not_verify_as_legacy() {
+    let (cert_pem, key_pem) = make_test_ca("acme");
+    let verifier = RsaPssVerifier::new_with_root_pem(cert_pem.as_bytes());
+    let mut manifest = signed_manifest(
+        &key_pem,
+        &cert_pem,
+        vec![file_entry("SKILL.md", b"# skill")],
+    );
+    manifest.files.clear();
+    assert!(matches!(
+        verify_manifest(
+            &verifier,
+            &manifest,
+            SigningScheme::FileList,
+            Some("acme"),
+            None
+        ),
+        Err(VerifyError::MissingFileList)
+    ));
+    // Even where the registry allows the legacy scheme, the signature is
+    // over the prefixed digest, not `sha256`.
+    assert!(matches!(
+        verify_manifest(
+            &verifier,
+            &manifest,
+            SigningScheme::Legacy,
+            Some("acme"),
+            None
+        ),
+        Err(VerifyError::SignatureMismatch)
+    ));
+}
+
+#[test]
+fn legacy_manifest_verifies_only_under_the_legacy_scheme() {
+    let (cert_pem, key_pem) = make_test_ca("acme");
+    let verifier = RsaPssVerifier::new_with_root_pem(cert_pem.as_bytes());
+    let mut manifest = signed_manifest(&key_pem, &cert_pem, vec![file_entry("SKILL.md", b"#")]);
+    manifest.files.clear();
+    manifest.sha256 = Sha256Digest::of(b"unsigned tarball");
+    manifest.publisher_sig_hex = Some(hex::encode(pss_sign(&key_pem, manifest.sha256.as_hex())));
+    assert!(verify_manifest(
+        &verifier,
+        &manifest,
+        SigningScheme::Legacy,
+        Some("acme"),
+        None
+    )
+    .is_ok());
+    assert!(matches!(
+        verify_manifest(
+            &verifier,
+            &manifest,
+            SigningScheme::FileList,
+            Some("acme"),
+            None
+        ),
+        Err(VerifyError::MissingFileList)
+    ));
+}
+
+#[test]
+fn verify_files_detects_altered_and_extra_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("scripts")).unwrap();
+    std::fs::write(dir.path().join("SKILL.md"), "# skill").unwrap();
+    std::fs::write(dir.path().join("scripts/run.sh"), "echo hi").unwrap();
+    std::fs::write(dir.path().join("manifest.json"), "{}").unwrap();
+    let files = vec![
+        file_entry("SKILL.md", b"# skill"),
+        file_entry("scripts/run.sh", b"echo hi"),
+    ];
+    assert!(verify_files(dir.path(), &files).is_ok());
+
+    std::fs::write(dir.path().join("scripts/run.sh"), "echo hacked").unwrap();
+    assert!(matches!(
+        verify_files(dir.path(), &files),
+        Err(VerifyError::FileMismatch { ref path, .. }) if path == "scripts/run.sh"
+    ));
+
+    std::fs::write(dir.path().join("scripts/run.sh"), "echo hi").unwrap();
+    std::fs::write(dir.path().join("scripts/extra.sh"), "x").unwrap();
+    assert!(matches!(
+        verify_files(dir.path(), &files),
+        Err(VerifyError::FileMismatch { ref path, .. }) if path == "scripts/extra.sh"
+    ));
+}
+
+// ---- Certification paths ----
+
+use rcgen::{BasicConstraints, IsCa, KeyUsagePurpose};
+
+/// Cached RSA keys; generating one per certificate makes these tests slow.
+fn test_key(slot: usize) -> String {
+    static KEYS: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+    KEYS.get_or_init(|| {
+        (0..2)
+            .map(|_| {
+                rsa::RsaPrivateKey::new(&mut rand::thread_rng(), 2048)
+                    .unwrap()
+                    .to_pkcs8_pem(rsa::pkcs8::LineEnding::LF)
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    })[slot]
+        .clone()
+}
+
+/// A certificate plus its PEM, issued by `issuer` (self-signed if `None`).
+struct TestCert {
+    cert: rcgen::Certificate,
+    pem: String,
+}
+
+fn issue(
+    cn: &str,
+    is_ca: IsCa,
+    usages: Vec<KeyUsagePurpose>,
+    issuer: Option<&TestCert>,
+    customize: impl FnOnce(&mut rcgen::CertificateParams),
+) -> TestCert {
+    let key_pair =
+        rcgen::KeyPair::from_pem_and_sign_algo(&test_key(0), &rcgen::PKCS_RSA_SHA256).unwrap();
+    let mut params = rcgen::CertificateParams::new(vec![]);
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, cn);
+    params.is_ca = is_ca;
+    params.key_usages = usages;
+    params.key_pair = Some(key_pair);
+    params.alg = &rcgen::PKCS_RSA_SHA256;
+    customize(&mut params);
+    let cert = rcgen::Certificate::from_params(params).unwrap();
+    let pem = match issuer {
+        Some(issuer) => cert.serialize_pem_with_signer(&issuer.cert).unwrap(),
+        None => cert.serialize_pem().unwrap(),
+    };
+    TestCert { cert, pem }
+}
+
+fn ca(cn: &str, path_len: Option<u8>, issuer: Option<&TestCert>) -> TestCert {
+    let constraints = path_len.map_or(
+        BasicConstraints::Unconstrained,
+        BasicConstraints::Constrained,
+    );
+    issue(
+        cn,
+        IsCa::Ca(constraints),
+        vec![KeyUsagePurpose::KeyCertSign],
+        issuer,
+        |_| {},
+    )
+}
+
+fn leaf(issuer: &TestCert) -> TestCert {
+    issue(
+        "acme",
+        IsCa::ExplicitNoCa,
+        vec![KeyUsagePurpose::DigitalSignature],
+        Some(issuer),
+        |_| {},
+    )
+}
+
+/// Verify a signature by the leaf over a fixed digest.
+fn verify_chain(
+    roots: &[&TestCert],
+    chain: &[&TestCert],
+) -> Result<skreg_crypto::verifier::VerifiedSigner, VerifyError> {
+    let bundle: String = roots.iter().map(|r| r.pem.as_str()).collect();
+    let digest_hex = "3".repeat(64);
+    let signature = pss_sign(&test_key(0), &digest_hex);
+    let digest = Sha256Digest::from_hex(&digest_hex).unwrap();
+    let chain: Vec<String> = chain.iter().map(|c| c.pem.clone()).collect();
+    RsaPssVerifier::new_with_root_pem(bundle.as_bytes())
+        .verify_with_namespace(&digest, &signature, &chain, "acme")
+}
+
+#[test]
+fn multi_tier_chain_verifies() {
+    let root = ca("root", None, None);
+    let policy = ca("policy-ca", Some(1), Some(&root));
+    let publisher = ca("publisher-ca", Some(0), Some(&policy));
+    let leaf = leaf(&publisher);
+
+    let signer = verify_chain(&[&root], &[&leaf, &publisher, &policy]).unwrap();
+    assert!(signer.ca_verified);
+    assert_eq!(signer.common_name, "acme");
+}
+
+#[test]
+fn chain_may_end_with_any_root_in_bundle() {
+    let old_root = ca("old-root", None, None);
+    let root = ca("root", None, None);
+    let publisher = ca("publisher-ca", Some(0), Some(&root));
+    let leaf = leaf(&publisher);
+
+    assert!(verify_chain(&[&old_root, &root], &[&leaf, &publisher]).is_ok());
+    assert!(verify_chain(&[&old_root, &root], &[&leaf, &publisher, &root]).is_ok());
+}
+
+#[test]
+fn chain_to_root_outside_bundle_rejected() {
+    let root = ca("root", None, None);
+    // Same subject, different key: the names chain but the signature does not.
+    let impostor = issue(
+        "root",
+        IsCa::Ca(BasicConstraints::Unconstrained),
+        vec![KeyUsagePurpose::KeyCertSign],
+        None,
+        |p| {
+            p.key_pair = Some(
+                rcgen::KeyPair::from_pem_and_sign_algo(&test_key(1), &rcgen::PKCS_RSA_SHA256)
+                    .unwrap(),
+            );
+        },
+    );
+    let publisher = ca("publisher-ca", Some(0), Some(&root));
+    let leaf = leaf(&publisher);
+
+    let result = verify_chain(&[&impostor], &[&leaf, &publisher]);
+    assert!(
+        matches!(result, Err(VerifyError::UntrustedRoot { ref subject }) if subject == "publisher-ca"),
+        "expected UntrustedRoot, got {result:?}"
+    );
+}
+
+#[test]
+fn intermediate_without_ca_flag_rejected() {
+    let root = ca("root", None, None);
+    let not_ca = issue(
+        "not-a-ca",
+        IsCa::ExplicitNoCa,
+        vec![KeyUsagePurpose::KeyCertSign],
+        Some(&root),
+        |_| {},
+    );
+    let leaf = leaf(&not_ca);
+
+    let result = verify_chain(&[&root], &[&leaf, &not_ca]);
+    assert!(
+        matches!(result, Err(VerifyError::NotCa { ref subject }) if subject == "not-a-ca"),
+        "expected NotCa, got {result:?}"
+    );
+}
+
+#[test]
+fn path_len_constraint_enforced() {
+    let root = ca("root", None, None);
+    let policy = ca("policy-ca", Some(0), Some(&root));
+    let publisher = ca("publisher-ca", Some(0), Some(&policy));
+    let leaf = leaf(&publisher);
+
+    let result = verify_chain(&[&root], &[&leaf, &publisher, &policy]);
+    assert!(
+        matches!(
+            result,
+            Err(VerifyError::PathLenExceeded { path_len: 0, .. })
+        ),
+        "expected PathLenExceeded, got {result:?}"
+    );
+}
+
+#[test]
+fn key_usage_enforced_for_issuers_and_leaf() {
+    let root = ca("root", None, None);
+    let signing_only = issue(
+        "publisher-ca",
+        IsCa::Ca(BasicConstraints::Constrained(0)),
+        vec![KeyUsagePurpose::DigitalSignature],
+        Some(&root),
+        |_| {},
+    );
+    let result = verify_chain(&[&root], &[&leaf(&signing_only), &signing_only]);
+    assert!(
+        matches!(
+            result,
+            Err(VerifyError::KeyUsageNotPermitted {
+                usage: "keyCertSign",
+                ..
+            })
+        ),
+        "expected KeyUsageNotPermitted, got {result:?}"
+    );
+
+    let publisher = ca("publisher-ca", Some(0), Some(&root));
+    let cert_signer = issue(
+        "acme",
+        IsCa::ExplicitNoCa,
+        vec![KeyUsagePurpose::KeyCertSign],
+        Some(&publisher),
+        |_| {},
+    );
+    let result = verify_chain(&[&root], &[&cert_signer, &publisher]);
+    assert!(
+        matches!(
+            result,
+            Err(VerifyError::KeyUsageNotPermitted {
+                usage: "digitalSignature",
+                ..
+            })
+        ),
+        "expected KeyUsageNotPermitted, got {result:?}"
+    );
+}
+
+#[test]
+fn expired_intermediate_rejected() {
+    let root = ca("root", None, None);
+    let expired = issue(
+        "publisher-ca",
+        IsCa::Ca(BasicConstraints::Constrained(0)),
+        vec![KeyUsagePurpose::KeyCertSign],
+        Some(&root),
+        |p| {
+            p.not_before = rcgen::date_time_ymd(2000, 1, 1);
+            p.not_after = rcgen::date_time_ymd(2001, 1, 1);
+        },
+    );
+    let result = verify_chain(&[&root], &[&leaf(&expired), &expired]);
+    assert!(
+        matches!(result, Err(VerifyError::CertExpired(_))),
+        "expected CertExpired, got {result:?}"
+    );
+}
+
+// ---- Validity and timestamps ----
+
+use skreg_core::timestamp::{SignedTimestamp, Timestamp};
+
+/// A self-signed publisher cert valid only during 2020.
+fn cert_valid_in_2020() -> TestCert {
+    issue("acme", IsCa::ExplicitNoCa, vec![], None, |p| {
+        p.not_before = rcgen::date_time_ymd(2020, 1, 1);
+        p.not_after = rcgen::date_time_ymd(2021, 1, 1);
+    })
+}
+
+/// A timestamp signed by `root` over the manifest's publisher signature.
+fn timestamp_for(root: &TestCert, manifest: &Manifest, signed_at: &str) -> SignedTimestamp {
+    let signature = hex::decode(manifest.publisher_sig_hex.as_deref().unwrap()).unwrap();
+    let body = serde_json::to_string(&Timestamp {
+        imprint: Timestamp::imprint_of(&signature),
+        signed_at: signed_at.parse().unwrap(),
+    })
+    .unwrap();
+    let digest = Sha256Digest::of(body.as_bytes());
+    SignedTimestamp {
+        signature_hex: hex::encode(pss_sign(&test_key(0), digest.as_hex())),
+        timestamp: body,
+        signer_cert_pem: root.pem.clone(),
+    }
+}
+
+#[test]
+fn expired_cert_of_current_version_rejected_without_timestamp() {
+    let cert = cert_valid_in_2020();
+    let verifier = RsaPssVerifier::new_with_root_pem(cert.pem.as_bytes());
+    let manifest = signed_manifest(&test_key(0), &cert.pem, vec![file_entry("SKILL.md", b"#")]);
+
+    let result = verify_manifest(
+        &verifier,
+        &manifest,
+        SigningScheme::FileList,
+        Some("acme"),
+        None,
+    );
+    assert!(
+        matches!(result, Err(VerifyError::CertExpired(_))),
+        "expected CertExpired, got {result:?}"
+    );
+}
+
+#[test]
+fn expired_cert_of_legacy_version_accepted_without_timestamp() {
+    let cert = cert_valid_in_2020();
+    let verifier = RsaPssVerifier::new_with_root_pem(cert.pem.as_bytes());
+    let mut manifest = signed_manifest(&test_key(0), &cert.pem, vec![file_entry("SKILL.md", b"#")]);
+    manifest.publisher_sig_hex = Some(hex::encode(pss_sign(
+        &test_key(0),
+        manifest.sha256.as_hex(),
+    )));
+
+    let result = verify_manifest(
+        &verifier,
+        &manifest,
+        SigningScheme::Legacy,
+        Some("acme"),
+        None,
+    );
+    assert!(result.is_ok(), "expected Ok, got {result:?}");
+}
+
+#[test]
+fn timestamp_judges_cert_at_signing_time() {
+    let root = ca("root", None, None);
+    let cert = cert_valid_in_2020();
+    let verifier = RsaPssVerifier::new_with_root_pem(root.pem.as_bytes());
+    let manifest = signed_manifest(&test_key(0), &cert.pem, vec![file_entry("SKILL.md", b"#")]);
+
+    let during = timestamp_for(&root, &manifest, "2020-06-01T00:00:00Z");
+    assert!(verify_manifest(
+        &verifier,
+        &manifest,
+        SigningScheme::FileList,
+        Some("acme"),
+        Some(&during)
+    )
+    .is_ok());
+
+    let after = timestamp_for(&root, &manifest, "2022-06-01T00:00:00Z");
+    assert!(matches!(
+        verify_manifest(
+            &verifier,
+            &manifest,
+            SigningScheme::FileList,
+            Some("acme"),
+            Some(&after)
+        ),
+        Err(VerifyError::CertExpired(_))
+    ));
+}
+
+#[test]
+fn timestamp_must_cover_the_signature_and_be_registry_signed() {
+    let root = ca("root", None, None);
+    let cert = cert_valid_in_2020();
+    let verifier = RsaPssVerifier::new_with_root_pem(root.pem.as_bytes());
+    let manifest = signed_manifest(&test_key(0), &cert.pem, vec![file_entry("SKILL.md", b"#")]);
+    let other = signed_manifest(&test_key(0), &cert.pem, vec![file_entry("SKILL.md", b"!")]);
+
+    let for_other = timestamp_for(&root, &other, "2020-06-01T00:00:00Z");
+    assert!(matches!(
+        verify_manifest(
+            &verifier,
+            &manifest,
+            SigningScheme::FileList,
+            Some("acme"),
+            Some(&for_other)
+        ),
+        Err(VerifyError::InvalidTimestamp(_))
+    ));
+
+    // Signed by the publisher's own key rather than the registry.
+    let mut self_issued = timestamp_for(&root, &manifest, "2020-06-01T00:00:00Z");
+    self_issued.signer_cert_pem = cert.pem.clone();
+    assert!(verify_manifest(
+        &verifier,
+        &manifest,
+        SigningScheme::FileList,
+        Some("acme"),
+        Some(&self_issued)
+    )
+    .is_err());
+}
+
+// ---- Key logs ----
+
+use skreg_core::key_log::{KeyEvent, KeyLog, SignedKeyLog};
+
+/// `log` serialized and signed by `root`.
+fn signed_key_log(root: &TestCert, log: &KeyLog) -> SignedKeyLog {
+    let body = serde_json::to_string(log).unwrap();
+    let digest = Sha256Digest::of(body.as_bytes());
+    SignedKeyLog {
+        signature_hex: hex::encode(pss_sign(&test_key(0), digest.as_hex())),
+        key_log: body,
+        signer_cert_pem: root.pem.clone(),
+    }
+}
+
+fn two_entry_log() -> KeyLog {
+    KeyLog::build(
+        "acme",
+        [
+            (
+                "2026-01-01T00:00:00Z".parse().unwrap(),
+                KeyEvent::Pinned {
+                    fingerprint: "aaa".into(),
+                },
+            ),
+            (
+                "2026-02-01T00:00:00Z".parse().unwrap(),
+                KeyEvent::Rotated {
+                    old_fingerprint: "aaa".into(),
+                    new_fingerprint: "bbb".into(),
+                },
+            ),
+        ],
+    )
+    .unwrap()
+}
+
+#[test]
+fn registry_signed_key_log_verifies() {
+    let root = ca("root", None, None);
+    let verifier = RsaPssVerifier::new_with_root_pem(root.pem.as_bytes());
+    let log = two_entry_log();
+    assert_eq!(
+        verifier
+            .verify_key_log(&signed_key_log(&root, &log))
+            .unwrap(),
+        log
+    );
+}
+
+#[test]
+fn key_log_must_link_and_be_registry_signed() {
+    let root = ca("root", None, None);
+    let verifier = RsaPssVerifier::new_with_root_pem(root.pem.as_bytes());
+
+    let mut unlinked = two_entry_log();
+    unlinked.entries[0].event = KeyEvent::Pinned {
+        fingerprint: "evil".into(),
+    };
+    assert!(matches!(
+        verifier.verify_key_log(&signed_key_log(&root, &unlinked)),
+        Err(VerifyError::InvalidKeyLog(_))
+    ));
+
+    let mut forged = signed_key_log(&root, &two_entry_log());
+    forged.key_log = serde_json::to_string(&unlinked).unwrap();
+    assert!(verifier.verify_key_log(&forged).is_err());
+}
+
+// ---- Key algorithms ----
+
+/// A fresh key of `alg` as PKCS#8 PEM.
+fn generate_key(alg: &'static rcgen::SignatureAlgorithm) -> String {
+    rcgen::KeyPair::generate(alg).unwrap().serialize_pem()
+}
+
+/// Use `key_pem` (of `alg`) as the certificate's key.
+fn with_key(
+    key_pem: &str,
+    alg: &'static rcgen::SignatureAlgorithm,
+) -> impl FnOnce(&mut rcgen::CertificateParams) {
+    let key_pair = rcgen::KeyPair::from_pem(key_pem).unwrap();
+    move |params| {
+        params.key_pair = Some(key_pair);
+        params.alg = alg;
+    }
+}
+
+/// Sign a digest with an Ed25519 or ECDSA P-256 key as skreg does: the 32
+/// digest bytes are the message.
+fn ring_sign(key_pem: &str, digest: &Sha256Digest) -> Vec<u8> {
+    use ring::signature::{EcdsaKeyPair, Ed25519KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    let message = hex::decode(digest.as_hex()).unwrap();
+    let key_pair = rcgen::KeyPair::from_pem(key_pem).unwrap();
+    let pkcs8 = key_pair.serialize_der();
+    if key_pair.algorithm() == &rcgen::PKCS_ED25519 {
+        Ed25519KeyPair::from_pkcs8(&pkcs8)
+            .unwrap()
+            .sign(&message)
+            .as_ref()
+            .to_vec()
+    } else {
+        let rng = ring::rand::SystemRandom::new();
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &pkcs8, &rng)
+            .unwrap()
+            .sign(&rng, &message)
+            .unwrap()
+            .as_ref()
+            .to_vec()
+    }
+}
+
+#[test]
+fn ed25519_and_p256_self_signed_sigs_verify() {
+    let verifier = RsaPssVerifier::new();
+    let digest = Sha256Digest::of(b"payload");
+    let other = Sha256Digest::of(b"other payload");
+    for alg in [&rcgen::PKCS_ED25519, &rcgen::PKCS_ECDSA_P256_SHA256] {
+        let key_pem = generate_key(alg);
+        let cert = issue("acme", IsCa::NoCa, vec![], None, with_key(&key_pem, alg));
+        let chain = vec![cert.pem.clone()];
+
+        let signature = ring_sign(&key_pem, &digest);
+        let signer = verifier
+            .verify_with_namespace(&digest, &signature, &chain, "acme")
+            .unwrap();
+        assert!(!signer.ca_verified);
+
+        let wrong = ring_sign(&key_pem, &other);
+        assert!(matches!(
+            verifier.verify(&digest, &wrong, &chain),
+            Err(VerifyError::SignatureMismatch)
+        ));
+    }
+}
+
+#[test]
+fn chain_mixing_key_algorithms_verifies() {
+    let root = ca("root", None, None);
+    let ca_key = generate_key(&rcgen::PKCS_ECDSA_P256_SHA256);
+    let publisher = issue(
+        "publisher-ca",
+        IsCa::Ca(BasicConstraints::Constrained(0)),
+        vec![KeyUsagePurpose::KeyCertSign],
+        Some(&root),
+        with_key(&ca_key, &rcgen::PKCS_ECDSA_P256_SHA256),
+    );
+    let leaf_key = generate_key(&rcgen::PKCS_ED25519);
+    let leaf = issue(
+        "acme",
+        IsCa::ExplicitNoCa,
+        vec![KeyUsagePurpose::DigitalSignature],
+        Some(&publisher),
+        with_key(&leaf_key, &rcgen::PKCS_ED25519),
+    );
+
+    let digest = Sha256Digest::of(b"payload");
+    let signature = ring_sign(&leaf_key, &digest);
+    let signer = RsaPssVerifier::new_with_root_pem(root.pem.as_bytes())
+        .verify_with_namespace(
+            &digest,
+            &signature,
+            &[leaf.pem.clone(), publisher.pem.clone()],
+            "acme",
+        )
+        .unwrap();
+    assert!(signer.ca_verified);
+}
+
+#[test]
+fn unsupported_key_algorithm_rejected() {
+    let alg = &rcgen::PKCS_ECDSA_P384_SHA384;
+    let key_pem = generate_key(alg);
+    let cert = issue("acme", IsCa::NoCa, vec![], None, with_key(&key_pem, alg));
+    let digest = Sha256Digest::of(b"payload");
+    assert!(matches!(
+        RsaPssVerifier::new().verify(&digest, &[0; 96], &[cert.pem]),
+        Err(VerifyError::UnsupportedAlgorithm(_))
+    ));
+}
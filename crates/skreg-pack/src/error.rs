@@ -20,4 +20,68 @@ pub enum PackError {
     /// A tar entry path contains '..' or an absolute path component.
     #[error("path traversal attempt in package entry: '{0}'")]
     PathTraversal(String),
+    /// A tar entry is a hard link, device node, FIFO or other non-regular type.
+    #[error("unsupported entry type ({kind}) in package: '{path}'")]
+    UnsupportedEntryType {
+        /// Entry path.
+        path: String,
+        /// Human-readable entry type.
+        kind: String,
+    },
+    /// The same path appears more than once.
+    #[error("duplicate entry in package: '{0}'")]
+    DuplicateEntry(String),
+    /// Two paths differ only by case and would collide on case-insensitive filesystems.
+    #[error("entry '{path}' collides with '{existing}' on case-insensitive filesystems")]
+    CaseCollision {
+        /// The later entry.
+        path: String,
+        /// The entry it collides with.
+        existing: String,
+    },
+    /// The archive holds more files than allowed.
+    #[error("package contains more than {max} files")]
+    TooManyFiles {
+        /// Maximum allowed count.
+        max: usize,
+    },
+    /// A single entry exceeds the per-file size limit.
+    #[error("file '{path}' is {size} bytes, exceeds limit of {max} bytes")]
+    FileTooLarge {
+        /// Entry path.
+        path: String,
+        /// Declared size.
+        size: u64,
+        /// Maximum allowed size.
+        max: u64,
+    },
+    /// The unpacked files together exceed the package size limit.
+    #[error("unpacked package exceeds {max} bytes")]
+    PackageTooLarge {
+        /// Maximum allowed total size.
+        max: u64,
+    },
+    /// The decompressed tar stream is larger than any valid package could be.
+    #[error("decompressed archive exceeds {max} bytes")]
+    DecompressedTooLarge {
+        /// Maximum allowed decompressed size.
+        max: u64,
+    },
+    /// The archive is neither gzip nor Zstandard.
+    #[error("unrecognised archive format (expected gzip or zstd)")]
+    UnknownFormat,
+    /// Re-packing the archive's own contents does not reproduce it.
+    #[error("archive is not reproducible: sha256 {actual}, rebuilt from its contents {rebuilt}")]
+    NotReproducible {
+        /// Digest of the archive as given.
+        actual: String,
+        /// Digest of the archive rebuilt from its unpacked contents.
+        rebuilt: String,
+    },
+    /// The archive's entries differ from those packing its contents writes.
+    #[error("archive entry {path} differs from the entry rebuilt from its contents")]
+    ContentsDiffer {
+        /// First path that is missing, extra or different.
+        path: String,
+    },
 }
@@ -0,0 +1,295 @@
+//! Compression formats of `.skill` archives.
+//!
+//! A `.skill` archive is a tar stream wrapped in one of the formats below and
+//! identified by its leading magic bytes, never by file name. Format 1 (gzip)
+//! is understood by every client; format 2 (Zstandard) is smaller and faster
+//! to unpack, and the registry serves it only to clients that ask for it in
+//! `Accept`.
+
+use std::fmt;
+use std::io::{self, Cursor, Read, Write};
+use std::str::FromStr;
+
+use flate2::write::GzEncoder;
+use flate2::{Compression, GzBuilder};
+
+use crate::error::PackError;
+
+/// gzip member magic.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Zstandard frame magic.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// gzip OS byte meaning "unknown", so the host platform does not leak into
+/// the archive.
+const GZIP_OS_UNKNOWN: u8 = 255;
+
+/// Zstandard level used for packing; fixed so output is reproducible.
+const ZSTD_LEVEL: i32 = 19;
+
+/// Largest Zstandard window accepted when unpacking (8 MiB), bounding
+/// decoder memory independently of what the frame header requests.
+const ZSTD_WINDOW_LOG_MAX: u32 = 23;
+
+/// Compression wrapped around the tar stream of a `.skill` archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArchiveFormat {
+    /// Format 1: gzip.
+    #[default]
+    Gzip = 1,
+    /// Format 2: Zstandard.
+    Zstd = 2,
+}
+
+impl ArchiveFormat {
+    /// Identify the format of an archive from its first bytes.
+    #[must_use]
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&ZSTD_MAGIC) {
+            Some(Self::Zstd)
+        } else if bytes.starts_with(&GZIP_MAGIC) {
+            Some(Self::Gzip)
+        } else {
+            None
+        }
+    }
+
+    /// HTTP media type for archives in this format.
+    #[must_use]
+    pub const fn media_type(self) -> &'static str {
+        match self {
+            Self::Gzip => "application/gzip",
+            Self::Zstd => "application/zstd",
+        }
+    }
+
+    /// Parse a media type, ignoring any parameters.
+    #[must_use]
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next().unwrap_or("").trim();
+        if essence.eq_ignore_ascii_case("application/gzip") {
+            Some(Self::Gzip)
+        } else if essence.eq_ignore_ascii_case("application/zstd") {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Choose the format to serve for an `Accept` header.
+    ///
+    /// Zstandard is chosen only when the header names `application/zstd`
+    /// with a non-zero quality at least as high as gzip's; wildcards, a
+    /// missing header and anything else get gzip, so clients that predate
+    /// Zstandard support keep receiving archives they can read.
+    #[must_use]
+    pub fn negotiate(accept: Option<&str>) -> Self {
+        let mut gzip_q = 0.0_f32;
+        let mut zstd_q = 0.0_f32;
+        for item in accept.unwrap_or("").split(',') {
+            let mut parts = item.split(';');
+            let Some(format) = Self::from_media_type(parts.next().unwrap_or("")) else {
+                continue;
+            };
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|v| v.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            match format {
+                Self::Gzip => gzip_q = q,
+                Self::Zstd => zstd_q = q,
+            }
+        }
+        if zstd_q > 0.0 && zstd_q >= gzip_q {
+            Self::Zstd
+        } else {
+            Self::Gzip
+        }
+    }
+
+    /// Deterministic compressing writer for this format.
+    pub(crate) fn encoder<W: Write>(self, out: W) -> io::Result<Encoder<W>> {
+        Ok(match self {
+            Self::Gzip => Encoder::Gzip(
+                GzBuilder::new()
+                    .mtime(0)
+                    .operating_system(GZIP_OS_UNKNOWN)
+                    .write(out, Compression::default()),
+            ),
+            Self::Zstd => {
+                let mut enc = zstd::stream::write::Encoder::new(out, ZSTD_LEVEL)?;
+                enc.include_checksum(true)?;
+                Encoder::Zstd(enc)
+            }
+        })
+    }
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        })
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gzip" | "gz" => Ok(Self::Gzip),
+            "zstd" | "zst" => Ok(Self::Zstd),
+            other => Err(format!(
+                "unknown archive format {other:?} (expected gzip or zstd)"
+            )),
+        }
+    }
+}
+
+/// A compressing writer for one [`ArchiveFormat`].
+pub(crate) enum Encoder<W: Write> {
+    Gzip(GzEncoder<W>),
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+}
+
+impl<W: Write> Encoder<W> {
+    /// Write the format trailer and return the inner writer.
+    pub(crate) fn finish(self) -> io::Result<W> {
+        match self {
+            Self::Gzip(enc) => enc.finish(),
+            Self::Zstd(enc) => enc.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Gzip(enc) => enc.write(buf),
+            Self::Zstd(enc) => enc.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Gzip(enc) => enc.flush(),
+            Self::Zstd(enc) => enc.flush(),
+        }
+    }
+}
+
+/// Wrap `reader` in a decompressor chosen from its magic bytes.
+///
+/// # Errors
+///
+/// Returns [`PackError::UnknownFormat`] if the stream starts with neither
+/// magic, or [`PackError::Io`] if it cannot be read.
+pub(crate) fn decoder<'a, R: Read + 'a>(mut reader: R) -> Result<Box<dyn Read + 'a>, PackError> {
+    let mut magic = [0u8; ZSTD_MAGIC.len()];
+    let mut filled = 0;
+    while filled < magic.len() {
+        match reader.read(&mut magic[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    let head = magic[..filled].to_vec();
+    let format = ArchiveFormat::detect(&head).ok_or(PackError::UnknownFormat)?;
+    let stream = Cursor::new(head).chain(reader);
+    Ok(match format {
+        ArchiveFormat::Gzip => Box::new(flate2::read::GzDecoder::new(stream)),
+        ArchiveFormat::Zstd => {
+            let mut dec = zstd::stream::read::Decoder::new(stream)?;
+            dec.window_log_max(ZSTD_WINDOW_LOG_MAX)?;
+            Box::new(dec)
+        }
+    })
+}
+
+/// Re-encode `bytes` as `to`, leaving the tar stream untouched.
+///
+/// Returns `bytes` unchanged if they are already in `to`.
+///
+/// # Errors
+///
+/// Returns [`PackError::UnknownFormat`] for an unrecognised archive,
+/// [`PackError::DecompressedTooLarge`] if the tar stream exceeds what the
+/// package limits allow, or [`PackError::Io`] on a decompression failure.
+pub fn transcode(bytes: &[u8], to: ArchiveFormat) -> Result<Vec<u8>, PackError> {
+    if ArchiveFormat::detect(bytes) == Some(to) {
+        return Ok(bytes.to_vec());
+    }
+    let max = crate::unpack::max_stream_size();
+    let mut tar = Vec::new();
+    decoder(bytes)?
+        .take(max.saturating_add(1))
+        .read_to_end(&mut tar)?;
+    if tar.len() as u64 > max {
+        return Err(PackError::DecompressedTooLarge { max });
+    }
+    let mut enc = to.encoder(Vec::new())?;
+    enc.write_all(&tar)?;
+    Ok(enc.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_formats_by_magic() {
+        assert_eq!(
+            ArchiveFormat::detect(&[0x1f, 0x8b, 8, 0]),
+            Some(ArchiveFormat::Gzip)
+        );
+        assert_eq!(
+            ArchiveFormat::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0]),
+            Some(ArchiveFormat::Zstd)
+        );
+        assert_eq!(ArchiveFormat::detect(b"PK\x03\x04"), None);
+    }
+
+    #[test]
+    fn negotiation_defaults_to_gzip() {
+        assert_eq!(ArchiveFormat::negotiate(None), ArchiveFormat::Gzip);
+        assert_eq!(ArchiveFormat::negotiate(Some("*/*")), ArchiveFormat::Gzip);
+        assert_eq!(
+            ArchiveFormat::negotiate(Some("application/gzip")),
+            ArchiveFormat::Gzip
+        );
+    }
+
+    #[test]
+    fn negotiation_honours_quality() {
+        assert_eq!(
+            ArchiveFormat::negotiate(Some("application/zstd, application/gzip;q=0.5")),
+            ArchiveFormat::Zstd
+        );
+        assert_eq!(
+            ArchiveFormat::negotiate(Some("application/zstd;q=0.2, application/gzip")),
+            ArchiveFormat::Gzip
+        );
+        assert_eq!(
+            ArchiveFormat::negotiate(Some("application/zstd;q=0")),
+            ArchiveFormat::Gzip
+        );
+    }
+
+    #[test]
+    fn transcoding_round_trips_the_tar_stream() {
+        let mut enc = ArchiveFormat::Gzip.encoder(Vec::new()).unwrap();
+        enc.write_all(b"tar bytes").unwrap();
+        let gzip = enc.finish().unwrap();
+
+        let zstd = transcode(&gzip, ArchiveFormat::Zstd).unwrap();
+        assert_eq!(ArchiveFormat::detect(&zstd), Some(ArchiveFormat::Zstd));
+        assert_eq!(transcode(&zstd, ArchiveFormat::Gzip).unwrap(), gzip);
+        assert!(matches!(
+            transcode(b"nope", ArchiveFormat::Gzip),
+            Err(PackError::UnknownFormat)
+        ));
+    }
+}
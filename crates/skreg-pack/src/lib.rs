@@ -3,5 +3,8 @@
 #![warn(missing_docs)]
 
 pub mod error;
+pub mod format;
 pub mod pack;
+pub mod reproducible;
+pub mod select;
 pub mod unpack;
@@ -1,60 +1,106 @@
-//! Creates a gzip-compressed `.skill` tarball from a directory.
+//! Creates a `.skill` tarball from a directory.
+//!
+//! Packing is deterministic: the same tree and [`ArchiveFormat`] always yield
+//! byte-identical output. Entries are written in sorted path order with
+//! zeroed mtime and owner, a mode derived from the path rather than the
+//! filesystem, and a compression header with no timestamp or host OS.
+//!
+//! Which files are packed is decided by [`crate::select`].
 
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use log::debug;
+use skreg_core::layout;
+use skreg_core::manifest::{FileEntry, Manifest};
+use skreg_core::types::Sha256Digest;
 
-/// Files that MUST be present in the source directory.
-const REQUIRED_FILES: &[&str] = &["SKILL.md"];
+use crate::format::ArchiveFormat;
 
-/// All files permitted at the root of a `.skill` tarball.
-const ALLOWED_ROOT_FILES: &[&str] = &["SKILL.md", "manifest.json"];
+/// Mode for files under `scripts/`; everything else is `0o644`.
+const SCRIPT_MODE: u32 = 0o755;
 
-/// Walk `source_dir` and append all allowed files to `tar`, skipping
-/// `manifest.json` (which is injected synthetically by `pack_with_manifest`).
-fn append_source_files<W: std::io::Write>(
+/// List the files of `source_dir` that belong in the archive as
+/// `(archive path, source path)` pairs, sorted by archive path.
+fn collect_source_files(source_dir: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    Ok(crate::select::select(source_dir)?.included)
+}
+
+/// Size and SHA-256 of every file [`pack_with_manifest`] would pack from
+/// `source_dir`, sorted by path, for [`Manifest::files`].
+///
+/// # Errors
+///
+/// Returns an error if `source_dir` or any file in it cannot be read.
+pub fn digest_source_files(source_dir: &Path) -> std::io::Result<Vec<FileEntry>> {
+    collect_source_files(source_dir)?
+        .into_iter()
+        .map(|(path, source)| {
+            let bytes = std::fs::read(source)?;
+            Ok(FileEntry {
+                path,
+                size: bytes.len() as u64,
+                sha256: Sha256Digest::of(&bytes),
+            })
+        })
+        .collect()
+}
+
+/// Append one regular-file entry with normalized metadata.
+fn append_entry<W: Write, R: std::io::Read>(
     tar: &mut tar::Builder<W>,
-    source_dir: &Path,
-) -> anyhow::Result<()> {
-    for entry in std::fs::read_dir(source_dir)? {
-        let entry = entry?;
-        let name = entry.file_name();
-        let name_str = name.to_string_lossy();
+    path: &str,
+    size: u64,
+    data: R,
+) -> std::io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(size);
+    header.set_mode(if path.starts_with("scripts/") {
+        SCRIPT_MODE
+    } else {
+        0o644
+    });
+    header.set_mtime(0);
+    header.set_uid(0);
+    header.set_gid(0);
+    tar.append_data(&mut header, path, data)
+}
 
-        // Skip hidden files, .git, and output .skill files.
-        if name_str.starts_with('.') || name_str == ".git" || name_str.ends_with(".skill") {
-            continue;
-        }
+/// Write a deterministic `format` archive of `source_dir` to `out`, with
+/// `manifest_json` as the first entry, and return the writer.
+///
+/// # Errors
+///
+/// Returns an error if any source file cannot be read or `out` cannot be
+/// written.
+pub fn pack_to_writer<W: Write>(
+    source_dir: &Path,
+    manifest_json: &[u8],
+    format: ArchiveFormat,
+    out: W,
+) -> std::io::Result<W> {
+    let mut tar = tar::Builder::new(format.encoder(out)?);
 
-        let path = entry.path();
+    // Inject manifest.json as a synthetic in-memory entry (first).
+    append_entry(
+        &mut tar,
+        "manifest.json",
+        manifest_json.len() as u64,
+        manifest_json,
+    )?;
 
-        if path.is_dir() {
-            // Only allow known subdirectory names.
-            let allowed_dirs = ["references", "scripts", "assets"];
-            if !allowed_dirs.contains(&name_str.as_ref()) {
-                debug!("skipping disallowed directory: {}", path.display());
-                continue;
-            }
-            debug!("packing dir: {}", path.display());
-            tar.append_dir_all(&name, &path)?;
-        } else {
-            // Skip manifest.json at the root — it is injected synthetically.
-            if name_str == "manifest.json" {
-                continue;
-            }
-            // Only allow known root files.
-            if !ALLOWED_ROOT_FILES.contains(&name_str.as_ref()) {
-                debug!("skipping disallowed root file: {}", path.display());
-                continue;
-            }
-            debug!("packing file: {}", path.display());
-            tar.append_path_with_name(&path, &name)?;
-        }
+    for (rel, path) in collect_source_files(source_dir)? {
+        debug!("packing file: {rel}");
+        let file = std::fs::File::open(&path)?;
+        let size = file.metadata()?.len();
+        append_entry(&mut tar, &rel, size, file)?;
     }
-    Ok(())
+
+    tar.into_inner()?.finish()
 }
 
-/// Pack `source_dir` into a `.skill` tarball at `output_path`.
+/// Pack `source_dir` into a gzip `.skill` tarball at `output_path`.
 ///
 /// The `manifest` value is serialised to JSON and injected as a synthetic
 /// `manifest.json` tar entry — the source directory is never modified.
@@ -65,33 +111,33 @@ fn append_source_files<W: std::io::Write>(
 /// or any I/O or serialisation step fails.
 pub fn pack_with_manifest(
     source_dir: &Path,
-    manifest: &skreg_core::manifest::Manifest,
+    manifest: &Manifest,
+    output_path: &Path,
+) -> anyhow::Result<()> {
+    pack_with_format(source_dir, manifest, output_path, ArchiveFormat::Gzip)
+}
+
+/// As [`pack_with_manifest`], compressing with `format`.
+///
+/// # Errors
+///
+/// As for [`pack_with_manifest`].
+pub fn pack_with_format(
+    source_dir: &Path,
+    manifest: &Manifest,
     output_path: &Path,
+    format: ArchiveFormat,
 ) -> anyhow::Result<()> {
     // Validate required files exist in source dir.
-    for required in REQUIRED_FILES {
+    for required in layout::REQUIRED_FILES {
         let p = source_dir.join(required);
         if !p.exists() {
             anyhow::bail!("required file missing: {required}");
         }
     }
 
-    let file = std::fs::File::create(output_path)?;
-    let enc = flate2::write::GzEncoder::new(file, flate2::Compression::default());
-    let mut tar = tar::Builder::new(enc);
-
-    // Inject manifest.json as a synthetic in-memory entry (first).
     let manifest_json = serde_json::to_vec_pretty(manifest)?;
-    let mut header = tar::Header::new_gnu();
-    header.set_size(manifest_json.len() as u64);
-    header.set_mode(0o644);
-    header.set_mtime(0);
-    header.set_cksum();
-    tar.append_data(&mut header, "manifest.json", manifest_json.as_slice())?;
-
-    // Append all source files (skip manifest.json if present in source).
-    append_source_files(&mut tar, source_dir)?;
-
-    tar.into_inner()?.finish()?;
+    let file = std::fs::File::create(output_path)?;
+    pack_to_writer(source_dir, &manifest_json, format, file)?;
     Ok(())
 }
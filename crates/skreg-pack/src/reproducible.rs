@@ -0,0 +1,105 @@
+//! Checks that an archive is exactly what deterministic packing of its own
+//! contents produces.
+//!
+//! A reproducible archive carries nothing beyond its files and manifest: no
+//! timestamps, owners, stray entries or non-canonical ordering. Anyone holding
+//! the unpacked files can rebuild it byte for byte.
+//!
+//! [`verify_contents`] is the weaker check the registry applies to uploads:
+//! the archive must hold exactly the entries packing its contents would
+//! write, with the same modes and bytes, however it was ordered, dated or
+//! compressed.
+
+use std::io::Read;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::error::PackError;
+use crate::format::{decoder, ArchiveFormat};
+use crate::pack::pack_to_writer;
+
+/// Rebuild a `format` archive from `unpacked`, a directory produced by
+/// unpacking it (including `manifest.json`).
+///
+/// # Errors
+///
+/// Returns [`PackError::MissingFile`] if `manifest.json` is absent, or
+/// [`PackError::Io`] if the files cannot be read.
+pub fn rebuild(unpacked: &Path, format: ArchiveFormat) -> Result<Vec<u8>, PackError> {
+    let manifest_json = std::fs::read(unpacked.join("manifest.json"))
+        .map_err(|_| PackError::MissingFile("manifest.json".to_owned()))?;
+    Ok(pack_to_writer(
+        unpacked,
+        &manifest_json,
+        format,
+        Vec::new(),
+    )?)
+}
+
+/// Verify that `bytes` equals the archive rebuilt from `unpacked`, its
+/// unpacked contents, in the same format.
+///
+/// # Errors
+///
+/// Returns [`PackError::UnknownFormat`] for an unrecognised archive,
+/// [`PackError::NotReproducible`] if the digests differ, or any error from
+/// [`rebuild`].
+pub fn verify_reproducible(bytes: &[u8], unpacked: &Path) -> Result<(), PackError> {
+    let format = ArchiveFormat::detect(bytes).ok_or(PackError::UnknownFormat)?;
+    let actual = hex_digest(bytes);
+    let rebuilt = hex_digest(&rebuild(unpacked, format)?);
+    if actual == rebuilt {
+        Ok(())
+    } else {
+        Err(PackError::NotReproducible { actual, rebuilt })
+    }
+}
+
+/// Verify that `bytes` holds the same entries as the archive rebuilt from
+/// `unpacked`: the same paths, each with the same permission bits and
+/// contents. Entry order, timestamps, owners and compression are ignored.
+///
+/// # Errors
+///
+/// Returns [`PackError::ContentsDiffer`] naming the first path that is
+/// missing, extra or different, or any error from [`rebuild`] or from
+/// reading either archive.
+pub fn verify_contents(bytes: &[u8], unpacked: &Path) -> Result<(), PackError> {
+    let format = ArchiveFormat::detect(bytes).ok_or(PackError::UnknownFormat)?;
+    let actual = list_entries(bytes)?;
+    let rebuilt = list_entries(&rebuild(unpacked, format)?)?;
+    let differing = actual
+        .iter()
+        .zip(&rebuilt)
+        .find(|(a, r)| a != r)
+        .map(|(a, r)| a.0.clone().min(r.0.clone()))
+        .or_else(|| actual.get(rebuilt.len()).map(|a| a.0.clone()))
+        .or_else(|| rebuilt.get(actual.len()).map(|r| r.0.clone()));
+    match differing {
+        Some(path) => Err(PackError::ContentsDiffer { path }),
+        None => Ok(()),
+    }
+}
+
+/// `(path, permission bits, SHA-256 of contents)` of every entry of an
+/// archive, sorted by path.
+fn list_entries(bytes: &[u8]) -> Result<Vec<(String, u32, String)>, PackError> {
+    let max = crate::unpack::max_stream_size();
+    let mut tar = tar::Archive::new(decoder(bytes)?.take(max.saturating_add(1)));
+    let mut entries = Vec::new();
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let mode = entry.header().mode()? & 0o777;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut entry, &mut hasher)?;
+        entries.push((path, mode, hex::encode(hasher.finalize())));
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+fn hex_digest(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
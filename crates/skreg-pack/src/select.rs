@@ -0,0 +1,195 @@
+//! Chooses which files of a source directory go into a `.skill` archive.
+//!
+//! Each entry is tested in turn: hidden entries are skipped, then anything
+//! matched by `.skregignore` (gitignore syntax), then — when include globs are
+//! configured — any file they do not match, and finally anything the shared
+//! [`layout`] policy rejects. `SKILL.md` is always included. Include globs
+//! come from `metadata.skreg-include` in the `SKILL.md` frontmatter and from
+//! `[pack] include` in `skreg.toml`; a file matching either is included.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Deserialize;
+use skreg_core::frontmatter::RawFrontmatter;
+use skreg_core::layout::{self, LayoutViolation};
+
+/// Gitignore-syntax file listing paths to leave out of the archive.
+pub const IGNORE_FILE: &str = ".skregignore";
+
+/// Optional packing configuration at the root of the source directory.
+pub const CONFIG_FILE: &str = "skreg.toml";
+
+/// Why an entry of the source directory was left out of the archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exclusion {
+    /// The name starts with `.`.
+    Hidden,
+    /// Matched by `.skregignore`.
+    Ignored,
+    /// Include globs are configured and none matches.
+    NotIncluded,
+    /// The registry's package layout does not allow it.
+    Layout(LayoutViolation),
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hidden => f.write_str("hidden"),
+            Self::Ignored => write!(f, "matched by {IGNORE_FILE}"),
+            Self::NotIncluded => f.write_str("not matched by any include glob"),
+            Self::Layout(v) => v.fmt(f),
+        }
+    }
+}
+
+/// The outcome of [`select`].
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// `(archive path, source path)` of every packed file, sorted by archive
+    /// path.
+    pub included: Vec<(String, PathBuf)>,
+    /// Skipped entries and why, sorted by path. Directories skipped as a
+    /// whole end in `/`.
+    pub excluded: Vec<(String, Exclusion)>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Config {
+    #[serde(default)]
+    pack: PackConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PackConfig {
+    #[serde(default)]
+    include: Vec<String>,
+}
+
+struct Rules {
+    ignore: Gitignore,
+    include: Option<GlobSet>,
+}
+
+fn invalid(what: &str, e: impl fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("invalid {what}: {e}"))
+}
+
+impl Rules {
+    fn load(source_dir: &Path) -> io::Result<Self> {
+        let ignore_path = source_dir.join(IGNORE_FILE);
+        let ignore = if ignore_path.is_file() {
+            let mut builder = GitignoreBuilder::new(source_dir);
+            if let Some(e) = builder.add(&ignore_path) {
+                return Err(invalid(IGNORE_FILE, e));
+            }
+            builder.build().map_err(|e| invalid(IGNORE_FILE, e))?
+        } else {
+            Gitignore::empty()
+        };
+
+        let mut globs = match std::fs::read_to_string(source_dir.join("SKILL.md")) {
+            Ok(content) => RawFrontmatter::parse(&content)
+                .map(|fm| fm.include_globs())
+                .unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+        let config_path = source_dir.join(CONFIG_FILE);
+        if config_path.is_file() {
+            let config: Config = toml::from_str(&std::fs::read_to_string(&config_path)?)
+                .map_err(|e| invalid(CONFIG_FILE, e))?;
+            globs.extend(config.pack.include);
+        }
+
+        let include = if globs.is_empty() {
+            None
+        } else {
+            let mut set = GlobSetBuilder::new();
+            for pattern in &globs {
+                let glob = GlobBuilder::new(pattern)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|e| invalid("include glob", e))?;
+                set.add(glob);
+            }
+            Some(set.build().map_err(|e| invalid("include glob", e))?)
+        };
+
+        Ok(Self { ignore, include })
+    }
+
+    fn walk(&self, dir: &Path, rel: Option<&str>, out: &mut Selection) -> io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let path = entry.path();
+            let is_dir = path.is_dir();
+
+            // Generated or configuration files at the root are never packed
+            // and not worth reporting.
+            if rel.is_none()
+                && (name == "manifest.json"
+                    || name == CONFIG_FILE
+                    || name == IGNORE_FILE
+                    || name.ends_with(".skill"))
+            {
+                continue;
+            }
+
+            let entry_rel =
+                rel.map_or_else(|| name.clone().into_owned(), |r| format!("{r}/{name}"));
+            let shown = if is_dir {
+                format!("{entry_rel}/")
+            } else {
+                entry_rel.clone()
+            };
+
+            // SKILL.md is required, so neither `.skregignore` nor the
+            // include globs can leave it out.
+            let required = !is_dir && entry_rel == "SKILL.md";
+            if name.starts_with('.') {
+                out.excluded.push((shown, Exclusion::Hidden));
+            } else if !required && self.ignore.matched(&path, is_dir).is_ignore() {
+                out.excluded.push((shown, Exclusion::Ignored));
+            } else if is_dir {
+                match layout::check_dir(Path::new(&entry_rel)) {
+                    Ok(()) => self.walk(&path, Some(&entry_rel), out)?,
+                    Err(v) => out.excluded.push((shown, Exclusion::Layout(v))),
+                }
+            } else if !required
+                && !self
+                    .include
+                    .as_ref()
+                    .map_or(true, |set| set.is_match(&entry_rel))
+            {
+                out.excluded.push((shown, Exclusion::NotIncluded));
+            } else {
+                match layout::check_file(Path::new(&entry_rel)) {
+                    Ok(()) => out.included.push((entry_rel, path)),
+                    Err(v) => out.excluded.push((shown, Exclusion::Layout(v))),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decide which files of `source_dir` belong in its archive.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read, or `.skregignore`,
+/// `skreg.toml` or an include glob is malformed.
+pub fn select(source_dir: &Path) -> io::Result<Selection> {
+    let rules = Rules::load(source_dir)?;
+    let mut selection = Selection::default();
+    rules.walk(source_dir, None, &mut selection)?;
+    selection.included.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    selection.excluded.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    Ok(selection)
+}
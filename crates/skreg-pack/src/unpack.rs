@@ -1,41 +1,258 @@
-//! Extracts a gzip-compressed `.skill` tarball into a target directory.
-
+//! Extracts a `.skill` tarball, gzip or Zstandard, into a target directory.
+//!
+//! Archives are untrusted input (the API unpacks every upload), so extraction
+//! is bounded: entry count, per-file size, total unpacked size and the raw
+//! decompressed stream are all capped while streaming, and only regular files
+//! and directories are accepted.
+
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
 
-use flate2::read::GzDecoder;
 use log::debug;
 use tempfile::TempDir;
 
+use skreg_core::limits;
 use skreg_core::manifest::Manifest;
 
 use crate::error::PackError;
+use crate::format::decoder;
+
+/// Allowance per entry for its tar header, long-name records and block
+/// padding when bounding the decompressed stream.
+const TAR_OVERHEAD_PER_ENTRY: u64 = 4096;
+
+/// End-of-archive blocks plus record padding written by common tar tools.
+const TAR_TRAILER: u64 = 10 * 1024;
+
+/// Bounds enforced while extracting.
+#[derive(Debug, Clone, Copy)]
+struct UnpackLimits {
+    /// Maximum number of regular files.
+    files: usize,
+    /// Maximum size of any one file, in bytes.
+    file_size: u64,
+    /// Maximum combined size of all files, in bytes.
+    total_size: u64,
+}
+
+impl Default for UnpackLimits {
+    fn default() -> Self {
+        Self {
+            files: limits::LIMIT_MAX_FILES,
+            file_size: limits::LIMIT_FILE_SIZE,
+            total_size: limits::LIMIT_PACKAGE_SIZE,
+        }
+    }
+}
+
+impl UnpackLimits {
+    /// Largest decompressed tar stream a package within these limits can produce.
+    fn max_stream_size(&self) -> u64 {
+        let entries = u64::try_from(self.files).unwrap_or(u64::MAX);
+        self.total_size
+            .saturating_add(
+                entries
+                    .saturating_add(1)
+                    .saturating_mul(TAR_OVERHEAD_PER_ENTRY),
+            )
+            .saturating_add(TAR_TRAILER)
+    }
+}
+
+/// Largest decompressed tar stream any valid package can produce.
+pub(crate) fn max_stream_size() -> u64 {
+    UnpackLimits::default().max_stream_size()
+}
+
+/// Reader that fails once more than `max` bytes have been read. The shared
+/// counter lets the caller tell this apart from other I/O errors after `tar`
+/// has wrapped it.
+struct BoundedReader<R> {
+    inner: R,
+    read: Rc<Cell<u64>>,
+    max: u64,
+}
+
+impl<R: Read> Read for BoundedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let total = self.read.get() + n as u64;
+        self.read.set(total);
+        if total > self.max {
+            return Err(std::io::Error::other("decompressed size limit exceeded"));
+        }
+        Ok(n)
+    }
+}
 
-/// Validate a single tar entry: reject symlinks and path-traversal components.
+fn entry_kind(entry_type: tar::EntryType) -> &'static str {
+    match entry_type {
+        tar::EntryType::Link => "hard link",
+        tar::EntryType::Char => "character device",
+        tar::EntryType::Block => "block device",
+        tar::EntryType::Fifo => "FIFO",
+        tar::EntryType::GNUSparse => "sparse file",
+        tar::EntryType::XGlobalHeader => "global extended header",
+        _ => "other",
+    }
+}
+
+/// Validate a single tar entry: reject symlinks, non-regular entry types and
+/// path-traversal components.
 ///
-/// Returns the entry's path on success.
-fn validate_entry<R: std::io::Read>(entry: &tar::Entry<R>) -> Result<PathBuf, PackError> {
+/// Returns the entry's path with any `.` components removed.
+fn validate_entry<R: Read>(entry: &tar::Entry<R>) -> Result<PathBuf, PackError> {
+    let raw = entry.path()?.to_path_buf();
+    let entry_type = entry.header().entry_type();
+
     // Reject symlinks.
-    if entry.header().entry_type().is_symlink() {
-        let path = entry.path()?.display().to_string();
-        return Err(PackError::Symlink(path));
+    if entry_type.is_symlink() {
+        return Err(PackError::Symlink(raw.display().to_string()));
+    }
+
+    // Only regular files and directories are extracted.
+    if !(entry_type.is_file() || entry_type.is_dir()) {
+        return Err(PackError::UnsupportedEntryType {
+            path: raw.display().to_string(),
+            kind: entry_kind(entry_type).to_owned(),
+        });
     }
 
     // Reject path traversal.
-    let path = entry.path()?.to_path_buf();
-    for component in path.components() {
+    let mut path = PathBuf::new();
+    for component in raw.components() {
         match component {
             Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
-                return Err(PackError::PathTraversal(path.display().to_string()));
+                return Err(PackError::PathTraversal(raw.display().to_string()));
             }
-            _ => {}
+            Component::CurDir => {}
+            Component::Normal(part) => path.push(part),
         }
     }
 
     Ok(path)
 }
 
+/// Write one regular-file entry of at most `size` bytes to `target`.
+fn write_file<R: Read>(
+    entry: &mut R,
+    target: &Path,
+    size: u64,
+    mode: u32,
+) -> Result<(), PackError> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = File::create(target)?;
+    std::io::copy(&mut entry.take(size), &mut file)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        let mode = if mode & 0o111 == 0 { 0o644 } else { 0o755 };
+        file.set_permissions(std::fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    Ok(())
+}
+
+/// Extract a tarball read from `reader` into `dest_dir`, enforcing `limits`
+/// as entries stream past.
+fn extract<R: Read>(
+    reader: R,
+    dest_dir: &Path,
+    limits: &UnpackLimits,
+    skip_manifest: bool,
+) -> Result<(), PackError> {
+    let read = Rc::new(Cell::new(0));
+    let max_stream = limits.max_stream_size();
+    let bounded = BoundedReader {
+        inner: decoder(reader)?,
+        read: Rc::clone(&read),
+        max: max_stream,
+    };
+    let over_limit = |e: std::io::Error| {
+        if read.get() > max_stream {
+            PackError::DecompressedTooLarge { max: max_stream }
+        } else {
+            PackError::Io(e)
+        }
+    };
+
+    let mut archive = tar::Archive::new(bounded);
+    // Lower-cased path → path as first seen, to catch duplicates and case collisions.
+    let mut seen: HashMap<String, String> = HashMap::new();
+    let mut file_count: usize = 0;
+    let mut total_size: u64 = 0;
+
+    for entry in archive.entries().map_err(over_limit)? {
+        let mut entry = entry.map_err(over_limit)?;
+        let path = validate_entry(&entry)?;
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+        let display = path.display().to_string();
+
+        if let Some(existing) = seen.insert(display.to_lowercase(), display.clone()) {
+            return Err(if existing == display {
+                PackError::DuplicateEntry(display)
+            } else {
+                PackError::CaseCollision {
+                    path: display,
+                    existing,
+                }
+            });
+        }
+
+        let target = dest_dir.join(&path);
+        if entry.header().entry_type().is_dir() {
+            std::fs::create_dir_all(&target)?;
+            continue;
+        }
+
+        file_count += 1;
+        if file_count > limits.files {
+            return Err(PackError::TooManyFiles { max: limits.files });
+        }
+
+        let size = entry.header().size()?;
+        if size > limits.file_size {
+            return Err(PackError::FileTooLarge {
+                path: display,
+                size,
+                max: limits.file_size,
+            });
+        }
+        total_size += size;
+        if total_size > limits.total_size {
+            return Err(PackError::PackageTooLarge {
+                max: limits.total_size,
+            });
+        }
+
+        // Skip top-level manifest.json
+        if skip_manifest && path == Path::new("manifest.json") {
+            debug!("skipping: {display}");
+            continue;
+        }
+
+        debug!("unpacking: {display}");
+        let mode = entry.header().mode().unwrap_or(0o644);
+        write_file(&mut entry, &target, size, mode).map_err(|e| match e {
+            PackError::Io(io) => over_limit(io),
+            other => other,
+        })?;
+    }
+
+    Ok(())
+}
+
 /// Unpack a `.skill` tarball into `dest_dir`.
 ///
 /// The destination directory is created if it does not exist.
@@ -43,23 +260,16 @@ fn validate_entry<R: std::io::Read>(entry: &tar::Entry<R>) -> Result<PathBuf, Pa
 ///
 /// # Errors
 ///
-/// Returns [`PackError::Symlink`] if any entry is a symlink.
-/// Returns [`PackError::PathTraversal`] if any entry path contains `..` or an absolute component.
-/// Returns [`PackError::Io`] on any other I/O or decompression failure.
+/// Returns [`PackError::Symlink`], [`PackError::UnsupportedEntryType`] or
+/// [`PackError::PathTraversal`] for a disallowed entry,
+/// [`PackError::DuplicateEntry`] or [`PackError::CaseCollision`] for a
+/// repeated path, a size or count variant if the archive exceeds the package
+/// limits, [`PackError::UnknownFormat`] if the archive is neither gzip nor
+/// Zstandard, or [`PackError::Io`] on any other I/O or decompression failure.
 pub fn unpack_tarball(tarball_path: &Path, dest_dir: &Path) -> Result<(), PackError> {
     std::fs::create_dir_all(dest_dir)?;
     let file = File::open(tarball_path)?;
-    let decoder = GzDecoder::new(file);
-    let mut archive = tar::Archive::new(decoder);
-
-    for entry in archive.entries()? {
-        let mut entry = entry?;
-        let path = validate_entry(&entry)?;
-        debug!("unpacking: {}", path.display());
-        entry.unpack_in(dest_dir)?;
-    }
-
-    Ok(())
+    extract(file, dest_dir, &UnpackLimits::default(), false)
 }
 
 /// Unpack a `.skill` tarball into `dest_dir`, skipping any `manifest.json` entry.
@@ -70,56 +280,28 @@ pub fn unpack_tarball(tarball_path: &Path, dest_dir: &Path) -> Result<(), PackEr
 ///
 /// # Errors
 ///
-/// Returns [`PackError::Symlink`] if any entry is a symlink.
-/// Returns [`PackError::PathTraversal`] if any entry path contains `..` or an absolute component.
-/// Returns [`PackError::Io`] on any other I/O or decompression failure.
+/// As for [`unpack_tarball`].
 pub fn unpack_tarball_skip_manifest(tarball_path: &Path, dest_dir: &Path) -> Result<(), PackError> {
     std::fs::create_dir_all(dest_dir)?;
     let file = File::open(tarball_path)?;
-    let decoder = GzDecoder::new(file);
-    let mut archive = tar::Archive::new(decoder);
-
-    for entry in archive.entries()? {
-        let mut entry = entry?;
-        let path = validate_entry(&entry)?;
-
-        // Skip top-level manifest.json
-        if path.file_name().and_then(|n| n.to_str()) == Some("manifest.json")
-            && path.components().count() == 1
-        {
-            debug!("skipping: {}", path.display());
-            continue;
-        }
-
-        debug!("unpacking: {}", path.display());
-        entry.unpack_in(dest_dir)?;
-    }
-
-    Ok(())
+    extract(file, dest_dir, &UnpackLimits::default(), true)
 }
 
 /// Unpack a `.skill` tarball from an in-memory byte slice into a new temporary directory.
 ///
-/// Rejects any entry that is a symlink or contains path traversal components (`..` or
-/// absolute paths). The returned [`TempDir`] owns the directory; it is deleted when dropped.
+/// The returned [`TempDir`] owns the directory; it is deleted when dropped.
 ///
 /// # Errors
 ///
-/// Returns [`PackError::Symlink`] if any entry is a symlink.
-/// Returns [`PackError::PathTraversal`] if any entry path contains `..` or an absolute component.
-/// Returns [`PackError::Io`] on any other I/O or decompression failure.
+/// As for [`unpack_tarball`].
 pub fn unpack_to_tempdir(bytes: &[u8]) -> Result<TempDir, PackError> {
     let tmp = TempDir::new()?;
-    let decoder = GzDecoder::new(Cursor::new(bytes));
-    let mut archive = tar::Archive::new(decoder);
-
-    for entry in archive.entries()? {
-        let mut entry = entry?;
-        let path = validate_entry(&entry)?;
-        debug!("unpacking: {}", path.display());
-        entry.unpack_in(tmp.path())?;
-    }
-
+    extract(
+        Cursor::new(bytes),
+        tmp.path(),
+        &UnpackLimits::default(),
+        false,
+    )?;
     Ok(tmp)
 }
 
@@ -128,18 +310,25 @@ pub fn unpack_to_tempdir(bytes: &[u8]) -> Result<TempDir, PackError> {
 /// # Errors
 ///
 /// Returns [`PackError::MissingFile`] if `manifest.json` is absent,
-/// [`PackError::Io`] on decompression failure, or [`PackError::ManifestParse`]
+/// [`PackError::FileTooLarge`] if it exceeds the manifest size limit,
+/// [`PackError::UnknownFormat`] for an unrecognised archive, [`PackError::Io`]
+/// on decompression failure, or [`PackError::ManifestParse`]
 /// if the JSON is malformed.
 pub fn read_manifest_from_bytes(bytes: &[u8]) -> Result<Manifest, PackError> {
-    use std::io::Read as _;
-
-    let decoder = GzDecoder::new(Cursor::new(bytes));
-    let mut archive = tar::Archive::new(decoder);
+    let mut archive = tar::Archive::new(decoder(Cursor::new(bytes))?);
 
     for entry in archive.entries()? {
         let mut entry = entry?;
         let path = entry.path()?.to_path_buf();
         if path == Path::new("manifest.json") {
+            let size = entry.header().size()?;
+            if size > limits::LIMIT_MANIFEST_SIZE {
+                return Err(PackError::FileTooLarge {
+                    path: "manifest.json".to_owned(),
+                    size,
+                    max: limits::LIMIT_MANIFEST_SIZE,
+                });
+            }
             let mut contents = String::new();
             entry.read_to_string(&mut contents)?;
             return serde_json::from_str(&contents)
@@ -258,6 +447,93 @@ mod tests {
         assert!(matches!(err, PackError::PathTraversal(_)), "got: {err}");
     }
 
+    #[test]
+    fn rejects_hardlink_and_fifo_entries() {
+        for entry_type in [tar::EntryType::Link, tar::EntryType::Fifo] {
+            let bytes = make_tarball(&[("SKILL.md", b"", entry_type)]);
+            let err = unpack_to_tempdir(&bytes).unwrap_err();
+            assert!(
+                matches!(err, PackError::UnsupportedEntryType { .. }),
+                "got: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_entry() {
+        let bytes = make_tarball(&[
+            ("SKILL.md", b"first", tar::EntryType::Regular),
+            ("./SKILL.md", b"second", tar::EntryType::Regular),
+        ]);
+        let err = unpack_to_tempdir(&bytes).unwrap_err();
+        assert!(matches!(err, PackError::DuplicateEntry(_)), "got: {err}");
+    }
+
+    #[test]
+    fn rejects_case_colliding_paths() {
+        let bytes = make_tarball(&[
+            ("SKILL.md", b"a", tar::EntryType::Regular),
+            ("skill.md", b"b", tar::EntryType::Regular),
+        ]);
+        let err = unpack_to_tempdir(&bytes).unwrap_err();
+        assert!(matches!(err, PackError::CaseCollision { .. }), "got: {err}");
+    }
+
+    fn extract_with(bytes: &[u8], limits: UnpackLimits) -> Result<(), PackError> {
+        let dir = tempfile::tempdir().unwrap();
+        extract(Cursor::new(bytes), dir.path(), &limits, false)
+    }
+
+    #[test]
+    fn enforces_file_count_and_size_limits() {
+        let limits = UnpackLimits {
+            files: 2,
+            file_size: 8,
+            total_size: 12,
+        };
+        let three = make_tarball(&[
+            ("a.md", b"1", tar::EntryType::Regular),
+            ("b.md", b"2", tar::EntryType::Regular),
+            ("c.md", b"3", tar::EntryType::Regular),
+        ]);
+        assert!(matches!(
+            extract_with(&three, limits),
+            Err(PackError::TooManyFiles { max: 2 })
+        ));
+
+        let big = make_tarball(&[("a.md", b"123456789", tar::EntryType::Regular)]);
+        assert!(matches!(
+            extract_with(&big, limits),
+            Err(PackError::FileTooLarge { size: 9, .. })
+        ));
+
+        let total = make_tarball(&[
+            ("a.md", b"1234567", tar::EntryType::Regular),
+            ("b.md", b"1234567", tar::EntryType::Regular),
+        ]);
+        assert!(matches!(
+            extract_with(&total, limits),
+            Err(PackError::PackageTooLarge { max: 12 })
+        ));
+    }
+
+    #[test]
+    fn bounds_decompressed_stream() {
+        // A GNU long-name record is buffered by `tar` before any entry is
+        // yielded, so only the stream bound can stop it.
+        let bytes = make_tarball_raw("././@LongLink", &vec![b'a'; 64 * 1024], b'L');
+        let limits = UnpackLimits {
+            files: 1,
+            file_size: 16,
+            total_size: 16,
+        };
+        let err = extract_with(&bytes, limits).unwrap_err();
+        assert!(
+            matches!(err, PackError::DecompressedTooLarge { .. }),
+            "got: {err}"
+        );
+    }
+
     #[test]
     fn accepts_normal_entries() {
         let bytes = make_tarball(&[
@@ -290,6 +566,7 @@ mod tests {
             sha256: Sha256Digest::from_hex(&"a".repeat(64)).unwrap(),
             cert_chain_pem: vec![],
             publisher_sig_hex: None,
+            files: vec![],
         };
 
         let out = src.path().join("out.skill");
@@ -24,6 +24,7 @@ fn stub_manifest() -> Manifest {
         sha256: Sha256Digest::from_hex(&"a".repeat(64)).unwrap(),
         cert_chain_pem: vec![],
         publisher_sig_hex: None,
+        files: vec![],
     }
 }
 
@@ -74,3 +75,285 @@ fn pack_with_manifest_missing_skill_md_fails() {
     let result = pack_with_manifest(dir.path(), &stub_manifest(), &out);
     assert!(result.is_err());
 }
+
+fn write_tree(root: &std::path::Path, files: &[(&str, &str)]) {
+    for (path, content) in files {
+        let p = root.join(path);
+        fs::create_dir_all(p.parent().unwrap()).unwrap();
+        fs::write(p, content).unwrap();
+    }
+}
+
+#[test]
+fn pack_is_byte_identical_across_trees() {
+    let files = [
+        ("SKILL.md", "---\nname: test\n---\n"),
+        ("references/b.md", "b"),
+        ("references/a.md", "a"),
+        ("scripts/run.sh", "echo hi"),
+    ];
+    let first = TempDir::new().unwrap();
+    write_tree(first.path(), &files);
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let second = TempDir::new().unwrap();
+    let mut reversed = files;
+    reversed.reverse();
+    write_tree(second.path(), &reversed);
+
+    let out = TempDir::new().unwrap();
+    pack_with_manifest(first.path(), &stub_manifest(), &out.path().join("1.skill")).unwrap();
+    pack_with_manifest(second.path(), &stub_manifest(), &out.path().join("2.skill")).unwrap();
+    assert_eq!(
+        fs::read(out.path().join("1.skill")).unwrap(),
+        fs::read(out.path().join("2.skill")).unwrap()
+    );
+}
+
+#[test]
+fn packed_archive_verifies_as_reproducible() {
+    use skreg_pack::reproducible::verify_reproducible;
+    use skreg_pack::unpack::unpack_to_tempdir;
+
+    let dir = TempDir::new().unwrap();
+    write_tree(
+        dir.path(),
+        &[
+            ("SKILL.md", "---\nname: test\n---\n"),
+            ("scripts/run.sh", "x"),
+        ],
+    );
+    let out = dir.path().join("out.skill");
+    pack_with_manifest(dir.path(), &stub_manifest(), &out).unwrap();
+    let bytes = fs::read(&out).unwrap();
+    let unpacked = unpack_to_tempdir(&bytes).unwrap();
+    verify_reproducible(&bytes, unpacked.path()).unwrap();
+}
+
+#[test]
+fn archive_with_real_mtimes_is_not_reproducible() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use skreg_pack::error::PackError;
+    use skreg_pack::reproducible::verify_reproducible;
+    use skreg_pack::unpack::unpack_to_tempdir;
+
+    let mut tar = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, data) in [("manifest.json", &b"{}"[..]), ("SKILL.md", &b"# skill"[..])] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(1_700_000_000);
+        tar.append_data(&mut header, path, data).unwrap();
+    }
+    let bytes = tar.into_inner().unwrap().finish().unwrap();
+
+    let unpacked = unpack_to_tempdir(&bytes).unwrap();
+    let err = verify_reproducible(&bytes, unpacked.path()).unwrap_err();
+    assert!(
+        matches!(err, PackError::NotReproducible { .. }),
+        "got: {err}"
+    );
+}
+
+#[test]
+fn archive_with_real_mtimes_has_reproducible_contents() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use skreg_pack::error::PackError;
+    use skreg_pack::reproducible::verify_contents;
+    use skreg_pack::unpack::unpack_to_tempdir;
+
+    let build = |entries: &[(&str, &[u8], u32)]| {
+        let mut tar = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::best()));
+        for (path, data, mode) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(*mode);
+            header.set_mtime(1_700_000_000);
+            tar.append_data(&mut header, path, *data).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap()
+    };
+
+    let bytes = build(&[
+        ("SKILL.md", b"# skill", 0o644),
+        ("manifest.json", b"{}", 0o644),
+    ]);
+    let unpacked = unpack_to_tempdir(&bytes).unwrap();
+    verify_contents(&bytes, unpacked.path()).unwrap();
+
+    let bytes = build(&[
+        ("manifest.json", b"{}", 0o644),
+        ("SKILL.md", b"# skill", 0o755),
+    ]);
+    let unpacked = unpack_to_tempdir(&bytes).unwrap();
+    let err = verify_contents(&bytes, unpacked.path()).unwrap_err();
+    assert!(
+        matches!(err, PackError::ContentsDiffer { ref path } if path == "SKILL.md"),
+        "got: {err}"
+    );
+
+    let bytes = build(&[
+        ("manifest.json", b"{}", 0o644),
+        ("SKILL.md", b"# skill", 0o644),
+        (".env", b"SECRET=1", 0o644),
+    ]);
+    let unpacked = unpack_to_tempdir(&bytes).unwrap();
+    let err = verify_contents(&bytes, unpacked.path()).unwrap_err();
+    assert!(
+        matches!(err, PackError::ContentsDiffer { ref path } if path == ".env"),
+        "got: {err}"
+    );
+}
+
+#[test]
+fn select_reports_ignored_unincluded_and_disallowed_files() {
+    use skreg_core::layout::LayoutViolation;
+    use skreg_pack::select::{select, Exclusion};
+
+    let dir = TempDir::new().unwrap();
+    write_tree(
+        dir.path(),
+        &[
+            ("SKILL.md", "---\nname: test\n---\n"),
+            ("LICENSE", "MIT"),
+            (".skregignore", "scripts/*.bash\n"),
+            (
+                "skreg.toml",
+                "[pack]\ninclude = [\"LICENSE\", \"scripts/*\"]\n",
+            ),
+            ("scripts/run.sh", "x"),
+            ("scripts/old.bash", "x"),
+            ("references/notes.md", "x"),
+            ("notes.txt", "x"),
+            ("docs/guide.md", "x"),
+            (".git/HEAD", "x"),
+        ],
+    );
+
+    let selection = select(dir.path()).unwrap();
+    let included: Vec<_> = selection.included.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(included, ["LICENSE", "SKILL.md", "scripts/run.sh"]);
+    assert_eq!(
+        selection.excluded,
+        [
+            (".git/".to_owned(), Exclusion::Hidden),
+            (
+                "docs/".to_owned(),
+                Exclusion::Layout(LayoutViolation::Directory("docs".to_owned()))
+            ),
+            ("notes.txt".to_owned(), Exclusion::NotIncluded),
+            ("references/notes.md".to_owned(), Exclusion::NotIncluded),
+            ("scripts/old.bash".to_owned(), Exclusion::Ignored),
+        ]
+    );
+}
+
+#[test]
+fn select_never_ignores_skill_md() {
+    use skreg_pack::select::{select, Exclusion};
+
+    let dir = TempDir::new().unwrap();
+    write_tree(
+        dir.path(),
+        &[
+            ("SKILL.md", "---\nname: test\n---\n"),
+            (".skregignore", "*.md\n"),
+            ("references/notes.md", "x"),
+        ],
+    );
+
+    let selection = select(dir.path()).unwrap();
+    let included: Vec<_> = selection.included.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(included, ["SKILL.md"]);
+    assert_eq!(
+        selection.excluded,
+        [("references/notes.md".to_owned(), Exclusion::Ignored)]
+    );
+}
+
+#[test]
+fn select_skips_files_the_registry_would_reject() {
+    use skreg_pack::select::select;
+
+    let dir = TempDir::new().unwrap();
+    write_tree(
+        dir.path(),
+        &[
+            ("SKILL.md", "---\nname: test\n---\n"),
+            ("LICENSE.md", "MIT"),
+            ("assets/logo.png", "x"),
+            ("assets/tool.exe", "x"),
+            ("references/deep/a.md", "x"),
+        ],
+    );
+
+    let selection = select(dir.path()).unwrap();
+    let included: Vec<_> = selection.included.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(included, ["LICENSE.md", "SKILL.md", "assets/logo.png"]);
+    let excluded: Vec<_> = selection.excluded.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(excluded, ["assets/tool.exe", "references/deep/"]);
+}
+
+#[test]
+fn include_globs_from_frontmatter_limit_packed_files() {
+    use skreg_pack::select::select;
+
+    let dir = TempDir::new().unwrap();
+    write_tree(
+        dir.path(),
+        &[
+            (
+                "SKILL.md",
+                "---\nname: test\nmetadata:\n  skreg-include: \"references/a.md\"\n---\n",
+            ),
+            ("references/a.md", "a"),
+            ("references/b.md", "b"),
+        ],
+    );
+
+    let selection = select(dir.path()).unwrap();
+    let included: Vec<_> = selection.included.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(included, ["SKILL.md", "references/a.md"]);
+}
+
+#[test]
+fn zstd_archive_unpacks_and_verifies_as_reproducible() {
+    use skreg_pack::format::{transcode, ArchiveFormat};
+    use skreg_pack::pack::pack_with_format;
+    use skreg_pack::reproducible::verify_reproducible;
+    use skreg_pack::unpack::{read_manifest_from_bytes, unpack_to_tempdir};
+
+    let dir = TempDir::new().unwrap();
+    write_tree(
+        dir.path(),
+        &[
+            ("SKILL.md", "---\nname: test\n---\n"),
+            ("references/a.md", "a"),
+        ],
+    );
+    let out = TempDir::new().unwrap();
+    let zst = out.path().join("out.skill");
+    pack_with_format(dir.path(), &stub_manifest(), &zst, ArchiveFormat::Zstd).unwrap();
+    let bytes = fs::read(&zst).unwrap();
+    assert_eq!(ArchiveFormat::detect(&bytes), Some(ArchiveFormat::Zstd));
+
+    assert_eq!(
+        read_manifest_from_bytes(&bytes).unwrap().name.as_str(),
+        "test"
+    );
+    let unpacked = unpack_to_tempdir(&bytes).unwrap();
+    assert_eq!(
+        fs::read_to_string(unpacked.path().join("references/a.md")).unwrap(),
+        "a"
+    );
+    verify_reproducible(&bytes, unpacked.path()).unwrap();
+
+    // Transcoding yields exactly what packing as gzip would.
+    let gz = out.path().join("out-gz.skill");
+    pack_with_manifest(dir.path(), &stub_manifest(), &gz).unwrap();
+    assert_eq!(
+        transcode(&bytes, ArchiveFormat::Gzip).unwrap(),
+        fs::read(&gz).unwrap()
+    );
+}
@@ -128,6 +128,7 @@ mod tests {
                     namespace: (*n).to_string(),
                     api_key: "k".to_string(),
                     root_ca_pem: None,
+                    signing_program: None,
                 },
             );
         }
@@ -226,8 +226,8 @@ impl PackageDetailView {
     }
 
     fn install(&mut self) {
-        let registry = self.config.registry().to_string();
-        let install_root = packages_dir();
+        let client = Arc::new(HttpRegistryClient::new(self.config.registry()));
+        let installer = Installer::for_config(client, packages_dir(), &self.config, false);
         let enforcement = self.config.policy.enforcement.clone();
         let ref_str = format!("{}/{}@{}", self.namespace, self.name, self.version);
         let (tx, rx) = oneshot::channel();
@@ -235,9 +235,11 @@ impl PackageDetailView {
         tokio::spawn(async move {
             let result = match PackageRef::parse(&ref_str) {
                 Ok(pkg_ref) => {
-                    let client = Arc::new(HttpRegistryClient::new(registry));
-                    let installer = Installer::new(client, install_root);
-                    match installer.install(&pkg_ref).await {
+                    let outcome = match installer {
+                        Ok(installer) => installer.install(&pkg_ref).await,
+                        Err(e) => Err(e),
+                    };
+                    match outcome {
                         Ok((installed_pkg, _manifest)) => {
                             let label = format!(
                                 "{} v{}",
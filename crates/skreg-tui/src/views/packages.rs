@@ -19,6 +19,7 @@ use skreg_client::linker::{
     build_skill_entries, default_claude_md_path, default_links_path, default_tool_skill_dirs,
     Linker,
 };
+use skreg_core::advisory::{default_advisory_cache_path, AdvisoryCache};
 use skreg_core::config::CliConfig;
 use skreg_core::package_ref::PackageRef;
 use tokio::sync::oneshot;
@@ -110,6 +111,8 @@ pub struct PackageListView {
     install_rx: Option<oneshot::Receiver<Result<String, String>>>,
     /// Set of `"namespace/name"` keys that are currently installed locally.
     installed: HashSet<String>,
+    /// Subset of `installed` whose installed version has a cached advisory.
+    advisories: HashSet<String>,
     /// When `installed` was last rescanned from disk.
     last_installed_scan: Instant,
     /// Whether the uninstall confirmation prompt is active.
@@ -127,6 +130,7 @@ impl PackageListView {
     /// Create a new view and kick off the initial package fetch.
     #[must_use]
     pub fn new(config: CliConfig) -> Self {
+        let advisories = Self::scan_advisory_set(config.registry());
         let mut v = Self {
             config,
             state: ListState::new(),
@@ -138,6 +142,7 @@ impl PackageListView {
             search_changed_at: None,
             install_rx: None,
             installed: Self::scan_installed_set(),
+            advisories,
             last_installed_scan: Instant::now(),
             confirming: false,
             installed_mode: false,
@@ -155,18 +160,41 @@ impl PackageListView {
             .collect()
     }
 
+    /// Keys of installed packages affected by an advisory in the local cache
+    /// for `registry`. The cache is populated by `skreg audit`; the TUI never
+    /// fetches the feed itself.
+    fn scan_advisory_set(registry: &str) -> HashSet<String> {
+        let Ok(cache) = AdvisoryCache::load(&default_advisory_cache_path(registry)) else {
+            return HashSet::new();
+        };
+        if cache.advisories.is_empty() {
+            return HashSet::new();
+        }
+        scan_installed(&packages_dir())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| {
+                semver::Version::parse(&p.version)
+                    .is_ok_and(|v| !cache.affecting(&p.namespace, &p.name, &v).is_empty())
+            })
+            .map(|p| format!("{}/{}", p.namespace, p.name))
+            .collect()
+    }
+
     fn install_selected(&mut self, namespace: String, name: String, version: String) {
-        let registry = self.config.registry().to_string();
-        let install_root = packages_dir();
+        let client = Arc::new(HttpRegistryClient::new(self.config.registry()));
+        let installer = Installer::for_config(client, packages_dir(), &self.config, false);
         let enforcement = self.config.policy.enforcement.clone();
         let (tx, rx) = oneshot::channel();
         self.install_rx = Some(rx);
         tokio::spawn(async move {
-            let client = Arc::new(HttpRegistryClient::new(registry));
-            let installer = Installer::new(client, install_root);
             let pkg_ref = PackageRef::parse(&format!("{namespace}/{name}@{version}"))
                 .unwrap_or_else(|_| PackageRef::parse(&format!("{namespace}/{name}")).unwrap());
-            let result = match installer.install(&pkg_ref).await {
+            let outcome = match installer {
+                Ok(installer) => installer.install(&pkg_ref).await,
+                Err(e) => Err(e),
+            };
+            let result = match outcome {
                 Ok((installed_pkg, _manifest)) => {
                     let label = format!(
                         "{} v{}",
@@ -320,6 +348,7 @@ impl View for PackageListView {
         // without requiring a full view reload.
         if self.last_installed_scan.elapsed() >= Duration::from_secs(1) {
             self.installed = Self::scan_installed_set();
+            self.advisories = Self::scan_advisory_set(self.config.registry());
             self.last_installed_scan = Instant::now();
         }
 
@@ -435,10 +464,14 @@ impl View for PackageListView {
                         let desc = p.description.as_deref().unwrap_or("").to_string();
                         let key = format!("{}/{}", p.namespace, p.name);
                         let name_cell = if self.installed.contains(&key) {
-                            Cell::from(Line::from(vec![
+                            let mut spans = vec![
                                 Span::raw(p.name.clone()),
                                 Span::styled(" ●", theme.success()),
-                            ]))
+                            ];
+                            if self.advisories.contains(&key) {
+                                spans.push(Span::styled(" ⚠", theme.danger()));
+                            }
+                            Cell::from(Line::from(spans))
                         } else {
                             Cell::from(p.name.clone())
                         };
@@ -0,0 +1,24 @@
+//! Package vetting checks shared by the registry worker and `skreg check`.
+//!
+//! Covers the deterministic stages — structure (Stage 1), content and secrets
+//! (Stage 2), and YARA / hook scanning (Stage 2.5 Pass 1). Sandboxed script
+//! analysis (Pass 2) needs the worker's toolchain and stays in `skreg-worker`.
+#![deny(warnings, clippy::all, clippy::pedantic)]
+#![warn(missing_docs)]
+
+/// Stage 2: content validity checks.
+pub mod content;
+/// Aggregated findings and JSON / SARIF rendering.
+pub mod report;
+/// Hardcoded secret detection.
+pub mod secrets;
+/// Stage 2.5 Pass 1: magic bytes, YARA and hook command scanning.
+pub mod static_analysis;
+/// Stage 1: structural validity checks.
+pub mod structure;
+
+/// Source directory of the bundled YARA rule pack.
+///
+/// Only meaningful on the machine that built this crate (tests and dev
+/// tooling); shipped binaries use [`static_analysis::pass1::compile_bundled_rules`].
+pub const RULES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/rules");
@@ -0,0 +1,248 @@
+//! Aggregated vetting results and their JSON / SARIF renderings.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::content::{check_content, ContentError};
+use crate::static_analysis::pass1::CompiledRules;
+use crate::static_analysis::{run_yara_and_hooks, Severity, StaticAnalysisError};
+use crate::structure::check_structure;
+
+/// Pipeline stage that produced a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// Stage 1: layout, limits and frontmatter.
+    Structure,
+    /// Stage 2: description, references and secrets.
+    Content,
+    /// Stage 2.5 Pass 1: magic bytes, YARA and hook commands.
+    StaticAnalysis,
+}
+
+/// How serious a [`Diagnostic`] is. Names follow SARIF result levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    /// The registry rejects the package.
+    Error,
+    /// Recorded by the registry but does not block publishing.
+    Warning,
+    /// Informational.
+    Note,
+}
+
+impl Level {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+        }
+    }
+}
+
+impl From<&Severity> for Level {
+    fn from(s: &Severity) -> Self {
+        match s {
+            Severity::Error => Self::Error,
+            Severity::Warning => Self::Warning,
+            Severity::Info => Self::Note,
+        }
+    }
+}
+
+/// A single problem found in a package.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    /// Stage that raised it.
+    pub stage: Stage,
+    /// Stable rule identifier, e.g. `structure`, `secret/github-token`, `yara/reverse_shell`.
+    pub rule_id: String,
+    /// Severity.
+    pub level: Level,
+    /// Human-readable description.
+    pub message: String,
+    /// Path relative to the package root, when the problem is file-specific.
+    pub file: Option<String>,
+    /// 1-based line number within `file`, when known.
+    pub line: Option<usize>,
+}
+
+/// Every diagnostic for one package.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    /// Version of the YARA rule pack the package was scanned with.
+    pub rule_pack_version: String,
+    /// Diagnostics in stage order.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    /// Whether the registry would accept the package (no error-level diagnostics).
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        !self.diagnostics.iter().any(|d| d.level == Level::Error)
+    }
+
+    /// Render as a SARIF 2.1.0 log with a single run.
+    #[must_use]
+    pub fn to_sarif(&self) -> Value {
+        let rule_ids: BTreeSet<&str> = self
+            .diagnostics
+            .iter()
+            .map(|d| d.rule_id.as_str())
+            .collect();
+        let results: Vec<Value> = self
+            .diagnostics
+            .iter()
+            .map(|d| {
+                let mut result = json!({
+                    "ruleId": d.rule_id,
+                    "level": d.level.as_str(),
+                    "message": { "text": d.message },
+                });
+                if let Some(file) = &d.file {
+                    let mut location = json!({ "artifactLocation": { "uri": file } });
+                    if let Some(line) = d.line {
+                        location["region"] = json!({ "startLine": line });
+                    }
+                    result["locations"] = json!([{ "physicalLocation": location }]);
+                }
+                result
+            })
+            .collect();
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "skreg",
+                        "informationUri": "https://skreg.ai",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rule_ids.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                    }
+                },
+                "properties": { "rulePackVersion": self.rule_pack_version },
+                "results": results,
+            }]
+        })
+    }
+}
+
+fn content_diagnostics(err: ContentError) -> Vec<Diagnostic> {
+    match err {
+        ContentError::HardcodedSecret(findings) => findings
+            .into_iter()
+            .map(|f| Diagnostic {
+                stage: Stage::Content,
+                rule_id: format!("secret/{}", f.rule_id),
+                level: Level::Error,
+                message: format!("possible hardcoded secret ({})", f.rule_id),
+                file: Some(f.file),
+                line: Some(f.line),
+            })
+            .collect(),
+        other => vec![Diagnostic {
+            stage: Stage::Content,
+            rule_id: "content".to_owned(),
+            level: Level::Error,
+            message: other.to_string(),
+            file: None,
+            line: None,
+        }],
+    }
+}
+
+/// Run every local stage against the unpacked package at `path`.
+///
+/// Unlike the registry, which stops at the first failing stage, all stages
+/// run so that every problem is reported at once.
+///
+/// # Errors
+///
+/// Returns [`StaticAnalysisError`] if the YARA scan cannot run (I/O or scanner
+/// failure). Package problems are reported as diagnostics, not errors.
+pub fn check_dir(path: &Path, rules: &CompiledRules) -> Result<Report, StaticAnalysisError> {
+    let mut diagnostics = Vec::new();
+
+    if let Err(e) = check_structure(path) {
+        let line = e.skill_md_line();
+        diagnostics.push(Diagnostic {
+            stage: Stage::Structure,
+            rule_id: "structure".to_owned(),
+            level: Level::Error,
+            message: e.to_string(),
+            file: line.map(|_| "SKILL.md".to_owned()),
+            line,
+        });
+    }
+
+    if let Err(e) = check_content(path) {
+        diagnostics.extend(content_diagnostics(e));
+    }
+
+    for f in run_yara_and_hooks(path, rules)? {
+        diagnostics.push(Diagnostic {
+            stage: Stage::StaticAnalysis,
+            rule_id: format!("{}/{}", f.tool, f.rule_id),
+            level: Level::from(&f.severity),
+            message: f.message,
+            file: Some(f.file),
+            line: None,
+        });
+    }
+
+    Ok(Report {
+        rule_pack_version: rules.version().to_owned(),
+        diagnostics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::static_analysis::pass1::compile_bundled_rules;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn clean_package_passes() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("SKILL.md"),
+            "---\nname: clean-skill\ndescription: A clean skill with a long enough description\nmetadata:\n  version: \"1.0.0\"\n---\n# Clean\n",
+        )
+        .unwrap();
+        let report = check_dir(dir.path(), &compile_bundled_rules().unwrap()).unwrap();
+        assert!(report.passed(), "{:?}", report.diagnostics);
+    }
+
+    #[test]
+    fn secrets_are_reported_with_location_in_sarif() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("SKILL.md"),
+            "---\nname: leaky\ndescription: A skill that leaks a credential somewhere\nmetadata:\n  version: \"1.0.0\"\n---\nAKIAZ7Q4N2XK5RWD3VHM\n",
+        )
+        .unwrap();
+        let report = check_dir(dir.path(), &compile_bundled_rules().unwrap()).unwrap();
+        assert!(!report.passed());
+
+        let sarif = report.to_sarif();
+        let result = sarif["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["ruleId"] == "secret/aws-access-key-id")
+            .expect("secret result");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "SKILL.md");
+        assert_eq!(location["region"]["startLine"], 7);
+        assert_eq!(result["level"], "error");
+    }
+}
@@ -3,56 +3,17 @@
 
 use std::path::Path;
 
-use super::{Finding, StaticAnalysisError};
-use crate::stages::static_analysis::pass1::CompiledRules;
-
-/// Extract YAML frontmatter from `content`.
-///
-/// Returns the raw YAML string between the opening and closing `---` delimiters,
-/// or `None` if the document does not start with `---`.
-fn extract_frontmatter(content: &str) -> Option<&str> {
-    let rest = content.strip_prefix("---")?;
-    // closing delimiter is "\n---\n" or "\n---" at EOF
-    let close = rest.find("\n---\n").or_else(|| {
-        if rest.ends_with("\n---") {
-            Some(rest.len() - 4)
-        } else {
-            None
-        }
-    })?;
-    Some(&rest[..close])
-}
+use skreg_core::frontmatter::RawFrontmatter;
 
-/// Recursively walk a `serde_yaml::Value` rooted at the `hooks:` subtree,
-/// collecting all string values whose key is `"command"`.
-fn collect_commands(value: &serde_yaml::Value, out: &mut Vec<String>) {
-    match value {
-        serde_yaml::Value::Mapping(map) => {
-            for (k, v) in map {
-                if k.as_str() == Some("command") {
-                    if let Some(s) = v.as_str() {
-                        out.push(s.to_owned());
-                    }
-                } else {
-                    collect_commands(v, out);
-                }
-            }
-        }
-        serde_yaml::Value::Sequence(seq) => {
-            for item in seq {
-                collect_commands(item, out);
-            }
-        }
-        _ => {}
-    }
-}
+use super::pass1::CompiledRules;
+use super::{Finding, StaticAnalysisError};
 
 /// Scan all hook `command` strings from `SKILL.md` in `package_dir` with `rules`.
 ///
 /// Returns an empty list when:
-/// - `SKILL.md` has no frontmatter
+/// - `SKILL.md` has no frontmatter, or it does not parse
 /// - frontmatter has no `hooks:` key
-/// - `hooks:` is not a map or sequence
+/// - `hooks:` contains no `command` strings
 ///
 /// # Errors
 ///
@@ -64,24 +25,13 @@ pub fn scan_hook_commands(
     let skill_md = package_dir.join("SKILL.md");
     let content = std::fs::read_to_string(&skill_md).map_err(StaticAnalysisError::Io)?;
 
-    let Some(yaml_str) = extract_frontmatter(&content) else {
+    // A malformed block fails Stage 1; here it simply has no commands.
+    let Ok(frontmatter) = RawFrontmatter::parse(&content) else {
         return Ok(vec![]);
     };
 
-    let doc: serde_yaml::Value = match serde_yaml::from_str(yaml_str) {
-        Ok(v) => v,
-        Err(_) => return Ok(vec![]),
-    };
-
-    let Some(hooks_value) = doc.get("hooks") else {
-        return Ok(vec![]);
-    };
-
-    let mut commands = Vec::new();
-    collect_commands(hooks_value, &mut commands);
-
     let mut findings = Vec::new();
-    for cmd in &commands {
+    for cmd in frontmatter.hooks.commands() {
         let cmd_findings = scan_command_bytes(cmd.as_bytes(), rules)?;
         findings.extend(cmd_findings);
     }
@@ -123,12 +73,11 @@ mod tests {
     use tempfile::tempdir;
 
     fn rules_dir() -> std::path::PathBuf {
-        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("rules")
+        std::path::PathBuf::from(crate::RULES_DIR)
     }
 
     fn compiled() -> CompiledRules {
-        crate::stages::static_analysis::pass1::compile_rules(&rules_dir())
-            .expect("rules should compile")
+        super::super::pass1::compile_rules(&rules_dir()).expect("rules should compile")
     }
 
     fn make_skill_md(dir: &std::path::Path, content: &str) {
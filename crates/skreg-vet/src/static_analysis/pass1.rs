@@ -2,45 +2,158 @@
 
 use std::path::{Component, Path};
 
+use sha2::{Digest, Sha256};
 use yara_x::Compiler;
 
 use super::{Finding, Severity, StaticAnalysisError};
 
 /// Compiled YARA rules. Built once at worker startup.
-pub struct CompiledRules(yara_x::Rules);
+pub struct CompiledRules {
+    rules: yara_x::Rules,
+    version: String,
+}
 
 impl CompiledRules {
     /// Return a reference to the inner compiled YARA rules.
     #[must_use]
     pub fn inner(&self) -> &yara_x::Rules {
-        &self.0
+        &self.rules
+    }
+
+    /// Rule-pack version: hex SHA-256 over every `.yar` file's relative path
+    /// and contents, in sorted order. Identical rule trees share a version.
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
     }
 }
 
-/// Compile all `.yar` files under `rules_dir` into a [`CompiledRules`] instance.
-///
-/// # Errors
-///
-/// Returns `StaticAnalysisError::YaraCompilation` if any rule file fails to compile.
-pub fn compile_rules(rules_dir: &Path) -> Result<CompiledRules, StaticAnalysisError> {
-    let mut compiler = Compiler::new();
+/// The rule pack shipped in `rules/`, embedded so `skreg check` can run
+/// without a rules directory. Sorted by path, matching [`rule_pack_version`].
+const BUNDLED_RULES: &[(&str, &str)] = &[
+    (
+        "credentials/harvest.yar",
+        include_str!("../../rules/credentials/harvest.yar"),
+    ),
+    (
+        "credentials/package_manager_creds.yar",
+        include_str!("../../rules/credentials/package_manager_creds.yar"),
+    ),
+    (
+        "credentials/wallet_harvest.yar",
+        include_str!("../../rules/credentials/wallet_harvest.yar"),
+    ),
+    (
+        "malware/blockchain_c2.yar",
+        include_str!("../../rules/malware/blockchain_c2.yar"),
+    ),
+    (
+        "malware/proc_mem_read.yar",
+        include_str!("../../rules/malware/proc_mem_read.yar"),
+    ),
+    (
+        "malware/systemd_persistence.yar",
+        include_str!("../../rules/malware/systemd_persistence.yar"),
+    ),
+    (
+        "scripts/destructive.yar",
+        include_str!("../../rules/scripts/destructive.yar"),
+    ),
+    (
+        "scripts/encrypted_exfil.yar",
+        include_str!("../../rules/scripts/encrypted_exfil.yar"),
+    ),
+    (
+        "scripts/execution.yar",
+        include_str!("../../rules/scripts/execution.yar"),
+    ),
+    (
+        "scripts/reverse_shell.yar",
+        include_str!("../../rules/scripts/reverse_shell.yar"),
+    ),
+];
 
+/// Return the sorted `.yar` files under `rules_dir` with their sources.
+fn rule_sources(rules_dir: &Path) -> Result<Vec<(String, String)>, StaticAnalysisError> {
+    let mut sources = Vec::new();
     for entry in walkdir::WalkDir::new(rules_dir)
         .follow_links(false)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(std::result::Result::ok)
     {
         if entry.path().extension().and_then(|e| e.to_str()) != Some("yar") {
             continue;
         }
+        let rel = entry
+            .path()
+            .strip_prefix(rules_dir)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .into_owned();
         let source = std::fs::read_to_string(entry.path()).map_err(StaticAnalysisError::Io)?;
+        sources.push((rel, source));
+    }
+    Ok(sources)
+}
+
+fn version_of(sources: &[(String, String)]) -> String {
+    let mut hasher = Sha256::new();
+    for (rel, source) in sources {
+        hasher.update(rel.as_bytes());
+        hasher.update([0]);
+        hasher.update(source.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Compute the rule-pack version of `rules_dir` without compiling it.
+///
+/// # Errors
+///
+/// Returns `StaticAnalysisError::Io` if a rule file cannot be read.
+pub fn rule_pack_version(rules_dir: &Path) -> Result<String, StaticAnalysisError> {
+    Ok(version_of(&rule_sources(rules_dir)?))
+}
+
+/// Compile all `.yar` files under `rules_dir` into a [`CompiledRules`] instance.
+///
+/// # Errors
+///
+/// Returns `StaticAnalysisError::YaraCompilation` if any rule file fails to compile.
+pub fn compile_rules(rules_dir: &Path) -> Result<CompiledRules, StaticAnalysisError> {
+    compile_sources(&rule_sources(rules_dir)?)
+}
+
+/// Compile the rule pack embedded in this crate.
+///
+/// Its version equals [`rule_pack_version`] of the `rules/` directory it was
+/// built from, so local results can be matched to a registry rule pack.
+///
+/// # Errors
+///
+/// Returns `StaticAnalysisError::YaraCompilation` if a bundled rule fails to compile.
+pub fn compile_bundled_rules() -> Result<CompiledRules, StaticAnalysisError> {
+    let sources: Vec<(String, String)> = BUNDLED_RULES
+        .iter()
+        .map(|(rel, src)| ((*rel).to_owned(), (*src).to_owned()))
+        .collect();
+    compile_sources(&sources)
+}
+
+fn compile_sources(sources: &[(String, String)]) -> Result<CompiledRules, StaticAnalysisError> {
+    let mut compiler = Compiler::new();
+    for (_, source) in sources {
         compiler
             .add_source(source.as_str())
             .map_err(|e| StaticAnalysisError::YaraCompilation(e.to_string()))?;
     }
 
-    let rules = compiler.build();
-    Ok(CompiledRules(rules))
+    Ok(CompiledRules {
+        rules: compiler.build(),
+        version: version_of(sources),
+    })
 }
 
 /// Run Pass 1 on a single file.
@@ -104,7 +217,7 @@ pub fn run_pass1(
     }
 
     // YARA scan
-    let mut scanner = yara_x::Scanner::new(&rules.0);
+    let mut scanner = yara_x::Scanner::new(&rules.rules);
     let results = scanner
         .scan(&bytes)
         .map_err(|e| StaticAnalysisError::ToolError {
@@ -132,15 +245,36 @@ mod tests {
     use tempfile::tempdir;
 
     fn rules_dir() -> std::path::PathBuf {
-        // Locate rules dir relative to this source file at compile time.
-        let manifest = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
-        manifest.join("rules")
+        std::path::PathBuf::from(crate::RULES_DIR)
     }
 
     fn compiled() -> CompiledRules {
         compile_rules(&rules_dir()).expect("rules should compile")
     }
 
+    #[test]
+    fn bundled_rules_match_rules_directory() {
+        let bundled = compile_bundled_rules().expect("bundled rules compile");
+        assert_eq!(
+            bundled.version(),
+            rule_pack_version(&rules_dir()).unwrap(),
+            "BUNDLED_RULES is out of sync with the rules/ directory"
+        );
+    }
+
+    #[test]
+    fn rule_pack_version_is_content_addressed() {
+        assert_eq!(
+            compiled().version(),
+            rule_pack_version(&rules_dir()).unwrap()
+        );
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.yar"), "rule a { condition: true }").unwrap();
+        let before = rule_pack_version(dir.path()).unwrap();
+        fs::write(dir.path().join("a.yar"), "rule a { condition: false }").unwrap();
+        assert_ne!(before, rule_pack_version(dir.path()).unwrap());
+    }
+
     #[test]
     fn clean_script_produces_no_findings() {
         let rules = compiled();
@@ -2,19 +2,11 @@
 
 use std::path::{Component, Path};
 
-use semver::Version;
+use skreg_core::frontmatter::{FrontmatterError, SkillFrontmatter};
+use skreg_core::layout::{self, LayoutViolation};
 use skreg_core::limits;
 use thiserror::Error;
 
-const REQUIRED_FILES: &[&str] = &["SKILL.md"];
-const ALLOWED_ROOT_FILES: &[&str] = &["SKILL.md", "manifest.json"];
-const ALLOWED_ROOT_PREFIXES: &[&str] = &["LICENSE"];
-const SCRIPT_EXTENSIONS: &[&str] = &["py", "sh", "bash", "js", "ts", "rb"];
-const REFERENCE_EXTENSIONS: &[&str] = &["md"];
-const ASSET_EXTENSIONS: &[&str] = &[
-    "md", "txt", "json", "yaml", "yml", "csv", "png", "jpg", "svg", "pdf",
-];
-
 /// Errors produced by structural validation.
 #[derive(Debug, Error)]
 pub enum StructureError {
@@ -63,15 +55,22 @@ pub enum StructureError {
     #[error("SKILL.md is missing YAML frontmatter")]
     FrontmatterMissing,
     /// The frontmatter YAML could not be parsed.
-    #[error("SKILL.md frontmatter is invalid YAML: {0}")]
-    FrontmatterInvalid(String),
-    /// A required frontmatter field is absent or empty.
+    #[error("SKILL.md frontmatter is invalid YAML at line {line}: {message}")]
+    FrontmatterInvalid {
+        /// Parser message.
+        message: String,
+        /// 1-based `SKILL.md` line.
+        line: usize,
+    },
+    /// A required frontmatter field is absent, empty or malformed.
     #[error("SKILL.md frontmatter field '{field}' is invalid: {reason}")]
     FrontmatterFieldInvalid {
         /// The field name.
         field: String,
         /// The reason the field is invalid.
         reason: String,
+        /// 1-based `SKILL.md` line of the field, when known.
+        line: Option<usize>,
     },
     /// `SKILL.md` exceeds the maximum line count.
     #[error("SKILL.md exceeds {max} lines ({got} lines)")]
@@ -86,17 +85,44 @@ pub enum StructureError {
     Io(#[from] std::io::Error),
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct SkillFrontmatter {
-    name: Option<String>,
-    description: Option<String>,
-    compatibility: Option<String>,
-    #[allow(dead_code)]
-    license: Option<String>,
-    metadata: Option<std::collections::HashMap<String, String>>,
-    #[serde(rename = "allowed-tools")]
-    #[allow(dead_code)]
-    allowed_tools: Option<String>,
+impl StructureError {
+    /// 1-based `SKILL.md` line the error points at, for frontmatter errors.
+    #[must_use]
+    pub fn skill_md_line(&self) -> Option<usize> {
+        match self {
+            Self::FrontmatterFieldInvalid { line, .. } => *line,
+            Self::FrontmatterInvalid { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+}
+
+impl From<FrontmatterError> for StructureError {
+    fn from(e: FrontmatterError) -> Self {
+        let line = e.line();
+        match e {
+            FrontmatterError::Missing | FrontmatterError::Unclosed => Self::FrontmatterMissing,
+            FrontmatterError::Yaml { message, .. } => Self::FrontmatterInvalid {
+                message,
+                line: line.unwrap_or(1),
+            },
+            FrontmatterError::MissingField { field } => Self::FrontmatterFieldInvalid {
+                field,
+                reason: "field is missing or empty".to_owned(),
+                line: None,
+            },
+            FrontmatterError::InvalidField { field, reason, .. } => Self::FrontmatterFieldInvalid {
+                field,
+                reason,
+                line,
+            },
+            e @ FrontmatterError::UnsupportedSchema { .. } => Self::FrontmatterFieldInvalid {
+                field: "metadata.skreg-schema".to_owned(),
+                reason: e.to_string(),
+                line: None,
+            },
+        }
+    }
 }
 
 /// Run Stage 1 structural checks on the unpacked directory at `path`.
@@ -106,7 +132,7 @@ struct SkillFrontmatter {
 /// Returns the first [`StructureError`] encountered.
 pub fn check_structure(path: &Path) -> Result<(), StructureError> {
     // 1. Required files exist
-    for required in REQUIRED_FILES {
+    for required in layout::REQUIRED_FILES {
         if !path.join(required).exists() {
             return Err(StructureError::MissingFile((*required).to_owned()));
         }
@@ -163,13 +189,7 @@ pub fn check_structure(path: &Path) -> Result<(), StructureError> {
             .len();
 
         // Per-file size limit (scripts have a tighter cap)
-        let parts: Vec<_> = rel.components().collect();
-        let per_file_max = if matches!(parts.as_slice(), [Component::Normal(d), _] if *d == "scripts")
-        {
-            limits::LIMIT_SCRIPT_FILE_SIZE
-        } else {
-            limits::LIMIT_FILE_SIZE
-        };
+        let per_file_max = layout::file_size_limit(rel);
 
         if file_size > per_file_max {
             return Err(StructureError::FileTooLarge {
@@ -204,80 +224,10 @@ fn validate_path_safety(rel: &Path) -> Result<(), StructureError> {
 }
 
 fn validate_file_location(rel: &Path) -> Result<(), StructureError> {
-    let parts: Vec<_> = rel.components().collect();
-
-    match parts.as_slice() {
-        [Component::Normal(name)] => {
-            let name_str = name.to_string_lossy();
-            let allowed = ALLOWED_ROOT_FILES.contains(&&*name_str)
-                || ALLOWED_ROOT_PREFIXES
-                    .iter()
-                    .any(|p| name_str.starts_with(p));
-            if !allowed {
-                return Err(StructureError::DisallowedFile(rel.display().to_string()));
-            }
-        }
-        [Component::Normal(dir), Component::Normal(_file)] => {
-            let dir_str = dir.to_string_lossy();
-            let ext = rel.extension().and_then(|e| e.to_str()).unwrap_or("");
-            match dir_str.as_ref() {
-                "scripts" => {
-                    if !SCRIPT_EXTENSIONS.contains(&ext) {
-                        return Err(StructureError::DisallowedFile(rel.display().to_string()));
-                    }
-                }
-                "references" => {
-                    if !REFERENCE_EXTENSIONS.contains(&ext) {
-                        return Err(StructureError::DisallowedFile(rel.display().to_string()));
-                    }
-                }
-                "assets" => {
-                    if !ASSET_EXTENSIONS.contains(&ext) {
-                        return Err(StructureError::DisallowedFile(rel.display().to_string()));
-                    }
-                }
-                _ => return Err(StructureError::DisallowedFile(rel.display().to_string())),
-            }
-        }
-        [Component::Normal(dir), ..] => {
-            return Err(StructureError::DisallowedSubdirectory(
-                dir.to_string_lossy().into_owned(),
-            ));
-        }
-        _ => return Err(StructureError::DisallowedFile(rel.display().to_string())),
-    }
-    Ok(())
-}
-
-fn validate_name(name: &str) -> Result<(), StructureError> {
-    if name.len() > limits::LIMIT_NAME_LEN {
-        return Err(StructureError::FrontmatterFieldInvalid {
-            field: "name".to_owned(),
-            reason: "must be 64 characters or fewer".to_owned(),
-        });
-    }
-    if !name
-        .chars()
-        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
-    {
-        return Err(StructureError::FrontmatterFieldInvalid {
-            field: "name".to_owned(),
-            reason: "must contain only lowercase letters, digits, and hyphens".to_owned(),
-        });
-    }
-    if name.starts_with('-') || name.ends_with('-') {
-        return Err(StructureError::FrontmatterFieldInvalid {
-            field: "name".to_owned(),
-            reason: "must not start or end with a hyphen".to_owned(),
-        });
-    }
-    if name.contains("--") {
-        return Err(StructureError::FrontmatterFieldInvalid {
-            field: "name".to_owned(),
-            reason: "must not contain consecutive hyphens".to_owned(),
-        });
-    }
-    Ok(())
+    layout::check_file(rel).map_err(|e| match e {
+        LayoutViolation::Subdirectory(dir) => StructureError::DisallowedSubdirectory(dir),
+        _ => StructureError::DisallowedFile(rel.display().to_string()),
+    })
 }
 
 fn validate_skill_md(path: &Path) -> Result<(), StructureError> {
@@ -302,82 +252,8 @@ fn validate_skill_md(path: &Path) -> Result<(), StructureError> {
         });
     }
 
-    // Must start with ---
-    if !content.starts_with("---") {
-        return Err(StructureError::FrontmatterMissing);
-    }
-
-    // Find closing "---" on its own line: must be "\n---\n" or "\n---" at EOF
-    let after_open = &content[3..];
-    let close_pos = after_open
-        .find("\n---\n")
-        .or_else(|| after_open.strip_suffix("\n---").map(str::len))
-        .ok_or(StructureError::FrontmatterMissing)?;
-
-    let yaml_block = &after_open[..close_pos];
-
-    // Parse YAML
-    let frontmatter: SkillFrontmatter = serde_yaml::from_str(yaml_block)
-        .map_err(|e| StructureError::FrontmatterInvalid(e.to_string()))?;
-
-    // Validate name
-    match frontmatter.name.as_deref() {
-        None | Some("") => {
-            return Err(StructureError::FrontmatterFieldInvalid {
-                field: "name".to_owned(),
-                reason: "field is missing or empty".to_owned(),
-            });
-        }
-        Some(name) => validate_name(name)?,
-    }
-
-    // Validate description
-    match frontmatter.description.as_deref() {
-        None | Some("") => {
-            return Err(StructureError::FrontmatterFieldInvalid {
-                field: "description".to_owned(),
-                reason: "field is missing or empty".to_owned(),
-            });
-        }
-        Some(desc) => {
-            if desc.len() > limits::LIMIT_DESCRIPTION_LEN {
-                return Err(StructureError::FrontmatterFieldInvalid {
-                    field: "description".to_owned(),
-                    reason: "must be 1024 characters or fewer".to_owned(),
-                });
-            }
-        }
-    }
-
-    // Validate metadata.version — required, must be valid semver
-    let version_str = frontmatter
-        .metadata
-        .as_ref()
-        .and_then(|m| m.get("version"))
-        .map_or("", String::as_str);
-    if version_str.is_empty() {
-        return Err(StructureError::FrontmatterFieldInvalid {
-            field: "metadata.version".to_owned(),
-            reason: "field is missing or empty".to_owned(),
-        });
-    }
-    if Version::parse(version_str).is_err() {
-        return Err(StructureError::FrontmatterFieldInvalid {
-            field: "metadata.version".to_owned(),
-            reason: format!("{version_str:?} is not valid semver"),
-        });
-    }
-
-    // Optionally validate compatibility if present
-    if let Some(compat) = frontmatter.compatibility.as_deref() {
-        if compat.is_empty() || compat.len() > limits::LIMIT_COMPATIBILITY_LEN {
-            return Err(StructureError::FrontmatterFieldInvalid {
-                field: "compatibility".to_owned(),
-                reason: "must be 1–500 characters".to_owned(),
-            });
-        }
-    }
-
+    // Frontmatter schema, required fields and limits
+    SkillFrontmatter::parse(&content)?;
     Ok(())
 }
 
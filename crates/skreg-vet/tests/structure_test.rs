@@ -1,6 +1,6 @@
 use std::fs;
 
-use skreg_worker::stages::structure::check_structure;
+use skreg_vet::structure::check_structure;
 use tempfile::TempDir;
 
 fn make_valid_dir(dir: &TempDir) {
@@ -0,0 +1,81 @@
+//! Fetch a `.skill` tarball once and share it across pipeline stages.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use aws_sdk_s3::Client as S3Client;
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+
+/// A downloaded and unpacked `.skill` tarball.
+///
+/// The unpacked directory is removed when the artifact is dropped.
+pub struct Artifact {
+    bytes: Vec<u8>,
+    sha256: String,
+    dir: TempDir,
+}
+
+impl Artifact {
+    /// Download `key` from `bucket` and unpack it into a temporary directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the S3 download or tarball unpacking fails.
+    pub async fn fetch(s3: &S3Client, bucket: &str, key: &str) -> Result<Self> {
+        let obj = s3
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .context("downloading tarball from S3")?;
+        let bytes = obj
+            .body
+            .collect()
+            .await
+            .context("reading S3 object body")?
+            .into_bytes()
+            .to_vec();
+        Self::from_bytes(bytes)
+    }
+
+    /// Unpack an in-memory tarball.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tarball cannot be unpacked.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        let dir = skreg_pack::unpack::unpack_to_tempdir(&bytes).context("unpacking tarball")?;
+        let sha256 = hex::encode(Sha256::digest(&bytes));
+        Ok(Self { bytes, sha256, dir })
+    }
+
+    /// The raw tarball bytes.
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Lowercase hex sha256 of the tarball bytes.
+    #[must_use]
+    pub fn sha256(&self) -> &str {
+        &self.sha256
+    }
+
+    /// Directory the tarball was unpacked into.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_bytes_rejects_non_tarball() {
+        assert!(Artifact::from_bytes(b"not a tarball".to_vec()).is_err());
+    }
+}
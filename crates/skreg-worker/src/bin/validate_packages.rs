@@ -8,7 +8,7 @@
 //!
 //! Environment variables:
 //!   SKREG_YARA_RULES_DIR   Override the default YARA rules path
-//!                          (default: crates/skreg-worker/rules)
+//!                          (default: crates/skreg-vet/rules)
 //!   SKREG_TRACEE_SOCKET    Override the tracee eBPF socket path
 //!                          (default: /var/run/tracee/tracee.sock)
 
@@ -71,8 +71,7 @@ fn main() {
     }
 
     let rules_dir = PathBuf::from(
-        std::env::var("SKREG_YARA_RULES_DIR")
-            .unwrap_or_else(|_| "crates/skreg-worker/rules".into()),
+        std::env::var("SKREG_YARA_RULES_DIR").unwrap_or_else(|_| "crates/skreg-vet/rules".into()),
     );
 
     let compiled_rules = match compile_rules(&rules_dir) {
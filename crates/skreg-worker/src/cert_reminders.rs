@@ -0,0 +1,131 @@
+//! Scheduled reminders of publisher certificates about to expire.
+//!
+//! A certificate is reminded of once, [`REMIND_BEFORE_DAYS`] days or less
+//! before it expires, unless it is revoked or the namespace already holds a
+//! certificate that outlives it, i.e. it was renewed.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::email::{send_email, SmtpConfig};
+
+/// How many days before expiry a certificate is reminded of.
+pub const REMIND_BEFORE_DAYS: i32 = 14;
+
+/// Email every namespace whose certificate expires within
+/// [`REMIND_BEFORE_DAYS`] days, and mark the certificate reminded.
+///
+/// Certificates are claimed by marking them before any email is sent, so
+/// workers running at once never remind of the same one twice. Returns the
+/// number of reminders sent. A failed send is logged, and its mark cleared
+/// so the next run retries it.
+///
+/// # Errors
+///
+/// Returns an error if a database operation fails.
+pub async fn send_expiry_reminders(pool: &PgPool, smtp: &SmtpConfig, from: &str) -> Result<u64> {
+    let due = sqlx::query_as::<_, (Uuid, i64, DateTime<Utc>, String, Option<String>)>(
+        "UPDATE publisher_certs c SET expiry_reminded_at = now()
+         FROM namespaces n
+         WHERE n.id = c.namespace_id
+           AND c.expiry_reminded_at IS NULL
+           AND c.id IN (
+               SELECT d.id FROM publisher_certs d
+               WHERE d.revoked_at IS NULL
+                 AND d.expiry_reminded_at IS NULL
+                 AND d.expires_at > now()
+                 AND d.expires_at <= now() + make_interval(days => $1)
+                 AND NOT EXISTS (
+                     SELECT 1 FROM publisher_certs r
+                     WHERE r.namespace_id = d.namespace_id
+                       AND r.revoked_at IS NULL
+                       AND r.expires_at > d.expires_at)
+               FOR UPDATE SKIP LOCKED)
+         RETURNING c.id, c.serial, c.expires_at, n.slug,
+                   (SELECT ak.email FROM api_keys ak WHERE ak.namespace_id = n.id
+                     ORDER BY ak.created_at DESC LIMIT 1)",
+    )
+    .bind(REMIND_BEFORE_DAYS)
+    .fetch_all(pool)
+    .await?;
+
+    let mut sent = 0;
+    for (cert_id, serial, expires_at, namespace, email) in due {
+        let Some(email) = email else {
+            error!("no email found for namespace {namespace}, skipping expiry reminder");
+            release_claim(pool, cert_id).await?;
+            continue;
+        };
+        let (subject, body) = reminder_text(&namespace, serial, expires_at);
+        if let Err(e) = send_email(smtp, from, &email, &subject, &body).await {
+            error!("failed to send expiry reminder for certificate {serial}: {e}");
+            release_claim(pool, cert_id).await?;
+            continue;
+        }
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+/// Clear the reminder mark of certificate `cert_id`, so the next run tries
+/// again.
+async fn release_claim(pool: &PgPool, cert_id: Uuid) -> Result<()> {
+    sqlx::query("UPDATE publisher_certs SET expiry_reminded_at = NULL WHERE id = $1")
+        .bind(cert_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Send expiry reminders now and then every `interval`.
+pub fn spawn_expiry_reminders(pool: PgPool, smtp: SmtpConfig, from: String, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match send_expiry_reminders(&pool, &smtp, &from).await {
+                Ok(0) => {}
+                Ok(n) => info!("sent {n} certificate expiry reminder(s)"),
+                Err(e) => error!("certificate expiry reminders failed: {e}"),
+            }
+        }
+    });
+}
+
+/// Subject and body of the reminder for certificate `serial` of `namespace`.
+fn reminder_text(namespace: &str, serial: i64, expires_at: DateTime<Utc>) -> (String, String) {
+    let date = expires_at.format("%Y-%m-%d");
+    (
+        format!("Your skreg certificate for {namespace} expires on {date}"),
+        format!(
+            "Publisher certificate {serial} of namespace \"{namespace}\" expires on \
+             {date}. Packages cannot be published with it after that.\n\n\
+             Renew it with the same key by running:\n\n    skreg certify renew\n\n\
+             If you no longer publish with this key, ignore this email."
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reminder_names_the_certificate_and_the_renew_command() {
+        let expires_at = DateTime::parse_from_rfc3339("2026-11-02T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let (subject, body) = reminder_text("acme", 42, expires_at);
+        assert_eq!(
+            subject,
+            "Your skreg certificate for acme expires on 2026-11-02"
+        );
+        assert!(body.contains("certificate 42"));
+        assert!(body.contains("skreg certify renew"));
+    }
+}
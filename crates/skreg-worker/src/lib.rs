@@ -2,6 +2,10 @@
 #![deny(warnings, clippy::all, clippy::pedantic)]
 #![warn(missing_docs)]
 
+/// Downloaded and unpacked `.skill` tarball shared across stages.
+pub mod artifact;
+/// Scheduled reminders of publisher certificates about to expire.
+pub mod cert_reminders;
 /// Thin async SMTP send helper.
 pub mod email;
 /// Job runner: pg_notify listener and pipeline dispatch.
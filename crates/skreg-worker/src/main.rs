@@ -1,6 +1,8 @@
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     use anyhow::Context as _;
+    use skreg_worker::stages::rescan;
+    use skreg_worker::stages::static_analysis::rule_store::RuleStore;
 
     env_logger::init();
     let database_url = std::env::var("DATABASE_URL")?;
@@ -27,8 +29,87 @@ async fn main() -> anyhow::Result<()> {
         .build();
     let s3 = aws_sdk_s3::Client::from_conf(s3_conf);
 
-    let registry_ca_key_pem =
-        std::env::var("REGISTRY_CA_KEY_PEM").context("REGISTRY_CA_KEY_PEM must be set")?;
+    let registry_key = skreg_worker::stages::signing::RegistryKey {
+        key_pem: std::env::var("REGISTRY_CA_KEY_PEM").context("REGISTRY_CA_KEY_PEM must be set")?,
+        cert_pem: std::env::var("REGISTRY_CA_CERT_PEM")
+            .ok()
+            .filter(|pem| !pem.trim().is_empty()),
+    };
+    if registry_key.cert_pem.is_none() {
+        log::warn!("REGISTRY_CA_CERT_PEM is not set; signature timestamps will not be issued");
+    }
+
+    // Compile YARA rules once; every job shares the result. Skipped entirely
+    // when SKREG_SKIP_STATIC_ANALYSIS=true (dev fast-loop mode).
+    let rules = if std::env::var("SKREG_SKIP_STATIC_ANALYSIS").as_deref() == Ok("true") {
+        log::info!("static analysis disabled (SKREG_SKIP_STATIC_ANALYSIS=true)");
+        None
+    } else {
+        let rules_dir = std::path::PathBuf::from(
+            std::env::var("SKREG_YARA_RULES_DIR")
+                .unwrap_or_else(|_| "crates/skreg-vet/rules".into()),
+        );
+        let store = RuleStore::load(&rules_dir).context("compiling YARA rules")?;
+        let store = std::sync::Arc::new(store);
+        // Optional hot reload: poll the rules directory every N seconds.
+        if let Some(secs) = std::env::var("SKREG_YARA_RELOAD_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|&s| s > 0)
+        {
+            store.spawn_hot_reload(std::time::Duration::from_secs(secs));
+        }
+        Some(store)
+    };
+
+    // `skreg-worker rescan [--all]`: admin trigger that enqueues rescan jobs
+    // for the running workers and exits. Without `--all`, only versions
+    // scanned against an older rule pack are selected.
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("rescan") {
+        let all = args.any(|a| a == "--all");
+        let stale_against = match (&rules, all) {
+            (_, true) => None,
+            (Some(store), false) => Some(store.current().version().to_owned()),
+            (None, false) => anyhow::bail!("rescan requires static analysis to be enabled"),
+        };
+        let n = rescan::enqueue_rescans(&pool, stale_against.as_deref()).await?;
+        log::info!("enqueued {n} rescan job(s)");
+        return Ok(());
+    }
+
+    if rules.is_some() {
+        // Python and JS scripts only run in the WASM sandbox, so refuse to
+        // start without its interpreter modules unless
+        // SKREG_SKIP_WASM_SANDBOX=true.
+        skreg_worker::stages::static_analysis::wasm_sandbox::WasmSandbox::shared()
+            .context("loading the WASM sandbox")?;
+    }
+
+    if let Some(store) = &rules {
+        // Sweep the back catalogue at startup and, optionally, on an interval.
+        let interval = std::env::var("SKREG_RESCAN_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|&s| s > 0)
+            .map(std::time::Duration::from_secs);
+        rescan::spawn_sweep(pool.clone(), std::sync::Arc::clone(store), interval);
+    }
+
+    // Remind publishers of expiring certificates daily unless configured
+    // otherwise; an interval of 0 disables the reminders.
+    let reminder_secs = std::env::var("SKREG_CERT_REMINDER_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(24 * 60 * 60);
+    if reminder_secs > 0 {
+        skreg_worker::cert_reminders::spawn_expiry_reminders(
+            pool.clone(),
+            smtp.clone(),
+            from_email.clone(),
+            std::time::Duration::from_secs(reminder_secs),
+        );
+    }
 
-    skreg_worker::runner::run(pool, s3, smtp, from_email, bucket, registry_ca_key_pem).await
+    skreg_worker::runner::run(pool, s3, smtp, from_email, bucket, registry_key, rules).await
 }
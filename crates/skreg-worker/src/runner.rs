@@ -1,4 +1,10 @@
 //! Job runner: listens on `pg_notify("vetting_jobs")` and dispatches stage pipeline.
+//!
+//! Rescan jobs are announced on a separate channel and drained sequentially
+//! by a single background task so a sweep never floods the worker.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use anyhow::Result;
 use aws_sdk_s3::Client as S3Client;
@@ -7,20 +13,31 @@ use sqlx::postgres::PgListener;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::stages::rescan::{run_rescan, RescanOutcome, RESCAN_CHANNEL};
 use crate::stages::run_pipeline;
+use crate::stages::signing::RegistryKey;
+use crate::stages::static_analysis::rule_store::RuleStore;
 
 /// Shared configuration threaded through the job pipeline.
-struct JobCtx<'a> {
-    pool: &'a PgPool,
-    s3: &'a S3Client,
-    smtp: &'a crate::email::SmtpConfig,
-    from_email: &'a str,
-    bucket: &'a str,
-    registry_ca_key_pem: &'a str,
+struct JobCtx {
+    pool: PgPool,
+    s3: S3Client,
+    smtp: crate::email::SmtpConfig,
+    from_email: String,
+    bucket: String,
+    registry_key: RegistryKey,
+    rules: Option<Arc<RuleStore>>,
+    /// Held by the task currently draining rescan jobs.
+    rescan_lock: tokio::sync::Mutex<()>,
+    /// Set when a rescan notification arrives while a drain is running.
+    rescan_requested: AtomicBool,
 }
 
 /// Start the `pg_notify` listener loop. Blocks until a fatal error occurs.
 ///
+/// `rules` holds the YARA rule pack compiled at startup; `None` disables
+/// static analysis and rescans.
+///
 /// # Errors
 ///
 /// Returns an error if the initial database connection or listener setup fails.
@@ -30,44 +47,45 @@ pub async fn run(
     smtp: crate::email::SmtpConfig,
     from_email: String,
     bucket: String,
-    registry_ca_key_pem: String,
+    registry_key: RegistryKey,
+    rules: Option<Arc<RuleStore>>,
 ) -> Result<()> {
+    let ctx = Arc::new(JobCtx {
+        pool,
+        s3,
+        smtp,
+        from_email,
+        bucket,
+        registry_key,
+        rules,
+        rescan_lock: tokio::sync::Mutex::new(()),
+        rescan_requested: AtomicBool::new(false),
+    });
+
     // Process any jobs already pending in the DB before entering the listen loop.
     // This handles the timing gap where pg_notify fires before this process starts.
-    drain_pending(&JobCtx {
-        pool: &pool,
-        s3: &s3,
-        smtp: &smtp,
-        from_email: &from_email,
-        bucket: &bucket,
-        registry_ca_key_pem: &registry_ca_key_pem,
-    })
-    .await;
-
-    let mut listener = PgListener::connect_with(&pool).await?;
-    listener.listen("vetting_jobs").await?;
-    info!("worker listening on vetting_jobs channel");
+    drain_pending(&ctx).await;
+
+    let mut listener = PgListener::connect_with(&ctx.pool).await?;
+    listener
+        .listen_all(["vetting_jobs", RESCAN_CHANNEL])
+        .await?;
+    info!("worker listening on vetting_jobs and {RESCAN_CHANNEL} channels");
+
+    // Rescans enqueued before we started listening.
+    spawn_rescan_drain(&ctx);
 
     loop {
         let notification = listener.recv().await?;
+        if notification.channel() == RESCAN_CHANNEL {
+            spawn_rescan_drain(&ctx);
+            continue;
+        }
         let payload = notification.payload();
         match Uuid::parse_str(payload) {
             Ok(job_id) => {
-                let pool2 = pool.clone();
-                let s3_2 = s3.clone();
-                let smtp2 = smtp.clone();
-                let from2 = from_email.clone();
-                let bucket2 = bucket.clone();
-                let pem2 = registry_ca_key_pem.clone();
+                let ctx = Arc::clone(&ctx);
                 tokio::spawn(async move {
-                    let ctx = JobCtx {
-                        pool: &pool2,
-                        s3: &s3_2,
-                        smtp: &smtp2,
-                        from_email: &from2,
-                        bucket: &bucket2,
-                        registry_ca_key_pem: &pem2,
-                    };
                     if let Err(e) = process_job(job_id, &ctx).await {
                         error!("job {job_id} failed: {e}");
                     }
@@ -78,12 +96,13 @@ pub async fn run(
     }
 }
 
-/// Process jobs already sitting in `pending` state — handles startup timing gaps.
-async fn drain_pending(ctx: &JobCtx<'_>) {
+/// Process publish jobs already sitting in `pending` state — handles startup timing gaps.
+async fn drain_pending(ctx: &JobCtx) {
     let ids: Vec<Uuid> = match sqlx::query_scalar(
-        "SELECT id FROM vetting_jobs WHERE status = 'pending' ORDER BY created_at",
+        "SELECT id FROM vetting_jobs WHERE status = 'pending' AND kind = 'publish'
+         ORDER BY created_at",
     )
-    .fetch_all(ctx.pool)
+    .fetch_all(&ctx.pool)
     .await
     {
         Ok(ids) => ids,
@@ -100,10 +119,64 @@ async fn drain_pending(ctx: &JobCtx<'_>) {
     }
 }
 
-async fn process_job(job_id: Uuid, ctx: &JobCtx<'_>) -> Result<()> {
+/// Drain pending rescan jobs in the background unless a drain is already running.
+fn spawn_rescan_drain(ctx: &Arc<JobCtx>) {
+    if ctx.rules.is_none() {
+        return;
+    }
+    ctx.rescan_requested.store(true, Ordering::SeqCst);
+    let ctx = Arc::clone(ctx);
+    tokio::spawn(async move {
+        let Ok(_guard) = ctx.rescan_lock.try_lock() else {
+            return;
+        };
+        while ctx.rescan_requested.swap(false, Ordering::SeqCst) {
+            drain_rescans(&ctx).await;
+        }
+    });
+}
+
+/// Process pending rescan jobs one at a time until none remain.
+///
+/// Each job is attempted at most once per drain. A job another replica holds
+/// the lock for, or one that could not be marked failed, stays pending and is
+/// left to a later drain instead of being picked up again in a tight loop.
+async fn drain_rescans(ctx: &JobCtx) {
+    let mut attempted: Vec<Uuid> = Vec::new();
+    loop {
+        let ids: Vec<Uuid> = match sqlx::query_scalar(
+            "SELECT id FROM vetting_jobs WHERE status = 'pending' AND kind = 'rescan'
+               AND id <> ALL($1)
+             ORDER BY created_at LIMIT 100",
+        )
+        .bind(&attempted)
+        .fetch_all(&ctx.pool)
+        .await
+        {
+            Ok(ids) => ids,
+            Err(e) => {
+                error!("rescan drain query failed: {e}");
+                return;
+            }
+        };
+        if ids.is_empty() {
+            return;
+        }
+        info!("rescanning {} version(s)", ids.len());
+        for &job_id in &ids {
+            if let Err(e) = process_job(job_id, ctx).await {
+                error!("rescan job {job_id} failed: {e}");
+            }
+        }
+        attempted.extend(ids);
+    }
+}
+
+async fn process_job(job_id: Uuid, ctx: &JobCtx) -> Result<()> {
+    let lock_key = i64::from_ne_bytes(job_id.as_bytes()[..8].try_into()?);
     let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
-        .bind(i64::from_ne_bytes(job_id.as_bytes()[..8].try_into()?))
-        .fetch_one(ctx.pool)
+        .bind(lock_key)
+        .fetch_one(&ctx.pool)
         .await?;
 
     if !locked {
@@ -111,14 +184,65 @@ async fn process_job(job_id: Uuid, ctx: &JobCtx<'_>) -> Result<()> {
         return Ok(());
     }
 
-    info!("processing job {job_id}");
+    let result = dispatch_job(job_id, ctx).await;
+
+    sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(lock_key)
+        .execute(&ctx.pool)
+        .await?;
+
+    result
+}
+
+async fn dispatch_job(job_id: Uuid, ctx: &JobCtx) -> Result<()> {
+    let (kind, status): (String, String) =
+        sqlx::query_as("SELECT kind, status FROM vetting_jobs WHERE id = $1")
+            .bind(job_id)
+            .fetch_one(&ctx.pool)
+            .await?;
+
+    if status != "pending" {
+        info!("job {job_id} already {status}, skipping");
+        return Ok(());
+    }
+
+    info!("processing {kind} job {job_id}");
+
+    // Snapshot the rules so a hot reload cannot change them mid-job.
+    let rules = ctx.rules.as_deref().map(RuleStore::current);
+    let rule_pack_version = rules.as_ref().map(|r| r.version().to_owned());
+
+    if kind == "rescan" {
+        let Some(rules) = rules else {
+            anyhow::bail!("rescan job {job_id} requires static analysis to be enabled");
+        };
+        return match run_rescan(job_id, &ctx.pool, &ctx.s3, &ctx.bucket, &rules).await {
+            Ok(RescanOutcome::Clean) => {
+                info!("rescan job {job_id} passed");
+                Ok(())
+            }
+            Ok(RescanOutcome::Quarantined { reason }) => {
+                info!("rescan job {job_id} quarantined its version: {reason}");
+                if let Err(e) = send_quarantine_email(job_id, &reason, ctx).await {
+                    error!("failed to send quarantine email for job {job_id}: {e}");
+                }
+                Ok(())
+            }
+            Err(e) => {
+                let msg = e.to_string();
+                error!("rescan job {job_id} error: {msg}");
+                mark_failed(job_id, &msg, rule_pack_version.as_deref(), ctx).await
+            }
+        };
+    }
 
     match run_pipeline(
         job_id,
-        ctx.pool,
-        ctx.s3,
-        ctx.bucket,
-        ctx.registry_ca_key_pem,
+        &ctx.pool,
+        &ctx.s3,
+        &ctx.bucket,
+        &ctx.registry_key,
+        rules.as_deref(),
     )
     .await
     {
@@ -128,13 +252,7 @@ async fn process_job(job_id: Uuid, ctx: &JobCtx<'_>) -> Result<()> {
         Err(e) => {
             let msg = e.to_string();
             error!("job {job_id} pipeline error: {msg}");
-            sqlx::query(
-                "UPDATE vetting_jobs SET status = 'fail', results = $1, completed_at = now() WHERE id = $2",
-            )
-            .bind(serde_json::json!({"message": msg}))
-            .bind(job_id)
-            .execute(ctx.pool)
-            .await?;
+            mark_failed(job_id, &msg, rule_pack_version.as_deref(), ctx).await?;
 
             if let Err(email_err) = send_failure_email(job_id, &msg, ctx).await {
                 error!("failed to send failure email for job {job_id}: {email_err}");
@@ -142,16 +260,28 @@ async fn process_job(job_id: Uuid, ctx: &JobCtx<'_>) -> Result<()> {
         }
     }
 
-    sqlx::query("SELECT pg_advisory_unlock($1)")
-        .bind(i64::from_ne_bytes(job_id.as_bytes()[..8].try_into()?))
-        .execute(ctx.pool)
-        .await?;
+    Ok(())
+}
 
+async fn mark_failed(
+    job_id: Uuid,
+    message: &str,
+    rule_pack_version: Option<&str>,
+    ctx: &JobCtx,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE vetting_jobs SET status = 'fail', results = $1, completed_at = now() WHERE id = $2",
+    )
+    .bind(serde_json::json!({"message": message, "rule_pack_version": rule_pack_version}))
+    .bind(job_id)
+    .execute(&ctx.pool)
+    .await?;
     Ok(())
 }
 
-async fn send_failure_email(job_id: Uuid, message: &str, ctx: &JobCtx<'_>) -> Result<()> {
-    let row: Option<(String, String)> = sqlx::query_as(
+/// Look up the publisher contact address and `ns/name@version` for a job.
+async fn publisher_contact(job_id: Uuid, ctx: &JobCtx) -> Result<Option<(String, String)>> {
+    Ok(sqlx::query_as(
         "SELECT ak.email,
                 n.slug || '/' || p.name || '@' || v.version
          FROM vetting_jobs j
@@ -163,17 +293,42 @@ async fn send_failure_email(job_id: Uuid, message: &str, ctx: &JobCtx<'_>) -> Re
          LIMIT 1",
     )
     .bind(job_id)
-    .fetch_optional(ctx.pool)
-    .await?;
+    .fetch_optional(&ctx.pool)
+    .await?)
+}
+
+async fn send_quarantine_email(job_id: Uuid, reason: &str, ctx: &JobCtx) -> Result<()> {
+    let Some((to_email, pkg_ref)) = publisher_contact(job_id, ctx).await? else {
+        error!("no email found for job {job_id}, skipping quarantine email");
+        return Ok(());
+    };
+
+    crate::email::send_email(
+        &ctx.smtp,
+        &ctx.from_email,
+        &to_email,
+        &format!("{pkg_ref} has been quarantined"),
+        &format!(
+            "A rescan with updated security rules flagged {pkg_ref}: {reason}\n\n\
+             The version is no longer available for download and an advisory \
+             has been published. Please publish a fixed version."
+        ),
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!(e))?;
+
+    Ok(())
+}
 
-    let Some((to_email, pkg_ref)) = row else {
+async fn send_failure_email(job_id: Uuid, message: &str, ctx: &JobCtx) -> Result<()> {
+    let Some((to_email, pkg_ref)) = publisher_contact(job_id, ctx).await? else {
         error!("no email found for job {job_id}, skipping failure email");
         return Ok(());
     };
 
     crate::email::send_email(
-        ctx.smtp,
-        ctx.from_email,
+        &ctx.smtp,
+        &ctx.from_email,
         &to_email,
         &format!("Publishing {pkg_ref} failed"),
         &format!("Publishing {pkg_ref} failed: {message}"),
@@ -1,21 +1,28 @@
 //! Vetting pipeline stages.
 
-pub mod content;
+pub mod rescan;
 pub mod safety;
 pub mod signing;
 pub mod static_analysis;
-pub mod structure;
 pub mod verify_publisher;
 
+pub use skreg_vet::{content, secrets, structure};
+
 use anyhow::Result;
 use aws_sdk_s3::Client as S3Client;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::artifact::Artifact;
+use signing::RegistryKey;
+use static_analysis::pass1::CompiledRules;
 use structure::check_structure;
 
 /// Run the full vetting pipeline for `job_id`.
 ///
+/// The tarball is fetched from S3 once and shared by every stage. `rules` is
+/// the YARA rule pack compiled at worker startup; `None` skips Stage 2.5.
+///
 /// # Errors
 ///
 /// Returns an error if any stage fails or a database/S3 operation fails.
@@ -24,7 +31,8 @@ pub async fn run_pipeline(
     pool: &PgPool,
     s3: &S3Client,
     bucket: &str,
-    registry_ca_key_pem: &str,
+    registry_key: &RegistryKey,
+    rules: Option<&CompiledRules>,
 ) -> Result<()> {
     // Load job + version info (including namespace slug for Stage 4)
     let row = sqlx::query_as::<_, (Uuid, String, String, String, String, String)>(
@@ -40,67 +48,50 @@ pub async fn run_pipeline(
     .await?;
 
     let (version_id, sha256, storage_path, pkg_name, version, namespace_slug) = row;
-
-    // Download tarball from S3 to tempdir
-    let obj = s3
-        .get_object()
-        .bucket(bucket)
-        .key(&storage_path)
-        .send()
-        .await?;
-    let bytes = obj.body.collect().await?.into_bytes();
-    let tmp = skreg_pack::unpack::unpack_to_tempdir(&bytes)?;
+    let rule_pack_version = rules.map(CompiledRules::version);
+
+    // Download and unpack the tarball once; every stage reads from this copy.
+    let artifact = Artifact::fetch(s3, bucket, &storage_path).await?;
+    if !artifact.sha256().eq_ignore_ascii_case(&sha256) {
+        anyhow::bail!(
+            "stored tarball digest {} does not match recorded sha256 {sha256}",
+            artifact.sha256()
+        );
+    }
+    let tmp = artifact.path();
 
     // Stage 1
-    check_structure(tmp.path()).map_err(|e| anyhow::anyhow!("Stage 1 failed: {e}"))?;
+    check_structure(tmp).map_err(|e| anyhow::anyhow!("Stage 1 failed: {e}"))?;
 
     // Stage 2
-    content::check_content(tmp.path()).map_err(|e| anyhow::anyhow!("Stage 2 failed: {e}"))?;
+    content::check_content(tmp).map_err(|e| anyhow::anyhow!("Stage 2 failed: {e}"))?;
 
     // Stage 2.5 — static analysis
-    // Skipped when SKREG_SKIP_STATIC_ANALYSIS=true (dev fast-loop mode).
-    if std::env::var("SKREG_SKIP_STATIC_ANALYSIS").as_deref() == Ok("true") {
-        log::info!("Stage 2.5 skipped (SKREG_SKIP_STATIC_ANALYSIS=true)");
-    } else {
-        let rules_dir = std::path::PathBuf::from(
-            std::env::var("SKREG_YARA_RULES_DIR")
-                .unwrap_or_else(|_| "crates/skreg-worker/rules".into()),
-        );
-        let compiled_rules = static_analysis::startup::check_yara_rules(&rules_dir)
-            .map_err(|e| anyhow::anyhow!("YARA compilation failed: {e}"))?;
-        let tracee_available = std::path::Path::new("/var/run/tracee/tracee.sock").exists();
+    // Skipped when the worker was started with SKREG_SKIP_STATIC_ANALYSIS=true.
+    if let Some(compiled_rules) = rules {
+        let tracee_available =
+            std::path::Path::new(static_analysis::startup::TRACEE_SOCKET).exists();
 
         let sa_findings =
-            static_analysis::run_static_analysis(tmp.path(), &compiled_rules, tracee_available)
+            static_analysis::run_static_analysis(tmp, compiled_rules, tracee_available)
                 .map_err(|e| anyhow::anyhow!("Stage 2.5 failed: {e}"))?;
 
         if sa_findings.iter().any(|f| f.severity.is_blocking()) {
             let results = serde_json::json!({
                 "status": "quarantined",
                 "stage": "static_analysis",
-                "findings": sa_findings.iter().map(|f| serde_json::json!({
-                    "file": f.file,
-                    "tool": f.tool,
-                    "rule_id": f.rule_id,
-                    "severity": format!("{:?}", f.severity),
-                    "message": f.message,
-                })).collect::<Vec<_>>()
+                "rule_pack_version": rule_pack_version,
+                "findings": findings_json(&sa_findings),
             });
-            sqlx::query(
-                "UPDATE vetting_jobs SET status = 'quarantined', completed_at = now(), results = $1 WHERE id = $2"
-            )
-            .bind(sqlx::types::Json(results))
-            .bind(job_id)
-            .execute(pool)
-            .await?;
+            quarantine_at_publish(pool, job_id, version_id, results).await?;
             return Ok(());
         }
+    } else {
+        log::info!("Stage 2.5 skipped (SKREG_SKIP_STATIC_ANALYSIS=true)");
     }
 
-    // Stage 3 — load existing names and yanked versions from DB
-    let existing_names: Vec<String> = sqlx::query_scalar("SELECT name FROM packages")
-        .fetch_all(pool)
-        .await?;
+    // Stage 3 — similar names from other namespaces and yanked versions from DB
+    let similar = safety::similar_packages(pool, version_id).await?;
     let yanked: Vec<(String, String)> = sqlx::query_as(
         "SELECT p.name, v.version FROM versions v
          JOIN packages p ON p.id = v.package_id
@@ -109,40 +100,99 @@ pub async fn run_pipeline(
     .fetch_all(pool)
     .await?;
 
-    safety::check_safety(&pkg_name, &version, &existing_names, &yanked)
+    safety::check_safety(&pkg_name, &version, &similar, &yanked)
         .map_err(|e| anyhow::anyhow!("Stage 3 failed: {e}"))?;
 
     // Stage 4 — verify publisher signature
-    verify_publisher::run_verify_publisher(
+    let log_entry = verify_publisher::run_verify_publisher(
         version_id,
-        &sha256,
-        &storage_path,
+        &artifact,
         &namespace_slug,
+        registry_key,
         pool,
-        s3,
-        bucket,
     )
     .await
     .map_err(|e| anyhow::anyhow!("Stage 4 failed: {e}"))?;
 
     // Stage 5 — sign with registry CA and store .sig in S3
-    let sig_path = signing::run_signing(&sha256, &storage_path, s3, bucket, registry_ca_key_pem)
-        .await
-        .map_err(|e| anyhow::anyhow!("Stage 5 failed: {e}"))?;
+    let sig_path = signing::run_signing(
+        artifact.sha256(),
+        &storage_path,
+        s3,
+        bucket,
+        &registry_key.key_pem,
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("Stage 5 failed: {e}"))?;
 
-    sqlx::query("UPDATE versions SET sig_path = $1 WHERE id = $2")
-        .bind(&sig_path)
-        .bind(version_id)
-        .execute(pool)
-        .await?;
+    // Publish: record the version in the transparency log and pass the job
+    // together, so every installable version has a log entry.
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "UPDATE versions SET sig_path = $1, rule_pack_version = $2,
+         last_scanned_at = CASE WHEN $2::text IS NULL THEN NULL ELSE now() END,
+         log_index = append_log_entry($4)
+         WHERE id = $3",
+    )
+    .bind(&sig_path)
+    .bind(rule_pack_version)
+    .bind(version_id)
+    .bind(sqlx::types::Json(&log_entry))
+    .execute(&mut *tx)
+    .await?;
 
+    let results = serde_json::json!({
+        "message": "all stages passed",
+        "rule_pack_version": rule_pack_version,
+    });
     sqlx::query(
-        "UPDATE vetting_jobs SET status = 'pass', completed_at = now(),
-         results = '{\"message\": \"all stages passed\"}'::jsonb WHERE id = $1",
+        "UPDATE vetting_jobs SET status = 'pass', completed_at = now(), results = $1 WHERE id = $2",
     )
+    .bind(sqlx::types::Json(results))
     .bind(job_id)
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     Ok(())
 }
+
+/// Quarantine a version whose publish-time static analysis found blocking issues.
+async fn quarantine_at_publish(
+    pool: &PgPool,
+    job_id: Uuid,
+    version_id: Uuid,
+    results: serde_json::Value,
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE versions SET quarantined_at = now(), quarantine_reason = $1 WHERE id = $2")
+        .bind("blocking static analysis findings at publish time")
+        .bind(version_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "UPDATE vetting_jobs SET status = 'quarantined', completed_at = now(), results = $1 WHERE id = $2",
+    )
+    .bind(sqlx::types::Json(results))
+    .bind(job_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Serialise static-analysis findings for a job's `results` column.
+fn findings_json(findings: &[static_analysis::Finding]) -> Vec<serde_json::Value> {
+    findings
+        .iter()
+        .map(|f| {
+            serde_json::json!({
+                "file": f.file,
+                "tool": f.tool,
+                "rule_id": f.rule_id,
+                "severity": format!("{:?}", f.severity),
+                "message": f.message,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,212 @@
+//! Retroactive rescan of published versions when the YARA rule pack changes.
+//!
+//! A rescan re-runs the content (Stage 2) and static analysis (Stage 2.5)
+//! checks over an already-passed version. Every scanned version is stamped
+//! with the rule-pack version it was checked against; versions that now fail
+//! are quarantined and an advisory is recorded for installers.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use aws_sdk_s3::Client as S3Client;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::static_analysis::{self, pass1::CompiledRules, rule_store::RuleStore};
+use super::{content, findings_json};
+use crate::artifact::Artifact;
+
+/// `pg_notify` channel that tells workers to drain pending rescan jobs.
+pub const RESCAN_CHANNEL: &str = "vetting_rescan";
+
+/// Result of rescanning a single version.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RescanOutcome {
+    /// The version still passes every check.
+    Clean,
+    /// The version now fails and has been quarantined.
+    Quarantined {
+        /// Human-readable reason recorded on the version and advisory.
+        reason: String,
+    },
+}
+
+/// Insert a pending rescan job for every live, passed version.
+///
+/// With `stale_against` set, only versions whose stamped rule-pack version
+/// differs are selected; with `None`, every eligible version is rescanned.
+/// Versions that already have a pending rescan are skipped. Returns the
+/// number of jobs created and notifies [`RESCAN_CHANNEL`] if any were.
+///
+/// # Errors
+///
+/// Returns an error if a database operation fails.
+pub async fn enqueue_rescans(pool: &PgPool, stale_against: Option<&str>) -> Result<u64> {
+    let inserted = sqlx::query(
+        "INSERT INTO vetting_jobs (version_id, kind)
+         SELECT v.id, 'rescan' FROM versions v
+         WHERE v.yanked_at IS NULL
+           AND v.quarantined_at IS NULL
+           AND ($1::text IS NULL OR v.rule_pack_version IS DISTINCT FROM $1)
+           AND EXISTS (SELECT 1 FROM vetting_jobs j
+                       WHERE j.version_id = v.id AND j.kind = 'publish' AND j.status = 'pass')
+           AND NOT EXISTS (SELECT 1 FROM vetting_jobs j
+                           WHERE j.version_id = v.id AND j.kind = 'rescan' AND j.status = 'pending')",
+    )
+    .bind(stale_against)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if inserted > 0 {
+        sqlx::query("SELECT pg_notify($1, '')")
+            .bind(RESCAN_CHANNEL)
+            .execute(pool)
+            .await?;
+    }
+    Ok(inserted)
+}
+
+/// Enqueue rescans for versions stamped with an older rule pack.
+///
+/// Sweeps once immediately, then every `interval` if given, so a hot-reloaded
+/// rule pack is applied to the back catalogue on the next tick.
+pub fn spawn_sweep(
+    pool: PgPool,
+    rules: Arc<RuleStore>,
+    interval: Option<Duration>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = interval.map(tokio::time::interval);
+        loop {
+            if let Some(t) = ticker.as_mut() {
+                t.tick().await;
+            }
+            let version = rules.current().version().to_owned();
+            match enqueue_rescans(&pool, Some(&version)).await {
+                Ok(0) => {}
+                Ok(n) => log::info!("enqueued {n} rescan job(s) for rule pack {version}"),
+                Err(e) => log::error!("rescan sweep failed: {e}"),
+            }
+            if ticker.is_none() {
+                return;
+            }
+        }
+    })
+}
+
+/// Rescan the version behind rescan job `job_id` against `rules`.
+///
+/// Stamps the version with the rule-pack version, and on failure quarantines
+/// it and records an advisory. The job row is completed in the same
+/// transaction.
+///
+/// # Errors
+///
+/// Returns an error if the artifact cannot be fetched, an analysis tool
+/// fails to run, or a database operation fails. The version is left
+/// unstamped so the next sweep retries it.
+pub async fn run_rescan(
+    job_id: Uuid,
+    pool: &PgPool,
+    s3: &S3Client,
+    bucket: &str,
+    rules: &CompiledRules,
+) -> Result<RescanOutcome> {
+    let (version_id, sha256, storage_path) = sqlx::query_as::<_, (Uuid, String, String)>(
+        "SELECT v.id, v.sha256, v.storage_path
+         FROM vetting_jobs j
+         JOIN versions v ON v.id = j.version_id
+         WHERE j.id = $1",
+    )
+    .bind(job_id)
+    .fetch_one(pool)
+    .await?;
+
+    let artifact = Artifact::fetch(s3, bucket, &storage_path).await?;
+    let mut findings = Vec::new();
+
+    let failure = if artifact.sha256().eq_ignore_ascii_case(&sha256) {
+        if let Err(e) = content::check_content(artifact.path()) {
+            Some(format!("content check failed: {e}"))
+        } else {
+            let tracee_available =
+                std::path::Path::new(static_analysis::startup::TRACEE_SOCKET).exists();
+            findings =
+                static_analysis::run_static_analysis(artifact.path(), rules, tracee_available)
+                    .map_err(|e| anyhow::anyhow!("static analysis failed: {e}"))?;
+            let blocking = findings.iter().filter(|f| f.severity.is_blocking()).count();
+            (blocking > 0).then(|| {
+                format!(
+                    "{blocking} blocking static analysis finding(s) under rule pack {}",
+                    rules.version()
+                )
+            })
+        }
+    } else {
+        Some(format!(
+            "stored tarball digest {} no longer matches recorded sha256 {sha256}",
+            artifact.sha256()
+        ))
+    };
+
+    let findings = findings_json(&findings);
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "UPDATE versions SET rule_pack_version = $1, last_scanned_at = now() WHERE id = $2",
+    )
+    .bind(rules.version())
+    .bind(version_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let (status, outcome) = if let Some(reason) = failure {
+        sqlx::query(
+            "UPDATE versions SET quarantined_at = now(), quarantine_reason = $1 WHERE id = $2",
+        )
+        .bind(&reason)
+        .bind(version_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO advisories
+                 (version_id, package_id, kind, affected, severity, summary,
+                  rule_pack_version, findings)
+             SELECT v.id, v.package_id, 'quarantined', '=' || v.version, 'critical', $2, $3, $4
+             FROM versions v WHERE v.id = $1",
+        )
+        .bind(version_id)
+        .bind(&reason)
+        .bind(rules.version())
+        .bind(sqlx::types::Json(&findings))
+        .execute(&mut *tx)
+        .await?;
+        ("quarantined", RescanOutcome::Quarantined { reason })
+    } else {
+        ("pass", RescanOutcome::Clean)
+    };
+
+    let message = match &outcome {
+        RescanOutcome::Clean => "rescan passed".to_owned(),
+        RescanOutcome::Quarantined { reason } => reason.clone(),
+    };
+    let results = serde_json::json!({
+        "message": message,
+        "stage": "rescan",
+        "rule_pack_version": rules.version(),
+        "findings": findings,
+    });
+    sqlx::query(
+        "UPDATE vetting_jobs SET status = $1, completed_at = now(), results = $2 WHERE id = $3",
+    )
+    .bind(status)
+    .bind(sqlx::types::Json(results))
+    .bind(job_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(outcome)
+}
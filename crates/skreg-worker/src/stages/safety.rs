@@ -1,50 +1,110 @@
 //! Stage 3: safety checks — name squatting and yanked re-upload detection.
+//!
+//! Candidate names are found in Postgres (`pg_trgm` similarity plus the
+//! `name_skeleton` / `name_segment_key` indexes) and then classified with
+//! [`skreg_core::names::classify`]. Packages in the publisher's own namespace
+//! are never compared, and admin-allowlisted pairs are excluded.
 
+use skreg_core::names::{classify, NameMatch};
+use sqlx::PgPool;
 use thiserror::Error;
+use uuid::Uuid;
+
+pub use skreg_core::names::levenshtein;
+
+/// Maximum candidates fetched from the database per check.
+const MAX_CANDIDATES: i64 = 50;
 
 /// Errors returned by [`check_safety`].
 #[derive(Debug, Error)]
 pub enum SafetyError {
     /// The submitted package name is too similar to an existing one.
-    #[error("name '{0}' is too similar to existing package '{1}' (Levenshtein distance {2})")]
-    NameSquatting(String, String, usize),
+    #[error("name '{name}' is too similar to existing package '{existing}' ({reason})")]
+    NameSquatting {
+        /// Submitted package name.
+        name: String,
+        /// `namespace/name` of the package it resembles.
+        existing: String,
+        /// Why the names were judged similar.
+        reason: NameMatch,
+    },
     /// The package version was previously yanked and cannot be re-published.
     #[error("package '{0}' was previously yanked and cannot be re-published at the same version")]
     YankedVersion(String),
 }
 
-/// Compute the Levenshtein edit distance between two strings.
-#[must_use]
-pub fn levenshtein(a: &str, b: &str) -> usize {
-    let a: Vec<char> = a.chars().collect();
-    let b: Vec<char> = b.chars().collect();
-    let (m, n) = (a.len(), b.len());
-    let mut dp = vec![vec![0usize; n + 1]; m + 1];
-    for (i, row) in dp.iter_mut().enumerate() {
-        row[0] = i;
-    }
-    for (j, val) in dp[0].iter_mut().enumerate() {
-        *val = j;
-    }
-    for i in 1..=m {
-        for j in 1..=n {
-            dp[i][j] = if a[i - 1] == b[j - 1] {
-                dp[i - 1][j - 1]
-            } else {
-                1 + dp[i - 1][j].min(dp[i][j - 1]).min(dp[i - 1][j - 1])
-            };
-        }
-    }
-    dp[m][n]
+/// An existing package in another namespace whose name resembles the one
+/// being published.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SimilarName {
+    /// Owning namespace slug.
+    pub namespace: String,
+    /// Package name.
+    pub name: String,
+    /// Whether the owning namespace is a verified organisation (an `org`
+    /// namespace holding an unrevoked publisher certificate).
+    pub verified: bool,
 }
 
-/// Return `true` if `name` is within Levenshtein distance 2 of any existing name.
+/// Fetch packages from other namespaces whose names resemble the package
+/// behind `version_id`.
+///
+/// Only packages created before it are considered, so an established package
+/// is never blocked by a newer lookalike. Allowlisted pairs are excluded.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn similar_packages(
+    pool: &PgPool,
+    version_id: Uuid,
+) -> Result<Vec<SimilarName>, sqlx::Error> {
+    sqlx::query_as(
+        "WITH me AS (
+             SELECT p.name, p.namespace_id, p.created_at, n.slug
+             FROM versions v
+             JOIN packages p ON p.id = v.package_id
+             JOIN namespaces n ON n.id = p.namespace_id
+             WHERE v.id = $1
+         )
+         SELECT n.slug AS namespace, p.name,
+                (n.kind = 'org' AND EXISTS (
+                    SELECT 1 FROM publisher_certs pc
+                    WHERE pc.namespace_id = n.id AND pc.revoked_at IS NULL)) AS verified
+         FROM me, packages p
+         JOIN namespaces n ON n.id = p.namespace_id
+         WHERE p.namespace_id <> me.namespace_id
+           AND p.created_at < me.created_at
+           AND n.banned_at IS NULL
+           AND (p.name % me.name
+                OR name_skeleton(p.name) = name_skeleton(me.name)
+                OR name_segment_key(p.name) = name_segment_key(me.name))
+           AND NOT EXISTS (
+                SELECT 1 FROM name_allowlist a
+                WHERE a.kind = 'package'
+                  AND ((a.name = me.slug || '/' || me.name AND a.similar_to = n.slug || '/' || p.name)
+                    OR (a.similar_to = me.slug || '/' || me.name AND a.name = n.slug || '/' || p.name)))
+         ORDER BY similarity(p.name, me.name) DESC
+         LIMIT $2",
+    )
+    .bind(version_id)
+    .bind(MAX_CANDIDATES)
+    .fetch_all(pool)
+    .await
+}
+
+/// Return the first candidate `name` squats on, with the reason.
+///
+/// Names owned by verified organisations are protected against any close
+/// match; other namespaces only against confusable or reordered names.
 #[must_use]
-pub fn is_squatting(name: &str, existing: &[String]) -> bool {
-    existing.iter().any(|e| {
-        let dist = levenshtein(name, e);
-        dist > 0 && dist <= 2
-    })
+pub fn find_squatted<'a>(
+    name: &str,
+    candidates: &'a [SimilarName],
+) -> Option<(&'a SimilarName, NameMatch)> {
+    candidates
+        .iter()
+        .find_map(|c| classify(name, &c.name, c.verified).map(|m| (c, m)))
 }
 
 /// Run Stage 3 safety checks.
@@ -56,20 +116,16 @@ pub fn is_squatting(name: &str, existing: &[String]) -> bool {
 pub fn check_safety(
     name: &str,
     version: &str,
-    existing_names: &[String],
+    candidates: &[SimilarName],
     yanked_versions: &[(String, String)],
 ) -> Result<(), SafetyError> {
     // Squatting check
-    if let Some(existing) = existing_names.iter().find(|e| {
-        let d = levenshtein(name, e);
-        d > 0 && d <= 2
-    }) {
-        let dist = levenshtein(name, existing);
-        return Err(SafetyError::NameSquatting(
-            name.to_owned(),
-            existing.clone(),
-            dist,
-        ));
+    if let Some((existing, reason)) = find_squatted(name, candidates) {
+        return Err(SafetyError::NameSquatting {
+            name: name.to_owned(),
+            existing: format!("{}/{}", existing.namespace, existing.name),
+            reason,
+        });
     }
 
     // Yanked re-upload check
@@ -87,6 +143,14 @@ pub fn check_safety(
 mod tests {
     use super::*;
 
+    fn candidate(name: &str, verified: bool) -> SimilarName {
+        SimilarName {
+            namespace: "other".to_owned(),
+            name: name.to_owned(),
+            verified,
+        }
+    }
+
     #[test]
     fn levenshtein_identical_is_zero() {
         assert_eq!(levenshtein("abc", "abc"), 0);
@@ -103,15 +167,35 @@ mod tests {
     }
 
     #[test]
-    fn squatting_detected_within_two() {
-        assert!(is_squatting("reakt", &["react".to_owned()]));
+    fn squatting_detected_against_verified_org() {
+        let result = check_safety("reakt", "1.0.0", &[candidate("react", true)], &[]);
+        assert!(matches!(
+            result,
+            Err(SafetyError::NameSquatting {
+                reason: NameMatch::EditDistance(1),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn near_names_allowed_across_unverified_namespaces() {
+        assert!(check_safety("lints", "1.0.0", &[candidate("lint", false)], &[]).is_ok());
+    }
+
+    #[test]
+    fn homoglyphs_detected_in_any_namespace() {
+        let candidates = [candidate("model", false)];
+        let hit = find_squatted("rnodel", &candidates);
+        assert!(matches!(hit, Some((_, NameMatch::Confusable))));
     }
 
     #[test]
-    fn no_squatting_when_clear() {
-        assert!(!is_squatting(
-            "my-unique-skill-name-xyz",
-            &["react".to_owned()]
+    fn yanked_version_rejected() {
+        let yanked = [("helper".to_owned(), "1.0.0".to_owned())];
+        assert!(matches!(
+            check_safety("helper", "1.0.0", &[], &yanked),
+            Err(SafetyError::YankedVersion(_))
         ));
     }
 }
@@ -1,13 +1,61 @@
 //! Stage 5: sign tarball sha256 using the provided CA private key PEM, write .sig to S3.
+//!
+//! The same key issues the signature timestamps recorded in Stage 4.
 
 use anyhow::{Context, Result};
 use aws_sdk_s3::Client as S3Client;
+use chrono::{DateTime, Utc};
 use rsa::pkcs1::DecodeRsaPrivateKey;
 use rsa::pkcs1v15::SigningKey;
-use rsa::signature::hazmat::PrehashSigner;
+use rsa::pss::BlindedSigningKey;
+use rsa::signature::hazmat::{PrehashSigner, RandomizedPrehashSigner};
 use rsa::signature::SignatureEncoding;
 use rsa::RsaPrivateKey;
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
+use skreg_core::timestamp::{SignedTimestamp, Timestamp};
+
+/// The registry CA key the worker signs with.
+pub struct RegistryKey {
+    /// PKCS#1 PEM private key (`REGISTRY_CA_KEY_PEM`).
+    pub key_pem: String,
+    /// PEM certificate for the key (`REGISTRY_CA_CERT_PEM`). Timestamps are
+    /// only issued when it is set, since clients need it to verify them.
+    pub cert_pem: Option<String>,
+}
+
+/// Issue a timestamp proving `publisher_sig` existed at `signed_at`.
+///
+/// Returns `None` if `key` has no certificate to present with it.
+///
+/// # Errors
+///
+/// Returns an error if the key cannot be parsed or signing fails.
+pub fn issue_timestamp(
+    key: &RegistryKey,
+    publisher_sig: &[u8],
+    signed_at: DateTime<Utc>,
+) -> Result<Option<SignedTimestamp>> {
+    let Some(cert_pem) = &key.cert_pem else {
+        return Ok(None);
+    };
+    let private_key =
+        RsaPrivateKey::from_pkcs1_pem(&key.key_pem).context("parsing RSA private key PEM")?;
+    let timestamp = serde_json::to_string(&Timestamp {
+        imprint: Timestamp::imprint_of(publisher_sig),
+        signed_at,
+    })?;
+    let signature = BlindedSigningKey::<Sha256>::new(private_key)
+        .sign_prehash_with_rng(
+            &mut rand::thread_rng(),
+            &Sha256::digest(timestamp.as_bytes()),
+        )
+        .context("signing timestamp")?;
+    Ok(Some(SignedTimestamp {
+        timestamp,
+        signature_hex: hex::encode(signature.to_bytes()),
+        signer_cert_pem: cert_pem.clone(),
+    }))
+}
 
 /// Sign `data` (a pre-computed hash) with `signing_key` using RSA PKCS#1v1.5 + SHA-256.
 ///
@@ -83,4 +131,33 @@ mod tests {
         let sig_obj = rsa::pkcs1v15::Signature::try_from(sig.as_slice()).unwrap();
         assert!(verifying_key.verify_prehash(data, &sig_obj).is_ok());
     }
+
+    #[test]
+    fn timestamp_is_issued_only_with_a_cert_and_covers_the_signature() {
+        use rsa::pkcs1::EncodeRsaPrivateKey;
+        use rsa::pss::{Signature, VerifyingKey};
+
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+        let mut key = RegistryKey {
+            key_pem: private_key
+                .to_pkcs1_pem(rsa::pkcs8::LineEnding::LF)
+                .unwrap()
+                .to_string(),
+            cert_pem: None,
+        };
+        let signed_at = Utc::now();
+        assert!(issue_timestamp(&key, b"sig", signed_at).unwrap().is_none());
+
+        key.cert_pem = Some("registry cert".to_owned());
+        let token = issue_timestamp(&key, b"sig", signed_at).unwrap().unwrap();
+        let body: Timestamp = serde_json::from_str(&token.timestamp).unwrap();
+        assert_eq!(body.imprint, Timestamp::imprint_of(b"sig"));
+        assert_eq!(body.signed_at, signed_at);
+
+        let sig =
+            Signature::try_from(hex::decode(&token.signature_hex).unwrap().as_slice()).unwrap();
+        assert!(VerifyingKey::<Sha256>::new(private_key.to_public_key())
+            .verify_prehash(&Sha256::digest(token.timestamp.as_bytes()), &sig)
+            .is_ok());
+    }
 }
@@ -1,104 +1,20 @@
 //! Stage: Static Analysis — YARA + language-specific tools.
+//!
+//! Pass 1 and the hook scan live in `skreg-vet` so `skreg check` can run them
+//! locally; this module adds the sandboxed Pass 2 tools.
 
-pub mod hooks;
-pub mod pass1;
+pub mod nsjail;
 pub mod pass2;
+pub mod rule_store;
 pub mod startup;
+pub mod wasm_sandbox;
 
-use std::path::Path;
-
-use thiserror::Error;
-
-/// A single finding from any analysis tool.
-#[derive(Debug, Clone)]
-pub struct Finding {
-    /// Relative path of the file that triggered the finding.
-    pub file: String,
-    /// Which tool produced this finding.
-    pub tool: String,
-    /// Tool-specific rule or check identifier.
-    pub rule_id: String,
-    /// Normalised severity.
-    pub severity: Severity,
-    /// Human-readable description (internal use only — never sent to publishers).
-    pub message: String,
-}
-
-/// Normalised severity across all tools.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Severity {
-    /// Finding blocks publishing.
-    Error,
-    /// Advisory — recorded but does not block publishing.
-    Warning,
-    /// Informational — recorded but does not block.
-    Info,
-}
-
-impl Severity {
-    /// Returns `true` if this severity causes rejection.
-    #[must_use]
-    pub fn is_blocking(&self) -> bool {
-        matches!(self, Severity::Error)
-    }
-}
-
-/// YARA rule identifiers that are advisory rather than rejecting. These detect
-/// operations that are legitimate in many skills — secure file shredding,
-/// scp/ssh transfer, sudo — so they warn rather than block. This mirrors the
-/// `severity = "Warning"` metadata in the corresponding `.yar` files, which
-/// yara-x does not expose on match results.
-pub const WARNING_RULES: &[&str] = &[
-    "destructive_ops",
-    "network_transfer",
-    "privilege_escalation_sudo",
-];
-
-/// Map a matched YARA rule identifier to its pipeline severity.
-#[must_use]
-pub fn severity_for_rule(rule_id: &str) -> Severity {
-    if WARNING_RULES.contains(&rule_id) {
-        Severity::Warning
-    } else {
-        Severity::Error
-    }
-}
-
-/// Trait implemented by every analysis tool adapter.
-pub trait Analyzer: Send + Sync {
-    /// Run the analyser against `file` and return a list of findings.
-    ///
-    /// # Errors
-    ///
-    /// Returns `Err` on subprocess failure, timeout, or I/O error (distinct
-    /// from a non-empty findings list, which is a normal result).
-    fn analyze(&self, file: &Path) -> Result<Vec<Finding>, StaticAnalysisError>;
-}
+pub use skreg_vet::static_analysis::{
+    hooks, pass1, run_yara_and_hooks, severity_for_rule, Analyzer, Finding, Severity,
+    StaticAnalysisError, WARNING_RULES,
+};
 
-/// Errors produced by the static analysis stage.
-#[derive(Debug, Error)]
-pub enum StaticAnalysisError {
-    /// A YARA rule file failed to compile.
-    #[error("YARA compilation error: {0}")]
-    YaraCompilation(String),
-    /// A subprocess tool was not found or failed to execute.
-    #[error("tool '{tool}' failed: {reason}")]
-    ToolError {
-        /// Name of the tool that failed.
-        tool: String,
-        /// Reason for the failure.
-        reason: String,
-    },
-    /// A subprocess exceeded its per-file timeout.
-    #[error("tool '{0}' timed out")]
-    Timeout(String),
-    /// A required tool or resource is missing at startup.
-    #[error("missing required component: {0}")]
-    MissingComponent(String),
-    /// An I/O error occurred.
-    #[error("I/O error: {0}")]
-    Io(#[from] std::io::Error),
-}
+use std::path::Path;
 
 /// Entry point for the static analysis stage.
 ///
@@ -157,80 +73,10 @@ pub fn run_static_analysis(
     Ok(all_findings)
 }
 
-/// Run Pass 1 (YARA magic-byte check + YARA rules) and hook command scan on
-/// every file in `path`, without invoking Pass 2 subprocess tools.
-///
-/// Use this when the full subprocess toolchain (shellcheck, bandit, semgrep,
-/// tracee) is unavailable. Structure and content stages are not run here —
-/// callers are responsible for running those first.
-///
-/// Returns the full list of findings; callers decide whether to reject based
-/// on blocking severity.
-///
-/// # Errors
-///
-/// Returns `Err` on I/O failure, YARA scanner error, or walkdir error.
-pub fn run_yara_and_hooks(
-    path: &Path,
-    rules: &pass1::CompiledRules,
-) -> Result<Vec<Finding>, StaticAnalysisError> {
-    let mut all_findings: Vec<Finding> = Vec::new();
-
-    for entry in walkdir::WalkDir::new(path).follow_links(false) {
-        let entry = entry.map_err(|e| std::io::Error::other(e.to_string()))?;
-        if entry.file_type().is_dir() {
-            continue;
-        }
-        let abs = entry.path();
-        let rel = abs
-            .strip_prefix(path)
-            .map_err(|e| std::io::Error::other(e.to_string()))?;
-
-        let file_findings = pass1::run_pass1(abs, rel, rules)?;
-        let blocking = file_findings.iter().any(|f| f.severity.is_blocking());
-        all_findings.extend(file_findings);
-        if blocking {
-            return Ok(all_findings);
-        }
-    }
-
-    let hook_findings = hooks::scan_hook_commands(path, rules)?;
-    all_findings.extend(hook_findings);
-
-    Ok(all_findings)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn error_severity_is_blocking() {
-        assert!(Severity::Error.is_blocking());
-    }
-
-    #[test]
-    fn warning_severity_is_not_blocking() {
-        assert!(!Severity::Warning.is_blocking());
-    }
-
-    #[test]
-    fn advisory_rules_map_to_warning() {
-        assert_eq!(severity_for_rule("destructive_ops"), Severity::Warning);
-        assert_eq!(severity_for_rule("network_transfer"), Severity::Warning);
-        assert_eq!(
-            severity_for_rule("privilege_escalation_sudo"),
-            Severity::Warning
-        );
-        assert_eq!(severity_for_rule("aws_credential_harvest"), Severity::Error);
-        assert!(!severity_for_rule("destructive_ops").is_blocking());
-    }
-
-    #[test]
-    fn info_severity_is_not_blocking() {
-        assert!(!Severity::Info.is_blocking());
-    }
-
     #[test]
     fn findings_with_only_info_do_not_block() {
         let findings = [Finding {
@@ -305,8 +151,8 @@ mod tests {
         ).unwrap();
         fs::write(dir.path().join("manifest.json"), "{}").unwrap();
 
-        let rules_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("rules");
-        let rules = pass1::compile_rules(&rules_dir).expect("rules compile");
+        let rules_dir = std::path::Path::new(skreg_vet::RULES_DIR);
+        let rules = pass1::compile_rules(rules_dir).expect("rules compile");
 
         let findings = run_static_analysis(dir.path(), &rules, false).unwrap();
         assert!(
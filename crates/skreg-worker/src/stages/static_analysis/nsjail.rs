@@ -0,0 +1,733 @@
+//! nsjail dynamic execution for shell and Ruby scripts.
+//!
+//! Scripts run once inside nsjail with a fresh network namespace (no
+//! interfaces besides an unconfigured loopback), a read-only bind of the host
+//! root, a writable scratch directory, a seccomp policy and rlimits. Syscall
+//! activity is collected through a pluggable [`EventSource`]: the tracee eBPF
+//! socket when present, otherwise `strace` attached from outside the jail.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use super::startup::TRACEE_SOCKET;
+use super::{Analyzer, Finding, Severity, StaticAnalysisError};
+
+/// Guest path of the per-run scratch directory containing the script.
+const SCRATCH_GUEST_DIR: &str = "/sandbox";
+
+/// Wall-clock limit passed to nsjail, in seconds.
+const TIME_LIMIT_SECS: u32 = 30;
+
+/// Maximum distinct events reported per script.
+const MAX_EVENTS: usize = 100;
+
+/// Seccomp policy (kafel syntax). Everything not listed is allowed; the
+/// listed syscalls fail with `EPERM` so the attempt is visible in the trace.
+const SECCOMP_POLICY: &str = "ERRNO(1) { ptrace, process_vm_readv, process_vm_writev, \
+    mount, umount2, pivot_root, chroot, unshare, setns, bpf, perf_event_open, \
+    init_module, finit_module, delete_module, kexec_load, kexec_file_load, \
+    keyctl, add_key, request_key, reboot, swapon, swapoff, acct, \
+    open_by_handle_at, userfaultfd } DEFAULT ALLOW";
+
+/// Host path prefixes a script may legitimately read inside the jail.
+const READ_ALLOWED_PREFIXES: &[&str] = &[
+    SCRATCH_GUEST_DIR,
+    "/tmp",
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib64",
+    "/dev/null",
+    "/dev/zero",
+    "/dev/urandom",
+    "/dev/tty",
+    "/proc/self",
+    "/etc/ld.so",
+    "/etc/localtime",
+    "/etc/nsswitch.conf",
+    "/etc/inputrc",
+    "/etc/bash.bashrc",
+    "/etc/profile",
+    "/etc/locale",
+    "/etc/ssl",
+];
+
+/// Host path prefixes a script may write to inside the jail.
+const WRITE_ALLOWED_PREFIXES: &[&str] = &[SCRATCH_GUEST_DIR, "/tmp", "/dev/null", "/dev/tty"];
+
+/// Programs whose execution indicates an attempt to reach the network.
+const NETWORK_TOOLS: &[&str] = &[
+    "curl", "wget", "nc", "ncat", "netcat", "socat", "ssh", "scp", "sftp", "telnet", "ftp", "rsync",
+];
+
+/// Resource limits applied inside the jail.
+#[derive(Debug, Clone, Copy)]
+pub struct JailLimits {
+    /// Address-space limit in MiB.
+    pub address_space_mb: u32,
+    /// CPU time limit in seconds.
+    pub cpu_secs: u32,
+    /// Largest file the script may create, in MiB.
+    pub file_size_mb: u32,
+    /// Maximum open descriptors.
+    pub open_files: u32,
+    /// Maximum processes for the jail user.
+    pub processes: u32,
+}
+
+impl Default for JailLimits {
+    fn default() -> Self {
+        Self {
+            address_space_mb: 512,
+            cpu_secs: 10,
+            file_size_mb: 16,
+            open_files: 64,
+            processes: 32,
+        }
+    }
+}
+
+/// A syscall-level event observed while the script ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyscallEvent {
+    /// Syscall or tracee event name (e.g. `connect`, `security_file_open`).
+    pub syscall: String,
+    /// What was observed.
+    pub kind: EventKind,
+    /// `true` if the kernel or seccomp policy refused the call.
+    pub denied: bool,
+}
+
+/// Normalised category of a [`SyscallEvent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    /// A program was executed.
+    Exec(String),
+    /// A path was opened.
+    Open {
+        /// Absolute path inside the jail.
+        path: String,
+        /// Whether the open requested write access.
+        write: bool,
+    },
+    /// A socket operation; carries the address when known.
+    Network(String),
+    /// Any other syscall worth reporting (only recorded when denied).
+    Other(String),
+}
+
+/// Source of syscall events for a single jailed run.
+pub trait EventSource: Send + Sync {
+    /// Short name used in logs and findings.
+    fn name(&self) -> &'static str;
+
+    /// Start collecting events for a run whose jail hostname is `hostname`,
+    /// and return the command to launch in place of `jail`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the collector cannot be attached.
+    fn attach(
+        &self,
+        jail: Command,
+        hostname: &str,
+        scratch: &Path,
+    ) -> Result<(Command, Box<dyn EventCollector>), StaticAnalysisError>;
+}
+
+/// Collector returned by [`EventSource::attach`], drained once the jail exits.
+pub trait EventCollector: Send {
+    /// Stop collecting and return the observed events.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the event stream cannot be read.
+    fn finish(self: Box<Self>) -> Result<Vec<SyscallEvent>, StaticAnalysisError>;
+}
+
+/// Select the best available event source: tracee when its socket is
+/// present (at `SKREG_TRACEE_SOCKET`, default [`TRACEE_SOCKET`]), otherwise
+/// the `strace` fallback.
+///
+/// # Errors
+///
+/// Returns `StaticAnalysisError::MissingComponent` if neither is available.
+pub fn default_event_source(
+    tracee_available: bool,
+) -> Result<Box<dyn EventSource>, StaticAnalysisError> {
+    if tracee_available {
+        return Ok(Box::new(TraceeSource {
+            socket: PathBuf::from(
+                std::env::var("SKREG_TRACEE_SOCKET").unwrap_or_else(|_| TRACEE_SOCKET.into()),
+            ),
+        }));
+    }
+    if tool_available("strace") {
+        return Ok(Box::new(PtraceSource {
+            strace: PathBuf::from("strace"),
+        }));
+    }
+    Err(StaticAnalysisError::MissingComponent(
+        "no syscall event source: tracee socket absent and strace not found".into(),
+    ))
+}
+
+fn tool_available(tool: &str) -> bool {
+    Command::new(tool)
+        .arg("-V")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// Executes Bash and Ruby scripts inside nsjail and reports suspicious
+/// syscall activity.
+pub struct NsjailAnalyzer {
+    /// Where syscall events come from.
+    pub events: Box<dyn EventSource>,
+    /// Resource limits applied inside the jail.
+    pub limits: JailLimits,
+}
+
+impl NsjailAnalyzer {
+    /// Create an analyzer using [`default_event_source`] and default limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StaticAnalysisError::MissingComponent` if no event source is
+    /// available.
+    pub fn new(tracee_available: bool) -> Result<Self, StaticAnalysisError> {
+        Ok(Self {
+            events: default_event_source(tracee_available)?,
+            limits: JailLimits::default(),
+        })
+    }
+
+    fn jail_command(&self, hostname: &str, scratch: &Path, argv: &[String]) -> Command {
+        let l = self.limits;
+        let mut cmd = Command::new("nsjail");
+        cmd.args(["--mode", "o", "--really_quiet"])
+            .args(["--hostname", hostname])
+            .args(["--chroot", "/"])
+            .arg("--bindmount")
+            .arg(format!("{}:{SCRATCH_GUEST_DIR}", scratch.display()))
+            .args(["--tmpfsmount", "/tmp"])
+            .args(["--cwd", SCRATCH_GUEST_DIR])
+            .args(["--user", "65534", "--group", "65534"])
+            .args(["--time_limit", &TIME_LIMIT_SECS.to_string()])
+            .args(["--rlimit_as", &l.address_space_mb.to_string()])
+            .args(["--rlimit_cpu", &l.cpu_secs.to_string()])
+            .args(["--rlimit_fsize", &l.file_size_mb.to_string()])
+            .args(["--rlimit_nofile", &l.open_files.to_string()])
+            .args(["--rlimit_nproc", &l.processes.to_string()])
+            .args([
+                "--env",
+                "PATH=/usr/local/bin:/usr/bin:/bin",
+                "--env",
+                "HOME=/tmp",
+            ])
+            .args(["--seccomp_string", SECCOMP_POLICY])
+            .arg("--")
+            .args(argv)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        cmd
+    }
+}
+
+impl Analyzer for NsjailAnalyzer {
+    fn analyze(&self, file: &Path) -> Result<Vec<Finding>, StaticAnalysisError> {
+        let interpreter = match file.extension().and_then(|e| e.to_str()) {
+            Some("sh") => "/bin/sh",
+            Some("bash") => "/bin/bash",
+            Some("rb") => "/usr/bin/ruby",
+            _ => return Ok(vec![]),
+        };
+        let name = file
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| std::io::Error::other("script has no UTF-8 file name"))?;
+
+        let scratch = tempfile::tempdir()?;
+        std::fs::copy(file, scratch.path().join(name))?;
+        let hostname = format!("skreg-{}", uuid::Uuid::new_v4().simple());
+        let argv = vec![
+            interpreter.to_owned(),
+            format!("{SCRATCH_GUEST_DIR}/{name}"),
+        ];
+
+        let jail = self.jail_command(&hostname, scratch.path(), &argv);
+        let (mut cmd, collector) = self.events.attach(jail, &hostname, scratch.path())?;
+        let started = std::time::Instant::now();
+        let status = cmd.status().map_err(|e| StaticAnalysisError::ToolError {
+            tool: "nsjail".into(),
+            reason: e.to_string(),
+        })?;
+        let events = collector.finish()?;
+
+        let file_label = file.to_string_lossy();
+        let mut findings: BTreeSet<(String, String)> = events
+            .iter()
+            .filter_map(classify)
+            .take(MAX_EVENTS)
+            .collect();
+        if !status.success() && started.elapsed() >= Duration::from_secs(TIME_LIMIT_SECS.into()) {
+            findings.insert((
+                "nsjail_timeout".into(),
+                format!("script exceeded the {TIME_LIMIT_SECS}s time limit"),
+            ));
+        }
+        Ok(findings
+            .into_iter()
+            .map(|(rule_id, message)| Finding {
+                file: file_label.clone().into_owned(),
+                tool: format!("nsjail+{}", self.events.name()),
+                rule_id,
+                severity: Severity::Warning,
+                message,
+            })
+            .collect())
+    }
+}
+
+/// Map an observed event to a `(rule_id, message)` pair, or `None` if the
+/// event is ordinary script behaviour.
+#[must_use]
+pub fn classify(event: &SyscallEvent) -> Option<(String, String)> {
+    let denied = if event.denied { " (denied)" } else { "" };
+    match &event.kind {
+        EventKind::Network(addr) => Some((
+            "nsjail_network_attempt".into(),
+            format!("{} {addr}{denied}", event.syscall),
+        )),
+        EventKind::Exec(prog) => {
+            let base = prog.rsplit('/').next().unwrap_or(prog);
+            NETWORK_TOOLS.contains(&base).then(|| {
+                (
+                    "nsjail_network_tool_exec".into(),
+                    format!("executed {prog}{denied}"),
+                )
+            })
+        }
+        EventKind::Open { path, write: true } if !has_prefix(path, WRITE_ALLOWED_PREFIXES) => {
+            Some((
+                "nsjail_fs_write_outside_scratch".into(),
+                format!("write to {path}{denied}"),
+            ))
+        }
+        EventKind::Open { path, .. } if !has_prefix(path, READ_ALLOWED_PREFIXES) => Some((
+            "nsjail_sensitive_read".into(),
+            format!("read of {path}{denied}"),
+        )),
+        EventKind::Other(detail) if event.denied => Some((
+            "nsjail_seccomp_denied".into(),
+            format!("{} {detail}", event.syscall),
+        )),
+        _ => None,
+    }
+}
+
+fn has_prefix(path: &str, prefixes: &[&str]) -> bool {
+    let path = Path::new(path);
+    // A `..` component could climb out of any allowed directory.
+    if path.components().any(|c| c == Component::ParentDir) {
+        return false;
+    }
+    // Relative paths resolve against the scratch working directory.
+    // Prefixes match whole components, so `/usr/lib` does not cover
+    // `/usr/lib64-evil`.
+    !path.has_root() || prefixes.iter().any(|p| path.starts_with(p))
+}
+
+// ── tracee ────────────────────────────────────────────────────────────────
+
+/// Reads newline-delimited JSON events from the tracee socket, keeping only
+/// those whose `hostName` matches the jail.
+pub struct TraceeSource {
+    /// Path to the tracee Unix socket.
+    pub socket: PathBuf,
+}
+
+struct TraceeCollector {
+    stop: Arc<AtomicBool>,
+    events: Arc<Mutex<Vec<SyscallEvent>>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl EventSource for TraceeSource {
+    fn name(&self) -> &'static str {
+        "tracee"
+    }
+
+    fn attach(
+        &self,
+        jail: Command,
+        hostname: &str,
+        _scratch: &Path,
+    ) -> Result<(Command, Box<dyn EventCollector>), StaticAnalysisError> {
+        let stream = UnixStream::connect(&self.socket)?;
+        stream.set_read_timeout(Some(Duration::from_millis(200)))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let hostname = hostname.to_owned();
+        let reader = {
+            let stop = Arc::clone(&stop);
+            let events = Arc::clone(&events);
+            std::thread::spawn(move || {
+                let mut lines = BufReader::new(stream);
+                let mut line = String::new();
+                while !stop.load(Ordering::SeqCst) {
+                    line.clear();
+                    match lines.read_line(&mut line) {
+                        Ok(0) => break,
+                        Ok(_) => {
+                            if let Some(event) = parse_tracee_event(&line, &hostname) {
+                                if let Ok(mut events) = events.lock() {
+                                    events.push(event);
+                                }
+                            }
+                        }
+                        Err(e)
+                            if matches!(
+                                e.kind(),
+                                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                            ) => {}
+                        Err(_) => break,
+                    }
+                }
+            })
+        };
+        Ok((
+            jail,
+            Box::new(TraceeCollector {
+                stop,
+                events,
+                reader: Some(reader),
+            }),
+        ))
+    }
+}
+
+impl EventCollector for TraceeCollector {
+    fn finish(mut self: Box<Self>) -> Result<Vec<SyscallEvent>, StaticAnalysisError> {
+        // Give tracee a moment to flush events emitted just before exit.
+        std::thread::sleep(Duration::from_millis(500));
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(reader) = self.reader.take() {
+            reader
+                .join()
+                .map_err(|_| std::io::Error::other("tracee reader panicked"))?;
+        }
+        let events = self
+            .events
+            .lock()
+            .map_err(|_| std::io::Error::other("tracee event buffer poisoned"))?;
+        Ok(events.clone())
+    }
+}
+
+/// Parse one tracee JSON event, returning `None` if it belongs to another
+/// host or is not a kind we report.
+#[must_use]
+pub fn parse_tracee_event(line: &str, hostname: &str) -> Option<SyscallEvent> {
+    let json: Value = serde_json::from_str(line).ok()?;
+    if json["hostName"].as_str() != Some(hostname) {
+        return None;
+    }
+    let name = json["eventName"].as_str()?.to_owned();
+    let arg = |key: &str| {
+        json["args"].as_array().and_then(|args| {
+            args.iter()
+                .find(|a| a["name"] == key)
+                .map(|a| match &a["value"] {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+        })
+    };
+    let denied = json["returnValue"].as_i64().is_some_and(|r| r < 0);
+    let kind = match name.as_str() {
+        "sched_process_exec" | "execve" => EventKind::Exec(arg("pathname")?),
+        "security_file_open" | "openat" | "open" => {
+            let flags = arg("flags").unwrap_or_default();
+            EventKind::Open {
+                path: arg("pathname")?,
+                write: flags.contains("O_WRONLY")
+                    || flags.contains("O_RDWR")
+                    || flags.contains("O_CREAT"),
+            }
+        }
+        "security_socket_connect" | "connect" | "security_socket_bind" | "bind" | "sendto" => {
+            EventKind::Network(
+                arg("remote_addr")
+                    .or_else(|| arg("addr"))
+                    .unwrap_or_default(),
+            )
+        }
+        _ if denied => EventKind::Other(String::new()),
+        _ => return None,
+    };
+    Some(SyscallEvent {
+        syscall: name,
+        kind,
+        denied,
+    })
+}
+
+// ── strace fallback ───────────────────────────────────────────────────────
+
+/// Runs nsjail under `strace -f` from outside the jail. Works on any Linux
+/// host that permits ptrace of child processes; no eBPF required.
+pub struct PtraceSource {
+    /// Path to the `strace` binary.
+    pub strace: PathBuf,
+}
+
+struct PtraceCollector {
+    log: PathBuf,
+    _dir: tempfile::TempDir,
+}
+
+impl EventSource for PtraceSource {
+    fn name(&self) -> &'static str {
+        "strace"
+    }
+
+    fn attach(
+        &self,
+        jail: Command,
+        _hostname: &str,
+        _scratch: &Path,
+    ) -> Result<(Command, Box<dyn EventCollector>), StaticAnalysisError> {
+        // The trace lives outside the scratch dir so the script cannot edit it.
+        let dir = tempfile::tempdir()?;
+        let log = dir.path().join("trace");
+        let mut cmd = Command::new(&self.strace);
+        cmd.args(["-f", "-qq", "-s", "256", "-e"])
+            .arg("trace=%process,%file,%network,ptrace,mount,bpf,unshare,setns")
+            .arg("-o")
+            .arg(&log)
+            .arg("--")
+            .arg(jail.get_program())
+            .args(jail.get_args())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        Ok((cmd, Box::new(PtraceCollector { log, _dir: dir })))
+    }
+}
+
+impl EventCollector for PtraceCollector {
+    fn finish(self: Box<Self>) -> Result<Vec<SyscallEvent>, StaticAnalysisError> {
+        let trace = std::fs::read_to_string(&self.log).unwrap_or_default();
+        Ok(parse_strace(&trace, SCRATCH_GUEST_DIR))
+    }
+}
+
+/// Parse `strace -f` output, keeping only events from the process that
+/// `execve`s a script under `script_dir` and its descendants, so nsjail's own
+/// namespace setup is ignored.
+#[must_use]
+pub fn parse_strace(trace: &str, script_dir: &str) -> Vec<SyscallEvent> {
+    let mut tracked: HashSet<u32> = HashSet::new();
+    let mut pending: HashMap<u32, String> = HashMap::new();
+    let mut events = Vec::new();
+
+    for raw in trace.lines() {
+        let Some((pid, rest)) = raw.split_once(' ') else {
+            continue;
+        };
+        let Ok(pid) = pid.trim().parse::<u32>() else {
+            continue;
+        };
+        let rest = rest.trim_start();
+
+        // Stitch `<unfinished ...>` / `<... resumed>` pairs back together.
+        let line = if let Some(head) = rest.strip_suffix(" <unfinished ...>") {
+            pending.insert(pid, head.to_owned());
+            continue;
+        } else if let Some(tail) = rest.strip_prefix("<... ") {
+            let Some(head) = pending.remove(&pid) else {
+                continue;
+            };
+            let tail = tail.split_once("resumed>").map_or("", |(_, t)| t);
+            format!("{head}{tail}")
+        } else {
+            rest.to_owned()
+        };
+
+        let Some((syscall, after)) = line.split_once('(') else {
+            continue;
+        };
+        let (args, result) = after.rsplit_once(" = ").unwrap_or((after, ""));
+        let denied = result.trim_start().starts_with("-1");
+
+        if !tracked.contains(&pid) {
+            let starts_script =
+                syscall == "execve" && !denied && args.contains(&format!("\"{script_dir}/"));
+            if starts_script {
+                tracked.insert(pid);
+            }
+            continue;
+        }
+
+        if matches!(syscall, "clone" | "clone3" | "fork" | "vfork") {
+            if let Ok(child) = result
+                .split_whitespace()
+                .next()
+                .unwrap_or("")
+                .parse::<u32>()
+            {
+                tracked.insert(child);
+            }
+            continue;
+        }
+
+        let first_quoted = || args.split('"').nth(1).map(str::to_owned);
+        let kind = match syscall {
+            "execve" | "execveat" => EventKind::Exec(first_quoted().unwrap_or_default()),
+            "open" | "openat" | "openat2" | "creat" => {
+                let Some(path) = first_quoted() else {
+                    continue;
+                };
+                EventKind::Open {
+                    path,
+                    write: syscall == "creat"
+                        || args.contains("O_WRONLY")
+                        || args.contains("O_RDWR")
+                        || args.contains("O_CREAT"),
+                }
+            }
+            "connect" | "bind" | "sendto" | "sendmsg" => {
+                if args.contains("AF_UNIX") || args.contains("AF_NETLINK") {
+                    continue;
+                }
+                let addr = args
+                    .split_once('{')
+                    .and_then(|(_, a)| a.split_once('}'))
+                    .map_or(String::new(), |(a, _)| a.to_owned());
+                EventKind::Network(addr)
+            }
+            _ if denied => EventKind::Other(args.chars().take(120).collect()),
+            _ => continue,
+        };
+        events.push(SyscallEvent {
+            syscall: syscall.to_owned(),
+            kind,
+            denied,
+        });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACE: &str = r#"100 execve("/usr/bin/nsjail", ["nsjail", "--mode", "o"], 0x7ffd /* 3 vars */) = 0
+100 openat(AT_FDCWD, "/proc/self/uid_map", O_WRONLY|O_CLOEXEC) = 5
+100 clone(child_stack=NULL, flags=CLONE_NEWNET|SIGCHLD) = 101
+101 mount("/", "/", NULL, MS_BIND|MS_REC, NULL) = 0
+101 execve("/bin/bash", ["/bin/bash", "/sandbox/run.sh"], 0x55d0 /* 2 vars */) = 0
+101 openat(AT_FDCWD, "/usr/lib/x86_64-linux-gnu/libc.so.6", O_RDONLY|O_CLOEXEC) = 3
+101 clone(child_stack=NULL, flags=CLONE_CHILD_CLEARTID|SIGCHLD, child_tidptr=0x7f) = 102
+102 execve("/usr/bin/curl", ["curl", "http://evil.example"], 0x55d1 /* 2 vars */) = 0
+102 connect(3, {sa_family=AF_INET, sin_port=htons(80), sin_addr=inet_addr("203.0.113.5")}, 16 <unfinished ...>
+101 openat(AT_FDCWD, "/etc/shadow", O_RDONLY <unfinished ...>
+102 <... connect resumed>) = -1 ENETUNREACH (Network is unreachable)
+101 <... openat resumed>) = -1 EACCES (Permission denied)
+101 openat(AT_FDCWD, "/home/user/.bashrc", O_WRONLY|O_CREAT|O_TRUNC, 0666) = -1 EROFS (Read-only file system)
+101 ptrace(PTRACE_TRACEME) = -1 EPERM (Operation not permitted)
+"#;
+
+    #[test]
+    fn strace_parser_ignores_jail_setup() {
+        let events = parse_strace(TRACE, SCRATCH_GUEST_DIR);
+        assert!(
+            !events.iter().any(|e| e.syscall == "mount"
+                || matches!(&e.kind, EventKind::Open { path, .. } if path == "/proc/self/uid_map")),
+            "got: {events:?}"
+        );
+    }
+
+    #[test]
+    fn strace_parser_follows_children_and_stitches_resumed_calls() {
+        let events = parse_strace(TRACE, SCRATCH_GUEST_DIR);
+        assert!(events.contains(&SyscallEvent {
+            syscall: "execve".into(),
+            kind: EventKind::Exec("/usr/bin/curl".into()),
+            denied: false,
+        }));
+        let connect = events.iter().find(|e| e.syscall == "connect").unwrap();
+        assert!(connect.denied);
+        assert!(matches!(&connect.kind, EventKind::Network(a) if a.contains("203.0.113.5")));
+        assert!(events.contains(&SyscallEvent {
+            syscall: "openat".into(),
+            kind: EventKind::Open {
+                path: "/etc/shadow".into(),
+                write: false,
+            },
+            denied: true,
+        }));
+    }
+
+    #[test]
+    fn classify_flags_suspicious_behaviour_only() {
+        let findings: Vec<String> = parse_strace(TRACE, SCRATCH_GUEST_DIR)
+            .iter()
+            .filter_map(classify)
+            .map(|(rule, _)| rule)
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                "nsjail_network_tool_exec",
+                "nsjail_network_attempt",
+                "nsjail_sensitive_read",
+                "nsjail_fs_write_outside_scratch",
+                "nsjail_seccomp_denied",
+            ]
+        );
+    }
+
+    #[test]
+    fn allowed_prefixes_match_whole_components() {
+        assert!(has_prefix("/usr/lib/libc.so.6", READ_ALLOWED_PREFIXES));
+        assert!(has_prefix("/tmp", WRITE_ALLOWED_PREFIXES));
+        assert!(has_prefix("scratch.txt", WRITE_ALLOWED_PREFIXES));
+        assert!(!has_prefix("/usr/lib64-evil/x", &["/usr/lib"]));
+        assert!(!has_prefix("/tmpfoo/x", WRITE_ALLOWED_PREFIXES));
+        assert!(!has_prefix("/tmp/../etc/passwd", WRITE_ALLOWED_PREFIXES));
+        assert!(!has_prefix("../etc/passwd", READ_ALLOWED_PREFIXES));
+    }
+
+    #[test]
+    fn tracee_events_filtered_by_jail_hostname() {
+        let line = r#"{"hostName":"skreg-abc","eventName":"security_socket_connect","returnValue":0,"args":[{"name":"remote_addr","value":{"sa_family":"AF_INET","sin_addr":"203.0.113.5"}}]}"#;
+        assert!(parse_tracee_event(line, "other-host").is_none());
+        let event = parse_tracee_event(line, "skreg-abc").unwrap();
+        assert!(matches!(event.kind, EventKind::Network(ref a) if a.contains("203.0.113.5")));
+    }
+
+    #[test]
+    fn tracee_file_open_with_write_flags() {
+        let line = r#"{"hostName":"h","eventName":"security_file_open","args":[{"name":"pathname","value":"/etc/cron.d/x"},{"name":"flags","value":"O_WRONLY|O_CREAT"}]}"#;
+        let event = parse_tracee_event(line, "h").unwrap();
+        assert_eq!(
+            classify(&event).map(|(rule, _)| rule).as_deref(),
+            Some("nsjail_fs_write_outside_scratch")
+        );
+    }
+}
@@ -160,59 +160,29 @@ impl Analyzer for SemgrepAnalyzer {
     }
 }
 
-// ── Sandbox stub implementations ─────────────────────────────────────────
-//
-// NOTE: The WASM sandbox (wasmtime + custom WasiCtx) and nsjail execution
-// are required by the spec but are NOT fully implemented here. These stubs
-// exist so the dispatch logic and Finding types are in place. Full
-// implementation requires:
-//   - WasmSandboxAnalyzer: wasmtime + pre-compiled python.wasm/quickjs.wasm
-//     with a custom WasiCtx that records denied WASI calls as Warning findings
-//   - NsjailAnalyzer: nsjail subprocess with seccomp-bpf + tracee eBPF event
-//     stream parsing
-// These are tracked as follow-up tasks to this branch.
-
-/// Stub WASM sandbox analyzer for Python and JS/TS scripts.
-/// Full implementation: wasmtime + custom `WasiCtx` that intercepts WASI calls.
-pub struct WasmSandboxAnalyzer;
-
-impl Analyzer for WasmSandboxAnalyzer {
-    fn analyze(&self, _file: &Path) -> Result<Vec<Finding>, StaticAnalysisError> {
-        // TODO: run script inside pre-compiled python.wasm or quickjs.wasm
-        // with a custom WasiCtx that records denied WASI capability attempts
-        // as Warning-severity findings.
-        Ok(vec![])
-    }
-}
+// ── Sandbox implementations ───────────────────────────────────────────────
 
-/// Stub nsjail sandbox analyzer for Bash and Ruby scripts.
-/// Full implementation: nsjail subprocess + tracee eBPF event stream.
-pub struct NsjailAnalyzer;
-
-impl Analyzer for NsjailAnalyzer {
-    fn analyze(&self, _file: &Path) -> Result<Vec<Finding>, StaticAnalysisError> {
-        // TODO: execute script inside nsjail with:
-        //   - network namespace isolation
-        //   - read-only tmpfs
-        //   - seccomp-bpf whitelist
-        //   - resource limits
-        // Collect tracee eBPF events via /var/run/tracee/tracee.sock and
-        // normalise suspicious syscall patterns into Warning findings.
-        Ok(vec![])
-    }
-}
+pub use super::nsjail::NsjailAnalyzer;
+pub use super::wasm_sandbox::WasmSandboxAnalyzer;
 
 /// Dispatch Pass 2 analysis for a single file under `scripts/`.
 ///
 /// Runs both static analysis and sandbox execution (where implemented).
+/// Python and JS scripts execute in the [`WasmSandboxAnalyzer`], which loads
+/// its interpreter modules from `SKREG_WASM_DIR` and records a
+/// `sandbox_skipped` info finding instead only when the worker was started
+/// with `SKREG_SKIP_WASM_SANDBOX=true`; shell and
+/// Ruby scripts execute in the [`NsjailAnalyzer`], observed through tracee
+/// when `tracee_available` and through `strace` otherwise.
 /// Extensions not in `SCRIPT_EXTENSIONS` are silently ignored (structure
 /// stage guards this earlier).
 ///
 /// # Errors
 ///
-/// Returns `Err(StaticAnalysisError::MissingComponent)` when `tracee_available`
-/// is `false` and the file extension requires sandbox execution (`sh`, `bash`,
-/// `rb`). Returns `Err(StaticAnalysisError::ToolError)` if a subprocess tool
+/// Returns `Err(StaticAnalysisError::MissingComponent)` when a `sh`, `bash`
+/// or `rb` file needs sandbox execution but neither tracee nor `strace` is
+/// available, or when the WASM interpreter modules are missing for `py`/`js`.
+/// Returns `Err(StaticAnalysisError::ToolError)` if a subprocess tool
 /// fails to launch.
 pub fn run_pass2_file(
     file: &Path,
@@ -236,27 +206,19 @@ pub fn run_pass2_file(
             findings.extend(WasmSandboxAnalyzer.analyze(file)?);
         }
         "rb" => {
-            if !tracee_available {
-                return Err(StaticAnalysisError::MissingComponent(
-                    "tracee socket absent — cannot process Ruby scripts safely".into(),
-                ));
-            }
+            let jail = NsjailAnalyzer::new(tracee_available)?;
             findings.extend(
                 SemgrepAnalyzer {
                     config: "p/ruby-security-audit".into(),
                 }
                 .analyze(file)?,
             );
-            findings.extend(NsjailAnalyzer.analyze(file)?);
+            findings.extend(jail.analyze(file)?);
         }
         "sh" | "bash" => {
-            if !tracee_available {
-                return Err(StaticAnalysisError::MissingComponent(
-                    "tracee socket absent — cannot process bash scripts safely".into(),
-                ));
-            }
+            let jail = NsjailAnalyzer::new(tracee_available)?;
             findings.extend(ShellcheckAnalyzer.analyze(file)?);
-            findings.extend(NsjailAnalyzer.analyze(file)?);
+            findings.extend(jail.analyze(file)?);
         }
         _ => {}
     }
@@ -342,9 +304,13 @@ mod tests {
     // ── dispatch routing ──────────────────────────────────────────────────
 
     #[test]
-    fn bash_without_tracee_returns_missing_component_error() {
-        // Tracee unavailable → bash scripts must be rejected, not silently skipped.
+    fn bash_without_event_source_returns_missing_component_error() {
+        // Neither tracee nor strace → bash scripts must be rejected, not
+        // silently skipped. With strace installed the fallback is used instead.
         use std::path::PathBuf;
+        if super::super::nsjail::default_event_source(false).is_ok() {
+            return;
+        }
         let err = run_pass2_file(
             &PathBuf::from("/tmp/test.sh"),
             Path::new("scripts/test.sh"),
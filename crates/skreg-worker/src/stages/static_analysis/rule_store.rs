@@ -0,0 +1,118 @@
+//! Process-wide compiled YARA rules with optional hot reload.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use super::pass1::{rule_pack_version, CompiledRules};
+use super::StaticAnalysisError;
+
+/// Holds the current [`CompiledRules`] for a rules directory.
+///
+/// Jobs take a snapshot with [`RuleStore::current`], so a reload never
+/// changes the rules underneath a running scan.
+pub struct RuleStore {
+    dir: PathBuf,
+    current: RwLock<Arc<CompiledRules>>,
+}
+
+impl RuleStore {
+    /// Compile the rules in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StaticAnalysisError::YaraCompilation` if any rule fails to compile.
+    pub fn load(dir: &Path) -> Result<Self, StaticAnalysisError> {
+        let rules = super::startup::check_yara_rules(dir)?;
+        log::info!("loaded YARA rule pack {}", rules.version());
+        Ok(Self {
+            dir: dir.to_owned(),
+            current: RwLock::new(Arc::new(rules)),
+        })
+    }
+
+    /// Return the rules in effect right now.
+    #[must_use]
+    pub fn current(&self) -> Arc<CompiledRules> {
+        Arc::clone(
+            &self
+                .current
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        )
+    }
+
+    /// Recompile if the rule-pack version on disk differs from the loaded one.
+    ///
+    /// Returns `true` if new rules were swapped in. A pack that fails to
+    /// compile is rejected and the previous rules stay active.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the rules cannot be read or compiled.
+    pub fn reload_if_changed(&self) -> Result<bool, StaticAnalysisError> {
+        let on_disk = rule_pack_version(&self.dir)?;
+        if on_disk == self.current().version() {
+            return Ok(false);
+        }
+        let rules = super::startup::check_yara_rules(&self.dir)?;
+        log::info!("reloaded YARA rule pack {}", rules.version());
+        *self
+            .current
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Arc::new(rules);
+        Ok(true)
+    }
+
+    /// Poll the rules directory every `interval` and reload on change.
+    pub fn spawn_hot_reload(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let store = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let store = Arc::clone(&store);
+                match tokio::task::spawn_blocking(move || store.reload_if_changed()).await {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => {
+                        log::error!("YARA rule reload failed, keeping previous rules: {e}");
+                    }
+                    Err(e) => log::error!("YARA rule reload task panicked: {e}"),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reload_swaps_rules_only_when_changed() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.yar"), "rule a { condition: true }").unwrap();
+        let store = RuleStore::load(dir.path()).unwrap();
+        let before = store.current();
+        assert!(!store.reload_if_changed().unwrap());
+
+        fs::write(dir.path().join("b.yar"), "rule b { condition: false }").unwrap();
+        assert!(store.reload_if_changed().unwrap());
+        assert_ne!(before.version(), store.current().version());
+    }
+
+    #[test]
+    fn broken_rules_keep_previous_pack() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.yar"), "rule a { condition: true }").unwrap();
+        let store = RuleStore::load(dir.path()).unwrap();
+        let before = store.current().version().to_owned();
+
+        fs::write(dir.path().join("a.yar"), "rule a { condition: ").unwrap();
+        assert!(store.reload_if_changed().is_err());
+        assert_eq!(store.current().version(), before);
+    }
+}
@@ -10,8 +10,7 @@ use super::StaticAnalysisError;
 const REQUIRED_TOOLS: &[&str] = &["shellcheck", "bandit", "semgrep", "nsjail"];
 
 /// Path to the tracee Unix socket.
-#[allow(dead_code)]
-const TRACEE_SOCKET: &str = "/var/run/tracee/tracee.sock";
+pub const TRACEE_SOCKET: &str = "/var/run/tracee/tracee.sock";
 
 /// Check that all required subprocess tools are present and executable.
 ///
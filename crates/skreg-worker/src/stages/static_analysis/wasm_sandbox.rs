@@ -0,0 +1,1073 @@
+//! WASM sandbox execution for Python and JavaScript scripts.
+//!
+//! Scripts run inside a pre-compiled interpreter module (`python.wasm` or
+//! `quickjs.wasm`) under wasmtime. The WASI context only exposes a per-run
+//! scratch directory (and, for Python, a read-only standard library); every
+//! other filesystem or socket access is denied and recorded. Execution is
+//! bounded by fuel and linear-memory limits, and guest sleeps advance a
+//! virtual clock instead of blocking the worker.
+
+use std::any::Any;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+use wasi_common::dir::{OpenResult, ReaddirCursor, ReaddirEntity};
+use wasi_common::file::{FdFlags, FileAccessMode, Filestat, OFlags};
+use wasi_common::pipe::WritePipe;
+use wasi_common::sched::subscription::{RwEventFlags, Subscription};
+use wasi_common::sched::{Poll, WasiSched};
+use wasi_common::sync::clocks::{MonotonicClock, SystemClock};
+use wasi_common::{
+    Error, ErrorExt, I32Exit, SystemTimeSpec, Table, WasiClocks, WasiCtx, WasiDir,
+    WasiMonotonicClock,
+};
+use wasmtime::{Config, Engine, Linker, Module, ResourceLimiter, Store, Trap};
+
+use super::{Finding, Severity, StaticAnalysisError};
+
+/// Environment variable overriding the directory holding the interpreter modules.
+pub const WASM_DIR_ENV: &str = "SKREG_WASM_DIR";
+
+/// Default directory holding `python.wasm`, `quickjs.wasm` and `python-lib/`.
+pub const DEFAULT_WASM_DIR: &str = "/opt/skreg/wasm";
+
+/// Environment variable that, set to `true`, disables the sandbox instead of
+/// requiring its interpreter modules (dev mode only).
+pub const SKIP_SANDBOX_ENV: &str = "SKREG_SKIP_WASM_SANDBOX";
+
+/// Guest path of the per-run scratch directory containing the script.
+const SCRATCH_GUEST_DIR: &str = "/sandbox";
+
+/// Guest path at which `python-lib/` is mounted read-only.
+const PYTHON_LIB_GUEST_DIR: &str = "/usr/local/lib";
+
+/// File the Python bootstrap opens once interpreter startup is complete.
+/// Filesystem probes made before it is seen are not recorded.
+const ARM_SENTINEL: &str = ".skreg-sandbox-begin";
+
+/// Descriptor the Python audit hook writes events to. Deliberately not
+/// contiguous with the preopens so that wasi-libc stops enumerating before it.
+const AUDIT_FD: u32 = 64;
+
+/// Maximum bytes of audit output retained per run.
+const AUDIT_BUFFER_CAP: usize = 64 * 1024;
+
+/// Maximum distinct capability events reported per script.
+const MAX_EVENTS: usize = 100;
+
+/// Python audit events that indicate capability use worth recording.
+const PYTHON_AUDIT_EVENTS: &[&str] = &[
+    "socket.connect",
+    "socket.bind",
+    "socket.sendto",
+    "socket.getaddrinfo",
+    "socket.gethostbyname",
+    "socket.gethostbyname_ex",
+    "urllib.Request",
+    "http.client.connect",
+    "smtplib.connect",
+    "ftplib.connect",
+    "subprocess.Popen",
+    "os.system",
+    "os.exec",
+    "os.posix_spawn",
+    "os.spawn",
+    "os.fork",
+    "os.forkpty",
+    "os.kill",
+    "os.killpg",
+    "webbrowser.open",
+];
+
+/// Python program run via `-c`. Installs an audit hook that reports watched
+/// events to [`AUDIT_FD`], arms filesystem recording, then runs the script
+/// as `__main__`.
+const PYTHON_BOOTSTRAP: &str = r#"import json, os, sys
+_WATCH = frozenset(json.loads(sys.argv[2]))
+def _skreg_audit(event, args, _write=os.write, _dumps=json.dumps, _fd=int(sys.argv[1])):
+    if event in _WATCH or event.startswith("ctypes."):
+        try:
+            _write(_fd, (_dumps([event, repr(args)[:200]]) + "\n").encode())
+        except Exception:
+            pass
+sys.addaudithook(_skreg_audit)
+try:
+    open("/.skreg-sandbox-begin").close()
+except OSError:
+    pass
+os.chdir("/sandbox")
+import runpy
+sys.argv = sys.argv[3:]
+runpy.run_path(sys.argv[0], run_name="__main__")
+"#;
+
+/// Interpreter used to execute a script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpreter {
+    /// `CPython` compiled to WASI (`python.wasm`).
+    Python,
+    /// `QuickJS` compiled to WASI (`quickjs.wasm`).
+    QuickJs,
+}
+
+impl Interpreter {
+    /// Select the interpreter for `file` by extension.
+    ///
+    /// Returns `None` for extensions that cannot be executed directly
+    /// (TypeScript needs a compile step `QuickJS` does not provide).
+    #[must_use]
+    pub fn for_path(file: &Path) -> Option<Self> {
+        match file.extension().and_then(|e| e.to_str()) {
+            Some("py") => Some(Self::Python),
+            Some("js") => Some(Self::QuickJs),
+            _ => None,
+        }
+    }
+
+    /// File name of the interpreter module inside the WASM directory.
+    #[must_use]
+    pub fn module_file(self) -> &'static str {
+        match self {
+            Self::Python => "python.wasm",
+            Self::QuickJs => "quickjs.wasm",
+        }
+    }
+}
+
+/// Resource bounds applied to every sandbox run.
+#[derive(Debug, Clone, Copy)]
+pub struct SandboxLimits {
+    /// Wasmtime fuel (roughly one unit per executed instruction).
+    pub fuel: u64,
+    /// Maximum linear memory size in bytes.
+    pub memory_bytes: usize,
+    /// Maximum number of table elements.
+    pub table_elements: usize,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 10_000_000_000,
+            memory_bytes: 256 * 1024 * 1024,
+            table_elements: 100_000,
+        }
+    }
+}
+
+/// Engine and compiled interpreter modules, shared across jobs.
+pub struct WasmSandbox {
+    engine: Engine,
+    python: Module,
+    quickjs: Module,
+    python_lib: Option<PathBuf>,
+    limits: SandboxLimits,
+}
+
+static SHARED: OnceLock<Option<WasmSandbox>> = OnceLock::new();
+
+impl WasmSandbox {
+    /// Compile the interpreter modules found in `wasm_dir`.
+    ///
+    /// If `wasm_dir/python-lib` exists it is mounted read-only as the Python
+    /// standard library; otherwise the module is assumed to embed it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StaticAnalysisError::MissingComponent` if a module is absent and
+    /// `StaticAnalysisError::ToolError` if one fails to compile.
+    pub fn load(wasm_dir: &Path, limits: SandboxLimits) -> Result<Self, StaticAnalysisError> {
+        super::startup::check_wasm_modules(wasm_dir)?;
+        let engine = Engine::new(Config::new().consume_fuel(true)).map_err(wasm_error)?;
+        let compile = |interp: Interpreter| {
+            Module::from_file(&engine, wasm_dir.join(interp.module_file())).map_err(wasm_error)
+        };
+        let python = compile(Interpreter::Python)?;
+        let quickjs = compile(Interpreter::QuickJs)?;
+        let python_lib = Some(wasm_dir.join("python-lib")).filter(|p| p.is_dir());
+        Ok(Self {
+            engine,
+            python,
+            quickjs,
+            python_lib,
+            limits,
+        })
+    }
+
+    /// Return the process-wide sandbox, loading it from [`WASM_DIR_ENV`] (or
+    /// [`DEFAULT_WASM_DIR`]) on first use, or `None` if it was disabled with
+    /// [`SKIP_SANDBOX_ENV`]`=true`.
+    ///
+    /// The worker calls this at startup so it refuses to run without the
+    /// interpreter modules.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from [`WasmSandbox::load`], including
+    /// `StaticAnalysisError::MissingComponent` for a missing module.
+    pub fn shared() -> Result<Option<&'static Self>, StaticAnalysisError> {
+        if let Some(sandbox) = SHARED.get() {
+            return Ok(sandbox.as_ref());
+        }
+        let sandbox = if std::env::var(SKIP_SANDBOX_ENV).as_deref() == Ok("true") {
+            log::warn!(
+                "WASM sandbox disabled ({SKIP_SANDBOX_ENV}=true); \
+                 Python and JS scripts will not be executed"
+            );
+            None
+        } else {
+            let dir = std::env::var(WASM_DIR_ENV).unwrap_or_else(|_| DEFAULT_WASM_DIR.into());
+            Some(Self::load(Path::new(&dir), SandboxLimits::default())?)
+        };
+        Ok(SHARED.get_or_init(|| sandbox).as_ref())
+    }
+
+    /// Execute `script` under `interp` and return the capability findings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the scratch directory cannot be prepared or the
+    /// interpreter fails to instantiate. Guest traps, exits and resource
+    /// exhaustion are reported as findings, not errors.
+    pub fn execute(
+        &self,
+        interp: Interpreter,
+        script: &Path,
+    ) -> Result<Vec<Finding>, StaticAnalysisError> {
+        let scratch = tempfile::tempdir()?;
+        let name = script
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| std::io::Error::other("script has no UTF-8 file name"))?;
+        std::fs::copy(script, scratch.path().join(name))?;
+        let guest_script = format!("{SCRATCH_GUEST_DIR}/{name}");
+
+        let run = match interp {
+            Interpreter::Python => {
+                let watch = serde_json::to_string(PYTHON_AUDIT_EVENTS)
+                    .map_err(|e| std::io::Error::other(e.to_string()))?;
+                let argv = [
+                    "python",
+                    "-I",
+                    "-B",
+                    "-c",
+                    PYTHON_BOOTSTRAP,
+                    &AUDIT_FD.to_string(),
+                    &watch,
+                    &guest_script,
+                ]
+                .map(String::from);
+                SandboxRun {
+                    module: &self.python,
+                    argv: argv.to_vec(),
+                    scratch: scratch.path(),
+                    stdlib: self.python_lib.as_deref(),
+                    armed: false,
+                }
+            }
+            Interpreter::QuickJs => SandboxRun {
+                module: &self.quickjs,
+                argv: vec!["qjs".into(), "--std".into(), guest_script],
+                scratch: scratch.path(),
+                stdlib: None,
+                armed: true,
+            },
+        };
+
+        let events = self.run(&run)?;
+        Ok(events
+            .into_iter()
+            .map(|e| e.into_finding(&script.to_string_lossy()))
+            .collect())
+    }
+
+    fn run(&self, run: &SandboxRun<'_>) -> Result<BTreeSet<CapabilityEvent>, StaticAnalysisError> {
+        let log = EventLog::new(run.armed);
+        let audit = Arc::new(RwLock::new(CappedBuffer::default()));
+
+        let skew = Arc::new(AtomicU64::new(0));
+        let clocks = WasiClocks::new()
+            .with_system(SystemClock::new(cap_std::ambient_authority()))
+            .with_monotonic(VirtualClock {
+                real: MonotonicClock::new(cap_std::ambient_authority()),
+                skew: Arc::clone(&skew),
+            });
+        let mut wasi = WasiCtx::new(
+            wasi_common::sync::random_ctx(),
+            clocks,
+            Box::new(VirtualSched { skew }),
+            Table::new(),
+        );
+        for arg in &run.argv {
+            wasi.push_arg(arg).map_err(wasm_error)?;
+        }
+
+        wasi.push_preopened_dir(
+            Box::new(DenyDir {
+                guest_path: String::new(),
+                log: log.clone(),
+            }),
+            "/",
+        )
+        .map_err(wasm_error)?;
+        let scratch =
+            cap_std::fs::Dir::open_ambient_dir(run.scratch, cap_std::ambient_authority())?;
+        wasi.push_preopened_dir(
+            Box::new(wasi_common::sync::dir::Dir::from_cap_std(scratch)),
+            SCRATCH_GUEST_DIR,
+        )
+        .map_err(wasm_error)?;
+        if let Some(stdlib) = run.stdlib {
+            let dir = cap_std::fs::Dir::open_ambient_dir(stdlib, cap_std::ambient_authority())?;
+            wasi.push_preopened_dir(
+                Box::new(ReadOnlyDir {
+                    inner: Box::new(wasi_common::sync::dir::Dir::from_cap_std(dir)),
+                    guest_path: PYTHON_LIB_GUEST_DIR.into(),
+                    log: log.clone(),
+                }),
+                PYTHON_LIB_GUEST_DIR,
+            )
+            .map_err(wasm_error)?;
+        }
+        wasi.insert_file(
+            AUDIT_FD,
+            Box::new(WritePipe::from_shared(Arc::clone(&audit))),
+            FileAccessMode::WRITE,
+        );
+
+        let mut store = Store::new(
+            &self.engine,
+            SandboxState {
+                wasi,
+                limiter: Limiter {
+                    limits: self.limits,
+                    log: log.clone(),
+                },
+                log: log.clone(),
+            },
+        );
+        store.limiter(|s| &mut s.limiter);
+        store.set_fuel(self.limits.fuel).map_err(wasm_error)?;
+
+        let mut linker: Linker<SandboxState> = Linker::new(&self.engine);
+        wasi_common::sync::add_to_linker(&mut linker, |s: &mut SandboxState| &mut s.wasi)
+            .map_err(wasm_error)?;
+        deny_sockets(&mut linker).map_err(wasm_error)?;
+
+        let instance = linker
+            .instantiate(&mut store, run.module)
+            .map_err(wasm_error)?;
+        let start = instance
+            .get_typed_func::<(), ()>(&mut store, "_start")
+            .map_err(wasm_error)?;
+
+        if let Err(err) = start.call(&mut store, ()) {
+            if err.downcast_ref::<I32Exit>().is_none() {
+                match err.downcast_ref::<Trap>() {
+                    Some(Trap::OutOfFuel) => log.force(CapabilityEvent::new(
+                        "sandbox_fuel_exhausted",
+                        format!("execution exceeded {} fuel", self.limits.fuel),
+                    )),
+                    Some(trap) => log.force(CapabilityEvent::new("sandbox_trap", trap.to_string())),
+                    None => return Err(wasm_error(err)),
+                }
+            }
+        }
+
+        let audit = audit
+            .read()
+            .map_err(|_| std::io::Error::other("audit buffer poisoned"))?;
+        for event in parse_audit_events(&audit.bytes) {
+            log.force(event);
+        }
+        Ok(log.into_events())
+    }
+}
+
+/// Execute Python and JS scripts inside the shared [`WasmSandbox`].
+pub struct WasmSandboxAnalyzer;
+
+impl super::Analyzer for WasmSandboxAnalyzer {
+    fn analyze(&self, file: &Path) -> Result<Vec<Finding>, StaticAnalysisError> {
+        let Some(interp) = Interpreter::for_path(file) else {
+            log::debug!(
+                "no WASM interpreter for {}, skipping sandbox",
+                file.display()
+            );
+            return Ok(vec![]);
+        };
+        match WasmSandbox::shared()? {
+            Some(sandbox) => sandbox.execute(interp, file),
+            None => Ok(vec![skipped_finding(file)]),
+        }
+    }
+}
+
+/// Informational finding recording that `file` was not executed because the
+/// sandbox was disabled with [`SKIP_SANDBOX_ENV`].
+fn skipped_finding(file: &Path) -> Finding {
+    Finding {
+        file: file.to_string_lossy().into_owned(),
+        tool: "wasm_sandbox".into(),
+        rule_id: "sandbox_skipped".into(),
+        severity: Severity::Info,
+        message: format!("WASM sandbox disabled by {SKIP_SANDBOX_ENV}"),
+    }
+}
+
+// ── Run state ─────────────────────────────────────────────────────────────
+
+struct SandboxRun<'a> {
+    module: &'a Module,
+    argv: Vec<String>,
+    scratch: &'a Path,
+    stdlib: Option<&'a Path>,
+    armed: bool,
+}
+
+struct SandboxState {
+    wasi: WasiCtx,
+    limiter: Limiter,
+    log: EventLog,
+}
+
+/// A single observed capability attempt.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct CapabilityEvent {
+    rule_id: String,
+    detail: String,
+}
+
+impl CapabilityEvent {
+    fn new(rule_id: &str, detail: impl Into<String>) -> Self {
+        Self {
+            rule_id: rule_id.into(),
+            detail: detail.into(),
+        }
+    }
+
+    fn into_finding(self, file: &str) -> Finding {
+        Finding {
+            file: file.into(),
+            tool: "wasm_sandbox".into(),
+            rule_id: self.rule_id,
+            severity: Severity::Warning,
+            message: self.detail,
+        }
+    }
+}
+
+/// Deduplicating, bounded record of capability events shared by the WASI
+/// context, the resource limiter and the host functions.
+#[derive(Clone)]
+struct EventLog {
+    armed: Arc<AtomicBool>,
+    events: Arc<Mutex<BTreeSet<CapabilityEvent>>>,
+}
+
+impl EventLog {
+    fn new(armed: bool) -> Self {
+        Self {
+            armed: Arc::new(AtomicBool::new(armed)),
+            events: Arc::new(Mutex::new(BTreeSet::new())),
+        }
+    }
+
+    fn arm(&self) {
+        self.armed.store(true, Ordering::SeqCst);
+    }
+
+    /// Record `event` if interpreter startup has finished.
+    fn record(&self, event: CapabilityEvent) {
+        if self.armed.load(Ordering::SeqCst) {
+            self.force(event);
+        }
+    }
+
+    /// Record `event` regardless of startup state.
+    fn force(&self, event: CapabilityEvent) {
+        if let Ok(mut events) = self.events.lock() {
+            if events.len() < MAX_EVENTS {
+                events.insert(event);
+            }
+        }
+    }
+
+    fn into_events(self) -> BTreeSet<CapabilityEvent> {
+        self.events.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+}
+
+/// Parse the JSON-lines written by the Python audit hook.
+fn parse_audit_events(bytes: &[u8]) -> Vec<CapabilityEvent> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<(String, String)>(line).ok())
+        .map(|(event, args)| {
+            CapabilityEvent::new(
+                &format!("sandbox_audit_{}", event.replace('.', "_")),
+                format!("{event}{args}"),
+            )
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct CappedBuffer {
+    bytes: Vec<u8>,
+}
+
+impl Write for CappedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let room = AUDIT_BUFFER_CAP.saturating_sub(self.bytes.len());
+        self.bytes.extend_from_slice(&buf[..buf.len().min(room)]);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// ── Resource limits ───────────────────────────────────────────────────────
+
+struct Limiter {
+    limits: SandboxLimits,
+    log: EventLog,
+}
+
+impl ResourceLimiter for Limiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        let allowed = desired <= self.limits.memory_bytes;
+        if !allowed {
+            self.log.force(CapabilityEvent::new(
+                "sandbox_memory_limit",
+                format!("memory growth to {desired} bytes denied"),
+            ));
+        }
+        Ok(allowed)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        Ok(desired <= self.limits.table_elements)
+    }
+}
+
+// ── Network ───────────────────────────────────────────────────────────────
+
+/// WASI `errno::notsup`.
+const ERRNO_NOTSUP: i32 = 58;
+
+/// Replace the preview1 socket calls with stubs that record the attempt and
+/// fail. No sockets are ever preopened, so these never reach a real socket.
+fn deny_sockets(linker: &mut Linker<SandboxState>) -> anyhow::Result<()> {
+    const MODULE: &str = "wasi_snapshot_preview1";
+    fn deny(caller: &wasmtime::Caller<'_, SandboxState>, call: &str, fd: i32) -> i32 {
+        caller.data().log.force(CapabilityEvent::new(
+            "sandbox_network_denied",
+            format!("{call} on fd {fd}"),
+        ));
+        ERRNO_NOTSUP
+    }
+    linker.allow_shadowing(true);
+    linker.func_wrap(
+        MODULE,
+        "sock_accept",
+        |c: wasmtime::Caller<'_, SandboxState>, fd: i32, _: i32, _: i32| {
+            deny(&c, "sock_accept", fd)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "sock_recv",
+        |c: wasmtime::Caller<'_, SandboxState>, fd: i32, _: i32, _: i32, _: i32, _: i32, _: i32| {
+            deny(&c, "sock_recv", fd)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "sock_send",
+        |c: wasmtime::Caller<'_, SandboxState>, fd: i32, _: i32, _: i32, _: i32, _: i32| {
+            deny(&c, "sock_send", fd)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "sock_shutdown",
+        |c: wasmtime::Caller<'_, SandboxState>, fd: i32, _: i32| deny(&c, "sock_shutdown", fd),
+    )?;
+    linker.allow_shadowing(false);
+    Ok(())
+}
+
+// ── Filesystem ────────────────────────────────────────────────────────────
+
+fn join_guest(base: &str, path: &str) -> String {
+    format!("{base}/{}", path.trim_start_matches('/'))
+}
+
+/// Preopened at `/`: denies and records every access that is not routed to
+/// a more specific preopen.
+struct DenyDir {
+    guest_path: String,
+    log: EventLog,
+}
+
+impl DenyDir {
+    fn deny(&self, rule_id: &str, op: &str, path: &str) -> Error {
+        self.log.record(CapabilityEvent::new(
+            rule_id,
+            format!("{op} {}", join_guest(&self.guest_path, path)),
+        ));
+        Error::perm()
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiDir for DenyDir {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn open_file(
+        &self,
+        _symlink_follow: bool,
+        path: &str,
+        oflags: OFlags,
+        _read: bool,
+        write: bool,
+        _fdflags: FdFlags,
+    ) -> Result<OpenResult, Error> {
+        if path == ARM_SENTINEL {
+            self.log.arm();
+            return Err(Error::not_found());
+        }
+        if write || oflags.intersects(OFlags::CREATE | OFlags::TRUNCATE) {
+            Err(self.deny("sandbox_fs_write_denied", "open for write", path))
+        } else {
+            Err(self.deny("sandbox_fs_read_denied", "open", path))
+        }
+    }
+
+    async fn create_dir(&self, path: &str) -> Result<(), Error> {
+        Err(self.deny("sandbox_fs_write_denied", "mkdir", path))
+    }
+
+    async fn readdir(
+        &self,
+        _cursor: ReaddirCursor,
+    ) -> Result<Box<dyn Iterator<Item = Result<ReaddirEntity, Error>> + Send>, Error> {
+        Err(self.deny("sandbox_fs_read_denied", "readdir", ""))
+    }
+
+    async fn symlink(&self, _old_path: &str, new_path: &str) -> Result<(), Error> {
+        Err(self.deny("sandbox_fs_write_denied", "symlink", new_path))
+    }
+
+    async fn remove_dir(&self, path: &str) -> Result<(), Error> {
+        Err(self.deny("sandbox_fs_write_denied", "rmdir", path))
+    }
+
+    async fn unlink_file(&self, path: &str) -> Result<(), Error> {
+        Err(self.deny("sandbox_fs_write_denied", "unlink", path))
+    }
+
+    async fn read_link(&self, path: &str) -> Result<PathBuf, Error> {
+        Err(self.deny("sandbox_fs_read_denied", "readlink", path))
+    }
+
+    async fn get_path_filestat(
+        &self,
+        path: &str,
+        _follow_symlinks: bool,
+    ) -> Result<Filestat, Error> {
+        Err(self.deny("sandbox_fs_read_denied", "stat", path))
+    }
+
+    async fn rename(
+        &self,
+        path: &str,
+        _dest_dir: &dyn WasiDir,
+        _dest_path: &str,
+    ) -> Result<(), Error> {
+        Err(self.deny("sandbox_fs_write_denied", "rename", path))
+    }
+
+    async fn hard_link(
+        &self,
+        path: &str,
+        _target_dir: &dyn WasiDir,
+        _target_path: &str,
+    ) -> Result<(), Error> {
+        Err(self.deny("sandbox_fs_write_denied", "link", path))
+    }
+
+    async fn set_times(
+        &self,
+        path: &str,
+        _atime: Option<SystemTimeSpec>,
+        _mtime: Option<SystemTimeSpec>,
+        _follow_symlinks: bool,
+    ) -> Result<(), Error> {
+        Err(self.deny("sandbox_fs_write_denied", "utimes", path))
+    }
+}
+
+/// Wraps a real directory, forwarding reads and denying (and recording)
+/// every mutation. Keeps the shared Python stdlib immutable across jobs.
+struct ReadOnlyDir {
+    inner: Box<dyn WasiDir>,
+    guest_path: String,
+    log: EventLog,
+}
+
+impl ReadOnlyDir {
+    fn deny(&self, op: &str, path: &str) -> Error {
+        self.log.record(CapabilityEvent::new(
+            "sandbox_fs_write_denied",
+            format!("{op} {}", join_guest(&self.guest_path, path)),
+        ));
+        Error::perm()
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiDir for ReadOnlyDir {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn open_file(
+        &self,
+        symlink_follow: bool,
+        path: &str,
+        oflags: OFlags,
+        read: bool,
+        write: bool,
+        fdflags: FdFlags,
+    ) -> Result<OpenResult, Error> {
+        if write || oflags.intersects(OFlags::CREATE | OFlags::TRUNCATE) {
+            return Err(self.deny("open for write", path));
+        }
+        match self
+            .inner
+            .open_file(symlink_follow, path, oflags, read, false, fdflags)
+            .await?
+        {
+            OpenResult::Dir(inner) => Ok(OpenResult::Dir(Box::new(ReadOnlyDir {
+                inner,
+                guest_path: join_guest(&self.guest_path, path),
+                log: self.log.clone(),
+            }))),
+            file @ OpenResult::File(_) => Ok(file),
+        }
+    }
+
+    async fn create_dir(&self, path: &str) -> Result<(), Error> {
+        Err(self.deny("mkdir", path))
+    }
+
+    async fn readdir(
+        &self,
+        cursor: ReaddirCursor,
+    ) -> Result<Box<dyn Iterator<Item = Result<ReaddirEntity, Error>> + Send>, Error> {
+        self.inner.readdir(cursor).await
+    }
+
+    async fn symlink(&self, _old_path: &str, new_path: &str) -> Result<(), Error> {
+        Err(self.deny("symlink", new_path))
+    }
+
+    async fn remove_dir(&self, path: &str) -> Result<(), Error> {
+        Err(self.deny("rmdir", path))
+    }
+
+    async fn unlink_file(&self, path: &str) -> Result<(), Error> {
+        Err(self.deny("unlink", path))
+    }
+
+    async fn read_link(&self, path: &str) -> Result<PathBuf, Error> {
+        self.inner.read_link(path).await
+    }
+
+    async fn get_filestat(&self) -> Result<Filestat, Error> {
+        self.inner.get_filestat().await
+    }
+
+    async fn get_path_filestat(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+    ) -> Result<Filestat, Error> {
+        self.inner.get_path_filestat(path, follow_symlinks).await
+    }
+
+    async fn rename(
+        &self,
+        path: &str,
+        _dest_dir: &dyn WasiDir,
+        _dest_path: &str,
+    ) -> Result<(), Error> {
+        Err(self.deny("rename", path))
+    }
+
+    async fn hard_link(
+        &self,
+        path: &str,
+        _target_dir: &dyn WasiDir,
+        _target_path: &str,
+    ) -> Result<(), Error> {
+        Err(self.deny("link", path))
+    }
+
+    async fn set_times(
+        &self,
+        path: &str,
+        _atime: Option<SystemTimeSpec>,
+        _mtime: Option<SystemTimeSpec>,
+        _follow_symlinks: bool,
+    ) -> Result<(), Error> {
+        Err(self.deny("utimes", path))
+    }
+}
+
+// ── Virtual time ──────────────────────────────────────────────────────────
+
+/// Monotonic clock that runs at real speed plus any time skipped by
+/// [`VirtualSched`]. Sleeping to outlast the sandbox therefore costs nothing.
+struct VirtualClock {
+    real: MonotonicClock,
+    skew: Arc<AtomicU64>,
+}
+
+impl WasiMonotonicClock for VirtualClock {
+    fn resolution(&self) -> Duration {
+        self.real.resolution()
+    }
+
+    fn now(&self, precision: Duration) -> cap_std::time::Instant {
+        self.real.now(precision) + Duration::from_nanos(self.skew.load(Ordering::SeqCst))
+    }
+}
+
+/// Scheduler that never blocks: clock waits advance [`VirtualClock`] and
+/// descriptor waits complete immediately with a hang-up.
+struct VirtualSched {
+    skew: Arc<AtomicU64>,
+}
+
+impl VirtualSched {
+    fn advance(&self, by: Duration) {
+        let nanos = u64::try_from(by.as_nanos()).unwrap_or(u64::MAX);
+        self.skew
+            .fetch_add(nanos.saturating_add(1), Ordering::SeqCst);
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiSched for VirtualSched {
+    async fn poll_oneoff<'a>(&self, poll: &mut Poll<'a>) -> Result<(), Error> {
+        let mut any_rw = false;
+        for sub in poll.rw_subscriptions() {
+            if let Subscription::Read(rw) | Subscription::Write(rw) = sub {
+                rw.complete(0, RwEventFlags::HANGUP);
+                any_rw = true;
+            }
+        }
+        if !any_rw {
+            if let Some(wait) = poll
+                .earliest_clock_deadline()
+                .and_then(wasi_common::sched::MonotonicClockSubscription::duration_until)
+            {
+                self.advance(wait);
+            }
+        }
+        Ok(())
+    }
+
+    async fn sched_yield(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn sleep(&self, duration: Duration) -> Result<(), Error> {
+        self.advance(duration);
+        Ok(())
+    }
+}
+
+fn wasm_error(e: impl std::fmt::Display) -> StaticAnalysisError {
+    StaticAnalysisError::ToolError {
+        tool: "wasm_sandbox".into(),
+        reason: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox_with(wat: &str, limits: SandboxLimits) -> (WasmSandbox, Module) {
+        let engine = Engine::new(Config::new().consume_fuel(true)).unwrap();
+        let module = Module::new(&engine, wat).unwrap();
+        let sandbox = WasmSandbox {
+            engine,
+            python: module.clone(),
+            quickjs: module.clone(),
+            python_lib: None,
+            limits,
+        };
+        (sandbox, module)
+    }
+
+    fn run_wat(wat: &str, limits: SandboxLimits) -> BTreeSet<CapabilityEvent> {
+        let (sandbox, module) = sandbox_with(wat, limits);
+        let scratch = tempfile::tempdir().unwrap();
+        sandbox
+            .run(&SandboxRun {
+                module: &module,
+                argv: vec!["test".into()],
+                scratch: scratch.path(),
+                stdlib: None,
+                armed: true,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn interpreter_selected_by_extension() {
+        assert_eq!(
+            Interpreter::for_path(Path::new("scripts/a.py")),
+            Some(Interpreter::Python)
+        );
+        assert_eq!(
+            Interpreter::for_path(Path::new("scripts/a.js")),
+            Some(Interpreter::QuickJs)
+        );
+        assert_eq!(Interpreter::for_path(Path::new("scripts/a.ts")), None);
+    }
+
+    #[test]
+    fn open_outside_scratch_is_denied_and_recorded() {
+        // path_open(fd=3 "/", dirflags, "etc/passwd", oflags=0, rights, rights, fdflags, &fd)
+        let events = run_wat(
+            r#"(module
+                (import "wasi_snapshot_preview1" "path_open"
+                  (func $open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 16) "etc/passwd")
+                (func (export "_start")
+                  (drop (call $open (i32.const 3) (i32.const 0) (i32.const 16) (i32.const 10)
+                    (i32.const 0) (i64.const 2) (i64.const 0) (i32.const 0) (i32.const 64)))))"#,
+            SandboxLimits::default(),
+        );
+        assert!(
+            events
+                .iter()
+                .any(|e| e.rule_id == "sandbox_fs_read_denied" && e.detail.contains("/etc/passwd")),
+            "got: {events:?}"
+        );
+    }
+
+    #[test]
+    fn socket_use_is_denied_and_recorded() {
+        let events = run_wat(
+            r#"(module
+                (import "wasi_snapshot_preview1" "sock_send"
+                  (func $send (param i32 i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "_start")
+                  (drop (call $send (i32.const 3) (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 0)))))"#,
+            SandboxLimits::default(),
+        );
+        assert!(
+            events.iter().any(|e| e.rule_id == "sandbox_network_denied"),
+            "got: {events:?}"
+        );
+    }
+
+    #[test]
+    fn infinite_loop_exhausts_fuel() {
+        let events = run_wat(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "_start") (loop (br 0))))"#,
+            SandboxLimits {
+                fuel: 100_000,
+                ..SandboxLimits::default()
+            },
+        );
+        assert!(
+            events.iter().any(|e| e.rule_id == "sandbox_fuel_exhausted"),
+            "got: {events:?}"
+        );
+    }
+
+    #[test]
+    fn memory_growth_beyond_limit_is_recorded() {
+        let events = run_wat(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "_start") (drop (memory.grow (i32.const 100)))))"#,
+            SandboxLimits {
+                memory_bytes: 2 * 65536,
+                ..SandboxLimits::default()
+            },
+        );
+        assert!(
+            events.iter().any(|e| e.rule_id == "sandbox_memory_limit"),
+            "got: {events:?}"
+        );
+    }
+
+    #[test]
+    fn proc_exit_is_not_a_finding() {
+        let events = run_wat(
+            r#"(module
+                (import "wasi_snapshot_preview1" "proc_exit" (func $exit (param i32)))
+                (memory (export "memory") 1)
+                (func (export "_start") (call $exit (i32.const 1))))"#,
+            SandboxLimits::default(),
+        );
+        assert!(events.is_empty(), "got: {events:?}");
+    }
+
+    #[test]
+    fn audit_lines_become_events() {
+        let events =
+            parse_audit_events(b"[\"socket.connect\", \"(<socket>, ('1.2.3.4', 80))\"]\ngarbage\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].rule_id, "sandbox_audit_socket_connect");
+    }
+
+    #[test]
+    fn missing_modules_are_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("README.md"), "modules go here").unwrap();
+        let sandbox = WasmSandbox::load(dir.path(), SandboxLimits::default());
+        assert!(matches!(
+            sandbox,
+            Err(StaticAnalysisError::MissingComponent(_))
+        ));
+    }
+
+    #[test]
+    fn skipped_finding_does_not_block() {
+        let finding = skipped_finding(Path::new("scripts/run.py"));
+        assert_eq!(finding.rule_id, "sandbox_skipped");
+        assert!(!finding.severity.is_blocking());
+    }
+}
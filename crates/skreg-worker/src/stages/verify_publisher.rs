@@ -1,18 +1,29 @@
 //! Stage 4: verify publisher signature and certificate chain.
 
 use anyhow::{bail, Context, Result};
-use aws_sdk_s3::Client as S3Client;
-use skreg_core::types::Sha256Digest;
+use chrono::Utc;
+use skreg_core::manifest::Manifest;
+use skreg_core::transparency::LogEntry;
 use skreg_crypto::{
     error::VerifyError,
-    verifier::{RsaPssVerifier, SignatureVerifier},
+    package::{signed_digest, verify_files, SigningScheme},
+    rotation::spki_fingerprint,
+    verifier::{RsaPssVerifier, SignatureVerifier, MAX_CHAIN_LEN},
 };
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use super::signing::{issue_timestamp, RegistryKey};
+use crate::artifact::Artifact;
+
+/// Environment variable naming a PEM file of trusted root CAs that replaces
+/// the bundled root, so roots can be rotated without a new worker image.
+const TRUST_BUNDLE_ENV: &str = "SKREG_TRUST_BUNDLE";
+
 /// Kinds of publisher verification failure.
 pub(crate) enum FailureKind {
-    /// The signature bytes do not match the tarball digest.
+    /// The signature does not match the signed payload, or the files do not
+    /// match the signed file list.
     SignatureMismatch,
     /// The signing certificate expired on the given date.
     CertExpired(String),
@@ -24,7 +35,7 @@ pub(crate) enum FailureKind {
     CertRevoked(i64),
     /// The self-signed publisher key has been revoked by the registry.
     SelfSignedKeyRevoked,
-    /// The certificate chain has an unsupported length (not 1 or 2).
+    /// The certificate chain is empty or longer than [`MAX_CHAIN_LEN`].
     InvalidChainLength,
 }
 
@@ -47,9 +58,9 @@ pub(crate) fn failure_message(kind: FailureKind) -> String {
                 .to_owned()
         }
         FailureKind::ChainInvalid => {
-            "The certificate chain could not be validated against the skreg root CA. \
+            "The certificate chain could not be validated against the skreg root CAs. \
              Ensure you are providing the complete chain from your leaf certificate \
-             up to the Publisher CA."
+             up to the Publisher CA, and that every certificate in it is current."
                 .to_owned()
         }
         FailureKind::CertRevoked(serial) => {
@@ -64,62 +75,48 @@ pub(crate) fn failure_message(kind: FailureKind) -> String {
                 .to_owned()
         }
         FailureKind::InvalidChainLength => {
-            "The certificate chain must contain exactly 1 or 2 certificates \
-             (self-signed leaf, or leaf + Publisher CA)."
-                .to_owned()
+            format!(
+                "The certificate chain must contain 1 to {MAX_CHAIN_LEN} certificates \
+                 (self-signed leaf, or leaf followed by its issuing CAs)."
+            )
         }
     }
 }
 
 /// Run Stage 4: verify the publisher signature and update the `signer` column.
 ///
-/// Loads the `.skill` tarball from S3, reads `manifest.json`, verifies the
-/// RSA-PSS signature against the certificate chain, and writes the signer kind
-/// (`"self_signed"` or `"publisher"`) back to the `versions` table.
+/// Reads `manifest.json` from the unpacked artifact, verifies the publisher
+/// signature against the certificate chain as of now, and writes the signer
+/// kind (`"self_signed"` or `"publisher"`) back to the `versions` table along
+/// with a timestamp from `registry_key` proving the signature existed now.
+///
+/// Returns the transparency log entry to append once the version passes.
 ///
 /// # Errors
 ///
-/// Returns an error if the S3 download, tarball unpacking, manifest parsing,
-/// revocation lookup, or signature verification fails.
+/// Returns an error if manifest parsing, revocation lookup, or signature
+/// verification fails.
 pub async fn run_verify_publisher(
     version_id: Uuid,
-    _sha256: &str,
-    storage_path: &str,
+    artifact: &Artifact,
     namespace: &str,
+    registry_key: &RegistryKey,
     pool: &PgPool,
-    s3: &S3Client,
-    bucket: &str,
-) -> Result<()> {
-    // 1. Download tarball from S3
-    let obj = s3
-        .get_object()
-        .bucket(bucket)
-        .key(storage_path)
-        .send()
-        .await
-        .context("downloading tarball from S3")?;
-    let bytes = obj
-        .body
-        .collect()
-        .await
-        .context("reading S3 object body")?
-        .into_bytes();
-
-    // 2. Unpack and read manifest.json
-    let tmp = skreg_pack::unpack::unpack_to_tempdir(&bytes).context("unpacking tarball")?;
-    let manifest_raw = std::fs::read_to_string(tmp.path().join("manifest.json"))
+) -> Result<LogEntry> {
+    // 1. Read manifest.json
+    let manifest_raw = std::fs::read_to_string(artifact.path().join("manifest.json"))
         .context("reading manifest.json")?;
     let manifest: serde_json::Value =
         serde_json::from_str(&manifest_raw).context("parsing manifest.json")?;
 
-    // 3. publisher_sig_hex must be present
+    // 2. publisher_sig_hex must be present
     let sig_hex = manifest["publisher_sig_hex"]
         .as_str()
         .ok_or_else(|| anyhow::anyhow!("{}", failure_message(FailureKind::SignatureMismatch)))?;
 
     let sig_bytes = hex::decode(sig_hex).context("decoding publisher_sig_hex")?;
 
-    // 4. cert_chain must be 1 or 2 entries
+    // 3. cert_chain must be 1 to MAX_CHAIN_LEN entries
     let cert_chain: Vec<String> = manifest["cert_chain_pem"]
         .as_array()
         .ok_or_else(|| anyhow::anyhow!("{}", failure_message(FailureKind::InvalidChainLength)))?
@@ -127,20 +124,20 @@ pub async fn run_verify_publisher(
         .filter_map(|v| v.as_str().map(str::to_owned))
         .collect();
 
-    if cert_chain.is_empty() || cert_chain.len() > 2 {
+    if cert_chain.is_empty() || cert_chain.len() > MAX_CHAIN_LEN {
         bail!("{}", failure_message(FailureKind::InvalidChainLength));
     }
 
     let chain_len = cert_chain.len();
 
-    // 5. Load revoked serials from publisher_certs table
+    // 4. Load revoked serials from publisher_certs table
     let revoked_serials: Vec<i64> =
         sqlx::query_scalar("SELECT serial FROM publisher_certs WHERE revoked_at IS NOT NULL")
             .fetch_all(pool)
             .await
             .context("loading revoked publisher cert serials")?;
 
-    // 6. For self-signed (chain len == 1): check revoked_self_signed_keys
+    // 5. For self-signed (chain len == 1): check revoked_self_signed_keys
     if chain_len == 1 {
         // Derive SPKI fingerprint from the PEM cert: SHA-256 of the DER-encoded SubjectPublicKeyInfo
         use sha2::Digest as _;
@@ -169,25 +166,32 @@ pub async fn run_verify_publisher(
         }
     }
 
-    // 7. Verify signature
-    // Use the sha256 embedded in the manifest (= hash of the unsigned tarball_1),
-    // which is what publisher_sig_hex was actually signed over.  The `sha256`
-    // parameter comes from the DB and equals hash(tarball_2) — wrong for this check.
-    let content_sha256 = manifest["sha256"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("manifest.json missing sha256 field"))?;
-    let digest = Sha256Digest::from_hex(content_sha256)
-        .map_err(|e| anyhow::anyhow!("invalid sha256 in manifest: {e}"))?;
+    // 6. Verify signature over the file-list digest. New publishes must
+    // carry a file list; only versions vetted before it are legacy.
+    let parsed: Manifest = serde_json::from_str(&manifest_raw).context("parsing manifest.json")?;
+    let digest = signed_digest(&parsed, SigningScheme::FileList).map_err(|e| {
+        anyhow::anyhow!(
+            "{}",
+            failure_message(map_verify_error(&e, &revoked_serials))
+        )
+    })?;
 
-    let verifier = RsaPssVerifier::new();
+    let verifier = match std::env::var_os(TRUST_BUNDLE_ENV) {
+        Some(path) => RsaPssVerifier::new_with_root_pem(
+            &std::fs::read(&path).with_context(|| format!("reading {TRUST_BUNDLE_ENV}"))?,
+        ),
+        None => RsaPssVerifier::new(),
+    };
+    let now = Utc::now();
     let signer = verifier
-        .verify_with_namespace(&digest, &sig_bytes, &cert_chain, namespace)
+        .verify_at(&digest, &sig_bytes, &cert_chain, now.into())
+        .and_then(|signer| signer.require_namespace(namespace))
         .map_err(|e| {
             let kind = map_verify_error(&e, &revoked_serials);
             anyhow::anyhow!("{}", failure_message(kind))
         })?;
 
-    // 8. Check revoked CA-issued serials (after successful chain verification)
+    // 7. Check revoked CA-issued serials (after successful chain verification)
     if let Some(serial) = signer.cert_serial {
         #[allow(clippy::cast_possible_wrap)]
         let serial_i64 = serial as i64;
@@ -196,31 +200,56 @@ pub async fn run_verify_publisher(
         }
     }
 
-    // 9. Update versions with signer kind
+    // 8. The unpacked files must be exactly the signed list
+    verify_files(artifact.path(), &parsed.files).map_err(|e| {
+        anyhow::anyhow!("{} ({e})", failure_message(FailureKind::SignatureMismatch))
+    })?;
+
+    // 9. Update versions with signer kind and the signature timestamp
     let signer_kind = if signer.ca_verified {
         "publisher"
     } else {
         "self_signed"
     };
+    let timestamp = issue_timestamp(registry_key, &sig_bytes, now)?;
 
-    sqlx::query("UPDATE versions SET signer = $1 WHERE id = $2")
+    sqlx::query("UPDATE versions SET signer = $1, signed_timestamp = $2 WHERE id = $3")
         .bind(signer_kind)
+        .bind(timestamp.map(sqlx::types::Json))
         .bind(version_id)
         .execute(pool)
         .await
         .context("updating signer on versions")?;
 
-    Ok(())
+    let signer_fingerprint = spki_fingerprint(&cert_chain[0])
+        .map_err(|e| anyhow::anyhow!("parsing signing cert: {e}"))?;
+    Ok(LogEntry::publish(&parsed, signer_fingerprint))
 }
 
 fn map_verify_error(e: &VerifyError, revoked_serials: &[i64]) -> FailureKind {
     match e {
-        VerifyError::SignatureMismatch => FailureKind::SignatureMismatch,
+        VerifyError::SignatureMismatch
+        | VerifyError::MissingSignature
+        | VerifyError::MissingFileList
+        | VerifyError::PayloadMismatch
+        | VerifyError::FileMismatch { .. } => FailureKind::SignatureMismatch,
         VerifyError::CertExpired(date) => FailureKind::CertExpired(date.clone()),
         VerifyError::CnMismatch { .. } => FailureKind::CnMismatch,
+        VerifyError::ChainLength { .. } => FailureKind::InvalidChainLength,
         VerifyError::InvalidCertChain(_)
         | VerifyError::Der(_)
-        | VerifyError::CertNotYetValid(_) => FailureKind::ChainInvalid,
+        | VerifyError::CertNotYetValid(_)
+        | VerifyError::NoTrustedRoots
+        | VerifyError::UntrustedRoot { .. }
+        | VerifyError::IssuerMismatch { .. }
+        | VerifyError::NotCa { .. }
+        | VerifyError::PathLenExceeded { .. }
+        | VerifyError::KeyUsageNotPermitted { .. }
+        | VerifyError::UnsupportedAlgorithm(_)
+        | VerifyError::InvalidTimestamp(_)
+        | VerifyError::InvalidRotation(_)
+        | VerifyError::InvalidLogProof(_)
+        | VerifyError::InvalidKeyLog(_) => FailureKind::ChainInvalid,
         VerifyError::Revoked { serial } => {
             #[allow(clippy::cast_possible_wrap)]
             let s = *serial as i64;
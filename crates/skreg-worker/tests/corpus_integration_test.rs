@@ -21,7 +21,7 @@ use skreg_worker::stages::{
 
 #[allow(dead_code)]
 fn rules_dir() -> PathBuf {
-    Path::new(env!("CARGO_MANIFEST_DIR")).join("rules")
+    PathBuf::from(skreg_vet::RULES_DIR)
 }
 
 #[allow(dead_code)]
@@ -10,10 +10,10 @@
 
 use std::path::Path;
 
-use skreg_worker::stages::static_analysis::{pass1, pass2, startup, Severity};
+use skreg_worker::stages::static_analysis::{pass1, pass2, startup, wasm_sandbox, Severity};
 
 fn rules_dir() -> std::path::PathBuf {
-    std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("rules")
+    std::path::PathBuf::from(skreg_vet::RULES_DIR)
 }
 
 /// Verify all required tools are present. Fails hard if any are missing.
@@ -31,6 +31,14 @@ fn tracee_socket_is_present() {
     );
 }
 
+#[test]
+fn wasm_modules_are_present() {
+    let dir = std::env::var(wasm_sandbox::WASM_DIR_ENV)
+        .unwrap_or_else(|_| wasm_sandbox::DEFAULT_WASM_DIR.into());
+    startup::check_wasm_modules(Path::new(&dir))
+        .expect("python.wasm and quickjs.wasm must be present in integration environment");
+}
+
 #[test]
 fn yara_rules_compile_in_integration_env() {
     startup::check_yara_rules(&rules_dir()).expect("YARA rules must compile");
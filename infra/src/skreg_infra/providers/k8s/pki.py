@@ -18,6 +18,7 @@ class K8sPki(pulumi.ComponentResource):
           --from-literal=PUBLISHER_CA_KEY_PEM="$(cat publisher-ca.key)" \\
           --from-literal=PUBLISHER_CA_CERT_PEM="$(cat publisher-ca.pem)" \\
           --from-literal=REGISTRY_CA_KEY_PEM="$(cat registry-ca.key)" \\
+          --from-literal=REGISTRY_CA_CERT_PEM="$(cat registry-ca.pem)" \\
           --from-literal=ROOT_CA_CERT_PEM="$(cat root-ca.pem)"
     """
 